@@ -0,0 +1,260 @@
+//! Operation throughput benchmarks.
+//!
+//! Run with `cargo bench`. Each benchmark prints the number of operations per
+//! second it sustained. Passing a name filters the benchmarks that run, e.g.
+//! `cargo bench -- file_`.
+//!
+//! With `--features bench-internals`, the average submit and submit-to-complete
+//! latencies recorded by the driver are also reported.
+//!
+//! To catch regressions, save a run with `BENCH_SAVE=<path>` and compare a
+//! later run against it with `BENCH_BASELINE=<path>`. The run fails if any
+//! benchmark is more than `BENCH_THRESHOLD` percent (default 10) slower than
+//! the baseline.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+use tokio_uring::fs::File;
+use tokio_uring::net::{TcpListener, TcpStream, UdpSocket};
+
+const WARMUP: usize = 1_000;
+const ITERS: usize = 50_000;
+const BUF_SIZE: usize = 4096;
+
+type Bench = (&'static str, fn() -> Report);
+
+struct Report {
+    name: &'static str,
+    ops_per_sec: f64,
+}
+
+fn main() {
+    let filter = env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let mut reports = vec![];
+
+    let benches: &[Bench] = &[
+        ("file_read_4k", file_read_4k),
+        ("file_write_4k", file_write_4k),
+        ("tcp_accept", tcp_accept),
+        ("tcp_read_4k", tcp_read_4k),
+        ("udp_recv_from_4k", udp_recv_from_4k),
+    ];
+
+    for (name, bench) in benches {
+        if let Some(filter) = &filter {
+            if !name.contains(filter.as_str()) {
+                continue;
+            }
+        }
+
+        reports.push(bench());
+    }
+
+    if let Ok(path) = env::var("BENCH_SAVE") {
+        let mut out = fs::File::create(path).unwrap();
+        for report in &reports {
+            writeln!(out, "{} {}", report.name, report.ops_per_sec).unwrap();
+        }
+    }
+
+    if let Ok(path) = env::var("BENCH_BASELINE") {
+        let threshold: f64 = env::var("BENCH_THRESHOLD")
+            .map(|t| t.parse().expect("invalid BENCH_THRESHOLD"))
+            .unwrap_or(10.0);
+
+        if !compare(&reports, &path, threshold) {
+            process::exit(1);
+        }
+    }
+}
+
+/// Compares the reports against a saved baseline, returning `false` if any
+/// benchmark regressed by more than `threshold` percent.
+fn compare(reports: &[Report], path: &str, threshold: f64) -> bool {
+    let baseline: HashMap<String, f64> = fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter_map(|line| {
+            let (name, ops) = line.split_once(' ')?;
+            Some((name.to_string(), ops.parse().ok()?))
+        })
+        .collect();
+
+    let mut ok = true;
+
+    for report in reports {
+        let base = match baseline.get(report.name) {
+            Some(base) => *base,
+            None => continue,
+        };
+
+        let change = (report.ops_per_sec - base) / base * 100.0;
+        println!("{:<20} {:+.1}% vs baseline", report.name, change);
+
+        if change < -threshold {
+            println!("{:<20} REGRESSED", report.name);
+            ok = false;
+        }
+    }
+
+    ok
+}
+
+/// Runs `op` `ITERS` times on a fresh runtime after `setup`, reporting the
+/// sustained rate.
+fn bench<S, SF, T, F, Fut>(name: &'static str, setup: S, op: F) -> Report
+where
+    S: FnOnce() -> SF,
+    SF: Future<Output = T>,
+    F: Fn(Rc<T>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    tokio_uring::start(async {
+        let state = Rc::new(setup().await);
+
+        for _ in 0..WARMUP {
+            op(state.clone()).await;
+        }
+
+        #[cfg(feature = "bench-internals")]
+        tokio_uring::bench_internals::reset_driver_stats();
+
+        let start = Instant::now();
+        for _ in 0..ITERS {
+            op(state.clone()).await;
+        }
+        let elapsed = start.elapsed();
+
+        let report = Report {
+            name,
+            ops_per_sec: ITERS as f64 / elapsed.as_secs_f64(),
+        };
+
+        print!(
+            "{:<20} {:>12.0} ops/sec {:>10?}/op",
+            name,
+            report.ops_per_sec,
+            elapsed / ITERS as u32
+        );
+
+        #[cfg(feature = "bench-internals")]
+        {
+            let stats = tokio_uring::bench_internals::driver_stats();
+            print!(
+                "  submit {:?}  complete {:?}",
+                stats.avg_submit(),
+                stats.avg_complete()
+            );
+        }
+
+        println!();
+        report
+    })
+}
+
+fn file_read_4k() -> Report {
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(&[1; BUF_SIZE]).unwrap();
+    let path = tempfile.path().to_owned();
+
+    bench(
+        "file_read_4k",
+        || async move { File::open(path).await.unwrap() },
+        |file| async move {
+            let (res, _) = file.read_at(vec![0; BUF_SIZE], 0).await;
+            res.unwrap();
+        },
+    )
+}
+
+fn file_write_4k() -> Report {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+    let path = tempfile.path().to_owned();
+
+    bench(
+        "file_write_4k",
+        || async move { File::create(path).await.unwrap() },
+        |file| async move {
+            let (res, _) = file.write_at(vec![1; BUF_SIZE], 0).await;
+            res.unwrap();
+        },
+    )
+}
+
+fn tcp_accept() -> Report {
+    let addr = free_addr();
+
+    bench(
+        "tcp_accept",
+        || async move { TcpListener::bind(addr).unwrap() },
+        move |listener| async move {
+            let (tx, rx) = tokio::join!(TcpStream::connect(addr), listener.accept());
+            tx.unwrap();
+            rx.unwrap();
+        },
+    )
+}
+
+fn tcp_read_4k() -> Report {
+    let addr = free_addr();
+
+    bench(
+        "tcp_read_4k",
+        || async move {
+            let listener = TcpListener::bind(addr).unwrap();
+            let (tx, rx) = tokio::join!(TcpStream::connect(addr), listener.accept());
+            (tx.unwrap(), rx.unwrap().0)
+        },
+        |pair| async move {
+            let (tx, rx) = &*pair;
+            let (res, _) = tx.write(vec![1; BUF_SIZE]).await;
+            let mut remaining = res.unwrap();
+            let mut buf = vec![0; BUF_SIZE];
+
+            while remaining > 0 {
+                let (res, b) = rx.read(buf).await;
+                remaining -= res.unwrap();
+                buf = b;
+            }
+        },
+    )
+}
+
+fn udp_recv_from_4k() -> Report {
+    let tx_addr = free_addr();
+    let rx_addr = free_addr();
+
+    bench(
+        "udp_recv_from_4k",
+        || async move {
+            let tx = UdpSocket::bind(tx_addr).await.unwrap();
+            let rx = UdpSocket::bind(rx_addr).await.unwrap();
+            (tx, rx)
+        },
+        move |pair| async move {
+            let (tx, rx) = &*pair;
+            let (res, _) = tx.send_to(vec![1; BUF_SIZE], rx_addr).await;
+            res.unwrap();
+            let (res, _) = rx.recv_from(vec![0; BUF_SIZE]).await;
+            res.unwrap();
+        },
+    )
+}
+
+/// Finds a local port that is currently unused.
+fn free_addr() -> SocketAddr {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // Give the kernel a moment to release the port
+    drop(listener);
+    std::thread::sleep(Duration::from_millis(1));
+
+    addr
+}
@@ -0,0 +1,67 @@
+//! Driver internals exposed for the benchmark suite.
+//!
+//! This module is only available with the `bench-internals` feature and is not
+//! part of the public API. It may change or disappear at any time.
+
+use crate::driver::bench;
+
+use std::time::Duration;
+
+/// Snapshot of the current driver's submit and completion timings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DriverStats {
+    /// Number of `io_uring_enter` calls made to submit operations.
+    pub submits: u64,
+
+    /// Total time spent inside `io_uring_enter` when submitting.
+    pub submit_time: Duration,
+
+    /// Number of operation completions processed.
+    pub completions: u64,
+
+    /// Total time between pushing an SQE and processing its CQE.
+    pub complete_time: Duration,
+}
+
+impl DriverStats {
+    /// Average time spent per submit call.
+    pub fn avg_submit(&self) -> Duration {
+        average(self.submit_time, self.submits)
+    }
+
+    /// Average time from pushing an SQE to processing its CQE.
+    pub fn avg_complete(&self) -> Duration {
+        average(self.complete_time, self.completions)
+    }
+}
+
+/// Returns the stats recorded by the current driver.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn driver_stats() -> DriverStats {
+    bench::with_stats(|stats| DriverStats {
+        submits: stats.submits,
+        submit_time: stats.submit_time,
+        completions: stats.completions,
+        complete_time: stats.complete_time,
+    })
+}
+
+/// Clears the stats recorded by the current driver.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn reset_driver_stats() {
+    bench::with_stats(|stats| stats.reset())
+}
+
+fn average(total: Duration, n: u64) -> Duration {
+    if n == 0 {
+        Duration::ZERO
+    } else {
+        total / n as u32
+    }
+}
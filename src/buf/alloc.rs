@@ -0,0 +1,215 @@
+use crate::buf::{IoBuf, IoBufMut};
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ops;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+/// A source of memory for the buffers the runtime allocates, such as the
+/// chunks of [`ReadChunks`] and the buffers of
+/// [`FixedBufRegistry::aligned_in`].
+///
+/// By default, buffers come from the global allocator of the program,
+/// [`Global`]. Embedders with constrained memory supply their own allocator
+/// instead, carving buffers out of a slab, of an arena, or of huge pages
+/// reserved up front.
+///
+/// # Safety
+///
+/// The memory returned by [`allocate`](Allocator::allocate) must be valid
+/// for reads and writes of `layout.size()` bytes, aligned to
+/// `layout.align()`, and stay valid until passed to
+/// [`deallocate`](Allocator::deallocate), whichever thread it is used on:
+/// the kernel accesses it while operations are in flight.
+///
+/// [`ReadChunks`]: crate::fs::ReadChunks
+/// [`FixedBufRegistry::aligned_in`]: crate::buf::FixedBufRegistry::aligned_in
+///
+/// # Examples
+///
+/// Allocating from huge pages, to spare TLB entries on large scans:
+///
+/// ```no_run
+/// use std::alloc::Layout;
+/// use std::ptr::NonNull;
+/// use std::rc::Rc;
+/// use tokio_uring::buf::Allocator;
+/// use tokio_uring::fs::File;
+///
+/// struct HugePages;
+///
+/// unsafe impl Allocator for HugePages {
+///     fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+///         let ptr = unsafe {
+///             libc::mmap(
+///                 std::ptr::null_mut(),
+///                 layout.size(),
+///                 libc::PROT_READ | libc::PROT_WRITE,
+///                 libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_HUGETLB,
+///                 -1,
+///                 0,
+///             )
+///         };
+///         if ptr == libc::MAP_FAILED {
+///             return None;
+///         }
+///         NonNull::new(ptr.cast())
+///     }
+///
+///     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+///         libc::munmap(ptr.as_ptr().cast(), layout.size());
+///     }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let file = File::open("dataset.bin").await?;
+///
+///         let mut chunks = file.read_chunks_in(2 * 1024 * 1024, Rc::new(HugePages)).await?;
+///         while let Some(chunk) = chunks.next().await? {
+///             // Process the chunk
+///             chunks.recycle(chunk);
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub unsafe trait Allocator {
+    /// Allocates memory fitting `layout`, of a non-zero size, or returns
+    /// `None` if out of memory.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Releases memory returned by [`allocate`](Allocator::allocate).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `allocate` on this allocator, with
+    /// the same `layout`, and not be released already.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// The global allocator of the program, the default [`Allocator`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc::alloc(layout) })
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout);
+    }
+}
+
+/// A buffer allocated by an [`Allocator`], and released to it when dropped.
+///
+/// Like a `Vec<u8>` of fixed capacity, the buffer derefs to its initialized
+/// bytes, which reads extend.
+pub struct AllocBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+    alloc: Rc<dyn Allocator>,
+}
+
+impl AllocBuf {
+    /// Allocates an empty buffer of `capacity` bytes from `alloc`.
+    ///
+    /// # Panics
+    ///
+    /// Aborts if the allocator is out of memory, as `Vec` does.
+    pub fn with_capacity_in(capacity: usize, alloc: Rc<dyn Allocator>) -> AllocBuf {
+        let layout = Layout::array::<u8>(capacity).expect("capacity overflow");
+
+        let ptr = if capacity == 0 {
+            NonNull::dangling()
+        } else {
+            match alloc.allocate(layout) {
+                Some(ptr) => ptr,
+                None => alloc::handle_alloc_error(layout),
+            }
+        };
+
+        AllocBuf {
+            ptr,
+            len: 0,
+            layout,
+            alloc,
+        }
+    }
+
+    /// Returns the number of bytes the buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Empties the buffer, keeping its memory.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the allocator of the buffer.
+    pub fn allocator(&self) -> &Rc<dyn Allocator> {
+        &self.alloc
+    }
+}
+
+unsafe impl IoBuf for AllocBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.capacity()
+    }
+}
+
+unsafe impl IoBufMut for AllocBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    unsafe fn set_init(&mut self, pos: usize) {
+        if self.len < pos {
+            self.len = pos;
+        }
+    }
+}
+
+impl ops::Deref for AllocBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl ops::DerefMut for AllocBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for AllocBuf {
+    fn drop(&mut self) {
+        if self.layout.size() > 0 {
+            unsafe { self.alloc.deallocate(self.ptr, self.layout) };
+        }
+    }
+}
+
+impl fmt::Debug for AllocBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllocBuf")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
@@ -0,0 +1,555 @@
+use crate::buf::{Allocator, Global, IoBuf, IoBufMut};
+use crate::driver::{self, BufRegistration};
+
+use std::alloc::{self, Layout};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::ops;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+/// `CAP_IPC_LOCK`, from `linux/capability.h`
+const CAP_IPC_LOCK: u32 = 14;
+
+/// A set of buffers registered with the ring, for the operations taking a
+/// [`FixedBuf`], such as [`File::read_fixed_at`].
+///
+/// Registered buffers are mapped by the kernel once, when registering them,
+/// instead of on every operation. The pages of the buffers are locked in
+/// memory while registered, and are accounted against the `RLIMIT_MEMLOCK`
+/// limit of the user, unless the process has the `CAP_IPC_LOCK` capability.
+/// Registering buffers exceeding the limit fails with a [`MemlockError`].
+///
+/// A ring has at most one set of buffers registered at once. The buffers are
+/// unregistered by [`unregister`](FixedBufRegistry::unregister), or when the
+/// registry is dropped.
+///
+/// [`File::read_fixed_at`]: crate::fs::File::read_fixed_at
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::FixedBufRegistry;
+/// use tokio_uring::fs::File;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let registry = FixedBufRegistry::new((0..4).map(|_| vec![0; 4096]));
+///         registry.register()?;
+///
+///         let file = File::open("hello.txt").await?;
+///         let buf = registry.check_out(0).unwrap();
+///         let (res, buf) = file.read_fixed_at(buf, 0).await;
+///         println!("{:?}", &buf[..res?]);
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct FixedBufRegistry {
+    state: Rc<RefCell<State>>,
+}
+
+/// A buffer of a [`FixedBufRegistry`], returned to the registry when dropped.
+///
+/// The buffer derefs to its initialized bytes.
+pub struct FixedBuf {
+    registry: Rc<RefCell<State>>,
+    index: u16,
+    ptr: *mut u8,
+    len: usize,
+    cap: usize,
+}
+
+/// Error registering buffers whose locked memory exceeds `RLIMIT_MEMLOCK`.
+///
+/// Returned by [`FixedBufRegistry::register`] as the inner error of an
+/// [`io::Error`] of kind [`OutOfMemory`](io::ErrorKind::OutOfMemory).
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::{FixedBufRegistry, MemlockError};
+///
+/// tokio_uring::start(async {
+///     let registry = FixedBufRegistry::new((0..1024).map(|_| vec![0; 1 << 20]));
+///     if let Err(e) = registry.register() {
+///         match e.get_ref().and_then(|e| e.downcast_ref::<MemlockError>()) {
+///             Some(e) => eprintln!("raise `ulimit -l` to {} KiB", e.required() / 1024),
+///             None => eprintln!("{}", e),
+///         }
+///     }
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemlockError {
+    required: u64,
+    limit: u64,
+}
+
+struct State {
+    bufs: Vec<Slot>,
+
+    /// Set while registered with a ring
+    registration: Option<BufRegistration>,
+
+    /// Allocator of the aligned buffers
+    alloc: Rc<dyn Allocator>,
+}
+
+/// The memory of a `Vec<u8>`, reassembled when the registry is dropped, or
+/// of an aligned allocation of the allocator of the registry.
+struct Slot {
+    ptr: *mut u8,
+    len: usize,
+    cap: usize,
+    checked_out: bool,
+
+    /// Alignment of an aligned allocation, `0` for a `Vec<u8>`
+    align: usize,
+}
+
+impl FixedBufRegistry {
+    /// Creates a registry of the given buffers, not registered yet. The whole
+    /// capacity of each buffer is registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than 16384 buffers, the limit of the kernel.
+    pub fn new<I: IntoIterator<Item = Vec<u8>>>(bufs: I) -> FixedBufRegistry {
+        let bufs: Vec<Slot> = bufs
+            .into_iter()
+            .map(|buf| {
+                let mut buf = std::mem::ManuallyDrop::new(buf);
+                Slot {
+                    ptr: buf.as_mut_ptr(),
+                    len: buf.len(),
+                    cap: buf.capacity(),
+                    checked_out: false,
+                    align: 0,
+                }
+            })
+            .collect();
+        assert!(bufs.len() <= 1 << 14, "too many buffers");
+
+        FixedBufRegistry {
+            state: Rc::new(RefCell::new(State {
+                bufs,
+                registration: None,
+                alloc: Rc::new(Global),
+            })),
+        }
+    }
+
+    /// Creates a registry of `count` zeroed buffers of `size` bytes, each
+    /// aligned to `align` bytes, not registered yet.
+    ///
+    /// Files opened with `O_DIRECT`, as by [`copy_direct`], transfer data
+    /// directly between the device and the buffers, which must be aligned to
+    /// the logical block size of the device. Aligning to the page size suits
+    /// all devices.
+    ///
+    /// [`copy_direct`]: crate::fs::copy_direct
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two, if `size` is zero, or if
+    /// there are more than 16384 buffers.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::buf::FixedBufRegistry;
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let registry = FixedBufRegistry::aligned(4, 64 * 1024, 4096);
+    ///         registry.register()?;
+    ///
+    ///         let file = File::open("disk.img").await?;
+    ///         let buf = registry.check_out(0).unwrap();
+    ///         let (res, _buf) = file.read_fixed_at(buf, 0).await;
+    ///         println!("read {} bytes", res?);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn aligned(count: usize, size: usize, align: usize) -> FixedBufRegistry {
+        FixedBufRegistry::aligned_in(count, size, align, Rc::new(Global))
+    }
+
+    /// Like [`aligned`](FixedBufRegistry::aligned), allocating the buffers
+    /// from `alloc`, for example from huge pages, which the kernel maps
+    /// with fewer entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two, if `size` is zero, or if
+    /// there are more than 16384 buffers. Aborts if the allocator is out of
+    /// memory.
+    pub fn aligned_in(
+        count: usize,
+        size: usize,
+        align: usize,
+        alloc: Rc<dyn Allocator>,
+    ) -> FixedBufRegistry {
+        assert!(size > 0, "empty buffers");
+        assert!(count <= 1 << 14, "too many buffers");
+        let layout = Layout::from_size_align(size, align).expect("invalid alignment");
+
+        let bufs = (0..count)
+            .map(|_| {
+                let ptr = match alloc.allocate(layout) {
+                    Some(ptr) => ptr.as_ptr(),
+                    None => alloc::handle_alloc_error(layout),
+                };
+
+                // Zeroed, so initialized
+                unsafe { ptr.write_bytes(0, size) };
+                Slot {
+                    ptr,
+                    len: size,
+                    cap: size,
+                    checked_out: false,
+                    align,
+                }
+            })
+            .collect();
+
+        FixedBufRegistry {
+            state: Rc::new(RefCell::new(State {
+                bufs,
+                registration: None,
+                alloc,
+            })),
+        }
+    }
+
+    /// Registers the buffers with the ring of the current runtime.
+    ///
+    /// Fails with a [`MemlockError`] if the buffers exceed `RLIMIT_MEMLOCK`,
+    /// with `EBUSY` if buffers are already registered with the ring, and if
+    /// called outside of a `tokio-uring` runtime.
+    pub fn register(&self) -> io::Result<()> {
+        let mut state = self.state.borrow_mut();
+        if state.registration.is_some() {
+            return Err(io::Error::from_raw_os_error(libc::EBUSY));
+        }
+
+        let required = state.locked_memory();
+        check_memlock(required as u64)?;
+
+        let iovecs: Vec<libc::iovec> = state
+            .bufs
+            .iter()
+            .map(|slot| libc::iovec {
+                iov_base: slot.ptr as *mut libc::c_void,
+                iov_len: slot.cap,
+            })
+            .collect();
+
+        // Safety: the memory is freed when the state is dropped, after the
+        // registration.
+        let registration = match unsafe { BufRegistration::new(&iovecs, required) } {
+            Ok(registration) => registration,
+            // The limit is shared with the other rings of the user
+            Err(e) if e.raw_os_error() == Some(libc::ENOMEM) => {
+                let limit = memlock_limit()?;
+                return Err(MemlockError::new(required as u64, limit).into());
+            }
+            Err(e) => return Err(e),
+        };
+
+        state.registration = Some(registration);
+        Ok(())
+    }
+
+    /// Unregisters the buffers from the ring.
+    ///
+    /// Fails with `EBUSY` if buffers are checked out, as operations may be
+    /// using them.
+    pub fn unregister(&self) -> io::Result<()> {
+        let mut state = self.state.borrow_mut();
+        if state.bufs.iter().any(|slot| slot.checked_out) {
+            return Err(io::Error::from_raw_os_error(libc::EBUSY));
+        }
+
+        state.registration = None;
+        Ok(())
+    }
+
+    /// Returns `true` if the buffers are registered.
+    pub fn is_registered(&self) -> bool {
+        self.state.borrow().registration.is_some()
+    }
+
+    /// Returns the number of buffers in the registry.
+    pub fn len(&self) -> usize {
+        self.state.borrow().bufs.len()
+    }
+
+    /// Returns `true` if the registry has no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the memory locked by the buffers while registered: the pages
+    /// they span, as accounted against `RLIMIT_MEMLOCK`.
+    pub fn locked_memory(&self) -> usize {
+        self.state.borrow().locked_memory()
+    }
+
+    /// Checks out the buffer at `index`, returning `None` if it is already
+    /// checked out, or if the registry has no such buffer.
+    ///
+    /// The buffer returns to the registry when dropped.
+    pub fn check_out(&self, index: usize) -> Option<FixedBuf> {
+        let mut state = self.state.borrow_mut();
+        let slot = state.bufs.get_mut(index)?;
+        if slot.checked_out {
+            return None;
+        }
+        slot.checked_out = true;
+
+        Some(FixedBuf {
+            registry: self.state.clone(),
+            index: index as u16,
+            ptr: slot.ptr,
+            len: slot.len,
+            cap: slot.cap,
+        })
+    }
+}
+
+impl fmt::Debug for FixedBufRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedBufRegistry")
+            .field("len", &self.len())
+            .field("registered", &self.is_registered())
+            .finish()
+    }
+}
+
+/// Returns the memory locked by the buffers registered with the ring of the
+/// current runtime, or `0` outside of a runtime.
+///
+/// The memory is accounted against the `RLIMIT_MEMLOCK` limit of the user,
+/// along with the memory locked by the other rings and processes of the user.
+pub fn registered_memory() -> usize {
+    driver::registered_bytes().unwrap_or(0)
+}
+
+impl State {
+    fn locked_memory(&self) -> usize {
+        let page = page_size();
+        self.bufs
+            .iter()
+            .filter(|slot| slot.cap > 0)
+            .map(|slot| {
+                let start = slot.ptr as usize & !(page - 1);
+                let end = (slot.ptr as usize + slot.cap + page - 1) & !(page - 1);
+                end - start
+            })
+            .sum()
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        // Unregister before freeing the memory
+        self.registration = None;
+
+        for slot in &self.bufs {
+            if slot.align == 0 {
+                drop(unsafe { Vec::from_raw_parts(slot.ptr, slot.len, slot.cap) });
+            } else {
+                let layout = Layout::from_size_align(slot.cap, slot.align).unwrap();
+                unsafe {
+                    self.alloc
+                        .deallocate(NonNull::new(slot.ptr).unwrap(), layout)
+                };
+            }
+        }
+    }
+}
+
+impl FixedBuf {
+    /// Returns the index of the buffer in its registry.
+    pub fn buf_index(&self) -> u16 {
+        self.index
+    }
+
+    /// Returns the number of bytes the buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Empties the buffer, keeping its memory.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Appends `data` to the initialized bytes of the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer does not have room for `data`.
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        assert!(data.len() <= self.cap - self.len, "buffer full");
+
+        // Safety: the buffer has room for `data`, which does not overlap it
+        // as the buffer is borrowed mutably
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), self.ptr.add(self.len), data.len());
+        }
+        self.len += data.len();
+    }
+
+    /// Sets the number of initialized bytes, as written by a write.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` bytes of the buffer must be initialized.
+    pub(crate) unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.cap);
+        self.len = len;
+    }
+}
+
+unsafe impl IoBuf for FixedBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.cap
+    }
+}
+
+unsafe impl IoBufMut for FixedBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    unsafe fn set_init(&mut self, pos: usize) {
+        if self.len < pos {
+            self.len = pos;
+        }
+    }
+}
+
+impl ops::Deref for FixedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl ops::DerefMut for FixedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for FixedBuf {
+    fn drop(&mut self) {
+        let mut state = self.registry.borrow_mut();
+        let slot = &mut state.bufs[self.index as usize];
+        slot.len = self.len;
+        slot.checked_out = false;
+    }
+}
+
+impl fmt::Debug for FixedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedBuf")
+            .field("index", &self.index)
+            .field("len", &self.len)
+            .field("cap", &self.cap)
+            .finish()
+    }
+}
+
+impl MemlockError {
+    fn new(required: u64, limit: u64) -> MemlockError {
+        MemlockError { required, limit }
+    }
+
+    /// Returns the memory the buffers lock, in bytes. The limit must be at
+    /// least this large, plus the memory locked by the other rings and
+    /// processes of the user.
+    pub fn required(&self) -> u64 {
+        self.required
+    }
+
+    /// Returns the soft `RLIMIT_MEMLOCK` limit of the process, in bytes.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+}
+
+impl fmt::Display for MemlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "registering buffers requires {} bytes of locked memory, exceeding RLIMIT_MEMLOCK of {} bytes",
+            self.required, self.limit
+        )
+    }
+}
+
+impl Error for MemlockError {}
+
+impl From<MemlockError> for io::Error {
+    fn from(e: MemlockError) -> io::Error {
+        io::Error::new(io::ErrorKind::OutOfMemory, e)
+    }
+}
+
+/// Fails if `required` bytes of locked memory exceed `RLIMIT_MEMLOCK`, unless
+/// the process is exempt from the limit.
+fn check_memlock(required: u64) -> io::Result<()> {
+    let limit = memlock_limit()?;
+    if required <= limit || has_cap_ipc_lock() {
+        return Ok(());
+    }
+
+    Err(MemlockError::new(required, limit).into())
+}
+
+fn memlock_limit() -> io::Result<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    syscall!(getrlimit(libc::RLIMIT_MEMLOCK, &mut limit))?;
+    Ok(limit.rlim_cur)
+}
+
+/// Whether the process has the `CAP_IPC_LOCK` capability, exempting it from
+/// `RLIMIT_MEMLOCK`.
+fn has_cap_ipc_lock() -> bool {
+    let status = match std::fs::read_to_string("/proc/thread-self/status") {
+        Ok(status) => status,
+        Err(_) => return false,
+    };
+
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+        .is_some_and(|caps| caps & (1 << CAP_IPC_LOCK) != 0)
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
@@ -3,6 +3,18 @@
 //! `io-uring` APIs require passing ownership of buffers to the runtime. The
 //! crate defines [`IoBuf`] and [`IoBufMut`] traits which are implemented by buffer
 //! types that respect the `io-uring` contract.
+//!
+//! Buffers registered with the ring, from a [`FixedBufRegistry`] or a
+//! [`SizeClassPool`], spare the kernel mapping them on each operation.
+//!
+//! The buffers the runtime allocates on its own, such as the chunks of a
+//! pipelined read, come from an [`Allocator`], which embedders can supply.
+
+mod alloc;
+pub use alloc::{AllocBuf, Allocator, Global};
+
+mod fixed;
+pub use fixed::{registered_memory, FixedBuf, FixedBufRegistry, MemlockError};
 
 mod io_buf;
 pub use io_buf::IoBuf;
@@ -10,6 +22,12 @@ pub use io_buf::IoBuf;
 mod io_buf_mut;
 pub use io_buf_mut::IoBufMut;
 
+mod pool;
+pub use pool::SizeClassPool;
+
+mod recycle;
+pub use recycle::{clear_orphan_recycler, set_orphan_recycler};
+
 mod slice;
 pub use slice::Slice;
 
@@ -0,0 +1,150 @@
+use crate::buf::{FixedBuf, FixedBufRegistry};
+
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+
+/// A pool of registered buffers of a few sizes, handing out the smallest
+/// buffer fitting each request.
+///
+/// The buffers of the pool are registered with the ring, as the ones of a
+/// [`FixedBufRegistry`], and the reads and writes of their memory use the
+/// registration without calling the methods dedicated to fixed buffers: a
+/// buffer of the pool passed to [`File::read_at`], [`File::write_at`] or the
+/// reads and writes of pipes, whole or [sliced](crate::buf::IoBuf::slice), is
+/// read into or written from as by [`File::read_fixed_at`]. The runtime tells
+/// them apart by their address. Code written for `Vec<u8>` buffers, generic
+/// over [`IoBufMut`](crate::buf::IoBufMut), benefits from the registration by
+/// taking its buffers from the pool.
+///
+/// As for a `FixedBufRegistry`, the buffers are locked in memory while
+/// registered, and a ring has at most one set of buffers registered at once.
+///
+/// [`File::read_at`]: crate::fs::File::read_at
+/// [`File::write_at`]: crate::fs::File::write_at
+/// [`File::read_fixed_at`]: crate::fs::File::read_fixed_at
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::SizeClassPool;
+/// use tokio_uring::fs::File;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         // 64 buffers of 4 KiB, and 16 of 64 KiB
+///         let pool = SizeClassPool::new(&[(4096, 64), (64 * 1024, 16)]);
+///         pool.register()?;
+///
+///         let file = File::open("hello.txt").await?;
+///         let buf = pool.get(1024).unwrap();
+///         assert_eq!(buf.capacity(), 4096);
+///
+///         let (res, buf) = file.read_at(buf, 0).await;
+///         println!("{:?}", &buf[..res?]);
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct SizeClassPool {
+    registry: FixedBufRegistry,
+
+    /// Size, first index in the registry, and number of buffers of each
+    /// class, in increasing order of size
+    classes: Vec<(usize, usize, usize)>,
+
+    /// Index within its class of the next buffer to try, by class
+    cursors: Vec<Cell<usize>>,
+}
+
+impl SizeClassPool {
+    /// Creates a pool with the given size classes, as pairs of the size and
+    /// of the number of buffers of the class. The buffers are allocated, but
+    /// not registered yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a size is zero, or if there are more than 16384 buffers,
+    /// the limit of the kernel.
+    pub fn new(classes: &[(usize, usize)]) -> SizeClassPool {
+        let mut sizes = classes.to_vec();
+        sizes.sort_unstable();
+        assert!(sizes.iter().all(|&(size, _)| size > 0), "empty buffers");
+
+        let mut first = 0;
+        let classes: Vec<_> = sizes
+            .iter()
+            .map(|&(size, count)| {
+                let class = (size, first, count);
+                first += count;
+                class
+            })
+            .collect();
+
+        let bufs = sizes
+            .iter()
+            .flat_map(|&(size, count)| (0..count).map(move |_| Vec::with_capacity(size)));
+
+        SizeClassPool {
+            registry: FixedBufRegistry::new(bufs),
+            cursors: classes.iter().map(|_| Cell::new(0)).collect(),
+            classes,
+        }
+    }
+
+    /// Registers the buffers with the ring of the current runtime.
+    ///
+    /// See [`FixedBufRegistry::register`].
+    pub fn register(&self) -> io::Result<()> {
+        self.registry.register()
+    }
+
+    /// Unregisters the buffers from the ring. Fails with `EBUSY` if buffers
+    /// are handed out.
+    pub fn unregister(&self) -> io::Result<()> {
+        self.registry.unregister()
+    }
+
+    /// Returns `true` if the buffers are registered.
+    pub fn is_registered(&self) -> bool {
+        self.registry.is_registered()
+    }
+
+    /// Hands out an empty buffer of at least `capacity` bytes: the smallest
+    /// one available. Returns `None` if the buffers of the classes fitting
+    /// `capacity` are all handed out, or if none fits it.
+    ///
+    /// The buffer returns to the pool when dropped.
+    pub fn get(&self, capacity: usize) -> Option<FixedBuf> {
+        self.classes
+            .iter()
+            .zip(&self.cursors)
+            .filter(|((size, _, _), _)| *size >= capacity)
+            .find_map(|(&(_, first, count), cursor)| {
+                // Starting after the buffer handed out last, as the buffers
+                // before it are likely still out
+                (0..count).find_map(|i| {
+                    let index = (cursor.get() + i) % count;
+                    let mut buf = self.registry.check_out(first + index)?;
+                    cursor.set(index + 1);
+                    buf.clear();
+                    Some(buf)
+                })
+            })
+    }
+
+    /// Returns the sizes of the classes of the pool, in increasing order.
+    pub fn sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.classes.iter().map(|&(size, _, _)| size)
+    }
+}
+
+impl fmt::Debug for SizeClassPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SizeClassPool")
+            .field("classes", &self.classes)
+            .field("registered", &self.is_registered())
+            .finish()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::buf::IoBuf;
+use crate::driver::recycle;
+
+/// Sets the callback receiving the buffers of type `B` of the operations
+/// dropped while in flight, replacing the previous one.
+///
+/// An operation dropped before completing, e.g. by a branch of
+/// `tokio::select!` that lost, leaves its buffer to the runtime until the
+/// kernel releases it. By default the buffer is then dropped. With a recycler,
+/// the buffer is passed to it instead, for example to return it to a pool.
+///
+/// Recyclers are per runtime, and matched on the exact type of the buffer
+/// passed to the operation: a recycler of `Vec<u8>` does not receive the
+/// buffers of operations passed a `Slice<Vec<u8>>`. A recycler is called on
+/// the runtime thread, and may submit operations.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// tokio_uring::start(async {
+///     let pool = Rc::new(RefCell::new(Vec::<Vec<u8>>::new()));
+///
+///     let recycled = pool.clone();
+///     tokio_uring::buf::set_orphan_recycler(move |mut buf: Vec<u8>| {
+///         buf.clear();
+///         recycled.borrow_mut().push(buf);
+///     });
+/// });
+/// ```
+pub fn set_orphan_recycler<B: IoBuf, F: FnMut(B) + 'static>(f: F) {
+    recycle::set(f)
+}
+
+/// Removes the recycler of the buffers of type `B`, so that the buffers of
+/// operations dropped while in flight are dropped.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn clear_orphan_recycler<B: IoBuf>() {
+    recycle::clear::<B>()
+}
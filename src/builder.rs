@@ -0,0 +1,977 @@
+use crate::driver;
+use crate::driver::watchdog::{Hook, Watchdog};
+use crate::runtime::Runtime;
+use crate::OpKind;
+
+use io_uring::register::Restriction;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `MPOL_DEFAULT`, from `linux/mempolicy.h`
+const MPOL_DEFAULT: libc::c_int = 0;
+/// `MPOL_PREFERRED`
+const MPOL_PREFERRED: libc::c_int = 1;
+
+/// `IORING_REGISTER_NAPI`, from `linux/io_uring.h`
+const IORING_REGISTER_NAPI: libc::c_uint = 27;
+
+/// `IORING_REGISTER_*` opcodes the runtime uses once started, allowed by the
+/// restrictions of [`Builder::restrict`]: registering and unregistering
+/// buffers and personalities, probing the supported opcodes, and registering
+/// and updating the fixed-file table of [`Builder::auto_register_files`].
+const REGISTER_OPS: &[u8] = &[0, 1, 6, 8, 9, 10, 13];
+
+/// `IORING_OP_*` opcodes the runtime submits on its own: `IORING_OP_CLOSE`,
+/// `IORING_OP_ASYNC_CANCEL` and `IORING_OP_TIMEOUT_REMOVE` when dropping
+/// resources and operations, and `IORING_OP_POLL_ADD` to wait for its
+/// `eventfd`s.
+const RUNTIME_OPS: &[u8] = &[19, 14, 12, 6];
+
+/// Every SQE flag, `IOSQE_*`, allowed by the restrictions
+const SQE_FLAGS: u8 = 0x7f;
+
+/// Number of NUMA nodes covered by the node masks
+const MAX_NODES: usize = 1024;
+
+type NodeMask = [libc::c_ulong; MAX_NODES / libc::c_ulong::BITS as usize];
+
+/// Configures a `tokio-uring` runtime before starting it.
+///
+/// [`start`](crate::start) runs a runtime with the default configuration.
+/// The builder controls where the runtime runs: the CPUs its thread runs on,
+/// a kernel thread polling the submission queue, and the NUMA node its memory
+/// is allocated from.
+///
+/// # Examples
+///
+/// Pinning a latency-sensitive runtime to CPU 2, with the kernel thread
+/// polling the submission queue on CPU 3, both on NUMA node 0:
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::builder()
+///         .thread_cpus([2])
+///         .sqpoll(Duration::from_millis(100))
+///         .sqpoll_cpu(3)
+///         .numa_node(0)
+///         .start(async {
+///             // Serve requests
+///         })
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Builder {
+    /// Idle time of the SQPOLL thread, if enabled
+    sqpoll: Option<Duration>,
+
+    /// CPU the SQPOLL thread is bound to
+    sqpoll_cpu: Option<u32>,
+
+    /// CPUs the runtime thread is pinned to
+    thread_cpus: Option<Vec<usize>>,
+
+    /// NUMA node memory is allocated from
+    numa_node: Option<u32>,
+
+    /// Age from which operations are reported as slow
+    slow_op_threshold: Option<Duration>,
+
+    /// Whether slow operations are cancelled once reported
+    cancel_slow_ops: bool,
+
+    /// Called with each slow operation
+    on_slow_op: Option<SlowOpHook>,
+
+    /// How the runtime waits for completions
+    wait_strategy: WaitStrategy,
+
+    /// Number of completions to wait for once woken up, and for how long
+    min_complete: Option<(usize, Duration)>,
+
+    /// NAPI busy poll timeout, and whether busy polling is preferred
+    napi: Option<(Duration, bool)>,
+
+    /// Whether the operations with a fallback are avoided
+    seccomp_compatible: bool,
+
+    /// Opcodes the ring is restricted to, if any
+    restrictions: Option<Vec<u8>>,
+
+    /// Number of operations the driver has room for up front
+    preallocated_ops: usize,
+
+    /// Maximum number of operations in flight, and what submitting more does
+    max_ops: Option<(usize, Backpressure)>,
+
+    /// Same, for each file descriptor
+    max_ops_per_fd: Option<(usize, Backpressure)>,
+
+    /// Number of times a submission rejected with `EBUSY` is retried before
+    /// being deferred, and what submitting more does meanwhile
+    busy_retries: (u32, Backpressure),
+
+    /// Size of the fixed-file table the file descriptors used most are
+    /// registered into
+    auto_register_files: Option<u32>,
+}
+
+/// `struct io_uring_napi`
+#[repr(C)]
+struct IoUringNapi {
+    busy_poll_to: u32,
+    prefer_busy_poll: u8,
+    pad: [u8; 3],
+    resv: u64,
+}
+
+/// How the runtime waits for the completion of operations, set with
+/// [`Builder::wait_strategy`].
+///
+/// Polling for completions instead of sleeping until the next one saves the
+/// time of waking the thread up, a few microseconds, at the cost of keeping a
+/// CPU busy. Polling pays off when the thread is pinned to a dedicated CPU,
+/// see [`Builder::thread_cpus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WaitStrategy {
+    /// Sleeps until an operation completes, the default.
+    Block,
+
+    /// Polls for completions continuously, never sleeping: the thread keeps
+    /// its CPU busy even while idle.
+    BusyPoll,
+
+    /// Polls for completions for the given duration after the last one, then
+    /// sleeps until the next one. Bursts of operations are served with the
+    /// latency of polling, and idle periods do not burn a CPU.
+    Hybrid(Duration),
+}
+
+/// What submitting an operation does once [`Builder::max_ops`] operations are
+/// in flight, or [`Builder::max_ops_per_fd`] operations on its file
+/// descriptor, or while the kernel is busy, see [`Builder::busy_retries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backpressure {
+    /// The operation fails with
+    /// [`QuotaExceeded`](io::ErrorKind::QuotaExceeded), or with `EBUSY` while
+    /// the kernel is busy.
+    Fail,
+
+    /// The operation waits for another one to complete, then is submitted.
+    Wait,
+}
+
+/// An operation in flight for longer than the threshold set with
+/// [`Builder::slow_op_threshold`], or still in flight when the runtime shuts
+/// down.
+///
+/// Its `Display` implementation describes the operation, for logging.
+#[derive(Debug, Clone)]
+pub struct SlowOp {
+    pub(crate) kind: OpKind,
+    pub(crate) fd: RawFd,
+    pub(crate) age: Duration,
+    pub(crate) cancelled: bool,
+    pub(crate) leaked: bool,
+}
+
+#[derive(Clone)]
+struct SlowOpHook(Hook);
+
+/// Creates a [`Builder`] with the default configuration.
+pub fn builder() -> Builder {
+    Builder::new()
+}
+
+impl Builder {
+    /// Creates a builder with the default configuration: the runtime thread
+    /// runs on any CPU, operations are submitted by the runtime thread, and
+    /// memory follows the memory policy of the thread.
+    pub fn new() -> Builder {
+        Builder {
+            sqpoll: None,
+            sqpoll_cpu: None,
+            thread_cpus: None,
+            numa_node: None,
+            slow_op_threshold: None,
+            cancel_slow_ops: false,
+            on_slow_op: None,
+            wait_strategy: WaitStrategy::Block,
+            min_complete: None,
+            napi: None,
+            seccomp_compatible: false,
+            restrictions: None,
+            preallocated_ops: 0,
+            max_ops: None,
+            max_ops_per_fd: None,
+            busy_retries: (driver::DEFAULT_BUSY_RETRIES, Backpressure::Wait),
+            auto_register_files: None,
+        }
+    }
+
+    /// Polls the submission queue from a kernel thread, `IORING_SETUP_SQPOLL`,
+    /// so that submitting operations does not take a system call while the
+    /// thread is awake. The thread goes to sleep after being `idle` for the
+    /// given duration, rounded down to milliseconds.
+    ///
+    /// The kernel thread spins on a CPU while awake. Requires
+    /// `CAP_SYS_NICE` on kernels older than 5.11.
+    pub fn sqpoll(&mut self, idle: Duration) -> &mut Builder {
+        self.sqpoll = Some(idle);
+        self
+    }
+
+    /// Binds the kernel thread polling the submission queue to `cpu`,
+    /// `IORING_SETUP_SQ_AFF`.
+    ///
+    /// Starting the runtime fails with `EINVAL` unless
+    /// [`sqpoll`](Builder::sqpoll) is set.
+    pub fn sqpoll_cpu(&mut self, cpu: u32) -> &mut Builder {
+        self.sqpoll_cpu = Some(cpu);
+        self
+    }
+
+    /// Pins the thread running the runtime to the given CPUs, with
+    /// `sched_setaffinity(2)`.
+    ///
+    /// The affinity of the thread is restored when the runtime exits.
+    /// Starting the runtime fails with `EINVAL` if none of the CPUs is
+    /// online.
+    pub fn thread_cpus<I: IntoIterator<Item = usize>>(&mut self, cpus: I) -> &mut Builder {
+        self.thread_cpus = Some(cpus.into_iter().collect());
+        self
+    }
+
+    /// Allocates memory from the NUMA node `node` preferably, with
+    /// `set_mempolicy(2)`.
+    ///
+    /// The policy applies to the thread running the runtime while it runs:
+    /// the rings shared with the kernel, and the buffers allocated by the
+    /// tasks, come from the node. Other nodes are used when the node runs out
+    /// of memory. The policy of the thread is restored when the runtime exits.
+    pub fn numa_node(&mut self, node: u32) -> &mut Builder {
+        self.numa_node = Some(node);
+        self
+    }
+
+    /// Reports the operations on files and block devices which are in flight
+    /// for longer than `threshold`, such as the operations stuck on a hung
+    /// storage device.
+    ///
+    /// Each slow operation is reported once, to the hook set with
+    /// [`on_slow_op`](Builder::on_slow_op), or logged to stderr. Operations
+    /// on other fds, such as sockets and pipes, and operations without an fd,
+    /// such as timeouts, may wait for an event indefinitely and are never
+    /// reported. Operations are checked periodically, and reported up to a
+    /// quarter of `threshold` late.
+    pub fn slow_op_threshold(&mut self, threshold: Duration) -> &mut Builder {
+        self.slow_op_threshold = Some(threshold);
+        self
+    }
+
+    /// Cancels the slow operations once reported, so that the tasks waiting
+    /// for them resume with `ECANCELED`. Does nothing unless
+    /// [`slow_op_threshold`](Builder::slow_op_threshold) is set.
+    ///
+    /// The kernel may not be able to cancel an operation a device is
+    /// processing, which then completes whenever the device does.
+    pub fn cancel_slow_ops(&mut self, cancel: bool) -> &mut Builder {
+        self.cancel_slow_ops = cancel;
+        self
+    }
+
+    /// Calls `f` with each slow operation, instead of logging it to stderr.
+    /// Does nothing unless [`slow_op_threshold`](Builder::slow_op_threshold)
+    /// is set.
+    ///
+    /// `f` is also called with the operations leaked on shutdown, see
+    /// [`SlowOp::leaked`].
+    ///
+    /// `f` runs on the thread of the runtime, and must not block it.
+    pub fn on_slow_op<F>(&mut self, f: F) -> &mut Builder
+    where
+        F: Fn(&SlowOp) + Send + Sync + 'static,
+    {
+        self.on_slow_op = Some(SlowOpHook(Arc::new(f)));
+        self
+    }
+
+    /// Sets how the runtime waits for completions, trading CPU time for
+    /// latency. See [`WaitStrategy`].
+    ///
+    /// With [`sqpoll`](Builder::sqpoll), busy polling the completions and the
+    /// kernel thread polling the submissions keep two CPUs busy.
+    pub fn wait_strategy(&mut self, strategy: WaitStrategy) -> &mut Builder {
+        self.wait_strategy = strategy;
+        self
+    }
+
+    /// Batches completions: once an operation completes, the runtime waits
+    /// for `count` completions, or for `max_wait`, whichever comes first,
+    /// before resuming the tasks.
+    ///
+    /// The wait is a single `io_uring_enter(2)` with a timeout, during which
+    /// the thread is not woken by each completion. This cuts wakeups and
+    /// increases throughput for workloads with many operations in flight, at
+    /// the cost of up to `max_wait` of latency. Tasks, timers and Tokio I/O
+    /// also wait, so `max_wait` should be short, such as a few hundred
+    /// microseconds.
+    ///
+    /// Only applies when the runtime sleeps, see [`WaitStrategy`]. Requires
+    /// Linux 5.11 or later.
+    pub fn min_complete(&mut self, count: usize, max_wait: Duration) -> &mut Builder {
+        self.min_complete = Some((count, max_wait));
+        self
+    }
+
+    /// Busy polls the network devices of the sockets of the runtime, NAPI
+    /// busy polling, while waiting for completions, for up to `timeout`,
+    /// rounded down to microseconds.
+    ///
+    /// Instead of waiting for the network card to raise an interrupt when
+    /// packets arrive, the kernel polls its receive queue from the thread of
+    /// the runtime, cutting the receive latency at the cost of CPU time. With
+    /// `prefer_busy_poll`, the interrupts of the device are deferred while
+    /// the thread polls, `SO_PREFER_BUSY_POLL`.
+    ///
+    /// Requires Linux 6.9 or later, and a network driver supporting NAPI:
+    /// starting the runtime fails with `EINVAL` on older kernels.
+    pub fn napi_busy_poll(&mut self, timeout: Duration, prefer_busy_poll: bool) -> &mut Builder {
+        self.napi = Some((timeout, prefer_busy_poll));
+        self
+    }
+
+    /// Avoids the optional system calls and `io-uring` operations, for the
+    /// runtime to behave predictably under tight `seccomp(2)` profiles, such
+    /// as the ones of containers and sandboxes.
+    ///
+    /// The runtime then makes the system calls listed by
+    /// [`syscalls`](Builder::syscalls), and no others. The operations which
+    /// have a fallback use it: the size of a file is read with `fstat(2)`
+    /// instead of an `IORING_OP_STATX` operation, and the opcodes supported
+    /// by the ring are not probed, the operations which depend on them
+    /// falling back to plain reads and writes. The runtime never uses
+    /// `openat2(2)`, in either mode.
+    ///
+    /// Note that `seccomp` filters apply to system calls only, and not to the
+    /// `io-uring` operations, which the kernel performs on behalf of the
+    /// process. Profiles denying `io_uring_setup(2)`, such as the default
+    /// profile of recent container runtimes, prevent starting the runtime
+    /// altogether.
+    pub fn seccomp_compatible(&mut self, enabled: bool) -> &mut Builder {
+        self.seccomp_compatible = enabled;
+        self
+    }
+
+    /// Allocates room for `ops` operations in flight when starting the
+    /// runtime, instead of growing the state of the operations as needed.
+    ///
+    /// Latency-sensitive services avoid allocating on their hot paths, where
+    /// the allocator may take locks or fault pages in. Once the runtime runs
+    /// with at most `ops` operations in flight, submitting and completing the
+    /// following operations allocates nothing:
+    ///
+    /// * reads and writes of files at an offset or at their position, into
+    ///   and from buffers the caller owns, such as [`File::read_at`], or
+    ///   registered with [`FixedBufRegistry`], such as
+    ///   [`File::read_fixed_at`];
+    /// * reads and writes of connected sockets and pipes;
+    /// * syncs of files.
+    ///
+    /// The operations taking a path or a socket address, such as opening a
+    /// file or [`UdpSocket::send_to`], allocate a copy of it, as do the
+    /// operations dropped while in flight, whose state the runtime keeps
+    /// until they complete. The [slow operations
+    /// watchdog](Builder::slow_op_threshold) and the latency metrics
+    /// allocate to track each operation. Spawning a task allocates the task.
+    ///
+    /// [`File::read_at`]: crate::fs::File::read_at
+    /// [`File::read_fixed_at`]: crate::fs::File::read_fixed_at
+    /// [`FixedBufRegistry`]: crate::buf::FixedBufRegistry
+    /// [`UdpSocket::send_to`]: crate::net::UdpSocket::send_to
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::buf::FixedBufRegistry;
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::builder().preallocate_ops(1024).start(async {
+    ///         // Set up everything up front
+    ///         let registry = FixedBufRegistry::new((0..64).map(|_| vec![0; 4096]));
+    ///         registry.register()?;
+    ///         let file = File::open("data.bin").await?;
+    ///
+    ///         // Then serve without allocating
+    ///         let mut buf = registry.check_out(0).unwrap();
+    ///         for pos in (0..1 << 30).step_by(4096) {
+    ///             let (res, b) = file.read_fixed_at(buf, pos).await;
+    ///             res?;
+    ///             buf = b;
+    ///         }
+    ///
+    ///         Ok::<(), std::io::Error>(())
+    ///     })??;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn preallocate_ops(&mut self, ops: usize) -> &mut Builder {
+        self.preallocated_ops = ops;
+        self
+    }
+
+    /// Bounds the number of operations in flight to `ops`, applying
+    /// `backpressure` to the operations submitted past it.
+    ///
+    /// The state of the operations in flight grows as needed, from the room
+    /// allocated with [`preallocate_ops`](Builder::preallocate_ops), and is
+    /// kept until the runtime exits. Bounding it caps the memory of a runtime
+    /// flooded with operations, such as one serving connections it does not
+    /// limit otherwise. The operations count until their future observed
+    /// their completion, or until they completed if their future was dropped.
+    ///
+    /// Once `ops` operations are in flight, the reads and writes of files,
+    /// sockets and pipes apply `backpressure` before being submitted, and
+    /// timers wait for room. The other operations fail with
+    /// [`QuotaExceeded`](io::ErrorKind::QuotaExceeded) either way: to wait for
+    /// room before any operation, acquire an [`OpsPermit`], which is bounded
+    /// by `ops` too.
+    ///
+    /// [`OpsPermit`]: crate::sync::OpsPermit
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ops` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::Backpressure;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::builder()
+    ///         .preallocate_ops(1024)
+    ///         .max_ops(4096, Backpressure::Wait)
+    ///         .start(async {
+    ///             // Serve requests
+    ///         })
+    /// }
+    /// ```
+    pub fn max_ops(&mut self, ops: usize, backpressure: Backpressure) -> &mut Builder {
+        assert!(ops > 0, "`max_ops` must be positive");
+        self.max_ops = Some((ops, backpressure));
+        self
+    }
+
+    /// Bounds the number of operations in flight on each file descriptor to
+    /// `ops`, applying `backpressure` to the reads and writes submitted past
+    /// it.
+    ///
+    /// On a server, a client which does not read its responses, or a file on
+    /// a slow device, would otherwise take up the operations in flight of the
+    /// runtime, up to [`max_ops`](Builder::max_ops), and starve the other
+    /// ones. The reads and writes of files, sockets and pipes, whichever task
+    /// submits them, wait or fail once `ops` operations are in flight on
+    /// their file descriptor. Every operation on the file descriptor counts,
+    /// until it completed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ops` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::Backpressure;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::builder()
+    ///         .max_ops(4096, Backpressure::Wait)
+    ///         .max_ops_per_fd(16, Backpressure::Wait)
+    ///         .start(async {
+    ///             // Serve clients
+    ///         })
+    /// }
+    /// ```
+    pub fn max_ops_per_fd(&mut self, ops: usize, backpressure: Backpressure) -> &mut Builder {
+        assert!(ops > 0, "`max_ops_per_fd` must be positive");
+        self.max_ops_per_fd = Some((ops, backpressure));
+        self
+    }
+
+    /// Retries a submission the kernel rejects with `EBUSY` up to `retries`
+    /// times, then defers it, applying `backpressure` to the reads and
+    /// writes submitted until it went through.
+    ///
+    /// The kernel rejects submissions with `EBUSY` while it cannot post more
+    /// completions, once the completion queue overflowed. The runtime
+    /// processes the completions before each retry, making room in the
+    /// queue. Once the retries are exhausted, the operations stay in the
+    /// submission queue, and the runtime submits them again after yielding
+    /// to the other tasks, rather than spinning. The deferral is visible to
+    /// the application: the reads and writes of files, sockets and pipes wait
+    /// or fail with `EBUSY` according to `backpressure`, as do the other
+    /// operations once the submission queue is full. With the `metrics`
+    /// feature, the deferrals are counted by
+    /// [`metrics::busy_deferrals`](crate::metrics::busy_deferrals).
+    ///
+    /// Defaults to 8 retries, and [`Backpressure::Wait`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::Backpressure;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::builder()
+    ///         .busy_retries(2, Backpressure::Fail)
+    ///         .start(async {
+    ///             // Serve requests, shedding load while the kernel is busy
+    ///         })
+    /// }
+    /// ```
+    pub fn busy_retries(&mut self, retries: u32, backpressure: Backpressure) -> &mut Builder {
+        self.busy_retries = (retries, backpressure);
+        self
+    }
+
+    /// Registers the file descriptors used most into a fixed-file table of
+    /// `slots` files, for their operations to skip looking the file up and
+    /// taking a reference to it, as `IOSQE_FIXED_FILE` operations do.
+    ///
+    /// The registration is transparent: a file descriptor is registered by
+    /// the second operation using it, evicting the file descriptor used least
+    /// recently once the table is full, and is unregistered before it is
+    /// closed. Registering and evicting take an `io_uring_register(2)` system
+    /// call, which pays off for the file descriptors used for many operations,
+    /// such as the files of a database or long-lived connections, but not for
+    /// short-lived connections. The reads, writes, syncs and polls of files,
+    /// sockets and pipes use the table.
+    ///
+    /// Starting the runtime fails if the table cannot be registered, on
+    /// kernels older than 5.19.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::builder()
+    ///         .auto_register_files(256)
+    ///         .start(async {
+    ///             // Serve requests
+    ///         })
+    /// }
+    /// ```
+    pub fn auto_register_files(&mut self, slots: u32) -> &mut Builder {
+        self.auto_register_files = Some(slots);
+        self
+    }
+
+    /// Restricts the ring to the operations with the given opcodes, the
+    /// `IORING_OP_*` constants of `io_uring.h`, also available as the `CODE`
+    /// of the types of `io_uring::opcode`, with
+    /// `IORING_REGISTER_RESTRICTIONS`.
+    ///
+    /// The restrictions are registered before the ring is enabled, and cannot
+    /// be lifted: operations with other opcodes fail with `EACCES`
+    /// ([`PermissionDenied`](io::ErrorKind::PermissionDenied)), whichever
+    /// code submits them. This bounds what the code running on the runtime,
+    /// such as plugins, can have the kernel do on its behalf, as a defense in
+    /// depth. The opcodes the runtime submits on its own, to close the fds of
+    /// dropped resources, to cancel dropped operations, and to wait for the
+    /// readiness of fds, are always allowed.
+    ///
+    /// Requires Linux 5.10 or later.
+    ///
+    /// # Examples
+    ///
+    /// Allowing to open and read files only:
+    ///
+    /// ```no_run
+    /// use io_uring::opcode;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::builder()
+    ///         .restrict(&[opcode::OpenAt::CODE, opcode::Read::CODE])
+    ///         .start(async {
+    ///             // Run the plugins
+    ///         })
+    /// }
+    /// ```
+    pub fn restrict(&mut self, opcodes: &[u8]) -> &mut Builder {
+        self.restrictions = Some(opcodes.to_vec());
+        self
+    }
+
+    /// Returns the system calls the runtime makes with this configuration,
+    /// by their names in `syscalls(2)`, for building a `seccomp(2)` profile.
+    ///
+    /// The list covers starting the runtime, driving its operations, and
+    /// shutting it down, including the system calls of the Tokio runtime it
+    /// runs on. It does not cover the resources used by the tasks: sockets
+    /// and files are created with `io-uring` operations, but some of their
+    /// methods, such as the ones changing socket options or permissions,
+    /// make the system calls documented on them. The C library may implement
+    /// a call with a related system call, such as `fstat` with `newfstatat`,
+    /// depending on its version and on the architecture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut builder = tokio_uring::builder();
+    /// builder.seccomp_compatible(true);
+    ///
+    /// let syscalls = builder.syscalls();
+    /// assert!(syscalls.contains(&"io_uring_enter"));
+    /// assert!(!syscalls.contains(&"set_mempolicy"));
+    /// ```
+    pub fn syscalls(&self) -> Vec<&'static str> {
+        let mut syscalls = vec![
+            // The ring
+            "io_uring_setup",
+            "io_uring_enter",
+            "mmap",
+            "munmap",
+            "close",
+            // The Tokio runtime, and waking it
+            "epoll_create1",
+            "epoll_ctl",
+            "epoll_wait",
+            "epoll_pwait",
+            "eventfd2",
+            "fcntl",
+            "read",
+            "write",
+            "futex",
+            "clock_gettime",
+        ];
+
+        if self.seccomp_compatible {
+            // Reading the size of a file, instead of `IORING_OP_STATX`
+            syscalls.push("fstat");
+        }
+        if self.thread_cpus.is_some() {
+            syscalls.extend(["sched_getaffinity", "sched_setaffinity"]);
+        }
+        if self.numa_node.is_some() {
+            syscalls.extend(["get_mempolicy", "set_mempolicy"]);
+        }
+        if self.napi.is_some() || self.restrictions.is_some() || self.auto_register_files.is_some()
+        {
+            syscalls.push("io_uring_register");
+        }
+        if self.slow_op_threshold.is_some() || cfg!(debug_assertions) {
+            // Inspecting the fds of the operations
+            syscalls.push("fstat");
+        }
+
+        syscalls.sort_unstable();
+        syscalls.dedup();
+        syscalls
+    }
+
+    /// Starts a runtime with the configuration of the builder, and runs
+    /// `future` to completion on it.
+    ///
+    /// See [`start`](crate::start). Fails if the configuration cannot be
+    /// applied, for example if the kernel does not support it, or if called
+    /// from within a `tokio-uring` or a Tokio runtime.
+    pub fn start<F: Future>(&self, future: F) -> io::Result<F::Output> {
+        let mut rt = Runtime::new(self)?;
+        Ok(rt.block_on(future))
+    }
+
+    pub(crate) fn build_uring(&self) -> io::Result<io_uring::IoUring> {
+        let mut builder = io_uring::IoUring::builder();
+
+        if let Some(idle) = self.sqpoll {
+            let idle = u32::try_from(idle.as_millis()).unwrap_or(u32::MAX);
+            builder.setup_sqpoll(idle);
+        }
+        if let Some(cpu) = self.sqpoll_cpu {
+            builder.setup_sqpoll_cpu(cpu);
+        }
+
+        // Restrictions can only be registered while the ring is disabled
+        if self.restrictions.is_some() {
+            builder.setup_r_disabled();
+        }
+
+        let uring = builder.build(256)?;
+
+        if let Some((timeout, prefer_busy_poll)) = self.napi {
+            register_napi(&uring, timeout, prefer_busy_poll)?;
+        }
+
+        if let Some(opcodes) = &self.restrictions {
+            let mut restrictions: Vec<Restriction> = opcodes
+                .iter()
+                .chain(RUNTIME_OPS)
+                .map(|&op| Restriction::sqe_op(op))
+                .chain(REGISTER_OPS.iter().map(|&op| Restriction::register_op(op)))
+                .collect();
+            restrictions.push(Restriction::sqe_flags_allowed(SQE_FLAGS));
+
+            let submitter = uring.submitter();
+            submitter.register_restrictions(&mut restrictions)?;
+            submitter.register_enable_rings()?;
+        }
+
+        Ok(uring)
+    }
+
+    pub(crate) fn seccomp_compatible_config(&self) -> bool {
+        self.seccomp_compatible
+    }
+
+    pub(crate) fn min_complete_config(&self) -> Option<(usize, Duration)> {
+        self.min_complete
+    }
+
+    /// Returns how long to poll for completions before sleeping.
+    pub(crate) fn spin_duration(&self) -> Option<Duration> {
+        match self.wait_strategy {
+            WaitStrategy::Block => None,
+            WaitStrategy::BusyPoll => Some(Duration::MAX),
+            WaitStrategy::Hybrid(spin) => Some(spin),
+        }
+    }
+
+    pub(crate) fn preallocated_ops(&self) -> usize {
+        self.preallocated_ops
+    }
+
+    pub(crate) fn max_ops_config(&self) -> Option<(usize, Backpressure)> {
+        self.max_ops
+    }
+
+    pub(crate) fn max_ops_per_fd_config(&self) -> Option<(usize, Backpressure)> {
+        self.max_ops_per_fd
+    }
+
+    pub(crate) fn busy_retries_config(&self) -> (u32, Backpressure) {
+        self.busy_retries
+    }
+
+    pub(crate) fn auto_register_files_config(&self) -> Option<u32> {
+        self.auto_register_files
+    }
+
+    pub(crate) fn build_watchdog(&self) -> Option<Watchdog> {
+        let threshold = self.slow_op_threshold?;
+        let hook = self.on_slow_op.as_ref().map(|hook| hook.0.clone());
+        Some(Watchdog::new(threshold, self.cancel_slow_ops, hook))
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+impl SlowOp {
+    /// Returns the kind of the operation.
+    pub fn kind(&self) -> OpKind {
+        self.kind
+    }
+
+    /// Returns the opcode of the operation, one of the `IORING_OP_*`
+    /// constants of `io_uring.h`.
+    pub fn opcode(&self) -> u8 {
+        self.kind.opcode()
+    }
+
+    /// Returns the fd the operation applies to, or -1 for a leaked operation
+    /// whose fd is unknown.
+    pub fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Returns how long the operation had been in flight when reported, or
+    /// zero for a leaked operation whose age is unknown.
+    pub fn age(&self) -> Duration {
+        self.age
+    }
+
+    /// Returns `true` if the operation is being cancelled, see
+    /// [`Builder::cancel_slow_ops`].
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Returns `true` if the operation was still in flight when the runtime
+    /// shut down. Its state, such as its buffer, is leaked, as the kernel may
+    /// still use it.
+    pub fn leaked(&self) -> bool {
+        self.leaked
+    }
+}
+
+impl fmt::Display for SlowOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.leaked {
+            write!(f, "leaking {} operation", self.kind)?;
+            if self.fd >= 0 {
+                write!(f, " on fd {}", self.fd)?;
+            }
+            return f.write_str(" still in flight on shutdown");
+        }
+
+        write!(
+            f,
+            "{} operation on fd {} in flight for {:?}",
+            self.kind, self.fd, self.age
+        )?;
+        if self.cancelled {
+            f.write_str(", cancelling")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SlowOpHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SlowOpHook")
+    }
+}
+
+/// Placement of the runtime thread, restoring the previous one on drop.
+pub(crate) struct Placement {
+    /// Affinity of the thread before it was pinned
+    affinity: Option<libc::cpu_set_t>,
+
+    /// Memory policy of the thread before it was set
+    mempolicy: Option<(libc::c_int, NodeMask)>,
+}
+
+impl Placement {
+    /// Applies the placement configured by `builder` to the current thread.
+    pub(crate) fn apply(builder: &Builder) -> io::Result<Placement> {
+        let mut placement = Placement {
+            affinity: None,
+            mempolicy: None,
+        };
+
+        if let Some(cpus) = &builder.thread_cpus {
+            let previous = get_affinity()?;
+
+            let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            for &cpu in cpus {
+                if cpu >= libc::CPU_SETSIZE as usize {
+                    return Err(io::Error::from_raw_os_error(libc::EINVAL));
+                }
+                unsafe { libc::CPU_SET(cpu, &mut set) };
+            }
+
+            set_affinity(&set)?;
+            placement.affinity = Some(previous);
+        }
+
+        if let Some(node) = builder.numa_node {
+            let node = node as usize;
+            if node >= MAX_NODES {
+                return Err(io::Error::from_raw_os_error(libc::EINVAL));
+            }
+
+            let previous = get_mempolicy()?;
+
+            let mut mask: NodeMask = [0; MAX_NODES / libc::c_ulong::BITS as usize];
+            let bits = libc::c_ulong::BITS as usize;
+            mask[node / bits] |= 1 << (node % bits);
+
+            set_mempolicy(MPOL_PREFERRED, &mask)?;
+            placement.mempolicy = Some(previous);
+        }
+
+        Ok(placement)
+    }
+}
+
+impl Drop for Placement {
+    fn drop(&mut self) {
+        if let Some(set) = &self.affinity {
+            let _ = set_affinity(set);
+        }
+        if let Some((mode, mask)) = &self.mempolicy {
+            let _ = set_mempolicy(*mode, mask);
+        }
+    }
+}
+
+fn register_napi(
+    uring: &io_uring::IoUring,
+    timeout: Duration,
+    prefer_busy_poll: bool,
+) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut napi = IoUringNapi {
+        busy_poll_to: u32::try_from(timeout.as_micros()).unwrap_or(u32::MAX),
+        prefer_busy_poll: prefer_busy_poll as u8,
+        pad: [0; 3],
+        resv: 0,
+    };
+
+    syscall!(syscall(
+        libc::SYS_io_uring_register,
+        uring.as_raw_fd(),
+        IORING_REGISTER_NAPI,
+        &mut napi as *mut IoUringNapi,
+        1
+    ))?;
+    Ok(())
+}
+
+fn get_affinity() -> io::Result<libc::cpu_set_t> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    syscall!(sched_getaffinity(
+        0,
+        std::mem::size_of::<libc::cpu_set_t>(),
+        &mut set
+    ))?;
+    Ok(set)
+}
+
+fn set_affinity(set: &libc::cpu_set_t) -> io::Result<()> {
+    syscall!(sched_setaffinity(
+        0,
+        std::mem::size_of::<libc::cpu_set_t>(),
+        set
+    ))?;
+    Ok(())
+}
+
+fn get_mempolicy() -> io::Result<(libc::c_int, NodeMask)> {
+    let mut mode = MPOL_DEFAULT;
+    let mut mask: NodeMask = [0; MAX_NODES / libc::c_ulong::BITS as usize];
+    syscall!(syscall(
+        libc::SYS_get_mempolicy,
+        &mut mode as *mut libc::c_int,
+        mask.as_mut_ptr(),
+        MAX_NODES + 1,
+        std::ptr::null_mut::<libc::c_void>(),
+        0
+    ))?;
+    Ok((mode, mask))
+}
+
+fn set_mempolicy(mode: libc::c_int, mask: &NodeMask) -> io::Result<()> {
+    syscall!(syscall(
+        libc::SYS_set_mempolicy,
+        mode,
+        mask.as_ptr(),
+        MAX_NODES + 1
+    ))?;
+    Ok(())
+}
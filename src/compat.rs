@@ -0,0 +1,334 @@
+//! Adapters to the I/O traits of Tokio, to run libraries written against
+//! them, such as `hyper`, over `tokio-uring` streams.
+//!
+//! [`Compat`] implements [`AsyncRead`] and [`AsyncWrite`] over a
+//! [`TcpStream`] or a [`UnixStream`], by submitting reads and writes to the
+//! ring with buffers it owns. Data is copied between these buffers and the
+//! ones of the caller, the price of the borrowed buffers of the Tokio
+//! traits.
+//!
+//! # Running hyper
+//!
+//! With the `hyper` feature, [`Compat`] also implements the I/O traits of
+//! `hyper` 1.x, `hyper::rt::Read` and `hyper::rt::Write`, and
+//! `LocalExecutor` spawns the tasks of `hyper` onto the `tokio-uring`
+//! runtime, as the futures of the connections are `!Send`. See the example
+//! of `LocalExecutor`.
+//!
+//! [`TcpStream`]: crate::net::TcpStream
+//! [`UnixStream`]: crate::net::UnixStream
+
+use crate::buf::{IoBuf, Slice};
+use crate::driver::{Op, Read, Socket, Write};
+use crate::net::{TcpStream, UnixStream};
+
+use std::cmp;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Size of the buffers of the reads and writes submitted.
+const BUF_SIZE: usize = 64 * 1024;
+
+/// A stream implementing [`AsyncRead`] and [`AsyncWrite`], see the
+/// [module documentation](self).
+///
+/// Writes are buffered: `poll_write` copies the data and submits a write,
+/// which completes in the background. Errors of the write are reported by
+/// the next call to `poll_write`, `poll_flush` or `poll_shutdown`, so flush
+/// the stream before dropping it to know whether the data was written.
+pub struct Compat<S> {
+    stream: S,
+
+    /// Socket of the stream, used by the operations
+    socket: Socket,
+
+    /// Data read and not returned yet, from `read_pos`, unless a read is in
+    /// flight
+    read_buf: Option<Vec<u8>>,
+    read_pos: usize,
+    reading: Option<Op<Read<Vec<u8>>>>,
+
+    /// Buffer of the writes, unless a write is in flight
+    write_buf: Option<Vec<u8>>,
+    writing: Option<Op<Write<Slice<Vec<u8>>>>>,
+}
+
+impl From<TcpStream> for Compat<TcpStream> {
+    fn from(stream: TcpStream) -> Compat<TcpStream> {
+        let socket = stream.inner.clone();
+        Compat::with_socket(stream, socket)
+    }
+}
+
+impl From<UnixStream> for Compat<UnixStream> {
+    fn from(stream: UnixStream) -> Compat<UnixStream> {
+        let socket = stream.inner.clone();
+        Compat::with_socket(stream, socket)
+    }
+}
+
+impl<S> Compat<S> {
+    /// Wraps a stream, a [`TcpStream`] or a [`UnixStream`].
+    pub fn new(stream: S) -> Compat<S>
+    where
+        Compat<S>: From<S>,
+    {
+        Compat::from(stream)
+    }
+
+    fn with_socket(stream: S, socket: Socket) -> Compat<S> {
+        Compat {
+            stream,
+            socket,
+            read_buf: Some(Vec::with_capacity(BUF_SIZE)),
+            read_pos: 0,
+            reading: None,
+            write_buf: Some(Vec::with_capacity(BUF_SIZE)),
+            writing: None,
+        }
+    }
+
+    /// Returns the wrapped stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns the wrapped stream.
+    ///
+    /// The data read and not returned yet is lost, and a write in flight
+    /// completes in the background. Flush the stream first to wait for it.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Waits for the write in flight, resubmitting the rest of short writes.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some(op) = &mut self.writing {
+            let (res, slice) = ready!(op.poll_write(cx));
+            self.writing = None;
+
+            let end = slice.end();
+            let begin = match res {
+                Ok(0) => Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => Ok(slice.begin() + n),
+                Err(e) => Err(e),
+            };
+            let mut buf = slice.into_inner();
+
+            match begin {
+                Ok(begin) if begin < end => {
+                    let op = Op::write_at(self.socket.shared_fd(), buf.slice(begin..end), 0)?;
+                    self.writing = Some(op);
+                }
+                res => {
+                    buf.clear();
+                    self.write_buf = Some(buf);
+                    res?;
+                }
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Passes up to `remaining` bytes of the data read to `put`, reading
+    /// more once it was all returned. Nothing is passed at the end of the
+    /// stream.
+    fn poll_read_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        remaining: usize,
+        put: impl FnOnce(&[u8]),
+    ) -> Poll<io::Result<()>> {
+        loop {
+            if let Some(op) = &mut self.reading {
+                let (res, buf) = ready!(op.poll_read(cx));
+                self.reading = None;
+                self.read_buf = Some(buf);
+                self.read_pos = 0;
+
+                // A read of 0 bytes is the end of the stream
+                res?;
+                break;
+            }
+
+            let buf = self.read_buf.as_mut().expect("read buffer in flight");
+            if self.read_pos < buf.len() {
+                break;
+            }
+            if remaining == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            buf.clear();
+            let buf = self.read_buf.take().unwrap();
+            match Op::read_at_with_flags(self.socket.shared_fd(), buf, 0, 0) {
+                Ok(op) => self.reading = Some(op),
+                Err((e, buf)) => {
+                    self.read_buf = Some(buf);
+                    return Poll::Ready(Err(e));
+                }
+            }
+        }
+
+        let buf = self.read_buf.as_ref().unwrap();
+        let n = cmp::min(buf.len() - self.read_pos, remaining);
+        put(&buf[self.read_pos..self.read_pos + n]);
+        self.read_pos += n;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: Unpin> AsyncRead for Compat<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        out: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let remaining = out.remaining();
+        self.get_mut()
+            .poll_read_with(cx, remaining, |data| out.put_slice(data))
+    }
+}
+
+impl<S: Unpin> AsyncWrite for Compat<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+
+        let mut buf = this.write_buf.take().unwrap();
+        let n = cmp::min(data.len(), buf.capacity());
+        buf.extend_from_slice(&data[..n]);
+
+        let op = Op::write_at(this.socket.shared_fd(), buf.slice(..n), 0);
+        match op {
+            Ok(op) => {
+                this.writing = Some(op);
+                Poll::Ready(Ok(n))
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_drain(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+
+        syscall!(shutdown(this.socket.shared_fd().raw_fd(), libc::SHUT_WR))?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl<S: Unpin> hyper::rt::Read for Compat<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        mut out: hyper::rt::ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        let remaining = out.remaining();
+        self.get_mut()
+            .poll_read_with(cx, remaining, |data| out.put_slice(data))
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl<S: Unpin> hyper::rt::Write for Compat<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(self, cx, data)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(self, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(self, cx)
+    }
+}
+
+impl<S> fmt::Debug for Compat<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Compat")
+            .field("fd", &self.socket.shared_fd().raw_fd())
+            .field("reading", &self.reading.is_some())
+            .field("writing", &self.writing.is_some())
+            .finish()
+    }
+}
+
+/// An executor of `hyper`, spawning its tasks onto the current `tokio-uring`
+/// runtime with [`spawn`](crate::spawn).
+///
+/// Over `tokio-uring` streams, the tasks of `hyper` are `!Send`, which rules
+/// out the executors spawning onto a multi-threaded runtime.
+///
+/// # Panics
+///
+/// Executing a task panics outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// Serving HTTP/2:
+///
+/// ```no_run
+/// use std::convert::Infallible;
+///
+/// use http_body_util::Full;
+/// use hyper::body::{Bytes, Incoming};
+/// use hyper::server::conn::http2;
+/// use hyper::service::service_fn;
+/// use hyper::{Request, Response};
+/// use tokio_uring::compat::{Compat, LocalExecutor};
+/// use tokio_uring::net::TcpListener;
+///
+/// async fn hello(_: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("hello"))))
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///         loop {
+///             let (stream, _) = listener.accept().await?;
+///             let conn = http2::Builder::new(LocalExecutor)
+///                 .serve_connection(Compat::new(stream), service_fn(hello));
+///             tokio_uring::spawn(async move {
+///                 if let Err(e) = conn.await {
+///                     eprintln!("connection failed: {}", e);
+///                 }
+///             });
+///         }
+///     })
+/// }
+/// ```
+#[cfg(feature = "hyper")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalExecutor;
+
+#[cfg(feature = "hyper")]
+impl<F> hyper::rt::Executor<F> for LocalExecutor
+where
+    F: std::future::Future + 'static,
+{
+    fn execute(&self, future: F) {
+        crate::spawn(future);
+    }
+}
@@ -0,0 +1,217 @@
+//! Logging of the writes and syncs of a file, for crash-consistency tests.
+//!
+//! A [`LoggedFile`] wraps a file, forwarding its writes and syncs while
+//! appending them, in the order they completed, to a [`WriteLog`], as the
+//! `dm-log-writes` device mapper target does for a block device. A crash of
+//! the application at any point leaves the file in the state of a prefix of
+//! the log, replayed with [`WriteLog::replay`]: a checker replays each prefix
+//! onto a copy of the initial contents of the file, runs the recovery of the
+//! application on it, and checks that what was acknowledged as durable
+//! survived. This module is only available with the `test-util` feature.
+//!
+//! The log only orders the operations of the application. It does not model
+//! the page cache reordering the writes not synced yet: a crash-consistency
+//! checker treats the writes between two [`LogEntry::Sync`] entries as
+//! unordered for a more thorough search.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_uring::crash::LoggedFile;
+//! use tokio_uring::fs::File;
+//!
+//! tokio_uring::start(async {
+//!     let file = LoggedFile::new(File::create("db").await.unwrap());
+//!
+//!     let (res, _) = file.write_at(b"record".to_vec(), 0).await;
+//!     res.unwrap();
+//!     file.sync_data().await.unwrap();
+//!     let (res, _) = file.write_at(b"commit".to_vec(), 6).await;
+//!     res.unwrap();
+//!
+//!     let log = file.log().clone();
+//!     for prefix in 0..=log.len() {
+//!         let image = tempfile::tempfile().unwrap();
+//!         log.replay(prefix, &image).unwrap();
+//!         // Run the recovery of the database on `image`...
+//!     }
+//! });
+//! ```
+
+use crate::buf::IoBuf;
+use crate::fs::File;
+
+use std::cell::RefCell;
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::rc::Rc;
+
+/// A write or a sync of a [`LoggedFile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LogEntry {
+    /// The bytes written at `offset`, up to the number of bytes the write
+    /// reported as written.
+    Write {
+        /// Position of the write in the file.
+        offset: u64,
+        /// The data written.
+        data: Vec<u8>,
+    },
+
+    /// A sync which completed successfully, making the writes logged before
+    /// it durable.
+    ///
+    /// The sync is logged at the position of the log at which it started:
+    /// the writes completed while it was in flight follow it, as they may
+    /// not be durable.
+    Sync,
+}
+
+/// The sequence of [`LogEntry`]s of a [`LoggedFile`].
+///
+/// Clones share the same entries, so that a log outlives the file it was
+/// taken from.
+#[derive(Debug, Clone, Default)]
+pub struct WriteLog {
+    entries: Rc<RefCell<Vec<LogEntry>>>,
+}
+
+impl WriteLog {
+    /// Creates an empty log.
+    pub fn new() -> WriteLog {
+        WriteLog::default()
+    }
+
+    /// Returns a copy of the entries logged so far.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.borrow().clone()
+    }
+
+    /// Returns the number of entries logged.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Returns `true` if nothing was logged.
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    /// Returns the lengths of the prefixes of the log ending with a sync: a
+    /// crash after a sync completed leaves the file in the state of a prefix
+    /// at least as long as the sync point.
+    pub fn sync_points(&self) -> Vec<usize> {
+        self.entries
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| **entry == LogEntry::Sync)
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    /// Applies the writes of the first `prefix` entries to `file`, holding
+    /// the contents of the logged file before the first entry, leaving it in
+    /// the state of a crash after them.
+    ///
+    /// The writes use blocking system calls, as checkers run outside of the
+    /// runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is larger than the length of the log.
+    pub fn replay(&self, prefix: usize, file: &std::fs::File) -> io::Result<()> {
+        for entry in &self.entries.borrow()[..prefix] {
+            if let LogEntry::Write { offset, data } = entry {
+                file.write_all_at(data, *offset)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&self, entry: LogEntry) {
+        self.entries.borrow_mut().push(entry);
+    }
+}
+
+/// A file, whose writes and syncs are appended to a [`WriteLog`].
+///
+/// The file is written with [`write_at`](LoggedFile::write_at), and synced
+/// with [`sync_all`](LoggedFile::sync_all) and
+/// [`sync_data`](LoggedFile::sync_data). The operations of the underlying
+/// file, returned by [`as_file`](LoggedFile::as_file), are not logged.
+#[derive(Debug)]
+pub struct LoggedFile {
+    file: File,
+    log: WriteLog,
+}
+
+impl LoggedFile {
+    /// Wraps `file`, logging to a new log.
+    pub fn new(file: File) -> LoggedFile {
+        LoggedFile::with_log(file, WriteLog::new())
+    }
+
+    /// Wraps `file`, appending to `log`.
+    pub fn with_log(file: File, log: WriteLog) -> LoggedFile {
+        LoggedFile { file, log }
+    }
+
+    /// Returns the log.
+    pub fn log(&self) -> &WriteLog {
+        &self.log
+    }
+
+    /// Writes some data of the buffer to the file at `pos`, logging the
+    /// bytes written. A failed write is not logged.
+    pub async fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.file.write_at(buf, pos).await;
+
+        if let Ok(n) = res {
+            if n > 0 {
+                self.log.push(LogEntry::Write {
+                    offset: pos,
+                    data: crate::buf::deref(&buf)[..n].to_vec(),
+                });
+            }
+        }
+        (res, buf)
+    }
+
+    /// Syncs the data and the metadata of the file, logging a sync once it
+    /// succeeded.
+    ///
+    /// See [`File::sync_all`].
+    pub async fn sync_all(&self) -> io::Result<()> {
+        let start = self.log.len();
+        self.file.sync_all().await?;
+        self.log_sync(start);
+        Ok(())
+    }
+
+    /// Syncs the data of the file, logging a sync once it succeeded.
+    ///
+    /// See [`File::sync_data`].
+    pub async fn sync_data(&self) -> io::Result<()> {
+        let start = self.log.len();
+        self.file.sync_data().await?;
+        self.log_sync(start);
+        Ok(())
+    }
+
+    /// Returns the underlying file.
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Returns the underlying file.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+
+    /// Logs a sync, at the position of the log `start` at which it started.
+    fn log_sync(&self, start: usize) {
+        self.log.entries.borrow_mut().insert(start, LogEntry::Sync);
+    }
+}
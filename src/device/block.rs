@@ -0,0 +1,379 @@
+use crate::fs::{File, OpenOptions};
+use crate::runtime::spawn_blocking;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Deref;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::Path;
+
+/// `_IOR(0x12, 114, size_t)`
+const BLKGETSIZE64: libc::c_ulong = 0x8008_1272;
+
+/// `_IO(0x12, 119)`
+const BLKDISCARD: libc::c_ulong = 0x1277;
+
+/// `_IOWR(0x12, 130, struct blk_zone_report)`
+const BLKREPORTZONE: libc::c_ulong = 0xc010_1282;
+
+/// `_IOW(0x12, 131, struct blk_zone_range)`
+const BLKRESETZONE: libc::c_ulong = 0x4010_1283;
+
+/// `_IOR(0x12, 132, __u32)`
+const BLKGETZONESZ: libc::c_ulong = 0x8004_1284;
+
+/// `_IOR(0x12, 133, __u32)`
+const BLKGETNRZONES: libc::c_ulong = 0x8004_1285;
+
+/// `BLK_ZONE_REP_CAPACITY`, set when the zones report their capacity
+const BLK_ZONE_REP_CAPACITY: u32 = 1;
+
+/// Size of the sectors the ioctls count in
+const SECTOR_SIZE: u64 = 512;
+
+/// Number of zones reported by each `BLKREPORTZONE`
+const ZONES_PER_REPORT: usize = 128;
+
+/// A block device, such as a disk, a partition or an NVMe namespace.
+///
+/// The device derefs to [`File`], which reads and writes it. Opened with
+/// [`open`](Block::open), the device is read and written with `O_DIRECT`,
+/// bypassing the page cache: the buffers, offsets and lengths must then be
+/// aligned to the [logical block size](Block::logical_block_size).
+///
+/// Zoned devices, such as SMR disks and ZNS namespaces, are divided into
+/// zones written sequentially, described by [`zones`](Block::zones).
+///
+/// The ioctls issuing commands to the device, such as
+/// [`discard`](Block::discard), run on the blocking thread pool, as they wait
+/// for the device.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::device::Block;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let disk = Block::open("/dev/nvme0n1").await?;
+///         println!(
+///             "{} bytes, blocks of {} bytes",
+///             disk.size()?,
+///             disk.logical_block_size()?
+///         );
+///
+///         // Trim the first MiB
+///         disk.discard(0, 1024 * 1024).await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct Block {
+    file: File,
+}
+
+/// A zone of a zoned block device, see [`Block::zones`].
+///
+/// The positions and sizes are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zone {
+    /// Position of the first byte of the zone.
+    pub start: u64,
+
+    /// Size of the zone.
+    pub len: u64,
+
+    /// Number of bytes of the zone which can be written, which may be less
+    /// than its size.
+    pub capacity: u64,
+
+    /// Position of the next write to the zone, for sequential zones.
+    pub write_pointer: u64,
+
+    /// Type of the zone.
+    pub kind: ZoneKind,
+
+    /// Condition of the zone.
+    pub condition: ZoneCondition,
+}
+
+/// The type of a [`Zone`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ZoneKind {
+    /// A zone written at any position, as a regular device.
+    Conventional,
+    /// A zone which must be written sequentially, at its write pointer.
+    SequentialRequired,
+    /// A zone best written sequentially.
+    SequentialPreferred,
+    /// A type unknown to this version of the crate.
+    Other(u8),
+}
+
+/// The condition of a [`Zone`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ZoneCondition {
+    /// A conventional zone, without write pointer.
+    NotWritePointer,
+    /// A zone not written yet.
+    Empty,
+    /// A zone opened implicitly by a write.
+    ImplicitOpen,
+    /// A zone opened explicitly.
+    ExplicitOpen,
+    /// A zone partially written, and closed.
+    Closed,
+    /// A zone which cannot be written, until reset.
+    ReadOnly,
+    /// A zone written up to its capacity.
+    Full,
+    /// A zone which cannot be read nor written.
+    Offline,
+    /// A condition unknown to this version of the crate.
+    Other(u8),
+}
+
+/// `struct blk_zone`
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct BlkZone {
+    start: u64,
+    len: u64,
+    wp: u64,
+    kind: u8,
+    cond: u8,
+    non_seq: u8,
+    reset: u8,
+    resv: [u8; 4],
+    capacity: u64,
+    reserved: [u8; 24],
+}
+
+/// `struct blk_zone_report`, followed by the zones
+#[repr(C)]
+struct BlkZoneReport {
+    sector: u64,
+    nr_zones: u32,
+    flags: u32,
+    zones: [BlkZone; ZONES_PER_REPORT],
+}
+
+impl Block {
+    /// Opens the block device at `path` for reading and writing, with
+    /// `O_DIRECT`.
+    ///
+    /// Fails with `ENOTBLK` if the file is not a block device.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<Block> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true);
+        options.custom_flags = libc::O_DIRECT;
+
+        Block::from_file(options.open(path).await?)
+    }
+
+    /// Wraps a file opened on a block device, with options of its own.
+    ///
+    /// Fails with `ENOTBLK` if the file is not a block device.
+    pub fn from_file(file: File) -> io::Result<Block> {
+        // Safety: `stat` is plain data, filled in by `fstat`
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        syscall!(fstat(file.as_raw_fd(), &mut stat))?;
+
+        if stat.st_mode & libc::S_IFMT != libc::S_IFBLK {
+            return Err(io::Error::from_raw_os_error(libc::ENOTBLK));
+        }
+        Ok(Block { file })
+    }
+
+    /// Returns the size of the device, in bytes.
+    pub fn size(&self) -> io::Result<u64> {
+        let mut size: u64 = 0;
+        syscall!(ioctl(self.file.as_raw_fd(), BLKGETSIZE64 as _, &mut size))?;
+        Ok(size)
+    }
+
+    /// Returns the logical block size of the device, the smallest unit it
+    /// addresses, to which the `O_DIRECT` reads and writes are aligned.
+    pub fn logical_block_size(&self) -> io::Result<u32> {
+        let mut size: libc::c_int = 0;
+        syscall!(ioctl(
+            self.file.as_raw_fd(),
+            libc::BLKSSZGET as _,
+            &mut size
+        ))?;
+        Ok(size as u32)
+    }
+
+    /// Returns the physical block size of the device, the unit it writes
+    /// atomically. Writes of smaller units are read, modified and written.
+    pub fn physical_block_size(&self) -> io::Result<u32> {
+        let mut size: libc::c_uint = 0;
+        syscall!(ioctl(
+            self.file.as_raw_fd(),
+            libc::BLKPBSZGET as _,
+            &mut size
+        ))?;
+        Ok(size)
+    }
+
+    /// Discards `len` bytes of the device from `offset`, see `BLKDISCARD`:
+    /// the device may reclaim the blocks, such as an SSD trimming them, and
+    /// the data of the range is lost.
+    ///
+    /// The range must be aligned to the logical block size. Fails with
+    /// `EOPNOTSUPP` if the device does not support discarding.
+    pub async fn discard(&self, offset: u64, len: u64) -> io::Result<()> {
+        let range = [offset, len];
+        self.ioctl_blocking(move |fd| syscall!(ioctl(fd, BLKDISCARD as _, &range)).map(drop))
+            .await
+    }
+
+    /// Returns the size of the zones of the device in bytes, or `None` if the
+    /// device is not zoned.
+    pub fn zone_size(&self) -> io::Result<Option<u64>> {
+        let mut sectors: u32 = 0;
+        syscall!(ioctl(
+            self.file.as_raw_fd(),
+            BLKGETZONESZ as _,
+            &mut sectors
+        ))?;
+        Ok(Some(sectors as u64 * SECTOR_SIZE).filter(|&size| size > 0))
+    }
+
+    /// Returns the number of zones of the device, 0 if it is not zoned.
+    pub fn zone_count(&self) -> io::Result<u32> {
+        let mut count: u32 = 0;
+        syscall!(ioctl(self.file.as_raw_fd(), BLKGETNRZONES as _, &mut count))?;
+        Ok(count)
+    }
+
+    /// Reports up to `max` zones of the device, starting with the zone
+    /// holding `offset`, see `BLKREPORTZONE`.
+    ///
+    /// Fails with `ENOTTY` if the device is not zoned.
+    pub async fn zones(&self, offset: u64, max: usize) -> io::Result<Vec<Zone>> {
+        self.ioctl_blocking(move |fd| {
+            let mut zones = Vec::new();
+            let mut sector = offset / SECTOR_SIZE;
+
+            // Safety: the report is plain data
+            let mut report: Box<BlkZoneReport> = Box::new(unsafe { std::mem::zeroed() });
+            while zones.len() < max {
+                report.sector = sector;
+                report.nr_zones = (max - zones.len()).min(ZONES_PER_REPORT) as u32;
+                syscall!(ioctl(fd, BLKREPORTZONE as _, &mut *report))?;
+
+                let reported = &report.zones[..report.nr_zones as usize];
+                let last = match reported.last() {
+                    Some(last) => last,
+                    // Past the last zone
+                    None => break,
+                };
+                sector = last.start + last.len;
+
+                let capacity = report.flags & BLK_ZONE_REP_CAPACITY != 0;
+                zones.extend(reported.iter().map(|zone| Zone::from_raw(zone, capacity)));
+            }
+
+            Ok(zones)
+        })
+        .await
+    }
+
+    /// Resets the write pointers of the zones in the `len` bytes from
+    /// `offset`, which must be aligned to zones, see `BLKRESETZONE`. The
+    /// data of the zones is lost.
+    pub async fn reset_zones(&self, offset: u64, len: u64) -> io::Result<()> {
+        let range = [offset / SECTOR_SIZE, len / SECTOR_SIZE];
+        self.ioctl_blocking(move |fd| syscall!(ioctl(fd, BLKRESETZONE as _, &range)).map(drop))
+            .await
+    }
+
+    /// Returns the underlying file.
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Converts into the underlying file.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+
+    /// Closes the device.
+    ///
+    /// See [`File::close`].
+    pub async fn close(self) -> io::Result<()> {
+        self.file.close().await
+    }
+
+    /// Runs `f` on the blocking thread pool, with a duplicate of the file
+    /// descriptor of the device.
+    async fn ioctl_blocking<F, T>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(RawFd) -> io::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        // The thread may outlive the `Block` if the future is dropped, so it
+        // gets its own descriptor
+        let fd = syscall!(fcntl(self.file.as_raw_fd(), libc::F_DUPFD_CLOEXEC, 0))?;
+        // Safety: the duplicate is owned by the file
+        let file = unsafe { fs::File::from_raw_fd(fd) };
+
+        spawn_blocking(move || f(file.as_raw_fd())).await
+    }
+}
+
+impl Zone {
+    fn from_raw(zone: &BlkZone, has_capacity: bool) -> Zone {
+        Zone {
+            start: zone.start * SECTOR_SIZE,
+            len: zone.len * SECTOR_SIZE,
+            capacity: if has_capacity {
+                zone.capacity
+            } else {
+                zone.len
+            } * SECTOR_SIZE,
+            write_pointer: zone.wp * SECTOR_SIZE,
+            kind: match zone.kind {
+                1 => ZoneKind::Conventional,
+                2 => ZoneKind::SequentialRequired,
+                3 => ZoneKind::SequentialPreferred,
+                kind => ZoneKind::Other(kind),
+            },
+            condition: match zone.cond {
+                0x0 => ZoneCondition::NotWritePointer,
+                0x1 => ZoneCondition::Empty,
+                0x2 => ZoneCondition::ImplicitOpen,
+                0x3 => ZoneCondition::ExplicitOpen,
+                0x4 => ZoneCondition::Closed,
+                0xd => ZoneCondition::ReadOnly,
+                0xe => ZoneCondition::Full,
+                0xf => ZoneCondition::Offline,
+                cond => ZoneCondition::Other(cond),
+            },
+        }
+    }
+}
+
+impl Deref for Block {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        &self.file
+    }
+}
+
+impl AsRawFd for Block {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl fmt::Debug for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Block").field("file", &self.file).finish()
+    }
+}
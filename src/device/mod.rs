@@ -0,0 +1,13 @@
+//! Network, character and block device operations.
+
+mod block;
+pub use block::{Block, Zone, ZoneCondition, ZoneKind};
+
+mod serial;
+pub use serial::Serial;
+
+mod tun;
+pub use tun::{Tun, TunOptions};
+
+mod zoned;
+pub use zoned::ZonedFile;
@@ -0,0 +1,202 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{self, SharedFd};
+
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+use std::path::Path;
+
+/// A serial port, terminal or other character device.
+///
+/// Character devices such as ttys do not support non-blocking reads and
+/// writes through `io-uring`. `Serial` opens them in non-blocking mode, and
+/// waits for the device to become ready with a poll operation whenever a
+/// read or write would block.
+///
+/// Terminal attributes are configured with [`set_raw`](Serial::set_raw),
+/// [`set_baud_rate`](Serial::set_baud_rate) or, for full control,
+/// [`termios`](Serial::termios) and [`set_termios`](Serial::set_termios).
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::device::Serial;
+///
+/// fn main() -> std::io::Result<()> {
+///     let port = Serial::open("/dev/ttyUSB0")?;
+///     port.set_raw()?;
+///     port.set_baud_rate(115_200)?;
+///
+///     tokio_uring::start(async {
+///         let (res, _) = port.write(b"AT\r\n".as_slice()).await;
+///         res?;
+///
+///         let (res, buf) = port.read(vec![0; 64]).await;
+///         let n = res?;
+///         println!("{:?}", &buf[..n]);
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct Serial {
+    /// Open file descriptor, in non-blocking mode
+    fd: SharedFd,
+}
+
+impl Serial {
+    /// Opens the character device at `path` for reading and writing.
+    ///
+    /// The device does not become the controlling terminal of the process.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Serial> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC | libc::O_NOCTTY | libc::O_NONBLOCK)
+            .open(path)?;
+
+        Ok(Serial {
+            fd: SharedFd::new(file.into_raw_fd()),
+        })
+    }
+
+    /// Adopts an already opened character device, such as one end of a
+    /// pseudoterminal, switching it to non-blocking mode.
+    pub fn from_std(file: std::fs::File) -> io::Result<Serial> {
+        let fd = file.as_raw_fd();
+        let flags = syscall!(fcntl(fd, libc::F_GETFL))?;
+        syscall!(fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+
+        Ok(Serial {
+            fd: SharedFd::new(file.into_raw_fd()),
+        })
+    }
+
+    /// Read some data from the device into the buffer, returning the original
+    /// buffer and quantity of data read.
+    ///
+    /// Waits until some data is available.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        driver::read_ready(&self.fd, buf).await
+    }
+
+    /// Write some data to the device from the buffer, returning the original
+    /// buffer and quantity of data written.
+    ///
+    /// Waits until the device accepts some data.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        driver::write_ready(&self.fd, buf).await
+    }
+
+    /// Returns the terminal attributes of the device.
+    pub fn termios(&self) -> io::Result<libc::termios> {
+        let mut termios = unsafe { std::mem::zeroed() };
+        syscall!(tcgetattr(self.fd.raw_fd(), &mut termios))?;
+        Ok(termios)
+    }
+
+    /// Sets the terminal attributes of the device, taking effect immediately.
+    pub fn set_termios(&self, termios: &libc::termios) -> io::Result<()> {
+        syscall!(tcsetattr(self.fd.raw_fd(), libc::TCSANOW, termios))?;
+        Ok(())
+    }
+
+    /// Puts the device in raw mode: input is available byte by byte, and no
+    /// characters are translated or interpreted, as with `cfmakeraw(3)`.
+    pub fn set_raw(&self) -> io::Result<()> {
+        let mut termios = self.termios()?;
+        unsafe { libc::cfmakeraw(&mut termios) };
+        self.set_termios(&termios)
+    }
+
+    /// Returns the output baud rate of the device.
+    pub fn baud_rate(&self) -> io::Result<u32> {
+        let termios = self.termios()?;
+        let speed = unsafe { libc::cfgetospeed(&termios) };
+
+        BAUD_RATES
+            .iter()
+            .find(|&&(_, s)| s == speed)
+            .map(|&(rate, _)| rate)
+            .ok_or_else(|| io::Error::other("unknown baud rate"))
+    }
+
+    /// Sets the input and output baud rate of the device.
+    ///
+    /// Returns an [`InvalidInput`](io::ErrorKind::InvalidInput) error if the
+    /// rate is not one of the standard rates, such as 9600 or 115200.
+    pub fn set_baud_rate(&self, rate: u32) -> io::Result<()> {
+        let speed = BAUD_RATES
+            .iter()
+            .find(|&&(r, _)| r == rate)
+            .map(|&(_, speed)| speed)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "unsupported baud rate"))?;
+
+        let mut termios = self.termios()?;
+        syscall!(cfsetspeed(&mut termios, speed))?;
+        self.set_termios(&termios)
+    }
+
+    /// Closes the device.
+    ///
+    /// The method completes once the close operation has completed,
+    /// guaranteeing that resources associated with the device have been
+    /// released.
+    ///
+    /// If `close` is not called before dropping the device, the device is
+    /// closed in the background, but there is no guarantee as to **when** the
+    /// close operation will complete.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await;
+        Ok(())
+    }
+}
+
+impl AsRawFd for Serial {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl std::fmt::Debug for Serial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Serial")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
+
+/// Standard baud rates and their `B*` speed constant.
+const BAUD_RATES: &[(u32, libc::speed_t)] = &[
+    (50, libc::B50),
+    (75, libc::B75),
+    (110, libc::B110),
+    (134, libc::B134),
+    (150, libc::B150),
+    (200, libc::B200),
+    (300, libc::B300),
+    (600, libc::B600),
+    (1200, libc::B1200),
+    (1800, libc::B1800),
+    (2400, libc::B2400),
+    (4800, libc::B4800),
+    (9600, libc::B9600),
+    (19200, libc::B19200),
+    (38400, libc::B38400),
+    (57600, libc::B57600),
+    (115_200, libc::B115200),
+    (230_400, libc::B230400),
+    (460_800, libc::B460800),
+    (500_000, libc::B500000),
+    (576_000, libc::B576000),
+    (921_600, libc::B921600),
+    (1_000_000, libc::B1000000),
+    (1_152_000, libc::B1152000),
+    (1_500_000, libc::B1500000),
+    (2_000_000, libc::B2000000),
+    (2_500_000, libc::B2500000),
+    (3_000_000, libc::B3000000),
+    (3_500_000, libc::B3500000),
+    (4_000_000, libc::B4000000),
+];
@@ -0,0 +1,322 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{room, Op, SharedFd};
+
+use std::ffi::CStr;
+use std::fs::OpenOptions;
+use std::io;
+use std::net::Ipv4Addr;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+
+/// `_IOW('T', 202, int)`
+const TUNSETIFF: libc::c_ulong = 0x4004_54ca;
+
+/// A tun or tap virtual network device.
+///
+/// A tun device exchanges IP packets with the kernel network stack, while a
+/// tap device exchanges Ethernet frames. Each [`read`](Tun::read) returns a
+/// single packet routed to the device, and each [`write`](Tun::write) injects
+/// a single packet into the network stack.
+///
+/// The device is removed once the `Tun` is closed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::device::TunOptions;
+/// use std::net::Ipv4Addr;
+///
+/// fn main() -> std::io::Result<()> {
+///     let tun = TunOptions::new()
+///         .address(Ipv4Addr::new(10, 0, 0, 1))
+///         .netmask(Ipv4Addr::new(255, 255, 255, 0))
+///         .up(true)
+///         .open("tun%d")?;
+///
+///     println!("created {}", tun.name());
+///
+///     tokio_uring::start(async {
+///         loop {
+///             let (res, buf) = tun.read(vec![0; 1500]).await;
+///             let n = res?;
+///             println!("packet: {:?}", &buf[..n]);
+///         }
+///     })
+/// }
+/// ```
+pub struct Tun {
+    /// Open file descriptor
+    fd: SharedFd,
+
+    /// Name of the interface
+    name: String,
+}
+
+/// Options and flags which can be used to configure how a [`Tun`] device is
+/// created.
+///
+/// By default, a tun device without packet information is created, and the
+/// interface is left down.
+#[derive(Debug, Clone)]
+pub struct TunOptions {
+    tap: bool,
+    packet_info: bool,
+    up: bool,
+    mtu: Option<u32>,
+    address: Option<Ipv4Addr>,
+    netmask: Option<Ipv4Addr>,
+}
+
+impl Tun {
+    /// Creates a tun device with the given name, and brings the interface up.
+    ///
+    /// See the [`TunOptions::open`] method for more details.
+    pub fn open(name: &str) -> io::Result<Tun> {
+        TunOptions::new().up(true).open(name)
+    }
+
+    /// Returns the name of the interface, as assigned by the kernel.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Read a single packet from the device into the buffer, returning the
+    /// original buffer and quantity of data read.
+    ///
+    /// The buffer should fit the MTU of the interface, and the packet
+    /// information header if enabled, otherwise the packet is truncated.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::read_at(&self.fd, buf, 0).unwrap();
+        op.read().await
+    }
+
+    /// Write a single packet to the device from the buffer, returning the
+    /// original buffer and quantity of data written.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::write_at(&self.fd, buf, 0).unwrap();
+        op.write().await
+    }
+
+    /// Closes the device, removing the interface.
+    ///
+    /// The method completes once the close operation has completed,
+    /// guaranteeing that resources associated with the device have been
+    /// released.
+    ///
+    /// If `close` is not called before dropping the device, the device is
+    /// closed in the background, but there is no guarantee as to **when** the
+    /// close operation will complete.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await;
+        Ok(())
+    }
+}
+
+impl AsRawFd for Tun {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl std::fmt::Debug for Tun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tun")
+            .field("fd", &self.fd.raw_fd())
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl TunOptions {
+    /// Creates a blank new set of options ready for configuration.
+    pub fn new() -> TunOptions {
+        TunOptions {
+            tap: false,
+            packet_info: false,
+            up: false,
+            mtu: None,
+            address: None,
+            netmask: None,
+        }
+    }
+
+    /// Sets the option to create a tap device, exchanging Ethernet frames
+    /// instead of IP packets.
+    pub fn tap(&mut self, tap: bool) -> &mut TunOptions {
+        self.tap = tap;
+        self
+    }
+
+    /// Sets the option to prefix each packet with a 4 byte header, holding
+    /// flags and the protocol of the packet.
+    pub fn packet_info(&mut self, packet_info: bool) -> &mut TunOptions {
+        self.packet_info = packet_info;
+        self
+    }
+
+    /// Sets the option to bring the interface up once created.
+    pub fn up(&mut self, up: bool) -> &mut TunOptions {
+        self.up = up;
+        self
+    }
+
+    /// Sets the MTU of the interface.
+    pub fn mtu(&mut self, mtu: u32) -> &mut TunOptions {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    /// Sets the IPv4 address of the interface.
+    pub fn address(&mut self, address: Ipv4Addr) -> &mut TunOptions {
+        self.address = Some(address);
+        self
+    }
+
+    /// Sets the IPv4 netmask of the interface.
+    pub fn netmask(&mut self, netmask: Ipv4Addr) -> &mut TunOptions {
+        self.netmask = Some(netmask);
+        self
+    }
+
+    /// Creates the device with the options specified by `self`, and
+    /// configures the interface.
+    ///
+    /// The name may contain a `%d`, which the kernel replaces with the first
+    /// free number, and may be empty to let the kernel pick a name. If a
+    /// persistent device with the given name exists, it is attached to.
+    ///
+    /// # Errors
+    ///
+    /// Creating a device requires the `CAP_NET_ADMIN` capability. Errors are
+    /// also returned if the name is longer than 15 bytes, or if configuring
+    /// the interface fails.
+    pub fn open(&self, name: &str) -> io::Result<Tun> {
+        let mut req = IfReq::new(name)?;
+        req.data.flags = if self.tap {
+            libc::IFF_TAP
+        } else {
+            libc::IFF_TUN
+        } as libc::c_short;
+        if !self.packet_info {
+            unsafe { req.data.flags |= libc::IFF_NO_PI as libc::c_short };
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open("/dev/net/tun")?;
+
+        syscall!(ioctl(file.as_raw_fd(), TUNSETIFF as _, &mut req))?;
+
+        // The kernel wrote back the assigned name
+        let name = req.name()?;
+        self.configure(&name)?;
+
+        Ok(Tun {
+            fd: SharedFd::new(file.into_raw_fd()),
+            name,
+        })
+    }
+
+    fn configure(&self, name: &str) -> io::Result<()> {
+        // Interfaces are configured through any socket
+        let socket = socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::DGRAM, None)?;
+        let fd = socket.as_raw_fd();
+
+        if let Some(mtu) = self.mtu {
+            let mut req = IfReq::new(name)?;
+            req.data.mtu = mtu as libc::c_int;
+            syscall!(ioctl(fd, libc::SIOCSIFMTU as _, &mut req))?;
+        }
+
+        if let Some(address) = self.address {
+            let mut req = IfReq::new(name)?;
+            req.data.addr = sockaddr_in(address);
+            syscall!(ioctl(fd, libc::SIOCSIFADDR as _, &mut req))?;
+        }
+
+        if let Some(netmask) = self.netmask {
+            let mut req = IfReq::new(name)?;
+            req.data.addr = sockaddr_in(netmask);
+            syscall!(ioctl(fd, libc::SIOCSIFNETMASK as _, &mut req))?;
+        }
+
+        if self.up {
+            let mut req = IfReq::new(name)?;
+            syscall!(ioctl(fd, libc::SIOCGIFFLAGS as _, &mut req))?;
+            unsafe { req.data.flags |= libc::IFF_UP as libc::c_short };
+            syscall!(ioctl(fd, libc::SIOCSIFFLAGS as _, &mut req))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TunOptions {
+    fn default() -> TunOptions {
+        TunOptions::new()
+    }
+}
+
+/// `struct ifreq`
+#[repr(C)]
+struct IfReq {
+    name: [u8; libc::IFNAMSIZ],
+    data: IfReqData,
+}
+
+#[repr(C)]
+union IfReqData {
+    flags: libc::c_short,
+    mtu: libc::c_int,
+    addr: libc::sockaddr_in,
+    // Size of the kernel union
+    _pad: [u8; 24],
+}
+
+impl IfReq {
+    fn new(name: &str) -> io::Result<IfReq> {
+        // The name must be null-terminated
+        if name.len() >= libc::IFNAMSIZ || name.as_bytes().contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid interface name",
+            ));
+        }
+
+        let mut req = IfReq {
+            name: [0; libc::IFNAMSIZ],
+            data: IfReqData { _pad: [0; 24] },
+        };
+        req.name[..name.len()].copy_from_slice(name.as_bytes());
+        Ok(req)
+    }
+
+    fn name(&self) -> io::Result<String> {
+        let name = CStr::from_bytes_until_nul(&self.name)
+            .map_err(|_| io::Error::other("interface name is not null-terminated"))?;
+        name.to_str()
+            .map(str::to_owned)
+            .map_err(|_| io::Error::other("interface name is not UTF-8"))
+    }
+}
+
+fn sockaddr_in(addr: Ipv4Addr) -> libc::sockaddr_in {
+    libc::sockaddr_in {
+        sin_family: libc::AF_INET as libc::sa_family_t,
+        sin_port: 0,
+        sin_addr: libc::in_addr {
+            s_addr: u32::from_ne_bytes(addr.octets()),
+        },
+        sin_zero: [0; 8],
+    }
+}
@@ -0,0 +1,212 @@
+use crate::buf::IoBuf;
+use crate::device::{Block, Zone, ZoneCondition, ZoneKind};
+use crate::sync::Semaphore;
+
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// A zoned block device, appended to zone by zone.
+///
+/// The zones of a zoned device, such as an SMR disk or a ZNS namespace, must
+/// be written sequentially, at their write pointer. [`append`] writes a
+/// buffer to the end of a zone, and returns the position it was written at,
+/// which the application records to read the data back, as with the zone
+/// append command of ZNS devices.
+///
+/// Linux only exposes the zone append command to applications through NVMe
+/// passthrough. The appends are instead written at the write pointer of their
+/// zone, tracked by the `ZonedFile`: the appends to a zone are written one
+/// at a time, in the order they were called, while the appends to different
+/// zones are in flight concurrently. The device is not to be written by
+/// other means meanwhile.
+///
+/// [`append`]: ZonedFile::append
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::buf::FixedBufRegistry;
+/// use tokio_uring::device::ZonedFile;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let dev = ZonedFile::open("/dev/nvme0n2").await?;
+///
+///         // A buffer of one block, aligned for `O_DIRECT`
+///         let registry = FixedBufRegistry::aligned(1, 4096, 4096);
+///         let mut buf = registry.check_out(0).unwrap();
+///         buf.extend_from_slice(&[0; 4096]);
+///
+///         let (res, _) = dev.append(1, buf).await;
+///         println!("written at {}", res?);
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct ZonedFile {
+    block: Block,
+    zones: Vec<ZoneState>,
+}
+
+struct ZoneState {
+    zone: Cell<Zone>,
+
+    /// Held while appending to the zone
+    writing: Semaphore,
+}
+
+impl ZonedFile {
+    /// Opens the zoned block device at `path`, with [`Block::open`].
+    ///
+    /// Fails with `ENOTTY` if the device is not zoned.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<ZonedFile> {
+        ZonedFile::from_block(Block::open(path).await?).await
+    }
+
+    /// Wraps a zoned block device, reading the state of its zones.
+    ///
+    /// Fails with `ENOTTY` if the device is not zoned.
+    pub async fn from_block(block: Block) -> io::Result<ZonedFile> {
+        let count = block.zone_count()?;
+        if count == 0 {
+            return Err(io::Error::from_raw_os_error(libc::ENOTTY));
+        }
+
+        let zones = block
+            .zones(0, count as usize)
+            .await?
+            .into_iter()
+            .map(|zone| ZoneState {
+                zone: Cell::new(zone),
+                writing: Semaphore::new(1),
+            })
+            .collect();
+
+        Ok(ZonedFile { block, zones })
+    }
+
+    /// Appends the buffer to zone `zone`, by index, returning the position
+    /// it was written at.
+    ///
+    /// The whole buffer is written, or none of it is appended: fails with
+    /// `ENOSPC` if it does not fit in the remaining capacity of the zone.
+    /// With `O_DIRECT`, its length must be a multiple of the logical block
+    /// size. After a failed write, the write pointer of the zone is read
+    /// again from the device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `zone` is not the index of a zone of the device.
+    pub async fn append<T: IoBuf>(&self, zone: usize, buf: T) -> crate::BufResult<u64, T> {
+        let state = &self.zones[zone];
+        let _permit = state.writing.acquire().await.unwrap();
+
+        let current = state.zone.get();
+        let len = buf.bytes_init();
+        let pos = current.write_pointer;
+        if current.kind == ZoneKind::Conventional
+            || pos + len as u64 > current.start + current.capacity
+        {
+            return (Err(io::Error::from_raw_os_error(libc::ENOSPC)), buf);
+        }
+
+        let mut buf = buf;
+        let mut written = 0;
+        while written < len {
+            let (res, slice) = self
+                .block
+                .write_at(buf.slice(written..len), pos + written as u64)
+                .await;
+            buf = slice.into_inner();
+
+            match res {
+                Ok(0) => {
+                    let _ = self.refresh(zone).await;
+                    let err = io::Error::new(io::ErrorKind::WriteZero, "failed to append");
+                    return (Err(err), buf);
+                }
+                Ok(n) => written += n,
+                Err(e) => {
+                    let _ = self.refresh(zone).await;
+                    return (Err(e), buf);
+                }
+            }
+        }
+
+        let mut current = current;
+        current.write_pointer = pos + len as u64;
+        if current.write_pointer == current.start + current.capacity {
+            current.condition = ZoneCondition::Full;
+        }
+        state.zone.set(current);
+
+        (Ok(pos), buf)
+    }
+
+    /// Returns the zones of the device, as tracked by the appends.
+    pub fn zones(&self) -> Vec<Zone> {
+        self.zones.iter().map(|state| state.zone.get()).collect()
+    }
+
+    /// Returns zone `zone`, by index, as tracked by the appends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `zone` is not the index of a zone of the device.
+    pub fn zone(&self, zone: usize) -> Zone {
+        self.zones[zone].zone.get()
+    }
+
+    /// Resets the write pointer of zone `zone`, by index, discarding its
+    /// data, see [`Block::reset_zones`]. Waits for the appends to the zone in
+    /// flight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `zone` is not the index of a zone of the device.
+    pub async fn reset(&self, zone: usize) -> io::Result<()> {
+        let state = &self.zones[zone];
+        let _permit = state.writing.acquire().await.unwrap();
+
+        let current = state.zone.get();
+        let res = self.block.reset_zones(current.start, current.len).await;
+        let refreshed = self.refresh(zone).await;
+        res.and(refreshed)
+    }
+
+    /// Returns the underlying block device.
+    pub fn as_block(&self) -> &Block {
+        &self.block
+    }
+
+    /// Closes the device.
+    ///
+    /// See [`File::close`](crate::fs::File::close).
+    pub async fn close(self) -> io::Result<()> {
+        self.block.close().await
+    }
+
+    /// Reads the state of zone `zone` from the device again, after a failed
+    /// write or a reset.
+    async fn refresh(&self, zone: usize) -> io::Result<()> {
+        let state = &self.zones[zone];
+        let start = state.zone.get().start;
+
+        match self.block.zones(start, 1).await?.first() {
+            Some(&current) => state.zone.set(current),
+            None => return Err(io::Error::from_raw_os_error(libc::EIO)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ZonedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZonedFile")
+            .field("block", &self.block)
+            .field("zones", &self.zones.len())
+            .finish()
+    }
+}
@@ -0,0 +1,338 @@
+//! Checks of the `io_uring` support of the kernel.
+//!
+//! Kernels differ in the operations they support, and some deployments
+//! restrict `io_uring` further, with `seccomp(2)` profiles or sysctls such
+//! as `kernel.io_uring_disabled`. [`self_test`] exercises the operations the
+//! runtime relies on, and returns a [`SelfTestReport`] of what works, for
+//! services to verify the support of the host at startup, and fail early
+//! with a clear message rather than on their first request.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! tokio_uring::start(async {
+//!     let report = tokio_uring::diagnostics::self_test().await;
+//!     if !report.passed() {
+//!         eprintln!("io_uring is not fully supported:\n{}", report);
+//!         std::process::exit(1);
+//!     }
+//! });
+//! ```
+
+use crate::buf::FixedBufRegistry;
+use crate::driver::{self, Op};
+use crate::fs::NamedTempFile;
+use crate::net::{TcpListener, TcpStream};
+
+use std::ffi::CStr;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Longest time a check may take before it is failed, so that a hung
+/// operation does not hang the startup of the application.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Payload of the round trips.
+const PAYLOAD: &[u8] = b"tokio-uring self-test";
+
+/// Runs quick checks of the operations of the runtime: probing the opcodes
+/// supported by the ring, a no-op, a timeout, writes, reads and syncs of a
+/// temporary file, a TCP connection on the loopback interface, and reads and
+/// writes of registered buffers.
+///
+/// The checks run one after the other, each failing after 5 seconds. The
+/// temporary file is created in [`std::env::temp_dir`], and deleted.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub async fn self_test() -> SelfTestReport {
+    let mut report = SelfTestReport {
+        kernel_release: kernel_release(),
+        opcodes: Vec::new(),
+        checks: Vec::new(),
+    };
+
+    let start = Instant::now();
+    let outcome = match driver::supported_opcodes() {
+        Ok(opcodes) => {
+            report.opcodes = opcodes;
+            Outcome::Passed
+        }
+        Err(e) => Outcome::from(e),
+    };
+    report.checks.push(Check {
+        name: "probe",
+        outcome,
+        elapsed: start.elapsed(),
+    });
+
+    report.run("nop", check_nop()).await;
+    report.run("timeout", check_timeout()).await;
+    report.run("file", check_file()).await;
+    report.run("tcp", check_tcp()).await;
+    report.run("registered buffers", check_fixed_bufs()).await;
+
+    report
+}
+
+/// The results of [`self_test`].
+///
+/// Its `Display` implementation prints the kernel release, and the outcome
+/// of each check on a line.
+#[derive(Debug, Clone)]
+pub struct SelfTestReport {
+    kernel_release: String,
+    opcodes: Vec<u8>,
+    checks: Vec<Check>,
+}
+
+/// A check of [`self_test`], with its outcome.
+#[derive(Debug, Clone)]
+pub struct Check {
+    name: &'static str,
+    outcome: Outcome,
+    elapsed: Duration,
+}
+
+/// The outcome of a [`Check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Outcome {
+    /// The check passed.
+    Passed,
+
+    /// The check failed, with the error number of the failure if any, and a
+    /// description.
+    Failed {
+        /// Error number of the failure, if the system reported one.
+        errno: Option<i32>,
+        /// Description of the failure.
+        message: String,
+    },
+
+    /// The check did not run, for the given reason.
+    Skipped(&'static str),
+}
+
+impl SelfTestReport {
+    /// Returns `true` if no check failed.
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Returns the release of the running kernel, as `uname -r` prints it.
+    pub fn kernel_release(&self) -> &str {
+        &self.kernel_release
+    }
+
+    /// Returns the opcodes the ring supports, the `IORING_OP_*` constants of
+    /// `io_uring.h`, empty if probing failed.
+    pub fn supported_opcodes(&self) -> &[u8] {
+        &self.opcodes
+    }
+
+    /// Returns the checks, in the order they ran.
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    /// Returns the checks which failed.
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| !check.passed())
+    }
+
+    /// Runs the check `name`, recording its outcome.
+    async fn run<F: Future<Output = Outcome>>(&mut self, name: &'static str, check: F) {
+        let start = Instant::now();
+        let outcome = match crate::time::timeout(CHECK_TIMEOUT, check).await {
+            Ok(outcome) => outcome,
+            Err(_) => Outcome::Failed {
+                errno: None,
+                message: format!("did not complete within {:?}", CHECK_TIMEOUT),
+            },
+        };
+
+        self.checks.push(Check {
+            name,
+            outcome,
+            elapsed: start.elapsed(),
+        });
+    }
+}
+
+impl Check {
+    /// Returns the name of the check.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Returns the outcome of the check.
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+
+    /// Returns `true` unless the check failed.
+    pub fn passed(&self) -> bool {
+        !matches!(self.outcome, Outcome::Failed { .. })
+    }
+
+    /// Returns the time the check took.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl From<io::Error> for Outcome {
+    fn from(e: io::Error) -> Outcome {
+        Outcome::Failed {
+            errno: e.raw_os_error(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "kernel {}", self.kernel_release)?;
+        writeln!(f, "{} opcodes supported", self.opcodes.len())?;
+
+        for check in &self.checks {
+            write!(f, "{}: ", check.name)?;
+            match &check.outcome {
+                Outcome::Passed => writeln!(f, "ok ({:?})", check.elapsed)?,
+                Outcome::Failed { message, .. } => writeln!(f, "FAILED: {}", message)?,
+                Outcome::Skipped(reason) => writeln!(f, "skipped: {}", reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fails with `message` if `condition` does not hold.
+fn ensure(condition: bool, message: &str) -> Outcome {
+    if condition {
+        Outcome::Passed
+    } else {
+        Outcome::Failed {
+            errno: None,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Converts the result of a check to its outcome.
+fn outcome(res: io::Result<Outcome>) -> Outcome {
+    res.unwrap_or_else(Outcome::from)
+}
+
+async fn check_nop() -> Outcome {
+    outcome(async { Op::nop()?.await.result.map(|_| Outcome::Passed) }.await)
+}
+
+async fn check_timeout() -> Outcome {
+    let start = Instant::now();
+    crate::time::sleep(Duration::from_millis(1)).await;
+    ensure(
+        start.elapsed() >= Duration::from_millis(1),
+        "the timeout completed early",
+    )
+}
+
+async fn check_file() -> Outcome {
+    outcome(
+        async {
+            let file = NamedTempFile::new().await?;
+
+            let (res, _) = file.as_file().write_at(PAYLOAD, 0).await;
+            let written = res?;
+            file.as_file().sync_data().await?;
+            let (res, buf) = file.as_file().read_at(vec![0; PAYLOAD.len()], 0).await;
+            let read = res?;
+
+            file.close().await?;
+            Ok(ensure(
+                written == PAYLOAD.len() && buf[..read] == *PAYLOAD,
+                "the data read differs from the data written",
+            ))
+        }
+        .await,
+    )
+}
+
+async fn check_tcp() -> Outcome {
+    outcome(
+        async {
+            let listener = TcpListener::bind(([127, 0, 0, 1], 0).into())?;
+            let addr = socket2::SockRef::from(&listener)
+                .local_addr()?
+                .as_socket()
+                .ok_or_else(|| io::Error::from_raw_os_error(libc::EAFNOSUPPORT))?;
+
+            let accept = crate::spawn(async move { listener.accept().await });
+            let client = TcpStream::connect(addr).await?;
+            let (server, _) = accept.await.map_err(io::Error::other)??;
+
+            let (res, _) = client.write(PAYLOAD).await;
+            res?;
+            let (res, buf) = server.read(vec![0; PAYLOAD.len()]).await;
+            let read = res?;
+
+            Ok(ensure(
+                buf[..read] == PAYLOAD[..read] && read > 0,
+                "the data received differs from the data sent",
+            ))
+        }
+        .await,
+    )
+}
+
+async fn check_fixed_bufs() -> Outcome {
+    let registry = FixedBufRegistry::new(vec![Vec::with_capacity(4096)]);
+    match registry.register() {
+        Ok(()) => {}
+        // The application registered buffers of its own
+        Err(e) if e.raw_os_error() == Some(libc::EBUSY) => {
+            return Outcome::Skipped("buffers are already registered");
+        }
+        Err(e) => return Outcome::from(e),
+    }
+
+    outcome(
+        async {
+            let file = NamedTempFile::new().await?;
+
+            let mut buf = registry.check_out(0).unwrap();
+            buf.extend_from_slice(PAYLOAD);
+            let (res, mut buf) = file.as_file().write_fixed_at(buf, 0).await;
+            let written = res?;
+
+            buf.clear();
+            let (res, buf) = file.as_file().read_fixed_at(buf, 0).await;
+            let read = res?;
+
+            file.close().await?;
+            Ok(ensure(
+                written == PAYLOAD.len() && buf[..read] == *PAYLOAD,
+                "the data read differs from the data written",
+            ))
+        }
+        .await,
+    )
+}
+
+/// Returns the release of the running kernel.
+fn kernel_release() -> String {
+    // Safety: `utsname` is plain data, filled in by `uname`
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return String::from("unknown");
+    }
+
+    // Safety: the release is NUL terminated
+    unsafe { CStr::from_ptr(uts.release.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
@@ -16,7 +16,7 @@ impl Op<Accept> {
         ));
         Op::submit_with(
             Accept {
-                fd: fd.clone(),
+                fd: fd.for_op(),
                 socketaddr,
             },
             |accept| {
@@ -0,0 +1,109 @@
+//! Validation of the SQEs in debug builds.
+//!
+//! The kernel rejects malformed SQEs with a bare `EINVAL` or `EBADF`, which
+//! does not tell which operation was wrong, or accepts them and completes
+//! them with a surprising result. The mistakes found here are bugs of the
+//! caller, and make the submission panic instead.
+
+use super::futex::{IORING_OP_FUTEX_WAIT, IORING_OP_FUTEX_WAKE};
+use super::sqe;
+use super::waitid::IORING_OP_WAITID;
+use crate::OpKind;
+
+use io_uring::{opcode, squeue};
+
+/// `IOSQE_FIXED_FILE`
+const IOSQE_FIXED_FILE: u8 = 1;
+
+/// Offset of reads and writes using the file position
+const CURRENT_POSITION: u64 = u64::MAX;
+
+/// Returns the description of the mistake if `sqe` is malformed.
+pub(crate) fn audit(sqe: &squeue::Entry) -> Result<(), String> {
+    let raw = sqe::raw(sqe);
+    let name = match name(raw.opcode) {
+        Some(name) => name,
+        None => return check_fd(raw, OpKind::from(raw.opcode).name()),
+    };
+
+    check_fd(raw, name)?;
+
+    if raw.opcode == opcode::Readv::CODE && raw.len == 0 {
+        return Err(format!(
+            "tokio-uring: {} of no buffers on fd {}",
+            name, raw.fd
+        ));
+    }
+
+    if raw.flags & IOSQE_FIXED_FILE == 0 && raw.off != 0 && raw.off != CURRENT_POSITION {
+        if let Some(kind) = non_seekable(raw.fd) {
+            return Err(format!(
+                "tokio-uring: {} at offset {} on fd {}, which is a {} and cannot seek",
+                name, raw.off, raw.fd, kind
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Fails if the fd of the SQE is closed, for example if the operation was
+/// built from the raw fd of a resource which was since dropped.
+fn check_fd(raw: &sqe::RawSqe, name: &str) -> Result<(), String> {
+    // Operations without an fd set it to -1, fixed files are indices
+    if raw.fd < 0 || raw.flags & IOSQE_FIXED_FILE != 0 {
+        return Ok(());
+    }
+
+    // Closes are submitted on drop, where panicking may abort
+    if raw.opcode == opcode::Close::CODE {
+        return Ok(());
+    }
+
+    // Futex operations hold their flags in the fd field, and waits for a
+    // process its pid
+    if matches!(
+        raw.opcode,
+        IORING_OP_FUTEX_WAIT | IORING_OP_FUTEX_WAKE | IORING_OP_WAITID
+    ) {
+        return Ok(());
+    }
+
+    if unsafe { libc::fcntl(raw.fd, libc::F_GETFD) } == -1 {
+        return Err(format!(
+            "tokio-uring: {} (opcode {}) on fd {}, which is closed",
+            name, raw.opcode, raw.fd
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the name of the reads and writes, which take a buffer and an
+/// offset.
+fn name(opcode: u8) -> Option<&'static str> {
+    let name = match opcode {
+        opcode::Read::CODE => "read",
+        opcode::ReadFixed::CODE => "read of a fixed buffer",
+        opcode::Readv::CODE => "vectored read",
+        opcode::Write::CODE => "write",
+        opcode::WriteFixed::CODE => "write of a fixed buffer",
+        opcode::Writev::CODE => "vectored write",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Returns the kind of `fd` if it is a pipe or a socket.
+fn non_seekable(fd: i32) -> Option<&'static str> {
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(fd, &mut stat) } == -1 {
+        return None;
+    }
+
+    match stat.st_mode & libc::S_IFMT {
+        libc::S_IFIFO => Some("pipe"),
+        libc::S_IFSOCK => Some("socket"),
+        _ => None,
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+/// Submit and completion timings recorded by the driver.
+///
+/// Only compiled with the `bench-internals` feature; the default build does
+/// not pay for the timestamps.
+#[derive(Default)]
+pub(crate) struct Stats {
+    /// Number of `io_uring_enter` calls made to submit operations
+    pub(crate) submits: u64,
+
+    /// Total time spent inside `io_uring_enter` when submitting
+    pub(crate) submit_time: Duration,
+
+    /// Number of operation completions processed
+    pub(crate) completions: u64,
+
+    /// Total time between pushing an SQE and processing its CQE
+    pub(crate) complete_time: Duration,
+
+    /// When each in-flight operation was pushed, indexed by slab index
+    pushed_at: Vec<Option<Instant>>,
+}
+
+impl Stats {
+    pub(crate) fn pushed(&mut self, index: usize) {
+        if self.pushed_at.len() <= index {
+            self.pushed_at.resize(index + 1, None);
+        }
+
+        self.pushed_at[index] = Some(Instant::now());
+    }
+
+    pub(crate) fn submitted(&mut self, start: Instant) {
+        self.submits += 1;
+        self.submit_time += start.elapsed();
+    }
+
+    pub(crate) fn completed(&mut self, index: usize) {
+        if let Some(pushed_at) = self.pushed_at.get_mut(index).and_then(Option::take) {
+            self.completions += 1;
+            self.complete_time += pushed_at.elapsed();
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        let pushed_at = std::mem::take(&mut self.pushed_at);
+        *self = Stats {
+            pushed_at,
+            ..Stats::default()
+        };
+    }
+}
+
+/// Access the stats of the current driver.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn with_stats<R>(f: impl FnOnce(&mut Stats) -> R) -> R {
+    super::CURRENT.with(|inner| f(&mut inner.borrow_mut().stats))
+}
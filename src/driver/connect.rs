@@ -15,7 +15,7 @@ impl Op<Connect> {
 
         Op::submit_with(
             Connect {
-                fd: fd.clone(),
+                fd: fd.for_op(),
                 socket_addr,
             },
             |connect| {
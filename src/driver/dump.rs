@@ -0,0 +1,98 @@
+use crate::driver::{op, Inner, CURRENT};
+use crate::{DriverDump, OpKind};
+
+use std::os::unix::io::AsRawFd;
+
+impl Inner {
+    /// Record the kind of an operation pushed onto the submission queue.
+    pub(super) fn set_kind(&mut self, index: usize, kind: OpKind) {
+        if self.kinds.len() <= index {
+            self.kinds.resize(index + 1, OpKind::Nop);
+        }
+        self.kinds[index] = kind;
+    }
+
+    /// Returns the kind of the operation at `index`.
+    pub(super) fn kind(&self, index: usize) -> OpKind {
+        self.kinds.get(index).copied().unwrap_or(OpKind::Nop)
+    }
+}
+
+/// Snapshot the state of the ring and of the operations of the current
+/// driver.
+pub(crate) fn dump() -> DriverDump {
+    assert!(
+        CURRENT.is_set(),
+        "`debug_dump` must be called from a `tokio-uring` runtime"
+    );
+
+    CURRENT.with(|inner| {
+        let mut inner = inner.borrow_mut();
+        let inner = &mut *inner;
+
+        let (unsubmitted, dropped) = {
+            let mut sq = inner.uring.submission();
+            sq.sync();
+            (sq.len(), sq.dropped())
+        };
+        let (unprocessed, overflow) = {
+            let mut cq = inner.uring.completion();
+            cq.sync();
+            (cq.len(), cq.overflow())
+        };
+
+        let mut in_flight = Vec::<(OpKind, usize)>::new();
+        let mut ignored = 0;
+        let mut completed = 0;
+        for (index, lifecycle) in inner.ops.0.iter() {
+            match lifecycle {
+                op::Lifecycle::Completed(..) => {
+                    completed += 1;
+                    continue;
+                }
+                op::Lifecycle::Ignored(..) => ignored += 1,
+                _ => {}
+            }
+
+            let kind = inner.kind(index);
+            match in_flight.binary_search_by_key(&kind.opcode(), |&(kind, _)| kind.opcode()) {
+                Ok(i) => in_flight[i].1 += 1,
+                Err(i) => in_flight.insert(i, (kind, 1)),
+            }
+        }
+
+        let fd = inner.uring.as_raw_fd();
+        let fdinfo = std::fs::read_to_string(format!("/proc/self/fdinfo/{}", fd)).ok();
+        let field = |name: &str| -> Option<u32> {
+            fdinfo.as_deref()?.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                if key != name {
+                    return None;
+                }
+                value.trim().parse().ok()
+            })
+        };
+
+        DriverDump {
+            fd,
+            sq_entries: inner.uring.params().sq_entries(),
+            cq_entries: inner.uring.params().cq_entries(),
+            sq_head: field("SqHead"),
+            sq_tail: field("SqTail"),
+            cq_head: field("CqHead"),
+            cq_tail: field("CqTail"),
+            unsubmitted,
+            unprocessed,
+            dropped,
+            overflow,
+            in_flight,
+            ignored,
+            completed,
+            waiting_for_room: inner.permits.num_waiters(),
+            max_ops: Some(inner.max_ops).filter(|&max| max != usize::MAX),
+            registered_bytes: inner.registered_bytes,
+            registered_files: field("UserFiles"),
+            registered_buffers: field("UserBufs"),
+        }
+    })
+}
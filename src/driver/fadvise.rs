@@ -0,0 +1,29 @@
+use crate::driver::{Op, SharedFd};
+
+use std::io;
+
+use io_uring::{opcode, types};
+
+pub(crate) struct Fadvise {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+}
+
+impl Op<Fadvise> {
+    /// Advise on the use of a range of the file, `posix_fadvise(2)`. A `len`
+    /// of 0 extends to the end of the file.
+    pub(crate) fn fadvise(
+        fd: &SharedFd,
+        offset: u64,
+        len: u32,
+        advice: libc::c_int,
+    ) -> io::Result<Op<Fadvise>> {
+        Op::submit_with(Fadvise { fd: fd.for_op() }, |_| {
+            opcode::Fadvise::new(types::Fd(fd.raw_fd()), len as _, advice)
+                .offset(offset as _)
+                .build()
+        })
+    }
+}
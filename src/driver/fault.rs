@@ -0,0 +1,147 @@
+use crate::driver::sqe;
+use crate::fault::{Action, Fault};
+
+use io_uring::{opcode, squeue, types};
+use std::collections::VecDeque;
+use std::io;
+
+/// Faults injected into the operations submitted on this driver.
+#[derive(Default)]
+pub(crate) struct Injector {
+    faults: Vec<Fault>,
+
+    /// Timeouts used to delay operations, with the position of their entry in
+    /// the submission queue. The kernel reads them when it consumes the entry,
+    /// which may happen after `intercept` returns, so they are boxed to keep
+    /// their address stable, and dropped once the entry was consumed.
+    timespecs: VecDeque<(u32, Box<types::Timespec>)>,
+
+    /// Number of submissions left to reject with `EBUSY`
+    busy_submits: usize,
+
+    /// Error the next submission fails with, once the `EBUSY` ones are done
+    submit_error: Option<i32>,
+}
+
+/// How an intercepted operation must be submitted.
+pub(crate) enum Intercept {
+    /// Complete the operation with the error, without submitting it.
+    Fail(io::Error),
+
+    /// Push this entry before the operation. It is hard linked to it.
+    Delay(squeue::Entry),
+}
+
+impl Injector {
+    pub(crate) fn push(&mut self, fault: Fault) {
+        self.faults.push(fault);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.faults.clear();
+        self.busy_submits = 0;
+        self.submit_error = None;
+    }
+
+    pub(crate) fn reject_submits(&mut self, n: usize) {
+        self.busy_submits += n;
+    }
+
+    pub(crate) fn fail_submit(&mut self, errno: i32) {
+        self.submit_error = Some(errno);
+    }
+
+    /// Returns the error the submission must fail with, if any.
+    pub(crate) fn take_submit_error(&mut self) -> Option<i32> {
+        if self.busy_submits > 0 {
+            self.busy_submits -= 1;
+            return Some(libc::EBUSY);
+        }
+
+        self.submit_error.take()
+    }
+
+    /// Drops the timeouts of the entries the kernel consumed, given the
+    /// position of the entry it consumes next.
+    pub(crate) fn release(&mut self, head: u32) {
+        while let Some((pos, _)) = self.timespecs.front() {
+            if !sqe::consumed(head, *pos) {
+                break;
+            }
+            self.timespecs.pop_front();
+        }
+    }
+
+    /// Apply the first fault matching the operation, if any. Short transfers
+    /// are applied by updating `sqe` in place. A delay is pushed at `tail`,
+    /// the position of the next entry of the submission queue.
+    pub(crate) fn intercept(&mut self, sqe: &mut squeue::Entry, tail: u32) -> Option<Intercept> {
+        let raw = sqe::raw_mut(sqe);
+        let pos = self.faults.iter().position(|f| f.matches(raw.opcode))?;
+        let fault = &mut self.faults[pos];
+
+        if fault.skip > 0 {
+            fault.skip -= 1;
+            return None;
+        }
+
+        let action = fault.action;
+
+        if let Some(remaining) = &mut fault.remaining {
+            *remaining -= 1;
+
+            if *remaining == 0 {
+                self.faults.remove(pos);
+            }
+        }
+
+        match action {
+            Action::Error(errno) => Some(Intercept::Fail(io::Error::from_raw_os_error(errno))),
+            Action::Short(len) => {
+                raw.len = raw.len.min(len);
+                None
+            }
+            Action::Delay(delay) => {
+                let timespec = Box::new(
+                    types::Timespec::new()
+                        .sec(delay.as_secs())
+                        .nsec(delay.subsec_nanos()),
+                );
+
+                // The timeout completes with -ETIME, the hard link starts the
+                // operation regardless. The CQE is ignored by the driver.
+                let timeout = opcode::Timeout::new(&*timespec)
+                    .build()
+                    .flags(squeue::Flags::IO_HARDLINK)
+                    .user_data(u64::MAX);
+
+                self.timespecs.push_back((tail, timespec));
+                Some(Intercept::Delay(timeout))
+            }
+        }
+    }
+}
+
+/// Access the fault injector of the current driver.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn with_injector<R>(f: impl FnOnce(&mut Injector) -> R) -> R {
+    super::CURRENT.with(|inner| f(&mut inner.borrow_mut().faults))
+}
+
+/// Removes the faults of the current driver, along with the timeouts of the
+/// delays the kernel consumed. The others are still to be read by the
+/// kernel, and are dropped once consumed.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn clear() {
+    super::CURRENT.with(|inner| {
+        let mut inner = inner.borrow_mut();
+        inner.trim_queued();
+        inner.faults.clear();
+    })
+}
@@ -0,0 +1,162 @@
+//! Registration of the file descriptors used most into the fixed-file table,
+//! see `Builder::auto_register_files`.
+
+use super::sqe;
+
+use io_uring::{opcode, squeue, IoUring};
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::io::RawFd;
+
+/// `IOSQE_FIXED_FILE`
+const IOSQE_FIXED_FILE: u8 = 1;
+
+/// Number of operations on a file descriptor from which it is registered
+const USES_BEFORE_REGISTRATION: u32 = 2;
+
+/// Slots of the fixed-file table, holding the file descriptors used most
+/// recently.
+pub(crate) struct FixedFiles {
+    /// File descriptor of each slot, and its last use
+    slots: Vec<Option<Slot>>,
+
+    /// Slot of each registered file descriptor
+    by_fd: HashMap<RawFd, u32>,
+
+    /// Operations on the file descriptors not registered yet
+    uses: HashMap<RawFd, u32>,
+
+    /// Incremented by each operation on a registered file descriptor
+    clock: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Slot {
+    fd: RawFd,
+
+    /// Value of the clock when last used
+    used: u64,
+
+    /// Position in the submission queue of the last entry rewritten to use
+    /// the slot, which resolves the slot once consumed by the kernel
+    pos: u32,
+}
+
+impl FixedFiles {
+    /// Registers a sparse table of `slots` files with `uring`.
+    pub(crate) fn register(uring: &IoUring, slots: u32) -> io::Result<FixedFiles> {
+        uring.submitter().register_files_sparse(slots)?;
+
+        Ok(FixedFiles {
+            slots: vec![None; slots as usize],
+            by_fd: HashMap::new(),
+            uses: HashMap::new(),
+            clock: 0,
+        })
+    }
+
+    /// Rewrites `sqe`, about to be pushed at position `pos` of the submission
+    /// queue, to use the fixed file of its file descriptor, if it is used
+    /// often enough to be registered.
+    ///
+    /// `head` is the position of the next entry the kernel consumes: the
+    /// slots used by the entries from `head` on are not evicted, as these
+    /// would run against the newly registered file.
+    pub(crate) fn fix(&mut self, uring: &IoUring, head: u32, pos: u32, sqe: &mut squeue::Entry) {
+        let raw = sqe::raw_mut(sqe);
+        if raw.fd < 0 || raw.flags & IOSQE_FIXED_FILE != 0 || !takes_fixed_file(raw.opcode) {
+            return;
+        }
+
+        let slot = match self.by_fd.get(&raw.fd) {
+            Some(&slot) => slot,
+            None => match self.insert(uring, head, raw.fd) {
+                Some(slot) => slot,
+                None => return,
+            },
+        };
+
+        self.clock += 1;
+        self.slots[slot as usize] = Some(Slot {
+            fd: raw.fd,
+            used: self.clock,
+            pos,
+        });
+        raw.fd = slot as i32;
+        raw.flags |= IOSQE_FIXED_FILE;
+    }
+
+    /// Unregisters `fd` before it is closed, as the table holds a reference
+    /// to its file, and the number may be reused for another file.
+    pub(crate) fn forget(&mut self, uring: &IoUring, fd: RawFd) {
+        self.uses.remove(&fd);
+
+        if let Some(slot) = self.by_fd.remove(&fd) {
+            self.slots[slot as usize] = None;
+            let _ = uring.submitter().register_files_update(slot, &[-1]);
+        }
+    }
+
+    /// Counts a use of `fd`, and registers it once used often enough,
+    /// evicting the file descriptor used least recently if the table is full.
+    /// The file descriptors used by entries not consumed yet are kept.
+    fn insert(&mut self, uring: &IoUring, head: u32, fd: RawFd) -> Option<u32> {
+        let uses = self.uses.entry(fd).or_insert(0);
+        *uses += 1;
+        if *uses < USES_BEFORE_REGISTRATION {
+            // Forget the file descriptors used once, from time to time
+            if self.uses.len() > 4 * self.slots.len() {
+                self.uses.clear();
+            }
+            return None;
+        }
+
+        let slot = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_none_or(|slot| sqe::consumed(head, slot.pos)))
+            .min_by_key(|(_, slot)| slot.map_or(0, |slot| slot.used))
+            .map(|(slot, _)| slot as u32)?;
+
+        // The operations in flight on the evicted file keep using it, the
+        // kernel resolved their file when it consumed their entry
+        if uring
+            .submitter()
+            .register_files_update(slot, &[fd])
+            .is_err()
+        {
+            return None;
+        }
+
+        if let Some(evicted) = self.slots[slot as usize] {
+            self.by_fd.remove(&evicted.fd);
+        }
+        self.uses.remove(&fd);
+        self.by_fd.insert(fd, slot);
+        Some(slot)
+    }
+}
+
+/// Whether the file descriptor of operations with `opcode` may be a fixed
+/// file. The file descriptors of the other operations are either not files,
+/// such as the ones of futex operations, or closed by them.
+fn takes_fixed_file(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        opcode::Read::CODE
+            | opcode::Write::CODE
+            | opcode::Readv::CODE
+            | opcode::Writev::CODE
+            | opcode::ReadFixed::CODE
+            | opcode::WriteFixed::CODE
+            | opcode::Fsync::CODE
+            | opcode::Fallocate64::CODE
+            | opcode::SyncFileRange::CODE
+            | opcode::Send::CODE
+            | opcode::Recv::CODE
+            | opcode::SendMsg::CODE
+            | opcode::RecvMsg::CODE
+            | opcode::PollAdd::CODE
+    )
+}
@@ -0,0 +1,38 @@
+//! Detection of drivers inherited across `fork(2)`.
+//!
+//! A child process shares the rings of its parent: submitting to them, or
+//! reaping their completions, would corrupt the runtimes of the parent. The
+//! child handler of `pthread_atfork(3)` bumps a generation, which drivers
+//! compare with the generation they were created in.
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+static REGISTER: Once = Once::new();
+
+/// Returns the current generation, registering the fork handler first.
+pub(crate) fn register() -> usize {
+    REGISTER.call_once(|| unsafe {
+        libc::pthread_atfork(None, None, Some(forked));
+    });
+    generation()
+}
+
+pub(crate) fn generation() -> usize {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// The error of the operations submitted to an inherited driver.
+pub(crate) fn inherited() -> io::Error {
+    io::Error::other(
+        "the tokio-uring runtime was inherited across fork(), and its ring belongs \
+         to the parent process; start a new runtime in the child process",
+    )
+}
+
+extern "C" fn forked() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
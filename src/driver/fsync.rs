@@ -1,6 +1,7 @@
-use crate::driver::{Op, SharedFd};
+use crate::driver::{self, Op, RenameAt, SharedFd};
 
 use std::io;
+use std::path::Path;
 
 use io_uring::{opcode, types};
 
@@ -10,16 +11,38 @@ pub(crate) struct Fsync {
 
 impl Op<Fsync> {
     pub(crate) fn fsync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
-        Op::submit_with(Fsync { fd: fd.clone() }, |fsync| {
+        Op::submit_with(Fsync { fd: fd.for_op() }, |fsync| {
             opcode::Fsync::new(types::Fd(fsync.fd.raw_fd())).build()
         })
     }
 
     pub(crate) fn datasync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
-        Op::submit_with(Fsync { fd: fd.clone() }, |fsync| {
+        Op::submit_with(Fsync { fd: fd.for_op() }, |fsync| {
             opcode::Fsync::new(types::Fd(fsync.fd.raw_fd()))
                 .flags(types::FsyncFlags::DATASYNC)
                 .build()
         })
     }
+
+    /// Submit a request to sync `fd`, linked to a request to rename `from` to
+    /// `to` once the sync succeeded.
+    ///
+    /// If the sync fails, the rename completes with `ECANCELED`.
+    pub(crate) fn fsync_then_rename(
+        fd: &SharedFd,
+        from: &Path,
+        to: &Path,
+    ) -> io::Result<(Op<Fsync>, Op<RenameAt>)> {
+        let rename = RenameAt {
+            from: driver::util::cstr(from)?,
+            to: driver::util::cstr(to)?,
+        };
+
+        Op::submit_linked(
+            Fsync { fd: fd.for_op() },
+            |fsync| opcode::Fsync::new(types::Fd(fsync.fd.raw_fd())).build(),
+            rename,
+            RenameAt::entry,
+        )
+    }
 }
@@ -0,0 +1,150 @@
+use crate::driver::{sqe, Op};
+
+use io_uring::{opcode, squeue};
+use std::io;
+use std::rc::Rc;
+use std::sync::atomic::AtomicU32;
+
+/// `IORING_OP_FUTEX_WAIT`, not exposed by `io-uring` yet
+pub(super) const IORING_OP_FUTEX_WAIT: u8 = 51;
+
+/// `IORING_OP_FUTEX_WAKE`, not exposed by `io-uring` yet
+pub(super) const IORING_OP_FUTEX_WAKE: u8 = 52;
+
+/// `FUTEX2_SIZE_U32`, from `linux/futex.h`
+const FUTEX2_SIZE_U32: u32 = 0x02;
+
+/// `FUTEX2_PRIVATE`, from `linux/futex.h`
+const FUTEX2_PRIVATE: u32 = 128;
+
+/// `FUTEX_BITSET_MATCH_ANY`, from `linux/futex.h`
+const FUTEX_BITSET_MATCH_ANY: u64 = 0xffff_ffff;
+
+/// Memory holding a futex word.
+pub(crate) enum Word {
+    /// Private to the process
+    Boxed(Box<AtomicU32>),
+
+    /// Mapping of a file shared with other processes, holding the word at
+    /// `offset`
+    Mapped {
+        ptr: *mut libc::c_void,
+        len: usize,
+        offset: usize,
+    },
+}
+
+/// Waits on, or wakes the waiters of, a futex word.
+pub(crate) struct Futex {
+    /// Held until the operation completes, as the kernel accesses the word
+    #[allow(dead_code)]
+    word: Rc<Word>,
+}
+
+impl Op<Futex> {
+    /// Submit a wait on `word`, completing once woken, or with `EAGAIN` if
+    /// it does not hold `expected`.
+    pub(crate) fn futex_wait(word: &Rc<Word>, expected: u32) -> io::Result<Op<Futex>> {
+        Op::submit_with(Futex { word: word.clone() }, |futex| {
+            futex.entry(IORING_OP_FUTEX_WAIT, u64::from(expected))
+        })
+    }
+
+    /// Submit a wake of at most `count` waiters of `word`, completing with
+    /// the number of waiters woken.
+    pub(crate) fn futex_wake(word: &Rc<Word>, count: u32) -> io::Result<Op<Futex>> {
+        Op::submit_with(Futex { word: word.clone() }, |futex| {
+            futex.entry(IORING_OP_FUTEX_WAKE, u64::from(count))
+        })
+    }
+}
+
+impl Futex {
+    fn entry(&mut self, opcode: u8, value: u64) -> squeue::Entry {
+        // Start from a blank entry, see `Xattr::entry`
+        let mut entry = opcode::Nop::new().build();
+        let raw = sqe::raw_mut(&mut entry);
+        raw.opcode = opcode;
+        raw.addr = self.word.as_atomic() as *const AtomicU32 as u64;
+        raw.off = value;
+        raw.addr3 = FUTEX_BITSET_MATCH_ANY;
+        raw.fd = self.word.flags() as i32;
+        entry
+    }
+}
+
+impl Word {
+    /// Maps the page of the file `fd` holding the word at `offset`, which
+    /// is a multiple of 4 within the file.
+    pub(crate) fn map(fd: libc::c_int, offset: u64) -> io::Result<Word> {
+        if !offset.is_multiple_of(4) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "futex words are aligned on 4 bytes",
+            ));
+        }
+
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        syscall!(fstat(fd, &mut stat))?;
+        if (stat.st_size as u64) < offset + 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the file does not hold the futex word",
+            ));
+        }
+
+        let page = syscall!(sysconf(libc::_SC_PAGESIZE))? as u64;
+        let start = offset - offset % page;
+        let len = (offset - start + 4) as usize;
+
+        // Safety: a new mapping, unmapped by `drop`
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                start as libc::off_t,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Word::Mapped {
+            ptr,
+            len,
+            offset: (offset - start) as usize,
+        })
+    }
+
+    pub(crate) fn as_atomic(&self) -> &AtomicU32 {
+        match self {
+            Word::Boxed(word) => word,
+            // Safety: the mapping is aligned on a page, and the word within
+            // it on 4 bytes. It lives as long as `self`.
+            Word::Mapped { ptr, offset, .. } => unsafe {
+                &*((*ptr as *mut u8).add(*offset) as *const AtomicU32)
+            },
+        }
+    }
+
+    /// `FUTEX2_*` flags of the word: waiters in other processes are only
+    /// looked for if the word is shared.
+    fn flags(&self) -> u32 {
+        match self {
+            Word::Boxed(_) => FUTEX2_SIZE_U32 | FUTEX2_PRIVATE,
+            Word::Mapped { .. } => FUTEX2_SIZE_U32,
+        }
+    }
+}
+
+impl Drop for Word {
+    fn drop(&mut self) {
+        if let Word::Mapped { ptr, len, .. } = *self {
+            // Safety: mapped by `map`, and no longer referenced
+            unsafe { libc::munmap(ptr, len) };
+        }
+    }
+}
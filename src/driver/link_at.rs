@@ -0,0 +1,51 @@
+use crate::driver::{self, Op, SharedFd};
+
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+
+/// Link an open file to a path relative to the current working directory of
+/// the caller's process.
+pub(crate) struct LinkAt {
+    /// Holds a strong ref to the FD, keeping the `/proc` path valid while the
+    /// operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) old_path: CString,
+    pub(crate) new_path: CString,
+}
+
+impl Op<LinkAt> {
+    /// Submit a request to create a new name for the open file `fd`.
+    ///
+    /// The file is linked through its `/proc/self/fd` entry, which unlike
+    /// `AT_EMPTY_PATH` needs no privileges, and can give a name to a file
+    /// opened with `O_TMPFILE`.
+    pub(crate) fn link_fd(fd: &SharedFd, new_path: &Path) -> io::Result<Op<LinkAt>> {
+        use io_uring::{opcode, types};
+
+        let old_path = CString::new(format!("/proc/self/fd/{}", fd.raw_fd()))?;
+        let new_path = driver::util::cstr(new_path)?;
+
+        Op::submit_with(
+            LinkAt {
+                fd: fd.for_op(),
+                old_path,
+                new_path,
+            },
+            |link| {
+                // The strings are held by the operation state until the
+                // operation completes.
+                opcode::LinkAt::new(
+                    types::Fd(libc::AT_FDCWD),
+                    link.old_path.as_ptr(),
+                    types::Fd(libc::AT_FDCWD),
+                    link.new_path.as_ptr(),
+                )
+                .flags(libc::AT_SYMLINK_FOLLOW)
+                .build()
+            },
+        )
+    }
+}
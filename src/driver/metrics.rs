@@ -0,0 +1,73 @@
+use crate::metrics::Histogram;
+use crate::OpKind;
+
+use io_uring::squeue;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Latencies of the operations, from pushing their SQE to processing their
+/// CQE, by opcode.
+///
+/// Only compiled with the `metrics` feature.
+#[derive(Default)]
+pub(crate) struct Latencies {
+    /// Opcode of each in-flight operation, and when it was pushed, indexed
+    /// by slab index
+    pushed_at: Vec<Option<(u8, Instant)>>,
+
+    histograms: BTreeMap<u8, Histogram>,
+}
+
+impl Latencies {
+    pub(crate) fn pushed(&mut self, index: usize, sqe: &squeue::Entry) {
+        if self.pushed_at.len() <= index {
+            self.pushed_at.resize(index + 1, None);
+        }
+
+        let opcode = super::sqe::raw(sqe).opcode;
+        self.pushed_at[index] = Some((opcode, Instant::now()));
+    }
+
+    pub(crate) fn completed(&mut self, index: usize, now: Instant) {
+        if let Some((opcode, pushed_at)) = self.pushed_at.get_mut(index).and_then(Option::take) {
+            self.histograms
+                .entry(opcode)
+                .or_insert_with(Histogram::new)
+                .record(now - pushed_at);
+        }
+    }
+
+    pub(crate) fn histograms(&self) -> Vec<(OpKind, Histogram)> {
+        self.histograms
+            .iter()
+            .map(|(&opcode, histogram)| (OpKind::from(opcode), histogram.clone()))
+            .collect()
+    }
+
+    pub(crate) fn histogram(&self, opcode: u8) -> Option<&Histogram> {
+        self.histograms.get(&opcode)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.histograms.clear();
+    }
+}
+
+/// Access the latencies recorded by the current driver.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn with_latencies<R>(f: impl FnOnce(&mut Latencies) -> R) -> R {
+    super::CURRENT.with(|inner| f(&mut inner.borrow_mut().latencies))
+}
+
+/// Returns the number of submissions deferred by the current driver, after
+/// the kernel rejected them with `EBUSY`.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn busy_deferrals() -> u64 {
+    super::CURRENT.with(|inner| inner.borrow().busy_deferrals)
+}
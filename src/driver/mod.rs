@@ -1,22 +1,86 @@
 mod accept;
+pub(crate) use accept::Accept;
+
+#[cfg(debug_assertions)]
+mod audit;
+
+#[cfg(feature = "bench-internals")]
+pub(crate) mod bench;
 
 mod close;
 pub(crate) use close::Close;
 
 mod connect;
 
+mod dump;
+pub(crate) use dump::dump;
+
+#[cfg(feature = "test-util")]
+pub(crate) mod fault;
+
+mod fadvise;
+
+mod files;
+
+mod fork;
+
 mod fsync;
 
+mod futex;
+pub(crate) use futex::{Futex, Word};
+
+mod link_at;
+
+#[cfg(feature = "metrics")]
+pub(crate) mod metrics;
+
+#[cfg(test)]
+mod model;
+
+mod multishot;
+
+mod nop;
+
 mod op;
-pub(crate) use op::Op;
+pub(crate) use op::{Completion, Op};
+
+mod permit;
+pub(crate) use permit::{poll_any_room, poll_room, room, OpPermit};
+
+pub(crate) mod personality;
 
 mod open;
 
+mod poll_add;
+pub(crate) use poll_add::{read_ready, ready, write_ready, PollAdd};
+
+mod race;
+pub(crate) use race::Losers;
+
 mod read;
+pub(crate) use read::{complete_read, Read};
+
+mod read_extents;
+
+pub(crate) mod recycle;
+
+mod register;
+pub(crate) use register::{registered_bytes, use_registered_buf, BufRegistration};
+
+mod recv;
 
 mod recv_from;
+pub(crate) use recv_from::RecvFrom;
+
+mod rename_at;
+pub(crate) use rename_at::RenameAt;
+
+mod send;
+
+mod send_msg;
 
 mod send_to;
+pub(crate) use send_to::SendTo;
 
 mod shared_fd;
 pub(crate) use shared_fd::SharedFd;
@@ -24,19 +88,51 @@ pub(crate) use shared_fd::SharedFd;
 mod socket;
 pub(crate) use socket::Socket;
 
+mod sqe;
+
+mod splice;
+pub(crate) use splice::{splice, Splice};
+
+mod statx;
+
+#[cfg(feature = "test-util")]
+pub(crate) mod time;
+
+mod timeout;
+pub(crate) use timeout::Timeout;
+
 mod unlink_at;
 
 mod util;
+pub(crate) use util::cstr;
+
+mod waitid;
+pub(crate) use waitid::Waitid;
+
+pub(crate) mod watchdog;
 
 mod write;
+pub(crate) use write::Write;
 
-use io_uring::{cqueue, IoUring};
+mod xattr;
+pub(crate) use xattr::Xattr;
+
+use crate::builder::SlowOp;
+use crate::{Backpressure, OpKind};
+
+use io_uring::{cqueue, squeue, IoUring};
 use scoped_tls::scoped_thread_local;
 use slab::Slab;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::io;
 use std::os::unix::io::{AsRawFd, RawFd};
 use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// Number of times a submission rejected with `EBUSY` is retried by default
+pub(crate) const DEFAULT_BUSY_RETRIES: u32 = 8;
 
 pub(crate) struct Driver {
     inner: Handle,
@@ -48,36 +144,446 @@ struct Inner {
     /// In-flight operations
     ops: Ops,
 
+    /// Opcode of each operation, by slab index, see `dump`
+    kinds: Vec<OpKind>,
+
+    /// State of ignored operations that completed. It is dropped once the
+    /// driver is no longer borrowed, as it may hold the last handle to a
+    /// `SharedFd` whose drop submits a close operation.
+    orphans: Vec<op::Lifecycle>,
+
+    /// Receive the buffers of the orphans
+    recyclers: Rc<recycle::Recyclers>,
+
     /// IoUring bindings
     uring: IoUring,
+
+    /// Permits bounding operations by the capacity of the ring
+    permits: permit::Permits,
+
+    /// Memory locked by the registered buffers
+    registered_bytes: usize,
+
+    /// Address ranges of the registered buffers, with their indices, in
+    /// address order
+    registered_bufs: Vec<(u64, u64, u16)>,
+
+    /// Maximum number of operations in the slab, see `Builder::max_ops`
+    max_ops: usize,
+
+    /// What the reads and writes do once the slab is full
+    backpressure: Backpressure,
+
+    /// Maximum number of operations in flight on a file descriptor before
+    /// the reads and writes apply backpressure, see `Builder::max_ops_per_fd`
+    max_ops_per_fd: Option<(usize, Backpressure)>,
+
+    /// Number of times a submission rejected with `EBUSY` is retried before
+    /// being deferred, see `Builder::busy_retries`
+    busy_retries: u32,
+
+    /// What the reads and writes do while a submission is deferred
+    busy_backpressure: Backpressure,
+
+    /// Set once a submission was deferred, until it goes through
+    deferred: bool,
+
+    /// Wakes the runtime once a submission is deferred, see
+    /// `poll_deferred`
+    deferred_waker: Option<Waker>,
+
+    /// Number of submissions deferred
+    #[cfg(feature = "metrics")]
+    busy_deferrals: u64,
+
+    /// User data of the entries pushed onto the submission queue and not
+    /// consumed by the kernel yet, oldest first, see `fail_queued`
+    queued: VecDeque<u64>,
+
+    /// Number of entries pushed onto the submission queue, wrapping as its
+    /// tail does, see `fail_queued`
+    pushed: u32,
+
+    /// Submit and completion timings
+    #[cfg(feature = "bench-internals")]
+    pub(crate) stats: bench::Stats,
+
+    /// Latencies of the operations, by opcode
+    #[cfg(feature = "metrics")]
+    latencies: metrics::Latencies,
+
+    /// Reports the operations in flight for too long, if enabled
+    watchdog: Option<watchdog::Watchdog>,
+
+    /// Fixed-file table the file descriptors used most are registered into,
+    /// if enabled
+    files: Option<files::FixedFiles>,
+
+    /// Fork generation the driver was created in, see `fork`
+    generation: usize,
+
+    /// Personality of the operations submitted, set while polling a future
+    /// wrapped with `with_personality`
+    personality: Option<u16>,
+
+    /// Opcodes supported by the ring, probed on first use
+    probe: Option<io_uring::Probe>,
+
+    /// Whether operations with a fallback, such as `statx`, may be
+    /// submitted. Unset in seccomp-compatible mode.
+    optional_ops: bool,
+
+    /// Set by `shutdown_now`: the driver does not wait for the operations
+    /// in flight when dropped
+    shutting_down: bool,
+
+    /// When set, submissions are captured by the model instead of being
+    /// pushed to the kernel.
+    #[cfg(test)]
+    model: Option<model::Model>,
+
+    /// Faults injected into submitted operations
+    #[cfg(feature = "test-util")]
+    faults: fault::Injector,
+
+    /// Virtual clock driving timers while time is paused
+    #[cfg(feature = "test-util")]
+    clock: time::Clock,
 }
 
 // When dropping the driver, all in-flight operations must have completed. This
-// type wraps the slab and ensures that, on drop, the slab is empty.
+// type wraps the slab and leaks the operations left in it on drop, if any.
 struct Ops(Slab<op::Lifecycle>);
 
 scoped_thread_local!(static CURRENT: Rc<RefCell<Inner>>);
 
+/// Returns `true` if called from the context of a driver.
+pub(crate) fn is_current() -> bool {
+    CURRENT.is_set()
+}
+
+/// Cancel the operations in flight on the current driver, which leaks the
+/// ones still in flight once dropped instead of waiting for them, see
+/// `shutdown_now`.
+pub(crate) fn shutdown_now() {
+    CURRENT.with(|inner| {
+        let mut inner = inner.borrow_mut();
+        inner.shutting_down = true;
+
+        // The ring is the one of the parent process
+        if !inner.forked() {
+            inner.cancel_all();
+        }
+    })
+}
+
+/// Unregister `fd` from the fixed-file table of the current driver, if
+/// registered, before it is closed.
+pub(crate) fn forget_file(fd: RawFd) {
+    if !CURRENT.is_set() {
+        return;
+    }
+
+    CURRENT.with(|inner| {
+        let mut inner = inner.borrow_mut();
+        let inner = &mut *inner;
+
+        if let Some(files) = &mut inner.files {
+            files.forget(&inner.uring, fd);
+        }
+    })
+}
+
+/// Returns `true` if the current driver may submit the operations which have
+/// a fallback, such as `statx`.
+pub(crate) fn optional_ops() -> bool {
+    !CURRENT.is_set() || CURRENT.with(|inner| inner.borrow().optional_ops)
+}
+
+/// Returns `true` if the ring of the current driver supports `opcode`, as
+/// reported by `IORING_REGISTER_PROBE`. Optional operations are reported as
+/// unsupported when disabled, without probing the ring.
+pub(crate) fn supports(opcode: u8) -> bool {
+    if !CURRENT.is_set() {
+        return false;
+    }
+
+    CURRENT.with(|inner| {
+        let mut inner = inner.borrow_mut();
+        if !inner.optional_ops {
+            return false;
+        }
+
+        if inner.probe.is_none() {
+            let mut probe = io_uring::Probe::new();
+            // An empty probe reports every opcode as unsupported
+            let _ = inner.uring.submitter().register_probe(&mut probe);
+            inner.probe = Some(probe);
+        }
+
+        inner
+            .probe
+            .as_ref()
+            .is_some_and(|probe| probe.is_supported(opcode))
+    })
+}
+
+/// Returns the opcodes supported by the ring of the current driver, probing
+/// it even if the optional operations are disabled.
+pub(crate) fn supported_opcodes() -> io::Result<Vec<u8>> {
+    CURRENT.with(|inner| {
+        let inner = inner.borrow();
+        let mut probe = io_uring::Probe::new();
+        inner.uring.submitter().register_probe(&mut probe)?;
+        Ok((0..=u8::MAX).filter(|&op| probe.is_supported(op)).collect())
+    })
+}
+
+/// Returns the features of the ring of the current driver, see
+/// `crate::features`.
+pub(crate) fn features() -> crate::Features {
+    let params = CURRENT.with(|inner| inner.borrow().uring.params().clone());
+    crate::Features::detect(&params, supports)
+}
+
 impl Driver {
+    #[cfg(test)]
     pub(crate) fn new() -> io::Result<Driver> {
         let uring = IoUring::new(256)?;
+        Ok(Driver::from_uring(uring))
+    }
 
+    /// Create a driver over a ring configured by the caller.
+    pub(crate) fn from_uring(uring: IoUring) -> Driver {
         let inner = Rc::new(RefCell::new(Inner {
             ops: Ops::new(),
+            kinds: Vec::new(),
+            orphans: Vec::new(),
+            recyclers: Rc::default(),
             uring,
+            permits: permit::Permits::default(),
+            registered_bytes: 0,
+            registered_bufs: Vec::new(),
+            max_ops: usize::MAX,
+            backpressure: Backpressure::Fail,
+            max_ops_per_fd: None,
+            busy_retries: DEFAULT_BUSY_RETRIES,
+            busy_backpressure: Backpressure::Wait,
+            deferred: false,
+            deferred_waker: None,
+            #[cfg(feature = "metrics")]
+            busy_deferrals: 0,
+            queued: VecDeque::new(),
+            pushed: 0,
+            #[cfg(feature = "bench-internals")]
+            stats: bench::Stats::default(),
+            #[cfg(feature = "metrics")]
+            latencies: metrics::Latencies::default(),
+            watchdog: None,
+            files: None,
+            generation: fork::register(),
+            personality: None,
+            probe: None,
+            optional_ops: true,
+            shutting_down: false,
+            #[cfg(test)]
+            model: None,
+            #[cfg(feature = "test-util")]
+            faults: fault::Injector::default(),
+            #[cfg(feature = "test-util")]
+            clock: time::Clock::default(),
         }));
 
-        Ok(Driver { inner })
+        Driver { inner }
+    }
+
+    /// Report the operations in flight for longer than the threshold of
+    /// `watchdog`.
+    pub(crate) fn set_watchdog(&self, watchdog: watchdog::Watchdog) {
+        self.inner.borrow_mut().watchdog = Some(watchdog);
+    }
+
+    /// Make room for `ops` operations in flight, see
+    /// `Builder::preallocate_ops`.
+    pub(crate) fn reserve_ops(&self, ops: usize) {
+        let mut inner = self.inner.borrow_mut();
+        inner.ops.0.reserve(ops);
+        inner.kinds.reserve(ops);
+    }
+
+    /// Bound the operations in flight, see `Builder::max_ops`.
+    pub(crate) fn set_max_ops(&self, ops: usize, backpressure: Backpressure) {
+        let mut inner = self.inner.borrow_mut();
+        inner.max_ops = ops;
+        inner.backpressure = backpressure;
+    }
+
+    /// Bound the operations in flight on each file descriptor, see
+    /// `Builder::max_ops_per_fd`.
+    pub(crate) fn set_max_ops_per_fd(&self, ops: usize, backpressure: Backpressure) {
+        self.inner.borrow_mut().max_ops_per_fd = Some((ops, backpressure));
+    }
+
+    /// Retry the submissions rejected with `EBUSY`, see
+    /// `Builder::busy_retries`.
+    pub(crate) fn set_busy_retries(&self, retries: u32, backpressure: Backpressure) {
+        let mut inner = self.inner.borrow_mut();
+        inner.busy_retries = retries;
+        inner.busy_backpressure = backpressure;
+    }
+
+    /// Returns `true` if a submission was deferred, for the runtime to
+    /// submit it again on its next tick rather than sleeping.
+    pub(crate) fn submission_deferred(&self) -> bool {
+        self.inner.borrow().deferred
+    }
+
+    /// Completes once a submission is deferred, waking the runtime waiting
+    /// for completions.
+    pub(crate) fn poll_deferred(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.deferred {
+            return Poll::Ready(());
+        }
+
+        inner.deferred_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Register the file descriptors used most into a fixed-file table of
+    /// `slots` files, see `Builder::auto_register_files`.
+    pub(crate) fn auto_register_files(&self, slots: u32) -> io::Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        inner.files = Some(files::FixedFiles::register(&inner.uring, slots)?);
+        Ok(())
+    }
+
+    /// Use the fallbacks of the operations which have one, such as `fstat(2)`
+    /// for `statx`, see `Builder::seccomp_compatible`.
+    pub(crate) fn disable_optional_ops(&self) {
+        self.inner.borrow_mut().optional_ops = false;
+    }
+
+    /// Interval at which `check_slow_ops` must be called, if the watchdog is
+    /// enabled.
+    pub(crate) fn watchdog_period(&self) -> Option<Duration> {
+        self.inner.borrow().watchdog.as_ref().map(|w| w.period())
+    }
+
+    /// Report the slow operations, and cancel them if configured to.
+    pub(crate) fn check_slow_ops(&self) {
+        let (slow, hook) = {
+            let mut inner = self.inner.borrow_mut();
+            let watchdog = match &mut inner.watchdog {
+                Some(watchdog) => watchdog,
+                None => return,
+            };
+
+            let slow = watchdog.check();
+            let hook = watchdog.hook();
+
+            if watchdog.cancels() && !slow.is_empty() {
+                for (index, _) in &slow {
+                    let _ = inner.push_cancel(*index);
+                }
+                let _ = inner.submit();
+            }
+
+            (slow, hook)
+        };
+
+        // The hook may use the driver
+        for (_, op) in &slow {
+            watchdog::report(hook.as_deref(), op);
+        }
     }
 
     /// Enter the driver context. This enables using uring types.
     pub(crate) fn with<R>(&self, f: impl FnOnce() -> R) -> R {
-        CURRENT.set(&self.inner, || f())
+        CURRENT.set(&self.inner, f)
     }
 
     pub(crate) fn tick(&self) {
+        let (orphans, recyclers) = {
+            let mut inner = self.inner.borrow_mut();
+            inner.tick();
+
+            // The submission deferred by `EBUSY`, made room for by the
+            // completions just processed
+            if inner.deferred {
+                let _ = inner.submit();
+            }
+
+            (std::mem::take(&mut inner.orphans), inner.recyclers.clone())
+        };
+
+        recyclers.recycle(orphans);
+    }
+
+    /// Process the available completions without blocking. Returns `true`
+    /// if there were any.
+    pub(crate) fn poll_completions(&self) -> bool {
+        /// `IORING_ENTER_GETEVENTS`
+        const IORING_ENTER_GETEVENTS: u32 = 1;
+
+        let ready = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.forked() {
+                return false;
+            }
+
+            if inner.uring.completion().is_empty() {
+                // Completions of some operations are posted by task work,
+                // which runs when entering the kernel. With `min_complete` of
+                // 0, this does not wait.
+                let _ = unsafe {
+                    inner.uring.submitter().enter::<libc::sigset_t>(
+                        0,
+                        0,
+                        IORING_ENTER_GETEVENTS,
+                        None,
+                    )
+                };
+            }
+
+            let mut cq = inner.uring.completion();
+            cq.sync();
+            !cq.is_empty()
+        };
+
+        if ready {
+            self.tick();
+        }
+        ready
+    }
+
+    /// Wait until `count` completions are available, or `timeout` elapsed.
+    pub(crate) fn wait_for_completions(&self, count: usize, timeout: Duration) -> io::Result<()> {
+        use io_uring::types::{SubmitArgs, Timespec};
+
         let mut inner = self.inner.borrow_mut();
-        inner.tick();
+        if inner.forked() {
+            return Err(fork::inherited());
+        }
+        let available = {
+            let mut cq = inner.uring.completion();
+            cq.sync();
+            cq.len()
+        };
+        if available >= count {
+            return Ok(());
+        }
+
+        let ts = Timespec::new()
+            .sec(timeout.as_secs())
+            .nsec(timeout.subsec_nanos());
+        let args = SubmitArgs::new().timespec(&ts);
+
+        match inner.uring.submitter().submit_with_args(count, &args) {
+            Ok(_) => Ok(()),
+            Err(e) if matches!(e.raw_os_error(), Some(libc::ETIME) | Some(libc::EINTR)) => Ok(()),
+            Err(e) => Err(e),
+        }
     }
 
     fn wait(&self) -> io::Result<usize> {
@@ -87,17 +593,38 @@ impl Driver {
         inner.uring.submit_and_wait(1)
     }
 
+    #[cfg(test)]
     fn num_operations(&self) -> usize {
         let inner = self.inner.borrow();
         inner.ops.0.len()
     }
+
+    /// Number of operations the kernel did not complete yet.
+    fn num_pending(&self) -> usize {
+        let inner = self.inner.borrow();
+        inner
+            .ops
+            .0
+            .iter()
+            .filter(|(_, lifecycle)| !lifecycle.is_finished())
+            .count()
+    }
 }
 
 impl Inner {
     fn tick(&mut self) {
+        // The completions are the ones of the parent process
+        if self.forked() {
+            return;
+        }
+
         let mut cq = self.uring.completion();
         cq.sync();
 
+        // The completions are processed at the same time
+        #[cfg(feature = "metrics")]
+        let now = std::time::Instant::now();
+
         for cqe in cq {
             if cqe.user_data() == u64::MAX {
                 // Result of the cancellation action. There isn't anything we
@@ -108,21 +635,108 @@ impl Inner {
 
             let index = cqe.user_data() as _;
 
-            self.ops.complete(index, resultify(&cqe), cqe.flags());
+            #[cfg(feature = "bench-internals")]
+            self.stats.completed(index);
+
+            #[cfg(feature = "metrics")]
+            self.latencies.completed(index, now);
+
+            if let Some(watchdog) = &mut self.watchdog {
+                watchdog.completed(index);
+            }
+
+            if let Some(orphan) = self.ops.complete(index, resultify(&cqe), cqe.flags()) {
+                self.orphans.push(orphan);
+            }
         }
+
+        // Operations completed, permits may be available
+        self.permits.wake_all();
     }
 
     fn submit(&mut self) -> io::Result<()> {
+        if self.forked() {
+            return Err(fork::inherited());
+        }
+
+        let mut retries = 0;
         loop {
-            match self.uring.submit() {
+            #[cfg(feature = "bench-internals")]
+            let start = std::time::Instant::now();
+
+            // With SQPOLL, the kernel thread consumes the entries whether
+            // the submission fails or not
+            #[cfg(feature = "test-util")]
+            let res = match self.faults.take_submit_error() {
+                Some(errno) if !self.uring.params().is_setup_sqpoll() => {
+                    Err(io::Error::from_raw_os_error(errno))
+                }
+                _ => self.uring.submit(),
+            };
+            #[cfg(not(feature = "test-util"))]
+            let res = self.uring.submit();
+
+            #[cfg(feature = "bench-internals")]
+            self.stats.submitted(start);
+
+            match res {
                 Ok(_) => {
-                    self.uring.submission().sync();
+                    self.trim_queued();
+
+                    // Operations waiting for the deferred submission may
+                    // proceed
+                    if self.deferred {
+                        self.deferred = false;
+                        self.permits.wake_all();
+                    }
+
+                    let full = {
+                        let mut sq = self.uring.submission();
+                        sq.sync();
+                        sq.is_full()
+                    };
+
+                    // With SQPOLL, the entries are consumed by the kernel
+                    // thread in the background. Wait for room.
+                    if full && self.uring.params().is_setup_sqpoll() {
+                        self.uring.submitter().squeue_wait()?;
+                    }
                     return Ok(());
                 }
+                // The completion queue overflowed: make room, and retry
                 Err(ref e) if e.raw_os_error() == Some(libc::EBUSY) => {
                     self.tick();
+
+                    if retries == self.busy_retries {
+                        // The entries stay in the submission queue, for the
+                        // next submission or tick
+                        self.deferred = true;
+                        if let Some(waker) = self.deferred_waker.take() {
+                            waker.wake();
+                        }
+                        #[cfg(feature = "metrics")]
+                        {
+                            self.busy_deferrals += 1;
+                        }
+                        return Err(io::Error::from_raw_os_error(libc::EBUSY));
+                    }
+                    retries += 1;
+                }
+                // The entries stay in the submission queue, for the next
+                // submission
+                Err(e)
+                    if matches!(
+                        e.raw_os_error(),
+                        Some(libc::EAGAIN) | Some(libc::ENOMEM) | Some(libc::EINTR)
+                    ) =>
+                {
+                    self.trim_queued();
+                    return Err(e);
                 }
+                // The kernel thread consumes the entries regardless
+                Err(e) if self.uring.params().is_setup_sqpoll() => return Err(e),
                 Err(e) => {
+                    self.fail_queued(&e);
                     return Err(e);
                 }
             }
@@ -130,6 +744,166 @@ impl Inner {
     }
 }
 
+impl Inner {
+    /// Forgets the entries the kernel consumed: the entries left in the
+    /// submission queue are the last ones pushed.
+    fn trim_queued(&mut self) {
+        let pending = {
+            let mut sq = self.uring.submission();
+            sq.sync();
+            sq.len()
+        };
+
+        while self.queued.len() > pending {
+            self.queued.pop_front();
+        }
+
+        #[cfg(feature = "test-util")]
+        self.faults
+            .release(self.pushed.wrapping_sub(pending as u32));
+    }
+
+    /// Fails the operations whose entries were in the submission which
+    /// failed with `err`, rather than leaving their futures waiting for a
+    /// completion which never comes.
+    ///
+    /// The kernel did not consume the entries, and the submission queue
+    /// cannot be rewound: the entries are turned into no-ops in place, for
+    /// the kernel not to start the failed operations after their buffers
+    /// were returned. The driver stays usable. If the entries cannot be
+    /// rewritten, the operations are left queued for the next submission.
+    fn fail_queued(&mut self, err: &io::Error) {
+        self.trim_queued();
+        if sqe::neutralize(&self.uring, self.pushed, self.queued.len()).is_err() {
+            return;
+        }
+        let errno = err.raw_os_error().unwrap_or(libc::EIO);
+
+        for user_data in self.queued.iter_mut() {
+            let index = std::mem::replace(user_data, u64::MAX) as usize;
+            if index == u64::MAX as usize || !self.ops.0.contains(index) {
+                continue;
+            }
+
+            if let Some(watchdog) = &mut self.watchdog {
+                watchdog.completed(index);
+            }
+
+            let res = Err(io::Error::from_raw_os_error(errno));
+            if let Some(orphan) = self.ops.complete(index, res, 0) {
+                self.orphans.push(orphan);
+            }
+        }
+
+        self.permits.wake_all();
+    }
+
+    /// Tracks the entry with `user_data` just pushed onto the submission
+    /// queue.
+    pub(super) fn track_pushed(&mut self, user_data: u64) {
+        self.queued.push_back(user_data);
+        self.pushed = self.pushed.wrapping_add(1);
+    }
+
+    /// Submits the queued entries if the submission queue has room for fewer
+    /// than `count` more, failing with `EBUSY` if it still does not then.
+    fn make_room(&mut self, count: usize) -> io::Result<()> {
+        let free = |inner: &mut Inner| {
+            let mut sq = inner.uring.submission();
+            sq.sync();
+            sq.capacity() - sq.len()
+        };
+
+        if free(self) < count {
+            self.submit()?;
+
+            if free(self) < count {
+                return Err(io::Error::from_raw_os_error(libc::EBUSY));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the driver was inherited from the parent process.
+    fn forked(&self) -> bool {
+        self.generation != fork::generation()
+    }
+
+    /// Request the cancellation of all in-flight operations.
+    fn cancel_all(&mut self) {
+        let indices: Vec<usize> = self
+            .ops
+            .0
+            .iter()
+            .filter(|(_, lifecycle)| !lifecycle.is_finished())
+            .map(|(index, _)| index)
+            .collect();
+
+        for index in indices {
+            if self.push_cancel(index).is_err() {
+                return;
+            }
+        }
+
+        let _ = self.submit();
+    }
+
+    /// Push a request to cancel the operation at `index`, without submitting
+    /// it.
+    fn push_cancel(&mut self, index: usize) -> io::Result<()> {
+        use io_uring::opcode;
+
+        // The result of the cancellation is ignored, the canceled operation
+        // completes on its own.
+        self.push_untracked(opcode::AsyncCancel::new(index as _).build())
+    }
+
+    /// Empty the slab on shutdown, once the operations which could be waited
+    /// for completed. The completed operations are returned, for their state
+    /// to be dropped, along with a report of each operation still in flight.
+    /// These are leaked, as the kernel may still write to their buffers.
+    fn abandon_ops(&mut self) -> (Vec<op::Lifecycle>, Vec<SlowOp>) {
+        let mut orphans = Vec::new();
+        let mut leaked = Vec::new();
+
+        for (index, lifecycle) in std::mem::take(&mut self.ops.0) {
+            if lifecycle.is_finished() {
+                orphans.push(lifecycle);
+                continue;
+            }
+
+            let report = self.watchdog.as_ref().and_then(|w| w.leaked(index));
+            leaked.push(report.unwrap_or_else(|| SlowOp {
+                kind: self.kind(index),
+                fd: -1,
+                age: Duration::ZERO,
+                cancelled: true,
+                leaked: true,
+            }));
+            std::mem::forget(lifecycle);
+        }
+
+        (orphans, leaked)
+    }
+
+    /// Push an entry whose completion is ignored, such as a cancellation.
+    fn push_untracked(&mut self, sqe: squeue::Entry) -> io::Result<()> {
+        let sqe = sqe.user_data(u64::MAX);
+
+        if self.uring.submission().is_full() {
+            self.submit()?;
+        }
+
+        if unsafe { self.uring.submission().push(&sqe).is_err() } {
+            return Err(io::ErrorKind::Other.into());
+        }
+        self.track_pushed(u64::MAX);
+
+        Ok(())
+    }
+}
+
 impl AsRawFd for Driver {
     fn as_raw_fd(&self) -> RawFd {
         self.inner.borrow().uring.as_raw_fd()
@@ -138,12 +912,68 @@ impl AsRawFd for Driver {
 
 impl Drop for Driver {
     fn drop(&mut self) {
-        while self.num_operations() > 0 {
-            // If waiting fails, ignore the error. The wait will be attempted
-            // again on the next loop.
-            let _ = self.wait().unwrap();
+        // Nothing reached the kernel, there is nothing to wait for. Discard
+        // the operations left in-flight.
+        #[cfg(test)]
+        if self.inner.borrow().model.is_some() {
+            let ops = std::mem::take(&mut self.inner.borrow_mut().ops.0);
+            drop(ops);
+            return;
+        }
+
+        // The operations in flight were submitted by the parent process, which
+        // owns their buffers: the kernel does not write to the memory of the
+        // child. Discard them without touching the ring.
+        if self.inner.borrow().forked() {
+            let ops = std::mem::take(&mut self.inner.borrow_mut().ops.0);
+            drop(ops);
+            return;
+        }
+
+        // Virtual timers never complete on their own. The operations still
+        // holding them find their slot gone and skip it.
+        #[cfg(feature = "test-util")]
+        {
+            let mut inner = self.inner.borrow_mut();
+            for index in inner.clock.drain() {
+                inner.ops.remove(index);
+            }
+        }
+
+        // Operations still in-flight were abandoned by their task. Cancel them
+        // instead of waiting for them to complete on their own, which may
+        // never happen, e.g. for an accept.
+        self.inner.borrow_mut().cancel_all();
+
+        // The operations are leaked, without waiting for the cancellations
+        if self.inner.borrow().shutting_down {
+            let (orphans, _) = self.inner.borrow_mut().abandon_ops();
+            drop(orphans);
+            return;
+        }
+
+        // Operations completed while their future was leaked, or forgotten,
+        // stay in the slab but are not waited for
+        while self.num_pending() > 0 {
+            match self.wait() {
+                Ok(_) => {}
+                Err(e) if e.raw_os_error() == Some(libc::EINTR) => {}
+                // The remaining operations cannot be waited for
+                Err(_) => break,
+            }
             self.tick();
         }
+
+        // Dropped once the driver is released, as dropping their state may
+        // close a file descriptor
+        let (orphans, leaked) = self.inner.borrow_mut().abandon_ops();
+        drop(orphans);
+
+        // The hook may use the driver
+        let hook = self.inner.borrow().watchdog.as_ref().and_then(|w| w.hook());
+        for op in &leaked {
+            watchdog::report(hook.as_deref(), op);
+        }
     }
 }
 
@@ -166,16 +996,33 @@ impl Ops {
         self.0.remove(index);
     }
 
-    fn complete(&mut self, index: usize, result: io::Result<u32>, flags: u32) {
+    // Complete an operation. If the submitter no longer has interest in the
+    // result, the operation is removed and its lifecycle returned so the caller
+    // can drop the operation state.
+    fn complete(
+        &mut self,
+        index: usize,
+        result: io::Result<u32>,
+        flags: u32,
+    ) -> Option<op::Lifecycle> {
         if self.0[index].complete(result, flags) {
-            self.0.remove(index);
+            Some(self.0.remove(index))
+        } else {
+            None
         }
     }
 }
 
 impl Drop for Ops {
     fn drop(&mut self) {
-        assert!(self.0.is_empty());
+        if self.0.is_empty() {
+            return;
+        }
+
+        // The driver empties the slab on shutdown, see `abandon_ops`. If the
+        // driver itself was leaked, the kernel may still write to the buffers
+        // of the operations left in flight: leak them as well.
+        std::mem::forget(std::mem::take(&mut self.0));
     }
 }
 
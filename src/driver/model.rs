@@ -0,0 +1,478 @@
+//! Model driver used to exhaustively check the operation lifecycle and the
+//! `SharedFd` close state machine.
+//!
+//! In model mode, SQEs are recorded instead of being pushed to the kernel and
+//! completions are delivered by the test. The explorers replay every ordering
+//! of the events a real executor and kernel could produce, checking invariants
+//! after each step and once no more events are possible.
+
+use crate::driver::{sqe, Driver, Op};
+
+use io_uring::squeue;
+use std::io;
+
+/// Submissions captured instead of being pushed to the kernel.
+pub(super) struct Model {
+    submitted: Vec<Submission>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Submission {
+    /// Operation index in the slab
+    pub(crate) index: usize,
+
+    /// `IORING_OP_*` opcode of the SQE
+    pub(crate) opcode: u8,
+}
+
+impl Model {
+    pub(super) fn push(&mut self, index: usize, sqe: &squeue::Entry) {
+        let opcode = sqe::raw(sqe).opcode;
+        self.submitted.push(Submission { index, opcode });
+    }
+}
+
+impl Driver {
+    /// Create a driver whose submissions never reach the kernel.
+    pub(crate) fn new_model() -> io::Result<Driver> {
+        let driver = Driver::new()?;
+        driver.inner.borrow_mut().model = Some(Model { submitted: vec![] });
+        Ok(driver)
+    }
+
+    /// Operations submitted so far, in order.
+    pub(crate) fn submissions(&self) -> Vec<Submission> {
+        let inner = self.inner.borrow();
+        inner
+            .model
+            .as_ref()
+            .expect("not a model driver")
+            .submitted
+            .clone()
+    }
+
+    /// Deliver a completion, as `tick` would.
+    pub(crate) fn complete(&self, index: usize, result: io::Result<u32>) {
+        let orphan = self.inner.borrow_mut().ops.complete(index, result, 0);
+        drop(orphan);
+    }
+}
+
+impl<T> Op<T> {
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+}
+
+mod test {
+    use crate::driver::read::Read;
+    use crate::driver::{Driver, Op, SharedFd};
+
+    use io_uring::opcode;
+    use std::future::Future;
+    use std::os::unix::io::{IntoRawFd, RawFd};
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    /// Longest event sequence explored before forcing the remaining events.
+    const MAX_DEPTH: usize = 7;
+
+    struct CountWaker(AtomicUsize);
+
+    impl Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn waker() -> (Arc<CountWaker>, Waker) {
+        let count = Arc::new(CountWaker(AtomicUsize::new(0)));
+        (count.clone(), Waker::from(count))
+    }
+
+    fn woken(count: &CountWaker) -> usize {
+        count.0.load(Ordering::SeqCst)
+    }
+
+    /// Replays every event sequence reachable from `prefix`. `run` applies the
+    /// events and returns the events enabled afterwards.
+    fn explore<E: Copy>(prefix: &mut Vec<E>, run: &impl Fn(&[E], bool) -> Vec<E>) -> usize {
+        let enabled = run(prefix, prefix.len() == MAX_DEPTH);
+
+        if prefix.len() == MAX_DEPTH || enabled.is_empty() {
+            return 1;
+        }
+
+        let mut paths = 0;
+
+        for event in enabled {
+            prefix.push(event);
+            paths += explore(prefix, run);
+            prefix.pop();
+        }
+
+        paths
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum OpEvent {
+        /// Poll the operation from one of two tasks
+        Poll(usize),
+        /// The kernel posts the CQE
+        Complete,
+        /// The submitter drops the operation future
+        Drop,
+    }
+
+    struct OpModel<'a> {
+        events: &'a [OpEvent],
+        driver: &'a Driver,
+        op: Option<Op<Rc<()>>>,
+        index: usize,
+        tasks: [(Arc<CountWaker>, Waker); 2],
+        completed: bool,
+        ready: bool,
+        registered: Option<usize>,
+    }
+
+    impl OpModel<'_> {
+        fn apply(&mut self, event: OpEvent) {
+            let events = self.events;
+
+            match event {
+                OpEvent::Poll(task) => {
+                    let mut cx = Context::from_waker(&self.tasks[task].1);
+
+                    match Pin::new(self.op.as_mut().unwrap()).poll(&mut cx) {
+                        Poll::Ready(completion) => {
+                            assert!(self.completed, "{:?}: ready before completion", events);
+                            assert_eq!(completion.result.unwrap(), 0);
+                            self.ready = true;
+                        }
+                        Poll::Pending => {
+                            assert!(!self.completed, "{:?}: pending after completion", events);
+                            self.registered = Some(task);
+                        }
+                    }
+                }
+                OpEvent::Complete => {
+                    let before = self.tasks.each_ref().map(|(count, _)| woken(count));
+                    self.driver.complete(self.index, Ok(0));
+                    self.completed = true;
+
+                    for (task, (count, _)) in self.tasks.iter().enumerate() {
+                        let expect = before[task] + (self.registered == Some(task)) as usize;
+                        assert_eq!(woken(count), expect, "{:?}: wrong task notified", events);
+                    }
+                }
+                OpEvent::Drop => {
+                    self.op = None;
+                    self.registered = None;
+                }
+            }
+        }
+
+        fn enabled(&self) -> Vec<OpEvent> {
+            use OpEvent::*;
+
+            let mut enabled = vec![];
+
+            if !self.completed {
+                enabled.push(Complete);
+            }
+
+            if self.op.is_some() {
+                if !self.ready {
+                    enabled.extend([Poll(0), Poll(1)]);
+                }
+                enabled.push(Drop);
+            }
+
+            enabled
+        }
+    }
+
+    fn run_op(events: &[OpEvent], finish: bool) -> Vec<OpEvent> {
+        let driver = Driver::new_model().unwrap();
+        let data = Rc::new(());
+
+        let op =
+            driver.with(|| Op::submit_with(data.clone(), |_| opcode::Nop::new().build()).unwrap());
+
+        let mut model = OpModel {
+            events,
+            driver: &driver,
+            index: op.index(),
+            op: Some(op),
+            tasks: [waker(), waker()],
+            completed: false,
+            ready: false,
+            registered: None,
+        };
+
+        for &event in events {
+            model.apply(event);
+        }
+
+        let enabled = model.enabled();
+
+        if finish || enabled.is_empty() {
+            if !model.completed {
+                model.apply(OpEvent::Complete);
+            }
+            model.apply(OpEvent::Drop);
+
+            assert_eq!(driver.num_operations(), 0, "{:?}: op leaked", events);
+            assert_eq!(Rc::strong_count(&data), 1, "{:?}: data leaked", events);
+        }
+
+        enabled
+    }
+
+    #[test]
+    fn op_lifecycle() {
+        let paths = explore(&mut vec![], &run_op);
+        assert!(paths > 100);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum CloseEvent {
+        /// The kernel posts the CQE of a read
+        CompleteRead(usize),
+        /// The submitter drops a read future
+        DropRead(usize),
+        /// The executor polls the `close()` future after it was notified
+        PollClose,
+        /// The kernel posts the CQE of the close
+        CompleteClose,
+        /// The last user handle is dropped without calling `close()`
+        DropHandle,
+    }
+
+    const IORING_OP_CLOSE: u8 = opcode::Close::CODE;
+
+    struct CloseModel<'a> {
+        events: &'a [CloseEvent],
+        driver: &'a Driver,
+        raw: RawFd,
+        reads: Vec<Option<Op<Read<Vec<u8>>>>>,
+        indices: Vec<usize>,
+        completed: [bool; 2],
+        task: (Arc<CountWaker>, Waker),
+        polled: bool,
+        seen: usize,
+        closing: Option<Pin<Box<dyn Future<Output = ()>>>>,
+        handle: Option<SharedFd>,
+        close_complete: bool,
+        closed: bool,
+    }
+
+    impl CloseModel<'_> {
+        fn close_op(&self) -> Option<usize> {
+            let submissions = self.driver.submissions();
+            let mut closes = submissions.iter().filter(|s| s.opcode == IORING_OP_CLOSE);
+            let close = closes.next().map(|s| s.index);
+            assert!(closes.next().is_none(), "{:?}: closed twice", self.events);
+            close
+        }
+
+        fn notified(&self) -> bool {
+            woken(&self.task.0) > self.seen
+        }
+
+        fn apply(&mut self, event: CloseEvent) {
+            use CloseEvent::*;
+
+            let events = self.events;
+
+            match event {
+                CompleteRead(i) => {
+                    self.driver.complete(self.indices[i], Ok(0));
+                    self.completed[i] = true;
+                }
+                DropRead(i) => self.reads[i] = None,
+                PollClose => {
+                    self.polled = true;
+                    self.seen = woken(&self.task.0);
+                    let mut cx = Context::from_waker(&self.task.1);
+
+                    if self
+                        .closing
+                        .as_mut()
+                        .unwrap()
+                        .as_mut()
+                        .poll(&mut cx)
+                        .is_ready()
+                    {
+                        assert!(self.close_complete, "{:?}: closed early", events);
+                        self.closing = None;
+                        self.closed = true;
+                    }
+                }
+                CompleteClose => {
+                    let close = self.close_op().unwrap();
+
+                    // Do what the kernel would have done
+                    unsafe { libc::close(self.raw) };
+                    self.driver.complete(close, Ok(0));
+                    self.close_complete = true;
+
+                    if self.handle.is_none() && self.closing.is_none() && !self.polled {
+                        // Closed on drop, nobody is waiting
+                        self.closed = true;
+                    }
+                }
+                DropHandle => self.handle = None,
+            }
+
+            if self.close_op().is_some() {
+                for i in 0..2 {
+                    // Reads hold the FD until they are both completed and
+                    // dropped.
+                    let released = self.completed[i] && self.reads[i].is_none();
+                    assert!(released, "{:?}: closed with read in flight", events);
+                }
+            }
+        }
+
+        fn enabled(&self) -> Vec<CloseEvent> {
+            use CloseEvent::*;
+
+            let mut enabled = vec![];
+
+            for i in 0..2 {
+                if !self.completed[i] {
+                    enabled.push(CompleteRead(i));
+                }
+                if self.reads[i].is_some() {
+                    enabled.push(DropRead(i));
+                }
+            }
+
+            // The executor only polls a task again once it has been notified
+            if self.closing.is_some() && (!self.polled || self.notified()) {
+                enabled.push(PollClose);
+            }
+
+            if self.close_op().is_some() && !self.close_complete {
+                enabled.push(CompleteClose);
+            }
+
+            if self.handle.is_some() {
+                enabled.push(DropHandle);
+            }
+
+            enabled
+        }
+
+        /// Force the remaining events, checking the close future is notified
+        /// whenever it can make progress.
+        fn finish(&mut self) {
+            use CloseEvent::*;
+
+            let events = self.events;
+
+            for i in 0..2 {
+                if !self.completed[i] {
+                    self.apply(CompleteRead(i));
+                }
+                if self.reads[i].is_some() {
+                    self.apply(DropRead(i));
+                }
+            }
+
+            if self.handle.is_some() {
+                self.apply(DropHandle);
+            }
+
+            if self.closing.is_some() {
+                if self.polled && self.close_op().is_none() {
+                    assert!(self.notified(), "{:?}: reads released silently", events);
+                }
+                self.apply(PollClose);
+            }
+
+            if !self.close_complete {
+                assert!(self.close_op().is_some(), "{:?}: never closed", events);
+                self.apply(CompleteClose);
+            }
+
+            if self.closing.is_some() {
+                assert!(self.notified(), "{:?}: close completed silently", events);
+                self.apply(PollClose);
+            }
+        }
+    }
+
+    fn run_close(explicit: bool, events: &[CloseEvent], finish: bool) -> Vec<CloseEvent> {
+        let driver = Driver::new_model().unwrap();
+
+        driver.with(|| {
+            let raw = std::fs::File::open("/dev/null").unwrap().into_raw_fd();
+            let fd = SharedFd::new(raw);
+
+            let reads: Vec<_> = (0..2)
+                .map(|_| Some(Op::read_at(&fd, vec![0; 1], 0).unwrap()))
+                .collect();
+
+            let mut model = CloseModel {
+                events,
+                driver: &driver,
+                raw,
+                indices: reads
+                    .iter()
+                    .map(|op| op.as_ref().unwrap().index())
+                    .collect(),
+                reads,
+                completed: [false; 2],
+                task: waker(),
+                polled: false,
+                seen: 0,
+                closing: None,
+                handle: None,
+                close_complete: false,
+                closed: false,
+            };
+
+            if explicit {
+                model.closing = Some(Box::pin(fd.close()));
+            } else {
+                model.handle = Some(fd);
+            }
+
+            for &event in events {
+                model.apply(event);
+            }
+
+            let enabled = model.enabled();
+
+            if finish || enabled.is_empty() {
+                model.finish();
+
+                assert!(model.closed, "{:?}: close did not complete", events);
+                assert_eq!(driver.num_operations(), 0, "{:?}: op leaked", events);
+            }
+
+            enabled
+        })
+    }
+
+    #[test]
+    fn shared_fd_explicit_close() {
+        let paths = explore(&mut vec![], &|events: &[CloseEvent], finish| {
+            run_close(true, events, finish)
+        });
+        assert!(paths > 100);
+    }
+
+    #[test]
+    fn shared_fd_close_on_drop() {
+        let paths = explore(&mut vec![], &|events: &[CloseEvent], finish| {
+            run_close(false, events, finish)
+        });
+        assert!(paths > 100);
+    }
+}
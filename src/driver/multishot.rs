@@ -0,0 +1,78 @@
+use crate::driver::{self, op::Lifecycle, Op};
+
+use io_uring::{cqueue, squeue};
+use std::collections::VecDeque;
+use std::io;
+use std::task::{Context, Poll};
+
+impl<T> Op<T> {
+    /// Submit a multishot operation, posting completions until one comes
+    /// without `IORING_CQE_F_MORE`, see `poll_next_shot`.
+    pub(super) fn submit_multishot_with<F>(data: T, f: F) -> io::Result<Op<T>>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+    {
+        driver::CURRENT.with(|inner_rc| {
+            let inner_ref = inner_rc.borrow_mut();
+
+            if inner_ref.forked() {
+                return Err(driver::fork::inherited());
+            }
+
+            let op = Op::push_with(data, f, inner_ref, inner_rc)?;
+
+            // Tracked as multishot before any completion is processed. An
+            // injected fault already completed the operation.
+            let mut inner = inner_rc.borrow_mut();
+            let lifecycle = inner.ops.get_mut(op.index).expect("invalid internal state");
+            *lifecycle = match std::mem::replace(lifecycle, Lifecycle::Submitted) {
+                Lifecycle::Completed(result, flags) => {
+                    Lifecycle::Multishot(VecDeque::from(vec![(result, flags)]), None)
+                }
+                _ => Lifecycle::Multishot(VecDeque::new(), None),
+            };
+
+            // See `submit_with`
+            let _ = inner.submit();
+            drop(inner);
+            Ok(op)
+        })
+    }
+
+    /// Poll the next completion of a multishot operation, returning its
+    /// result and flags, or `None` once the last one was returned.
+    pub(crate) fn poll_next_shot(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(io::Result<u32>, u32)>> {
+        let mut inner = self.driver.borrow_mut();
+        let lifecycle = match inner.ops.get_mut(self.index) {
+            Some(lifecycle) => lifecycle,
+            None => return Poll::Ready(None),
+        };
+
+        let completions = match lifecycle {
+            Lifecycle::Multishot(completions, waker) => match completions.pop_front() {
+                Some(completion) => completion,
+                None => {
+                    match waker {
+                        Some(waker) if waker.will_wake(cx.waker()) => {}
+                        _ => *waker = Some(cx.waker().clone()),
+                    }
+                    return Poll::Pending;
+                }
+            },
+            _ => unreachable!("not a multishot operation"),
+        };
+
+        if !cqueue::more(completions.1) {
+            inner.ops.remove(self.index);
+            self.index = usize::MAX;
+
+            // Make room for the tasks waiting for it
+            inner.permits.wake_all();
+        }
+
+        Poll::Ready(Some(completions))
+    }
+}
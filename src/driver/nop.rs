@@ -0,0 +1,15 @@
+use crate::driver::Op;
+
+use std::io;
+
+/// No operation. Completes as soon as the kernel processes the submission.
+pub(crate) struct Nop;
+
+impl Op<Nop> {
+    /// Submit a no-op, failing if called off runtime.
+    pub(crate) fn nop() -> io::Result<Op<Nop>> {
+        use io_uring::opcode;
+
+        Op::try_submit_with(Nop, |_| opcode::Nop::new().build())
+    }
+}
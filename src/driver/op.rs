@@ -1,13 +1,18 @@
-use std::cell::RefCell;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::future::Future;
 use std::io;
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll, Waker};
 
-use io_uring::squeue;
+use io_uring::{cqueue, squeue};
 
 use crate::driver;
+use crate::driver::recycle::{self, OrphanBuf};
+use crate::OpKind;
 
 /// In-flight operation
 pub(crate) struct Op<T: 'static> {
@@ -19,6 +24,26 @@ pub(crate) struct Op<T: 'static> {
 
     // Per-operation data
     data: Option<T>,
+
+    // Extracts the buffer of the data, for its recycler
+    into_buf: Option<IntoBuf>,
+}
+
+/// Extracts the buffer of type-erased operation data.
+type IntoBuf = fn(Box<dyn Any>) -> Option<Box<dyn Any>>;
+
+/// Operations awaited together, woken once all of them completed.
+pub(crate) struct Group {
+    pending: Cell<usize>,
+    waker: RefCell<Option<Waker>>,
+}
+
+/// Waits for a batch of operations, see `Op::join_all`.
+pub(crate) struct JoinAll<T: 'static> {
+    ops: Vec<Op<T>>,
+
+    /// Set once the operations in flight joined the group
+    group: Option<Rc<Group>>,
 }
 
 /// Operation completion. Returns stored state with the result of the operation.
@@ -38,21 +63,36 @@ pub(crate) enum Lifecycle {
     /// The submitter is waiting for the completion of the operation
     Waiting(Waker),
 
+    /// The submitter is waiting for the completion of a group of operations,
+    /// including this one
+    Grouped(Rc<Group>),
+
     /// The submitter no longer has interest in the operation result. The state
     /// must be passed to the driver and held until the operation completes.
-    Ignored(Box<dyn std::any::Any>),
+    /// Its buffer, if any, is then passed to the recycler of its type.
+    Ignored(Box<dyn Any>, Option<IntoBuf>),
 
     /// The operation has completed.
     Completed(io::Result<u32>, u32),
+
+    /// A multishot operation, posting completions until one comes without
+    /// `IORING_CQE_F_MORE`. Holds the completions not consumed yet, and the
+    /// task waiting for the next one.
+    Multishot(VecDeque<(io::Result<u32>, u32)>, Option<Waker>),
 }
 
 impl<T> Op<T> {
     /// Create a new operation
-    fn new(data: T, inner: &mut driver::Inner, inner_rc: &Rc<RefCell<driver::Inner>>) -> Op<T> {
+    pub(super) fn new(
+        data: T,
+        inner: &mut driver::Inner,
+        inner_rc: &Rc<RefCell<driver::Inner>>,
+    ) -> Op<T> {
         Op {
             driver: inner_rc.clone(),
             index: inner.ops.insert(),
             data: Some(data),
+            into_buf: None,
         }
     }
 
@@ -61,40 +101,343 @@ impl<T> Op<T> {
     /// `state` is stored during the operation tracking any state submitted to
     /// the kernel.
     pub(super) fn submit_with<F>(data: T, f: F) -> io::Result<Op<T>>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+    {
+        Op::submit_or_return(data, f).map_err(|(e, _)| e)
+    }
+
+    /// Like `submit_with`, returning the data along with the error if the
+    /// operation could not be pushed, for the buffer of a `BufResult`.
+    pub(super) fn submit_or_return<F>(data: T, f: F) -> Result<Op<T>, (io::Error, T)>
     where
         F: FnOnce(&mut T) -> squeue::Entry,
     {
         driver::CURRENT.with(|inner_rc| {
-            let mut inner_ref = inner_rc.borrow_mut();
+            let inner_ref = inner_rc.borrow_mut();
+
+            if inner_ref.forked() {
+                return Err((driver::fork::inherited(), data));
+            }
+
+            let op = Op::push_or_return(data, f, inner_ref, inner_rc)?;
+
+            // Submit the new operation. At this point, the operation has been
+            // pushed onto the queue and the tail pointer has been updated, so
+            // the submission entry is visible to the kernel. If there is an
+            // error here (probably EAGAIN), we still return the operation. A
+            // future `io_uring_enter` will fully submit the event.
+            let _ = inner_rc.borrow_mut().submit();
+            Ok(op)
+        })
+    }
+
+    /// Submit a batch of operations, with a single `io_uring_enter` unless
+    /// the submission queue fills up. The SQE of each operation is built by
+    /// `f` from its data and an argument, such as its offset.
+    pub(super) fn submit_all<A, F>(data: Vec<(T, A)>, mut f: F) -> io::Result<Vec<Op<T>>>
+    where
+        F: FnMut(&mut T, A) -> squeue::Entry,
+    {
+        driver::CURRENT.with(|inner_rc| {
+            if inner_rc.borrow().forked() {
+                return Err(driver::fork::inherited());
+            }
+
+            let mut ops = Vec::with_capacity(data.len());
+            for (data, arg) in data {
+                let inner_ref = inner_rc.borrow_mut();
+                ops.push(Op::push_with(
+                    data,
+                    |data| f(data, arg),
+                    inner_ref,
+                    inner_rc,
+                )?);
+            }
+
+            // See `submit_with`
+            let _ = inner_rc.borrow_mut().submit();
+            Ok(ops)
+        })
+    }
+
+    /// Push an operation onto the submission queue, without submitting it.
+    pub(super) fn push_with<F>(
+        data: T,
+        f: F,
+        inner_ref: std::cell::RefMut<'_, driver::Inner>,
+        inner_rc: &Rc<RefCell<driver::Inner>>,
+    ) -> io::Result<Op<T>>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+    {
+        Op::push_or_return(data, f, inner_ref, inner_rc).map_err(|(e, _)| e)
+    }
+
+    /// Like `push_with`, returning the data along with the error if the
+    /// operation could not be pushed.
+    fn push_or_return<F>(
+        data: T,
+        f: F,
+        mut inner_ref: std::cell::RefMut<'_, driver::Inner>,
+        inner_rc: &Rc<RefCell<driver::Inner>>,
+    ) -> Result<Op<T>, (io::Error, T)>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+    {
+        {
             let inner = &mut *inner_ref;
 
             // If the submission queue is full, flush it to the kernel
             if inner.uring.submission().is_full() {
-                inner.submit()?;
+                if let Err(e) = inner.submit() {
+                    return Err((e, data));
+                }
             }
 
-            // Create the operation
+            // Create the operation, if the slab has room for it
+            if let Err(e) = inner.check_room(1) {
+                return Err((e, data));
+            }
             let mut op = Op::new(data, inner, inner_rc);
 
-            // Configure the SQE
-            let sqe = f(op.data.as_mut().unwrap()).user_data(op.index as _);
+            // Configure the SQE. This calls into the buffer, which may panic:
+            // the operation is discarded before unwinding then, as dropping
+            // it borrows the driver.
+            let sqe = panic::catch_unwind(AssertUnwindSafe(|| f(op.data.as_mut().unwrap())));
+            let mut sqe = match sqe {
+                Ok(sqe) => sqe.user_data(op.index as _),
+                Err(payload) => {
+                    inner.ops.remove(op.index);
+                    op.index = usize::MAX;
+                    drop(inner_ref);
+                    drop(op);
+                    panic::resume_unwind(payload);
+                }
+            };
+
+            if let Some(id) = inner.personality {
+                sqe = sqe.personality(id);
+            }
+            inner.set_kind(op.index, OpKind::from(driver::sqe::raw(&sqe).opcode));
+
+            #[cfg(test)]
+            if let Some(model) = &mut inner.model {
+                model.push(op.index, &sqe);
+                return Ok(op);
+            }
+
+            #[cfg(debug_assertions)]
+            if let Err(mistake) = driver::audit::audit(&sqe) {
+                // Discard the operation before panicking, it never reaches
+                // the kernel
+                let _ = inner
+                    .ops
+                    .complete(op.index, Err(io::ErrorKind::Other.into()), 0);
+                drop(inner_ref);
+                drop(op);
+                panic!("{}", mistake);
+            }
+
+            #[cfg(feature = "test-util")]
+            match inner.faults.intercept(&mut sqe, inner.pushed) {
+                Some(driver::fault::Intercept::Fail(err)) => {
+                    // Complete the operation without involving the kernel
+                    let _ = inner.ops.complete(op.index, Err(err), 0);
+                    return Ok(op);
+                }
+                Some(driver::fault::Intercept::Delay(timeout)) => {
+                    // The timeout and the operation must be submitted in the
+                    // same batch for the link to apply.
+                    if let Err(e) = inner.make_room(2) {
+                        // Discard the operation, it never reaches the kernel
+                        inner.ops.remove(op.index);
+                        op.index = usize::MAX;
+                        drop(inner_ref);
+                        return Err((e, op.data.take().unwrap()));
+                    }
+
+                    let mut sq = inner.uring.submission();
+
+                    if unsafe { sq.push(&timeout).is_err() } {
+                        unreachable!("room was made for the timeout and the operation");
+                    }
+                    drop(sq);
+                    inner.track_pushed(u64::MAX);
+                }
+                None => {}
+            }
 
             {
+                // The fd and the buffer are rewritten in the entry pushed
+                // only, the others track the operation as submitted
+                let mut pushed = sqe.clone();
+                if let Some(files) = &mut inner.files {
+                    let head = inner
+                        .pushed
+                        .wrapping_sub(inner.uring.submission().len() as u32);
+                    files.fix(&inner.uring, head, inner.pushed, &mut pushed);
+                }
+                driver::use_registered_buf(&inner.registered_bufs, &mut pushed);
+
                 let mut sq = inner.uring.submission();
 
                 // Push the new operation
-                if unsafe { sq.push(&sqe).is_err() } {
+                if unsafe { sq.push(&pushed).is_err() } {
                     unimplemented!("when is this hit?");
                 }
             }
+            inner.track_pushed(op.index as u64);
+
+            #[cfg(feature = "bench-internals")]
+            inner.stats.pushed(op.index);
+
+            #[cfg(feature = "metrics")]
+            inner.latencies.pushed(op.index, &sqe);
+
+            if let Some(watchdog) = &mut inner.watchdog {
+                watchdog.pushed(op.index, &sqe);
+            }
 
-            // Submit the new operation. At this point, the operation has been
-            // pushed onto the queue and the tail pointer has been updated, so
-            // the submission entry is visible to the kernel. If there is an
-            // error here (probably EAGAIN), we still return the operation. A
-            // future `io_uring_enter` will fully submit the event.
-            let _ = inner.submit();
             Ok(op)
+        }
+    }
+
+    /// Submit two operations linked with `IOSQE_IO_LINK`: the second one
+    /// starts once the first one completed successfully, and completes with
+    /// `ECANCELED` otherwise.
+    ///
+    /// Both entries are pushed in the same batch for the link to apply. Fault
+    /// injection does not apply to linked operations.
+    pub(super) fn submit_linked<U: 'static, F, G>(
+        first: T,
+        f: F,
+        second: U,
+        g: G,
+    ) -> io::Result<(Op<T>, Op<U>)>
+    where
+        F: FnOnce(&mut T) -> squeue::Entry,
+        G: FnOnce(&mut U) -> squeue::Entry,
+    {
+        driver::CURRENT.with(|inner_rc| {
+            let mut inner_ref = inner_rc.borrow_mut();
+            let inner = &mut *inner_ref;
+
+            if inner.forked() {
+                return Err(driver::fork::inherited());
+            }
+
+            // Make room for both entries
+            inner.make_room(2)?;
+            inner.check_room(2)?;
+
+            let mut first = Op::new(first, inner, inner_rc);
+            let mut second = Op::new(second, inner, inner_rc);
+
+            // See `push_with`
+            let sqes = panic::catch_unwind(AssertUnwindSafe(|| {
+                (
+                    f(first.data.as_mut().unwrap()),
+                    g(second.data.as_mut().unwrap()),
+                )
+            }));
+            let (first_sqe, second_sqe) = match sqes {
+                Ok(sqes) => sqes,
+                Err(payload) => {
+                    inner.ops.remove(first.index);
+                    inner.ops.remove(second.index);
+                    first.index = usize::MAX;
+                    second.index = usize::MAX;
+                    drop(inner_ref);
+                    drop((first, second));
+                    panic::resume_unwind(payload);
+                }
+            };
+
+            let mut first_sqe = first_sqe
+                .user_data(first.index as _)
+                .flags(squeue::Flags::IO_LINK);
+            let mut second_sqe = second_sqe.user_data(second.index as _);
+
+            if let Some(id) = inner.personality {
+                first_sqe = first_sqe.personality(id);
+                second_sqe = second_sqe.personality(id);
+            }
+            inner.set_kind(
+                first.index,
+                OpKind::from(driver::sqe::raw(&first_sqe).opcode),
+            );
+            inner.set_kind(
+                second.index,
+                OpKind::from(driver::sqe::raw(&second_sqe).opcode),
+            );
+
+            #[cfg(test)]
+            if let Some(model) = &mut inner.model {
+                model.push(first.index, &first_sqe);
+                model.push(second.index, &second_sqe);
+                return Ok((first, second));
+            }
+
+            #[cfg(debug_assertions)]
+            if let Err(mistake) =
+                driver::audit::audit(&first_sqe).and_then(|_| driver::audit::audit(&second_sqe))
+            {
+                // See `submit_with`
+                let _ = inner
+                    .ops
+                    .complete(first.index, Err(io::ErrorKind::Other.into()), 0);
+                let _ = inner
+                    .ops
+                    .complete(second.index, Err(io::ErrorKind::Other.into()), 0);
+                drop(inner_ref);
+                drop((first, second));
+                panic!("{}", mistake);
+            }
+
+            {
+                // See `push_with`
+                let mut pushed = (first_sqe.clone(), second_sqe.clone());
+                if let Some(files) = &mut inner.files {
+                    let head = inner
+                        .pushed
+                        .wrapping_sub(inner.uring.submission().len() as u32);
+                    files.fix(&inner.uring, head, inner.pushed, &mut pushed.0);
+                    let pos = inner.pushed.wrapping_add(1);
+                    files.fix(&inner.uring, head, pos, &mut pushed.1);
+                }
+                driver::use_registered_buf(&inner.registered_bufs, &mut pushed.0);
+                driver::use_registered_buf(&inner.registered_bufs, &mut pushed.1);
+
+                let mut sq = inner.uring.submission();
+
+                if unsafe { sq.push(&pushed.0).is_err() || sq.push(&pushed.1).is_err() } {
+                    unreachable!("room was made for both entries");
+                }
+            }
+            inner.track_pushed(first.index as u64);
+            inner.track_pushed(second.index as u64);
+
+            #[cfg(feature = "bench-internals")]
+            {
+                inner.stats.pushed(first.index);
+                inner.stats.pushed(second.index);
+            }
+
+            #[cfg(feature = "metrics")]
+            {
+                inner.latencies.pushed(first.index, &first_sqe);
+                inner.latencies.pushed(second.index, &second_sqe);
+            }
+
+            if let Some(watchdog) = &mut inner.watchdog {
+                watchdog.pushed(first.index, &first_sqe);
+                watchdog.pushed(second.index, &second_sqe);
+            }
+
+            // See `submit_with`
+            let _ = inner.submit();
+            Ok((first, second))
         })
     }
 
@@ -111,6 +454,136 @@ impl<T> Op<T> {
     }
 }
 
+impl<T: OrphanBuf> Op<T> {
+    /// Pass the buffer of the operation to the recycler of its type if the
+    /// operation is dropped while in flight.
+    pub(super) fn recycle_orphan_buf(mut self) -> Op<T> {
+        self.into_buf = Some(recycle::into_buf::<T>);
+        self
+    }
+}
+
+impl<T: OrphanBuf> JoinAll<T> {
+    /// See `Op::recycle_orphan_buf`.
+    pub(super) fn recycle_orphan_bufs(mut self) -> JoinAll<T> {
+        self.ops = self.ops.into_iter().map(Op::recycle_orphan_buf).collect();
+        self
+    }
+}
+
+impl<T: Unpin + 'static> Op<T> {
+    /// Request the cancellation of the operation, and wait for its completion.
+    ///
+    /// The operation completes with `ECANCELED` if it was canceled, or with
+    /// its own result if it completed before the cancellation reached it.
+    /// Either way, the state submitted to the kernel is returned.
+    pub(crate) async fn cancel_and_wait(self) -> Completion<T> {
+        self.request_cancel();
+        self.await
+    }
+
+    /// Request the cancellation of the operation, without waiting for it.
+    /// Dropped then, the operation completes in the background, without
+    /// holding its resources until it completes on its own.
+    pub(crate) fn request_cancel(&self) {
+        let mut inner = self.driver.borrow_mut();
+
+        let completed = matches!(
+            inner.ops.get_mut(self.index),
+            Some(Lifecycle::Completed(..))
+        );
+
+        // If the cancellation cannot be submitted, the operation still
+        // completes on its own.
+        if !completed && inner.push_cancel(self.index).is_ok() {
+            let _ = inner.submit();
+        }
+    }
+}
+
+impl<T: Unpin + 'static> Op<T> {
+    /// Submit a batch of operations, and wait for all of them to complete.
+    ///
+    /// The batch is submitted with a single `io_uring_enter`, and the task is
+    /// woken once, when the last operation completes, instead of once per
+    /// operation. The completions are returned in the order of `data`.
+    pub(crate) fn join_all<A, F>(data: Vec<(T, A)>, f: F) -> io::Result<JoinAll<T>>
+    where
+        F: FnMut(&mut T, A) -> squeue::Entry,
+    {
+        Ok(JoinAll {
+            ops: Op::submit_all(data, f)?,
+            group: None,
+        })
+    }
+}
+
+impl Group {
+    fn completed(&self) {
+        let pending = self.pending.get() - 1;
+        self.pending.set(pending);
+
+        if pending == 0 {
+            if let Some(waker) = self.waker.borrow_mut().take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T: Unpin + 'static> Future for JoinAll<T> {
+    type Output = Vec<Completion<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = &mut *self;
+
+        let group = match &me.group {
+            Some(group) => group,
+            None => {
+                let group = Rc::new(Group {
+                    pending: Cell::new(0),
+                    waker: RefCell::new(None),
+                });
+
+                if let Some(op) = me.ops.first() {
+                    let mut inner = op.driver.borrow_mut();
+                    for op in &me.ops {
+                        let lifecycle =
+                            inner.ops.get_mut(op.index).expect("invalid internal state");
+                        if let Lifecycle::Submitted = lifecycle {
+                            *lifecycle = Lifecycle::Grouped(group.clone());
+                            group.pending.set(group.pending.get() + 1);
+                        }
+                    }
+                }
+
+                me.group.insert(group)
+            }
+        };
+
+        if group.pending.get() > 0 {
+            let mut waker = group.waker.borrow_mut();
+            match &*waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                _ => *waker = Some(cx.waker().clone()),
+            }
+            return Poll::Pending;
+        }
+
+        let completions = me
+            .ops
+            .iter_mut()
+            .map(|op| match Pin::new(op).poll(cx) {
+                Poll::Ready(completion) => completion,
+                Poll::Pending => unreachable!("invalid operation state"),
+            })
+            .collect();
+        me.ops.clear();
+
+        Poll::Ready(completions)
+    }
+}
+
 impl<T> Future for Op<T>
 where
     T: Unpin + 'static,
@@ -137,11 +610,16 @@ where
                 *lifecycle = Lifecycle::Waiting(waker);
                 Poll::Pending
             }
-            Lifecycle::Ignored(..) => unreachable!(),
+            Lifecycle::Ignored(..) | Lifecycle::Grouped(_) | Lifecycle::Multishot(..) => {
+                unreachable!()
+            }
             Lifecycle::Completed(result, flags) => {
                 inner.ops.remove(me.index);
                 me.index = usize::MAX;
 
+                // Make room for the tasks waiting for it
+                inner.permits.wake_all();
+
                 Poll::Ready(Completion {
                     data: me.data.take().expect("unexpected operation state"),
                     result,
@@ -154,18 +632,36 @@ where
 
 impl<T> Drop for Op<T> {
     fn drop(&mut self) {
-        let mut inner = self.driver.borrow_mut();
+        let mut inner = match self.driver.try_borrow_mut() {
+            Ok(inner) => inner,
+            // Unwinding from a panic raised while the driver was borrowed. A
+            // second panic would abort the process: the state of the
+            // operation is leaked instead, as the kernel may still use it.
+            Err(_) if std::thread::panicking() => {
+                std::mem::forget(self.data.take());
+                return;
+            }
+            Err(e) => panic!("{}", e),
+        };
         let lifecycle = match inner.ops.get_mut(self.index) {
             Some(lifecycle) => lifecycle,
             None => return,
         };
 
         match lifecycle {
-            Lifecycle::Submitted | Lifecycle::Waiting(_) => {
-                *lifecycle = Lifecycle::Ignored(Box::new(self.data.take()));
+            Lifecycle::Submitted | Lifecycle::Waiting(_) | Lifecycle::Grouped(_) => {
+                *lifecycle = Lifecycle::Ignored(Box::new(self.data.take()), self.into_buf);
             }
             Lifecycle::Completed(..) => {
                 inner.ops.remove(self.index);
+                inner.permits.wake_all();
+            }
+            Lifecycle::Multishot(..) if lifecycle.is_finished() => {
+                inner.ops.remove(self.index);
+                inner.permits.wake_all();
+            }
+            Lifecycle::Multishot(..) => {
+                *lifecycle = Lifecycle::Ignored(Box::new(self.data.take()), self.into_buf);
             }
             Lifecycle::Ignored(..) => unreachable!(),
         }
@@ -186,10 +682,49 @@ impl Lifecycle {
                 waker.wake();
                 false
             }
-            Lifecycle::Ignored(..) => true,
+            Lifecycle::Grouped(group) => {
+                *self = Lifecycle::Completed(result, flags);
+                group.completed();
+                false
+            }
+            Lifecycle::Ignored(data, into_buf) => {
+                // The caller removes the operation and drops the data, once
+                // a multishot operation posted its last completion.
+                *self = Lifecycle::Ignored(data, into_buf);
+                !cqueue::more(flags)
+            }
+            Lifecycle::Multishot(mut completions, waker) => {
+                completions.push_back((result, flags));
+                if let Some(waker) = &waker {
+                    waker.wake_by_ref();
+                }
+                *self = Lifecycle::Multishot(completions, waker);
+                false
+            }
             Lifecycle::Completed(..) => unreachable!("invalid operation state"),
         }
     }
+
+    /// Returns `true` once the kernel posted the last completion of the
+    /// operation.
+    pub(super) fn is_finished(&self) -> bool {
+        match self {
+            Lifecycle::Completed(..) => true,
+            Lifecycle::Multishot(completions, _) => completions
+                .back()
+                .is_some_and(|(_, flags)| !cqueue::more(*flags)),
+            _ => false,
+        }
+    }
+
+    /// Extracts the buffer of an ignored operation, dropping the rest of its
+    /// state.
+    pub(super) fn into_orphan_buf(self) -> Option<Box<dyn Any>> {
+        match self {
+            Lifecycle::Ignored(data, Some(into_buf)) => into_buf(data),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -311,6 +846,79 @@ mod test {
         release(driver);
     }
 
+    #[test]
+    fn leaked_op_drained_on_shutdown() {
+        let (op, driver, data) = init();
+        complete(&op, Ok(1));
+
+        // The slot stays in the slab, and the driver does not wait for it
+        std::mem::forget(op);
+        assert_eq!(1, driver.num_operations());
+        assert_eq!(0, driver.num_pending());
+
+        let inner = driver.inner.clone();
+        drop(driver);
+        assert!(inner.borrow().ops.0.is_empty());
+
+        // The data is held by the leaked future
+        assert_eq!(2, Rc::strong_count(&data));
+    }
+
+    #[test]
+    fn linked_op_canceled_on_failure() {
+        use io_uring::{opcode, types};
+
+        crate::start(async {
+            // The first operation fails on a bad fd, canceling the second one
+            let (first, second) = Op::submit_linked(
+                (),
+                |_| opcode::Fsync::new(types::Fd(-1)).build(),
+                (),
+                |_| opcode::Nop::new().build(),
+            )
+            .unwrap();
+
+            let err = first.await.result.unwrap_err();
+            assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+
+            let err = second.await.result.unwrap_err();
+            assert_eq!(err.raw_os_error(), Some(libc::ECANCELED));
+        });
+    }
+
+    #[test]
+    fn join_all_wakes_once() {
+        let (first, driver, data) = init();
+        let [second, third] = [(), ()].map(|_| {
+            let mut inner = driver.inner.borrow_mut();
+            Op::new(data.clone(), &mut inner, &driver.inner)
+        });
+        complete(&first, Ok(1));
+
+        let mut join = task::spawn(JoinAll {
+            ops: vec![first, second, third],
+            group: None,
+        });
+        assert_pending!(join.poll());
+
+        // Only the completion of the last operation wakes the task
+        complete(&join.ops[1], Ok(2));
+        assert!(!join.is_woken());
+        complete(&join.ops[2], Ok(3));
+        assert!(join.is_woken());
+
+        let completions = assert_ready!(join.poll());
+        let results: Vec<_> = completions
+            .iter()
+            .map(|c| *c.result.as_ref().unwrap())
+            .collect();
+        assert_eq!(results, [1, 2, 3]);
+        assert_eq!(0, driver.num_operations());
+
+        drop(completions);
+        assert_eq!(1, Rc::strong_count(&data));
+    }
+
     fn init() -> (Op<Rc<()>>, crate::driver::Driver, Rc<()>) {
         use crate::driver::Driver;
 
@@ -0,0 +1,201 @@
+use crate::driver::{self, op, Handle, Inner, SharedFd};
+use crate::Backpressure;
+
+use std::io;
+use std::task::{Context, Poll, Waker};
+
+/// Permits bounding the operations of the tasks holding them by the capacity
+/// of the ring.
+#[derive(Default)]
+pub(super) struct Permits {
+    /// Number of permits held
+    held: usize,
+
+    /// Tasks waiting for a permit, or for room in the slab
+    waiters: Vec<Waker>,
+}
+
+/// A permit held by a task, released when dropped.
+pub(crate) struct OpPermit {
+    driver: Handle,
+}
+
+impl OpPermit {
+    /// Acquires a permit once fewer than `limit` permits are held, and fewer
+    /// than `limit` operations are in flight.
+    pub(crate) fn poll_acquire(cx: &mut Context<'_>) -> Poll<io::Result<OpPermit>> {
+        if !driver::CURRENT.is_set() {
+            return Poll::Ready(Err(io::ErrorKind::Other.into()));
+        }
+
+        driver::CURRENT.with(|inner_rc| {
+            let mut inner = inner_rc.borrow_mut();
+            let limit = inner.permit_limit();
+
+            if inner.permits.held < limit && inner.num_in_flight() < limit {
+                inner.permits.held += 1;
+                return Poll::Ready(Ok(OpPermit {
+                    driver: inner_rc.clone(),
+                }));
+            }
+
+            inner.permits.wait(cx);
+            Poll::Pending
+        })
+    }
+
+    /// Maximum number of permits, and of operations in flight for a permit
+    /// to be acquired.
+    pub(crate) fn limit(&self) -> usize {
+        self.driver.borrow().permit_limit()
+    }
+}
+
+impl Drop for OpPermit {
+    fn drop(&mut self) {
+        let mut inner = self.driver.borrow_mut();
+        inner.permits.held -= 1;
+        inner.permits.wake_all();
+    }
+}
+
+/// Waits for room for another operation in flight on `fd`, or fails,
+/// according to the backpressure of the driver, see `Builder::max_ops`,
+/// `Builder::max_ops_per_fd` and `Builder::busy_retries`.
+///
+/// The operation must be submitted right after, in the same poll, for the
+/// room not to be taken by another task.
+pub(crate) async fn room(fd: &SharedFd) -> io::Result<()> {
+    crate::future::poll_fn(|cx| poll_room(fd, cx)).await
+}
+
+pub(crate) fn poll_room(fd: &SharedFd, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    // Outside of a runtime, the operation fails on its own
+    if !driver::CURRENT.is_set() {
+        return Poll::Ready(Ok(()));
+    }
+
+    driver::CURRENT.with(|inner_rc| {
+        let mut inner = inner_rc.borrow_mut();
+
+        if let Some((limit, backpressure)) = inner.max_ops_per_fd {
+            if fd.poll_ops_below(limit, cx).is_pending() {
+                return match backpressure {
+                    Backpressure::Fail => Poll::Ready(Err(fd_ops_full())),
+                    Backpressure::Wait => Poll::Pending,
+                };
+            }
+        }
+
+        // The kernel is busy, until the deferred submission goes through
+        if inner.deferred {
+            return match inner.busy_backpressure {
+                Backpressure::Fail => Poll::Ready(Err(io::Error::from_raw_os_error(libc::EBUSY))),
+                Backpressure::Wait => {
+                    inner.permits.wait(cx);
+                    Poll::Pending
+                }
+            };
+        }
+
+        if inner.ops.0.len() < inner.max_ops {
+            return Poll::Ready(Ok(()));
+        }
+
+        match inner.backpressure {
+            Backpressure::Fail => Poll::Ready(Err(ops_full())),
+            Backpressure::Wait => {
+                inner.permits.wait(cx);
+                Poll::Pending
+            }
+        }
+    })
+}
+
+/// Waits for room for another operation in the driver, whatever its
+/// backpressure, for the operations which wait rather than fail, such as
+/// timers.
+pub(crate) fn poll_any_room(cx: &mut Context<'_>) -> Poll<()> {
+    if !driver::CURRENT.is_set() {
+        return Poll::Ready(());
+    }
+
+    driver::CURRENT.with(|inner_rc| {
+        let mut inner = inner_rc.borrow_mut();
+
+        if !inner.deferred && inner.ops.0.len() < inner.max_ops {
+            return Poll::Ready(());
+        }
+
+        inner.permits.wait(cx);
+        Poll::Pending
+    })
+}
+
+impl Permits {
+    /// Registers the task to be woken once a permit or room may be
+    /// available.
+    fn wait(&mut self, cx: &mut Context<'_>) {
+        if !self.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+            self.waiters.push(cx.waker().clone());
+        }
+    }
+
+    /// Wakes the waiting tasks, to check again for an available permit.
+    pub(super) fn wake_all(&mut self) {
+        for waker in self.waiters.drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Number of tasks waiting for a permit, or for room in the slab.
+    pub(super) fn num_waiters(&self) -> usize {
+        self.waiters.len()
+    }
+}
+
+impl Inner {
+    /// The number of completion queue entries: more operations in flight may
+    /// overflow the completion queue. Lowered to the maximum number of
+    /// operations, if bounded.
+    fn permit_limit(&self) -> usize {
+        std::cmp::min(self.uring.params().cq_entries() as usize, self.max_ops)
+    }
+
+    /// Fails unless `count` more operations fit in the slab.
+    pub(super) fn check_room(&self, count: usize) -> io::Result<()> {
+        if self.max_ops.saturating_sub(self.ops.0.len()) < count {
+            return Err(ops_full());
+        }
+
+        Ok(())
+    }
+
+    /// Number of operations not completed by the kernel yet.
+    fn num_in_flight(&self) -> usize {
+        let ops = &self.ops.0;
+
+        // Most of the time, the slab is far from the limit
+        if ops.len() < self.permit_limit() {
+            return ops.len();
+        }
+
+        ops.iter()
+            .filter(|(_, lifecycle)| !matches!(lifecycle, op::Lifecycle::Completed(..)))
+            .count()
+    }
+}
+
+fn ops_full() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::QuotaExceeded,
+        "too many operations in flight",
+    )
+}
+
+fn fd_ops_full() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::QuotaExceeded,
+        "too many operations in flight on the file descriptor",
+    )
+}
@@ -0,0 +1,47 @@
+use crate::driver::{self, Handle};
+
+use std::io;
+
+/// Credentials registered with the ring, unregistered when dropped.
+pub(crate) struct PersonalityRegistration {
+    driver: Handle,
+
+    /// Identifier of the credentials, set in the SQEs
+    pub(crate) id: u16,
+}
+
+impl PersonalityRegistration {
+    /// Registers the credentials of the current thread with the ring of the
+    /// current runtime.
+    pub(crate) fn new() -> io::Result<PersonalityRegistration> {
+        if !driver::CURRENT.is_set() {
+            return Err(io::ErrorKind::Other.into());
+        }
+
+        driver::CURRENT.with(|inner_rc| {
+            let id = inner_rc.borrow().uring.submitter().register_personality()?;
+
+            Ok(PersonalityRegistration {
+                driver: inner_rc.clone(),
+                id,
+            })
+        })
+    }
+}
+
+impl Drop for PersonalityRegistration {
+    fn drop(&mut self) {
+        let inner = self.driver.borrow();
+        let _ = inner.uring.submitter().unregister_personality(self.id);
+    }
+}
+
+/// Sets the personality of the operations submitted until the next call,
+/// returning the previous one.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn set(id: Option<u16>) -> Option<u16> {
+    driver::CURRENT.with(|inner| std::mem::replace(&mut inner.borrow_mut().personality, id))
+}
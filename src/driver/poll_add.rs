@@ -0,0 +1,76 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{room, Op, SharedFd};
+use crate::BufResult;
+
+use std::io;
+
+/// Wait for a file descriptor to become ready.
+pub(crate) struct PollAdd {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+}
+
+impl Op<PollAdd> {
+    /// Submit a one-shot poll for the `POLL*` events in `events`. The
+    /// operation completes with the ready events.
+    pub(crate) fn poll_add(fd: &SharedFd, events: libc::c_short) -> io::Result<Op<PollAdd>> {
+        use io_uring::{opcode, types};
+
+        Op::submit_with(PollAdd { fd: fd.for_op() }, |poll_add| {
+            opcode::PollAdd::new(types::Fd(poll_add.fd.raw_fd()), events as _).build()
+        })
+    }
+}
+
+/// Read from `fd`, which may be in non-blocking mode, waiting for it to
+/// become readable whenever the read would block.
+pub(crate) async fn read_ready<T: IoBufMut>(fd: &SharedFd, mut buf: T) -> BufResult<usize, T> {
+    loop {
+        if let Err(e) = room(fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::read_at(fd, buf, 0).unwrap();
+        let (res, b) = op.read().await;
+
+        match res {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => buf = b,
+            res => return (res, b),
+        }
+
+        if let Err(e) = ready(fd, libc::POLLIN).await {
+            return (Err(e), buf);
+        }
+    }
+}
+
+/// Write to `fd`, which may be in non-blocking mode, waiting for it to
+/// become writable whenever the write would block.
+pub(crate) async fn write_ready<T: IoBuf>(fd: &SharedFd, mut buf: T) -> BufResult<usize, T> {
+    loop {
+        if let Err(e) = room(fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::write_at(fd, buf, 0).unwrap();
+        let (res, b) = op.write().await;
+
+        match res {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => buf = b,
+            res => return (res, b),
+        }
+
+        if let Err(e) = ready(fd, libc::POLLOUT).await {
+            return (Err(e), buf);
+        }
+    }
+}
+
+/// Waits for the `POLL*` events in `events`.
+pub(crate) async fn ready(fd: &SharedFd, events: libc::c_short) -> io::Result<()> {
+    let op = Op::poll_add(fd, events)?;
+    op.await.result?;
+    Ok(())
+}
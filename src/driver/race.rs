@@ -0,0 +1,100 @@
+use crate::driver::{Completion, Op};
+
+use futures_core::Stream;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Waits for the first of a set of operations to complete, see `Op::race`.
+pub(crate) struct Race<T: 'static> {
+    /// Operations in flight, with their index in the set
+    ops: Vec<(usize, Op<T>)>,
+}
+
+/// The operations which lost a race, canceled. Yields their completions, as
+/// they complete.
+pub(crate) struct Losers<T: 'static> {
+    ops: Vec<(usize, Op<T>)>,
+}
+
+impl<T: Unpin + 'static> Op<T> {
+    /// Wait for the first of `ops` to complete, then request the
+    /// cancellation of the others.
+    ///
+    /// Resolves with the index of the winner in `ops`, its completion, and
+    /// the losers, which complete with `ECANCELED`, or with their own result
+    /// if they completed before the cancellation reached them.
+    pub(crate) fn race(ops: Vec<Op<T>>) -> Race<T> {
+        Race {
+            ops: ops.into_iter().enumerate().collect(),
+        }
+    }
+}
+
+impl<T: Unpin + 'static> Future for Race<T> {
+    type Output = (usize, Completion<T>, Losers<T>);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = &mut *self;
+
+        let (index, completion) = ready!(poll_first(&mut me.ops, cx));
+        let losers = std::mem::take(&mut me.ops);
+
+        if let Some((_, op)) = losers.first() {
+            let mut inner = op.driver.borrow_mut();
+
+            // If a cancellation cannot be submitted, the operation still
+            // completes on its own.
+            for (_, op) in &losers {
+                let _ = inner.push_cancel(op.index);
+            }
+            let _ = inner.submit();
+        }
+
+        Poll::Ready((index, completion, Losers { ops: losers }))
+    }
+}
+
+impl<T> Losers<T> {
+    /// Returns the number of losers which did not complete yet.
+    pub(crate) fn len(&self) -> usize {
+        self.ops.len()
+    }
+}
+
+impl<T: Unpin + 'static> Stream for Losers<T> {
+    type Item = (usize, Completion<T>);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = &mut *self;
+
+        if me.ops.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        poll_first(&mut me.ops, cx).map(Some)
+    }
+}
+
+/// Polls the operations, removing and returning the first one which
+/// completed, with its index in the set.
+fn poll_first<T: Unpin + 'static>(
+    ops: &mut Vec<(usize, Op<T>)>,
+    cx: &mut Context<'_>,
+) -> Poll<(usize, Completion<T>)> {
+    let done = ops
+        .iter_mut()
+        .enumerate()
+        .find_map(|(i, (_, op))| match Pin::new(op).poll(cx) {
+            Poll::Ready(completion) => Some((i, completion)),
+            Poll::Pending => None,
+        });
+
+    match done {
+        Some((i, completion)) => {
+            let (index, _) = ops.remove(i);
+            Poll::Ready((index, completion))
+        }
+        None => Poll::Pending,
+    }
+}
@@ -1,5 +1,6 @@
-use crate::buf::IoBufMut;
-use crate::driver::{Op, SharedFd};
+use crate::buf::{FixedBuf, IoBuf, IoBufMut};
+use crate::driver::recycle::OrphanBuf;
+use crate::driver::{Completion, Op, SharedFd};
 use crate::BufResult;
 
 use std::io;
@@ -15,13 +16,32 @@ pub(crate) struct Read<T> {
     pub(crate) buf: T,
 }
 
+impl<T: IoBufMut> OrphanBuf for Read<T> {
+    type Buf = T;
+
+    fn into_buf(self) -> T {
+        self.buf
+    }
+}
+
 impl<T: IoBufMut> Op<Read<T>> {
     pub(crate) fn read_at(fd: &SharedFd, buf: T, offset: u64) -> io::Result<Op<Read<T>>> {
+        Op::read_at_with_flags(fd, buf, offset, 0).map_err(|(e, _)| e)
+    }
+
+    /// Read with the `RWF_*` flags of `preadv2(2)`, returning the buffer
+    /// along with the error if the read cannot be submitted.
+    pub(crate) fn read_at_with_flags(
+        fd: &SharedFd,
+        buf: T,
+        offset: u64,
+        flags: libc::c_int,
+    ) -> Result<Op<Read<T>>, (io::Error, T)> {
         use io_uring::{opcode, types};
 
-        Op::submit_with(
+        Op::submit_or_return(
             Read {
-                fd: fd.clone(),
+                fd: fd.for_op(),
                 buf,
             },
             |read| {
@@ -30,9 +50,43 @@ impl<T: IoBufMut> Op<Read<T>> {
                 let len = read.buf.bytes_total();
                 opcode::Read::new(types::Fd(fd.raw_fd()), ptr, len as _)
                     .offset(offset as _)
+                    .rw_flags(flags)
                     .build()
             },
         )
+        .map_err(|(e, read)| (e, read.buf))
+        .map(Op::recycle_orphan_buf)
+    }
+
+    /// Read into each buffer at its offset, submitting the reads as a batch
+    /// and waiting for all of them.
+    pub(crate) async fn read_all_at(
+        fd: &SharedFd,
+        bufs: Vec<(T, u64)>,
+    ) -> io::Result<Vec<BufResult<usize, T>>> {
+        use io_uring::{opcode, types};
+
+        let reads = bufs
+            .into_iter()
+            .map(|(buf, offset)| {
+                let read = Read {
+                    fd: fd.for_op(),
+                    buf,
+                };
+                (read, offset)
+            })
+            .collect();
+
+        let ops = Op::join_all(reads, |read: &mut Read<T>, offset| {
+            let ptr = read.buf.stable_mut_ptr();
+            let len = read.buf.bytes_total();
+            opcode::Read::new(types::Fd(read.fd.raw_fd()), ptr, len as _)
+                .offset(offset as _)
+                .build()
+        })?
+        .recycle_orphan_bufs();
+
+        Ok(ops.await.into_iter().map(complete_read).collect())
     }
 
     pub(crate) async fn read(mut self) -> BufResult<usize, T> {
@@ -44,20 +98,59 @@ impl<T: IoBufMut> Op<Read<T>> {
         use std::pin::Pin;
 
         let complete = ready!(Pin::new(self).poll(cx));
+        Poll::Ready(complete_read(complete))
+    }
+
+    /// Cancel the read, returning the buffer once the kernel released it.
+    ///
+    /// If the read completed before the cancellation reached it, its result
+    /// is returned instead of `ECANCELED`.
+    pub(crate) async fn cancel_read(self) -> BufResult<usize, T> {
+        complete_read(self.cancel_and_wait().await)
+    }
+}
 
-        // Convert the operation result to `usize`
-        let res = complete.result.map(|v| v as usize);
-        // Recover the buffer
-        let mut buf = complete.data.buf;
-
-        // If the operation was successful, advance the initialized cursor.
-        if let Ok(n) = res {
-            // Safety: the kernel wrote `n` bytes to the buffer.
-            unsafe {
-                buf.set_init(n);
-            }
+pub(crate) fn complete_read<T: IoBufMut>(complete: Completion<Read<T>>) -> BufResult<usize, T> {
+    // Convert the operation result to `usize`
+    let res = complete.result.map(|v| v as usize);
+    // Recover the buffer
+    let mut buf = complete.data.buf;
+
+    // If the operation was successful, advance the initialized cursor.
+    if let Ok(n) = res {
+        // Safety: the kernel wrote `n` bytes to the buffer.
+        unsafe {
+            buf.set_init(n);
         }
+    }
+
+    (res, buf)
+}
 
-        Poll::Ready((res, buf))
+impl Op<Read<FixedBuf>> {
+    /// Read into a registered buffer, `IORING_OP_READ_FIXED`, returning the
+    /// buffer along with the error if the read cannot be submitted.
+    pub(crate) fn read_fixed_at(
+        fd: &SharedFd,
+        buf: FixedBuf,
+        offset: u64,
+    ) -> Result<Op<Read<FixedBuf>>, (io::Error, FixedBuf)> {
+        use io_uring::{opcode, types};
+
+        Op::submit_or_return(
+            Read {
+                fd: fd.for_op(),
+                buf,
+            },
+            |read| {
+                let ptr = read.buf.stable_mut_ptr();
+                let len = read.buf.bytes_total();
+                let index = read.buf.buf_index();
+                opcode::ReadFixed::new(types::Fd(fd.raw_fd()), ptr, len as _, index)
+                    .offset(offset as _)
+                    .build()
+            },
+        )
+        .map_err(|(e, read)| (e, read.buf))
     }
 }
@@ -0,0 +1,92 @@
+use crate::driver::{Op, SharedFd};
+
+use std::io;
+use std::mem::ManuallyDrop;
+use std::rc::Rc;
+
+/// Read of an extent of a file into its part of a shared allocation.
+pub(crate) struct ReadExtent {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// Held until the read completes, even if its future was dropped.
+    #[allow(dead_code)]
+    mem: Rc<Mem>,
+}
+
+/// Allocation shared by the reads of a set of extents, written by the
+/// kernel through raw pointers.
+struct Mem {
+    ptr: *mut u8,
+    len: usize,
+    cap: usize,
+}
+
+impl Op<ReadExtent> {
+    /// Read the extents, as offsets and lengths, of `fd` into a single
+    /// allocation, where they follow each other in order. The reads are
+    /// submitted as a batch.
+    ///
+    /// Returns the allocation and the result of each read.
+    pub(crate) async fn read_extents(
+        fd: &SharedFd,
+        extents: &[(u64, usize)],
+    ) -> io::Result<(Vec<u8>, Vec<io::Result<usize>>)> {
+        use io_uring::{opcode, types};
+
+        let total = extents.iter().map(|&(_, len)| len).sum();
+        let mut buf = ManuallyDrop::new(vec![0u8; total]);
+        let mem = Rc::new(Mem {
+            ptr: buf.as_mut_ptr(),
+            len: buf.len(),
+            cap: buf.capacity(),
+        });
+
+        let mut start = 0;
+        let reads = extents
+            .iter()
+            .map(|&(offset, len)| {
+                let read = ReadExtent {
+                    fd: fd.for_op(),
+                    mem: mem.clone(),
+                };
+                let region = (offset, start, len);
+                start += len;
+                (read, region)
+            })
+            .collect();
+
+        let ops = Op::join_all(reads, |read: &mut ReadExtent, (offset, start, len)| {
+            // Safety: the regions of the reads are disjoint, and in bounds
+            let ptr = unsafe { read.mem.ptr.add(start) };
+            opcode::Read::new(types::Fd(read.fd.raw_fd()), ptr, len as _)
+                .offset(offset as _)
+                .build()
+        })?;
+
+        let results = ops
+            .await
+            .into_iter()
+            .map(|completion| completion.result.map(|n| n as usize))
+            .collect();
+
+        // All the reads completed, releasing the allocation
+        let mem = Rc::try_unwrap(mem).unwrap_or_else(|_| unreachable!());
+        Ok((mem.into_vec(), results))
+    }
+}
+
+impl Mem {
+    fn into_vec(self) -> Vec<u8> {
+        let mem = ManuallyDrop::new(self);
+        unsafe { Vec::from_raw_parts(mem.ptr, mem.len, mem.cap) }
+    }
+}
+
+impl Drop for Mem {
+    fn drop(&mut self) {
+        drop(unsafe { Vec::from_raw_parts(self.ptr, self.len, self.cap) });
+    }
+}
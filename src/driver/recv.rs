@@ -0,0 +1,78 @@
+use crate::buf::IoBufMut;
+use crate::driver::recycle::OrphanBuf;
+use crate::driver::{Op, SharedFd};
+use crate::BufResult;
+
+use std::io;
+use std::task::{Context, Poll};
+
+pub(crate) struct Recv<T> {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// Reference to the in-flight buffer.
+    pub(crate) buf: T,
+}
+
+impl<T: IoBufMut> OrphanBuf for Recv<T> {
+    type Buf = T;
+
+    fn into_buf(self) -> T {
+        self.buf
+    }
+}
+
+impl<T: IoBufMut> Op<Recv<T>> {
+    /// Receive from a socket, passing `flags` (`MSG_*`) to the kernel.
+    pub(crate) fn recv_buf(fd: &SharedFd, buf: T, flags: libc::c_int) -> io::Result<Op<Recv<T>>> {
+        use io_uring::{opcode, types};
+
+        Op::submit_with(
+            Recv {
+                fd: fd.for_op(),
+                buf,
+            },
+            |recv| {
+                // Get raw buffer info
+                let ptr = recv.buf.stable_mut_ptr();
+                let len = recv.buf.bytes_total();
+                opcode::Recv::new(types::Fd(fd.raw_fd()), ptr, len as _)
+                    .flags(flags)
+                    .build()
+            },
+        )
+        .map(Op::recycle_orphan_buf)
+    }
+
+    pub(crate) async fn received(mut self) -> BufResult<usize, T> {
+        crate::future::poll_fn(move |cx| self.poll_recv(cx)).await
+    }
+
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+
+        // Convert the operation result to `usize`
+        let res = complete.result.map(|v| v as usize);
+        // Recover the buffer
+        let mut buf = complete.data.buf;
+
+        // If the operation was successful, advance the initialized cursor. With
+        // `MSG_TRUNC`, the result is the length of the datagram, which may
+        // exceed the buffer.
+        if let Ok(n) = res {
+            let n = n.min(buf.bytes_total());
+
+            // Safety: the kernel wrote `n` bytes to the buffer.
+            unsafe {
+                buf.set_init(n);
+            }
+        }
+
+        Poll::Ready((res, buf))
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     buf::IoBufMut,
+    driver::recycle::OrphanBuf,
     driver::{Op, SharedFd},
     BufResult,
 };
@@ -7,7 +8,7 @@ use socket2::SockAddr;
 use std::{
     io::IoSliceMut,
     task::{Context, Poll},
-    {boxed::Box, io, net::SocketAddr},
+    {boxed::Box, io},
 };
 
 #[allow(dead_code)]
@@ -15,29 +16,42 @@ pub(crate) struct RecvFrom<T> {
     fd: SharedFd,
     pub(crate) buf: T,
     io_slices: Vec<IoSliceMut<'static>>,
-    pub(crate) socket_addr: Box<SockAddr>,
+    /// Written by the kernel, the length is stored in `msghdr.msg_namelen`.
+    pub(crate) socket_addr: Box<libc::sockaddr_storage>,
     pub(crate) msghdr: Box<libc::msghdr>,
 }
 
+impl<T: IoBufMut> OrphanBuf for RecvFrom<T> {
+    type Buf = T;
+
+    fn into_buf(self) -> T {
+        self.buf
+    }
+}
+
 impl<T: IoBufMut> Op<RecvFrom<T>> {
-    pub(crate) fn recv_from(fd: &SharedFd, mut buf: T) -> io::Result<Op<RecvFrom<T>>> {
+    pub(crate) fn recv_from(
+        fd: &SharedFd,
+        mut buf: T,
+        flags: libc::c_int,
+    ) -> io::Result<Op<RecvFrom<T>>> {
         use io_uring::{opcode, types};
 
         let mut io_slices = vec![IoSliceMut::new(unsafe {
             std::slice::from_raw_parts_mut(buf.stable_mut_ptr(), buf.bytes_total())
         })];
 
-        let socket_addr = Box::new(unsafe { SockAddr::init(|_, _| Ok(()))?.1 });
+        let mut socket_addr: Box<libc::sockaddr_storage> = Box::new(unsafe { std::mem::zeroed() });
 
         let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
         msghdr.msg_iov = io_slices.as_mut_ptr().cast();
         msghdr.msg_iovlen = io_slices.len() as _;
-        msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
-        msghdr.msg_namelen = socket_addr.len();
+        msghdr.msg_name = socket_addr.as_mut() as *mut _ as *mut libc::c_void;
+        msghdr.msg_namelen = std::mem::size_of::<libc::sockaddr_storage>() as _;
 
         Op::submit_with(
             RecvFrom {
-                fd: fd.clone(),
+                fd: fd.for_op(),
                 buf,
                 io_slices,
                 socket_addr,
@@ -48,12 +62,14 @@ impl<T: IoBufMut> Op<RecvFrom<T>> {
                     types::Fd(recv_from.fd.raw_fd()),
                     recv_from.msghdr.as_mut() as *mut _,
                 )
+                .flags(flags as _)
                 .build()
             },
         )
+        .map(Op::recycle_orphan_buf)
     }
 
-    pub(crate) async fn recv(mut self) -> BufResult<(usize, SocketAddr), T> {
+    pub(crate) async fn recv(mut self) -> BufResult<(usize, SockAddr), T> {
         use crate::future::poll_fn;
 
         poll_fn(move |cx| self.poll_recv_from(cx)).await
@@ -62,7 +78,7 @@ impl<T: IoBufMut> Op<RecvFrom<T>> {
     pub(crate) fn poll_recv_from(
         &mut self,
         cx: &mut Context<'_>,
-    ) -> Poll<BufResult<(usize, SocketAddr), T>> {
+    ) -> Poll<BufResult<(usize, SockAddr), T>> {
         use std::future::Future;
         use std::pin::Pin;
 
@@ -70,20 +86,32 @@ impl<T: IoBufMut> Op<RecvFrom<T>> {
 
         // Recover the buffer
         let mut buf = complete.data.buf;
+        let socket_addr = complete.data.socket_addr;
+        let socket_addr_len = complete.data.msghdr.msg_namelen;
 
-        let result = match complete.result {
-            Ok(v) => {
-                let v = v as usize;
-                let socket_addr: Option<SocketAddr> = (*complete.data.socket_addr).as_socket();
-                // If the operation was successful, advance the initialized cursor.
-                // Safety: the kernel wrote `v` bytes to the buffer.
-                unsafe {
-                    buf.set_init(v);
-                }
-                Ok((v, socket_addr.unwrap()))
+        let result = complete.result.and_then(|v| {
+            let v = v as usize;
+
+            // Safety: the kernel wrote an address of `msg_namelen` bytes.
+            let (_, socket_addr) = unsafe {
+                SockAddr::init(|storage, len| {
+                    *storage = *socket_addr;
+                    *len = socket_addr_len;
+                    Ok(())
+                })?
+            };
+
+            // If the operation was successful, advance the initialized cursor.
+            // With `MSG_TRUNC`, `v` is the length of the datagram, which may
+            // exceed the buffer.
+            // Safety: the kernel wrote up to `v` bytes to the buffer.
+            unsafe {
+                buf.set_init(v.min(buf.bytes_total()));
             }
-            Err(e) => Err(e),
-        };
+
+            Ok((v, socket_addr))
+        });
+
         Poll::Ready((result, buf))
     }
 }
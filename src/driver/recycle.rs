@@ -0,0 +1,86 @@
+use crate::driver::op::Lifecycle;
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Callbacks receiving the buffers of operations dropped while in flight, by
+/// buffer type.
+#[derive(Default)]
+pub(super) struct Recyclers(RefCell<HashMap<TypeId, Recycler>>);
+
+/// Receives type-erased buffers of a single type.
+type Recycler = Box<dyn FnMut(Box<dyn Any>)>;
+
+/// State of an operation holding a buffer. If the operation is dropped while
+/// in flight, the buffer is passed to the recycler of its type once the kernel
+/// released it.
+pub(crate) trait OrphanBuf: 'static {
+    type Buf: 'static;
+
+    fn into_buf(self) -> Self::Buf;
+}
+
+impl Recyclers {
+    /// Passes the buffers of the orphans to their recycler, or drops them if
+    /// there is none.
+    ///
+    /// Must be called while the driver is not borrowed, the recyclers may
+    /// submit operations.
+    pub(super) fn recycle(&self, orphans: Vec<Lifecycle>) {
+        for orphan in orphans {
+            let buf = match orphan.into_orphan_buf() {
+                Some(buf) => buf,
+                None => continue,
+            };
+
+            // The recycler is taken out while called, so that it can replace
+            // the recyclers.
+            let id = (*buf).type_id();
+            let recycler = self.0.borrow_mut().remove(&id);
+
+            if let Some(mut recycler) = recycler {
+                recycler(buf);
+                self.0.borrow_mut().entry(id).or_insert(recycler);
+            }
+        }
+    }
+}
+
+/// Extracts the buffer of type-erased operation state.
+pub(super) fn into_buf<T: OrphanBuf>(data: Box<dyn Any>) -> Option<Box<dyn Any>> {
+    let data = data.downcast::<Option<T>>().ok()?;
+    Some(Box::new((*data)?.into_buf()))
+}
+
+/// Sets the recycler of the buffers of type `B`.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn set<B: 'static>(mut f: impl FnMut(B) + 'static) {
+    let recycler: Recycler = Box::new(move |buf| {
+        if let Ok(buf) = buf.downcast::<B>() {
+            f(*buf);
+        }
+    });
+
+    super::CURRENT.with(|inner| {
+        let recyclers = inner.borrow().recyclers.clone();
+        let mut recyclers = recyclers.0.borrow_mut();
+        recyclers.insert(TypeId::of::<B>(), recycler);
+    });
+}
+
+/// Removes the recycler of the buffers of type `B`.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn clear<B: 'static>() {
+    super::CURRENT.with(|inner| {
+        let recyclers = inner.borrow().recyclers.clone();
+        let recycler = recyclers.0.borrow_mut().remove(&TypeId::of::<B>());
+        drop(recycler);
+    });
+}
@@ -0,0 +1,90 @@
+use crate::driver::{self, sqe, Handle};
+
+use io_uring::{opcode, squeue};
+use std::io;
+
+/// Buffers registered with the ring, unregistered when dropped.
+pub(crate) struct BufRegistration {
+    driver: Handle,
+
+    /// Memory accounted for the buffers
+    bytes: usize,
+}
+
+impl BufRegistration {
+    /// Registers the buffers with the ring of the current runtime, accounting
+    /// `bytes` of locked memory for them.
+    ///
+    /// # Safety
+    ///
+    /// The buffers must remain valid until the registration is dropped.
+    pub(crate) unsafe fn new(iovecs: &[libc::iovec], bytes: usize) -> io::Result<BufRegistration> {
+        if !driver::CURRENT.is_set() {
+            return Err(io::ErrorKind::Other.into());
+        }
+
+        driver::CURRENT.with(|inner_rc| {
+            let mut inner = inner_rc.borrow_mut();
+            inner.uring.submitter().register_buffers(iovecs)?;
+            inner.registered_bytes += bytes;
+            inner.registered_bufs = iovecs
+                .iter()
+                .enumerate()
+                .map(|(index, iovec)| {
+                    let start = iovec.iov_base as u64;
+                    (start, start + iovec.iov_len as u64, index as u16)
+                })
+                .collect();
+            inner.registered_bufs.sort_unstable();
+
+            Ok(BufRegistration {
+                driver: inner_rc.clone(),
+                bytes,
+            })
+        })
+    }
+}
+
+impl Drop for BufRegistration {
+    fn drop(&mut self) {
+        let mut inner = self.driver.borrow_mut();
+        let _ = inner.uring.submitter().unregister_buffers();
+        inner.registered_bytes -= self.bytes;
+        inner.registered_bufs.clear();
+    }
+}
+
+/// Rewrites a read or a write of `sqe` into the read or the write of a fixed
+/// buffer, if its buffer lies within one of the `registered` buffers, given
+/// by their address ranges and indices, in address order.
+pub(crate) fn use_registered_buf(registered: &[(u64, u64, u16)], sqe: &mut squeue::Entry) {
+    let raw = sqe::raw_mut(sqe);
+    let fixed = match raw.opcode {
+        opcode::Read::CODE => opcode::ReadFixed::CODE,
+        opcode::Write::CODE => opcode::WriteFixed::CODE,
+        _ => return,
+    };
+
+    // The last buffer starting at or before the one of the operation
+    let (start, end) = (raw.addr, raw.addr + u64::from(raw.len));
+    let candidate = registered.partition_point(|&(from, _, _)| from <= start);
+    if candidate == 0 {
+        return;
+    }
+
+    let (_, to, index) = registered[candidate - 1];
+    if end <= to {
+        raw.opcode = fixed;
+        raw.buf_index = index;
+    }
+}
+
+/// Memory locked by the buffers registered with the ring of the current
+/// runtime, if any.
+pub(crate) fn registered_bytes() -> Option<usize> {
+    if !driver::CURRENT.is_set() {
+        return None;
+    }
+
+    driver::CURRENT.with(|inner_rc| Some(inner_rc.borrow().registered_bytes))
+}
@@ -0,0 +1,38 @@
+use crate::driver::{self, Op};
+
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+
+use io_uring::{opcode, squeue, types};
+
+/// Rename a path relative to the current working directory of the caller's
+/// process.
+pub(crate) struct RenameAt {
+    pub(crate) from: CString,
+    pub(crate) to: CString,
+}
+
+impl Op<RenameAt> {
+    /// Submit a request to rename `from` to `to`, replacing `to` if it exists.
+    pub(crate) fn rename(from: &Path, to: &Path) -> io::Result<Op<RenameAt>> {
+        let from = driver::util::cstr(from)?;
+        let to = driver::util::cstr(to)?;
+
+        Op::submit_with(RenameAt { from, to }, RenameAt::entry)
+    }
+}
+
+impl RenameAt {
+    pub(super) fn entry(&mut self) -> squeue::Entry {
+        // The strings are held by the operation state until the operation
+        // completes.
+        opcode::RenameAt::new(
+            types::Fd(libc::AT_FDCWD),
+            self.from.as_ptr(),
+            types::Fd(libc::AT_FDCWD),
+            self.to.as_ptr(),
+        )
+        .build()
+    }
+}
@@ -0,0 +1,60 @@
+use crate::buf::IoBuf;
+use crate::driver::recycle::OrphanBuf;
+use crate::driver::{Op, SharedFd};
+use crate::BufResult;
+
+use std::io;
+use std::task::{Context, Poll};
+
+pub(crate) struct Send<T> {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) buf: T,
+}
+
+impl<T: IoBuf> OrphanBuf for Send<T> {
+    type Buf = T;
+
+    fn into_buf(self) -> T {
+        self.buf
+    }
+}
+
+impl<T: IoBuf> Op<Send<T>> {
+    /// Send on a socket, passing `flags` (`MSG_*`) to the kernel.
+    pub(crate) fn send_buf(fd: &SharedFd, buf: T, flags: libc::c_int) -> io::Result<Op<Send<T>>> {
+        use io_uring::{opcode, types};
+
+        Op::submit_with(
+            Send {
+                fd: fd.for_op(),
+                buf,
+            },
+            |send| {
+                // Get raw buffer info
+                let ptr = send.buf.stable_ptr();
+                let len = send.buf.bytes_init();
+
+                opcode::Send::new(types::Fd(fd.raw_fd()), ptr, len as _)
+                    .flags(flags)
+                    .build()
+            },
+        )
+        .map(Op::recycle_orphan_buf)
+    }
+
+    pub(crate) async fn sent(mut self) -> BufResult<usize, T> {
+        crate::future::poll_fn(move |cx| self.poll_send(cx)).await
+    }
+
+    pub(crate) fn poll_send(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, T>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+        Poll::Ready((complete.result.map(|v| v as _), complete.data.buf))
+    }
+}
@@ -0,0 +1,87 @@
+use crate::buf::IoBuf;
+use crate::driver::{Op, SharedFd};
+use crate::BufResult;
+use socket2::SockAddr;
+use std::io::IoSlice;
+use std::task::{Context, Poll};
+use std::{boxed::Box, io};
+
+pub(crate) struct SendMsg<T> {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    pub(crate) bufs: Vec<T>,
+
+    /// Referenced by `msghdr`.
+    #[allow(dead_code)]
+    io_slices: Vec<IoSlice<'static>>,
+    #[allow(dead_code)]
+    socket_addr: Option<Box<SockAddr>>,
+
+    pub(crate) msghdr: Box<libc::msghdr>,
+}
+
+impl<T: IoBuf> Op<SendMsg<T>> {
+    /// Send the buffers as a single message, to `socket_addr` if given.
+    pub(crate) fn send_msg(
+        fd: &SharedFd,
+        bufs: Vec<T>,
+        socket_addr: Option<SockAddr>,
+        flags: libc::c_int,
+    ) -> io::Result<Op<SendMsg<T>>> {
+        use io_uring::{opcode, types};
+
+        let io_slices: Vec<IoSlice<'static>> = bufs
+            .iter()
+            .map(|buf| {
+                IoSlice::new(unsafe {
+                    std::slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init())
+                })
+            })
+            .collect();
+
+        let socket_addr = socket_addr.map(Box::new);
+
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
+        msghdr.msg_iov = io_slices.as_ptr() as *mut _;
+        msghdr.msg_iovlen = io_slices.len() as _;
+        if let Some(socket_addr) = &socket_addr {
+            msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
+            msghdr.msg_namelen = socket_addr.len();
+        }
+
+        Op::submit_with(
+            SendMsg {
+                fd: fd.for_op(),
+                bufs,
+                io_slices,
+                socket_addr,
+                msghdr,
+            },
+            |send_msg| {
+                opcode::SendMsg::new(
+                    types::Fd(send_msg.fd.raw_fd()),
+                    send_msg.msghdr.as_ref() as *const _,
+                )
+                .flags(flags as _)
+                .build()
+            },
+        )
+    }
+
+    pub(crate) async fn send(mut self) -> BufResult<usize, Vec<T>> {
+        use crate::future::poll_fn;
+
+        poll_fn(move |cx| self.poll_send(cx)).await
+    }
+
+    pub(crate) fn poll_send(&mut self, cx: &mut Context<'_>) -> Poll<BufResult<usize, Vec<T>>> {
+        use std::future::Future;
+        use std::pin::Pin;
+
+        let complete = ready!(Pin::new(self).poll(cx));
+        Poll::Ready((complete.result.map(|v| v as _), complete.data.bufs))
+    }
+}
@@ -1,10 +1,11 @@
 use crate::buf::IoBuf;
+use crate::driver::recycle::OrphanBuf;
 use crate::driver::{Op, SharedFd};
 use crate::BufResult;
 use socket2::SockAddr;
 use std::io::IoSlice;
 use std::task::{Context, Poll};
-use std::{boxed::Box, io, net::SocketAddr};
+use std::{boxed::Box, io};
 
 pub(crate) struct SendTo<T> {
     #[allow(dead_code)]
@@ -17,11 +18,20 @@ pub(crate) struct SendTo<T> {
     pub(crate) msghdr: Box<libc::msghdr>,
 }
 
+impl<T: IoBuf> OrphanBuf for SendTo<T> {
+    type Buf = T;
+
+    fn into_buf(self) -> T {
+        self.buf
+    }
+}
+
 impl<T: IoBuf> Op<SendTo<T>> {
     pub(crate) fn send_to(
         fd: &SharedFd,
         buf: T,
-        socket_addr: SocketAddr,
+        socket_addr: SockAddr,
+        flags: libc::c_int,
     ) -> io::Result<Op<SendTo<T>>> {
         use io_uring::{opcode, types};
 
@@ -29,7 +39,7 @@ impl<T: IoBuf> Op<SendTo<T>> {
             std::slice::from_raw_parts(buf.stable_ptr(), buf.bytes_init())
         })];
 
-        let socket_addr = Box::new(SockAddr::from(socket_addr));
+        let socket_addr = Box::new(socket_addr);
 
         let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { std::mem::zeroed() });
         msghdr.msg_iov = io_slices.as_ptr() as *mut _;
@@ -39,7 +49,7 @@ impl<T: IoBuf> Op<SendTo<T>> {
 
         Op::submit_with(
             SendTo {
-                fd: fd.clone(),
+                fd: fd.for_op(),
                 buf,
                 io_slices,
                 socket_addr,
@@ -50,9 +60,11 @@ impl<T: IoBuf> Op<SendTo<T>> {
                     types::Fd(send_to.fd.raw_fd()),
                     send_to.msghdr.as_ref() as *const _,
                 )
+                .flags(flags as _)
                 .build()
             },
         )
+        .map(Op::recycle_orphan_buf)
     }
 
     pub(crate) async fn send(mut self) -> BufResult<usize, T> {
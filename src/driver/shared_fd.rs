@@ -1,16 +1,19 @@
 use crate::driver::{Close, Op};
 use crate::future::poll_fn;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::os::unix::io::{FromRawFd, RawFd};
 use std::rc::Rc;
-use std::task::Waker;
+use std::task::{Context, Poll, Waker};
 
 // Tracks in-flight operations on a file descriptor. Ensures all in-flight
 // operations complete before submitting the close.
-#[derive(Clone)]
 pub(crate) struct SharedFd {
     inner: Rc<Inner>,
+
+    // Whether the handle is held by an in-flight operation, counted in
+    // `Inner::ops`
+    op: bool,
 }
 
 struct Inner {
@@ -19,6 +22,12 @@ struct Inner {
 
     // Waker to notify when the close operation completes.
     state: RefCell<State>,
+
+    // Number of in-flight operations on the FD
+    ops: Cell<usize>,
+
+    // Tasks waiting for fewer in-flight operations
+    op_waiters: RefCell<Vec<Waker>>,
 }
 
 enum State {
@@ -41,8 +50,34 @@ impl SharedFd {
             inner: Rc::new(Inner {
                 fd,
                 state: RefCell::new(State::Init),
+                ops: Cell::new(0),
+                op_waiters: RefCell::new(Vec::new()),
             }),
+            op: false,
+        }
+    }
+
+    /// Returns a handle for an operation on the FD, which counts as in-flight
+    /// until the handle is dropped along with the state of the operation.
+    pub(crate) fn for_op(&self) -> SharedFd {
+        self.inner.ops.set(self.inner.ops.get() + 1);
+        SharedFd {
+            inner: self.inner.clone(),
+            op: true,
+        }
+    }
+
+    /// Completes once fewer than `limit` operations are in flight on the FD.
+    pub(crate) fn poll_ops_below(&self, limit: usize, cx: &mut Context<'_>) -> Poll<()> {
+        if self.inner.ops.get() < limit {
+            return Poll::Ready(());
+        }
+
+        let mut waiters = self.inner.op_waiters.borrow_mut();
+        if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+            waiters.push(cx.waker().clone());
         }
+        Poll::Pending
     }
 
     /// Returns the RawFd
@@ -54,22 +89,78 @@ impl SharedFd {
     /// This prevents bugs where in-flight reads could operate on the incorrect
     /// file descriptor.
     ///
-    /// TO model this, if there are no in-flight operations, then
+    /// To model this, every in-flight operation holds a clone of the
+    /// `SharedFd`. The close operation is only submitted once this is the last
+    /// handle, waiting for the other handles to be dropped if needed.
     pub(crate) async fn close(mut self) {
-        // Get a mutable reference to Inner, indicating there are no
-        // in-flight operations on the FD.
-        if let Some(inner) = Rc::get_mut(&mut self.inner) {
-            // Submit the close operation
-            inner.submit_close_op();
+        loop {
+            // Get a mutable reference to Inner, indicating there are no
+            // in-flight operations on the FD.
+            if let Some(inner) = Rc::get_mut(&mut self.inner) {
+                // Submit the close operation
+                inner.submit_close_op();
+                break;
+            }
+
+            self.unique().await;
         }
 
         self.inner.closed().await;
     }
+
+    /// Completes when this is the only remaining handle to the FD.
+    async fn unique(&self) {
+        poll_fn(|cx| {
+            if Rc::strong_count(&self.inner) == 1 {
+                return Poll::Ready(());
+            }
+
+            let mut state = self.inner.state.borrow_mut();
+
+            match &mut *state {
+                State::Waiting(Some(waker)) if waker.will_wake(cx.waker()) => {}
+                _ => *state = State::Waiting(Some(cx.waker().clone())),
+            }
+
+            Poll::Pending
+        })
+        .await;
+    }
+}
+
+impl Clone for SharedFd {
+    fn clone(&self) -> SharedFd {
+        SharedFd {
+            inner: self.inner.clone(),
+            op: false,
+        }
+    }
+}
+
+impl Drop for SharedFd {
+    fn drop(&mut self) {
+        if self.op {
+            self.inner.ops.set(self.inner.ops.get() - 1);
+            for waker in self.inner.op_waiters.borrow_mut().drain(..) {
+                waker.wake();
+            }
+        }
+
+        // A task may be waiting in `close()` for this handle to go away.
+        if let State::Waiting(waker) = &mut *self.inner.state.borrow_mut() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
+    }
 }
 
 impl Inner {
     /// If there are no in-flight operations, submit the operation.
     fn submit_close_op(&mut self) {
+        // The number may be reused once closed, by a file it does not refer to
+        crate::driver::forget_file(self.fd);
+
         // Close the FD
         let state = RefCell::get_mut(&mut self.state);
 
@@ -98,27 +189,12 @@ impl Inner {
     async fn closed(&self) {
         use std::future::Future;
         use std::pin::Pin;
-        use std::task::Poll;
 
         poll_fn(|cx| {
             let mut state = self.state.borrow_mut();
 
             match &mut *state {
-                State::Init => {
-                    *state = State::Waiting(Some(cx.waker().clone()));
-                    Poll::Pending
-                }
-                State::Waiting(Some(waker)) => {
-                    if !waker.will_wake(cx.waker()) {
-                        *waker = cx.waker().clone();
-                    }
-
-                    Poll::Pending
-                }
-                State::Waiting(None) => {
-                    *state = State::Waiting(Some(cx.waker().clone()));
-                    Poll::Pending
-                }
+                State::Init | State::Waiting(..) => unreachable!("close not submitted"),
                 State::Closing(op) => {
                     // Nothing to do if the close opeation failed.
                     let _ = ready!(Pin::new(op).poll(cx));
@@ -1,12 +1,16 @@
 use crate::{
     buf::{IoBuf, IoBufMut},
-    driver::{Op, SharedFd},
+    driver::{ready, room, Accept, Op, RecvFrom, SendTo, SharedFd},
+    future::poll_fn,
 };
 use std::{
+    future::Future,
     io,
     net::SocketAddr,
     os::unix::io::{AsRawFd, IntoRawFd, RawFd},
     path::Path,
+    pin::Pin,
+    task::{Context, Poll},
 };
 
 #[derive(Clone)]
@@ -39,36 +43,221 @@ impl Socket {
         Ok(Socket { fd })
     }
 
+    pub(crate) fn shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+
+    /// Adopt an already created socket.
+    pub(crate) fn from_socket2(socket: socket2::Socket) -> Socket {
+        let fd = SharedFd::new(socket.into_raw_fd());
+        Socket { fd }
+    }
+
+    /// Create a socket of any domain, type and protocol.
+    pub(crate) fn new_raw(
+        domain: libc::c_int,
+        socket_type: libc::c_int,
+        protocol: libc::c_int,
+    ) -> io::Result<Socket> {
+        let socket_type = socket_type | libc::SOCK_CLOEXEC;
+        let fd = socket2::Socket::new(domain.into(), socket_type.into(), Some(protocol.into()))?
+            .into_raw_fd();
+        let fd = SharedFd::new(fd);
+        Ok(Socket { fd })
+    }
+
+    /// Create a Multipath TCP socket, or a TCP socket if MPTCP is not
+    /// supported or disabled by the `net.mptcp.enabled` sysctl.
+    pub(crate) fn new_mptcp(socket_addr: SocketAddr) -> io::Result<Socket> {
+        let domain = get_domain(socket_addr);
+        match Socket::new_raw(domain, libc::SOCK_STREAM, libc::IPPROTO_MPTCP) {
+            Err(e) if mptcp_unsupported(&e) => Socket::new(socket_addr, libc::SOCK_STREAM),
+            res => res,
+        }
+    }
+
+    pub(crate) fn new_vsock(socket_type: libc::c_int) -> io::Result<Socket> {
+        let socket_type = socket_type | libc::SOCK_CLOEXEC;
+        let domain = libc::AF_VSOCK;
+        let fd = socket2::Socket::new(domain.into(), socket_type.into(), None)?.into_raw_fd();
+        let fd = SharedFd::new(fd);
+        Ok(Socket { fd })
+    }
+
     pub(crate) async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
         let op = Op::write_at(&self.fd, buf, 0).unwrap();
         op.write().await
     }
 
+    pub(crate) async fn send<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: libc::c_int,
+    ) -> crate::BufResult<usize, T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::send_buf(&self.fd, buf, flags).unwrap();
+        op.sent().await
+    }
+
     pub(crate) async fn send_to<T: IoBuf>(
         &self,
         buf: T,
         socket_addr: SocketAddr,
+        flags: libc::c_int,
+    ) -> crate::BufResult<usize, T> {
+        self.send_to_sockaddr(buf, socket_addr.into(), flags).await
+    }
+
+    pub(crate) async fn send_to_sockaddr<T: IoBuf>(
+        &self,
+        buf: T,
+        socket_addr: socket2::SockAddr,
+        flags: libc::c_int,
     ) -> crate::BufResult<usize, T> {
-        let op = Op::send_to(&self.fd, buf, socket_addr).unwrap();
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::send_to(&self.fd, buf, socket_addr, flags).unwrap();
+        op.send().await
+    }
+
+    pub(crate) async fn send_msg<T: IoBuf>(
+        &self,
+        bufs: Vec<T>,
+        socket_addr: Option<socket2::SockAddr>,
+        flags: libc::c_int,
+    ) -> crate::BufResult<usize, Vec<T>> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), bufs);
+        }
+
+        let op = Op::send_msg(&self.fd, bufs, socket_addr, flags).unwrap();
         op.send().await
     }
 
     pub(crate) async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
         let op = Op::read_at(&self.fd, buf, 0).unwrap();
         op.read().await
     }
 
+    pub(crate) async fn recv<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: libc::c_int,
+    ) -> crate::BufResult<usize, T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::recv_buf(&self.fd, buf, flags).unwrap();
+        op.received().await
+    }
+
     pub(crate) async fn recv_from<T: IoBufMut>(
         &self,
         buf: T,
+        flags: libc::c_int,
     ) -> crate::BufResult<(usize, SocketAddr), T> {
-        let op = Op::recv_from(&self.fd, buf).unwrap();
+        let (res, buf) = self.recv_from_sockaddr(buf, flags).await;
+
+        let res = res.and_then(|(n, socket_addr)| {
+            let socket_addr = socket_addr
+                .as_socket()
+                .ok_or_else(|| io::Error::other("Could not get socket IP address"))?;
+            Ok((n, socket_addr))
+        });
+
+        (res, buf)
+    }
+
+    pub(crate) async fn recv_from_sockaddr<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: libc::c_int,
+    ) -> crate::BufResult<(usize, socket2::SockAddr), T> {
+        if let Err(e) = room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::recv_from(&self.fd, buf, flags).unwrap();
         op.recv().await
     }
 
+    /// Submit a `recvmsg` operation, to be polled with `poll_recv_from`.
+    pub(crate) fn recv_from_op<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: libc::c_int,
+    ) -> io::Result<Op<RecvFrom<T>>> {
+        Op::recv_from(&self.fd, buf, flags)
+    }
+
+    pub(crate) fn poll_recv_from<T: IoBufMut>(
+        op: &mut Op<RecvFrom<T>>,
+        cx: &mut Context<'_>,
+    ) -> Poll<crate::BufResult<(usize, SocketAddr), T>> {
+        let (res, buf) = ready!(op.poll_recv_from(cx));
+
+        let res = res.and_then(|(n, socket_addr)| {
+            let socket_addr = socket_addr
+                .as_socket()
+                .ok_or_else(|| io::Error::other("Could not get socket IP address"))?;
+            Ok((n, socket_addr))
+        });
+
+        Poll::Ready((res, buf))
+    }
+
+    /// Submit a `sendmsg` operation, to be polled with `Op::poll_send`.
+    pub(crate) fn send_to_op<T: IoBuf>(
+        &self,
+        buf: T,
+        socket_addr: SocketAddr,
+        flags: libc::c_int,
+    ) -> io::Result<Op<SendTo<T>>> {
+        Op::send_to(&self.fd, buf, socket_addr.into(), flags)
+    }
+
     pub(crate) async fn accept(&self) -> io::Result<(Socket, Option<SocketAddr>)> {
-        let op = Op::accept(&self.fd)?;
-        let completion = op.await;
+        let (socket, addr) = self.accept_sockaddr().await?;
+        Ok((socket, addr.as_socket()))
+    }
+
+    pub(crate) async fn accept_sockaddr(&self) -> io::Result<(Socket, socket2::SockAddr)> {
+        let mut op = Op::accept(&self.fd)?;
+        poll_fn(|cx| Socket::poll_accept_sockaddr(&mut op, cx)).await
+    }
+
+    /// Submit an accept operation, to be polled with `poll_accept`.
+    pub(crate) fn accept_op(&self) -> io::Result<Op<Accept>> {
+        Op::accept(&self.fd)
+    }
+
+    pub(crate) fn poll_accept(
+        op: &mut Op<Accept>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(Socket, Option<SocketAddr>)>> {
+        let (socket, addr) = ready!(Socket::poll_accept_sockaddr(op, cx))?;
+        Poll::Ready(Ok((socket, addr.as_socket())))
+    }
+
+    fn poll_accept_sockaddr(
+        op: &mut Op<Accept>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(Socket, socket2::SockAddr)>> {
+        let completion = ready!(Pin::new(op).poll(cx));
         let fd = completion.result?;
         let fd = SharedFd::new(fd as i32);
         let data = completion.data;
@@ -80,7 +269,7 @@ impl Socket {
                 Ok(())
             })?
         };
-        Ok((socket, addr.as_socket()))
+        Poll::Ready(Ok((socket, addr)))
     }
 
     pub(crate) async fn connect(&self, socket_addr: socket2::SockAddr) -> io::Result<()> {
@@ -90,32 +279,169 @@ impl Socket {
         Ok(())
     }
 
+    /// Connects with `sendto(MSG_FASTOPEN)`, carrying the data in the SYN when
+    /// the peer granted a Fast Open cookie. Otherwise, the data is sent once
+    /// the connection is established.
+    pub(crate) async fn connect_with_data<T: IoBuf>(
+        &self,
+        socket_addr: socket2::SockAddr,
+        buf: T,
+    ) -> crate::BufResult<usize, T> {
+        let (res, buf) = self
+            .send_to_sockaddr(buf, socket_addr.clone(), libc::MSG_FASTOPEN)
+            .await;
+
+        match res {
+            // The SYN was sent without data, no cookie was cached yet
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {
+                let connected =
+                    ready(&self.fd, libc::POLLOUT).await.and_then(
+                        |_| match socket2::SockRef::from(self).take_error()? {
+                            Some(e) => Err(e),
+                            None => Ok(()),
+                        },
+                    );
+                if let Err(e) = connected {
+                    return (Err(e), buf);
+                }
+            }
+            // Fast Open is disabled by the `net.ipv4.tcp_fastopen` sysctl
+            Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => {
+                if let Err(e) = self.connect(socket_addr).await {
+                    return (Err(e), buf);
+                }
+            }
+            res => return (res, buf),
+        }
+
+        self.send(buf, 0).await
+    }
+
     pub(crate) fn bind(socket_addr: SocketAddr, socket_type: libc::c_int) -> io::Result<Socket> {
         Self::bind_internal(
             socket_addr.into(),
             get_domain(socket_addr).into(),
             socket_type.into(),
+            None,
         )
     }
 
+    /// Bind a Multipath TCP socket, falling back to TCP like
+    /// [`Socket::new_mptcp`].
+    pub(crate) fn bind_mptcp(socket_addr: SocketAddr) -> io::Result<Socket> {
+        let domain = get_domain(socket_addr).into();
+        let protocol = Some(libc::IPPROTO_MPTCP.into());
+        match Self::bind_internal(socket_addr.into(), domain, socket2::Type::STREAM, protocol) {
+            Err(e) if mptcp_unsupported(&e) => Socket::bind(socket_addr, libc::SOCK_STREAM),
+            res => res,
+        }
+    }
+
+    /// Whether the socket was created with the MPTCP protocol.
+    pub(crate) fn is_mptcp(&self) -> io::Result<bool> {
+        let protocol = socket2::SockRef::from(self).protocol()?;
+        Ok(protocol == Some(libc::IPPROTO_MPTCP.into()))
+    }
+
+    /// The CPU the socket prefers, or which handled its last incoming
+    /// packet, `SO_INCOMING_CPU`.
+    pub(crate) fn incoming_cpu(&self) -> io::Result<usize> {
+        socket2::SockRef::from(self).cpu_affinity()
+    }
+
+    pub(crate) fn set_incoming_cpu(&self, cpu: usize) -> io::Result<()> {
+        socket2::SockRef::from(self).set_cpu_affinity(cpu)
+    }
+
+    /// Attach a classic BPF program to the `SO_REUSEPORT` group of the socket,
+    /// selecting the socket of index `cpu % group_size` for the packets
+    /// handled by `cpu`.
+    pub(crate) fn set_reuseport_cpu_steering(&self, group_size: u32) -> io::Result<()> {
+        if group_size == 0 {
+            return Err(io::Error::from_raw_os_error(libc::EINVAL));
+        }
+
+        let mut filter = [
+            // A = current CPU
+            bpf_stmt(
+                libc::BPF_LD | libc::BPF_W | libc::BPF_ABS,
+                (libc::SKF_AD_OFF + libc::SKF_AD_CPU) as u32,
+            ),
+            // A = A % group_size
+            bpf_stmt(libc::BPF_ALU | libc::BPF_MOD | libc::BPF_K, group_size),
+            // Select the socket of index A
+            bpf_stmt(libc::BPF_RET | libc::BPF_A, 0),
+        ];
+        let program = libc::sock_fprog {
+            len: filter.len() as libc::c_ushort,
+            filter: filter.as_mut_ptr(),
+        };
+
+        syscall!(setsockopt(
+            self.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_ATTACH_REUSEPORT_CBPF,
+            &program as *const libc::sock_fprog as *const libc::c_void,
+            std::mem::size_of::<libc::sock_fprog>() as libc::socklen_t
+        ))?;
+        Ok(())
+    }
+
     pub(crate) fn bind_unix<P: AsRef<Path>>(
         path: P,
         socket_type: libc::c_int,
     ) -> io::Result<Socket> {
         let addr = socket2::SockAddr::unix(path.as_ref())?;
-        Self::bind_internal(addr, libc::AF_UNIX.into(), socket_type.into())
+        Self::bind_unix_sockaddr(addr, socket_type)
+    }
+
+    pub(crate) fn bind_unix_sockaddr(
+        addr: socket2::SockAddr,
+        socket_type: libc::c_int,
+    ) -> io::Result<Socket> {
+        Self::bind_internal(addr, libc::AF_UNIX.into(), socket_type.into(), None)
+    }
+
+    pub(crate) fn bind_vsock(
+        addr: socket2::SockAddr,
+        socket_type: libc::c_int,
+    ) -> io::Result<Socket> {
+        Self::bind_internal(addr, libc::AF_VSOCK.into(), socket_type.into(), None)
+    }
+
+    /// Create a pair of connected unix sockets.
+    pub(crate) fn pair_unix(socket_type: libc::c_int) -> io::Result<(Socket, Socket)> {
+        let mut fds = [-1; 2];
+        syscall!(socketpair(
+            libc::AF_UNIX,
+            socket_type | libc::SOCK_CLOEXEC,
+            0,
+            fds.as_mut_ptr()
+        ))?;
+
+        let first = Socket {
+            fd: SharedFd::new(fds[0]),
+        };
+        let second = Socket {
+            fd: SharedFd::new(fds[1]),
+        };
+        Ok((first, second))
     }
 
     fn bind_internal(
         socket_addr: socket2::SockAddr,
         domain: socket2::Domain,
         socket_type: socket2::Type,
+        protocol: Option<socket2::Protocol>,
     ) -> io::Result<Socket> {
-        let sys_listener = socket2::Socket::new(domain, socket_type, None)?;
-        let addr = socket2::SockAddr::from(socket_addr);
+        let sys_listener = socket2::Socket::new(domain, socket_type, protocol)?;
+        let addr = socket_addr;
 
-        sys_listener.set_reuse_port(true)?;
-        sys_listener.set_reuse_address(true)?;
+        // Only IP sockets support `SO_REUSEPORT`.
+        if domain == socket2::Domain::IPV4 || domain == socket2::Domain::IPV6 {
+            sys_listener.set_reuse_port(true)?;
+            sys_listener.set_reuse_address(true)?;
+        }
 
         // TODO: config for buffer sizes
         // sys_listener.set_send_buffer_size(send_buf_size)?;
@@ -128,10 +454,47 @@ impl Socket {
         Ok(Self { fd })
     }
 
+    pub(crate) fn bind_sockaddr(&self, socket_addr: &socket2::SockAddr) -> io::Result<()> {
+        syscall!(bind(
+            self.as_raw_fd(),
+            socket_addr.as_ptr(),
+            socket_addr.len()
+        ))?;
+        Ok(())
+    }
+
     pub(crate) fn listen(&self, backlog: libc::c_int) -> io::Result<()> {
         syscall!(listen(self.as_raw_fd(), backlog))?;
         Ok(())
     }
+
+    /// The address the socket is bound to.
+    pub(crate) fn local_sockaddr(&self) -> io::Result<socket2::SockAddr> {
+        let fd = self.as_raw_fd();
+        let (_, addr) = unsafe {
+            socket2::SockAddr::init(|addr_storage, len| {
+                if libc::getsockname(fd, addr_storage.cast(), len) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            })?
+        };
+        Ok(addr)
+    }
+
+    /// The address of the peer the socket is connected to.
+    pub(crate) fn peer_sockaddr(&self) -> io::Result<socket2::SockAddr> {
+        let fd = self.as_raw_fd();
+        let (_, addr) = unsafe {
+            socket2::SockAddr::init(|addr_storage, len| {
+                if libc::getpeername(fd, addr_storage.cast(), len) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            })?
+        };
+        Ok(addr)
+    }
 }
 
 impl AsRawFd for Socket {
@@ -139,3 +502,21 @@ impl AsRawFd for Socket {
         self.fd.raw_fd()
     }
 }
+
+fn bpf_stmt(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+/// Whether `socket()` failed because MPTCP is not available, as opposed to
+/// failing for any protocol.
+fn mptcp_unsupported(err: &io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(libc::EPROTONOSUPPORT) | Some(libc::ENOPROTOOPT) | Some(libc::EINVAL)
+    )
+}
@@ -0,0 +1,61 @@
+use crate::driver::{Op, SharedFd};
+
+use std::io;
+
+use io_uring::{opcode, types};
+
+/// Move data between two fds, one of which is a pipe, without copying it to
+/// user space.
+pub(crate) struct Splice {
+    /// Hold strong refs to the FDs, preventing them from being closed while
+    /// the operation is in-flight.
+    #[allow(dead_code)]
+    fd_in: SharedFd,
+    #[allow(dead_code)]
+    fd_out: SharedFd,
+}
+
+impl Op<Splice> {
+    /// Submit a request to move up to `len` bytes from `fd_in` at `off_in`
+    /// to `fd_out` at `off_out`, `-1` standing for the position of the fd,
+    /// and required for pipes, with the `SPLICE_F_*` flags `flags`.
+    pub(crate) fn splice(
+        fd_in: &SharedFd,
+        off_in: i64,
+        fd_out: &SharedFd,
+        off_out: i64,
+        len: u32,
+        flags: u32,
+    ) -> io::Result<Op<Splice>> {
+        Op::submit_with(
+            Splice {
+                fd_in: fd_in.for_op(),
+                fd_out: fd_out.for_op(),
+            },
+            |_| {
+                opcode::Splice::new(
+                    types::Fd(fd_in.raw_fd()),
+                    off_in,
+                    types::Fd(fd_out.raw_fd()),
+                    off_out,
+                    len,
+                )
+                .flags(flags)
+                .build()
+            },
+        )
+    }
+}
+
+/// Move up to `len` bytes from `fd_in` to `fd_out`, returning the number of
+/// bytes moved. See `Op::splice`.
+pub(crate) async fn splice(
+    fd_in: &SharedFd,
+    off_in: i64,
+    fd_out: &SharedFd,
+    off_out: i64,
+    len: u32,
+) -> io::Result<usize> {
+    let op = Op::splice(fd_in, off_in, fd_out, off_out, len, 0)?;
+    Ok(op.await.result? as usize)
+}
@@ -0,0 +1,83 @@
+use io_uring::{opcode, squeue, IoUring};
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+/// Offset of the SQE array in the mappings of a ring, `IORING_OFF_SQES`.
+const IORING_OFF_SQES: libc::off_t = 0x1000_0000;
+
+/// Layout of the kernel's `io_uring_sqe`, giving access to the fields of an
+/// already built entry.
+#[repr(C)]
+pub(crate) struct RawSqe {
+    pub(crate) opcode: u8,
+    pub(crate) flags: u8,
+    pub(crate) ioprio: u16,
+    pub(crate) fd: i32,
+    pub(crate) off: u64,
+    pub(crate) addr: u64,
+    pub(crate) len: u32,
+    pub(crate) op_flags: u32,
+    pub(crate) user_data: u64,
+    pub(crate) buf_index: u16,
+    pub(crate) personality: u16,
+    pub(crate) splice_fd_in: i32,
+    pub(crate) addr3: u64,
+    pub(crate) pad: u64,
+}
+
+const _: () = assert!(std::mem::size_of::<RawSqe>() == std::mem::size_of::<squeue::Entry>());
+
+pub(crate) fn raw(sqe: &squeue::Entry) -> &RawSqe {
+    // Safety: `Entry` is a `repr(C)` wrapper around the kernel SQE, which has
+    // the same layout as `RawSqe`.
+    unsafe { &*(sqe as *const squeue::Entry as *const RawSqe) }
+}
+
+pub(crate) fn raw_mut(sqe: &mut squeue::Entry) -> &mut RawSqe {
+    // Safety: see `raw`
+    unsafe { &mut *(sqe as *mut squeue::Entry as *mut RawSqe) }
+}
+
+/// Whether the entry at position `pos` of the submission queue was consumed
+/// by the kernel, which consumes the entry at `head` next.
+pub(crate) fn consumed(head: u32, pos: u32) -> bool {
+    (head.wrapping_sub(pos) as i32) > 0
+}
+
+/// Turns the `count` entries before `tail` in the submission queue of
+/// `uring` into no-ops completing untracked, for the kernel not to start the
+/// operations they were pushed for.
+///
+/// The queue only gives access to the entries being pushed: the entries are
+/// rewritten through a mapping of their own of the SQE array, in which the
+/// queue places its positions in order.
+pub(crate) fn neutralize(uring: &IoUring, tail: u32, count: usize) -> io::Result<()> {
+    let entries = uring.params().sq_entries() as usize;
+    let len = entries * std::mem::size_of::<squeue::Entry>();
+
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            uring.as_raw_fd(),
+            IORING_OFF_SQES,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+
+    let sqes = ptr as *mut squeue::Entry;
+    let nop = opcode::Nop::new().build().user_data(u64::MAX);
+    for i in 1..=count {
+        let slot = tail.wrapping_sub(i as u32) as usize & (entries - 1);
+        // Safety: the slot is in the mapping, and the kernel does not read
+        // the entries until the next submission
+        unsafe { sqes.add(slot).write_volatile(nop.clone()) };
+    }
+
+    unsafe { libc::munmap(ptr, len) };
+    Ok(())
+}
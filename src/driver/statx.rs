@@ -0,0 +1,40 @@
+use crate::driver::{Op, SharedFd};
+
+use std::ffi::CStr;
+use std::io;
+
+use io_uring::{opcode, types};
+
+/// Get the status of an open file.
+pub(crate) struct Statx {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(dead_code)]
+    fd: SharedFd,
+
+    /// Status written by the kernel, boxed to keep its address stable.
+    pub(crate) statx: Box<libc::statx>,
+}
+
+impl Op<Statx> {
+    /// Submit a request for the fields of `mask` of the status of `fd`.
+    pub(crate) fn statx(fd: &SharedFd, mask: u32) -> io::Result<Op<Statx>> {
+        // An empty path refers to `fd` itself
+        const EMPTY: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(b"\0") };
+
+        Op::submit_with(
+            Statx {
+                fd: fd.for_op(),
+                statx: Box::new(unsafe { std::mem::zeroed() }),
+            },
+            |statx| {
+                let buf = &mut *statx.statx as *mut libc::statx as *mut types::statx;
+
+                opcode::Statx::new(types::Fd(fd.raw_fd()), EMPTY.as_ptr(), buf)
+                    .flags(libc::AT_EMPTY_PATH)
+                    .mask(mask)
+                    .build()
+            },
+        )
+    }
+}
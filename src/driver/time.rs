@@ -0,0 +1,138 @@
+use crate::driver::{Inner, Op};
+
+use std::io;
+use std::time::Duration;
+
+/// Virtual clock. While paused, timers are completed by the driver as the
+/// clock is advanced instead of being submitted to the kernel.
+#[derive(Default)]
+pub(crate) struct Clock {
+    /// Virtual time elapsed since the clock was paused, `None` while running.
+    now: Option<Duration>,
+
+    /// Pending timers, as deadlines and operation indices.
+    timers: Vec<(Duration, usize)>,
+}
+
+impl Clock {
+    pub(crate) fn pause(&mut self) {
+        assert!(self.now.is_none(), "time is already paused");
+        self.now = Some(Duration::ZERO);
+    }
+
+    /// Advance the clock, returning the operation indices of the expired
+    /// timers in deadline order.
+    pub(crate) fn advance(&mut self, duration: Duration) -> Vec<usize> {
+        let now = self.now.as_mut().expect("time is not paused");
+        *now += duration;
+        let now = *now;
+
+        self.timers.sort_by_key(|&(deadline, _)| deadline);
+        let expired = self
+            .timers
+            .partition_point(|&(deadline, _)| deadline <= now);
+        self.timers
+            .drain(..expired)
+            .map(|(_, index)| index)
+            .collect()
+    }
+
+    /// Indices of the pending timers, which are forgotten by the clock.
+    pub(crate) fn drain(&mut self) -> Vec<usize> {
+        self.timers.drain(..).map(|(_, index)| index).collect()
+    }
+}
+
+/// Register a timer with the virtual clock of the current driver. If the clock
+/// is not paused, the timer is handed back to be submitted to the kernel.
+pub(crate) fn register<T>(data: T, duration: Duration) -> Result<Op<T>, T> {
+    super::CURRENT.with(|inner_rc| {
+        let mut inner_ref = inner_rc.borrow_mut();
+        let inner = &mut *inner_ref;
+
+        let now = match inner.clock.now {
+            Some(now) => now,
+            None => return Err(data),
+        };
+
+        // Handed back to be submitted, which fails
+        if inner.check_room(1).is_err() {
+            return Err(data);
+        }
+
+        let op = Op::new(data, inner, inner_rc);
+        inner.set_kind(op.index, crate::OpKind::Timeout);
+
+        if duration.is_zero() {
+            // The operation was just created, it cannot have been ignored.
+            let _ = inner.ops.complete(op.index, expired(), 0);
+        } else {
+            inner.clock.timers.push((now + duration, op.index));
+        }
+
+        Ok(op)
+    })
+}
+
+/// Cancel a pending virtual timer of `inner`, returning `false` if the clock
+/// is not tracking the operation.
+pub(super) fn cancel(inner: &mut Inner, index: usize) -> bool {
+    match inner.clock.timers.iter().position(|&(_, i)| i == index) {
+        Some(pos) => {
+            inner.clock.timers.remove(pos);
+            let cancelled = Err(io::Error::from_raw_os_error(libc::ECANCELED));
+
+            if let Some(orphan) = inner.ops.complete(index, cancelled, 0) {
+                inner.orphans.push(orphan);
+            }
+
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether the virtual clock of the current driver is paused.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn paused() -> bool {
+    super::CURRENT.with(|inner| inner.borrow().clock.now.is_some())
+}
+
+/// Pause the virtual clock of the current driver.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn pause() {
+    super::CURRENT.with(|inner| inner.borrow_mut().clock.pause());
+}
+
+/// Advance the virtual clock of the current driver, completing the expired
+/// timers.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub(crate) fn advance(duration: Duration) {
+    let orphans = super::CURRENT.with(|inner_rc| {
+        let mut inner = inner_rc.borrow_mut();
+        let inner: &mut Inner = &mut inner;
+        let mut orphans = vec![];
+
+        for index in inner.clock.advance(duration) {
+            orphans.extend(inner.ops.complete(index, expired(), 0));
+        }
+
+        orphans
+    });
+
+    drop(orphans);
+}
+
+/// Result of an expired timer, matching the kernel.
+fn expired() -> io::Result<u32> {
+    Err(io::Error::from_raw_os_error(libc::ETIME))
+}
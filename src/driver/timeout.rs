@@ -0,0 +1,75 @@
+use crate::driver::{sqe, Op};
+
+use io_uring::{opcode, types};
+use std::io;
+use std::time::Duration;
+
+/// `IORING_TIMEOUT_MULTISHOT`, from `io_uring.h`
+const IORING_TIMEOUT_MULTISHOT: u32 = 1 << 6;
+
+/// Relative timer. Completes with `ETIME` once it expires.
+pub(crate) struct Timeout {
+    /// Read by the kernel when the SQE is submitted. Boxed so the address is
+    /// stable.
+    timespec: Box<types::Timespec>,
+}
+
+impl Op<Timeout> {
+    /// Submit a timer expiring after `duration`.
+    pub(crate) fn timeout(duration: Duration) -> io::Result<Op<Timeout>> {
+        let timespec = Box::new(
+            types::Timespec::new()
+                .sec(duration.as_secs())
+                .nsec(duration.subsec_nanos()),
+        );
+
+        #[cfg(feature = "test-util")]
+        let timespec = match super::time::register(Timeout { timespec }, duration) {
+            Ok(op) => return Ok(op),
+            Err(timeout) => timeout.timespec,
+        };
+
+        Op::submit_with(Timeout { timespec }, |timeout| {
+            opcode::Timeout::new(&*timeout.timespec).build()
+        })
+    }
+
+    /// Submit a timer expiring every `period`, posting a completion with
+    /// `ETIME` each time, until canceled. Kernels older than 6.4 fail the
+    /// first completion with `EINVAL`.
+    ///
+    /// The timer is submitted to the kernel even while time is paused.
+    pub(crate) fn interval(period: Duration) -> io::Result<Op<Timeout>> {
+        let timespec = Box::new(
+            types::Timespec::new()
+                .sec(period.as_secs())
+                .nsec(period.subsec_nanos()),
+        );
+
+        Op::submit_multishot_with(Timeout { timespec }, |timeout| {
+            let mut entry = opcode::Timeout::new(&*timeout.timespec).build();
+            sqe::raw_mut(&mut entry).op_flags |= IORING_TIMEOUT_MULTISHOT;
+            entry
+        })
+    }
+
+    /// Cancel the timer. If it has not expired yet, it completes with
+    /// `ECANCELED`.
+    ///
+    /// Goes through the driver of the timer rather than the current one, as
+    /// timers are dropped with their tasks once the runtime shut down.
+    pub(crate) fn cancel(&self) {
+        let mut inner = self.driver.borrow_mut();
+
+        #[cfg(feature = "test-util")]
+        if super::time::cancel(&mut inner, self.index) {
+            return;
+        }
+
+        // The removal is not tracked, the timer completion is enough.
+        let remove = opcode::TimeoutRemove::new(self.index as _).build();
+        if inner.push_untracked(remove).is_ok() {
+            let _ = inner.submit();
+        }
+    }
+}
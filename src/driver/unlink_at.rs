@@ -26,7 +26,7 @@ impl Op<Unlink> {
 
         let path = driver::util::cstr(path)?;
 
-        Op::submit_with(Unlink { path }, |unlink| {
+        Op::try_submit_with(Unlink { path }, |unlink| {
             // Get a reference to the memory. The string will be held by the
             // operation state and will not be accessed again until the operation
             // completes.
@@ -2,7 +2,7 @@ use std::ffi::CString;
 use std::io;
 use std::path::Path;
 
-pub(super) fn cstr(p: &Path) -> io::Result<CString> {
+pub(crate) fn cstr(p: &Path) -> io::Result<CString> {
     use std::os::unix::ffi::OsStrExt;
     Ok(CString::new(p.as_os_str().as_bytes())?)
 }
@@ -0,0 +1,34 @@
+use crate::driver::{sqe, Op};
+
+use io_uring::opcode;
+use std::io;
+
+/// `IORING_OP_WAITID`, not exposed by `io-uring` yet
+pub(super) const IORING_OP_WAITID: u8 = 50;
+
+/// Wait for a change of state of a child process.
+pub(crate) struct Waitid {
+    /// Written by the kernel once the child changed state
+    info: Box<libc::siginfo_t>,
+}
+
+impl Op<Waitid> {
+    /// Submit a wait for the child process `pid` to change state, with the
+    /// `W*` options of `waitid(2)` in `options`.
+    pub(crate) fn waitid(pid: libc::pid_t, options: libc::c_int) -> io::Result<Op<Waitid>> {
+        // Safety: `siginfo_t` is plain data
+        let info = Box::new(unsafe { std::mem::zeroed() });
+
+        Op::submit_with(Waitid { info }, |waitid| {
+            // Start from a blank entry, see `Xattr::entry`
+            let mut entry = opcode::Nop::new().build();
+            let raw = sqe::raw_mut(&mut entry);
+            raw.opcode = IORING_OP_WAITID;
+            raw.len = libc::P_PID;
+            raw.fd = pid;
+            raw.splice_fd_in = options;
+            raw.off = &mut *waitid.info as *mut libc::siginfo_t as u64;
+            entry
+        })
+    }
+}
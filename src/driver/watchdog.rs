@@ -0,0 +1,165 @@
+use crate::builder::SlowOp;
+use crate::OpKind;
+
+use io_uring::squeue;
+use std::os::unix::io::RawFd;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// `IOSQE_FIXED_FILE`, the fd of the SQE is an index into the registered
+/// files
+const IOSQE_FIXED_FILE: u8 = 1;
+
+/// Called with each slow operation.
+pub(crate) type Hook = Arc<dyn Fn(&SlowOp) + Send + Sync>;
+
+/// Reports the operations in flight for longer than a threshold.
+pub(crate) struct Watchdog {
+    threshold: Duration,
+
+    /// Whether slow operations are cancelled once reported
+    cancel: bool,
+
+    /// Called with each slow operation, logs it to stderr if unset
+    hook: Option<Hook>,
+
+    /// Each in-flight operation, indexed by slab index
+    in_flight: Vec<Option<InFlight>>,
+}
+
+struct InFlight {
+    kind: OpKind,
+
+    /// Fd the operation applies to, if any
+    fd: Option<RawFd>,
+
+    pushed_at: Instant,
+
+    /// Set once reported, each operation is reported once
+    reported: bool,
+}
+
+impl Watchdog {
+    pub(crate) fn new(threshold: Duration, cancel: bool, hook: Option<Hook>) -> Watchdog {
+        Watchdog {
+            threshold,
+            cancel,
+            hook,
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// Interval between two checks, for operations to be reported at most
+    /// a quarter of the threshold late.
+    pub(crate) fn period(&self) -> Duration {
+        (self.threshold / 4).max(Duration::from_millis(1))
+    }
+
+    pub(crate) fn pushed(&mut self, index: usize, sqe: &squeue::Entry) {
+        if self.in_flight.len() <= index {
+            self.in_flight.resize_with(index + 1, || None);
+        }
+
+        let raw = super::sqe::raw(sqe);
+        let fd = if raw.fd < 0 || raw.flags & IOSQE_FIXED_FILE != 0 {
+            None
+        } else {
+            Some(raw.fd)
+        };
+
+        self.in_flight[index] = Some(InFlight {
+            kind: OpKind::from(raw.opcode),
+            fd,
+            pushed_at: Instant::now(),
+            reported: false,
+        });
+    }
+
+    pub(crate) fn completed(&mut self, index: usize) {
+        if let Some(in_flight) = self.in_flight.get_mut(index) {
+            *in_flight = None;
+        }
+    }
+
+    /// Returns the slab index of the operations on files and block devices
+    /// in flight for longer than the threshold, which were not reported yet.
+    ///
+    /// Operations on other fds, such as sockets and pipes, and operations
+    /// without an fd, such as timeouts, may wait for an event indefinitely
+    /// and are never reported.
+    pub(crate) fn check(&mut self) -> Vec<(usize, SlowOp)> {
+        let now = Instant::now();
+        let cancel = self.cancel;
+        let mut slow = Vec::new();
+
+        for (index, in_flight) in self.in_flight.iter_mut().enumerate() {
+            let in_flight = match in_flight {
+                Some(in_flight) if !in_flight.reported => in_flight,
+                _ => continue,
+            };
+
+            let age = now - in_flight.pushed_at;
+            if age < self.threshold {
+                continue;
+            }
+
+            let fd = match in_flight.fd {
+                Some(fd) if is_storage(fd) => fd,
+                _ => continue,
+            };
+
+            in_flight.reported = true;
+            slow.push((
+                index,
+                SlowOp {
+                    kind: in_flight.kind,
+                    fd,
+                    age,
+                    cancelled: cancel,
+                    leaked: false,
+                },
+            ));
+        }
+
+        slow
+    }
+
+    /// Describes the operation at `index`, still in flight on shutdown.
+    pub(crate) fn leaked(&self, index: usize) -> Option<SlowOp> {
+        let in_flight = self.in_flight.get(index)?.as_ref()?;
+
+        Some(SlowOp {
+            kind: in_flight.kind,
+            fd: in_flight.fd.unwrap_or(-1),
+            age: in_flight.pushed_at.elapsed(),
+            cancelled: true,
+            leaked: true,
+        })
+    }
+
+    pub(crate) fn cancels(&self) -> bool {
+        self.cancel
+    }
+
+    pub(crate) fn hook(&self) -> Option<Hook> {
+        self.hook.clone()
+    }
+}
+
+/// Reports a slow operation to the hook, or logs it to stderr.
+pub(crate) fn report(hook: Option<&(dyn Fn(&SlowOp) + Send + Sync)>, op: &SlowOp) {
+    match hook {
+        Some(hook) => hook(op),
+        None => eprintln!("tokio-uring: {}", op),
+    }
+}
+
+/// Returns `true` if `fd` is a regular file or a block device.
+fn is_storage(fd: RawFd) -> bool {
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstat(fd, &mut stat) } == -1 {
+        return false;
+    }
+
+    matches!(stat.st_mode & libc::S_IFMT, libc::S_IFREG | libc::S_IFBLK)
+}
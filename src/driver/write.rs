@@ -1,5 +1,6 @@
 use crate::{
-    buf::IoBuf,
+    buf::{FixedBuf, IoBuf},
+    driver::recycle::OrphanBuf,
     driver::{Op, SharedFd},
     BufResult,
 };
@@ -17,13 +18,32 @@ pub(crate) struct Write<T> {
     pub(crate) buf: T,
 }
 
+impl<T: IoBuf> OrphanBuf for Write<T> {
+    type Buf = T;
+
+    fn into_buf(self) -> T {
+        self.buf
+    }
+}
+
 impl<T: IoBuf> Op<Write<T>> {
     pub(crate) fn write_at(fd: &SharedFd, buf: T, offset: u64) -> io::Result<Op<Write<T>>> {
+        Op::write_at_with_flags(fd, buf, offset, 0).map_err(|(e, _)| e)
+    }
+
+    /// Write with the `RWF_*` flags of `pwritev2(2)`, returning the buffer
+    /// along with the error if the write cannot be submitted.
+    pub(crate) fn write_at_with_flags(
+        fd: &SharedFd,
+        buf: T,
+        offset: u64,
+        flags: libc::c_int,
+    ) -> Result<Op<Write<T>>, (io::Error, T)> {
         use io_uring::{opcode, types};
 
-        Op::submit_with(
+        Op::submit_or_return(
             Write {
-                fd: fd.clone(),
+                fd: fd.for_op(),
                 buf,
             },
             |write| {
@@ -33,9 +53,12 @@ impl<T: IoBuf> Op<Write<T>> {
 
                 opcode::Write::new(types::Fd(fd.raw_fd()), ptr, len as _)
                     .offset(offset as _)
+                    .rw_flags(flags)
                     .build()
             },
         )
+        .map_err(|(e, write)| (e, write.buf))
+        .map(Op::recycle_orphan_buf)
     }
 
     pub(crate) async fn write(mut self) -> BufResult<usize, T> {
@@ -51,4 +74,41 @@ impl<T: IoBuf> Op<Write<T>> {
         let complete = ready!(Pin::new(self).poll(cx));
         Poll::Ready((complete.result.map(|v| v as _), complete.data.buf))
     }
+
+    /// Cancel the write, returning the buffer once the kernel released it.
+    ///
+    /// If the write completed before the cancellation reached it, its result
+    /// is returned instead of `ECANCELED`.
+    pub(crate) async fn cancel_write(self) -> BufResult<usize, T> {
+        let complete = self.cancel_and_wait().await;
+        (complete.result.map(|v| v as _), complete.data.buf)
+    }
+}
+
+impl Op<Write<FixedBuf>> {
+    /// Write from a registered buffer, `IORING_OP_WRITE_FIXED`, returning
+    /// the buffer along with the error if the write cannot be submitted.
+    pub(crate) fn write_fixed_at(
+        fd: &SharedFd,
+        buf: FixedBuf,
+        offset: u64,
+    ) -> Result<Op<Write<FixedBuf>>, (io::Error, FixedBuf)> {
+        use io_uring::{opcode, types};
+
+        Op::submit_or_return(
+            Write {
+                fd: fd.for_op(),
+                buf,
+            },
+            |write| {
+                let ptr = write.buf.stable_ptr();
+                let len = write.buf.bytes_init();
+                let index = write.buf.buf_index();
+                opcode::WriteFixed::new(types::Fd(fd.raw_fd()), ptr, len as _, index)
+                    .offset(offset as _)
+                    .build()
+            },
+        )
+        .map_err(|(e, write)| (e, write.buf))
+    }
 }
@@ -0,0 +1,78 @@
+use crate::driver::{self, sqe, Op};
+
+use std::ffi::CString;
+use std::io;
+use std::path::Path;
+
+use io_uring::{opcode, squeue};
+
+/// `IORING_OP_SETXATTR`, not exposed by `io-uring` yet
+const IORING_OP_SETXATTR: u8 = 42;
+
+/// `IORING_OP_GETXATTR`, not exposed by `io-uring` yet
+const IORING_OP_GETXATTR: u8 = 44;
+
+/// Get or set an extended attribute of a path.
+pub(crate) struct Xattr {
+    pub(crate) path: CString,
+    pub(crate) name: CString,
+
+    /// Value to set, or buffer receiving the value. The kernel accesses up to
+    /// its capacity.
+    pub(crate) value: Vec<u8>,
+}
+
+impl Op<Xattr> {
+    /// Submit a request to read the attribute `name` of `path` into `value`.
+    ///
+    /// Completes with the length of the value. If `value` has no capacity,
+    /// completes with the length of the value without reading it.
+    pub(crate) fn get_xattr(path: &Path, name: &str, value: Vec<u8>) -> io::Result<Op<Xattr>> {
+        let xattr = Xattr::new(path, name, value)?;
+
+        Op::submit_with(xattr, |xattr| {
+            let len = xattr.value.capacity();
+            xattr.entry(IORING_OP_GETXATTR, len, 0)
+        })
+    }
+
+    /// Submit a request to set the attribute `name` of `path` to `value`.
+    pub(crate) fn set_xattr(
+        path: &Path,
+        name: &str,
+        value: Vec<u8>,
+        flags: libc::c_int,
+    ) -> io::Result<Op<Xattr>> {
+        let xattr = Xattr::new(path, name, value)?;
+
+        Op::submit_with(xattr, |xattr| {
+            let len = xattr.value.len();
+            xattr.entry(IORING_OP_SETXATTR, len, flags as u32)
+        })
+    }
+}
+
+impl Xattr {
+    fn new(path: &Path, name: &str, value: Vec<u8>) -> io::Result<Xattr> {
+        Ok(Xattr {
+            path: driver::util::cstr(path)?,
+            name: CString::new(name)?,
+            value,
+        })
+    }
+
+    fn entry(&mut self, opcode: u8, len: usize, flags: u32) -> squeue::Entry {
+        // Start from a blank entry, and fill in the fields of the opcode. The
+        // strings and the value are held by the operation state until the
+        // operation completes.
+        let mut entry = opcode::Nop::new().build();
+        let raw = sqe::raw_mut(&mut entry);
+        raw.opcode = opcode;
+        raw.addr = self.name.as_ptr() as u64;
+        raw.off = self.value.as_mut_ptr() as u64;
+        raw.len = len as u32;
+        raw.op_flags = flags;
+        raw.addr3 = self.path.as_ptr() as u64;
+        entry
+    }
+}
@@ -0,0 +1,208 @@
+use crate::driver;
+use crate::OpKind;
+
+use std::fmt;
+use std::os::unix::io::RawFd;
+
+/// Returns a snapshot of the state of the `io_uring` instance of the current
+/// runtime, to find out why operations do not complete.
+///
+/// Its `Display` implementation prints the positions of the submission and
+/// completion queues, the operations in flight by kind, the backlog of
+/// submissions and of tasks waiting for room, and the registered resources.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// Dumping the state of the ring when a request times out:
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tokio_uring::net::TcpStream;
+///
+/// tokio_uring::start(async {
+///     let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await.unwrap();
+///
+///     let read = stream.read(vec![0; 4096]);
+///     if tokio::time::timeout(Duration::from_secs(30), read).await.is_err() {
+///         eprintln!("{}", tokio_uring::debug_dump());
+///     }
+/// });
+/// ```
+pub fn debug_dump() -> DriverDump {
+    driver::dump()
+}
+
+/// The state of the `io_uring` instance of a runtime, returned by
+/// [`debug_dump`].
+///
+/// The head and tail positions of the queues, and the registered resources,
+/// are read from `/proc/self/fdinfo`, and are `None` if unavailable.
+#[derive(Debug, Clone)]
+pub struct DriverDump {
+    pub(crate) fd: RawFd,
+    pub(crate) sq_entries: u32,
+    pub(crate) cq_entries: u32,
+    pub(crate) sq_head: Option<u32>,
+    pub(crate) sq_tail: Option<u32>,
+    pub(crate) cq_head: Option<u32>,
+    pub(crate) cq_tail: Option<u32>,
+    pub(crate) unsubmitted: usize,
+    pub(crate) unprocessed: usize,
+    pub(crate) dropped: u32,
+    pub(crate) overflow: u32,
+    pub(crate) in_flight: Vec<(OpKind, usize)>,
+    pub(crate) ignored: usize,
+    pub(crate) completed: usize,
+    pub(crate) waiting_for_room: usize,
+    pub(crate) max_ops: Option<usize>,
+    pub(crate) registered_bytes: usize,
+    pub(crate) registered_files: Option<u32>,
+    pub(crate) registered_buffers: Option<u32>,
+}
+
+impl DriverDump {
+    /// Returns the file descriptor of the ring.
+    pub fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Returns the number of entries of the submission queue and of the
+    /// completion queue.
+    pub fn entries(&self) -> (u32, u32) {
+        (self.sq_entries, self.cq_entries)
+    }
+
+    /// Returns the head and the tail of the submission queue: the entries
+    /// between them were pushed, but not consumed by the kernel yet.
+    pub fn sq_head_tail(&self) -> Option<(u32, u32)> {
+        Some((self.sq_head?, self.sq_tail?))
+    }
+
+    /// Returns the head and the tail of the completion queue: the entries
+    /// between them were posted by the kernel, but not processed by the
+    /// runtime yet.
+    pub fn cq_head_tail(&self) -> Option<(u32, u32)> {
+        Some((self.cq_head?, self.cq_tail?))
+    }
+
+    /// Returns the number of entries of the submission queue not submitted
+    /// to the kernel yet.
+    pub fn unsubmitted(&self) -> usize {
+        self.unsubmitted
+    }
+
+    /// Returns the number of completions posted by the kernel, which the
+    /// runtime did not process yet.
+    pub fn unprocessed(&self) -> usize {
+        self.unprocessed
+    }
+
+    /// Returns the number of invalid submission entries the kernel dropped.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Returns the number of completions lost to overflows of the completion
+    /// queue.
+    pub fn overflow(&self) -> u32 {
+        self.overflow
+    }
+
+    /// Returns the number of operations in flight, by kind, in increasing
+    /// order of opcode.
+    pub fn in_flight(&self) -> &[(OpKind, usize)] {
+        &self.in_flight
+    }
+
+    /// Returns the number of operations in flight whose future was dropped,
+    /// which the runtime waits for before releasing their resources.
+    pub fn ignored(&self) -> usize {
+        self.ignored
+    }
+
+    /// Returns the number of operations completed, whose future was not
+    /// polled since.
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+
+    /// Returns the number of tasks waiting for room to submit operations,
+    /// see [`Builder::max_ops`](crate::Builder::max_ops).
+    pub fn waiting_for_room(&self) -> usize {
+        self.waiting_for_room
+    }
+
+    /// Returns the maximum number of operations in flight, if bounded.
+    pub fn max_ops(&self) -> Option<usize> {
+        self.max_ops
+    }
+
+    /// Returns the number of bytes of the buffers registered by the runtime.
+    pub fn registered_bytes(&self) -> usize {
+        self.registered_bytes
+    }
+
+    /// Returns the number of slots of the registered file table.
+    pub fn registered_files(&self) -> Option<u32> {
+        self.registered_files
+    }
+
+    /// Returns the number of registered buffers.
+    pub fn registered_buffers(&self) -> Option<u32> {
+        self.registered_buffers
+    }
+}
+
+impl fmt::Display for DriverDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn position(value: Option<u32>) -> String {
+            value.map_or_else(|| "?".to_string(), |value| value.to_string())
+        }
+
+        writeln!(f, "io_uring fd {}", self.fd)?;
+        writeln!(
+            f,
+            "  sq: {} entries, head {}, tail {}, {} unsubmitted, {} dropped",
+            self.sq_entries,
+            position(self.sq_head),
+            position(self.sq_tail),
+            self.unsubmitted,
+            self.dropped
+        )?;
+        writeln!(
+            f,
+            "  cq: {} entries, head {}, tail {}, {} unprocessed, {} overflowed",
+            self.cq_entries,
+            position(self.cq_head),
+            position(self.cq_tail),
+            self.unprocessed,
+            self.overflow
+        )?;
+
+        let total: usize = self.in_flight.iter().map(|(_, count)| count).sum();
+        write!(f, "  in flight: {}", total)?;
+        for (kind, count) in &self.in_flight {
+            write!(f, ", {}: {}", kind, count)?;
+        }
+        writeln!(
+            f,
+            "\n  ignored: {}, completed: {}, waiting for room: {}, max ops: {}",
+            self.ignored,
+            self.completed,
+            self.waiting_for_room,
+            self.max_ops
+                .map_or_else(|| "unbounded".to_string(), |max| max.to_string())
+        )?;
+        write!(
+            f,
+            "  registered: {} buffers ({} bytes), {} files",
+            position(self.registered_buffers),
+            self.registered_bytes,
+            position(self.registered_files)
+        )
+    }
+}
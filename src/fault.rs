@@ -0,0 +1,235 @@
+//! Fault injection for testing error handling.
+//!
+//! Faults are injected into the operations submitted on the current runtime,
+//! letting applications exercise their error paths without a misbehaving
+//! kernel or device. This module is only available with the `test-util`
+//! feature.
+//!
+//! A fault matches operations by [`Target`] and, when applied, either:
+//!
+//! * completes the operation with an error, without submitting it,
+//! * shortens the length of a read or write, so the kernel transfers fewer
+//!   bytes than requested, or
+//! * delays the start of the operation.
+//!
+//! By default a fault applies to the next matching operation only. See
+//! [`Fault::times`] and [`Fault::always`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_uring::fault::{self, Fault, Target};
+//! use tokio_uring::fs::File;
+//!
+//! tokio_uring::start(async {
+//!     let file = File::open("hello.txt").await.unwrap();
+//!
+//!     // The next read is interrupted
+//!     fault::inject(Fault::error(Target::Read, libc::EINTR));
+//!
+//!     let (res, buf) = file.read_at(vec![0; 4096], 0).await;
+//!     assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EINTR));
+//!
+//!     // Retrying succeeds
+//!     let (res, _) = file.read_at(buf, 0).await;
+//!     res.unwrap();
+//! });
+//! ```
+
+use crate::driver::fault;
+
+use io_uring::opcode;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// The operations a [`Fault`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Target {
+    /// Any operation, except closing a file descriptor.
+    Any,
+    /// File and stream reads.
+    Read,
+    /// File and stream writes.
+    Write,
+    /// Socket receives, e.g. `recv_from`.
+    Recv,
+    /// Socket sends, e.g. `send_to`.
+    Send,
+    /// Accepting a connection.
+    Accept,
+    /// Connecting a socket.
+    Connect,
+    /// Opening a file.
+    Open,
+    /// Syncing a file.
+    Fsync,
+}
+
+/// A fault to inject into matching operations.
+#[derive(Debug, Clone)]
+pub struct Fault {
+    target: Target,
+    pub(crate) action: Action,
+    /// Number of times the fault applies, `None` for always
+    pub(crate) remaining: Option<usize>,
+    /// Number of matching operations to let through first
+    pub(crate) skip: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Action {
+    Error(i32),
+    Short(u32),
+    Delay(Duration),
+}
+
+impl Fault {
+    /// Completes matching operations with the `errno` error, e.g.
+    /// `libc::EINTR`, `libc::EAGAIN` or `libc::ECANCELED`.
+    ///
+    /// The operation is never submitted to the kernel.
+    pub fn error(target: Target, errno: i32) -> Fault {
+        Fault::new(target, Action::Error(errno))
+    }
+
+    /// Limits matching operations to transferring at most `len` bytes.
+    ///
+    /// Only applies to operations taking a single buffer: file and stream
+    /// reads and writes. Datagram operations such as `send_to` are not
+    /// matched.
+    pub fn short(target: Target, len: usize) -> Fault {
+        let len = u32::try_from(len).unwrap_or(u32::MAX);
+        Fault::new(target, Action::Short(len))
+    }
+
+    /// Delays the start of matching operations by `delay`.
+    pub fn delay(target: Target, delay: Duration) -> Fault {
+        Fault::new(target, Action::Delay(delay))
+    }
+
+    /// Applies the fault to the next `n` matching operations.
+    pub fn times(mut self, n: usize) -> Fault {
+        self.remaining = Some(n);
+        self
+    }
+
+    /// Applies the fault to all matching operations, until [`clear`] is
+    /// called.
+    pub fn always(mut self) -> Fault {
+        self.remaining = None;
+        self
+    }
+
+    /// Lets the next `n` matching operations through before applying the
+    /// fault.
+    pub fn skip(mut self, n: usize) -> Fault {
+        self.skip = n;
+        self
+    }
+
+    fn new(target: Target, action: Action) -> Fault {
+        Fault {
+            target,
+            action,
+            remaining: Some(1),
+            skip: 0,
+        }
+    }
+
+    pub(crate) fn matches(&self, code: u8) -> bool {
+        if let Action::Short(_) = self.action {
+            let single_buf = [
+                opcode::Read::CODE,
+                opcode::Write::CODE,
+                opcode::Recv::CODE,
+                opcode::Send::CODE,
+            ];
+
+            if !single_buf.contains(&code) {
+                return false;
+            }
+        }
+
+        self.remaining != Some(0) && self.target.matches(code)
+    }
+}
+
+impl Target {
+    fn matches(self, code: u8) -> bool {
+        let codes: &[u8] = match self {
+            Target::Any => return code != opcode::Close::CODE,
+            Target::Read => &[
+                opcode::Read::CODE,
+                opcode::Readv::CODE,
+                opcode::ReadFixed::CODE,
+            ],
+            Target::Write => &[
+                opcode::Write::CODE,
+                opcode::Writev::CODE,
+                opcode::WriteFixed::CODE,
+            ],
+            Target::Recv => &[opcode::Recv::CODE, opcode::RecvMsg::CODE],
+            Target::Send => &[opcode::Send::CODE, opcode::SendMsg::CODE],
+            Target::Accept => &[opcode::Accept::CODE],
+            Target::Connect => &[opcode::Connect::CODE],
+            Target::Open => &[opcode::OpenAt::CODE, opcode::OpenAt2::CODE],
+            Target::Fsync => &[opcode::Fsync::CODE],
+        };
+
+        codes.contains(&code)
+    }
+}
+
+/// Injects a fault into the operations submitted on the current runtime.
+///
+/// Faults are matched in the order they were injected.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn inject(fault: Fault) {
+    fault::with_injector(|injector| injector.push(fault));
+}
+
+/// Rejects the next `n` submissions of the current runtime to the kernel
+/// with `EBUSY`, as the kernel does while the completion queue overflowed,
+/// to exercise the backpressure set with
+/// [`Builder::busy_retries`](crate::Builder::busy_retries).
+///
+/// The operations are not failed: they stay in the submission queue until a
+/// submission goes through.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn busy_submits(n: usize) {
+    fault::with_injector(|injector| injector.reject_submits(n));
+}
+
+/// Fails the next submission of the current runtime to the kernel with
+/// `errno`, as `io_uring_enter(2)` may, e.g. with `EOWNERDEAD`.
+///
+/// The operations in the failed submission complete with the error, and are
+/// never started by the kernel. The runtime stays usable: the operations
+/// submitted later go through. Transient errors, `EAGAIN`, `ENOMEM` and
+/// `EINTR`, leave the operations queued for the next submission instead.
+/// Ignored with
+/// [`Builder::sqpoll`](crate::Builder::sqpoll), as the kernel thread
+/// consumes the operations regardless.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn fail_submit(errno: i32) {
+    fault::with_injector(|injector| injector.fail_submit(errno));
+}
+
+/// Removes all faults injected into the current runtime.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn clear() {
+    fault::clear();
+}
@@ -0,0 +1,93 @@
+//! File descriptor table hygiene.
+//!
+//! Every file descriptor `tokio-uring` creates is closed on exec
+//! (`FD_CLOEXEC`), so that child processes do not inherit the connections,
+//! files and rings of the runtime: files are opened with `O_CLOEXEC`, sockets
+//! are created with `SOCK_CLOEXEC`, accepted connections with the `O_CLOEXEC`
+//! flag of `accept4(2)`, and pipes with `O_CLOEXEC`, unless disabled with
+//! [`PipeOptions::cloexec`](crate::pipe::PipeOptions::cloexec). File
+//! descriptors inherited through socket activation are marked as well.
+//!
+//! To pass a file descriptor to a child process on purpose, clear the flag
+//! with [`set_cloexec`]. [`inheritable_fds`] lists the file descriptors a
+//! child process would inherit, to check for leaks.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_uring::net::TcpListener;
+//! use std::os::unix::io::AsRawFd;
+//! use std::process::Command;
+//!
+//! tokio_uring::start(async {
+//!     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+//!
+//!     // Hand the listener over to a worker process
+//!     tokio_uring::fd::set_cloexec(&listener, false)?;
+//!     Command::new("worker")
+//!         .env("LISTEN_FD", listener.as_raw_fd().to_string())
+//!         .spawn()?;
+//!     tokio_uring::fd::set_cloexec(&listener, true)?;
+//!
+//!     Ok::<_, std::io::Error>(())
+//! })
+//! .unwrap();
+//! ```
+
+use std::fs;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// Sets or clears the close-on-exec flag of `fd`.
+///
+/// Child processes spawned while the flag is cleared inherit the file
+/// descriptor. The flag is per file descriptor, and is not shared with
+/// duplicates.
+pub fn set_cloexec<F: AsRawFd + ?Sized>(fd: &F, cloexec: bool) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+    let flags = syscall!(fcntl(fd, libc::F_GETFD))?;
+
+    let flags = if cloexec {
+        flags | libc::FD_CLOEXEC
+    } else {
+        flags & !libc::FD_CLOEXEC
+    };
+
+    syscall!(fcntl(fd, libc::F_SETFD, flags))?;
+    Ok(())
+}
+
+/// Returns `true` if `fd` is closed on exec.
+pub fn is_cloexec<F: AsRawFd + ?Sized>(fd: &F) -> io::Result<bool> {
+    let flags = syscall!(fcntl(fd.as_raw_fd(), libc::F_GETFD))?;
+    Ok(flags & libc::FD_CLOEXEC != 0)
+}
+
+/// Returns the file descriptors of the process which are not closed on exec,
+/// in increasing order. Child processes inherit them.
+///
+/// The standard input, output and error, `0`, `1` and `2`, are usually
+/// inherited on purpose, and are included.
+///
+/// The file descriptors are read from `/proc/self/fd`. File descriptors
+/// opened or closed concurrently by other threads may or may not be listed.
+pub fn inheritable_fds() -> io::Result<Vec<RawFd>> {
+    let mut fds = Vec::new();
+
+    // The directory itself is opened with `O_CLOEXEC`, and not listed
+    for entry in fs::read_dir("/proc/self/fd")? {
+        let fd: RawFd = match entry?.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(fd) => fd,
+            None => continue,
+        };
+
+        match syscall!(fcntl(fd, libc::F_GETFD)) {
+            Ok(flags) if flags & libc::FD_CLOEXEC == 0 => fds.push(fd),
+            // Closed since listed
+            _ => {}
+        }
+    }
+
+    fds.sort_unstable();
+    Ok(fds)
+}
@@ -0,0 +1,238 @@
+use io_uring::{opcode, Parameters};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+/// The `io_uring` features supported by the ring of a runtime, returned by
+/// [`features`](crate::features).
+///
+/// The features come from two sources: the `IORING_FEAT_*` flags the kernel
+/// reports when setting up the ring, and the opcodes the ring supports, as
+/// reported by `IORING_REGISTER_PROBE`. Applications check them to choose
+/// between the paths relying on recent kernels, such as multishot accepts or
+/// provided buffer rings, and their legacy equivalents.
+///
+/// With [`Builder::seccomp_compatible`](crate::Builder::seccomp_compatible),
+/// the ring is not probed, and the features detected from its opcodes are
+/// not reported.
+///
+/// Flags are combined with `|`, e.g.
+/// `Features::MULTISHOT_ACCEPT | Features::BUF_RING`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::Features;
+///
+/// tokio_uring::start(async {
+///     if tokio_uring::features().contains(Features::MULTISHOT_ACCEPT) {
+///         // Accept connections with a single multishot operation
+///     } else {
+///         // Accept connections one at a time
+///     }
+/// });
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Features(u64);
+
+impl Features {
+    /// Completions are never dropped when the completion queue overflows
+    /// (`IORING_FEAT_NODROP`, Linux 5.5).
+    pub const NODROP: Features = Features(1 << 0);
+
+    /// The data of submitted operations need not outlive their submission
+    /// (`IORING_FEAT_SUBMIT_STABLE`, Linux 5.5).
+    pub const SUBMIT_STABLE: Features = Features(1 << 1);
+
+    /// Reads and writes at offset `-1` use the current position of the file
+    /// (`IORING_FEAT_RW_CUR_POS`, Linux 5.6).
+    pub const RW_CUR_POS: Features = Features(1 << 2);
+
+    /// Operations run with the credentials of the submitting task
+    /// (`IORING_FEAT_CUR_PERSONALITY`, Linux 5.6).
+    pub const CUR_PERSONALITY: Features = Features(1 << 3);
+
+    /// Operations on sockets and pipes poll for readiness internally instead
+    /// of blocking a worker thread (`IORING_FEAT_FAST_POLL`, Linux 5.7).
+    pub const FAST_POLL: Features = Features(1 << 4);
+
+    /// Polls support the 32 bits of the event mask
+    /// (`IORING_FEAT_POLL_32BITS`, Linux 5.9).
+    pub const POLL_32BITS: Features = Features(1 << 5);
+
+    /// Waits for completions accept a timeout argument
+    /// (`IORING_FEAT_EXT_ARG`, Linux 5.11).
+    pub const EXT_ARG: Features = Features(1 << 6);
+
+    /// Blocking operations run on native kernel workers
+    /// (`IORING_FEAT_NATIVE_WORKERS`, Linux 5.12).
+    pub const NATIVE_WORKERS: Features = Features(1 << 7);
+
+    /// Completions of successful operations can be skipped
+    /// (`IORING_FEAT_CQE_SKIP`, Linux 5.17).
+    pub const CQE_SKIP: Features = Features(1 << 8);
+
+    /// The files of linked operations are resolved when the operations run
+    /// (`IORING_FEAT_LINKED_FILE`, Linux 5.17).
+    pub const LINKED_FILE: Features = Features(1 << 9);
+
+    /// Buffers can be provided to the kernel, for reads to pick from
+    /// (`IORING_OP_PROVIDE_BUFFERS`, Linux 5.7).
+    pub const PROVIDE_BUFFERS: Features = Features(1 << 10);
+
+    /// Data is spliced between files without copies (`IORING_OP_SPLICE`,
+    /// Linux 5.7).
+    pub const SPLICE: Features = Features(1 << 11);
+
+    /// Files are statted by the ring (`IORING_OP_STATX`, Linux 5.6).
+    pub const STATX: Features = Features(1 << 12);
+
+    /// Sockets are created by the ring (`IORING_OP_SOCKET`, Linux 5.19).
+    pub const SOCKET: Features = Features(1 << 13);
+
+    /// A single accept posts a completion per connection (Linux 5.19).
+    ///
+    /// Inferred from the support of `IORING_OP_SOCKET`, added in the same
+    /// release, as multishot accepts share the opcode of accepts.
+    pub const MULTISHOT_ACCEPT: Features = Features(1 << 14);
+
+    /// Buffers are provided through a ring shared with the kernel
+    /// (`IORING_REGISTER_PBUF_RING`, Linux 5.19).
+    ///
+    /// Inferred from the support of `IORING_OP_SOCKET`, added in the same
+    /// release.
+    pub const BUF_RING: Features = Features(1 << 15);
+
+    /// A single receive posts a completion per message received, into
+    /// provided buffers (Linux 6.0).
+    ///
+    /// Inferred from the support of `IORING_OP_SEND_ZC`, added in the same
+    /// release, as multishot receives share the opcode of receives.
+    pub const MULTISHOT_RECV: Features = Features(1 << 16);
+
+    /// Data is sent without copies (`IORING_OP_SEND_ZC`, Linux 6.0).
+    pub const SEND_ZC: Features = Features(1 << 17);
+
+    /// No features.
+    pub const fn empty() -> Features {
+        Features(0)
+    }
+
+    /// Returns `true` if all the features in `other` are supported.
+    pub const fn contains(self, other: Features) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no feature is supported.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the raw bits of the features, as defined by this type.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Detects the features of a ring from its parameters, and `supports`,
+    /// reporting whether it supports an opcode.
+    pub(crate) fn detect(params: &Parameters, supports: impl Fn(u8) -> bool) -> Features {
+        let mut features = Features::empty();
+        let mut set = |supported: bool, feature: Features| {
+            if supported {
+                features |= feature;
+            }
+        };
+
+        set(params.is_feature_nodrop(), Features::NODROP);
+        set(params.is_feature_submit_stable(), Features::SUBMIT_STABLE);
+        set(params.is_feature_rw_cur_pos(), Features::RW_CUR_POS);
+        set(
+            params.is_feature_cur_personality(),
+            Features::CUR_PERSONALITY,
+        );
+        set(params.is_feature_fast_poll(), Features::FAST_POLL);
+        set(params.is_feature_poll_32bits(), Features::POLL_32BITS);
+        set(params.is_feature_ext_arg(), Features::EXT_ARG);
+        set(params.is_feature_native_workers(), Features::NATIVE_WORKERS);
+        set(params.is_feature_skip_cqe_on_success(), Features::CQE_SKIP);
+        set(params.is_feature_linked_file(), Features::LINKED_FILE);
+
+        set(
+            supports(opcode::ProvideBuffers::CODE),
+            Features::PROVIDE_BUFFERS,
+        );
+        set(supports(opcode::Splice::CODE), Features::SPLICE);
+        set(supports(opcode::Statx::CODE), Features::STATX);
+
+        let socket = supports(opcode::Socket::CODE);
+        set(socket, Features::SOCKET);
+        set(socket, Features::MULTISHOT_ACCEPT);
+        set(socket, Features::BUF_RING);
+
+        let send_zc = supports(opcode::SendZc::CODE);
+        set(send_zc, Features::MULTISHOT_RECV);
+        set(send_zc, Features::SEND_ZC);
+
+        features
+    }
+}
+
+/// Names of the features, for `Debug`.
+const NAMES: &[(Features, &str)] = &[
+    (Features::NODROP, "NODROP"),
+    (Features::SUBMIT_STABLE, "SUBMIT_STABLE"),
+    (Features::RW_CUR_POS, "RW_CUR_POS"),
+    (Features::CUR_PERSONALITY, "CUR_PERSONALITY"),
+    (Features::FAST_POLL, "FAST_POLL"),
+    (Features::POLL_32BITS, "POLL_32BITS"),
+    (Features::EXT_ARG, "EXT_ARG"),
+    (Features::NATIVE_WORKERS, "NATIVE_WORKERS"),
+    (Features::CQE_SKIP, "CQE_SKIP"),
+    (Features::LINKED_FILE, "LINKED_FILE"),
+    (Features::PROVIDE_BUFFERS, "PROVIDE_BUFFERS"),
+    (Features::SPLICE, "SPLICE"),
+    (Features::STATX, "STATX"),
+    (Features::SOCKET, "SOCKET"),
+    (Features::MULTISHOT_ACCEPT, "MULTISHOT_ACCEPT"),
+    (Features::BUF_RING, "BUF_RING"),
+    (Features::MULTISHOT_RECV, "MULTISHOT_RECV"),
+    (Features::SEND_ZC, "SEND_ZC"),
+];
+
+impl BitOr for Features {
+    type Output = Features;
+
+    fn bitor(self, rhs: Features) -> Features {
+        Features(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Features {
+    fn bitor_assign(&mut self, rhs: Features) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Features {
+    type Output = Features;
+
+    fn bitand(self, rhs: Features) -> Features {
+        Features(self.0 & rhs.0)
+    }
+}
+
+impl fmt::Debug for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Features(")?;
+        let mut first = true;
+        for (feature, name) in NAMES {
+            if self.contains(*feature) {
+                if !first {
+                    write!(f, " | ")?;
+                }
+                write!(f, "{}", name)?;
+                first = false;
+            }
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,104 @@
+use crate::buf::IoBuf;
+use crate::fs::{File, OpenOptions, WriteAt};
+
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// A file opened in append mode, `O_APPEND`, for logs and journals.
+///
+/// Each write goes to the end of the file, as it is at the time of the write,
+/// atomically: writes of several tasks, or of several processes appending to
+/// the same file, never overwrite each other, whatever their order. With a
+/// [`File`] opened without `O_APPEND`, writers computing the offset of their
+/// writes from the size of the file race with each other, and clobber each
+/// other's records.
+///
+/// The file derefs to [`File`], to read it and sync it. Note that on Linux,
+/// positional writes such as [`File::write_at`] ignore their offset and
+/// append as well, on a file in append mode.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::AppendFile;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let log = AppendFile::open("app.log").await?;
+///
+///         let (res, _) = log.append(&b"started\n"[..]).await;
+///         res?;
+///         log.sync_data().await?;
+///
+///         log.close().await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct AppendFile {
+    file: File,
+}
+
+impl AppendFile {
+    /// Opens a file for appending, creating it if it does not exist.
+    ///
+    /// The file is opened for reading as well, to read back the records
+    /// appended.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<AppendFile> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .await?;
+
+        Ok(AppendFile { file })
+    }
+
+    /// Appends a buffer to the end of the file, returning how many bytes were
+    /// written.
+    ///
+    /// The bytes written are appended atomically with respect to other
+    /// writers. Like [`File::write_at`], the write may be short, for example
+    /// when the filesystem runs out of space: appending the remainder with
+    /// another write leaves room for the writes of others in between, so a
+    /// short write of a record is best handled as a failure.
+    pub fn append<T: IoBuf>(&self, buf: T) -> WriteAt<T> {
+        self.file.write(buf)
+    }
+
+    /// Returns the underlying file.
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Converts into the underlying file, which stays in append mode.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+
+    /// Closes the file.
+    ///
+    /// See [`File::close`].
+    pub async fn close(self) -> io::Result<()> {
+        self.file.close().await
+    }
+}
+
+impl Deref for AppendFile {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        &self.file
+    }
+}
+
+impl fmt::Debug for AppendFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppendFile")
+            .field("file", &self.file)
+            .finish()
+    }
+}
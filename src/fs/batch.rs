@@ -0,0 +1,318 @@
+use crate::buf::IoBuf;
+use crate::fs::File;
+use crate::sync::Semaphore;
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io;
+use std::mem;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Default number of bytes buffered before they are written.
+const DEFAULT_THRESHOLD: usize = 64 * 1024;
+
+/// Coalesces small writes to a file into larger ones, for logs and other
+/// streams of small records.
+///
+/// The data of [`write`](WriteBatcher::write) is buffered, and written at the
+/// current position of the file, in a single write, once the buffer holds
+/// [`threshold`](WriteBatcher::threshold) bytes. With a
+/// [`linger`](WriteBatcher::linger) time, the data buffered is also written
+/// at most that long after it was buffered, when writes are too rare to fill
+/// the buffer. [`flush`](WriteBatcher::flush) writes the buffered data right
+/// away.
+///
+/// A batch written is synced to the storage device according to the
+/// [`SyncPolicy`] of the batcher, so that one `fsync(2)` covers many records.
+///
+/// The batches are written in order, one at a time. An error writing a batch
+/// in the background, once the linger time elapsed, is returned by the next
+/// call to `write` or `flush`, and the data not written stays buffered.
+///
+/// Dropping the batcher writes the data left in the buffer, blocking the
+/// thread, as [`Stdout`](crate::io::Stdout) does: call
+/// [`close`](WriteBatcher::close) instead to write it through the ring, and
+/// to check for errors.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tokio_uring::fs::{AppendFile, SyncPolicy, WriteBatcher};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let log = AppendFile::open("app.log").await?;
+///
+///         let mut batcher = WriteBatcher::new(log.into_file());
+///         batcher
+///             .threshold(16 * 1024)
+///             .linger(Duration::from_millis(10))
+///             .sync_policy(SyncPolicy::Data);
+///
+///         for i in 0..1000 {
+///             batcher.write(format!("request {}\n", i).as_bytes()).await?;
+///         }
+///
+///         batcher.close().await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct WriteBatcher {
+    inner: Rc<Inner>,
+}
+
+/// Whether the batches written by a [`WriteBatcher`] are synced to the
+/// storage device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// The batches are left in the page cache, for the kernel to write back.
+    Never,
+
+    /// Each batch is synced with [`File::sync_data`].
+    Data,
+
+    /// Each batch is synced with [`File::sync_all`], along with the metadata
+    /// of the file.
+    All,
+}
+
+struct Inner {
+    file: File,
+
+    /// Data written, not flushed yet
+    buf: RefCell<Vec<u8>>,
+
+    threshold: Cell<usize>,
+    linger: Cell<Option<Duration>>,
+    sync: Cell<SyncPolicy>,
+
+    /// Set while a task waits for the linger time to flush the buffer
+    lingering: Cell<bool>,
+
+    /// Held while writing a batch, so that the batches are written in order
+    flushing: Semaphore,
+
+    /// Error of a flush in the background, returned by the next call
+    error: Cell<Option<io::Error>>,
+}
+
+impl WriteBatcher {
+    /// Creates a batcher writing to `file`, at its current position.
+    ///
+    /// The data is written once 64 KiB are buffered, without linger time,
+    /// and is not synced.
+    pub fn new(file: File) -> WriteBatcher {
+        WriteBatcher {
+            inner: Rc::new(Inner {
+                file,
+                buf: RefCell::new(Vec::new()),
+                threshold: Cell::new(DEFAULT_THRESHOLD),
+                linger: Cell::new(None),
+                sync: Cell::new(SyncPolicy::Never),
+                lingering: Cell::new(false),
+                flushing: Semaphore::new(1),
+                error: Cell::new(None),
+            }),
+        }
+    }
+
+    /// Sets the number of bytes buffered from which they are written.
+    pub fn threshold(&mut self, bytes: usize) -> &mut Self {
+        self.inner.threshold.set(bytes);
+        self
+    }
+
+    /// Sets the longest time data stays buffered, before it is written even
+    /// if the buffer holds fewer bytes than the threshold.
+    pub fn linger(&mut self, linger: Duration) -> &mut Self {
+        self.inner.linger.set(Some(linger));
+        self
+    }
+
+    /// Sets whether the batches written are synced to the storage device.
+    pub fn sync_policy(&mut self, policy: SyncPolicy) -> &mut Self {
+        self.inner.sync.set(policy);
+        self
+    }
+
+    /// Buffers `data`, writing the buffer once it holds as many bytes as the
+    /// threshold.
+    ///
+    /// Completes once the data is buffered, or written if it filled the
+    /// buffer: await [`flush`](WriteBatcher::flush) to know the data was
+    /// written.
+    pub async fn write(&self, data: &[u8]) -> io::Result<()> {
+        self.inner.take_error()?;
+
+        let len = {
+            let mut buf = self.inner.buf.borrow_mut();
+            buf.extend_from_slice(data);
+            buf.len()
+        };
+
+        if len >= self.inner.threshold.get() {
+            return self.inner.flush().await;
+        }
+
+        self.linger_flush();
+        Ok(())
+    }
+
+    /// Writes the buffered data, and syncs it according to the sync policy.
+    ///
+    /// Completes once the data buffered so far, including the one of the
+    /// batches written in the background, was written.
+    pub async fn flush(&self) -> io::Result<()> {
+        self.inner.take_error()?;
+        self.inner.flush().await
+    }
+
+    /// Returns the number of bytes buffered, not written yet.
+    pub fn buffered(&self) -> usize {
+        self.inner.buf.borrow().len()
+    }
+
+    /// Returns the underlying file.
+    pub fn get_ref(&self) -> &File {
+        &self.inner.file
+    }
+
+    /// Flushes the buffered data, and closes the file.
+    ///
+    /// See [`File::close`].
+    pub async fn close(self) -> io::Result<()> {
+        self.flush().await?;
+
+        // A task flushing in the background may still hold the file, the
+        // close waits for it to let go
+        let fd = self.inner.file.fd.clone();
+        drop(self);
+        fd.close().await;
+        Ok(())
+    }
+
+    /// Flushes the buffer once the linger time elapsed, unless already
+    /// scheduled.
+    fn linger_flush(&self) {
+        let linger = match self.inner.linger.get() {
+            Some(linger) => linger,
+            None => return,
+        };
+        if self.inner.lingering.replace(true) {
+            return;
+        }
+
+        // The buffer is written when the batcher is dropped, the task does
+        // not keep it alive
+        let inner = Rc::downgrade(&self.inner);
+        crate::spawn(async move {
+            crate::time::sleep(linger).await;
+
+            if let Some(inner) = inner.upgrade() {
+                inner.lingering.set(false);
+                if let Err(e) = inner.flush().await {
+                    inner.error.set(Some(e));
+                }
+            }
+        });
+    }
+}
+
+impl Inner {
+    fn take_error(&self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    async fn flush(&self) -> io::Result<()> {
+        // Taking the batch once the previous one was written keeps the order
+        let _permit = self.flushing.acquire().await.unwrap();
+
+        let mut batch = mem::take(&mut *self.buf.borrow_mut());
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        let mut written = 0;
+        while written < batch.len() {
+            let len = batch.len();
+            let (res, slice) = self.file.write(batch.slice(written..len)).await;
+            batch = slice.into_inner();
+
+            match res {
+                Ok(0) => {
+                    let err = io::Error::new(io::ErrorKind::WriteZero, "failed to write batch");
+                    self.unflushed(batch, written);
+                    return Err(err);
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.unflushed(batch, written);
+                    return Err(e);
+                }
+            }
+        }
+
+        match self.sync.get() {
+            SyncPolicy::Never => {}
+            SyncPolicy::Data => self.file.sync_data().await?,
+            SyncPolicy::All => self.file.sync_all().await?,
+        }
+
+        // Reuse the allocation for the next batch
+        let mut buf = self.buf.borrow_mut();
+        if buf.is_empty() && buf.capacity() < batch.capacity() {
+            batch.clear();
+            *buf = batch;
+        }
+
+        Ok(())
+    }
+
+    /// Puts back the data of `batch` not written, ahead of the data buffered
+    /// since.
+    fn unflushed(&self, mut batch: Vec<u8>, written: usize) {
+        let mut buf = self.buf.borrow_mut();
+        batch.drain(..written);
+        batch.extend_from_slice(&buf);
+        *buf = batch;
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Best effort, as `Stdout` does
+        let buf = self.buf.get_mut();
+        let mut data = &buf[..];
+        while !data.is_empty() {
+            let res = syscall!(write(
+                self.file.fd.raw_fd(),
+                data.as_ptr() as *const libc::c_void,
+                data.len()
+            ));
+            match res {
+                Ok(n) if n > 0 => data = &data[n as usize..],
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                _ => break,
+            }
+        }
+    }
+}
+
+impl fmt::Debug for WriteBatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteBatcher")
+            .field("file", &self.inner.file)
+            .field("buffered", &self.buffered())
+            .field("threshold", &self.inner.threshold.get())
+            .field("linger", &self.inner.linger.get())
+            .field("sync", &self.inner.sync.get())
+            .finish()
+    }
+}
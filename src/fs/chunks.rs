@@ -0,0 +1,61 @@
+use crate::buf::AllocBuf;
+use crate::fs::pipeline::ReadPipeline;
+
+use std::fmt;
+use std::io;
+
+/// A sequential reader of a file, returning it in chunks, created by
+/// [`File::read_chunks`].
+///
+/// Several reads are in flight at once, ahead of the chunk being processed,
+/// to keep the device busy. A chunk handed back with
+/// [`recycle`](ReadChunks::recycle) is reused by a next read, instead of
+/// allocating a new one. The chunks are allocated from the global allocator,
+/// or from the [`Allocator`] passed to [`File::read_chunks_in`].
+///
+/// [`Allocator`]: crate::buf::Allocator
+/// [`File::read_chunks_in`]: crate::fs::File::read_chunks_in
+/// [`File::read_chunks`]: crate::fs::File::read_chunks
+pub struct ReadChunks<'a> {
+    pipeline: ReadPipeline<'a>,
+}
+
+impl<'a> ReadChunks<'a> {
+    pub(crate) fn new(pipeline: ReadPipeline<'a>) -> ReadChunks<'a> {
+        ReadChunks { pipeline }
+    }
+
+    /// Drops the chunks from the page cache once they are consumed, with
+    /// `POSIX_FADV_DONTNEED`, if `enabled`.
+    ///
+    /// A large file read once, such as by a backup or a scan, otherwise
+    /// fills the page cache, evicting the pages other readers need. A chunk
+    /// is consumed once the next one is requested: the range it covers is
+    /// then dropped, in the background. Pages also mapped or cached for
+    /// other readers of the file are dropped as well, and read again from
+    /// the device by them.
+    pub fn drop_behind(&mut self, enabled: bool) -> &mut ReadChunks<'a> {
+        self.pipeline.set_drop_behind(enabled);
+        self
+    }
+
+    /// Returns the next chunk of the file, or `None` at the end of the file.
+    ///
+    /// Chunks are read until a read returns no data, so data appended to the
+    /// file while reading it is returned too.
+    pub async fn next(&mut self) -> io::Result<Option<AllocBuf>> {
+        self.pipeline.next().await
+    }
+
+    /// Reuses `buf`, a chunk previously returned by
+    /// [`next`](ReadChunks::next), for a next read.
+    pub fn recycle(&mut self, buf: AllocBuf) {
+        self.pipeline.recycle(buf);
+    }
+}
+
+impl fmt::Debug for ReadChunks<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadChunks").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,237 @@
+use crate::fs::File;
+use crate::future::poll_fn;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// Coalesces the syncs of a file requested by concurrent tasks, the group
+/// commit of write-ahead logs.
+///
+/// Each task appending a record to a log syncs the file before acknowledging
+/// the record. Rather than one `fsync(2)` per record, the coalescer issues
+/// one for all the tasks waiting at once: [`sync`](SyncCoalescer::sync)
+/// completes once an fsync started after the call completed, and the calls
+/// made while an fsync is in flight all wait for the next one.
+///
+/// With a [`window`](SyncCoalescer::window), each fsync waits that long
+/// before starting, gathering the requests made in the meantime, for fewer,
+/// larger batches at the cost of latency.
+///
+/// The coalescer holds the file descriptor of the file: closing the file
+/// waits for the coalescer to be dropped. Cloning the coalescer returns a
+/// handle to the same batches, to share them between tasks.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{AppendFile, SyncCoalescer};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let log = AppendFile::open("wal.log").await?;
+///         let syncs = SyncCoalescer::new(&log);
+///
+///         let mut tasks = Vec::new();
+///         for i in 0..100 {
+///             let log = log.as_file().try_clone()?;
+///             let syncs = syncs.clone();
+///             tasks.push(tokio_uring::spawn(async move {
+///                 let (res, _) = log.write(format!("record {}\n", i).into_bytes()).await;
+///                 res?;
+///                 syncs.sync().await
+///             }));
+///         }
+///         for task in tasks {
+///             task.await.unwrap()?;
+///         }
+///
+///         let stats = syncs.stats();
+///         println!("{} syncs for {} requests", stats.syncs, stats.requests);
+///         Ok(())
+///     })
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SyncCoalescer {
+    inner: Rc<Inner>,
+}
+
+/// Counters of a [`SyncCoalescer`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncStats {
+    /// Number of calls to [`sync`](SyncCoalescer::sync).
+    pub requests: u64,
+
+    /// Number of fsyncs issued.
+    pub syncs: u64,
+
+    /// Largest number of requests covered by a single fsync.
+    pub max_batch: u64,
+
+    /// Total time spent in the fsyncs, not counting the windows.
+    pub sync_time: Duration,
+}
+
+struct Inner {
+    file: File,
+    state: RefCell<State>,
+}
+
+struct State {
+    window: Duration,
+    data_only: bool,
+
+    /// Generation of the last fsync started, the calls made since wait for
+    /// the next one
+    started: u64,
+
+    /// Generation of the last fsync completed, and its result
+    done: u64,
+    result: Result<(), i32>,
+
+    /// Highest generation waited for
+    requested: u64,
+
+    /// Requests waiting for the next fsync
+    pending: u64,
+
+    /// Set while a task issues the fsyncs
+    running: bool,
+
+    waiters: Vec<Waker>,
+    stats: SyncStats,
+}
+
+impl SyncCoalescer {
+    /// Creates a coalescer syncing `file`, with no window: an fsync starts as
+    /// soon as it is requested, unless one is in flight.
+    pub fn new(file: &File) -> SyncCoalescer {
+        SyncCoalescer {
+            inner: Rc::new(Inner {
+                file: File::from_shared_fd(file.fd.clone()),
+                state: RefCell::new(State {
+                    window: Duration::ZERO,
+                    data_only: false,
+                    started: 0,
+                    done: 0,
+                    result: Ok(()),
+                    requested: 0,
+                    pending: 0,
+                    running: false,
+                    waiters: Vec::new(),
+                    stats: SyncStats::default(),
+                }),
+            }),
+        }
+    }
+
+    /// Sets how long each fsync waits for more requests before starting.
+    pub fn window(&mut self, window: Duration) -> &mut Self {
+        self.inner.state.borrow_mut().window = window;
+        self
+    }
+
+    /// Syncs the data only, with [`File::sync_data`], rather than with
+    /// [`File::sync_all`].
+    pub fn data_only(&mut self, data_only: bool) -> &mut Self {
+        self.inner.state.borrow_mut().data_only = data_only;
+        self
+    }
+
+    /// Waits until the data written to the file before the call reached the
+    /// storage device, through an fsync shared with the other requests.
+    ///
+    /// An error of the fsync is returned to all the requests it covers.
+    /// Dropping the future gives up on waiting, the fsync still happens.
+    pub async fn sync(&self) -> io::Result<()> {
+        let ticket = {
+            let mut state = self.inner.state.borrow_mut();
+            let ticket = state.started + 1;
+            state.requested = ticket;
+            state.pending += 1;
+            state.stats.requests += 1;
+
+            if !state.running {
+                state.running = true;
+                crate::spawn(self.inner.clone().run());
+            }
+            ticket
+        };
+
+        poll_fn(|cx| {
+            let mut state = self.inner.state.borrow_mut();
+            if state.done < ticket {
+                state.waiters.push(cx.waker().clone());
+                return Poll::Pending;
+            }
+
+            Poll::Ready(state.result.map_err(io::Error::from_raw_os_error))
+        })
+        .await
+    }
+
+    /// Returns the counters of the coalescer.
+    pub fn stats(&self) -> SyncStats {
+        self.inner.state.borrow().stats
+    }
+}
+
+impl Inner {
+    /// Issues fsyncs until no request waits for one.
+    async fn run(self: Rc<Self>) {
+        loop {
+            let (window, data_only) = {
+                let state = self.state.borrow();
+                (state.window, state.data_only)
+            };
+            if !window.is_zero() {
+                crate::time::sleep(window).await;
+            }
+
+            let generation = {
+                let mut state = self.state.borrow_mut();
+                state.started += 1;
+                state.stats.syncs += 1;
+                state.stats.max_batch = state.stats.max_batch.max(state.pending);
+                state.pending = 0;
+                state.started
+            };
+
+            let start = Instant::now();
+            let res = if data_only {
+                self.file.sync_data().await
+            } else {
+                self.file.sync_all().await
+            };
+
+            let mut state = self.state.borrow_mut();
+            state.stats.sync_time += start.elapsed();
+            state.done = generation;
+            state.result = res.map_err(|e| e.raw_os_error().unwrap_or(libc::EIO));
+            for waker in state.waiters.drain(..) {
+                waker.wake();
+            }
+
+            if state.requested <= generation {
+                state.running = false;
+                return;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SyncCoalescer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.borrow();
+        f.debug_struct("SyncCoalescer")
+            .field("file", &self.inner.file)
+            .field("window", &state.window)
+            .field("data_only", &state.data_only)
+            .field("stats", &state.stats)
+            .finish()
+    }
+}
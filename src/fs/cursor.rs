@@ -0,0 +1,161 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::fs::File;
+
+use std::fmt;
+use std::io::{self, SeekFrom};
+
+/// A file with a position of its own, read and written like
+/// [`std::fs::File`].
+///
+/// The cursor tracks the position in the file itself, and reads and writes
+/// at it with the positional operations, [`File::read_at`] and
+/// [`File::write_at`], advancing it by the bytes transferred. This eases
+/// porting synchronous code reading and writing a file in sequence. Unlike
+/// the position used by [`File::read`], the position of the cursor is not
+/// shared with the duplicates of the file descriptor, and moving it never
+/// performs a system call, except when seeking from the end of the file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::SeekFrom;
+/// use tokio_uring::fs::{File, FileCursor};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let mut cursor = FileCursor::new(File::create("foo.txt").await?);
+///
+///         cursor.write_all(b"hello world".to_vec()).await.0?;
+///         cursor.seek(SeekFrom::Start(6)).await?;
+///
+///         let (res, buf) = cursor.read(vec![0; 5]).await;
+///         assert_eq!(&buf[..res?], b"world");
+///
+///         cursor.close().await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct FileCursor {
+    file: File,
+    pos: u64,
+}
+
+impl FileCursor {
+    /// Wraps a file, with the cursor at its start.
+    pub fn new(file: File) -> FileCursor {
+        FileCursor { file, pos: 0 }
+    }
+
+    /// Reads into `buf` at the position of the cursor, returning how many
+    /// bytes were read, and advances the cursor past them.
+    ///
+    /// See [`File::read_at`]. A return value of 0 means the cursor is at
+    /// the end of the file, or `buf` has no capacity.
+    pub async fn read<T: IoBufMut>(&mut self, buf: T) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.file.read_at(buf, self.pos).await;
+        if let Ok(n) = res {
+            self.pos += n as u64;
+        }
+        (res, buf)
+    }
+
+    /// Writes `buf` at the position of the cursor, returning how many bytes
+    /// were written, and advances the cursor past them.
+    ///
+    /// See [`File::write_at`]. The write may be short.
+    pub async fn write<T: IoBuf>(&mut self, buf: T) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.file.write_at(buf, self.pos).await;
+        if let Ok(n) = res {
+            self.pos += n as u64;
+        }
+        (res, buf)
+    }
+
+    /// Writes the whole of `buf` at the position of the cursor, with as many
+    /// writes as needed, and advances the cursor past it.
+    ///
+    /// Fails with [`WriteZero`](io::ErrorKind::WriteZero) if a write
+    /// returns 0. On error, the cursor is past the bytes written until then.
+    pub async fn write_all<T: IoBuf>(&mut self, buf: T) -> crate::BufResult<(), T> {
+        let (file, start) = (&self.file, self.pos);
+        let len = buf.bytes_init();
+
+        let mut done = 0;
+        let (res, buf) = crate::io::write_all(buf, |slice, written| {
+            done = written;
+            file.write_at(slice, start + written as u64)
+        })
+        .await;
+
+        self.pos = start + if res.is_ok() { len } else { done } as u64;
+        (res, buf)
+    }
+
+    /// Moves the cursor, returning its new position from the start of the
+    /// file.
+    ///
+    /// Seeking from the end reads the size of the file, with `statx(2)`.
+    /// Seeking past the end is allowed: a write there extends the file,
+    /// leaving a hole. Seeking before the start fails with `EINVAL`, and
+    /// leaves the cursor unchanged.
+    pub async fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(pos) => {
+                self.pos = pos;
+                return Ok(pos);
+            }
+            SeekFrom::Current(offset) => (self.pos, offset),
+            SeekFrom::End(offset) => (self.file.len().await?, offset),
+        };
+
+        let pos = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+        match pos {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(io::Error::from_raw_os_error(libc::EINVAL)),
+        }
+    }
+
+    /// Returns the position of the cursor, from the start of the file.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Moves the cursor to `pos`, from the start of the file.
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// Returns the underlying file.
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Converts into the underlying file, dropping the position.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+
+    /// Closes the file.
+    ///
+    /// See [`File::close`].
+    pub async fn close(self) -> io::Result<()> {
+        self.file.close().await
+    }
+}
+
+impl fmt::Debug for FileCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileCursor")
+            .field("file", &self.file)
+            .field("pos", &self.pos)
+            .finish()
+    }
+}
@@ -0,0 +1,175 @@
+use crate::buf::{FixedBuf, FixedBufRegistry};
+use crate::driver::{Op, Read, Write};
+use crate::fs::{File, OpenOptions};
+
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+
+/// Alignment required by `O_DIRECT` of the sizes and offsets of the
+/// transfers, the smallest logical block size of devices.
+const SECTOR_SIZE: usize = 512;
+
+/// Copies the contents of the file `src` to the file `dst` with direct I/O,
+/// bypassing the page cache, and returns the number of bytes copied.
+///
+/// Both files are opened with `O_DIRECT`: data moves between the devices and
+/// `depth` buffers of `block_size` bytes, aligned to the page size, without
+/// filling the page cache with data which is not read again, as when cloning
+/// VM images or database files. Up to `depth` reads and writes are in flight
+/// at once. The buffers are registered with the ring, unless the ring
+/// already has registered buffers or the registration exceeds
+/// `RLIMIT_MEMLOCK`, in which case they are used unregistered.
+///
+/// `dst` is created if it does not exist, with the permissions of `src`, and
+/// truncated otherwise. The last block of `src` is written in full, then
+/// `dst` is truncated to the length of `src`. The data is not synced to the
+/// device: call [`File::sync_all`] on `dst` to make the copy durable.
+///
+/// # Errors
+///
+/// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if `depth` is
+/// zero or if `block_size` is not a multiple of 512, and with `EINVAL` if
+/// `block_size` is not a multiple of the logical block size of a device, or
+/// if a file system does not support `O_DIRECT`, such as `tmpfs`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let copied = fs::copy_direct("vm.img", "vm-clone.img", 1024 * 1024, 8).await?;
+///         println!("copied {} bytes", copied);
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn copy_direct<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    block_size: usize,
+    depth: usize,
+) -> io::Result<u64> {
+    if depth == 0 || block_size == 0 || !block_size.is_multiple_of(SECTOR_SIZE) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid block size or depth",
+        ));
+    }
+
+    let mut options = OpenOptions::new();
+    options.read(true);
+    options.custom_flags = libc::O_DIRECT;
+    let src = options.open(src).await?;
+
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    syscall!(fstat(src.fd.raw_fd(), &mut stat))?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    options.custom_flags = libc::O_DIRECT;
+    options.mode = stat.st_mode & 0o7777;
+    let dst = options.open(dst).await?;
+
+    let res = copy(&src, &dst, block_size, depth).await;
+
+    // Close even if the copy failed
+    let closed = dst.close().await;
+    src.close().await?;
+    let copied = res?;
+    closed?;
+
+    Ok(copied)
+}
+
+async fn copy(src: &File, dst: &File, block_size: usize, depth: usize) -> io::Result<u64> {
+    let len = src.len().await?;
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize };
+    let registry = FixedBufRegistry::aligned(depth, block_size, page_size);
+    let registered = match registry.register() {
+        Ok(()) => true,
+        Err(e) if e.raw_os_error() == Some(libc::EBUSY) => false,
+        Err(e) if e.kind() == io::ErrorKind::OutOfMemory => false,
+        Err(e) => return Err(e),
+    };
+    let mut free: Vec<FixedBuf> = (0..depth).map(|i| registry.check_out(i).unwrap()).collect();
+
+    // In file order, with their offset
+    let mut reads: VecDeque<(u64, Op<Read<FixedBuf>>)> = VecDeque::with_capacity(depth);
+    let mut writes: VecDeque<Op<Write<FixedBuf>>> = VecDeque::with_capacity(depth);
+
+    // End of the data, lowered if the file shrinks meanwhile
+    let mut end = len;
+    let mut pos = 0;
+
+    loop {
+        while pos < end {
+            let buf = match free.pop() {
+                Some(buf) => buf,
+                None => break,
+            };
+
+            // The reads are submitted now, not when awaited
+            let op = if registered {
+                Op::read_fixed_at(&src.fd, buf, pos).map_err(|(e, _)| e)?
+            } else {
+                Op::read_at(&src.fd, buf, pos)?
+            };
+            reads.push_back((pos, op));
+            pos += block_size as u64;
+        }
+
+        // Free a buffer for the next read, or wait for the data to write
+        if reads.is_empty() || (free.is_empty() && pos < end && !writes.is_empty()) {
+            let op = match writes.pop_front() {
+                Some(op) => op,
+                None => break,
+            };
+
+            let (res, buf) = op.write().await;
+            if res? != block_size {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "short direct write",
+                ));
+            }
+            free.push(buf);
+            continue;
+        }
+
+        let (offset, op) = reads.pop_front().unwrap();
+        let (res, mut buf) = op.read().await;
+        let n = res?;
+
+        if n < block_size {
+            // The end of the file, the following reads are past it
+            end = offset + n as u64;
+            reads.clear();
+            pos = end;
+        }
+
+        if n == 0 {
+            free.push(buf);
+            continue;
+        }
+
+        // Blocks are written in full, including the stale tail of the last
+        // one, which is truncated once copied.
+        // Safety: the buffers are zeroed when allocated
+        unsafe { buf.set_len(block_size) };
+
+        let op = if registered {
+            Op::write_fixed_at(&dst.fd, buf, offset).map_err(|(e, _)| e)?
+        } else {
+            Op::write_at(&dst.fd, buf, offset)?
+        };
+        writes.push_back(op);
+    }
+
+    syscall!(ftruncate(dst.fd.raw_fd(), end as libc::off_t))?;
+    Ok(end)
+}
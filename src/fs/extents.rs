@@ -0,0 +1,87 @@
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+/// The extents read by [`File::read_extents`].
+///
+/// The data of the extents is held in a single buffer, where they follow
+/// each other, in the order they were requested. Each extent is shorter
+/// than requested if its read was short, such as at the end of the file.
+///
+/// [`File::read_extents`]: crate::fs::File::read_extents
+pub struct Extents {
+    buf: Vec<u8>,
+
+    /// Region of `buf` reserved for each extent
+    regions: Vec<Range<usize>>,
+
+    /// Result of the read of each extent
+    results: Vec<io::Result<usize>>,
+}
+
+impl Extents {
+    pub(crate) fn new(
+        buf: Vec<u8>,
+        lens: impl Iterator<Item = usize>,
+        results: Vec<io::Result<usize>>,
+    ) -> Extents {
+        let mut start = 0;
+        let regions = lens
+            .map(|len| {
+                let region = start..start + len;
+                start += len;
+                region
+            })
+            .collect();
+
+        Extents {
+            buf,
+            regions,
+            results,
+        }
+    }
+
+    /// Returns the number of extents.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Returns `true` if no extent was read.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Returns the data of the extent at `index`, in the order of the
+    /// request, or the error of its read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Result<&[u8], &io::Error> {
+        let region = &self.regions[index];
+        match &self.results[index] {
+            Ok(n) => Ok(&self.buf[region.start..region.start + n]),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns an iterator over the extents, in the order of the request.
+    pub fn iter(&self) -> impl Iterator<Item = Result<&[u8], &io::Error>> + '_ {
+        (0..self.len()).map(move |i| self.get(i))
+    }
+
+    /// Returns the buffer holding the extents, one after the other, each in
+    /// a region of its requested length.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl fmt::Debug for Extents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extents")
+            .field("len", &self.len())
+            .field("results", &self.results)
+            .finish()
+    }
+}
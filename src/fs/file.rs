@@ -1,18 +1,33 @@
-use crate::buf::{IoBuf, IoBufMut};
-use crate::driver::{Op, SharedFd};
-use crate::fs::OpenOptions;
+use crate::buf::{Allocator, FixedBuf, Global, IoBuf, IoBufMut};
+use crate::driver::{self, Op, SharedFd};
+use crate::fs::pipeline::{ReadPipeline, MAX_ADVICE_LEN};
+use crate::fs::read_write::{read_to_end_at, READ_DEPTH};
+use crate::fs::{
+    Advice, Extents, FileExtent, MmapRegion, OpenOptions, RangeLock, ReadAt, ReadChunks, RwFlags,
+    WriteAt,
+};
+use crate::runtime::spawn_blocking;
 
+use std::convert::TryFrom;
 use std::fmt;
-use std::io;
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
 use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Offset of the reads and writes at the current position of the file
+const CURRENT_POSITION: u64 = u64::MAX;
 
 /// A reference to an open file on the filesystem.
 ///
 /// An instance of a `File` can be read and/or written depending on what options
 /// it was opened with. The `File` type provides **positional** read and write
-/// operations. The file does not maintain an internal cursor. The caller is
-/// required to specify an offset when issuing an operation.
+/// operations, where the caller specifies an offset when issuing an operation,
+/// and which leave the position of the file unchanged. [`read`](File::read)
+/// and [`write`](File::write) use and advance the position of the file instead,
+/// the file cursor of `std::fs::File`, which [`seek`](File::seek) moves.
 ///
 /// While files are automatically closed when they go out of scope, the
 /// operation happens asynchronously in the background. It is recommended to
@@ -53,7 +68,7 @@ use std::path::Path;
 /// ```
 pub struct File {
     /// Open file descriptor
-    fd: SharedFd,
+    pub(crate) fd: SharedFd,
 }
 
 impl File {
@@ -123,6 +138,12 @@ impl File {
     /// Read some bytes at the specified offset from the file into the specified
     /// buffer, returning how many bytes were read.
     ///
+    /// The read is submitted when the returned future is first polled, not
+    /// when this method is called. The future does not borrow the file, it
+    /// holds a handle of its own to the file descriptor: it may outlive the
+    /// `File`, and [`close`](File::close) waits for it to complete or to be
+    /// dropped.
+    ///
     /// # Return
     ///
     /// The method returns the operation result and the same buffer value passed
@@ -143,6 +164,11 @@ impl File {
     /// If this function encounters any form of I/O or other error, an error
     /// variant will be returned. The buffer is returned on error.
     ///
+    /// # Cancellation
+    ///
+    /// The returned future can be dropped while the read is in flight, or
+    /// canceled with [`ReadAt::cancel`] to get the buffer back.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -165,10 +191,8 @@ impl File {
     ///     })
     /// }
     /// ```
-    pub async fn read_at<T: IoBufMut>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
-        // Submit the read operation
-        let op = Op::read_at(&self.fd, buf, pos).unwrap();
-        op.read().await
+    pub fn read_at<T: IoBufMut>(&self, buf: T, pos: u64) -> ReadAt<T> {
+        ReadAt::new(&self.fd, buf, pos, RwFlags::empty())
     }
 
     /// Write a buffer into this file at the specified offset, returning how
@@ -178,6 +202,11 @@ impl File {
     /// the entire write may not succeed, or the write may also generate an
     /// error. The bytes will be written starting at the specified offset.
     ///
+    /// The write is submitted when the returned future is first polled, not
+    /// when this method is called. As with [`read_at`](File::read_at), the
+    /// future holds a handle of its own to the file descriptor, rather than
+    /// borrowing the file.
+    ///
     /// # Return
     ///
     /// The method returns the operation result and the same buffer value passed
@@ -194,6 +223,11 @@ impl File {
     /// It is **not** considered an error if the entire buffer could not be
     /// written to this writer.
     ///
+    /// # Cancellation
+    ///
+    /// The returned future can be dropped while the write is in flight, or
+    /// canceled with [`WriteAt::cancel`] to get the buffer back.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -217,9 +251,602 @@ impl File {
     /// ```
     ///
     /// [`Ok(n)`]: Ok
-    pub async fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
-        let op = Op::write_at(&self.fd, buf, pos).unwrap();
-        op.write().await
+    pub fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> WriteAt<T> {
+        WriteAt::new(&self.fd, buf, pos, RwFlags::empty())
+    }
+
+    /// Like [`read_at`](File::read_at), with flags modifying the read, such
+    /// as [`RwFlags::NOWAIT`] to read only the data in the page cache.
+    pub fn read_at_with_flags<T: IoBufMut>(&self, buf: T, pos: u64, flags: RwFlags) -> ReadAt<T> {
+        ReadAt::new(&self.fd, buf, pos, flags)
+    }
+
+    /// Reads into each buffer at its position, returning the buffers with
+    /// the result of their read, in order.
+    ///
+    /// The reads are submitted as a batch, with a single system call, and
+    /// the task is woken once all of them completed, instead of once per
+    /// read. This suits fanning out many small reads, such as fixed-size
+    /// records.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the reads cannot be submitted, dropping the buffers. Reads
+    /// which fail are reported along with their buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = File::open("records").await?;
+    ///
+    ///         // Records 3, 17 and 42, of 512 bytes each
+    ///         let reads = [3, 17, 42]
+    ///             .iter()
+    ///             .map(|i| (vec![0; 512], i * 512))
+    ///             .collect();
+    ///
+    ///         for (res, record) in file.read_many_at(reads).await? {
+    ///             let n = res?;
+    ///             println!("{:?}", &record[..n]);
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_many_at<T: IoBufMut>(
+        &self,
+        bufs: Vec<(T, u64)>,
+    ) -> io::Result<Vec<crate::BufResult<usize, T>>> {
+        Op::read_all_at(&self.fd, bufs).await
+    }
+
+    /// Reads several extents of the file, as positions and lengths, into a
+    /// single buffer.
+    ///
+    /// The reads are submitted as a batch, with a single system call, and
+    /// the task is woken once all of them completed. This suits the many
+    /// small positional reads of column stores and indexes, without
+    /// allocating a buffer per read.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the reads cannot be submitted. The reads which fail are
+    /// reported by [`Extents::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = File::open("index").await?;
+    ///
+    ///         let extents = file.read_extents(&[(0, 64), (8192, 128)]).await?;
+    ///         for extent in extents.iter() {
+    ///             match extent {
+    ///                 Ok(data) => println!("{:?}", data),
+    ///                 Err(e) => eprintln!("read failed: {}", e),
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_extents(&self, extents: &[(u64, usize)]) -> io::Result<Extents> {
+        let (buf, results) = Op::read_extents(&self.fd, extents).await?;
+        Ok(Extents::new(
+            buf,
+            extents.iter().map(|&(_, len)| len),
+            results,
+        ))
+    }
+
+    /// Reads `len` bytes at `pos`, hedging against a slow read: if the read
+    /// did not complete after `hedge_delay`, a second, identical read is
+    /// submitted, and the first of the two to complete wins. The other one
+    /// is canceled, and its buffer dropped once the kernel released it.
+    ///
+    /// The buffers are created by `buf_factory`, called with `len`, once or
+    /// twice. Hedging caps the latency of the reads which would otherwise
+    /// wait behind a stalled request, for example on network file systems,
+    /// at the cost of duplicate reads. A delay around the 95th percentile of
+    /// the read latency hedges about 5% of the reads.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    /// use std::time::Duration;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = File::open("/mnt/nfs/data").await?;
+    ///
+    ///         let (res, buf) = file
+    ///             .read_hedged(|len| vec![0; len], 0, 4096, Duration::from_millis(10))
+    ///             .await;
+    ///         let n = res?;
+    ///         println!("{:?}", &buf[..n]);
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_hedged<T, F>(
+        &self,
+        mut buf_factory: F,
+        pos: u64,
+        len: usize,
+        hedge_delay: Duration,
+    ) -> crate::BufResult<usize, T>
+    where
+        T: IoBufMut,
+        F: FnMut(usize) -> T,
+    {
+        let mut first = match Op::read_at_with_flags(&self.fd, buf_factory(len), pos, 0) {
+            Ok(op) => op,
+            Err((e, buf)) => return (Err(e), buf),
+        };
+
+        if let Ok(completion) = crate::time::timeout(hedge_delay, &mut first).await {
+            return driver::complete_read(completion);
+        }
+
+        // Without a second read, wait for the first one
+        let ops = match Op::read_at(&self.fd, buf_factory(len), pos) {
+            Ok(second) => vec![first, second],
+            Err(_) => vec![first],
+        };
+
+        let (_, completion, _losers) = Op::race(ops).await;
+        driver::complete_read(completion)
+    }
+
+    /// Like [`write_at`](File::write_at), with flags modifying the write.
+    ///
+    /// With [`RwFlags::DSYNC`], the data written is durable once the write
+    /// completes, which saves the round trip of a separate
+    /// [`sync_data`](File::sync_data), for example for the commit record of
+    /// a journal.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::{OpenOptions, RwFlags};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let journal = OpenOptions::new().write(true).open("journal").await?;
+    ///
+    ///         // Durable once written, without a separate sync
+    ///         let (res, _) = journal
+    ///             .write_at_with_flags(&b"commit"[..], 4096, RwFlags::DSYNC)
+    ///             .await;
+    ///         res?;
+    ///
+    ///         journal.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn write_at_with_flags<T: IoBuf>(&self, buf: T, pos: u64, flags: RwFlags) -> WriteAt<T> {
+        WriteAt::new(&self.fd, buf, pos, flags)
+    }
+
+    /// Read some bytes from the current position of the file into the
+    /// specified buffer, returning how many bytes were read, and advancing the
+    /// position by as many bytes.
+    ///
+    /// Like [`read_at`](File::read_at), with the offset `-1` of `preadv2(2)`,
+    /// standing for the current position. The position is shared by the
+    /// clones of the file descriptor: reads in flight at the same time on the
+    /// same file read ranges in an unspecified order, so await each read
+    /// before issuing the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///
+    ///         // Read the file 10 bytes at a time
+    ///         let mut buffer = vec![0; 10];
+    ///         loop {
+    ///             let (res, b) = f.read(buffer).await;
+    ///             let n = res?;
+    ///             if n == 0 {
+    ///                 break;
+    ///             }
+    ///             println!("The bytes: {:?}", &b[..n]);
+    ///             buffer = b;
+    ///         }
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn read<T: IoBufMut>(&self, buf: T) -> ReadAt<T> {
+        ReadAt::new(&self.fd, buf, CURRENT_POSITION, RwFlags::empty())
+    }
+
+    /// Write a buffer into this file at its current position, returning how
+    /// many bytes were written, and advancing the position by as many bytes.
+    ///
+    /// Like [`write_at`](File::write_at), with the offset `-1` of
+    /// `pwritev2(2)`, standing for the current position. Files opened in
+    /// [`append`](crate::fs::OpenOptions::append) mode are written at their
+    /// end instead. Like [`read`](File::read), await each write before
+    /// issuing the next one.
+    pub fn write<T: IoBuf>(&self, buf: T) -> WriteAt<T> {
+        WriteAt::new(&self.fd, buf, CURRENT_POSITION, RwFlags::empty())
+    }
+
+    /// Reads the file from its current position to its end, appending to
+    /// `buf`, and returns the buffer with the number of bytes read. The
+    /// position is advanced by as many bytes, as by [`read`](File::read).
+    ///
+    /// As [`fs::read`](crate::fs::read), the size of the file sizes the
+    /// buffer, and several chunks are read at once. Files which cannot seek,
+    /// such as pipes, are read until their end one read at a time, doubling
+    /// the capacity of `buf` each time it fills up.
+    ///
+    /// On error, `buf` holds the data read until then.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///
+    ///         let (res, contents) = f.read_to_end(Vec::new()).await;
+    ///         println!("read {} bytes", res?);
+    ///         println!("{}", String::from_utf8_lossy(&contents));
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_to_end(&self, mut buf: Vec<u8>) -> crate::BufResult<usize, Vec<u8>> {
+        let pos = match self.seek(SeekFrom::Current(0)) {
+            Ok(pos) => pos,
+            Err(e) if e.raw_os_error() == Some(libc::ESPIPE) => {
+                return crate::io::read_to_end(buf, move |buf| self.read(buf)).await;
+            }
+            Err(e) => return (Err(e), buf),
+        };
+
+        let start = buf.len();
+        let res = read_to_end_at(self, pos, &mut buf)
+            .await
+            .map(|()| buf.len() - start);
+
+        // The reads were positional, move the position past the data read
+        let end = pos + (buf.len() - start) as u64;
+        if let Err(e) = self.seek(SeekFrom::Start(end)) {
+            return (Err(e), buf);
+        }
+
+        (res, buf)
+    }
+
+    /// Reads the whole file, from the start, into a vector sized from the
+    /// size of the file.
+    ///
+    /// See [`read_at_auto`](File::read_at_auto). The position of the file is
+    /// left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///
+    ///         let contents = f.read_full().await?;
+    ///         println!("read {} bytes", contents.len());
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_full(&self) -> io::Result<Vec<u8>> {
+        self.read_at_auto(0).await
+    }
+
+    /// Reads the file from `pos` until its end, into a vector sized from the
+    /// remaining length of the file.
+    ///
+    /// The size of the file is read with `statx(2)`, and the vector is
+    /// allocated once for the data past `pos`, rather than truncating the
+    /// read to the capacity of a buffer allocated ahead. Several chunks are
+    /// read at once, as with [`fs::read`](crate::fs::read), and reading
+    /// continues past the size until the end of the file, in case the file
+    /// grew. Reading past the end of the file returns an empty vector.
+    ///
+    /// The position of the file is left unchanged. Files which cannot seek,
+    /// such as pipes, fail with `ESPIPE`; read them with
+    /// [`read_to_end`](File::read_to_end).
+    pub async fn read_at_auto(&self, pos: u64) -> io::Result<Vec<u8>> {
+        // Fails with `ESPIPE` if the file cannot seek, rather than reading
+        // from a pipe as if it had positions
+        self.seek(SeekFrom::Current(0))?;
+
+        let mut contents = Vec::new();
+        read_to_end_at(self, pos, &mut contents).await?;
+
+        Ok(contents)
+    }
+
+    /// Reads the file from the start, in chunks of `chunk_size` bytes, with
+    /// several reads in flight at once, see [`ReadChunks`].
+    ///
+    /// The position of the file is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// Scanning a large file without evicting the rest of the page cache:
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = File::open("backup.tar").await?;
+    ///
+    ///         let mut chunks = file.read_chunks(1024 * 1024).await?;
+    ///         chunks.drop_behind(true);
+    ///
+    ///         let mut lines = 0;
+    ///         while let Some(chunk) = chunks.next().await? {
+    ///             lines += chunk.iter().filter(|&&b| b == b'\n').count();
+    ///             chunks.recycle(chunk);
+    ///         }
+    ///         println!("{} lines", lines);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn read_chunks(&self, chunk_size: usize) -> io::Result<ReadChunks<'_>> {
+        self.read_chunks_in(chunk_size, Rc::new(Global)).await
+    }
+
+    /// Like [`read_chunks`](File::read_chunks), reading into chunks
+    /// allocated by `alloc`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub async fn read_chunks_in(
+        &self,
+        chunk_size: usize,
+        alloc: Rc<dyn Allocator>,
+    ) -> io::Result<ReadChunks<'_>> {
+        let size = self.len().await?;
+        let pipeline = ReadPipeline::with_allocator(self, size, chunk_size, READ_DEPTH, alloc);
+        Ok(ReadChunks::new(pipeline))
+    }
+
+    /// Advises the kernel on how a range of the file will be accessed, see
+    /// `posix_fadvise(2)`. A `len` of 0 extends the range to the end of the
+    /// file.
+    ///
+    /// [`Advice::DontNeed`] drops the pages of the range from the page
+    /// cache, for example once a large file was read, so that it does not
+    /// evict the pages of other files. [`ReadChunks::drop_behind`] does so
+    /// as the chunks are consumed. The kernel caches pages in folios of
+    /// several pages, and only drops the folios entirely in the range, and
+    /// which are not being written back.
+    pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let advice = match advice {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        };
+
+        // An operation covers at most 4 GiB, longer ranges take several
+        let mut offset = offset;
+        let mut len = len;
+        loop {
+            let n = len.min(MAX_ADVICE_LEN);
+            Op::fadvise(&self.fd, offset, n as u32, advice)?
+                .await
+                .result?;
+
+            offset += n;
+            len -= n;
+            if len == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Moves the position of the file, used by [`read`](File::read) and
+    /// [`write`](File::write), returning the new position from the start of
+    /// the file, see `lseek(2)`.
+    ///
+    /// The position is the one of the file description, shared with the
+    /// duplicates of the file descriptor. Seeking does not perform any I/O,
+    /// and completes immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::io::SeekFrom;
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///
+    ///         // Read the last 10 bytes
+    ///         f.seek(SeekFrom::End(-10))?;
+    ///         let (res, buf) = f.read(vec![0; 10]).await;
+    ///         println!("The bytes: {:?}", &buf[..res?]);
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (
+                libc::off_t::try_from(offset)
+                    .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?,
+                libc::SEEK_SET,
+            ),
+            SeekFrom::End(offset) => (offset, libc::SEEK_END),
+            SeekFrom::Current(offset) => (offset, libc::SEEK_CUR),
+        };
+
+        let pos = syscall!(lseek(self.fd.raw_fd(), offset, whence))?;
+        Ok(pos as u64)
+    }
+
+    /// Returns the position of the first byte of data at or after `pos`, or
+    /// `None` if there is only a hole from `pos` to the end of the file, see
+    /// `SEEK_DATA` in `lseek(2)`.
+    ///
+    /// Together with [`seek_hole`](File::seek_hole), this finds the data of
+    /// a sparse file, skipping its holes, as [`copy_sparse`] does. File
+    /// systems which do not track holes report the whole file as data.
+    ///
+    /// The file position is moved to the returned position, which matters to
+    /// [`read`](File::read) and [`write`](File::write).
+    ///
+    /// [`copy_sparse`]: crate::fs::copy_sparse
+    pub fn seek_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        let offset =
+            libc::off_t::try_from(pos).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+
+        match syscall!(lseek(self.fd.raw_fd(), offset, libc::SEEK_DATA)) {
+            Ok(pos) => Ok(Some(pos as u64)),
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the position of the first hole at or after `pos`, see
+    /// `SEEK_HOLE` in `lseek(2)`. The end of the file counts as a hole.
+    ///
+    /// The file position is moved to the returned position.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `ENXIO` if `pos` is past the end of the file.
+    pub fn seek_hole(&self, pos: u64) -> io::Result<u64> {
+        let offset =
+            libc::off_t::try_from(pos).map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+
+        let pos = syscall!(lseek(self.fd.raw_fd(), offset, libc::SEEK_HOLE))?;
+        Ok(pos as u64)
+    }
+
+    /// Returns the extents of the file, in order, as mapped by the file
+    /// system to the device, see the `FS_IOC_FIEMAP` ioctl.
+    ///
+    /// The extents cover the data of the file, and the space allocated to
+    /// it: the gaps between them are holes. Unlike
+    /// [`seek_data`](File::seek_data), the extents tell the physical layout,
+    /// allocated but unwritten ranges, and the ranges shared with other
+    /// files.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `EOPNOTSUPP` if the file system does not support
+    /// `FS_IOC_FIEMAP`, such as `tmpfs`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = File::open("disk.img").await?;
+    ///
+    ///         for extent in file.extent_map()? {
+    ///             println!(
+    ///                 "{} bytes at {} on the device",
+    ///                 extent.len(),
+    ///                 extent.physical()
+    ///             );
+    ///         }
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn extent_map(&self) -> io::Result<Vec<FileExtent>> {
+        crate::fs::sparse::extent_map(self.fd.raw_fd())
+    }
+
+    /// Read some bytes at the specified offset from the file into a buffer
+    /// registered with the ring, see [`FixedBufRegistry`].
+    ///
+    /// Like [`read_at`](File::read_at), but the kernel skips mapping the
+    /// buffer, which is mapped once when registering it. Fails with `EFAULT`
+    /// if the buffers of the registry are not registered.
+    ///
+    /// [`FixedBufRegistry`]: crate::buf::FixedBufRegistry
+    pub async fn read_fixed_at(
+        &self,
+        buf: FixedBuf,
+        pos: u64,
+    ) -> crate::BufResult<usize, FixedBuf> {
+        if let Err(e) = driver::room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        match Op::read_fixed_at(&self.fd, buf, pos) {
+            Ok(op) => op.read().await,
+            Err((e, buf)) => (Err(e), buf),
+        }
+    }
+
+    /// Write a buffer registered with the ring into this file at the
+    /// specified offset, returning how many bytes were written, see
+    /// [`FixedBufRegistry`].
+    ///
+    /// Like [`write_at`](File::write_at), but the kernel skips mapping the
+    /// buffer, which is mapped once when registering it. Fails with `EFAULT`
+    /// if the buffers of the registry are not registered.
+    ///
+    /// [`FixedBufRegistry`]: crate::buf::FixedBufRegistry
+    pub async fn write_fixed_at(
+        &self,
+        buf: FixedBuf,
+        pos: u64,
+    ) -> crate::BufResult<usize, FixedBuf> {
+        if let Err(e) = driver::room(&self.fd).await {
+            return (Err(e), buf);
+        }
+
+        match Op::write_fixed_at(&self.fd, buf, pos) {
+            Ok(op) => op.write().await,
+            Err((e, buf)) => (Err(e), buf),
+        }
     }
 
     /// Attempts to sync all OS-internal metadata to disk.
@@ -251,7 +878,8 @@ impl File {
     /// }
     /// ```
     pub async fn sync_all(&self) -> io::Result<()> {
-        let op = Op::fsync(&self.fd).unwrap();
+        driver::room(&self.fd).await?;
+        let op = Op::fsync(&self.fd)?;
         let completion = op.await;
 
         completion.result?;
@@ -292,13 +920,232 @@ impl File {
     /// }
     /// ```
     pub async fn sync_data(&self) -> io::Result<()> {
-        let op = Op::datasync(&self.fd).unwrap();
+        driver::room(&self.fd).await?;
+        let op = Op::datasync(&self.fd)?;
         let completion = op.await;
 
         completion.result?;
         Ok(())
     }
 
+    /// Locks `len` bytes of the file starting at `offset`, waiting until no
+    /// conflicting lock is held. A `len` of 0 locks up to the end of the file,
+    /// however large it grows.
+    ///
+    /// An exclusive lock conflicts with any other lock on the range, and
+    /// needs the file to be opened for writing. A shared lock only conflicts
+    /// with exclusive locks, and needs the file to be opened for reading.
+    ///
+    /// The lock is an open file description lock, which conflicts with the
+    /// locks taken through other opens of the file, in this process or
+    /// others, see [`RangeLock`]. It is released when the returned guard is
+    /// dropped.
+    ///
+    /// `io-uring` has no locking operation, so the lock is waited for on the
+    /// Tokio blocking pool. If the returned future is dropped while waiting,
+    /// the lock is released as soon as it is acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::OpenOptions;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = OpenOptions::new().read(true).write(true).open("db").await?;
+    ///
+    ///         // Lock the header page while updating it
+    ///         let lock = f.lock_range(0, 4096, true).await?;
+    ///         let (res, _) = f.write_at(&b"header"[..], 0).await;
+    ///         res?;
+    ///         lock.unlock()?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn lock_range(
+        &self,
+        offset: u64,
+        len: u64,
+        exclusive: bool,
+    ) -> io::Result<RangeLock> {
+        RangeLock::lock(self.fd.raw_fd(), offset, len, exclusive).await
+    }
+
+    /// Creates a new name for the file at `path`, which must not exist.
+    ///
+    /// The file is linked through its `/proc/self/fd` entry, so this also
+    /// gives a name to an anonymous file created by [`tempfile_in`], making
+    /// it visible atomically once fully written.
+    ///
+    /// [`tempfile_in`]: crate::fs::tempfile_in
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = fs::tempfile_in("/some/dir").await?;
+    ///         let (res, _) = f.write_at(&b"Hello, world!"[..], 0).await;
+    ///         res?;
+    ///         f.sync_all().await?;
+    ///
+    ///         // The file appears with its full contents
+    ///         f.link("/some/dir/hello.txt").await?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn link(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let op = Op::link_fd(&self.fd, path.as_ref())?;
+        op.await.result?;
+        Ok(())
+    }
+
+    /// Returns the size of the file, as reported by `statx(2)`, or by
+    /// `fstat(2)` in seccomp-compatible mode.
+    pub(crate) async fn len(&self) -> io::Result<u64> {
+        if !driver::optional_ops() {
+            let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+            syscall!(fstat(self.fd.raw_fd(), &mut stat))?;
+            return Ok(stat.st_size as u64);
+        }
+
+        let op = Op::statx(&self.fd, libc::STATX_SIZE)?;
+        let completion = op.await;
+        completion.result?;
+
+        Ok(completion.data.statx.stx_size)
+    }
+
+    /// Changes the last access and modification times of the file, see
+    /// `futimens(2)`.
+    ///
+    /// A `None` time is left unchanged. `io-uring` has no operation to change
+    /// timestamps, so this runs on the Tokio blocking pool, on a duplicate of
+    /// the file descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use tokio_uring::fs::File;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let f = File::open("foo.txt").await?;
+    ///
+    ///         // Backdate the modification time by an hour
+    ///         let mtime = SystemTime::now() - Duration::from_secs(3600);
+    ///         f.set_times(None, Some(mtime)).await?;
+    ///
+    ///         f.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn set_times(
+        &self,
+        accessed: Option<SystemTime>,
+        modified: Option<SystemTime>,
+    ) -> io::Result<()> {
+        let times = [timespec(accessed), timespec(modified)];
+
+        // The blocking task may outlive `self` if this future is dropped, so it
+        // gets its own descriptor, closed when done.
+        let fd = syscall!(fcntl(self.fd.raw_fd(), libc::F_DUPFD_CLOEXEC, 0))?;
+        let file = unsafe { std::fs::File::from_raw_fd(fd) };
+
+        spawn_blocking(move || {
+            syscall!(futimens(file.as_raw_fd(), times.as_ptr()))?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Maps `range` of the file into memory, shared and writable.
+    ///
+    /// The file must be opened for reading and writing. The range need not
+    /// be aligned to the page size, but must be within the file: accessing
+    /// the region past the end of the file raises `SIGBUS`.
+    ///
+    /// # Safety
+    ///
+    /// The region aliases the file contents, which may be modified at any
+    /// time by other processes or by writes to the file. The caller must
+    /// ensure that the file is not truncated while the region is mapped, and
+    /// that modifications by others do not break the assumptions of the code
+    /// reading the region.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::{Advice, OpenOptions};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = OpenOptions::new().read(true).write(true).open("foo.db").await?;
+    ///
+    ///         // Safety: the file is not truncated while mapped
+    ///         let region = unsafe { file.mmap(4096..8192)? };
+    ///         region.madvise(Advice::Random)?;
+    ///         println!("first byte: {}", region[0]);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub unsafe fn mmap(&self, range: Range<u64>) -> io::Result<MmapRegion> {
+        MmapRegion::new(&self.fd, range)
+    }
+
+    /// Creates a new `File` referring to the same open file, with a duplicate
+    /// of its file descriptor, closed on exec, see `dup(2)`.
+    ///
+    /// The two files have their own file descriptors, and are closed
+    /// independently: one task can read the file while another one writes
+    /// it, each closing its own `File` when done. They share the open file
+    /// description, so the position used by [`read`](File::read) and
+    /// [`write`](File::write), the status flags and the locks taken with
+    /// [`lock_range`](File::lock_range) are shared.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::OpenOptions;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = OpenOptions::new().read(true).write(true).open("foo.txt").await?;
+    ///         let writer = file.try_clone()?;
+    ///
+    ///         let task = tokio_uring::spawn(async move {
+    ///             let (res, _) = writer.write_at(&b"hello"[..], 0).await;
+    ///             res?;
+    ///             writer.close().await
+    ///         });
+    ///
+    ///         task.await??;
+    ///         let (res, buf) = file.read_at(vec![0; 5], 0).await;
+    ///         println!("The bytes: {:?}", &buf[..res?]);
+    ///
+    ///         file.close().await?;
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn try_clone(&self) -> io::Result<File> {
+        let fd = syscall!(fcntl(self.fd.raw_fd(), libc::F_DUPFD_CLOEXEC, 0))?;
+        Ok(File::from_shared_fd(SharedFd::new(fd)))
+    }
+
     /// Closes the file.
     ///
     /// The method completes once the close operation has completed,
@@ -330,6 +1177,40 @@ impl File {
     }
 }
 
+/// Converts a time to a `timespec`, `UTIME_OMIT` leaving the time unchanged.
+fn timespec(time: Option<SystemTime>) -> libc::timespec {
+    let time = match time {
+        Some(time) => time,
+        None => {
+            return libc::timespec {
+                tv_sec: 0,
+                tv_nsec: libc::UTIME_OMIT,
+            }
+        }
+    };
+
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => libc::timespec {
+            tv_sec: d.as_secs() as libc::time_t,
+            tv_nsec: d.subsec_nanos() as libc::c_long,
+        },
+        Err(e) => {
+            // Before the epoch, the nanoseconds still count forward
+            let d = e.duration();
+            let mut sec = -(d.as_secs() as libc::time_t);
+            let mut nsec = d.subsec_nanos() as libc::c_long;
+            if nsec > 0 {
+                sec -= 1;
+                nsec = 1_000_000_000 - nsec;
+            }
+            libc::timespec {
+                tv_sec: sec,
+                tv_nsec: nsec,
+            }
+        }
+    }
+}
+
 impl AsRawFd for File {
     fn as_raw_fd(&self) -> RawFd {
         self.fd.raw_fd()
@@ -0,0 +1,139 @@
+use crate::fs::pipeline::ReadPipeline;
+use crate::fs::read_write::{CHUNK_SIZE, READ_DEPTH};
+use crate::fs::File;
+
+use std::io;
+use std::path::Path;
+
+/// An incremental hash or checksum algorithm, fed by [`hash_file`].
+///
+/// Implemented by [`Crc32`], and by any [`std::hash::Hasher`], such as the
+/// FNV or xxHash hashers of third-party crates. Other algorithms, such as
+/// cryptographic digests, are supported by implementing this trait on a
+/// wrapper type.
+pub trait HashAlgorithm {
+    /// Hash or checksum computed by the algorithm.
+    type Output;
+
+    /// Feeds `data` to the algorithm.
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the hash of all the data fed.
+    fn finalize(self) -> Self::Output;
+}
+
+impl<H: std::hash::Hasher> HashAlgorithm for H {
+    type Output = u64;
+
+    fn update(&mut self, data: &[u8]) {
+        self.write(data);
+    }
+
+    fn finalize(self) -> u64 {
+        self.finish()
+    }
+}
+
+/// The CRC-32 checksum used by zlib, gzip and PNG (CRC-32/ISO-HDLC).
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::fs::{Crc32, HashAlgorithm};
+///
+/// let mut crc = Crc32::new();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.finalize(), 0xcbf4_3926);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Creates a checksum of no data.
+    pub fn new() -> Crc32 {
+        Crc32 { crc: !0 }
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Crc32 {
+        Crc32::new()
+    }
+}
+
+impl HashAlgorithm for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.crc = CRC32_TABLE[((self.crc ^ b as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    fn finalize(self) -> u32 {
+        !self.crc
+    }
+}
+
+/// Lookup table of the reflected CRC-32 polynomial, one entry per byte.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Hashes the entire contents of a file with `algorithm`.
+///
+/// The file is read in chunks, with several reads in flight while a chunk is
+/// being hashed, so that reading and hashing overlap.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{self, Crc32};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let crc = fs::hash_file("disk.img", Crc32::new()).await?;
+///         println!("crc32: {:08x}", crc);
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn hash_file<P: AsRef<Path>, H: HashAlgorithm>(
+    path: P,
+    mut algorithm: H,
+) -> io::Result<H::Output> {
+    let file = File::open(path).await?;
+    let size = file.len().await?;
+
+    let mut pipeline = ReadPipeline::new(&file, size, CHUNK_SIZE, READ_DEPTH);
+
+    while let Some(chunk) = pipeline.next().await? {
+        algorithm.update(&chunk);
+        pipeline.recycle(chunk);
+    }
+
+    // Drop the remaining reads before closing, which waits for them
+    drop(pipeline);
+    file.close().await?;
+
+    Ok(algorithm.finalize())
+}
@@ -0,0 +1,111 @@
+use crate::runtime::spawn_blocking;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+/// A byte-range lock on a file, released when dropped.
+///
+/// Created by [`File::lock_range`]. The lock is an open file description
+/// lock (see `F_OFD_SETLKW` in `fcntl(2)`): it is shared by all the handles
+/// to the opened file, conflicts with locks taken through other opens of the
+/// same file, including in the same process, and is released when the last
+/// handle to the opened file is closed.
+///
+/// The lock holds its own handle to the opened file, so it remains held if
+/// the [`File`] is closed first.
+///
+/// [`File`]: crate::fs::File
+/// [`File::lock_range`]: crate::fs::File::lock_range
+pub struct RangeLock {
+    /// Duplicate of the locked file descriptor
+    file: fs::File,
+
+    /// Start of the locked range
+    offset: libc::off_t,
+
+    /// Length of the locked range, 0 extending to the end of the file
+    len: libc::off_t,
+}
+
+impl RangeLock {
+    /// Locks `len` bytes at `offset` of the opened file `fd`, waiting on the
+    /// Tokio blocking pool until conflicting locks are released.
+    pub(crate) async fn lock(
+        fd: RawFd,
+        offset: u64,
+        len: u64,
+        exclusive: bool,
+    ) -> io::Result<RangeLock> {
+        let offset = to_off_t(offset)?;
+        let len = to_off_t(len)?;
+
+        // The waiting task may outlive the `File` if this future is dropped,
+        // so it gets its own descriptor.
+        let fd = syscall!(fcntl(fd, libc::F_DUPFD_CLOEXEC, 0))?;
+        let file = unsafe { fs::File::from_raw_fd(fd) };
+
+        let kind = if exclusive {
+            libc::F_WRLCK
+        } else {
+            libc::F_RDLCK
+        };
+
+        // The lock is created on the blocking pool: if this future is dropped
+        // before the lock is acquired, the pool drops the lock, releasing it.
+        spawn_blocking(move || {
+            let lock = RangeLock { file, offset, len };
+            lock.fcntl(libc::F_OFD_SETLKW, kind)?;
+            Ok(lock)
+        })
+        .await
+    }
+
+    /// Releases the lock, returning any error.
+    ///
+    /// Dropping the lock also releases it, ignoring errors. Releasing never
+    /// blocks.
+    pub fn unlock(self) -> io::Result<()> {
+        // Unlocking again on drop is a no-op
+        self.fcntl(libc::F_OFD_SETLK, libc::F_UNLCK)
+    }
+
+    fn fcntl(&self, cmd: libc::c_int, kind: libc::c_int) -> io::Result<()> {
+        let mut flock: libc::flock = unsafe { std::mem::zeroed() };
+        flock.l_type = kind as libc::c_short;
+        flock.l_whence = libc::SEEK_SET as libc::c_short;
+        flock.l_start = self.offset;
+        flock.l_len = self.len;
+
+        loop {
+            match syscall!(fcntl(self.file.as_raw_fd(), cmd, &flock)) {
+                Ok(_) => return Ok(()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for RangeLock {
+    fn drop(&mut self) {
+        let _ = self.fcntl(libc::F_OFD_SETLK, libc::F_UNLCK);
+    }
+}
+
+impl fmt::Debug for RangeLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeLock")
+            .field("fd", &self.file.as_raw_fd())
+            .field("offset", &self.offset)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+fn to_off_t(n: u64) -> io::Result<libc::off_t> {
+    libc::off_t::try_from(n)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "range out of bounds"))
+}
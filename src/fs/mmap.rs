@@ -0,0 +1,191 @@
+use crate::driver::{Op, SharedFd};
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::ops::{Deref, DerefMut, Range};
+use std::ptr;
+
+/// A shared, writable memory mapping of a range of a [`File`].
+///
+/// Writes to the region are written back to the file by the kernel in the
+/// background, or when flushed with [`msync_async`]. The region is unmapped
+/// when dropped.
+///
+/// The region holds a handle to the file for flushing, so [`File::close`]
+/// only completes once all the regions of the file are dropped.
+///
+/// [`File`]: crate::fs::File
+/// [`File::close`]: crate::fs::File::close
+/// [`msync_async`]: MmapRegion::msync_async
+pub struct MmapRegion {
+    /// File the region maps, used for flushing
+    fd: SharedFd,
+
+    /// Start of the mapping, aligned to the page size
+    base: *mut libc::c_void,
+
+    /// Length of the mapping
+    map_len: usize,
+
+    /// Offset of the requested range in the mapping
+    offset: usize,
+
+    /// Length of the requested range
+    len: usize,
+}
+
+/// Advice on the use of an [`MmapRegion`], or of a range of a [`File`], used
+/// by the kernel to pick read-ahead and caching strategies, see `madvise(2)`
+/// and `posix_fadvise(2)`.
+///
+/// [`File`]: crate::fs::File
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Advice {
+    /// No special treatment, the default.
+    Normal,
+
+    /// Pages are accessed in random order, read-ahead is less useful.
+    Random,
+
+    /// Pages are accessed in sequential order, read-ahead is aggressive.
+    Sequential,
+
+    /// Pages will be accessed soon, they are read ahead.
+    WillNeed,
+
+    /// Pages will not be accessed soon, their memory is released. The next
+    /// access reloads them from the file. The pages of a file which are not
+    /// written back yet are kept.
+    DontNeed,
+}
+
+impl MmapRegion {
+    /// Maps `range` of the file.
+    ///
+    /// # Safety
+    ///
+    /// See [`File::mmap`](crate::fs::File::mmap).
+    pub(crate) unsafe fn new(fd: &SharedFd, range: Range<u64>) -> io::Result<MmapRegion> {
+        if range.end < range.start {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "range end is before its start",
+            ));
+        }
+
+        let len = usize::try_from(range.end - range.start)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "range is too long"))?;
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot map an empty range",
+            ));
+        }
+
+        // The mapping must start on a page boundary
+        let page_size = libc::sysconf(libc::_SC_PAGESIZE) as u64;
+        let offset = (range.start % page_size) as usize;
+        let map_start = range.start - offset as u64;
+        let map_len = len + offset;
+
+        let base = libc::mmap(
+            ptr::null_mut(),
+            map_len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd.raw_fd(),
+            map_start as libc::off_t,
+        );
+        if base == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(MmapRegion {
+            fd: fd.clone(),
+            base,
+            map_len,
+            offset,
+            len,
+        })
+    }
+
+    /// Flushes the writes to the region to disk.
+    ///
+    /// The flush is submitted to `io-uring` as a data sync of the file, so the
+    /// completion also covers other modified data of the file, and the
+    /// metadata needed to read it back.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::fs::OpenOptions;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     tokio_uring::start(async {
+    ///         let file = OpenOptions::new().read(true).write(true).open("foo.db").await?;
+    ///
+    ///         // Safety: the file is not truncated while mapped
+    ///         let mut region = unsafe { file.mmap(0..4096)? };
+    ///         region[..5].copy_from_slice(b"hello");
+    ///         region.msync_async().await?;
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub async fn msync_async(&self) -> io::Result<()> {
+        let op = Op::datasync(&self.fd)?;
+        op.await.result?;
+        Ok(())
+    }
+
+    /// Advises the kernel on how the region will be used.
+    pub fn madvise(&self, advice: Advice) -> io::Result<()> {
+        let advice = match advice {
+            Advice::Normal => libc::MADV_NORMAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+        };
+
+        syscall!(madvise(self.base, self.map_len, advice))?;
+        Ok(())
+    }
+}
+
+impl Deref for MmapRegion {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // Safety: the mapping is valid until dropped
+        unsafe { std::slice::from_raw_parts(self.base.cast::<u8>().add(self.offset), self.len) }
+    }
+}
+
+impl DerefMut for MmapRegion {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // Safety: the mapping is valid until dropped, and writable
+        unsafe { std::slice::from_raw_parts_mut(self.base.cast::<u8>().add(self.offset), self.len) }
+    }
+}
+
+impl Drop for MmapRegion {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base, self.map_len);
+        }
+    }
+}
+
+impl fmt::Debug for MmapRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmapRegion")
+            .field("fd", &self.fd.raw_fd())
+            .field("ptr", &self.as_ptr())
+            .field("len", &self.len)
+            .finish()
+    }
+}
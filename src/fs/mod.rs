@@ -1,11 +1,69 @@
 //! Filesystem manipulation operations.
 
+mod append;
+pub use append::AppendFile;
+
+mod batch;
+pub use batch::{SyncPolicy, WriteBatcher};
+
+mod chunks;
+pub use chunks::ReadChunks;
+
+mod coalesce;
+pub use coalesce::{SyncCoalescer, SyncStats};
+
+mod cursor;
+pub use cursor::FileCursor;
+
+mod direct;
+pub use direct::copy_direct;
+
 mod directory;
 pub use directory::remove_dir;
 
+mod extents;
+pub use extents::Extents;
+
 mod file;
 pub use file::remove_file;
 pub use file::File;
 
+#[cfg(feature = "hash")]
+mod hash;
+#[cfg(feature = "hash")]
+pub use hash::{hash_file, Crc32, HashAlgorithm};
+
+mod lock;
+pub use lock::RangeLock;
+
+mod mmap;
+pub use mmap::{Advice, MmapRegion};
+
 mod open_options;
 pub use open_options::OpenOptions;
+
+mod permissions;
+pub use permissions::{chown, set_permissions};
+
+mod pipeline;
+
+mod positional;
+pub use positional::{ReadAt, WriteAt};
+
+mod race;
+pub use race::{read_first_at, ReadLosers};
+
+mod rw_flags;
+pub use rw_flags::RwFlags;
+
+mod read_write;
+pub use read_write::{read, read_to_string, write, write_atomic};
+
+mod sparse;
+pub use sparse::{copy_sparse, FileExtent};
+
+mod temp;
+pub use temp::{tempfile_in, NamedTempFile};
+
+mod xattr;
+pub use xattr::{get_xattr, list_xattr, set_xattr};
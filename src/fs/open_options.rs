@@ -62,6 +62,7 @@ pub struct OpenOptions {
     create: bool,
     create_new: bool,
     pub(crate) mode: libc::mode_t,
+    pub(crate) custom_flags: libc::c_int,
 }
 
 impl OpenOptions {
@@ -94,6 +95,7 @@ impl OpenOptions {
             create: false,
             create_new: false,
             mode: 0o666,
+            custom_flags: 0,
         }
     }
 
@@ -159,7 +161,12 @@ impl OpenOptions {
     ///
     /// For most filesystems, the operating system guarantees that all writes
     /// are atomic: no writes get mangled because another process writes at the
-    /// same time.
+    /// same time. On Linux, the offset of positional writes, such as
+    /// [`File::write_at`], is ignored: they append as well. See
+    /// [`AppendFile`], a file opened in append mode.
+    ///
+    /// [`File::write_at`]: crate::fs::File::write_at
+    /// [`AppendFile`]: crate::fs::AppendFile
     ///
     /// ## Note
     ///
@@ -374,3 +381,9 @@ impl OpenOptions {
         })
     }
 }
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,72 @@
+use crate::driver::cstr;
+use crate::runtime::spawn_blocking;
+
+use std::fs::Permissions;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Changes the permissions of the file at `path`, see `chmod(2)`.
+///
+/// Symbolic links are followed. `io-uring` has no operation to change
+/// permissions, so this runs on the Tokio blocking pool.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::Permissions;
+/// use std::os::unix::fs::PermissionsExt;
+///
+/// use tokio_uring::fs::set_permissions;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         set_permissions("/some/file.txt", Permissions::from_mode(0o644)).await?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn set_permissions<P: AsRef<Path>>(path: P, perm: Permissions) -> io::Result<()> {
+    let path = cstr(path.as_ref())?;
+    let mode = perm.mode() as libc::mode_t;
+
+    spawn_blocking(move || {
+        syscall!(chmod(path.as_ptr(), mode))?;
+        Ok(())
+    })
+    .await
+}
+
+/// Changes the owner and group of the file at `path`, see `chown(2)`.
+///
+/// A `None` id is left unchanged. Symbolic links are followed. `io-uring` has
+/// no operation to change ownership, so this runs on the Tokio blocking pool.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::chown;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         // Change the group only
+///         chown("/some/file.txt", None, Some(100)).await?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn chown<P: AsRef<Path>>(path: P, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+    let path = cstr(path.as_ref())?;
+
+    // -1 leaves the id unchanged
+    let uid = uid.map_or(libc::uid_t::MAX, |uid| uid as libc::uid_t);
+    let gid = gid.map_or(libc::gid_t::MAX, |gid| gid as libc::gid_t);
+
+    spawn_blocking(move || {
+        syscall!(chown(path.as_ptr(), uid, gid))?;
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,191 @@
+use crate::buf::{AllocBuf, Allocator, Global};
+use crate::driver::{Op, Read};
+use crate::fs::File;
+
+use std::collections::VecDeque;
+use std::io;
+use std::rc::Rc;
+
+/// Longest range dropped from the page cache at once, page aligned.
+pub(crate) const MAX_ADVICE_LEN: u64 = 1 << 31;
+
+/// Largest folio of the page cache, on the common architectures.
+const FOLIO_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Reads a file from the start, in chunks, with several reads in flight at
+/// once to keep the device busy while the caller processes a chunk.
+///
+/// The reads are submitted ahead up to the expected size of the file. Past
+/// the expected size, one chunk is read at a time until the end of the file,
+/// in case the file grew.
+pub(crate) struct ReadPipeline<'a> {
+    file: &'a File,
+
+    /// Reads in flight, in file order
+    in_flight: VecDeque<Op<Read<AllocBuf>>>,
+
+    /// Chunks returned by the caller, reused for the next reads
+    free: Vec<AllocBuf>,
+
+    /// Allocator of the chunks
+    alloc: Rc<dyn Allocator>,
+
+    chunk_size: usize,
+    depth: usize,
+
+    /// Expected size of the file
+    size: u64,
+
+    /// Offset of the next read to submit
+    pos: u64,
+
+    /// Offset of the next chunk to return
+    offset: u64,
+
+    /// Set once the end of the file was read
+    done: bool,
+
+    /// Whether to drop the chunks returned from the page cache, once
+    /// consumed, and up to where they were dropped
+    drop_behind: bool,
+    dropped: u64,
+}
+
+impl<'a> ReadPipeline<'a> {
+    pub(crate) fn new(file: &'a File, size: u64, chunk_size: usize, depth: usize) -> Self {
+        ReadPipeline::with_allocator(file, size, chunk_size, depth, Rc::new(Global))
+    }
+
+    /// Reads into chunks allocated by `alloc`.
+    pub(crate) fn with_allocator(
+        file: &'a File,
+        size: u64,
+        chunk_size: usize,
+        depth: usize,
+        alloc: Rc<dyn Allocator>,
+    ) -> Self {
+        assert!(chunk_size > 0 && depth > 0);
+
+        ReadPipeline {
+            file,
+            in_flight: VecDeque::with_capacity(depth),
+            free: Vec::new(),
+            alloc,
+            chunk_size,
+            depth,
+            size,
+            pos: 0,
+            offset: 0,
+            done: false,
+            drop_behind: false,
+            dropped: 0,
+        }
+    }
+
+    /// Starts reading at `pos` instead of the start of the file.
+    pub(crate) fn starting_at(mut self, pos: u64) -> Self {
+        self.pos = pos;
+        self.offset = pos;
+        self.dropped = pos;
+        self
+    }
+
+    /// Drops the chunks from the page cache once consumed, see
+    /// `ReadChunks::drop_behind`.
+    pub(crate) fn set_drop_behind(&mut self, enabled: bool) {
+        self.drop_behind = enabled;
+        self.dropped = self.offset;
+    }
+
+    /// Returns the next chunk of the file, or `None` at the end of the file.
+    pub(crate) async fn next(&mut self) -> io::Result<Option<AllocBuf>> {
+        if self.drop_behind && self.dropped < self.offset {
+            // The chunks returned so far are consumed
+            self.drop_cached(self.offset)?;
+        }
+
+        if self.done {
+            return Ok(None);
+        }
+
+        self.fill()?;
+
+        let (res, buf) = self.in_flight.pop_front().unwrap().read().await;
+        let n = res?;
+        if n == 0 {
+            self.done = true;
+            self.in_flight.clear();
+            if self.drop_behind {
+                self.drop_cached(u64::MAX)?;
+            }
+            return Ok(None);
+        }
+        self.offset += n as u64;
+
+        if n < buf.capacity() {
+            // A short read, the following reads would leave a gap. Drop them
+            // and continue after the data read so far.
+            self.in_flight.clear();
+            self.pos = self.offset;
+        }
+
+        Ok(Some(buf))
+    }
+
+    /// Reuses `buf`, a chunk previously returned by `next`, for a next read.
+    pub(crate) fn recycle(&mut self, mut buf: AllocBuf) {
+        if self.free.len() < self.depth {
+            buf.clear();
+            self.free.push(buf);
+        }
+    }
+
+    /// Drops the pages of the file from the page cache up to `end`, or to
+    /// the end of the file if `u64::MAX`. The advice completes in the
+    /// background.
+    fn drop_cached(&mut self, end: u64) -> io::Result<()> {
+        // Pages are cached in folios of up to `FOLIO_SIZE` bytes, which are
+        // only dropped whole, so a folio holding the last pages consumed is
+        // dropped along with the next ones.
+        let mut start = self.dropped & !(FOLIO_SIZE - 1);
+
+        while start < end {
+            let len = if end == u64::MAX {
+                0
+            } else {
+                (end - start).min(MAX_ADVICE_LEN)
+            };
+            Op::fadvise(&self.file.fd, start, len as u32, libc::POSIX_FADV_DONTNEED)?;
+
+            if len == 0 {
+                break;
+            }
+            start += len;
+        }
+
+        self.dropped = end;
+        Ok(())
+    }
+
+    /// Submits reads until `depth` are in flight.
+    fn fill(&mut self) -> io::Result<()> {
+        while self.in_flight.len() < self.depth
+            && (self.pos < self.size || self.in_flight.is_empty())
+        {
+            let buf = match self.free.pop() {
+                Some(buf) => buf,
+                None => AllocBuf::with_capacity_in(self.chunk_size, self.alloc.clone()),
+            };
+
+            // The whole capacity is read, which may exceed the chunk size
+            let len = buf.capacity();
+
+            // The read is submitted now, not when awaited
+            let op = Op::read_at(&self.file.fd, buf, self.pos)?;
+            self.in_flight.push_back(op);
+            self.pos += len as u64;
+        }
+
+        Ok(())
+    }
+}
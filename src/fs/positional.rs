@@ -0,0 +1,245 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{self, Op, Read, SharedFd, Write};
+use crate::fs::RwFlags;
+use crate::BufResult;
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Future returned by [`File::read_at`] and [`File::read`].
+///
+/// The read is submitted when the future is first polled. Dropping the future
+/// while the read is in flight is safe: the buffer is held by the runtime
+/// until the kernel releases it, and dropped then. To get the buffer back
+/// instead, for example in a branch of `tokio::select!` that lost, call
+/// [`cancel`](ReadAt::cancel).
+///
+/// [`File::read_at`]: crate::fs::File::read_at
+/// [`File::read`]: crate::fs::File::read
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tokio_uring::fs::File;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let file = File::open("/dev/random").await?;
+///
+///         let mut read = file.read_at(vec![0; 4096], 0);
+///         let buf = tokio::select! {
+///             (res, buf) = &mut read => {
+///                 res?;
+///                 buf
+///             }
+///             _ = tokio_uring::time::sleep(Duration::from_millis(100)) => {
+///                 // The buffer is returned once the kernel released it
+///                 let (_, buf) = read.cancel().await;
+///                 buf
+///             }
+///         };
+///
+///         assert_eq!(buf.capacity(), 4096);
+///         Ok(())
+///     })
+/// }
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadAt<T: IoBufMut> {
+    state: State<Read<T>, T>,
+}
+
+/// Future returned by [`File::write_at`] and [`File::write`].
+///
+/// The write is submitted when the future is first polled. Like [`ReadAt`],
+/// the future can be dropped while the write is in flight, or canceled with
+/// [`cancel`](WriteAt::cancel) to get the buffer back.
+///
+/// [`File::write_at`]: crate::fs::File::write_at
+/// [`File::write`]: crate::fs::File::write
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteAt<T: IoBuf> {
+    state: State<Write<T>, T>,
+}
+
+enum State<O: 'static, T> {
+    /// Not submitted yet
+    Idle {
+        fd: SharedFd,
+        buf: T,
+        pos: u64,
+        flags: RwFlags,
+    },
+
+    /// In flight
+    Submitted(Op<O>),
+
+    /// The buffer was returned
+    Done,
+}
+
+impl<T: IoBufMut> ReadAt<T> {
+    pub(crate) fn new(fd: &SharedFd, buf: T, pos: u64, flags: RwFlags) -> ReadAt<T> {
+        ReadAt {
+            state: State::Idle {
+                fd: fd.clone(),
+                buf,
+                pos,
+                flags,
+            },
+        }
+    }
+
+    /// Cancels the read, returning the buffer once the kernel released it.
+    ///
+    /// The result is `ECANCELED` if the read was canceled, or the result of
+    /// the read if it completed first, in which case the buffer holds the
+    /// bytes read. A read that was not submitted yet is not submitted.
+    pub async fn cancel(self) -> BufResult<usize, T> {
+        match self.state {
+            State::Idle { buf, .. } => (Err(canceled()), buf),
+            State::Submitted(op) => op.cancel_read().await,
+            State::Done => panic!("`ReadAt` canceled after completion"),
+        }
+    }
+}
+
+impl<T: IoBufMut> Future for ReadAt<T> {
+    type Output = BufResult<usize, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let State::Idle { fd, .. } = &self.state {
+            // Wait for room in the runtime, or fail, see `Builder::max_ops`
+            if let Err(e) = ready!(driver::poll_room(fd, cx)) {
+                return Poll::Ready((Err(e), self.state.take_buf()));
+            }
+
+            let (fd, buf, pos, flags) = match std::mem::replace(&mut self.state, State::Done) {
+                State::Idle {
+                    fd,
+                    buf,
+                    pos,
+                    flags,
+                } => (fd, buf, pos, flags),
+                _ => unreachable!(),
+            };
+            match Op::read_at_with_flags(&fd, buf, pos, flags.bits()) {
+                Ok(op) => self.state = State::Submitted(op),
+                Err((e, buf)) => return Poll::Ready((Err(e), buf)),
+            }
+        }
+
+        let res = match &mut self.state {
+            State::Submitted(op) => ready!(op.poll_read(cx)),
+            _ => panic!("`ReadAt` polled after completion"),
+        };
+
+        self.state = State::Done;
+        Poll::Ready(res)
+    }
+}
+
+impl<T: IoBufMut> fmt::Debug for ReadAt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadAt")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<T: IoBuf> WriteAt<T> {
+    pub(crate) fn new(fd: &SharedFd, buf: T, pos: u64, flags: RwFlags) -> WriteAt<T> {
+        WriteAt {
+            state: State::Idle {
+                fd: fd.clone(),
+                buf,
+                pos,
+                flags,
+            },
+        }
+    }
+
+    /// Cancels the write, returning the buffer once the kernel released it.
+    ///
+    /// The result is `ECANCELED` if the write was canceled, or the result of
+    /// the write if it completed first. A write that was not submitted yet is
+    /// not submitted.
+    pub async fn cancel(self) -> BufResult<usize, T> {
+        match self.state {
+            State::Idle { buf, .. } => (Err(canceled()), buf),
+            State::Submitted(op) => op.cancel_write().await,
+            State::Done => panic!("`WriteAt` canceled after completion"),
+        }
+    }
+}
+
+impl<T: IoBuf> Future for WriteAt<T> {
+    type Output = BufResult<usize, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let State::Idle { fd, .. } = &self.state {
+            // Wait for room in the runtime, or fail, see `Builder::max_ops`
+            if let Err(e) = ready!(driver::poll_room(fd, cx)) {
+                return Poll::Ready((Err(e), self.state.take_buf()));
+            }
+
+            let (fd, buf, pos, flags) = match std::mem::replace(&mut self.state, State::Done) {
+                State::Idle {
+                    fd,
+                    buf,
+                    pos,
+                    flags,
+                } => (fd, buf, pos, flags),
+                _ => unreachable!(),
+            };
+            match Op::write_at_with_flags(&fd, buf, pos, flags.bits()) {
+                Ok(op) => self.state = State::Submitted(op),
+                Err((e, buf)) => return Poll::Ready((Err(e), buf)),
+            }
+        }
+
+        let res = match &mut self.state {
+            State::Submitted(op) => ready!(op.poll_write(cx)),
+            _ => panic!("`WriteAt` polled after completion"),
+        };
+
+        self.state = State::Done;
+        Poll::Ready(res)
+    }
+}
+
+impl<T: IoBuf> fmt::Debug for WriteAt<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WriteAt")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<O, T> State<O, T> {
+    /// Returns the buffer of an operation not submitted yet.
+    fn take_buf(&mut self) -> T {
+        match std::mem::replace(self, State::Done) {
+            State::Idle { buf, .. } => buf,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<O, T> fmt::Debug for State<O, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            State::Idle { .. } => f.write_str("Idle"),
+            State::Submitted(_) => f.write_str("Submitted"),
+            State::Done => f.write_str("Done"),
+        }
+    }
+}
+
+fn canceled() -> io::Error {
+    io::Error::from_raw_os_error(libc::ECANCELED)
+}
@@ -0,0 +1,107 @@
+use crate::buf::IoBufMut;
+use crate::driver::{complete_read, Losers, Op, Read};
+use crate::fs::File;
+
+use futures_core::Stream;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Reads from several files at once, such as replicas of the same data, and
+/// returns the first read to complete, canceling the others.
+///
+/// Each read is a file, a buffer and a position. Returns the index of the
+/// first read to complete, successfully or not, its result and buffer, and
+/// the other reads, which were canceled. They yield their buffers once the
+/// kernel released them, with `ECANCELED`, or with their own result if they
+/// completed before the cancellation reached them. Dropping them drops the
+/// buffers once the reads complete.
+///
+/// Racing reads trades extra work for a lower latency, when some of the
+/// devices may be slow to respond.
+///
+/// # Errors
+///
+/// Fails if `reads` is empty, or if the reads cannot be submitted, dropping
+/// the buffers.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{self, File};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let primary = File::open("/mnt/a/data").await?;
+///         let replica = File::open("/mnt/b/data").await?;
+///
+///         let reads = vec![(&primary, vec![0; 4096], 0), (&replica, vec![0; 4096], 0)];
+///         let (winner, (res, buf), _losers) = fs::read_first_at(reads).await?;
+///         let n = res?;
+///
+///         println!("read {} bytes from replica {}", n, winner);
+///         println!("{:?}", &buf[..n]);
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn read_first_at<T: IoBufMut>(
+    reads: Vec<(&File, T, u64)>,
+) -> io::Result<(usize, crate::BufResult<usize, T>, ReadLosers<T>)> {
+    if reads.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no reads to race",
+        ));
+    }
+
+    let ops = reads
+        .into_iter()
+        .map(|(file, buf, pos)| Op::read_at(&file.fd, buf, pos))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let (index, completion, losers) = Op::race(ops).await;
+    Ok((index, complete_read(completion), ReadLosers { losers }))
+}
+
+/// The reads which lost a race, canceled.
+///
+/// Created by [`read_first_at`]. A stream of the index of each read and its
+/// result and buffer, as the reads complete.
+pub struct ReadLosers<T: IoBufMut> {
+    losers: Losers<Read<T>>,
+}
+
+impl<T: IoBufMut> ReadLosers<T> {
+    /// Returns the number of reads which did not complete yet.
+    pub fn len(&self) -> usize {
+        self.losers.len()
+    }
+
+    /// Returns `true` once all the reads completed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: IoBufMut> Stream for ReadLosers<T> {
+    type Item = (usize, crate::BufResult<usize, T>);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (index, completion) = match ready!(Pin::new(&mut self.losers).poll_next(cx)) {
+            Some(loser) => loser,
+            None => return Poll::Ready(None),
+        };
+
+        Poll::Ready(Some((index, complete_read(completion))))
+    }
+}
+
+impl<T: IoBufMut> fmt::Debug for ReadLosers<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadLosers")
+            .field("pending", &self.len())
+            .finish()
+    }
+}
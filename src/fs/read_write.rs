@@ -0,0 +1,185 @@
+use crate::buf::IoBuf;
+use crate::fs::pipeline::ReadPipeline;
+use crate::fs::{File, NamedTempFile, OpenOptions};
+
+use std::io;
+use std::path::Path;
+
+/// Size of the reads of [`read`].
+pub(crate) const CHUNK_SIZE: usize = 128 * 1024;
+
+/// Maximum number of reads of [`read`] in flight at once.
+pub(crate) const READ_DEPTH: usize = 4;
+
+/// Reads the entire contents of a file into a bytes vector.
+///
+/// The file is opened, its size is read with `statx(2)` to size the vector,
+/// and several chunks are read at once to keep the device busy. Reading
+/// continues past the size until the end of the file, in case the file grew.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let contents = fs::read("foo.txt").await?;
+///         println!("read {} bytes", contents.len());
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let file = File::open(path).await?;
+
+    let mut contents = Vec::new();
+    read_to_end_at(&file, 0, &mut contents).await?;
+    file.close().await?;
+
+    Ok(contents)
+}
+
+/// Reads `file` from `pos` until its end, appending to `buf`, which is sized
+/// from the size of the file. Several chunks are read at once, and reading
+/// continues past the size until the end of the file.
+///
+/// On error, `buf` holds the data read until then.
+pub(crate) async fn read_to_end_at(file: &File, pos: u64, buf: &mut Vec<u8>) -> io::Result<()> {
+    let size = file.len().await?;
+    buf.reserve(size.saturating_sub(pos) as usize);
+
+    // The remaining reads are dropped on return, which waits for them
+    let mut pipeline = ReadPipeline::new(file, size, CHUNK_SIZE, READ_DEPTH).starting_at(pos);
+    while let Some(chunk) = pipeline.next().await? {
+        buf.extend_from_slice(&chunk);
+        pipeline.recycle(chunk);
+    }
+
+    Ok(())
+}
+
+/// Reads the entire contents of a file into a string.
+///
+/// See [`read`]. Returns an [`InvalidData`](io::ErrorKind::InvalidData)
+/// error if the contents are not valid UTF-8.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let config = fs::read_to_string("config.toml").await?;
+///         println!("{}", config);
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let contents = read(path).await?;
+    String::from_utf8(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes a buffer as the entire contents of a file, returning the buffer.
+///
+/// The file is created if it does not exist, and truncated if it does. The
+/// whole buffer is written, then the file is closed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let (res, _) = fs::write("foo.txt", b"Hello, world!".to_vec()).await;
+///         res?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn write<P: AsRef<Path>, T: IoBuf>(path: P, buf: T) -> crate::BufResult<(), T> {
+    let file = match File::create(path).await {
+        Ok(file) => file,
+        Err(e) => return (Err(e), buf),
+    };
+
+    let (res, buf) = write_all(&file, buf).await;
+    if let Err(e) = res {
+        return (Err(e), buf);
+    }
+
+    (file.close().await, buf)
+}
+
+/// Atomically replaces the contents of a file with a buffer, returning the
+/// buffer.
+///
+/// The buffer is written to a temporary file in the same directory, which is
+/// synced to disk and renamed over `path`, before syncing the directory to
+/// persist the rename. After a crash, `path` holds either the old contents or
+/// the new ones, never a mix. The sync and the rename are linked operations,
+/// submitted together.
+///
+/// As with [`write()`], a new file is created with mode `0o666`, minus the
+/// umask.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let (res, _) = fs::write_atomic("state.json", b"{}".to_vec()).await;
+///         res?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn write_atomic<P: AsRef<Path>, T: IoBuf>(path: P, buf: T) -> crate::BufResult<(), T> {
+    let path = path.as_ref();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let tmp = match NamedTempFile::with_mode_in(dir, 0o666).await {
+        Ok(tmp) => tmp,
+        Err(e) => return (Err(e), buf),
+    };
+
+    // On failure, dropping the temporary file deletes it
+    let (res, buf) = write_all(&tmp, buf).await;
+    if let Err(e) = res {
+        return (Err(e), buf);
+    }
+
+    let res = async {
+        let file = tmp.sync_persist(path).await?;
+        file.close().await?;
+
+        // Persist the new directory entry
+        let mut options = OpenOptions::new();
+        options.read(true);
+        options.custom_flags = libc::O_DIRECTORY;
+
+        let dir = options.open(dir).await?;
+        dir.sync_all().await?;
+        dir.close().await
+    }
+    .await;
+
+    (res, buf)
+}
+
+/// Writes the whole buffer at the start of the file.
+async fn write_all<T: IoBuf>(file: &File, buf: T) -> crate::BufResult<(), T> {
+    crate::io::write_all(buf, |slice, written| file.write_at(slice, written as u64)).await
+}
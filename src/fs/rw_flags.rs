@@ -0,0 +1,60 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// Flags modifying a single read or write of a file, the `RWF_*` flags of
+/// `preadv2(2)` and `pwritev2(2)`.
+///
+/// Flags are combined with `|`, e.g. `RwFlags::DSYNC | RwFlags::APPEND`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RwFlags(libc::c_int);
+
+impl RwFlags {
+    /// Makes the data of the write durable before it completes, as if the
+    /// file was opened with `O_DSYNC` (`RWF_DSYNC`): the write is followed
+    /// by the equivalent of [`File::sync_data`] on its range, without the
+    /// round trip of a separate operation. Requires Linux 4.7 or later.
+    ///
+    /// [`File::sync_data`]: crate::fs::File::sync_data
+    pub const DSYNC: RwFlags = RwFlags(libc::RWF_DSYNC);
+
+    /// Like [`DSYNC`](RwFlags::DSYNC), and also makes the metadata of the
+    /// file durable, as if the file was opened with `O_SYNC` (`RWF_SYNC`).
+    pub const SYNC: RwFlags = RwFlags(libc::RWF_SYNC);
+
+    /// Appends the data to the end of the file, ignoring the offset of the
+    /// write, as if the file was opened with `O_APPEND` (`RWF_APPEND`).
+    /// Requires Linux 4.16 or later.
+    pub const APPEND: RwFlags = RwFlags(libc::RWF_APPEND);
+
+    /// Fails with `EAGAIN` instead of waiting for the device when the data
+    /// is not in the page cache, or the write would block (`RWF_NOWAIT`).
+    pub const NOWAIT: RwFlags = RwFlags(libc::RWF_NOWAIT);
+
+    /// No flags.
+    pub const fn empty() -> RwFlags {
+        RwFlags(0)
+    }
+
+    /// Returns `true` if all the flags in `other` are set.
+    pub const fn contains(self, other: RwFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the raw `RWF_*` flags.
+    pub const fn bits(self) -> libc::c_int {
+        self.0
+    }
+}
+
+impl BitOr for RwFlags {
+    type Output = RwFlags;
+
+    fn bitor(self, rhs: RwFlags) -> RwFlags {
+        RwFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for RwFlags {
+    fn bitor_assign(&mut self, rhs: RwFlags) {
+        self.0 |= rhs.0;
+    }
+}
@@ -0,0 +1,210 @@
+use crate::fs::File;
+
+use std::fmt;
+use std::io;
+
+/// `FS_IOC_FIEMAP`, `_IOWR('f', 11, struct fiemap)`.
+const FS_IOC_FIEMAP: libc::c_ulong = 0xc020_660b;
+
+/// Set on the last extent of the file.
+const FIEMAP_EXTENT_LAST: u32 = 0x1;
+
+/// Number of extents mapped by each `FS_IOC_FIEMAP` call.
+const EXTENTS_PER_CALL: usize = 64;
+
+/// Size of the buffer of [`copy_sparse`].
+const COPY_BUF_SIZE: usize = 1024 * 1024;
+
+/// An extent of a file, mapped to the device holding it.
+///
+/// Returned by [`File::extent_map`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FileExtent {
+    logical: u64,
+    physical: u64,
+    len: u64,
+    flags: u32,
+}
+
+impl FileExtent {
+    /// Unwritten extent: allocated, for example by `fallocate(2)`, but reads
+    /// return zeros.
+    pub const UNWRITTEN: u32 = 0x800;
+
+    /// Delayed allocation: the data is not on the device yet, and the
+    /// physical offset is meaningless.
+    pub const DELALLOC: u32 = 0x4;
+
+    /// Shared with other files, for example after a reflink copy.
+    pub const SHARED: u32 = 0x2000;
+
+    /// Returns the offset of the extent in the file.
+    pub fn logical(&self) -> u64 {
+        self.logical
+    }
+
+    /// Returns the offset of the extent on the device.
+    pub fn physical(&self) -> u64 {
+        self.physical
+    }
+
+    /// Returns the length of the extent, in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the extent is empty, which the kernel does not
+    /// report.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the `FIEMAP_EXTENT_*` flags of the extent, such as
+    /// [`UNWRITTEN`](FileExtent::UNWRITTEN).
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+impl fmt::Debug for FileExtent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileExtent")
+            .field("logical", &self.logical)
+            .field("physical", &self.physical)
+            .field("len", &self.len)
+            .field("flags", &format_args!("{:#x}", self.flags))
+            .finish()
+    }
+}
+
+#[repr(C)]
+struct Fiemap {
+    start: u64,
+    length: u64,
+    flags: u32,
+    mapped_extents: u32,
+    extent_count: u32,
+    reserved: u32,
+    extents: [FiemapExtent; EXTENTS_PER_CALL],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct FiemapExtent {
+    logical: u64,
+    physical: u64,
+    length: u64,
+    reserved64: [u64; 2],
+    flags: u32,
+    reserved: [u32; 3],
+}
+
+/// Maps the extents of the file `fd`, see `File::extent_map`.
+pub(crate) fn extent_map(fd: libc::c_int) -> io::Result<Vec<FileExtent>> {
+    let mut extents = Vec::new();
+    let mut fiemap: Box<Fiemap> = Box::new(unsafe { std::mem::zeroed() });
+    let mut start = 0;
+
+    loop {
+        fiemap.start = start;
+        fiemap.length = u64::MAX - start;
+        fiemap.extent_count = EXTENTS_PER_CALL as u32;
+        fiemap.mapped_extents = 0;
+
+        syscall!(ioctl(fd, FS_IOC_FIEMAP as _, &mut *fiemap as *mut Fiemap))?;
+
+        let mapped = &fiemap.extents[..fiemap.mapped_extents as usize];
+        let last = match mapped.last() {
+            Some(last) => *last,
+            None => return Ok(extents),
+        };
+
+        extents.extend(mapped.iter().map(|e| FileExtent {
+            logical: e.logical,
+            physical: e.physical,
+            len: e.length,
+            flags: e.flags,
+        }));
+
+        if last.flags & FIEMAP_EXTENT_LAST != 0 {
+            return Ok(extents);
+        }
+        start = last.logical + last.length;
+    }
+}
+
+/// Copies the data of `src` to `dst`, skipping the holes of `src`, and
+/// returns the number of bytes copied.
+///
+/// The ranges of `src` holding data are found with
+/// [`seek_data`](File::seek_data) and [`seek_hole`](File::seek_hole), and
+/// copied to the same positions of `dst`, which is then truncated or
+/// extended to the length of `src`. If `dst` is empty to begin with, it is a
+/// sparse copy of `src`, with the same holes, as long as its file system
+/// supports them. The file positions of `src` and `dst` are left
+/// unspecified.
+///
+/// File systems which do not track holes report the whole file as data,
+/// which is then copied in full.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::{self, File};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let src = File::open("disk.img").await?;
+///         let dst = File::create("disk.img.bak").await?;
+///
+///         let copied = fs::copy_sparse(&src, &dst).await?;
+///         println!("copied {} bytes of data", copied);
+///
+///         dst.sync_all().await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn copy_sparse(src: &File, dst: &File) -> io::Result<u64> {
+    let len = src.len().await?;
+    let mut buf = vec![0; COPY_BUF_SIZE];
+    let mut copied = 0;
+    let mut pos = 0;
+
+    while pos < len {
+        let start = match src.seek_data(pos)? {
+            Some(start) => start,
+            None => break,
+        };
+        let end = src.seek_hole(start)?.min(len);
+
+        pos = start;
+        while pos < end {
+            buf.clear();
+            let (res, mut b) = src.read_at(buf, pos).await;
+            let n = (res? as u64).min(end - pos);
+            if n == 0 {
+                // Truncated meanwhile
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            b.truncate(n as usize);
+            let (res, b) = write_all_at(dst, b, pos).await;
+            res?;
+            buf = b;
+
+            pos += n;
+            copied += n;
+        }
+    }
+
+    syscall!(ftruncate(dst.fd.raw_fd(), len as libc::off_t))?;
+    Ok(copied)
+}
+
+async fn write_all_at(file: &File, buf: Vec<u8>, pos: u64) -> crate::BufResult<(), Vec<u8>> {
+    crate::io::write_all(buf, |slice, written| {
+        file.write_at(slice, pos + written as u64)
+    })
+    .await
+}
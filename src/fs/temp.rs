@@ -0,0 +1,227 @@
+use crate::driver::Op;
+use crate::fs::{File, OpenOptions};
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// Number of names tried by [`NamedTempFile::new_in`] before giving up.
+const NUM_RETRIES: u32 = 1 << 16;
+
+/// Creates an anonymous file in the directory `dir`, see `O_TMPFILE` in
+/// `open(2)`.
+///
+/// The file has no name, and is deleted once closed, even if the process
+/// crashes. It can be given a name once written with [`File::link`], which
+/// makes it appear atomically with its full contents.
+///
+/// The filesystem of `dir` must support `O_TMPFILE`, as do most local
+/// filesystems since Linux 3.11.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let f = fs::tempfile_in("/tmp").await?;
+///         let (res, _) = f.write_at(&b"scratch data"[..], 0).await;
+///         res?;
+///
+///         // Closing the file deletes it
+///         f.close().await?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn tempfile_in<P: AsRef<Path>>(dir: P) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(true);
+    options.mode = 0o600;
+    options.custom_flags = libc::O_TMPFILE;
+
+    options.open(dir).await
+}
+
+/// A file with a random name in a temporary directory, deleted when dropped.
+///
+/// The file is opened for reading and writing, and derefs to [`File`]. It is
+/// deleted in the background when dropped, or with [`close`], which waits for
+/// the deletion. [`persist`] and [`keep`] keep it instead.
+///
+/// [`close`]: NamedTempFile::close
+/// [`persist`]: NamedTempFile::persist
+/// [`keep`]: NamedTempFile::keep
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::NamedTempFile;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let tmp = NamedTempFile::new().await?;
+///         let (res, _) = tmp.write_at(&b"new contents"[..], 0).await;
+///         res?;
+///         tmp.sync_all().await?;
+///
+///         // Atomically replace the old file
+///         let file = tmp.persist("config.toml").await?;
+///         file.close().await?;
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct NamedTempFile {
+    file: File,
+
+    /// Path of the file, `None` once kept
+    path: Option<PathBuf>,
+}
+
+impl NamedTempFile {
+    /// Creates a file in the temporary directory of the system, see
+    /// [`std::env::temp_dir`].
+    pub async fn new() -> io::Result<NamedTempFile> {
+        NamedTempFile::new_in(std::env::temp_dir()).await
+    }
+
+    /// Creates a file in the directory `dir`.
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<NamedTempFile> {
+        NamedTempFile::with_mode_in(dir.as_ref(), 0o600).await
+    }
+
+    /// Creates a file in the directory `dir`, with permissions `mode` before
+    /// the umask is applied.
+    pub(crate) async fn with_mode_in(dir: &Path, mode: libc::mode_t) -> io::Result<NamedTempFile> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        options.mode = mode;
+
+        for _ in 0..NUM_RETRIES {
+            let path = dir.join(tmpname());
+
+            match options.open(&path).await {
+                Ok(file) => {
+                    return Ok(NamedTempFile {
+                        file,
+                        path: Some(path),
+                    })
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "too many temporary files exist",
+        ))
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().unwrap()
+    }
+
+    /// Returns the file.
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Renames the file to `path`, replacing any file at `path`, and returns
+    /// the file, which is no longer deleted.
+    ///
+    /// The rename is atomic: other processes see either the old file at
+    /// `path`, or this one. On failure, the file is deleted.
+    pub async fn persist<P: AsRef<Path>>(self, path: P) -> io::Result<File> {
+        let op = Op::rename(self.path(), path.as_ref())?;
+        op.await.result?;
+
+        Ok(self.keep().0)
+    }
+
+    /// Syncs the file to disk, then renames it to `path`, with the rename
+    /// linked to the sync so that both complete in one round trip.
+    pub(crate) async fn sync_persist(self, path: &Path) -> io::Result<File> {
+        let (fsync, rename) = Op::fsync_then_rename(&self.file.fd, self.path(), path)?;
+
+        // If the sync failed, the rename is canceled
+        let fsync = fsync.await.result;
+        let rename = rename.await.result;
+        fsync?;
+        rename?;
+
+        Ok(self.keep().0)
+    }
+
+    /// Keeps the file, returning it and its path.
+    pub fn keep(mut self) -> (File, PathBuf) {
+        let path = self.path.take().unwrap();
+        let file = File::from_shared_fd(self.file.fd.clone());
+        (file, path)
+    }
+
+    /// Deletes and closes the file.
+    ///
+    /// The method completes once both operations have completed.
+    pub async fn close(mut self) -> io::Result<()> {
+        let path = self.path.take().unwrap();
+        let file = File::from_shared_fd(self.file.fd.clone());
+        drop(self);
+
+        let op = Op::unlink_file(&path)?;
+        let res = op.await.result;
+        file.close().await?;
+
+        res.map(|_| ())
+    }
+}
+
+impl Deref for NamedTempFile {
+    type Target = File;
+
+    fn deref(&self) -> &File {
+        &self.file
+    }
+}
+
+impl Drop for NamedTempFile {
+    fn drop(&mut self) {
+        let path = match self.path.take() {
+            Some(path) => path,
+            None => return,
+        };
+
+        // Delete the file in the background, or right away when off the
+        // runtime.
+        match Op::unlink_file(&path) {
+            Ok(op) => drop(op),
+            Err(_) => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for NamedTempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedTempFile")
+            .field("fd", &self.file.fd.raw_fd())
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Returns a random, hidden file name.
+fn tmpname() -> String {
+    // Each `RandomState` is seeded with new random keys
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    format!(".tmp{:016x}", hasher.finish())
+}
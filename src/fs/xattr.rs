@@ -0,0 +1,169 @@
+use crate::driver::{cstr, Op, Xattr};
+use crate::runtime::spawn_blocking;
+
+use std::ffi::{CString, OsString};
+use std::io;
+use std::os::unix::ffi::OsStringExt;
+use std::path::Path;
+
+/// Returns the value of the extended attribute `name` of the file at `path`,
+/// or `None` if the file has no such attribute.
+///
+/// Symbolic links are followed. The attribute is read with `io-uring` on Linux
+/// 5.19 or later, and with a blocking `getxattr(2)` otherwise.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::get_xattr;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         if let Some(value) = get_xattr("/some/file.txt", "user.origin").await? {
+///             println!("origin: {}", String::from_utf8_lossy(&value));
+///         }
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn get_xattr<P: AsRef<Path>>(path: P, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let path = path.as_ref();
+
+    loop {
+        // Learn the length of the value
+        let len = match get(path, name, Vec::new()).await {
+            Ok((len, _)) => len,
+            Err(e) if e.raw_os_error() == Some(libc::ENODATA) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        match get(path, name, Vec::with_capacity(len)).await {
+            Ok((_, value)) => return Ok(Some(value)),
+            // The value grew in between, try again
+            Err(e) if e.raw_os_error() == Some(libc::ERANGE) => continue,
+            Err(e) if e.raw_os_error() == Some(libc::ENODATA) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Sets the extended attribute `name` of the file at `path` to `value`,
+/// creating the attribute if needed.
+///
+/// Symbolic links are followed. The attribute is written with `io-uring` on
+/// Linux 5.19 or later, and with a blocking `setxattr(2)` otherwise.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::set_xattr;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         set_xattr("/some/file.txt", "user.origin", "backup").await?;
+///         Ok::<(), std::io::Error>(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub async fn set_xattr<P: AsRef<Path>, V: AsRef<[u8]>>(
+    path: P,
+    name: &str,
+    value: V,
+) -> io::Result<()> {
+    let op = Op::set_xattr(path.as_ref(), name, value.as_ref().to_vec(), 0)?;
+    let completion = op.await;
+
+    match completion.result {
+        Err(e) if is_unsupported(&e) => {
+            let Xattr { path, name, value } = completion.data;
+            spawn_blocking(move || {
+                syscall!(setxattr(
+                    path.as_ptr(),
+                    name.as_ptr(),
+                    value.as_ptr().cast(),
+                    value.len(),
+                    0
+                ))?;
+                Ok(())
+            })
+            .await
+        }
+        res => res.map(|_| ()),
+    }
+}
+
+/// Returns the names of the extended attributes of the file at `path`.
+///
+/// Symbolic links are followed. `io-uring` has no operation to list
+/// attributes, so this runs a blocking `listxattr(2)` on the Tokio blocking
+/// pool.
+pub async fn list_xattr<P: AsRef<Path>>(path: P) -> io::Result<Vec<OsString>> {
+    let path = cstr(path.as_ref())?;
+
+    spawn_blocking(move || loop {
+        let len = syscall!(listxattr(path.as_ptr(), std::ptr::null_mut(), 0))? as usize;
+
+        let mut names = vec![0u8; len];
+        let len = match syscall!(listxattr(path.as_ptr(), names.as_mut_ptr().cast(), len)) {
+            Ok(len) => len as usize,
+            // The list grew in between, try again
+            Err(e) if e.raw_os_error() == Some(libc::ERANGE) => continue,
+            Err(e) => return Err(e),
+        };
+        names.truncate(len);
+
+        // The names are null-terminated
+        return Ok(names
+            .split(|&b| b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| OsString::from_vec(name.to_vec()))
+            .collect());
+    })
+    .await
+}
+
+/// Reads the attribute into `value`, up to its capacity, returning the length
+/// of the value and the buffer.
+async fn get(path: &Path, name: &str, value: Vec<u8>) -> io::Result<(usize, Vec<u8>)> {
+    let op = Op::get_xattr(path, name, value)?;
+    let completion = op.await;
+
+    let (len, mut value) = match completion.result {
+        Err(e) if is_unsupported(&e) => {
+            let Xattr { path, name, value } = completion.data;
+            get_blocking(path, name, value).await?
+        }
+        res => (res? as usize, completion.data.value),
+    };
+
+    if value.capacity() > 0 {
+        // Safety: the kernel wrote `len` bytes to the buffer.
+        unsafe { value.set_len(len) };
+    }
+
+    Ok((len, value))
+}
+
+async fn get_blocking(
+    path: CString,
+    name: CString,
+    mut value: Vec<u8>,
+) -> io::Result<(usize, Vec<u8>)> {
+    spawn_blocking(move || {
+        let len = syscall!(getxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_mut_ptr().cast(),
+            value.capacity()
+        ))? as usize;
+        Ok((len, value))
+    })
+    .await
+}
+
+/// Kernels before 5.19 reject the xattr opcodes with `EINVAL`.
+fn is_unsupported(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(libc::EINVAL)
+}
@@ -1,6 +1,8 @@
 use std::future::Future;
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::Thread;
 
 macro_rules! ready {
     ($e:expr $(,)?) => {
@@ -32,6 +34,16 @@ where
     type Output = T;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
-        (&mut self.f)(cx)
+        (self.f)(cx)
+    }
+}
+
+/// Unparks a thread blocked outside of the runtime, waiting for a task of the
+/// runtime.
+pub(crate) struct ThreadWaker(pub(crate) Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
     }
 }
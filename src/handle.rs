@@ -0,0 +1,411 @@
+use crate::driver::{self, SharedFd};
+use crate::future::ThreadWaker;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::future::Future;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// A job queued by another thread, run on the thread of the runtime.
+type Job = Box<dyn FnOnce() + Send>;
+
+thread_local! {
+    /// Queue of the runtime running on this thread, once a handle to it was
+    /// created.
+    static QUEUE: RefCell<Option<Arc<Queue>>> = const { RefCell::new(None) };
+}
+
+/// A handle to a `tokio-uring` runtime, which can be sent to other threads.
+///
+/// The resource types of `tokio-uring` are `!Send`, and the runtime runs on a
+/// single thread. A handle lets threads outside of the runtime, such as the
+/// workers of a thread pool, hand work over to it: the futures spawned with
+/// the handle are sent to the runtime through a queue, and run as tasks of
+/// the runtime, where they can use `tokio-uring` resources.
+///
+/// Each spawned task returns a [`JoinHandle`], which resolves to its output.
+/// Dropping the `JoinHandle` detaches the task, for fire-and-forget work.
+///
+/// Once the runtime shuts down, the tasks it did not run yet are dropped, and
+/// spawning more fails with a [`JoinError`].
+///
+/// # Examples
+///
+/// Running the runtime on its own thread, and writing to a file from the
+/// main thread:
+///
+/// ```
+/// use tokio_uring::fs::File;
+/// use std::sync::mpsc;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let dir = tempfile::tempdir()?;
+///     let path = dir.path().join("hello.txt");
+///
+///     let (tx, rx) = mpsc::channel();
+///     std::thread::spawn(move || {
+///         tokio_uring::start(async {
+///             tx.send(tokio_uring::Handle::current()).unwrap();
+///
+///             // Serve the tasks spawned by other threads
+///             std::future::pending::<()>().await;
+///         })
+///     });
+///
+///     let handle = rx.recv()?;
+///     let join = handle.spawn_pinned(move || async move {
+///         let file = File::create(&path).await?;
+///         let (res, _) = file.write_at(&b"hello world"[..], 0).await;
+///         res?;
+///         file.close().await
+///     });
+///
+///     join.blocking_join()??;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Handle {
+    queue: Arc<Queue>,
+}
+
+/// An owned permission to wait for a task spawned with a [`Handle`].
+///
+/// The handle is a future resolving to the output of the task, and can be
+/// waited for from any thread, inside or outside of a runtime. Dropping the
+/// handle detaches the task, which still runs to completion.
+pub struct JoinHandle<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+/// The error of a task spawned with a [`Handle`], which did not complete.
+#[derive(Debug)]
+pub struct JoinError {
+    panicked: bool,
+}
+
+/// Jobs sent to the runtime, along with the `eventfd` waking it.
+struct Queue {
+    jobs: Mutex<Jobs>,
+    eventfd: File,
+}
+
+struct Jobs {
+    queue: VecDeque<Job>,
+
+    /// Set once the runtime shut down
+    closed: bool,
+}
+
+/// Output of a task, shared by the task and its `JoinHandle`.
+struct Slot<T> {
+    output: Option<Result<T, JoinError>>,
+    waker: Option<Waker>,
+}
+
+/// Completes a `JoinHandle`, with an error if dropped before `complete`.
+struct Completion<T> {
+    slot: Option<Arc<Mutex<Slot<T>>>>,
+}
+
+/// Closes the queue when the dispatcher task is dropped, on shutdown.
+struct Dispatcher {
+    queue: Arc<Queue>,
+}
+
+impl Handle {
+    /// Returns a handle to the runtime running on the current thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a `tokio-uring` runtime, or if the
+    /// runtime cannot create the `eventfd` waking it.
+    pub fn current() -> Handle {
+        assert!(
+            driver::is_current(),
+            "`Handle::current` must be called from a `tokio-uring` runtime"
+        );
+
+        let queue = QUEUE.with(|current| {
+            let mut current = current.borrow_mut();
+            match &*current {
+                Some(queue) => queue.clone(),
+                None => {
+                    let queue = Queue::new().expect("failed to create the runtime queue");
+                    crate::spawn(dispatch(Dispatcher {
+                        queue: queue.clone(),
+                    }));
+                    current.insert(queue).clone()
+                }
+            }
+        });
+
+        Handle { queue }
+    }
+
+    /// Spawns a future onto the runtime, returning a [`JoinHandle`] for it.
+    ///
+    /// The future is sent to the thread of the runtime, and runs there as a
+    /// task, which can use `tokio-uring` resources.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_pinned(move || future)
+    }
+
+    /// Spawns the future returned by `f` onto the runtime, returning a
+    /// [`JoinHandle`] for it.
+    ///
+    /// `f` is sent to the thread of the runtime, and called there: the future
+    /// it returns need not be `Send`, so it can hold `tokio-uring` resources
+    /// across awaits.
+    pub fn spawn_pinned<F, Fut>(&self, f: F) -> JoinHandle<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot {
+            output: None,
+            waker: None,
+        }));
+        let completion = Completion {
+            slot: Some(slot.clone()),
+        };
+
+        // Dropping the job before it runs, on shutdown, fails the task
+        self.queue.push(Box::new(move || {
+            let future = f();
+            crate::spawn(async move {
+                let mut completion = completion;
+                let output = future.await;
+                completion.complete(Ok(output));
+            });
+        }));
+
+        JoinHandle { slot }
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let jobs = self.queue.lock();
+        f.debug_struct("Handle")
+            .field("queued", &jobs.queue.len())
+            .field("closed", &jobs.closed)
+            .finish()
+    }
+}
+
+impl<T> JoinHandle<T> {
+    /// Blocks the current thread until the task completes, and returns its
+    /// output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from the thread of a `tokio-uring` runtime, which
+    /// blocking could deadlock. Await the handle there instead.
+    pub fn blocking_join(self) -> Result<T, JoinError> {
+        assert!(
+            !driver::is_current(),
+            "`JoinHandle::blocking_join` called from a `tokio-uring` runtime"
+        );
+
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+
+        loop {
+            let mut slot = lock(&self.slot);
+            if let Some(output) = slot.output.take() {
+                return output;
+            }
+            slot.waker = Some(waker.clone());
+            drop(slot);
+
+            thread::park();
+        }
+    }
+
+    /// Returns `true` once the task completed, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        lock(&self.slot).output.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = lock(&self.slot);
+        match slot.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                match &slot.waker {
+                    Some(waker) if waker.will_wake(cx.waker()) => {}
+                    _ => slot.waker = Some(cx.waker().clone()),
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl JoinError {
+    /// Returns `true` if the task was dropped before completing, because the
+    /// runtime shut down.
+    pub fn is_cancelled(&self) -> bool {
+        !self.panicked
+    }
+
+    /// Returns `true` if the task panicked.
+    pub fn is_panic(&self) -> bool {
+        self.panicked
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.panicked {
+            f.write_str("task panicked")
+        } else {
+            f.write_str("task was cancelled")
+        }
+    }
+}
+
+impl Error for JoinError {}
+
+impl Queue {
+    fn new() -> std::io::Result<Arc<Queue>> {
+        let fd = syscall!(eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK))?;
+
+        Ok(Arc::new(Queue {
+            jobs: Mutex::new(Jobs {
+                queue: VecDeque::new(),
+                closed: false,
+            }),
+            eventfd: unsafe { File::from_raw_fd(fd) },
+        }))
+    }
+
+    /// Queues a job, dropping it if the runtime shut down.
+    fn push(&self, job: Job) {
+        let mut jobs = self.lock();
+        if jobs.closed {
+            drop(jobs);
+            drop(job);
+            return;
+        }
+
+        let was_empty = jobs.queue.is_empty();
+        jobs.queue.push_back(job);
+        drop(jobs);
+
+        // The dispatcher waits for the `eventfd` once it emptied the queue
+        if was_empty {
+            let one = 1u64;
+            unsafe {
+                libc::write(
+                    self.eventfd.as_raw_fd(),
+                    &one as *const u64 as *const libc::c_void,
+                    8,
+                );
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Jobs> {
+        // The queue stays consistent if a holder of the lock panics
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T> Completion<T> {
+    fn complete(&mut self, output: Result<T, JoinError>) {
+        if let Some(slot) = self.slot.take() {
+            let mut slot = lock(&slot);
+            slot.output = Some(output);
+            let waker = slot.waker.take();
+            drop(slot);
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        // Dropped while unwinding if the task panicked
+        let panicked = thread::panicking();
+        self.complete(Err(JoinError { panicked }));
+    }
+}
+
+impl Drop for Dispatcher {
+    fn drop(&mut self) {
+        let jobs = {
+            let mut jobs = self.queue.lock();
+            jobs.closed = true;
+            std::mem::take(&mut jobs.queue)
+        };
+
+        // The tasks which did not run are cancelled
+        drop(jobs);
+
+        let _ = QUEUE.try_with(|current| current.borrow_mut().take());
+    }
+}
+
+/// Runs the jobs sent to the runtime, until it shuts down.
+async fn dispatch(dispatcher: Dispatcher) {
+    let fd = syscall!(fcntl(
+        dispatcher.queue.eventfd.as_raw_fd(),
+        libc::F_DUPFD_CLOEXEC,
+        0
+    ))
+    .expect("failed to duplicate the runtime queue eventfd");
+    let eventfd = SharedFd::new(fd);
+
+    loop {
+        let jobs = std::mem::take(&mut dispatcher.queue.lock().queue);
+        for job in jobs {
+            job();
+        }
+
+        if driver::ready(&eventfd, libc::POLLIN).await.is_err() {
+            return;
+        }
+
+        // Reset the `eventfd`, which is non-blocking
+        let mut count = 0u64;
+        unsafe {
+            libc::read(
+                eventfd.raw_fd(),
+                &mut count as *mut u64 as *mut libc::c_void,
+                8,
+            );
+        }
+    }
+}
+
+fn lock<T>(slot: &Mutex<Slot<T>>) -> MutexGuard<'_, Slot<T>> {
+    slot.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,332 @@
+use crate::driver::SharedFd;
+use crate::io::{read, write_all_to_fd};
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, IntoRawFd, RawFd};
+
+/// Size of the input buffer, enough for a burst of pasted text.
+const BUF_SIZE: usize = 1024;
+
+/// The terminal of the process, read as a sequence of keys.
+///
+/// In [raw mode](Console::enable_raw_mode), the terminal delivers each key
+/// as it is pressed, instead of buffering lines, and does not echo it:
+/// interactive programs such as editors and REPLs read keys with
+/// [`read_input`](Console::read_input) and draw the screen themselves, with
+/// [`write_all`](Console::write_all). The mode of the terminal is restored
+/// when the console is dropped.
+///
+/// Reads wait for the terminal to become readable with a poll operation,
+/// then read the available data, so that dropping a pending read does not
+/// leave a read blocked in the kernel.
+///
+/// # Examples
+///
+/// Echoing the keys pressed until `Ctrl-C`:
+///
+/// ```no_run
+/// use tokio_uring::io::{Console, Input};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let mut console = Console::open()?;
+///         console.enable_raw_mode()?;
+///
+///         while let Some(input) = console.read_input().await? {
+///             if input == Input::Ctrl('c') {
+///                 break;
+///             }
+///             console.write_all(format!("{:?}\r\n", input).as_bytes()).await?;
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct Console {
+    fd: SharedFd,
+
+    /// Settings to restore, while in raw mode
+    saved: Option<libc::termios>,
+
+    /// Data read, parsed up to `pos`
+    buf: Vec<u8>,
+    pos: usize,
+
+    /// Buffer of the reads
+    chunk: Vec<u8>,
+}
+
+/// A key read from a [`Console`].
+///
+/// Escape sequences are decoded for the common keys. With `Alt`, terminals
+/// send [`Esc`](Input::Esc) followed by the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Input {
+    /// A printable character.
+    Char(char),
+    /// A letter pressed with `Ctrl`, in lower case.
+    Ctrl(char),
+    /// The `Enter` key.
+    Enter,
+    /// The `Tab` key.
+    Tab,
+    /// The `Backspace` key.
+    Backspace,
+    /// The `Escape` key.
+    Esc,
+    /// The `Up` arrow.
+    Up,
+    /// The `Down` arrow.
+    Down,
+    /// The `Left` arrow.
+    Left,
+    /// The `Right` arrow.
+    Right,
+    /// The `Home` key.
+    Home,
+    /// The `End` key.
+    End,
+    /// The `Insert` key.
+    Insert,
+    /// The `Delete` key.
+    Delete,
+    /// The `Page Up` key.
+    PageUp,
+    /// The `Page Down` key.
+    PageDown,
+    /// An escape sequence which is not decoded, or a byte which is not
+    /// valid UTF-8.
+    Unknown(Vec<u8>),
+}
+
+impl Console {
+    /// Opens the controlling terminal of the process, `/dev/tty`.
+    ///
+    /// The terminal is opened even if the standard streams are redirected.
+    pub fn open() -> io::Result<Console> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC | libc::O_NOCTTY)
+            .open("/dev/tty")?;
+
+        Console::from_std(file)
+    }
+
+    /// Adopts an already opened terminal, such as the slave end of a
+    /// pseudoterminal.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `file` is not a terminal.
+    pub fn from_std(file: std::fs::File) -> io::Result<Console> {
+        if unsafe { libc::isatty(file.as_raw_fd()) } != 1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Console {
+            fd: SharedFd::new(file.into_raw_fd()),
+            saved: None,
+            buf: Vec::new(),
+            pos: 0,
+            chunk: Vec::with_capacity(BUF_SIZE),
+        })
+    }
+
+    /// Switches the terminal to raw mode, as with `cfmakeraw(3)`: keys are
+    /// delivered as they are pressed, without echo, and signals such as
+    /// `Ctrl-C` are delivered as input instead.
+    ///
+    /// Output is not processed either: a line feed only moves the cursor
+    /// down, write `"\r\n"` to start a new line.
+    pub fn enable_raw_mode(&mut self) -> io::Result<()> {
+        if self.saved.is_some() {
+            return Ok(());
+        }
+
+        let saved = self.termios()?;
+        let mut raw = saved;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        self.set_termios(&raw)?;
+
+        self.saved = Some(saved);
+        Ok(())
+    }
+
+    /// Restores the settings of the terminal from before
+    /// [`enable_raw_mode`](Console::enable_raw_mode).
+    pub fn disable_raw_mode(&mut self) -> io::Result<()> {
+        if let Some(saved) = self.saved {
+            self.set_termios(&saved)?;
+            self.saved = None;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the terminal is in raw mode.
+    pub fn is_raw_mode(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Returns the size of the terminal, as columns and rows.
+    pub fn size(&self) -> io::Result<(u16, u16)> {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        syscall!(ioctl(self.fd.raw_fd(), libc::TIOCGWINSZ, &mut size))?;
+        Ok((size.ws_col, size.ws_row))
+    }
+
+    /// Reads the next key, waiting for one to be pressed. Returns `None`
+    /// once the terminal hung up.
+    ///
+    /// Keys are only delivered as they are pressed in raw mode. Otherwise,
+    /// they are delivered once the line is entered.
+    pub async fn read_input(&mut self) -> io::Result<Option<Input>> {
+        loop {
+            let buffered = &self.buf[self.pos..];
+            if let Some((input, n)) = parse(buffered) {
+                self.pos += n;
+                return Ok(Some(input));
+            }
+
+            // Keep the start of an incomplete sequence
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+
+            let mut chunk = std::mem::take(&mut self.chunk);
+            chunk.clear();
+            let (res, chunk) = read(&self.fd, true, chunk).await;
+            self.buf.extend_from_slice(&chunk);
+            self.chunk = chunk;
+
+            match res {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                // The slave end of a pseudoterminal whose master was closed
+                Err(e) if e.raw_os_error() == Some(libc::EIO) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Writes all of `data` to the terminal.
+    pub async fn write_all(&self, data: &[u8]) -> io::Result<()> {
+        write_all_to_fd(&self.fd, data.to_vec()).await.0
+    }
+
+    fn termios(&self) -> io::Result<libc::termios> {
+        let mut termios = unsafe { std::mem::zeroed() };
+        syscall!(tcgetattr(self.fd.raw_fd(), &mut termios))?;
+        Ok(termios)
+    }
+
+    fn set_termios(&self, termios: &libc::termios) -> io::Result<()> {
+        syscall!(tcsetattr(self.fd.raw_fd(), libc::TCSANOW, termios))?;
+        Ok(())
+    }
+}
+
+impl AsRawFd for Console {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl fmt::Debug for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Console")
+            .field("fd", &self.fd.raw_fd())
+            .field("raw_mode", &self.is_raw_mode())
+            .finish()
+    }
+}
+
+impl Drop for Console {
+    fn drop(&mut self) {
+        let _ = self.disable_raw_mode();
+    }
+}
+
+/// Decodes the key at the start of `buf`, returning it and its length, or
+/// `None` if `buf` holds no complete key.
+fn parse(buf: &[u8]) -> Option<(Input, usize)> {
+    let input = match *buf.first()? {
+        b'\r' | b'\n' => Input::Enter,
+        b'\t' => Input::Tab,
+        0x7f | 0x08 => Input::Backspace,
+        0x1b => return parse_escape(buf),
+        b @ 0x01..=0x1a => Input::Ctrl((b'a' + b - 1) as char),
+        b if b < 0x80 => Input::Char(b as char),
+        b => {
+            let len = match b {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Some((Input::Unknown(vec![b]), 1)),
+            };
+            if buf.len() < len {
+                return None;
+            }
+
+            return match std::str::from_utf8(&buf[..len]) {
+                Ok(s) => Some((Input::Char(s.chars().next().unwrap()), len)),
+                Err(_) => Some((Input::Unknown(vec![b]), 1)),
+            };
+        }
+    };
+
+    Some((input, 1))
+}
+
+/// Decodes the escape sequence at the start of `buf`.
+fn parse_escape(buf: &[u8]) -> Option<(Input, usize)> {
+    // Sequences arrive in a single read, a lone escape is the key itself
+    let kind = match buf.get(1) {
+        None => return Some((Input::Esc, 1)),
+        Some(&kind) => kind,
+    };
+
+    match kind {
+        // SS3, sent for arrows and `Home`/`End` in application mode
+        b'O' => {
+            let input = match *buf.get(2)? {
+                b'A' => Input::Up,
+                b'B' => Input::Down,
+                b'C' => Input::Right,
+                b'D' => Input::Left,
+                b'H' => Input::Home,
+                b'F' => Input::End,
+                _ => Input::Unknown(buf[..3].to_vec()),
+            };
+            Some((input, 3))
+        }
+        // CSI: parameter and intermediate bytes, then a final byte
+        b'[' => {
+            let end = 2 + buf[2..].iter().position(|b| (0x40..=0x7e).contains(b))?;
+            let seq = &buf[..=end];
+
+            let input = match &seq[2..] {
+                b"A" => Input::Up,
+                b"B" => Input::Down,
+                b"C" => Input::Right,
+                b"D" => Input::Left,
+                b"H" | b"1~" | b"7~" => Input::Home,
+                b"F" | b"4~" | b"8~" => Input::End,
+                b"2~" => Input::Insert,
+                b"3~" => Input::Delete,
+                b"5~" => Input::PageUp,
+                b"6~" => Input::PageDown,
+                _ => Input::Unknown(seq.to_vec()),
+            };
+            Some((input, seq.len()))
+        }
+        _ => Some((Input::Esc, 1)),
+    }
+}
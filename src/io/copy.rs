@@ -0,0 +1,259 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{self, SharedFd};
+use crate::fs::File;
+use crate::io::DuplexStream;
+use crate::net::{TcpStream, UnixStream, VsockStream};
+use crate::pipe::{PipeRead, PipeWrite};
+
+use std::future::Future;
+use std::io;
+
+use io_uring::opcode;
+
+/// Size of the buffer of [`copy`], and of its splices.
+const COPY_BUF_SIZE: usize = 64 * 1024;
+
+/// A reader of owned buffers, read by [`copy`].
+///
+/// Implemented by the files, stream sockets, pipes and in-memory streams of
+/// the crate. Files are read at their position, which advances.
+pub trait OwnedRead: sealed::Sealed {
+    /// Reads into `buf`, returning it with the number of bytes read, 0 at
+    /// the end of the data.
+    fn read<T: IoBufMut>(&self, buf: T) -> impl Future<Output = crate::BufResult<usize, T>>;
+}
+
+/// A writer of owned buffers, written by [`copy`].
+///
+/// Implemented by the files, stream sockets, pipes and in-memory streams of
+/// the crate. Files are written at their position, which advances.
+pub trait OwnedWrite: sealed::Sealed {
+    /// Writes from `buf`, returning it with the number of bytes written,
+    /// which may be fewer than its length.
+    fn write<T: IoBuf>(&self, buf: T) -> impl Future<Output = crate::BufResult<usize, T>>;
+}
+
+/// Copies the data of `reader` to `writer` until the end of `reader`,
+/// returning the number of bytes copied, as [`std::io::copy`] does.
+///
+/// The data is read into a buffer of 64 KiB, allocated once, and each read
+/// is written entirely before reading more. Reads and writes interrupted by
+/// a signal are retried.
+///
+/// When one end is a pipe and the other a regular file, the data is spliced
+/// instead, without being copied to user space, if the kernel supports it.
+/// Note that a splice from a pipe waits for its data on a kernel worker
+/// thread.
+///
+/// # Errors
+///
+/// Fails with the first error of a read or a write, or with
+/// [`WriteZero`](io::ErrorKind::WriteZero) if a write returns 0. The data
+/// read and not written yet is lost.
+///
+/// # Examples
+///
+/// Sending a file to a client:
+///
+/// ```no_run
+/// use tokio_uring::fs::File;
+/// use tokio_uring::net::TcpListener;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///         let (stream, _) = listener.accept().await?;
+///
+///         let file = File::open("index.html").await?;
+///         let sent = tokio_uring::io::copy(&file, &stream).await?;
+///         println!("sent {} bytes", sent);
+///
+///         file.close().await
+///     })
+/// }
+/// ```
+pub async fn copy<R, W>(reader: &R, writer: &W) -> io::Result<u64>
+where
+    R: OwnedRead,
+    W: OwnedWrite,
+{
+    if let (Some(src), Some(dst)) = (reader.shared_fd(), writer.shared_fd()) {
+        if can_splice(src, dst) {
+            match splice_all(src, dst).await {
+                // The files do not support splicing, before copying anything
+                Err((e, 0)) if is_unsupported(&e) => {}
+                Err((e, _)) => return Err(e),
+                Ok(copied) => return Ok(copied),
+            }
+        }
+    }
+
+    let mut buf = Vec::with_capacity(COPY_BUF_SIZE);
+    let mut copied = 0;
+
+    loop {
+        buf.clear();
+        let (res, b) = reader.read(buf).await;
+        buf = b;
+        match res {
+            Ok(0) => return Ok(copied),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+
+        let len = buf.len();
+        let mut written = 0;
+        while written < len {
+            let (res, slice) = writer.write(buf.slice(written..len)).await;
+            buf = slice.into_inner();
+            match res {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        copied += len as u64;
+    }
+}
+
+/// Whether the data of `src` can be spliced to `dst`: one is a pipe, and the
+/// other a regular file.
+fn can_splice(src: &SharedFd, dst: &SharedFd) -> bool {
+    if !driver::supports(opcode::Splice::CODE) {
+        return false;
+    }
+
+    matches!(
+        (file_type(src), file_type(dst)),
+        (Some(libc::S_IFIFO), Some(libc::S_IFREG)) | (Some(libc::S_IFREG), Some(libc::S_IFIFO))
+    )
+}
+
+/// Returns the `S_IFMT` bits of the mode of `fd`.
+fn file_type(fd: &SharedFd) -> Option<libc::mode_t> {
+    let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+    syscall!(fstat(fd.raw_fd(), &mut stat)).ok()?;
+    Some(stat.st_mode & libc::S_IFMT)
+}
+
+/// Splices `src` to `dst` until the end of `src`, returning the error along
+/// with the number of bytes copied.
+async fn splice_all(src: &SharedFd, dst: &SharedFd) -> Result<u64, (io::Error, u64)> {
+    let mut copied = 0;
+
+    loop {
+        let n = driver::splice(src, -1, dst, -1, COPY_BUF_SIZE as u32)
+            .await
+            .map_err(|e| (e, copied))?;
+        if n == 0 {
+            return Ok(copied);
+        }
+        copied += n as u64;
+    }
+}
+
+/// Whether a splice failed as the files do not support it.
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EINVAL) | Some(libc::EOPNOTSUPP)
+    )
+}
+
+macro_rules! impl_owned_io {
+    ($($ty:ty),*) => {
+        $(
+            impl OwnedRead for $ty {
+                fn read<T: IoBufMut>(
+                    &self,
+                    buf: T,
+                ) -> impl Future<Output = crate::BufResult<usize, T>> {
+                    <$ty>::read(self, buf)
+                }
+            }
+
+            impl OwnedWrite for $ty {
+                fn write<T: IoBuf>(
+                    &self,
+                    buf: T,
+                ) -> impl Future<Output = crate::BufResult<usize, T>> {
+                    <$ty>::write(self, buf)
+                }
+            }
+        )*
+    };
+}
+
+impl_owned_io!(File, TcpStream, UnixStream, VsockStream, DuplexStream);
+
+impl OwnedRead for PipeRead {
+    fn read<T: IoBufMut>(&self, buf: T) -> impl Future<Output = crate::BufResult<usize, T>> {
+        PipeRead::read(self, buf)
+    }
+}
+
+impl OwnedWrite for PipeWrite {
+    fn write<T: IoBuf>(&self, buf: T) -> impl Future<Output = crate::BufResult<usize, T>> {
+        PipeWrite::write(self, buf)
+    }
+}
+
+// The file descriptor of a reader or writer, for the crate only
+#[allow(private_interfaces)]
+mod sealed {
+    use crate::driver::SharedFd;
+    use crate::fs::File;
+    use crate::io::DuplexStream;
+    use crate::net::{TcpStream, UnixStream, VsockStream};
+    use crate::pipe::{PipeRead, PipeWrite};
+
+    pub trait Sealed {
+        /// Returns the file descriptor to splice, if any.
+        fn shared_fd(&self) -> Option<&SharedFd>;
+    }
+
+    impl Sealed for File {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            Some(&self.fd)
+        }
+    }
+
+    impl Sealed for TcpStream {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            Some(self.inner.shared_fd())
+        }
+    }
+
+    impl Sealed for UnixStream {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            Some(self.inner.shared_fd())
+        }
+    }
+
+    impl Sealed for VsockStream {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            Some(self.inner.shared_fd())
+        }
+    }
+
+    impl Sealed for PipeRead {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            Some(&self.fd)
+        }
+    }
+
+    impl Sealed for PipeWrite {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            Some(&self.fd)
+        }
+    }
+
+    // In memory, without a file descriptor
+    impl Sealed for DuplexStream {
+        fn shared_fd(&self) -> Option<&SharedFd> {
+            None
+        }
+    }
+}
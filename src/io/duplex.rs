@@ -0,0 +1,208 @@
+use crate::buf::{IoBuf, IoBufMut};
+use crate::future::poll_fn;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+use std::task::{Poll, Waker};
+
+/// Creates a pair of connected in-memory streams, each buffering up to
+/// `capacity` bytes written to the other one.
+///
+/// The streams read and write with the methods of the sockets of the crate,
+/// without submitting operations, so that protocol code can be tested
+/// against a peer without setting up sockets. Like a socket, a write waits
+/// for room in the buffer of the peer, and writes what fits; a read waits
+/// for data, and returns what is buffered.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::io::duplex;
+///
+/// tokio_uring::start(async {
+///     let (client, server) = duplex(64);
+///
+///     let (res, _) = client.write(b"ping".to_vec()).await;
+///     assert_eq!(res.unwrap(), 4);
+///
+///     let (res, buf) = server.read(vec![0; 16]).await;
+///     assert_eq!(&buf[..res.unwrap()], b"ping");
+/// });
+/// ```
+pub fn duplex(capacity: usize) -> (DuplexStream, DuplexStream) {
+    assert!(capacity > 0, "capacity must be non-zero");
+
+    let a_to_b = Rc::new(RefCell::new(Pipe::new(capacity)));
+    let b_to_a = Rc::new(RefCell::new(Pipe::new(capacity)));
+
+    let a = DuplexStream {
+        rx: b_to_a.clone(),
+        tx: a_to_b.clone(),
+    };
+    let b = DuplexStream {
+        rx: a_to_b,
+        tx: b_to_a,
+    };
+    (a, b)
+}
+
+/// One end of an in-memory stream, created by [`duplex`].
+///
+/// Dropping a stream ends the data of its peer once read, and makes the
+/// writes of its peer fail with `EPIPE`.
+pub struct DuplexStream {
+    /// Data written by the peer
+    rx: Rc<RefCell<Pipe>>,
+
+    /// Data written to the peer
+    tx: Rc<RefCell<Pipe>>,
+}
+
+struct Pipe {
+    buf: VecDeque<u8>,
+    capacity: usize,
+
+    /// Whether the writing end was dropped
+    write_closed: bool,
+
+    /// Whether the reading end was dropped
+    read_closed: bool,
+
+    /// Task waiting for data
+    reader: Option<Waker>,
+
+    /// Task waiting for room
+    writer: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(capacity: usize) -> Pipe {
+        Pipe {
+            buf: VecDeque::with_capacity(capacity),
+            capacity,
+            write_closed: false,
+            read_closed: false,
+            reader: None,
+            writer: None,
+        }
+    }
+}
+
+impl DuplexStream {
+    /// Reads some data written by the peer into the buffer, returning the
+    /// original buffer and quantity of data read.
+    ///
+    /// Waits for data if none is buffered, and returns 0 once the peer was
+    /// dropped and its data read.
+    pub async fn read<T: IoBufMut>(&self, mut buf: T) -> crate::BufResult<usize, T> {
+        let len = buf.bytes_total();
+
+        let n = poll_fn(|cx| {
+            let mut pipe = self.rx.borrow_mut();
+
+            if len > 0 && pipe.buf.is_empty() && !pipe.write_closed {
+                pipe.reader = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+
+            let n = len.min(pipe.buf.len());
+            let ptr = buf.stable_mut_ptr();
+            for (i, byte) in pipe.buf.drain(..n).enumerate() {
+                // Safety: `i` is below the total size of the buffer
+                unsafe { ptr.add(i).write(byte) };
+            }
+
+            if let Some(waker) = pipe.writer.take() {
+                waker.wake();
+            }
+            Poll::Ready(n)
+        })
+        .await;
+
+        // Safety: the first `n` bytes were written above
+        unsafe { buf.set_init(n) };
+        (Ok(n), buf)
+    }
+
+    /// Reads until the peer is dropped, appending to `buf`, and returns the
+    /// buffer with the number of bytes read.
+    ///
+    /// The bytes are read into the spare capacity of `buf`, whose capacity
+    /// doubles each time it fills up. On error, `buf` holds the data read
+    /// until then.
+    pub async fn read_to_end(&self, buf: Vec<u8>) -> crate::BufResult<usize, Vec<u8>> {
+        crate::io::read_to_end(buf, move |buf| self.read(buf)).await
+    }
+
+    /// Writes some data of the buffer to the peer, returning the original
+    /// buffer and quantity of data written.
+    ///
+    /// Waits for the peer to read data if its buffer is full.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`BrokenPipe`](io::ErrorKind::BrokenPipe) if the peer was
+    /// dropped.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let res = poll_fn(|cx| {
+            let mut pipe = self.tx.borrow_mut();
+
+            if pipe.read_closed {
+                return Poll::Ready(Err(io::Error::from_raw_os_error(libc::EPIPE)));
+            }
+
+            let data = crate::buf::deref(&buf);
+            let room = pipe.capacity - pipe.buf.len();
+            if !data.is_empty() && room == 0 {
+                pipe.writer = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+
+            let n = data.len().min(room);
+            pipe.buf.extend(&data[..n]);
+
+            if let Some(waker) = pipe.reader.take() {
+                waker.wake();
+            }
+            Poll::Ready(Ok(n))
+        })
+        .await;
+
+        (res, buf)
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        let mut rx = self.rx.borrow_mut();
+        rx.read_closed = true;
+        if let Some(waker) = rx.writer.take() {
+            waker.wake();
+        }
+
+        let mut tx = self.tx.borrow_mut();
+        tx.write_closed = true;
+        if let Some(waker) = tx.reader.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl fmt::Debug for DuplexStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DuplexStream")
+            .field("readable", &self.rx.borrow().buf.len())
+            .field("writable", &{
+                let tx = self.tx.borrow();
+                tx.capacity - tx.buf.len()
+            })
+            .finish()
+    }
+}
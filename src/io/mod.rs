@@ -0,0 +1,216 @@
+//! Standard input, output and error of the process, its terminal, copying
+//! between readers and writers, and in-memory streams.
+//!
+//! The handles returned by [`stdin`], [`stdout`] and [`stderr`] read and
+//! write the standard streams with `io-uring` operations, so that command
+//! line tools do not block the runtime thread on them. [`Stdin`] buffers its
+//! reads, to read lines, and [`Stdout`] buffers its writes until a line is
+//! complete. [`Stderr`] is unbuffered.
+//!
+//! Each handle wraps a duplicate of the file descriptor of its stream,
+//! buffering independently of the other handles and of the standard library
+//! handles. Create a single handle for each stream, and pass it around.
+//!
+//! # Terminals
+//!
+//! A read from a terminal only completes once the user enters a line, and
+//! would hold a kernel worker thread meanwhile. When standard input is a
+//! terminal, [`Stdin`] first waits for it to become readable, with a poll
+//! operation which can be cancelled, then reads the available data, which
+//! does not block. Writes to a terminal complete right away, unless the
+//! output is suspended, and are submitted as is.
+//!
+//! Interactive programs read the keys pressed on the terminal with a
+//! [`Console`], which switches it to raw mode.
+//!
+//! # Copying
+//!
+//! [`copy`] copies the data of a reader to a writer until its end, such as a
+//! file to a socket. The files, stream sockets, pipes and in-memory streams
+//! of the crate are readers and writers, see [`OwnedRead`] and
+//! [`OwnedWrite`]. Data is spliced between pipes and files, without copying
+//! it to user space.
+//!
+//! # In-memory streams
+//!
+//! The streams created by [`duplex`] read and write like sockets, buffering
+//! the data in memory instead, to test protocol code without a peer process
+//! or sockets.
+//!
+//! # Examples
+//!
+//! Echoing the lines of the standard input in upper case:
+//!
+//! ```no_run
+//! use tokio_uring::io::{stdin, stdout};
+//!
+//! fn main() -> std::io::Result<()> {
+//!     tokio_uring::start(async {
+//!         let mut stdin = stdin()?;
+//!         let mut stdout = stdout()?;
+//!
+//!         let mut line = String::new();
+//!         while stdin.read_line(&mut line).await? > 0 {
+//!             stdout.write_all(line.to_uppercase().as_bytes()).await?;
+//!             line.clear();
+//!         }
+//!
+//!         stdout.flush().await
+//!     })
+//! }
+//! ```
+
+use crate::buf::{IoBuf, IoBufMut, Slice};
+use crate::driver::{self, Op, SharedFd};
+
+use std::future::Future;
+use std::io;
+
+mod console;
+pub use console::{Console, Input};
+
+mod copy;
+pub use copy::{copy, OwnedRead, OwnedWrite};
+
+mod duplex;
+pub use duplex::{duplex, DuplexStream};
+
+mod stdio;
+pub use stdio::{stderr, stdin, stdout, Stderr, Stdin, Stdout};
+
+/// Offset of the reads and writes, using and updating the file position of
+/// the stream, which matters when it is redirected to a regular file.
+const CURRENT_POSITION: u64 = u64::MAX;
+
+/// Reads from `fd`, which may be in non-blocking mode if other processes
+/// share it. Terminals are polled before reading.
+async fn read<T: IoBufMut>(fd: &SharedFd, tty: bool, mut buf: T) -> crate::BufResult<usize, T> {
+    let mut poll = tty;
+
+    loop {
+        if poll {
+            if let Err(e) = driver::ready(fd, libc::POLLIN).await {
+                return (Err(e), buf);
+            }
+        }
+
+        if let Err(e) = driver::room(fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::read_at(fd, buf, CURRENT_POSITION).unwrap();
+        let (res, b) = op.read().await;
+
+        match res {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                buf = b;
+                poll = true;
+            }
+            res => return (res, b),
+        }
+    }
+}
+
+/// Capacity reserved by [`read_to_end`] for the first read into an empty
+/// buffer.
+const MIN_READ_TO_END: usize = 8 * 1024;
+
+/// Reads with `read` until it reads 0 bytes, appending to `buf`, and returns
+/// the number of bytes read. The spare capacity of `buf` is read into, and
+/// doubled once full.
+pub(crate) async fn read_to_end<F, Fut>(
+    mut buf: Vec<u8>,
+    mut read: F,
+) -> crate::BufResult<usize, Vec<u8>>
+where
+    F: FnMut(Slice<Vec<u8>>) -> Fut,
+    Fut: Future<Output = crate::BufResult<usize, Slice<Vec<u8>>>>,
+{
+    let start = buf.len();
+
+    loop {
+        if buf.len() == buf.capacity() {
+            buf.reserve(buf.capacity().max(MIN_READ_TO_END));
+        }
+
+        let len = buf.len();
+        let (res, slice) = read(buf.slice(len..)).await;
+        buf = slice.into_inner();
+
+        match res {
+            Ok(0) => return (Ok(buf.len() - start), buf),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return (Err(e), buf),
+        }
+    }
+}
+
+/// Writes all of `buf` with `write`, called with the part of `buf` left to
+/// write and the number of bytes written so far. Writes interrupted by a
+/// signal are retried, and a write of 0 bytes fails with
+/// [`WriteZero`](io::ErrorKind::WriteZero).
+pub(crate) async fn write_all<T, F, Fut>(mut buf: T, mut write: F) -> crate::BufResult<(), T>
+where
+    T: IoBuf,
+    F: FnMut(Slice<T>, usize) -> Fut,
+    Fut: Future<Output = crate::BufResult<usize, Slice<T>>>,
+{
+    let len = buf.bytes_init();
+    let mut written = 0;
+
+    while written < len {
+        let (res, slice) = write(buf.slice(written..len), written).await;
+        buf = slice.into_inner();
+
+        match res {
+            Ok(0) => {
+                let err = io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer");
+                return (Err(err), buf);
+            }
+            Ok(n) => written += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return (Err(e), buf),
+        }
+    }
+
+    (Ok(()), buf)
+}
+
+/// Writes all of `buf` to `fd`, returning it emptied on success. On error,
+/// the data written until then is removed from `buf`.
+async fn write_all_to_fd(fd: &SharedFd, buf: Vec<u8>) -> crate::BufResult<(), Vec<u8>> {
+    let mut done = 0;
+    let (res, mut buf) = write_all(buf, |slice, written| {
+        done = written;
+        write(fd, slice)
+    })
+    .await;
+
+    let written = if res.is_ok() { buf.len() } else { done };
+    buf.drain(..written);
+    (res, buf)
+}
+
+/// Writes to `fd`, which may be in non-blocking mode if other processes
+/// share it.
+async fn write<T: IoBuf>(fd: &SharedFd, mut buf: T) -> crate::BufResult<usize, T> {
+    loop {
+        if let Err(e) = driver::room(fd).await {
+            return (Err(e), buf);
+        }
+
+        let op = Op::write_at(fd, buf, CURRENT_POSITION).unwrap();
+        let (res, b) = op.write().await;
+        buf = b;
+
+        match res {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if let Err(e) = driver::ready(fd, libc::POLLOUT).await {
+                    return (Err(e), buf);
+                }
+            }
+            res => return (res, buf),
+        }
+    }
+}
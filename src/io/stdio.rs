@@ -0,0 +1,265 @@
+use crate::buf::IoBufMut;
+use crate::driver::SharedFd;
+use crate::io::{read, write_all_to_fd};
+
+use std::fmt;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// Size of the buffers of [`Stdin`] and [`Stdout`].
+const BUF_SIZE: usize = 8 * 1024;
+
+/// Returns a handle to the standard input of the process.
+///
+/// # Errors
+///
+/// Fails if the file descriptor of the standard input cannot be duplicated,
+/// for example because it is closed.
+pub fn stdin() -> io::Result<Stdin> {
+    let fd = dup(libc::STDIN_FILENO)?;
+    let tty = is_tty(&fd);
+
+    Ok(Stdin {
+        fd,
+        tty,
+        buf: Vec::with_capacity(BUF_SIZE),
+        pos: 0,
+    })
+}
+
+/// Returns a handle to the standard output of the process.
+///
+/// # Errors
+///
+/// Fails if the file descriptor of the standard output cannot be
+/// duplicated, for example because it is closed.
+pub fn stdout() -> io::Result<Stdout> {
+    Ok(Stdout {
+        fd: dup(libc::STDOUT_FILENO)?,
+        buf: Vec::with_capacity(BUF_SIZE),
+    })
+}
+
+/// Returns a handle to the standard error of the process.
+///
+/// # Errors
+///
+/// Fails if the file descriptor of the standard error cannot be duplicated,
+/// for example because it is closed.
+pub fn stderr() -> io::Result<Stderr> {
+    Ok(Stderr {
+        fd: dup(libc::STDERR_FILENO)?,
+    })
+}
+
+/// A buffered handle to the standard input of the process.
+///
+/// Created by [`stdin`].
+pub struct Stdin {
+    fd: SharedFd,
+
+    /// Whether the standard input is a terminal
+    tty: bool,
+
+    /// Data read, consumed up to `pos`
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Stdin {
+    /// Read some data into the buffer, returning the original buffer and
+    /// quantity of data read. A read of 0 bytes means the end of the input.
+    ///
+    /// The data buffered by a previous [`read_line`](Stdin::read_line) is
+    /// returned first.
+    pub async fn read<T: IoBufMut>(&mut self, mut buf: T) -> crate::BufResult<usize, T> {
+        let buffered = &self.buf[self.pos..];
+        if buffered.is_empty() {
+            return read(&self.fd, self.tty, buf).await;
+        }
+
+        let n = buffered.len().min(buf.bytes_total());
+        unsafe {
+            std::ptr::copy_nonoverlapping(buffered.as_ptr(), buf.stable_mut_ptr(), n);
+            buf.set_init(n);
+        }
+        self.pos += n;
+
+        (Ok(n), buf)
+    }
+
+    /// Reads a line, appending it to `line`, and returns the number of bytes
+    /// read.
+    ///
+    /// The line includes its terminating newline, except for the last line
+    /// of an input which does not end with one. Returns 0 at the end of the
+    /// input.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the line is not valid
+    /// UTF-8, leaving `line` unchanged. The line is consumed nonetheless.
+    pub async fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+        let mut bytes = Vec::new();
+
+        loop {
+            if self.pos == self.buf.len() && !self.fill().await? {
+                break;
+            }
+
+            let buffered = &self.buf[self.pos..];
+            match buffered.iter().position(|&b| b == b'\n') {
+                Some(i) => {
+                    bytes.extend_from_slice(&buffered[..=i]);
+                    self.pos += i + 1;
+                    break;
+                }
+                None => {
+                    bytes.extend_from_slice(buffered);
+                    self.pos = self.buf.len();
+                }
+            }
+        }
+
+        let s = String::from_utf8(bytes)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "line is not valid UTF-8"))?;
+        line.push_str(&s);
+        Ok(s.len())
+    }
+
+    /// Reads more data into the buffer, once it was consumed. Returns
+    /// `false` at the end of the input.
+    async fn fill(&mut self) -> io::Result<bool> {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        self.pos = 0;
+
+        let (res, buf) = read(&self.fd, self.tty, buf).await;
+        self.buf = buf;
+        Ok(res? > 0)
+    }
+}
+
+impl AsRawFd for Stdin {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl fmt::Debug for Stdin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stdin")
+            .field("fd", &self.fd.raw_fd())
+            .field("tty", &self.tty)
+            .field("buffered", &(self.buf.len() - self.pos))
+            .finish()
+    }
+}
+
+/// A line buffered handle to the standard output of the process.
+///
+/// Created by [`stdout`]. The data written is buffered until a newline is
+/// written, or the buffer fills up. The data left in the buffer is written
+/// when the handle is dropped, blocking the thread: call
+/// [`flush`](Stdout::flush) before dropping the handle to avoid it.
+pub struct Stdout {
+    fd: SharedFd,
+
+    /// Data written, not flushed yet
+    buf: Vec<u8>,
+}
+
+impl Stdout {
+    /// Writes all of `data`, flushing the buffer if `data` completes a line.
+    pub async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        self.buf.extend_from_slice(data);
+
+        if data.contains(&b'\n') || self.buf.len() >= BUF_SIZE {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the buffered data.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+
+        let (res, buf) = write_all_to_fd(&self.fd, std::mem::take(&mut self.buf)).await;
+        self.buf = buf;
+        res
+    }
+}
+
+impl AsRawFd for Stdout {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl fmt::Debug for Stdout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stdout")
+            .field("fd", &self.fd.raw_fd())
+            .field("buffered", &self.buf.len())
+            .finish()
+    }
+}
+
+impl Drop for Stdout {
+    fn drop(&mut self) {
+        // Best effort, as the standard library does at exit
+        let mut data = &self.buf[..];
+        while !data.is_empty() {
+            let res = syscall!(write(
+                self.fd.raw_fd(),
+                data.as_ptr() as *const libc::c_void,
+                data.len()
+            ));
+            match res {
+                Ok(n) if n > 0 => data = &data[n as usize..],
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                _ => break,
+            }
+        }
+    }
+}
+
+/// An unbuffered handle to the standard error of the process.
+///
+/// Created by [`stderr`].
+pub struct Stderr {
+    fd: SharedFd,
+}
+
+impl Stderr {
+    /// Writes all of `data`.
+    pub async fn write_all(&self, data: &[u8]) -> io::Result<()> {
+        write_all_to_fd(&self.fd, data.to_vec()).await.0
+    }
+}
+
+impl AsRawFd for Stderr {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl fmt::Debug for Stderr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stderr")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
+
+fn dup(fd: RawFd) -> io::Result<SharedFd> {
+    let fd = syscall!(fcntl(fd, libc::F_DUPFD_CLOEXEC, 0))?;
+    Ok(SharedFd::new(fd))
+}
+
+fn is_tty(fd: &SharedFd) -> bool {
+    unsafe { libc::isatty(fd.raw_fd()) == 1 }
+}
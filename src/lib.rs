@@ -46,6 +46,22 @@
 //! For example, in the above example, reading from a `File` requires passing
 //! ownership of the buffer.
 //!
+//! # Cancellation
+//!
+//! Operation futures can be dropped at any point, for example by the branch of
+//! a `tokio::select!` that lost. The kernel may still be accessing the buffer
+//! of a dropped operation, so the runtime holds the buffer until the operation
+//! completes, and drops it then, or passes it to the recycler set with
+//! [`buf::set_orphan_recycler`]. The buffer is never freed while in use by the
+//! kernel.
+//!
+//! To get the buffer back instead of dropping it, cancel the operation
+//! explicitly, e.g. with [`ReadAt::cancel`]. The cancellation waits for the
+//! kernel to release the buffer, and returns the result of the operation if it
+//! completed first, so data already read is not lost.
+//!
+//! [`ReadAt::cancel`]: crate::fs::ReadAt::cancel
+//!
 //! # Closing resources
 //!
 //! With `io-uring`, closing a resource (e.g. a file) is an asynchronous
@@ -55,6 +71,21 @@
 //! will happen in the background. There is no guarantee as to **when** the
 //! implicit close-on-drop operation happens, so it is recommended to explicitly
 //! call `close()`.
+//!
+//! # Forking
+//!
+//! A process created with `fork(2)` shares the rings of its parent, and
+//! cannot use the runtimes it inherits: the kernel processes the operations
+//! of a ring in the context of the process which created it. Operations
+//! submitted from the child process to an inherited runtime fail with an
+//! error saying so, and do not reach the ring.
+//!
+//! A process which forks, for example to daemonize, must do so before
+//! starting its runtimes, or once they have exited, and start new runtimes
+//! in the child process. Tokio runtimes do not survive forking either. To
+//! run another program, spawn it with [`std::process::Command`], which
+//! executes it right after forking, and wait for it with
+//! [`process::Child`].
 
 #![warn(missing_docs)]
 
@@ -71,14 +102,52 @@ macro_rules! syscall {
 
 #[macro_use]
 mod future;
+mod builder;
 mod driver;
+mod dump;
+mod features;
+mod handle;
+mod op_kind;
 mod runtime;
 
 pub mod buf;
+pub mod compat;
+pub mod device;
+pub mod diagnostics;
+pub mod fd;
 pub mod fs;
+pub mod io;
 pub mod net;
+pub mod personality;
+pub mod pipe;
+pub mod process;
+pub mod proxy;
+pub mod sync;
+pub mod task;
+pub mod time;
+
+#[cfg(feature = "bench-internals")]
+#[doc(hidden)]
+pub mod bench_internals;
 
-pub use runtime::spawn;
+#[cfg(feature = "test-util")]
+pub mod crash;
+
+#[cfg(feature = "test-util")]
+pub mod fault;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "test-util")]
+pub mod record;
+
+pub use builder::{builder, Backpressure, Builder, SlowOp, WaitStrategy};
+pub use dump::{debug_dump, DriverDump};
+pub use features::Features;
+pub use handle::{Handle, JoinError, JoinHandle};
+pub use op_kind::OpKind;
+pub use runtime::{features, shutdown_now, spawn};
 
 use std::future::Future;
 
@@ -95,6 +164,17 @@ use std::future::Future;
 /// executed on the current thread. To add concurrency, spawn multiple threads,
 /// each with a `tokio-uring` runtime.
 ///
+/// To configure the runtime, for example to pin its thread to a CPU, see
+/// [`Builder`].
+///
+/// # Panics
+///
+/// Panics if the runtime cannot be created, or if called from within a
+/// `tokio-uring` runtime or a Tokio runtime, its blocking threads included:
+/// blocking on the future would block a thread of the outer runtime. To run
+/// a future from a task, [`spawn`] it instead, or start the runtime on a
+/// thread of its own.
+///
 /// # Examples
 ///
 /// Basic usage
@@ -140,7 +220,10 @@ use std::future::Future;
 /// }
 /// ```
 pub fn start<F: Future>(future: F) -> F::Output {
-    let mut rt = runtime::Runtime::new().unwrap();
+    let mut rt = match runtime::Runtime::new(&Builder::new()) {
+        Ok(rt) => rt,
+        Err(e) => panic!("failed to start the `tokio-uring` runtime: {}", e),
+    };
     rt.block_on(future)
 }
 
@@ -0,0 +1,213 @@
+//! Runtime metrics.
+//!
+//! This module is only available with the `metrics` feature; the default
+//! build does not pay for the timestamps.
+//!
+//! The driver records, for each operation, the time from pushing its SQE onto
+//! the submission queue to processing its CQE. This is the latency of the
+//! kernel and the device, plus the time the completion waited in the
+//! completion queue for the runtime thread. The time from processing the CQE
+//! to the task resuming is the scheduling latency: when the latency measured
+//! by a task is much larger than the one recorded here, the runtime thread is
+//! busy running other tasks.
+//!
+//! Latencies are recorded per kind of operation, see [`OpKind`], into
+//! [`Histogram`]s.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_uring::fs::File;
+//!
+//! tokio_uring::start(async {
+//!     let file = File::open("hello.txt").await.unwrap();
+//!     for _ in 0..1000 {
+//!         let (res, _) = file.read_at(vec![0; 4096], 0).await;
+//!         res.unwrap();
+//!     }
+//!
+//!     for (kind, latency) in tokio_uring::metrics::op_latencies() {
+//!         println!(
+//!             "{}: {} ops, p50 {:?}, p99 {:?}",
+//!             kind,
+//!             latency.count(),
+//!             latency.percentile(0.5),
+//!             latency.percentile(0.99)
+//!         );
+//!     }
+//! });
+//! ```
+
+use crate::driver::metrics;
+use crate::OpKind;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+/// Number of buckets, one per power of two of nanoseconds.
+const BUCKETS: usize = 64;
+
+/// A histogram of latencies, with buckets of exponentially growing width.
+///
+/// The bucket `i` counts the latencies between `2^i` and `2^(i+1)`
+/// nanoseconds, so that percentiles are accurate to a factor of two, while
+/// [`min`](Histogram::min), [`max`](Histogram::max) and
+/// [`mean`](Histogram::mean) are exact.
+#[derive(Clone)]
+pub struct Histogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl Histogram {
+    pub(crate) fn new() -> Histogram {
+        Histogram {
+            buckets: [0; BUCKETS],
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+
+        self.buckets[bucket(nanos)] += 1;
+        self.count += 1;
+        self.sum += u128::from(nanos);
+        self.min = self.min.min(nanos);
+        self.max = self.max.max(nanos);
+    }
+
+    /// Returns the number of latencies recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the smallest latency recorded, zero if none was.
+    pub fn min(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos(self.min)
+        }
+    }
+
+    /// Returns the largest latency recorded.
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    /// Returns the mean of the latencies recorded, zero if none was.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.sum / u128::from(self.count)) as u64)
+        }
+    }
+
+    /// Returns the latency below which the fraction `q` of the latencies
+    /// fall, such as `0.99` for the 99th percentile.
+    ///
+    /// The latency is the upper bound of the bucket it falls in, capped by
+    /// [`max`](Histogram::max). Returns zero if no latency was recorded.
+    pub fn percentile(&self, q: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Duration::from_nanos(upper_bound(i).min(self.max));
+            }
+        }
+
+        self.max()
+    }
+
+    /// Returns the non-empty buckets, as the upper bound of the latencies
+    /// they count, and the count.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, &n)| n > 0)
+            .map(|(i, &n)| (Duration::from_nanos(upper_bound(i)), n))
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("mean", &self.mean())
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+/// Returns the latency histograms of the operations completed by the current
+/// runtime, by kind, in increasing order of opcode.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn op_latencies() -> Vec<(OpKind, Histogram)> {
+    metrics::with_latencies(|latencies| latencies.histograms())
+}
+
+/// Returns the latency histogram of the operations of the given kind, or
+/// opcode, completed by the current runtime, if any completed.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn op_latency(kind: impl Into<OpKind>) -> Option<Histogram> {
+    let opcode = kind.into().opcode();
+    metrics::with_latencies(|latencies| latencies.histogram(opcode).cloned())
+}
+
+/// Clears the latencies recorded by the current runtime.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn reset_op_latencies() {
+    metrics::with_latencies(|latencies| latencies.reset())
+}
+
+/// Returns the number of times the current runtime deferred a submission,
+/// after the kernel rejected it with `EBUSY` more times than
+/// [`Builder::busy_retries`](crate::Builder::busy_retries) allows. A growing
+/// count means the runtime completes operations slower than it submits them.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn busy_deferrals() -> u64 {
+    metrics::busy_deferrals()
+}
+
+fn bucket(nanos: u64) -> usize {
+    // 0 and 1 both fall in the first bucket
+    (63 - (nanos | 1).leading_zeros()) as usize
+}
+
+fn upper_bound(bucket: usize) -> u64 {
+    if bucket + 1 >= BUCKETS {
+        u64::MAX
+    } else {
+        (1 << (bucket + 1)) - 1
+    }
+}
@@ -0,0 +1,97 @@
+use std::ops::{BitOr, BitOrAssign};
+
+macro_rules! msg_flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$flag_meta:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct $name(libc::c_int);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: $name = $name($value);
+            )*
+
+            /// No flags.
+            pub const fn empty() -> $name {
+                $name(0)
+            }
+
+            /// Returns `true` if all the flags in `other` are set.
+            pub const fn contains(self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns the raw `MSG_*` flags.
+            pub const fn bits(self) -> libc::c_int {
+                self.0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, rhs: $name) -> $name {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: $name) {
+                self.0 |= rhs.0;
+            }
+        }
+    };
+}
+
+msg_flags! {
+    /// Flags modifying how data is received from a socket.
+    ///
+    /// Flags are combined with `|`, e.g. `RecvFlags::PEEK | RecvFlags::TRUNC`.
+    pub struct RecvFlags {
+        /// Returns the data without removing it from the receive queue. The
+        /// next receive returns the same data again (`MSG_PEEK`).
+        const PEEK = libc::MSG_PEEK;
+
+        /// For datagram sockets, returns the real length of the datagram, even
+        /// when it is longer than the buffer (`MSG_TRUNC`). Only the part that
+        /// fits is written to the buffer.
+        const TRUNC = libc::MSG_TRUNC;
+
+        /// For stream sockets, waits until the buffer is full (`MSG_WAITALL`).
+        /// Fewer bytes may still be returned on end of stream, an error or a
+        /// signal.
+        const WAITALL = libc::MSG_WAITALL;
+
+        /// Fails with `EAGAIN` instead of waiting when no data is available
+        /// (`MSG_DONTWAIT`).
+        const DONTWAIT = libc::MSG_DONTWAIT;
+    }
+}
+
+msg_flags! {
+    /// Flags modifying how data is sent on a socket.
+    ///
+    /// Flags are combined with `|`, e.g. `SendFlags::MORE | SendFlags::NOSIGNAL`.
+    pub struct SendFlags {
+        /// More data follows, letting the kernel coalesce this data with the
+        /// next send before transmitting it (`MSG_MORE`).
+        const MORE = libc::MSG_MORE;
+
+        /// Does not raise `SIGPIPE` when the peer closed a stream socket. The
+        /// send still fails with `EPIPE` (`MSG_NOSIGNAL`).
+        const NOSIGNAL = libc::MSG_NOSIGNAL;
+
+        /// Fails with `EAGAIN` instead of waiting when the send buffer is full
+        /// (`MSG_DONTWAIT`).
+        const DONTWAIT = libc::MSG_DONTWAIT;
+    }
+}
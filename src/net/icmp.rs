@@ -0,0 +1,229 @@
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::Socket,
+};
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// `ICMP_ECHO`
+const ECHO_REQUEST_V4: u8 = 8;
+/// `ICMP_ECHOREPLY`
+const ECHO_REPLY_V4: u8 = 0;
+/// `ICMPV6_ECHO_REQUEST`
+const ECHO_REQUEST_V6: u8 = 128;
+/// `ICMPV6_ECHO_REPLY`
+const ECHO_REPLY_V6: u8 = 129;
+
+/// An unprivileged ICMP socket, sending echo requests and receiving their
+/// replies, as `ping` does.
+///
+/// The socket is a datagram socket of the `IPPROTO_ICMP` protocol (or
+/// `IPPROTO_ICMPV6`), which unlike raw sockets requires no privileges: the
+/// group of the process only needs to be in the `net.ipv4.ping_group_range`
+/// sysctl. Creating the socket fails with `EACCES` otherwise.
+///
+/// The kernel fills in the identifier of the echo requests with the
+/// [identifier](IcmpSocket::identifier) of the socket, computes their
+/// checksum, and only delivers the replies matching the identifier to the
+/// socket.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::net::IcmpSocket;
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use std::time::Instant;
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let socket = IcmpSocket::bind(IpAddr::V4(Ipv4Addr::UNSPECIFIED))?;
+///         let target = IpAddr::V4(Ipv4Addr::LOCALHOST);
+///
+///         for sequence in 0..4 {
+///             let start = Instant::now();
+///             socket.send_echo(target, sequence, vec![0; 56]).await.0?;
+///
+///             let (res, _) = socket.recv_echo(vec![0; 128]).await;
+///             let reply = res?;
+///             println!(
+///                 "reply from {}: seq={} time={:?}",
+///                 reply.source(),
+///                 reply.sequence(),
+///                 start.elapsed()
+///             );
+///         }
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct IcmpSocket {
+    inner: Socket,
+    v6: bool,
+}
+
+/// An echo reply received by [`IcmpSocket::recv_echo`].
+///
+/// The buffer holds the ICMP message, the payload starts after the header of
+/// [`HEADER_LEN`](EchoReply::HEADER_LEN) bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EchoReply {
+    source: IpAddr,
+    identifier: u16,
+    sequence: u16,
+    len: usize,
+}
+
+impl IcmpSocket {
+    /// Creates a socket bound to the given local address, of the same family.
+    /// The kernel picks the identifier of the socket.
+    ///
+    /// Binding to the unspecified address sends from any interface.
+    pub fn bind(addr: IpAddr) -> io::Result<IcmpSocket> {
+        IcmpSocket::bind_with_identifier(addr, 0)
+    }
+
+    /// Creates a socket bound to the given local address, with the given
+    /// identifier. An identifier of `0` lets the kernel pick one.
+    ///
+    /// Fails with `EADDRINUSE` if another socket has the identifier.
+    pub fn bind_with_identifier(addr: IpAddr, identifier: u16) -> io::Result<IcmpSocket> {
+        let (domain, protocol) = match addr {
+            IpAddr::V4(_) => (libc::AF_INET, libc::IPPROTO_ICMP),
+            IpAddr::V6(_) => (libc::AF_INET6, libc::IPPROTO_ICMPV6),
+        };
+
+        let inner = Socket::new_raw(domain, libc::SOCK_DGRAM, protocol)?;
+        inner.bind_sockaddr(&SocketAddr::new(addr, identifier).into())?;
+
+        Ok(IcmpSocket {
+            inner,
+            v6: addr.is_ipv6(),
+        })
+    }
+
+    /// Returns the identifier of the socket, set in the echo requests it
+    /// sends.
+    pub fn identifier(&self) -> io::Result<u16> {
+        let addr = self.inner.local_sockaddr()?;
+        addr.as_socket()
+            .map(|addr| addr.port())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not an IP socket"))
+    }
+
+    /// Sends an echo request with the given sequence number and payload to
+    /// `dest`.
+    ///
+    /// The payload is copied after the ICMP header, and returned.
+    pub async fn send_echo<T: IoBuf>(
+        &self,
+        dest: IpAddr,
+        sequence: u16,
+        payload: T,
+    ) -> crate::BufResult<(), T> {
+        let kind = if self.v6 {
+            ECHO_REQUEST_V6
+        } else {
+            ECHO_REQUEST_V4
+        };
+
+        // The kernel fills in the checksum and the identifier
+        let mut packet = Vec::with_capacity(EchoReply::HEADER_LEN + payload.bytes_init());
+        packet.extend_from_slice(&[kind, 0, 0, 0, 0, 0]);
+        packet.extend_from_slice(&sequence.to_be_bytes());
+        packet.extend_from_slice(crate::buf::deref(&payload));
+
+        let (res, _) = self.send_to(packet, dest).await;
+        (res.map(|_| ()), payload)
+    }
+
+    /// Receives the next echo reply into the buffer, header included.
+    ///
+    /// If the reply is longer than the buffer, the excess of the payload is
+    /// discarded. Messages which are not echo replies are skipped. Fails with
+    /// `EMSGSIZE` if the buffer cannot hold the ICMP header.
+    pub async fn recv_echo<T: IoBufMut>(&self, mut buf: T) -> crate::BufResult<EchoReply, T> {
+        if buf.bytes_total() < EchoReply::HEADER_LEN {
+            return (Err(io::Error::from_raw_os_error(libc::EMSGSIZE)), buf);
+        }
+
+        let reply = if self.v6 {
+            ECHO_REPLY_V6
+        } else {
+            ECHO_REPLY_V4
+        };
+
+        loop {
+            let (res, b) = self.recv_from(buf).await;
+            buf = b;
+
+            let (len, source) = match res {
+                Ok(received) => received,
+                Err(e) => return (Err(e), buf),
+            };
+
+            let header = &crate::buf::deref(&buf)[..len];
+            if header.len() < EchoReply::HEADER_LEN || header[0] != reply {
+                continue;
+            }
+
+            let reply = EchoReply {
+                source,
+                identifier: u16::from_be_bytes([header[4], header[5]]),
+                sequence: u16::from_be_bytes([header[6], header[7]]),
+                len,
+            };
+            return (Ok(reply), buf);
+        }
+    }
+
+    /// Sends an ICMP message built by the caller to `dest`, header included.
+    /// On success, returns the number of bytes written.
+    ///
+    /// The kernel only accepts echo requests on this socket.
+    pub async fn send_to<T: IoBuf>(&self, buf: T, dest: IpAddr) -> crate::BufResult<usize, T> {
+        self.inner.send_to(buf, SocketAddr::new(dest, 0), 0).await
+    }
+
+    /// Receives an ICMP message, header included. On success, returns the
+    /// number of bytes read and the source address.
+    pub async fn recv_from<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(usize, IpAddr), T> {
+        let (res, buf) = self.inner.recv_from(buf, 0).await;
+        (res.map(|(n, addr)| (n, addr.ip())), buf)
+    }
+}
+
+impl EchoReply {
+    /// Length of the header of ICMP echo messages, preceding the payload.
+    pub const HEADER_LEN: usize = 8;
+
+    /// Returns the address of the host which replied.
+    pub fn source(&self) -> IpAddr {
+        self.source
+    }
+
+    /// Returns the identifier of the reply, the one of the socket.
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Returns the sequence number of the request the reply is for.
+    pub fn sequence(&self) -> u16 {
+        self.sequence
+    }
+
+    /// Returns the length of the payload in the buffer, following the
+    /// header.
+    pub fn payload_len(&self) -> usize {
+        self.len - EchoReply::HEADER_LEN
+    }
+}
+
+impl AsRawFd for IcmpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -0,0 +1,89 @@
+use crate::driver::Socket;
+use std::{
+    env, io,
+    os::unix::io::{FromRawFd, RawFd},
+};
+
+/// The first file descriptor passed by the service manager.
+pub const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// Returns the file descriptors passed by the service manager for socket
+/// activation, as described in [`sd_listen_fds(3)`].
+///
+/// The descriptors are numbered consecutively from [`SD_LISTEN_FDS_START`],
+/// and are marked close-on-exec. An empty list is returned if the process was
+/// not socket activated, or if the descriptors were meant for another process.
+///
+/// The caller takes ownership of the returned descriptors. If
+/// `unset_environment` is `true`, the `LISTEN_*` variables are removed from
+/// the environment, so that the descriptors are not taken twice and are not
+/// inherited by child processes.
+///
+/// [`sd_listen_fds(3)`]: https://www.freedesktop.org/software/systemd/man/sd_listen_fds.html
+pub fn sd_listen_fds(unset_environment: bool) -> io::Result<Vec<RawFd>> {
+    let pid = env::var("LISTEN_PID");
+    let fds = env::var("LISTEN_FDS");
+
+    if unset_environment {
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+        env::remove_var("LISTEN_FDNAMES");
+    }
+
+    let (pid, fds) = match (pid, fds) {
+        (Ok(pid), Ok(fds)) => (pid, fds),
+        _ => return Ok(vec![]),
+    };
+
+    let pid: u32 = pid.parse().map_err(|_| invalid_var("LISTEN_PID"))?;
+    if pid != std::process::id() {
+        return Ok(vec![]);
+    }
+
+    let fds: RawFd = fds.parse().map_err(|_| invalid_var("LISTEN_FDS"))?;
+    if fds < 0 {
+        return Err(invalid_var("LISTEN_FDS"));
+    }
+
+    let fds: Vec<RawFd> = (SD_LISTEN_FDS_START..SD_LISTEN_FDS_START + fds).collect();
+    for &fd in &fds {
+        syscall!(fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+    }
+
+    Ok(fds)
+}
+
+/// Takes the socket activation descriptors, which must all be listening
+/// stream sockets in one of `domains`.
+///
+/// On error, all the descriptors are closed.
+pub(crate) fn listeners(domains: &[socket2::Domain]) -> io::Result<Vec<Socket>> {
+    let sockets: Vec<socket2::Socket> = sd_listen_fds(true)?
+        .into_iter()
+        // Safety: the descriptors are owned by the process, and not handed
+        // out anywhere else once removed from the environment.
+        .map(|fd| unsafe { socket2::Socket::from_raw_fd(fd) })
+        .collect();
+
+    for socket in &sockets {
+        let is_match = domains.contains(&socket.domain()?)
+            && socket.r#type()? == socket2::Type::STREAM
+            && socket.is_listener()?;
+
+        if !is_match {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "inherited file descriptor is not a listener of the expected type",
+            ));
+        }
+    }
+
+    Ok(sockets.into_iter().map(Socket::from_socket2).collect())
+}
+
+fn invalid_var(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid {} environment variable", name),
+    )
+}
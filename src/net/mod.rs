@@ -7,16 +7,54 @@
 //!
 //! * [`TcpListener`] and [`TcpStream`] provide functionality for communication over TCP
 //! * [`UdpSocket`] provides functionality for communication over UDP
+//! * [`IcmpSocket`] provides functionality for unprivileged ICMP echo requests, as sent by `ping`
+//! * [`UnixDatagram`] provides functionality for datagram communication over Unix sockets
+//! * [`UnixSeqpacketListener`] and [`UnixSeqpacket`] provide functionality for message-oriented connections over Unix sockets
+//! * [`VsockListener`] and [`VsockStream`] provide functionality for communication between virtual machines and their host
+//! * [`NetlinkSocket`] provides functionality for communication with the kernel over netlink
+//! * [`Socket`] provides functionality for sockets of any other type, such as raw and packet sockets
+//! * [`sd_listen_fds`] retrieves the sockets passed by systemd socket activation
+//! * [`connect_via_proxy`] opens TCP connections through SOCKS5 and HTTP proxies
 
 //!
 //! [`TcpListener`]: TcpListener
 //! [`TcpStream`]: TcpStream
 //! [`UdpSocket`]: UdpSocket
+//! [`IcmpSocket`]: IcmpSocket
+//! [`UnixDatagram`]: UnixDatagram
+//! [`UnixSeqpacketListener`]: UnixSeqpacketListener
+//! [`UnixSeqpacket`]: UnixSeqpacket
+//! [`VsockListener`]: VsockListener
+//! [`VsockStream`]: VsockStream
+//! [`NetlinkSocket`]: NetlinkSocket
+//! [`Socket`]: Socket
+//! [`sd_listen_fds`]: sd_listen_fds
+//! [`connect_via_proxy`]: connect_via_proxy
 
+mod flags;
+mod icmp;
+mod listen_fds;
+mod netlink;
+mod proxy;
+mod socket;
 mod tcp;
 mod udp;
 mod unix;
+mod vsock;
 
-pub use tcp::{TcpListener, TcpStream};
-pub use udp::UdpSocket;
-pub use unix::{UnixListener, UnixStream};
+pub use flags::{RecvFlags, SendFlags};
+pub use icmp::{EchoReply, IcmpSocket};
+pub use listen_fds::{sd_listen_fds, SD_LISTEN_FDS_START};
+pub use netlink::{NetlinkMessage, NetlinkMessages, NetlinkSocket};
+pub use proxy::{connect_via_proxy, Proxy};
+pub use socket::Socket;
+pub use socket2::SockAddr;
+pub use tcp::{
+    serve_file_range, Incoming, KeepaliveConfig, TcpInfo, TcpListener, TcpOptions, TcpStream,
+};
+pub use udp::{DatagramSink, Datagrams, UdpSocket};
+pub use unix::{
+    UCred, UnixDatagram, UnixListener, UnixSeqpacket, UnixSeqpacketListener, UnixSocketAddr,
+    UnixStream,
+};
+pub use vsock::{VsockAddr, VsockListener, VsockStream};
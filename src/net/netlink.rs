@@ -0,0 +1,264 @@
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::Socket,
+    net::{RecvFlags, SendFlags},
+};
+use socket2::SockAddr;
+use std::{
+    convert::TryInto,
+    io, mem,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// A netlink socket, for communication with the kernel.
+///
+/// Netlink sockets (`AF_NETLINK`) carry requests to and notifications from
+/// kernel subsystems, such as routing and network interfaces with
+/// `NETLINK_ROUTE`. Each datagram holds one or more messages, which are
+/// iterated with [`NetlinkMessages`].
+///
+/// # Examples
+///
+/// List the network interfaces:
+///
+/// ```
+/// use tokio_uring::net::{NetlinkMessages, NetlinkSocket};
+///
+/// tokio_uring::start(async {
+///     let socket = NetlinkSocket::new(libc::NETLINK_ROUTE).unwrap();
+///
+///     // `nlmsghdr` followed by an `rtgenmsg`, in native endianness
+///     let mut request = Vec::new();
+///     request.extend_from_slice(&20u32.to_ne_bytes());
+///     request.extend_from_slice(&libc::RTM_GETLINK.to_ne_bytes());
+///     request.extend_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+///     request.extend_from_slice(&1u32.to_ne_bytes());
+///     request.extend_from_slice(&0u32.to_ne_bytes());
+///     request.extend_from_slice(&[libc::AF_UNSPEC as u8, 0, 0, 0]);
+///
+///     let (res, _) = socket.send(request).await;
+///     res.unwrap();
+///
+///     let mut buf = vec![0; 32 * 1024];
+///     'dump: loop {
+///         let (res, b) = socket.recv(buf).await;
+///         let n = res.unwrap();
+///
+///         for msg in NetlinkMessages::new(&b[..n]) {
+///             let msg = msg.unwrap();
+///             if msg.message_type() == libc::NLMSG_DONE as u16 {
+///                 break 'dump;
+///             }
+///             println!("interface: {} bytes", msg.payload().len());
+///         }
+///
+///         buf = b;
+///     }
+/// });
+/// ```
+pub struct NetlinkSocket {
+    inner: Socket,
+}
+
+impl NetlinkSocket {
+    /// Creates a new netlink socket for the given protocol, such as
+    /// `NETLINK_ROUTE`.
+    ///
+    /// The kernel assigns the socket a port ID on the first send, unless it
+    /// is [bound](NetlinkSocket::bind) before.
+    pub fn new(protocol: libc::c_int) -> io::Result<NetlinkSocket> {
+        let inner = Socket::new_raw(libc::AF_NETLINK, libc::SOCK_RAW, protocol)?;
+        Ok(NetlinkSocket { inner })
+    }
+
+    /// Binds the socket to the given port ID, and subscribes it to the
+    /// multicast groups in the `groups` bit mask.
+    ///
+    /// A port ID of 0 lets the kernel assign one.
+    pub fn bind(&self, pid: u32, groups: u32) -> io::Result<()> {
+        self.inner.bind_sockaddr(&netlink_addr(pid, groups))
+    }
+
+    /// Sends a datagram of one or more messages to the kernel. On success,
+    /// returns the number of bytes written.
+    pub async fn send<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner
+            .send_to_sockaddr(buf, netlink_addr(0, 0), SendFlags::empty().bits())
+            .await
+    }
+
+    /// Receives a single datagram of one or more messages. On success,
+    /// returns the number of bytes read.
+    ///
+    /// Returns an error if the datagram is longer than the buffer, in which
+    /// case the datagram is lost. A buffer of 32KiB fits any datagram sent by
+    /// the kernel.
+    pub async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.recv_from(buf).await;
+        (res.map(|(n, _)| n), buf)
+    }
+
+    /// Receives a single datagram of one or more messages. On success,
+    /// returns the number of bytes read and the port ID of the sender, which
+    /// is 0 for the kernel.
+    ///
+    /// See [`recv`](NetlinkSocket::recv) for truncated datagrams.
+    pub async fn recv_from<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(usize, u32), T> {
+        let (res, buf) = self
+            .inner
+            .recv_from_sockaddr(buf, RecvFlags::TRUNC.bits())
+            .await;
+
+        let res = res.and_then(|(n, addr)| {
+            if n > buf.bytes_total() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "netlink datagram longer than the buffer",
+                ));
+            }
+
+            // Safety: `recvmsg` only reports `AF_NETLINK` addresses on a
+            // netlink socket.
+            let addr: &libc::sockaddr_nl = unsafe { &*addr.as_ptr().cast() };
+            Ok((n, addr.nl_pid))
+        });
+
+        (res, buf)
+    }
+}
+
+fn netlink_addr(pid: u32, groups: u32) -> SockAddr {
+    let (_, addr) = unsafe {
+        SockAddr::init(|storage, len| {
+            let storage = &mut *storage.cast::<libc::sockaddr_nl>();
+            storage.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            storage.nl_pid = pid;
+            storage.nl_groups = groups;
+            *len = mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+            Ok(())
+        })
+    }
+    // Never fails, the closure always succeeds
+    .unwrap();
+
+    addr
+}
+
+/// Size of `nlmsghdr`.
+const HEADER_LEN: usize = 16;
+
+/// A netlink message, borrowed from a received datagram.
+#[derive(Debug, Clone, Copy)]
+pub struct NetlinkMessage<'a> {
+    message_type: u16,
+    flags: u16,
+    sequence: u32,
+    pid: u32,
+    payload: &'a [u8],
+}
+
+impl<'a> NetlinkMessage<'a> {
+    /// Returns the message type, `nlmsg_type`.
+    pub fn message_type(&self) -> u16 {
+        self.message_type
+    }
+
+    /// Returns the `NLM_F_*` flags, `nlmsg_flags`.
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// Returns the sequence number, `nlmsg_seq`.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Returns the port ID of the sender, `nlmsg_pid`.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Returns the payload following the header.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns the error reported by an `NLMSG_ERROR` message.
+    ///
+    /// Returns `None` for other messages, and for acknowledgements, which are
+    /// `NLMSG_ERROR` messages with an error code of 0.
+    pub fn error(&self) -> Option<io::Error> {
+        if self.message_type != libc::NLMSG_ERROR as u16 || self.payload.len() < 4 {
+            return None;
+        }
+
+        let code = i32::from_ne_bytes(self.payload[..4].try_into().unwrap());
+        if code == 0 {
+            None
+        } else {
+            Some(io::Error::from_raw_os_error(-code))
+        }
+    }
+}
+
+/// Iterator over the netlink messages of a datagram.
+///
+/// Yields an error and stops if a message header is malformed.
+#[derive(Debug, Clone)]
+pub struct NetlinkMessages<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> NetlinkMessages<'a> {
+    /// Iterates over the messages in `buf`, which holds a received datagram.
+    pub fn new(buf: &'a [u8]) -> NetlinkMessages<'a> {
+        NetlinkMessages { buf }
+    }
+}
+
+impl<'a> Iterator for NetlinkMessages<'a> {
+    type Item = io::Result<NetlinkMessage<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+
+        let buf = mem::take(&mut self.buf);
+        if buf.len() < HEADER_LEN {
+            return Some(Err(malformed()));
+        }
+
+        let u16_at = |i: usize| u16::from_ne_bytes(buf[i..i + 2].try_into().unwrap());
+        let u32_at = |i: usize| u32::from_ne_bytes(buf[i..i + 4].try_into().unwrap());
+
+        let len = u32_at(0) as usize;
+        if len < HEADER_LEN || len > buf.len() {
+            return Some(Err(malformed()));
+        }
+
+        let message = NetlinkMessage {
+            message_type: u16_at(4),
+            flags: u16_at(6),
+            sequence: u32_at(8),
+            pid: u32_at(12),
+            payload: &buf[HEADER_LEN..len],
+        };
+
+        // Messages are aligned to 4 bytes, the last one may omit the padding
+        let aligned = (len + 3) & !3;
+        self.buf = buf.get(aligned..).unwrap_or(&[]);
+
+        Some(Ok(message))
+    }
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed netlink message")
+}
+
+impl AsRawFd for NetlinkSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
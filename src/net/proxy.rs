@@ -0,0 +1,414 @@
+use crate::net::{RecvFlags, TcpStream};
+
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+
+/// Maximum size of the response of an HTTP proxy to `CONNECT`, headers
+/// included.
+const MAX_RESPONSE: usize = 8 * 1024;
+
+/// A proxy server tunneling TCP connections, see [`connect_via_proxy`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::net::Proxy;
+///
+/// let mut proxy = Proxy::http("10.0.0.1:3128".parse().unwrap());
+/// proxy.credentials("user", "secret");
+/// ```
+#[derive(Clone)]
+pub struct Proxy {
+    protocol: Protocol,
+    addr: SocketAddr,
+
+    /// Username and password
+    credentials: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    Socks5,
+    Http,
+}
+
+impl Proxy {
+    /// A SOCKS5 proxy listening on `addr`, as of RFC 1928.
+    ///
+    /// The name of the target host is resolved by the proxy.
+    pub fn socks5(addr: SocketAddr) -> Proxy {
+        Proxy {
+            protocol: Protocol::Socks5,
+            addr,
+            credentials: None,
+        }
+    }
+
+    /// An HTTP proxy listening on `addr`, tunneling connections requested
+    /// with the `CONNECT` method.
+    pub fn http(addr: SocketAddr) -> Proxy {
+        Proxy {
+            protocol: Protocol::Http,
+            addr,
+            credentials: None,
+        }
+    }
+
+    /// Authenticates with a username and a password: the username/password
+    /// method of RFC 1929 for SOCKS5 proxies, and the `Basic` scheme for HTTP
+    /// proxies.
+    ///
+    /// Both send the password in clear text.
+    pub fn credentials(&mut self, username: &str, password: &str) -> &mut Proxy {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Returns the address of the proxy.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("protocol", &self.protocol)
+            .field("addr", &self.addr)
+            .field(
+                "username",
+                &self.credentials.as_ref().map(|(username, _)| username),
+            )
+            .finish()
+    }
+}
+
+/// Opens a TCP connection to `target` through `proxy`, returning the stream
+/// tunneled to `target` once the proxy connected to it.
+///
+/// `target` is a host name or an IP address, with a port, such as
+/// `"example.com:443"`, `"192.0.2.1:80"` or `"[2001:db8::1]:80"`. Host names
+/// are resolved by the proxy, so that clients restricted to the proxy can
+/// reach hosts they cannot resolve themselves.
+///
+/// # Errors
+///
+/// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if `target` is
+/// not a host with a port, with
+/// [`PermissionDenied`](io::ErrorKind::PermissionDenied) if the proxy
+/// rejected the credentials, or requires some, and with the error of the
+/// proxy connecting to the target otherwise, such as `ECONNREFUSED` or
+/// `EHOSTUNREACH` for SOCKS5 proxies.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::net::{connect_via_proxy, Proxy};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let proxy = Proxy::socks5("127.0.0.1:1080".parse().unwrap());
+///         let stream = connect_via_proxy(&proxy, "example.com:80").await?;
+///
+///         let request = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec();
+///         let (res, _) = stream.write(request).await;
+///         res?;
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn connect_via_proxy(proxy: &Proxy, target: &str) -> io::Result<TcpStream> {
+    let (host, port) = split_target(target)?;
+    let stream = TcpStream::connect(proxy.addr).await?;
+
+    match proxy.protocol {
+        Protocol::Socks5 => socks5_connect(&stream, proxy, host, port).await?,
+        Protocol::Http => http_connect(&stream, proxy, target).await?,
+    }
+
+    Ok(stream)
+}
+
+/// Splits `host:port`, removing the brackets of IPv6 addresses.
+fn split_target(target: &str) -> io::Result<(&str, u16)> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "invalid target address");
+
+    let (host, port) = target.rsplit_once(':').ok_or_else(invalid)?;
+    let port = port.parse().map_err(|_| invalid())?;
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    Ok((host, port))
+}
+
+async fn socks5_connect(
+    stream: &TcpStream,
+    proxy: &Proxy,
+    host: &str,
+    port: u16,
+) -> io::Result<()> {
+    /// No authentication, and username/password, from RFC 1928
+    const NO_AUTH: u8 = 0;
+    const PASSWORD: u8 = 2;
+    const NO_ACCEPTABLE: u8 = 0xff;
+
+    let methods: &[u8] = match proxy.credentials {
+        Some(_) => &[NO_AUTH, PASSWORD],
+        None => &[NO_AUTH],
+    };
+    let mut greeting = vec![5, methods.len() as u8];
+    greeting.extend_from_slice(methods);
+    write_all(stream, greeting).await?;
+
+    let reply = read_exact(stream, 2).await?;
+    check_version(reply[0], 5)?;
+    match (reply[1], &proxy.credentials) {
+        (NO_AUTH, _) => {}
+        (PASSWORD, Some((username, password))) => {
+            if username.len() > 255 || password.len() > 255 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "SOCKS5 credentials too long",
+                ));
+            }
+
+            // RFC 1929
+            let mut request = vec![1, username.len() as u8];
+            request.extend_from_slice(username.as_bytes());
+            request.push(password.len() as u8);
+            request.extend_from_slice(password.as_bytes());
+            write_all(stream, request).await?;
+
+            let reply = read_exact(stream, 2).await?;
+            if reply[1] != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "SOCKS5 proxy rejected the credentials",
+                ));
+            }
+        }
+        (NO_ACCEPTABLE, _) | (PASSWORD, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "SOCKS5 proxy requires authentication",
+            ))
+        }
+        (method, _) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected SOCKS5 authentication method {}", method),
+            ))
+        }
+    }
+
+    // CONNECT, to an address of type IPv4, domain name or IPv6
+    let mut request = vec![5, 1, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "host name too long",
+                ));
+            }
+            request.push(3);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    write_all(stream, request).await?;
+
+    let reply = read_exact(stream, 4).await?;
+    check_version(reply[0], 5)?;
+    if reply[1] != 0 {
+        return Err(socks5_error(reply[1]));
+    }
+
+    // The address the proxy connected from, unused
+    let len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => read_exact(stream, 1).await?[0] as usize,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected SOCKS5 address type",
+            ))
+        }
+    };
+    read_exact(stream, len + 2).await?;
+
+    Ok(())
+}
+
+/// Maps the reply codes of RFC 1928 to errors.
+fn socks5_error(reply: u8) -> io::Error {
+    let errno = match reply {
+        2 => {
+            return io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "SOCKS5 ruleset denied the connection",
+            )
+        }
+        3 => libc::ENETUNREACH,
+        4 => libc::EHOSTUNREACH,
+        5 => libc::ECONNREFUSED,
+        6 => libc::ETIMEDOUT,
+        7 => libc::EOPNOTSUPP,
+        8 => libc::EAFNOSUPPORT,
+        _ => return io::Error::other(format!("SOCKS5 proxy failure {}", reply)),
+    };
+    io::Error::from_raw_os_error(errno)
+}
+
+fn check_version(version: u8, expected: u8) -> io::Result<()> {
+    if version != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected SOCKS version",
+        ));
+    }
+    Ok(())
+}
+
+async fn http_connect(stream: &TcpStream, proxy: &Proxy, target: &str) -> io::Result<()> {
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if let Some((username, password)) = &proxy.credentials {
+        let credentials = format!("{}:{}", username, password);
+        request.push_str("Proxy-Authorization: Basic ");
+        request.push_str(&base64(credentials.as_bytes()));
+        request.push_str("\r\n");
+    }
+    request.push_str("\r\n");
+    write_all(stream, request.into_bytes()).await?;
+
+    let response = read_response(stream).await?;
+    let status_line = response.lines().next().unwrap_or_default();
+
+    // `HTTP/1.1 200 Connection established`
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP response"))?;
+
+    match status {
+        200..=299 => Ok(()),
+        407 => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "HTTP proxy requires authentication",
+        )),
+        _ => Err(io::Error::other(format!(
+            "HTTP proxy refused to connect: {}",
+            status_line
+        ))),
+    }
+}
+
+/// Reads the response of the proxy, up to the empty line ending its headers.
+///
+/// The data is peeked first and only the response is consumed, so that data
+/// the target sends first, such as the greeting of an SMTP server, is left
+/// in the stream.
+async fn read_response(stream: &TcpStream) -> io::Result<String> {
+    let mut response = Vec::new();
+    let mut buf = Vec::with_capacity(MAX_RESPONSE);
+
+    loop {
+        buf.clear();
+        let (res, b) = stream.recv_with_flags(buf, RecvFlags::PEEK).await;
+        buf = b;
+        if res? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        // The end may straddle the data consumed and the data peeked
+        let start = response.len().saturating_sub(3);
+        response.extend_from_slice(&buf);
+        let len = match find(&response[start..], b"\r\n\r\n") {
+            Some(pos) => start + pos + 4,
+            None => response.len(),
+        };
+        let consumed = buf.len() - (response.len() - len);
+        response.truncate(len);
+
+        read_exact(stream, consumed).await?;
+
+        if response.ends_with(b"\r\n\r\n") {
+            return String::from_utf8(response)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP response"));
+        }
+
+        if response.len() >= MAX_RESPONSE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "HTTP response too large",
+            ));
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+async fn write_all(stream: &TcpStream, buf: Vec<u8>) -> io::Result<()> {
+    crate::io::write_all(buf, |slice, _| stream.write(slice))
+        .await
+        .0
+}
+
+async fn read_exact(stream: &TcpStream, len: usize) -> io::Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(len);
+
+    while data.len() < len {
+        let (res, buf) = stream.read(Vec::with_capacity(len - data.len())).await;
+        if res? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        data.extend_from_slice(&buf);
+    }
+
+    Ok(data)
+}
+
+/// Encodes `data` in base64, with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,127 @@
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver,
+    net::{RecvFlags, SendFlags},
+};
+use socket2::SockAddr;
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// A socket of any domain, type and protocol.
+///
+/// `Socket` covers the sockets which have no dedicated type, such as raw IP
+/// sockets (`SOCK_RAW`) and packet sockets (`AF_PACKET`), for tools like ping,
+/// traceroute or packet capture. Addresses are passed as [`SockAddr`], which
+/// can hold any `sockaddr` type.
+///
+/// Socket options are not covered, they can be set on the file descriptor
+/// given by [`as_raw_fd`](AsRawFd::as_raw_fd).
+///
+/// # Examples
+///
+/// Send an ICMP echo request over a raw socket:
+///
+/// ```no_run
+/// use tokio_uring::net::{RecvFlags, SendFlags, SockAddr, Socket};
+/// use std::net::SocketAddr;
+///
+/// tokio_uring::start(async {
+///     let socket = Socket::new(libc::AF_INET, libc::SOCK_RAW, libc::IPPROTO_ICMP).unwrap();
+///
+///     // type, code, checksum, identifier, sequence number
+///     let request = vec![8, 0, 0xf7, 0xff, 0, 0, 0, 0];
+///     let addr = SockAddr::from("127.0.0.1:0".parse::<SocketAddr>().unwrap());
+///     let (res, _) = socket.send_to(request, &addr, SendFlags::empty()).await;
+///     res.unwrap();
+///
+///     // The reply includes the IP header
+///     let (res, buf) = socket.recv(vec![0; 1500], RecvFlags::empty()).await;
+///     let n = res.unwrap();
+///     println!("{:?}", &buf[..n]);
+/// });
+/// ```
+pub struct Socket {
+    inner: driver::Socket,
+}
+
+impl Socket {
+    /// Creates a new socket, see [`socket(2)`].
+    ///
+    /// [`socket(2)`]: https://man7.org/linux/man-pages/man2/socket.2.html
+    pub fn new(
+        domain: libc::c_int,
+        socket_type: libc::c_int,
+        protocol: libc::c_int,
+    ) -> io::Result<Socket> {
+        let inner = driver::Socket::new_raw(domain, socket_type, protocol)?;
+        Ok(Socket { inner })
+    }
+
+    /// Binds the socket to the given address.
+    pub fn bind(&self, addr: &SockAddr) -> io::Result<()> {
+        self.inner.bind_sockaddr(addr)
+    }
+
+    /// Connects the socket to the given address.
+    pub async fn connect(&self, addr: &SockAddr) -> io::Result<()> {
+        self.inner.connect(addr.clone()).await
+    }
+
+    /// Sends data on the connected socket, with the given flags. On success,
+    /// returns the number of bytes written.
+    pub async fn send<T: IoBuf>(&self, buf: T, flags: SendFlags) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
+    }
+
+    /// Sends data on the socket to the given address, with the given flags.
+    /// On success, returns the number of bytes written.
+    pub async fn send_to<T: IoBuf>(
+        &self,
+        buf: T,
+        addr: &SockAddr,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner
+            .send_to_sockaddr(buf, addr.clone(), flags.bits())
+            .await
+    }
+
+    /// Sends the data of all the buffers as a single message, to the given
+    /// address or to the connected peer, with the given flags. On success,
+    /// returns the number of bytes written.
+    ///
+    /// This is useful to send a header and a payload held in separate
+    /// buffers.
+    pub async fn send_msg<T: IoBuf>(
+        &self,
+        bufs: Vec<T>,
+        addr: Option<&SockAddr>,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, Vec<T>> {
+        self.inner.send_msg(bufs, addr.cloned(), flags.bits()).await
+    }
+
+    /// Receives data from the socket, with the given flags. On success,
+    /// returns the number of bytes read.
+    pub async fn recv<T: IoBufMut>(&self, buf: T, flags: RecvFlags) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
+    }
+
+    /// Receives data from the socket, with the given flags. On success,
+    /// returns the number of bytes read and the origin.
+    pub async fn recv_from<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from_sockaddr(buf, flags.bits()).await
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
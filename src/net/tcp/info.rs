@@ -0,0 +1,124 @@
+use std::fmt;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+/// Statistics of a TCP connection, as tracked by the kernel.
+///
+/// Returned by [`TcpStream::tcp_info`]. Fields which the running kernel does
+/// not report are zero.
+///
+/// [`TcpStream::tcp_info`]: crate::net::TcpStream::tcp_info
+#[derive(Clone, Copy)]
+pub struct TcpInfo {
+    inner: libc::tcp_info,
+}
+
+impl TcpInfo {
+    /// Reads the statistics of a TCP socket with `getsockopt(TCP_INFO)`.
+    pub(crate) fn from_fd(fd: RawFd) -> io::Result<TcpInfo> {
+        // Older kernels fill in a prefix of the structure
+        let mut inner: libc::tcp_info = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+
+        syscall!(getsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut inner as *mut libc::tcp_info as *mut libc::c_void,
+            &mut len
+        ))?;
+
+        Ok(TcpInfo { inner })
+    }
+
+    /// Returns the smoothed round-trip time.
+    pub fn rtt(&self) -> Duration {
+        Duration::from_micros(self.inner.tcpi_rtt.into())
+    }
+
+    /// Returns the variation of the round-trip time.
+    pub fn rtt_var(&self) -> Duration {
+        Duration::from_micros(self.inner.tcpi_rttvar.into())
+    }
+
+    /// Returns the minimum round-trip time observed.
+    pub fn min_rtt(&self) -> Duration {
+        Duration::from_micros(self.inner.tcpi_min_rtt.into())
+    }
+
+    /// Returns the retransmission timeout.
+    pub fn rto(&self) -> Duration {
+        Duration::from_micros(self.inner.tcpi_rto.into())
+    }
+
+    /// Returns the congestion window, in segments.
+    pub fn cwnd(&self) -> u32 {
+        self.inner.tcpi_snd_cwnd
+    }
+
+    /// Returns the slow start threshold, in segments.
+    pub fn ssthresh(&self) -> u32 {
+        self.inner.tcpi_snd_ssthresh
+    }
+
+    /// Returns the maximum segment size for sending, in bytes.
+    pub fn snd_mss(&self) -> u32 {
+        self.inner.tcpi_snd_mss
+    }
+
+    /// Returns the number of segments sent but not acknowledged yet.
+    pub fn unacked(&self) -> u32 {
+        self.inner.tcpi_unacked
+    }
+
+    /// Returns the number of segments retransmitted over the lifetime of the
+    /// connection.
+    pub fn total_retransmits(&self) -> u32 {
+        self.inner.tcpi_total_retrans
+    }
+
+    /// Returns the number of segments considered lost.
+    pub fn lost(&self) -> u32 {
+        self.inner.tcpi_lost
+    }
+
+    /// Returns the pacing rate of the connection, in bytes per second.
+    pub fn pacing_rate(&self) -> u64 {
+        self.inner.tcpi_pacing_rate
+    }
+
+    /// Returns the most recent estimate of the delivery rate, in bytes per
+    /// second.
+    pub fn delivery_rate(&self) -> u64 {
+        self.inner.tcpi_delivery_rate
+    }
+
+    /// Returns the number of bytes acknowledged by the peer.
+    pub fn bytes_acked(&self) -> u64 {
+        self.inner.tcpi_bytes_acked
+    }
+
+    /// Returns the number of bytes received from the peer.
+    pub fn bytes_received(&self) -> u64 {
+        self.inner.tcpi_bytes_received
+    }
+
+    /// Returns the number of bytes written to the socket but not sent yet.
+    pub fn notsent_bytes(&self) -> u32 {
+        self.inner.tcpi_notsent_bytes
+    }
+}
+
+impl fmt::Debug for TcpInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpInfo")
+            .field("rtt", &self.rtt())
+            .field("rtt_var", &self.rtt_var())
+            .field("cwnd", &self.cwnd())
+            .field("total_retransmits", &self.total_retransmits())
+            .field("pacing_rate", &self.pacing_rate())
+            .field("delivery_rate", &self.delivery_rate())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+/// Keepalive parameters of a TCP connection, set with
+/// [`TcpStream::set_keepalive`].
+///
+/// Once the connection has been idle for the [idle](KeepaliveConfig::with_idle)
+/// time, probes are sent every [interval](KeepaliveConfig::with_interval), and
+/// the connection is reset after [count](KeepaliveConfig::with_count)
+/// unanswered probes. The parameters left unset keep the system defaults,
+/// from the `net.ipv4.tcp_keepalive_*` sysctls.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tokio_uring::net::{KeepaliveConfig, TcpStream};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+///
+///         // A dead peer is detected after about 90 seconds
+///         let keepalive = KeepaliveConfig::new()
+///             .with_idle(Duration::from_secs(60))
+///             .with_interval(Duration::from_secs(10))
+///             .with_count(3);
+///         stream.set_keepalive(keepalive)?;
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`TcpStream::set_keepalive`]: crate::net::TcpStream::set_keepalive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeepaliveConfig {
+    pub(super) idle: Option<Duration>,
+    pub(super) interval: Option<Duration>,
+    pub(super) count: Option<u32>,
+}
+
+impl KeepaliveConfig {
+    /// Creates a configuration keeping the system defaults.
+    pub fn new() -> KeepaliveConfig {
+        KeepaliveConfig::default()
+    }
+
+    /// Sets the time the connection stays idle before the first probe is
+    /// sent, `TCP_KEEPIDLE`. Rounded down to seconds.
+    pub fn with_idle(mut self, idle: Duration) -> KeepaliveConfig {
+        self.idle = Some(idle);
+        self
+    }
+
+    /// Sets the time between two probes, `TCP_KEEPINTVL`. Rounded down to
+    /// seconds.
+    pub fn with_interval(mut self, interval: Duration) -> KeepaliveConfig {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Sets the number of unanswered probes after which the connection is
+    /// reset, `TCP_KEEPCNT`.
+    pub fn with_count(mut self, count: u32) -> KeepaliveConfig {
+        self.count = Some(count);
+        self
+    }
+
+    /// Returns the idle time before the first probe, if set.
+    pub fn idle(&self) -> Option<Duration> {
+        self.idle
+    }
+
+    /// Returns the time between two probes, if set.
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    /// Returns the number of unanswered probes before the reset, if set.
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+}
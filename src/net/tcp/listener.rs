@@ -1,6 +1,19 @@
 use super::TcpStream;
-use crate::driver::Socket;
-use std::{io, net::SocketAddr};
+use crate::{
+    driver::{Accept, Op, Socket},
+    net::listen_fds,
+};
+use futures_core::Stream;
+use std::{
+    cell::RefCell,
+    future::Future,
+    io,
+    net::SocketAddr,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
 
 /// A TCP socket server, listening for connections.
 ///
@@ -32,7 +45,7 @@ use std::{io, net::SocketAddr};
 /// }
 /// ```
 pub struct TcpListener {
-    inner: Socket,
+    pub(super) inner: Socket,
 }
 
 impl TcpListener {
@@ -48,7 +61,148 @@ impl TcpListener {
     pub fn bind(addr: SocketAddr) -> io::Result<Self> {
         let socket = Socket::bind(addr, libc::SOCK_STREAM)?;
         socket.listen(1024)?;
-        return Ok(TcpListener { inner: socket });
+        Ok(TcpListener { inner: socket })
+    }
+
+    /// Adopts the listeners passed by the service manager for socket
+    /// activation, in the order they are configured in the `.socket` unit.
+    ///
+    /// Returns an empty list if the process was not socket activated. Returns
+    /// an error if any of the passed descriptors is not a listening TCP
+    /// socket, closing all of them. The `LISTEN_*` environment variables are
+    /// removed in either case, see [`sd_listen_fds`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let listener = match TcpListener::from_listen_fds()?.pop() {
+    ///         Some(listener) => listener,
+    ///         // Not started by systemd
+    ///         None => TcpListener::bind("127.0.0.1:8080".parse().unwrap())?,
+    ///     };
+    ///
+    ///     tokio_uring::start(async move {
+    ///         let (_stream, addr) = listener.accept().await?;
+    ///         println!("connection from {}", addr);
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// [`sd_listen_fds`]: crate::net::sd_listen_fds
+    pub fn from_listen_fds() -> io::Result<Vec<TcpListener>> {
+        let domains = [socket2::Domain::IPV4, socket2::Domain::IPV6];
+        let sockets = listen_fds::listeners(&domains)?;
+        Ok(sockets
+            .into_iter()
+            .map(|inner| TcpListener { inner })
+            .collect())
+    }
+
+    /// Returns `true` if the listener is a Multipath TCP socket, created by
+    /// [`TcpOptions::mptcp`](crate::net::TcpOptions::mptcp).
+    pub fn is_mptcp(&self) -> io::Result<bool> {
+        self.inner.is_mptcp()
+    }
+
+    /// Returns the CPU the listener prefers, set with
+    /// [`set_incoming_cpu`](TcpListener::set_incoming_cpu), or `-1` as a
+    /// `usize` if unset.
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        self.inner.incoming_cpu()
+    }
+
+    /// Sets the CPU the listener prefers, `SO_INCOMING_CPU`.
+    ///
+    /// Among the listeners bound to the same address, a connection handled by
+    /// a CPU goes to the listener preferring this CPU, if any. Each thread
+    /// running a runtime pinned to a CPU can bind a listener preferring the
+    /// CPU, so its connections are mostly handled by its own ring. For a
+    /// strict steering, see
+    /// [`set_reuseport_cpu_steering`](TcpListener::set_reuseport_cpu_steering).
+    pub fn set_incoming_cpu(&self, cpu: usize) -> io::Result<()> {
+        self.inner.set_incoming_cpu(cpu)
+    }
+
+    /// Steers the connections among the listeners bound to the same address
+    /// by the CPU handling them: a connection handled by CPU `n` goes to the
+    /// listener bound `n % group_size`-th, counting from zero.
+    ///
+    /// Listeners are bound with `SO_REUSEPORT`, so that several threads, each
+    /// with its own runtime, can bind a listener to the same address. By
+    /// default the kernel spreads the connections by a hash of their
+    /// addresses. With the steering, and each thread pinned to a CPU and
+    /// binding its listener in the order of the CPUs, each ring handles the
+    /// connections whose interrupts land on its CPU, avoiding cross-CPU
+    /// traffic.
+    ///
+    /// The program applies to the whole group, and only needs to be set on
+    /// one of the listeners. Connections go to the listener of the hash when
+    /// the group has fewer than `group_size` listeners.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let addr = "0.0.0.0:8080".parse().unwrap();
+    ///     let cpus = std::thread::available_parallelism()?.get();
+    ///
+    ///     let mut threads = Vec::new();
+    ///     for cpu in 0..cpus {
+    ///         let (bound_tx, bound_rx) = std::sync::mpsc::channel();
+    ///         threads.push(std::thread::spawn(move || {
+    ///             // Pin the thread to `cpu` here, e.g. with `sched_setaffinity`
+    ///             let listener = TcpListener::bind(addr).unwrap();
+    ///             if cpu == 0 {
+    ///                 listener.set_reuseport_cpu_steering(cpus as u32).unwrap();
+    ///             }
+    ///             bound_tx.send(()).unwrap();
+    ///
+    ///             tokio_uring::start(async move {
+    ///                 loop {
+    ///                     let (stream, _) = listener.accept().await.unwrap();
+    ///                     assert_eq!(stream.incoming_cpu().unwrap() % cpus, cpu);
+    ///                 }
+    ///             })
+    ///         }));
+    ///
+    ///         // Bound in order, the listener of index `cpu` serves the CPU
+    ///         bound_rx.recv().unwrap();
+    ///     }
+    ///
+    ///     for thread in threads {
+    ///         thread.join().unwrap();
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_reuseport_cpu_steering(&self, group_size: u32) -> io::Result<()> {
+        self.inner.set_reuseport_cpu_steering(group_size)
+    }
+
+    /// Enables TCP Fast Open on the listener, letting clients send data in
+    /// the SYN, as [`TcpStream::connect_with_data`] does. `queue_len` bounds
+    /// the connections whose handshake is not complete yet but whose data was
+    /// accepted.
+    ///
+    /// The server side of Fast Open must also be enabled system wide, by
+    /// setting the `0x2` bit of the `net.ipv4.tcp_fastopen` sysctl. Otherwise
+    /// the clients fall back to a regular handshake.
+    pub fn set_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        let queue_len = queue_len as libc::c_int;
+        syscall!(setsockopt(
+            self.inner.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_FASTOPEN,
+            &queue_len as *const libc::c_int as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t
+        ))?;
+        Ok(())
     }
 
     /// Accepts a new incoming connection from this listener.
@@ -60,10 +214,335 @@ impl TcpListener {
     /// [`TcpStream`]: struct@crate::net::TcpStream
     pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
         let (socket, socket_addr) = self.inner.accept().await?;
-        let stream = TcpStream { inner: socket };
-        let socket_addr = socket_addr.ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Could not get socket IP address")
-        })?;
-        Ok((stream, socket_addr))
+        accepted(socket, socket_addr)
+    }
+
+    /// Returns a stream of the incoming connections on this listener.
+    ///
+    /// The stream never ends. Each item is the result of an
+    /// [`accept`](TcpListener::accept) call, errors do not stop the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::future::poll_fn;
+    /// use std::pin::Pin;
+    ///
+    /// use futures_core::Stream;
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// tokio_uring::start(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap()).unwrap();
+    ///     let mut incoming = listener.incoming();
+    ///
+    ///     while let Some(res) = poll_fn(|cx| Pin::new(&mut incoming).poll_next(cx)).await {
+    ///         let (stream, addr) = res.unwrap();
+    ///         println!("connection from {}", addr);
+    ///     }
+    /// });
+    /// ```
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming {
+            listener: self,
+            op: None,
+        }
+    }
+
+    /// Accepts connections, running `handler` on a new task for each of them.
+    ///
+    /// At most `limit` connections are handled concurrently. Once the limit is
+    /// reached, no more connections are accepted until a handler task
+    /// completes. Pending connections wait in the listen backlog meanwhile.
+    ///
+    /// This function only returns on failure to accept a connection.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::buf::IoBuf;
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// tokio_uring::start(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap()).unwrap();
+    ///
+    ///     // Echo the first message of up to 100 clients at a time
+    ///     let res = listener
+    ///         .serve(100, |stream, _addr| async move {
+    ///             let (res, buf) = stream.read(vec![0; 4096]).await;
+    ///
+    ///             if let Ok(n) = res {
+    ///                 let (_, _) = stream.write(buf.slice(..n)).await;
+    ///             }
+    ///         })
+    ///         .await;
+    ///
+    ///     res.unwrap();
+    /// });
+    /// ```
+    pub async fn serve<F, Fut>(&self, limit: usize, handler: F) -> io::Result<()>
+    where
+        F: FnMut(TcpStream, SocketAddr) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.serve_with_shutdown(limit, handler, std::future::pending())
+            .await
+    }
+
+    /// Accepts connections like [`serve`](TcpListener::serve), until
+    /// `shutdown` completes, then waits for the handlers of the connections
+    /// accepted to complete, draining them.
+    ///
+    /// A connection being accepted when `shutdown` completes may be closed
+    /// without reaching a handler. Handlers of long-lived connections must
+    /// end on their own for the draining to complete, for example by
+    /// watching the same signal as `shutdown`. To bound the draining, wrap
+    /// the returned future with [`time::timeout`](crate::time::timeout):
+    /// the handlers still running keep running on their tasks.
+    ///
+    /// Returns early on failure to accept a connection, without draining.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `limit` is zero.
+    ///
+    /// To serve a `tower::Service`, see `serve_service`, with the `tower`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// Echoing the first message of each client, until the server receives
+    /// a message on a channel:
+    ///
+    /// ```no_run
+    /// use tokio_uring::buf::IoBuf;
+    /// use tokio_uring::net::TcpListener;
+    ///
+    /// tokio_uring::start(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap()).unwrap();
+    ///     let (stop, stopped) = tokio_uring::sync::oneshot::channel::<()>();
+    ///
+    ///     listener
+    ///         .serve_with_shutdown(
+    ///             1000,
+    ///             |stream, _addr| async move {
+    ///                 let (res, buf) = stream.read(vec![0; 4096]).await;
+    ///                 if let Ok(n) = res {
+    ///                     let (_, _) = stream.write(buf.slice(..n)).await;
+    ///                 }
+    ///             },
+    ///             async move {
+    ///                 let _ = stopped.await;
+    ///             },
+    ///         )
+    ///         .await
+    ///         .unwrap();
+    /// });
+    /// ```
+    pub async fn serve_with_shutdown<F, Fut, S>(
+        &self,
+        limit: usize,
+        mut handler: F,
+        shutdown: S,
+    ) -> io::Result<()>
+    where
+        F: FnMut(TcpStream, SocketAddr) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+        S: Future<Output = ()>,
+    {
+        assert!(limit > 0, "connection limit must be positive");
+
+        let slots = Rc::new(RefCell::new(Slots {
+            available: limit,
+            waker: None,
+        }));
+
+        let accept = async {
+            loop {
+                crate::future::poll_fn(|cx| slots.borrow_mut().poll_acquire(cx)).await;
+
+                // Released if accepting fails, or is interrupted by shutdown
+                let permit = Permit(slots.clone());
+                let (stream, addr) = self.accept().await?;
+
+                let fut = handler(stream, addr);
+
+                crate::spawn(async move {
+                    // Released once the handler completes, or when the task is
+                    // dropped on shutdown.
+                    let _permit = permit;
+                    fut.await;
+                });
+            }
+        };
+
+        {
+            tokio::pin!(accept);
+            tokio::pin!(shutdown);
+
+            crate::future::poll_fn(|cx| {
+                if shutdown.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(Ok::<_, io::Error>(()));
+                }
+                accept.as_mut().poll(cx)
+            })
+            .await?;
+        }
+
+        // The accept loop was dropped, releasing the slot it held
+        crate::future::poll_fn(|cx| slots.borrow_mut().poll_drained(cx, limit)).await;
+        Ok(())
+    }
+
+    /// Accepts connections like
+    /// [`serve_with_shutdown`](TcpListener::serve_with_shutdown), calling a
+    /// clone of `service` with each of them on a new task.
+    ///
+    /// The clone is called once ready. The responses are dropped, and so are
+    /// the errors, of the calls and of the readiness of the clones: a
+    /// service reports its errors itself, for example with
+    /// `tower::ServiceExt::map_err`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::convert::Infallible;
+    ///
+    /// use tokio_uring::net::{TcpListener, TcpStream};
+    ///
+    /// tokio_uring::start(async {
+    ///     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap()).unwrap();
+    ///
+    ///     let service = tower::service_fn(|stream: TcpStream| async move {
+    ///         let (res, _) = stream.write(&b"hello\n"[..]).await;
+    ///         res
+    ///     });
+    ///
+    ///     listener
+    ///         .serve_service(1000, service, std::future::pending())
+    ///         .await
+    ///         .unwrap();
+    /// });
+    /// ```
+    #[cfg(feature = "tower")]
+    pub async fn serve_service<T, S>(&self, limit: usize, service: T, shutdown: S) -> io::Result<()>
+    where
+        T: tower::Service<TcpStream> + Clone + 'static,
+        T::Future: 'static,
+        S: Future<Output = ()>,
+    {
+        let handler = |stream, _| {
+            let mut service = service.clone();
+            async move {
+                if crate::future::poll_fn(|cx| service.poll_ready(cx))
+                    .await
+                    .is_ok()
+                {
+                    let _ = service.call(stream).await;
+                }
+            }
+        };
+
+        self.serve_with_shutdown(limit, handler, shutdown).await
+    }
+}
+
+/// Stream of the connections accepted by a [`TcpListener`].
+///
+/// Created by [`TcpListener::incoming`].
+pub struct Incoming<'a> {
+    listener: &'a TcpListener,
+
+    /// In-flight accept
+    op: Option<Op<Accept>>,
+}
+
+impl Stream for Incoming<'_> {
+    type Item = io::Result<(TcpStream, SocketAddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = &mut *self;
+
+        let op = match &mut me.op {
+            Some(op) => op,
+            None => match me.listener.inner.accept_op() {
+                Ok(op) => me.op.insert(op),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            },
+        };
+
+        let res = ready!(Socket::poll_accept(op, cx));
+        me.op = None;
+
+        Poll::Ready(Some(
+            res.and_then(|(socket, socket_addr)| accepted(socket, socket_addr)),
+        ))
+    }
+}
+
+/// Connection slots shared by `serve` and its handler tasks.
+struct Slots {
+    available: usize,
+    waker: Option<Waker>,
+}
+
+impl Slots {
+    fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.available == 0 {
+            self.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        self.available -= 1;
+        Poll::Ready(())
+    }
+
+    /// Waits for all the `limit` slots to be released.
+    fn poll_drained(&mut self, cx: &mut Context<'_>, limit: usize) -> Poll<()> {
+        if self.available < limit {
+            self.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        Poll::Ready(())
+    }
+
+    fn release(&mut self) {
+        self.available += 1;
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+struct Permit(Rc<RefCell<Slots>>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.borrow_mut().release();
+    }
+}
+
+fn accepted(
+    socket: Socket,
+    socket_addr: Option<SocketAddr>,
+) -> io::Result<(TcpStream, SocketAddr)> {
+    let stream = TcpStream { inner: socket };
+    let socket_addr =
+        socket_addr.ok_or_else(|| io::Error::other("Could not get socket IP address"))?;
+    Ok((stream, socket_addr))
+}
+
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
     }
 }
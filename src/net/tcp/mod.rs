@@ -1,5 +1,17 @@
+mod info;
+pub use info::TcpInfo;
+
+mod keepalive;
+pub use keepalive::KeepaliveConfig;
+
 mod listener;
-pub use listener::TcpListener;
+pub use listener::{Incoming, TcpListener};
+
+mod options;
+pub use options::TcpOptions;
+
+mod serve_file;
+pub use serve_file::serve_file_range;
 
 mod stream;
 pub use stream::TcpStream;
@@ -0,0 +1,86 @@
+use super::{TcpListener, TcpStream};
+use crate::driver::Socket;
+
+use std::{io, net::SocketAddr};
+
+/// Options to create TCP listeners and streams with.
+///
+/// # Examples
+///
+/// Serving over Multipath TCP, for clients able to use several network paths
+/// at once, such as Wi-Fi and cellular:
+///
+/// ```no_run
+/// use tokio_uring::net::TcpOptions;
+///
+/// fn main() -> std::io::Result<()> {
+///     let listener = TcpOptions::new()
+///         .mptcp(true)
+///         .bind("0.0.0.0:8080".parse().unwrap())?;
+///
+///     tokio_uring::start(async move {
+///         let (stream, addr) = listener.accept().await?;
+///         println!("connection from {}", addr);
+///         Ok(())
+///     })
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TcpOptions {
+    mptcp: bool,
+}
+
+impl TcpOptions {
+    /// Creates a blank new set of options ready for configuration.
+    ///
+    /// By default, the sockets are plain TCP sockets.
+    pub fn new() -> TcpOptions {
+        TcpOptions { mptcp: false }
+    }
+
+    /// Sets the option to create Multipath TCP sockets, `IPPROTO_MPTCP`.
+    ///
+    /// A Multipath TCP connection spreads over several subflows, one per
+    /// network path, and falls back to regular TCP when the peer does not
+    /// support Multipath TCP. When the kernel does not support it, or it is
+    /// disabled by the `net.mptcp.enabled` sysctl, TCP sockets are created
+    /// instead.
+    pub fn mptcp(&mut self, mptcp: bool) -> &mut TcpOptions {
+        self.mptcp = mptcp;
+        self
+    }
+
+    /// Creates a listener with the options specified by `self`, bound to the
+    /// specified address.
+    ///
+    /// See [`TcpListener::bind`].
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<TcpListener> {
+        let socket = if self.mptcp {
+            Socket::bind_mptcp(addr)?
+        } else {
+            Socket::bind(addr, libc::SOCK_STREAM)?
+        };
+        socket.listen(1024)?;
+        Ok(TcpListener { inner: socket })
+    }
+
+    /// Opens a connection to a remote host with the options specified by
+    /// `self`.
+    ///
+    /// See [`TcpStream::connect`].
+    pub async fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let socket = if self.mptcp {
+            Socket::new_mptcp(addr)?
+        } else {
+            Socket::new(addr, libc::SOCK_STREAM)?
+        };
+        socket.connect(socket2::SockAddr::from(addr)).await?;
+        Ok(TcpStream { inner: socket })
+    }
+}
+
+impl Default for TcpOptions {
+    fn default() -> TcpOptions {
+        TcpOptions::new()
+    }
+}
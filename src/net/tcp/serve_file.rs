@@ -0,0 +1,166 @@
+use crate::buf::IoBuf;
+use crate::driver::{self, SharedFd};
+use crate::fs::File;
+use crate::net::TcpStream;
+
+use std::cmp;
+use std::convert::TryFrom;
+use std::io;
+use std::ops::Range;
+
+use io_uring::opcode;
+
+/// Size requested for the pipe of the splices, fewer round trips than the
+/// default of 64 KiB.
+const PIPE_SIZE: libc::c_int = 1024 * 1024;
+
+/// Size of the reads of the fallback.
+const CHUNK_SIZE: usize = 128 * 1024;
+
+/// Sends the bytes of `range` of `file` over `stream`, returning the number of
+/// bytes sent, such as for the response to an HTTP range request.
+///
+/// When the ring supports `IORING_OP_SPLICE`, the bytes are spliced from the
+/// page cache to the socket through a pipe, without being copied to user
+/// space, like `sendfile(2)`. Otherwise, or if the file does not support
+/// splicing, they are read into a buffer and written to the stream. The
+/// kernels supporting zero-copy sends, `IORING_OP_SEND_ZC`, all support
+/// splicing, which is used instead.
+///
+/// Short transfers are resumed until the whole range is sent. Fewer bytes
+/// are sent if the file ends before the end of the range. On error, the
+/// number of bytes the peer received is unknown, and the connection should be
+/// closed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::File;
+/// use tokio_uring::net::{serve_file_range, TcpListener};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap())?;
+///         let file = File::open("video.mp4").await?;
+///
+///         let (stream, _) = listener.accept().await?;
+///         // Headers of the response omitted
+///         let sent = serve_file_range(&stream, &file, 1024..1024 * 1024).await?;
+///         println!("sent {} bytes", sent);
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub async fn serve_file_range(
+    stream: &TcpStream,
+    file: &File,
+    range: Range<u64>,
+) -> io::Result<u64> {
+    if range.start >= range.end {
+        return Ok(0);
+    }
+
+    if driver::supports(opcode::Splice::CODE) {
+        match splice_range(stream.inner.shared_fd(), &file.fd, range.clone()).await {
+            // The file does not support splicing, before sending anything
+            Err((e, 0)) if is_unsupported(&e) => {}
+            Err((e, _)) => return Err(e),
+            Ok(sent) => return Ok(sent),
+        }
+    }
+
+    copy_range(stream, file, range).await
+}
+
+/// Splices the range through a pipe, returning the error along with the
+/// number of bytes sent.
+async fn splice_range(
+    socket: &SharedFd,
+    file: &SharedFd,
+    range: Range<u64>,
+) -> Result<u64, (io::Error, u64)> {
+    let (rx, tx) = pipe().map_err(|e| (e, 0))?;
+
+    // Best effort: the default capacity works, with more round trips
+    let _ = syscall!(fcntl(tx.raw_fd(), libc::F_SETPIPE_SZ, PIPE_SIZE));
+    let capacity = syscall!(fcntl(tx.raw_fd(), libc::F_GETPIPE_SZ)).map_err(|e| (e, 0))?;
+
+    let len = range.end - range.start;
+    let mut sent = 0;
+
+    while sent < len {
+        let chunk = cmp::min(len - sent, capacity as u64) as u32;
+        let offset = i64::try_from(range.start + sent)
+            .map_err(|_| (io::Error::from_raw_os_error(libc::EINVAL), sent))?;
+
+        let mut filled = driver::splice(file, offset, &tx, -1, chunk)
+            .await
+            .map_err(|e| (e, sent))?;
+        if filled == 0 {
+            // End of file
+            break;
+        }
+
+        // Drain the pipe into the socket
+        while filled > 0 {
+            let n = driver::splice(&rx, -1, socket, -1, filled as u32)
+                .await
+                .map_err(|e| (e, sent))?;
+            if n == 0 {
+                return Err((io::ErrorKind::WriteZero.into(), sent));
+            }
+            filled -= n;
+            sent += n as u64;
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Reads the range into a buffer and writes it to the stream.
+async fn copy_range(stream: &TcpStream, file: &File, range: Range<u64>) -> io::Result<u64> {
+    let len = range.end - range.start;
+    let mut buf = Vec::with_capacity(cmp::min(len, CHUNK_SIZE as u64) as usize);
+    let mut sent = 0;
+
+    while sent < len {
+        // The bytes read past the end of the range are not sent
+        buf.clear();
+        let (res, b) = file.read_at(buf, range.start + sent).await;
+        buf = b;
+        let filled = cmp::min(res? as u64, len - sent) as usize;
+        if filled == 0 {
+            break;
+        }
+
+        let mut written = 0;
+        while written < filled {
+            let (res, slice) = stream.write(buf.slice(written..filled)).await;
+            buf = slice.into_inner();
+            match res? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => written += n,
+            }
+        }
+
+        sent += filled as u64;
+    }
+
+    Ok(sent)
+}
+
+fn pipe() -> io::Result<(SharedFd, SharedFd)> {
+    let mut fds = [-1; 2];
+    syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+    Ok((SharedFd::new(fds[0]), SharedFd::new(fds[1])))
+}
+
+/// Returns `true` for the errors of splicing from a file which does not
+/// support it, or with a ring restricted from splicing.
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EINVAL) | Some(libc::EOPNOTSUPP) | Some(libc::EACCES)
+    )
+}
@@ -1,8 +1,16 @@
-use std::{io, net::SocketAddr};
+use std::{
+    io,
+    net::SocketAddr,
+    os::unix::io::{AsRawFd, RawFd},
+    time::Duration,
+};
+
+use socket2::{SockRef, TcpKeepalive};
 
 use crate::{
     buf::{IoBuf, IoBufMut},
     driver::Socket,
+    net::{KeepaliveConfig, RecvFlags, SendFlags, TcpInfo},
 };
 
 /// A TCP stream between a local and a remote socket.
@@ -34,7 +42,7 @@ use crate::{
 /// [`accepting`]: crate::net::TcpListener::accept
 /// [`listener`]: crate::net::TcpListener
 pub struct TcpStream {
-    pub(super) inner: Socket,
+    pub(crate) inner: Socket,
 }
 
 impl TcpStream {
@@ -43,7 +51,172 @@ impl TcpStream {
         let socket = Socket::new(addr, libc::SOCK_STREAM)?;
         socket.connect(socket2::SockAddr::from(addr)).await?;
         let tcp_stream = TcpStream { inner: socket };
-        return Ok(tcp_stream);
+        Ok(tcp_stream)
+    }
+
+    /// Opens a TCP connection to a remote host at the given `SocketAddr`,
+    /// sending `buf` with TCP Fast Open. On success, returns the stream and
+    /// the number of bytes of `buf` sent, which may be less than its length.
+    ///
+    /// Once the host granted a Fast Open cookie on a previous connection, the
+    /// data is carried in the SYN and the host may process it one round trip
+    /// earlier. Otherwise the connection falls back to a regular handshake,
+    /// sending the data once established, so the call always succeeds on a
+    /// host without Fast Open support. The host enables Fast Open with
+    /// [`TcpListener::set_fastopen`].
+    ///
+    /// The data in the SYN may be delivered twice if the SYN is duplicated by
+    /// the network, so Fast Open suits idempotent requests.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let request = b"GET / HTTP/1.0\r\n\r\n".as_slice();
+    ///         let addr = "127.0.0.1:8080".parse().unwrap();
+    ///
+    ///         let (res, _) = TcpStream::connect_with_data(addr, request).await;
+    ///         let (stream, n) = res?;
+    ///         assert_eq!(n, request.len());
+    ///
+    ///         let (res, buf) = stream.read(vec![0; 4096]).await;
+    ///         println!("{:?}", &buf[..res?]);
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// [`TcpListener::set_fastopen`]: crate::net::TcpListener::set_fastopen
+    pub async fn connect_with_data<T: IoBuf>(
+        addr: SocketAddr,
+        buf: T,
+    ) -> crate::BufResult<(TcpStream, usize), T> {
+        let socket = match Socket::new(addr, libc::SOCK_STREAM) {
+            Ok(socket) => socket,
+            Err(e) => return (Err(e), buf),
+        };
+
+        let (res, buf) = socket
+            .connect_with_data(socket2::SockAddr::from(addr), buf)
+            .await;
+        (res.map(|n| (TcpStream { inner: socket }, n)), buf)
+    }
+
+    /// Returns `true` if the stream is a Multipath TCP socket, created by
+    /// [`TcpOptions::mptcp`](crate::net::TcpOptions::mptcp) or accepted by
+    /// such a listener.
+    ///
+    /// The connection may still have fallen back to regular TCP, if the peer
+    /// does not support Multipath TCP.
+    pub fn is_mptcp(&self) -> io::Result<bool> {
+        self.inner.is_mptcp()
+    }
+
+    /// Returns the CPU which handled the last packet received on the
+    /// connection, `SO_INCOMING_CPU`.
+    ///
+    /// See [`TcpListener::set_reuseport_cpu_steering`].
+    ///
+    /// [`TcpListener::set_reuseport_cpu_steering`]: crate::net::TcpListener::set_reuseport_cpu_steering
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        self.inner.incoming_cpu()
+    }
+
+    /// Returns the statistics of the connection tracked by the kernel, such as
+    /// the round-trip time, the congestion window and the retransmissions.
+    ///
+    /// The statistics are read with a plain `getsockopt(2)` call rather than
+    /// through the ring, so the operations in flight are not delayed. Reading
+    /// them periodically, rather than around every operation, keeps them off
+    /// the hot path.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::TcpStream;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     tokio_uring::start(async {
+    ///         let stream = TcpStream::connect("127.0.0.1:8080".parse().unwrap()).await?;
+    ///
+    ///         let info = stream.tcp_info()?;
+    ///         println!("rtt: {:?}, cwnd: {}", info.rtt(), info.cwnd());
+    ///
+    ///         Ok(())
+    ///     })
+    /// }
+    /// ```
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        TcpInfo::from_fd(self.inner.as_raw_fd())
+    }
+
+    /// Enables keepalive probes on the connection, with the given parameters,
+    /// so that a dead peer is detected on an idle connection.
+    ///
+    /// See [`KeepaliveConfig`] for an example.
+    pub fn set_keepalive(&self, config: KeepaliveConfig) -> io::Result<()> {
+        let mut keepalive = TcpKeepalive::new();
+        if let Some(idle) = config.idle {
+            keepalive = keepalive.with_time(idle);
+        }
+        if let Some(interval) = config.interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        if let Some(count) = config.count {
+            keepalive = keepalive.with_retries(count);
+        }
+
+        // Also sets `SO_KEEPALIVE`
+        SockRef::from(&self.inner).set_tcp_keepalive(&keepalive)
+    }
+
+    /// Returns the keepalive parameters of the connection, or `None` if
+    /// keepalive probes are disabled.
+    ///
+    /// All the parameters are set, to the system defaults if they were not
+    /// configured.
+    pub fn keepalive(&self) -> io::Result<Option<KeepaliveConfig>> {
+        let socket = SockRef::from(&self.inner);
+        if !socket.keepalive()? {
+            return Ok(None);
+        }
+
+        Ok(Some(KeepaliveConfig {
+            idle: Some(socket.keepalive_time()?),
+            interval: Some(socket.keepalive_interval()?),
+            count: Some(socket.keepalive_retries()?),
+        }))
+    }
+
+    /// Disables keepalive probes on the connection.
+    pub fn disable_keepalive(&self) -> io::Result<()> {
+        SockRef::from(&self.inner).set_keepalive(false)
+    }
+
+    /// Sets the time data may remain unacknowledged before the connection is
+    /// reset, `TCP_USER_TIMEOUT`. A timeout of zero restores the system
+    /// default.
+    ///
+    /// Unlike keepalive probes, the timeout also covers connections with data
+    /// in flight, such as a write to a peer which vanished. When keepalive is
+    /// enabled as well, the timeout also bounds the time spent probing.
+    pub fn set_user_timeout(&self, timeout: Duration) -> io::Result<()> {
+        let timeout = if timeout.is_zero() {
+            None
+        } else {
+            Some(timeout)
+        };
+        SockRef::from(&self.inner).set_tcp_user_timeout(timeout)
+    }
+
+    /// Returns the time data may remain unacknowledged before the connection
+    /// is reset, or `None` if the system default is used.
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        SockRef::from(&self.inner).tcp_user_timeout()
     }
 
     /// Read some data from the stream into the buffer, returning the original buffer and
@@ -52,9 +225,51 @@ impl TcpStream {
         self.inner.read(buf).await
     }
 
+    /// Reads until the end of the stream, appending to `buf`, and returns
+    /// the buffer with the number of bytes read.
+    ///
+    /// The bytes are read into the spare capacity of `buf`, whose capacity
+    /// doubles each time it fills up. On error, `buf` holds the data read
+    /// until then.
+    pub async fn read_to_end(&self, buf: Vec<u8>) -> crate::BufResult<usize, Vec<u8>> {
+        crate::io::read_to_end(buf, move |buf| self.read(buf)).await
+    }
+
     /// Write some data to the stream from the buffer, returning the original buffer and
     /// quantity of data written.
     pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
         self.inner.write(buf).await
     }
+
+    /// Read some data from the stream into the buffer with the given flags,
+    /// returning the original buffer and quantity of data read.
+    ///
+    /// With [`RecvFlags::WAITALL`], the read only completes once the buffer
+    /// is full, unless the stream ends or an error occurs first.
+    pub async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
+    }
+
+    /// Write some data to the stream from the buffer with the given flags,
+    /// returning the original buffer and quantity of data written.
+    ///
+    /// Passing [`SendFlags::NOSIGNAL`] makes writing to a stream closed by the
+    /// peer fail with `EPIPE` instead of raising `SIGPIPE`.
+    pub async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
 }
@@ -1,9 +1,17 @@
 use crate::{
     buf::{IoBuf, IoBufMut},
-    driver::Socket,
+    driver::{Op, RecvFrom, SendTo, Socket},
+    net::{RecvFlags, SendFlags},
 };
+use futures_core::Stream;
 use socket2::SockAddr;
-use std::{io, net::SocketAddr};
+use std::{
+    io,
+    net::SocketAddr,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 /// A UDP socket.
 ///
@@ -92,6 +100,29 @@ impl UdpSocket {
         Ok(UdpSocket { inner: socket })
     }
 
+    /// Returns the CPU the socket prefers, or which handled its last incoming
+    /// datagram, `SO_INCOMING_CPU`.
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        self.inner.incoming_cpu()
+    }
+
+    /// Sets the CPU the socket prefers among the sockets bound to the same
+    /// address, `SO_INCOMING_CPU`.
+    ///
+    /// See [`TcpListener::set_incoming_cpu`](crate::net::TcpListener::set_incoming_cpu).
+    pub fn set_incoming_cpu(&self, cpu: usize) -> io::Result<()> {
+        self.inner.set_incoming_cpu(cpu)
+    }
+
+    /// Steers the datagrams among the sockets bound to the same address by the
+    /// CPU handling them: a datagram handled by CPU `n` goes to the socket
+    /// bound `n % group_size`-th, counting from zero.
+    ///
+    /// See [`TcpListener::set_reuseport_cpu_steering`](crate::net::TcpListener::set_reuseport_cpu_steering).
+    pub fn set_reuseport_cpu_steering(&self, group_size: u32) -> io::Result<()> {
+        self.inner.set_reuseport_cpu_steering(group_size)
+    }
+
     /// Connects this UDP socket to a remote address, allowing the `write` and
     /// `read` syscalls to be used to send data and also applies filters to only
     /// receive data from the specified address.
@@ -110,13 +141,114 @@ impl UdpSocket {
         buf: T,
         socket_addr: SocketAddr,
     ) -> crate::BufResult<usize, T> {
-        self.inner.send_to(buf, socket_addr).await
+        self.inner.send_to(buf, socket_addr, 0).await
+    }
+
+    /// Sends data on the socket to the given address, with the given flags.
+    /// On success, returns the number of bytes written.
+    pub async fn send_to_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        socket_addr: SocketAddr,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send_to(buf, socket_addr, flags.bits()).await
+    }
+
+    /// Sends data on the socket to the remote address it is connected to,
+    /// with the given flags. On success, returns the number of bytes written.
+    ///
+    /// With [`SendFlags::MORE`], the data is corked and sent as a single
+    /// datagram together with the data of the following sends, up to the first
+    /// send without the flag.
+    pub async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
     }
 
     /// Receives a single datagram message on the socket. On success, returns
     /// the number of bytes read and the origin.
     pub async fn recv_from<T: IoBufMut>(&self, buf: T) -> crate::BufResult<(usize, SocketAddr), T> {
-        self.inner.recv_from(buf).await
+        self.inner.recv_from(buf, 0).await
+    }
+
+    /// Receives a single datagram message on the socket, with the given flags.
+    /// On success, returns the number of bytes read and the origin.
+    ///
+    /// See [`recv_with_flags`](UdpSocket::recv_with_flags) for the meaning of
+    /// the returned length with [`RecvFlags::TRUNC`].
+    pub async fn recv_from_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<(usize, SocketAddr), T> {
+        self.inner.recv_from(buf, flags.bits()).await
+    }
+
+    /// Receives a single datagram message on the socket from the remote
+    /// address it is connected to. On success, returns the number of bytes
+    /// read.
+    ///
+    /// If the datagram is longer than the buffer, the excess is discarded.
+    /// Use [`recv_with_flags`](UdpSocket::recv_with_flags) with
+    /// [`RecvFlags::TRUNC`] to learn the length of the datagram.
+    pub async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, 0).await
+    }
+
+    /// Receives a single datagram message on the socket from the remote
+    /// address it is connected to, without removing it from the receive
+    /// queue. On success, returns the number of bytes read.
+    pub async fn peek<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, libc::MSG_PEEK).await
+    }
+
+    /// Receives a single datagram message on the socket from the remote
+    /// address it is connected to, with the given flags.
+    ///
+    /// With [`RecvFlags::TRUNC`], the returned length is the length of the
+    /// datagram, which is greater than the buffer capacity if the datagram was
+    /// truncated. Combined with [`RecvFlags::PEEK`], this sizes a buffer
+    /// before receiving the datagram.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{RecvFlags, UdpSocket};
+    /// use std::net::SocketAddr;
+    ///
+    /// tokio_uring::start(async {
+    ///     let first_addr: SocketAddr = "127.0.0.1:2402".parse().unwrap();
+    ///     let second_addr: SocketAddr = "127.0.0.1:2403".parse().unwrap();
+    ///
+    ///     let socket = UdpSocket::bind(first_addr).await.unwrap();
+    ///     let other = UdpSocket::bind(second_addr).await.unwrap();
+    ///     socket.connect(second_addr).await.unwrap();
+    ///     other.connect(first_addr).await.unwrap();
+    ///
+    ///     let (res, _) = other.write(vec![1; 3000]).await;
+    ///     res.unwrap();
+    ///
+    ///     // Learn the length of the datagram, leaving it in the queue
+    ///     let flags = RecvFlags::PEEK | RecvFlags::TRUNC;
+    ///     let (res, _) = socket.recv_with_flags(vec![0; 1], flags).await;
+    ///     let len = res.unwrap();
+    ///     assert_eq!(len, 3000);
+    ///
+    ///     let (res, buf) = socket.recv(Vec::with_capacity(len)).await;
+    ///     assert_eq!(res.unwrap(), 3000);
+    ///     assert_eq!(buf.len(), 3000);
+    /// });
+    /// ```
+    pub async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
     }
 
     /// Read a packet of data from the socket into the buffer, returning the original buffer and
@@ -130,4 +262,195 @@ impl UdpSocket {
     pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
         self.inner.write(buf).await
     }
+
+    /// Returns a stream of the datagrams received on this socket, with their
+    /// origin.
+    ///
+    /// Each datagram is received into a new buffer of `buf_size` bytes. If a
+    /// datagram is longer, the excess is discarded, as with
+    /// [`recv_from`](UdpSocket::recv_from). The stream never ends, errors do
+    /// not stop it.
+    ///
+    /// # Examples
+    ///
+    /// Echoing the first datagrams back to their sender:
+    ///
+    /// ```no_run
+    /// use std::future::poll_fn;
+    /// use std::pin::Pin;
+    ///
+    /// use futures_core::Stream;
+    /// use tokio_uring::net::UdpSocket;
+    ///
+    /// tokio_uring::start(async {
+    ///     let socket = UdpSocket::bind("127.0.0.1:8080".parse().unwrap()).await.unwrap();
+    ///     let mut datagrams = socket.datagrams(1500);
+    ///     let mut sink = socket.sink();
+    ///
+    ///     while let Some(res) = poll_fn(|cx| Pin::new(&mut datagrams).poll_next(cx)).await {
+    ///         let (buf, addr) = res.unwrap();
+    ///         poll_fn(|cx| sink.poll_ready(cx)).await.unwrap();
+    ///         sink.start_send((buf, addr)).unwrap();
+    ///     }
+    /// });
+    /// ```
+    pub fn datagrams(&self, buf_size: usize) -> Datagrams<'_> {
+        Datagrams {
+            socket: self,
+            buf_size,
+            op: None,
+        }
+    }
+
+    /// Returns a sink sending datagrams, each to the address it is paired
+    /// with.
+    ///
+    /// See [`DatagramSink`].
+    pub fn sink<T: IoBuf>(&self) -> DatagramSink<'_, T> {
+        DatagramSink {
+            socket: self,
+            op: None,
+        }
+    }
+}
+
+/// Stream of the datagrams received by a [`UdpSocket`].
+///
+/// Created by [`UdpSocket::datagrams`]. A receive is in flight while the
+/// stream is polled, dropping the stream cancels it.
+pub struct Datagrams<'a> {
+    socket: &'a UdpSocket,
+    buf_size: usize,
+
+    /// In-flight receive
+    op: Option<Op<RecvFrom<Vec<u8>>>>,
+}
+
+impl Stream for Datagrams<'_> {
+    type Item = io::Result<(Vec<u8>, SocketAddr)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = &mut *self;
+
+        let op = match &mut me.op {
+            Some(op) => op,
+            None => match me
+                .socket
+                .inner
+                .recv_from_op(Vec::with_capacity(me.buf_size), 0)
+            {
+                Ok(op) => me.op.insert(op),
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            },
+        };
+
+        let (res, buf) = ready!(Socket::poll_recv_from(op, cx));
+        me.op = None;
+
+        Poll::Ready(Some(res.map(|(_, addr)| (buf, addr))))
+    }
+}
+
+impl Drop for Datagrams<'_> {
+    fn drop(&mut self) {
+        // The receive would consume a datagram no one reads
+        if let Some(op) = &self.op {
+            op.request_cancel();
+        }
+    }
+}
+
+/// Sink of the datagrams sent by a [`UdpSocket`].
+///
+/// Created by [`UdpSocket::sink`]. Items are a buffer and the address to send
+/// it to. The methods follow the contract of the `Sink` trait of the
+/// `futures` crate, which the sink implements with the `futures-sink`
+/// feature: call [`poll_ready`](DatagramSink::poll_ready) until it is ready
+/// before each [`start_send`](DatagramSink::start_send), and
+/// [`poll_flush`](DatagramSink::poll_flush) once done.
+///
+/// A single datagram is in flight at a time. The error of a send is returned
+/// by the next call to `poll_ready` or `poll_flush`. Dropping the sink
+/// cancels the datagram in flight.
+pub struct DatagramSink<'a, T: IoBuf> {
+    socket: &'a UdpSocket,
+
+    /// In-flight send
+    op: Option<Op<SendTo<T>>>,
+}
+
+impl<T: IoBuf> DatagramSink<'_, T> {
+    /// Waits for the sink to accept a datagram, once the previous one was
+    /// sent.
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+
+    /// Starts sending a datagram.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a datagram is still in flight, because
+    /// [`poll_ready`](DatagramSink::poll_ready) was not ready.
+    pub fn start_send(&mut self, item: (T, SocketAddr)) -> io::Result<()> {
+        if self.op.is_some() {
+            return Err(io::Error::other("datagram sink is not ready"));
+        }
+
+        let (buf, addr) = item;
+        self.op = Some(self.socket.inner.send_to_op(buf, addr, 0)?);
+        Ok(())
+    }
+
+    /// Waits for the datagram in flight, if any, to be sent.
+    pub fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(op) = &mut self.op {
+            let (res, _) = ready!(op.poll_send(cx));
+            self.op = None;
+            res?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Waits for the datagram in flight, if any, to be sent. The socket is
+    /// not closed, it is shared with the other streams and sinks.
+    pub fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[cfg(feature = "futures-sink")]
+impl<T: IoBuf> futures_sink::Sink<(T, SocketAddr)> for DatagramSink<'_, T> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        DatagramSink::poll_ready(self.get_mut(), cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: (T, SocketAddr)) -> io::Result<()> {
+        DatagramSink::start_send(self.get_mut(), item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        DatagramSink::poll_flush(self.get_mut(), cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        DatagramSink::poll_close(self.get_mut(), cx)
+    }
+}
+
+impl<T: IoBuf> Drop for DatagramSink<'_, T> {
+    fn drop(&mut self) {
+        if let Some(op) = &self.op {
+            op.request_cancel();
+        }
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
 }
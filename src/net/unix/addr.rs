@@ -0,0 +1,138 @@
+use socket2::SockAddr;
+use std::{ffi::OsStr, fmt, io, os::unix::ffi::OsStrExt, path::Path};
+
+/// An address associated with a Unix socket.
+///
+/// The address is either a file system path, a name in the Linux abstract
+/// namespace, or unnamed, as for sockets which were never bound.
+#[derive(Clone)]
+pub struct UnixSocketAddr {
+    pub(crate) inner: SockAddr,
+}
+
+impl UnixSocketAddr {
+    /// Creates an address pointing to the given file system path.
+    ///
+    /// Returns an error if the path is too long to fit in a `sockaddr_un`.
+    pub fn from_pathname<P: AsRef<Path>>(path: P) -> io::Result<UnixSocketAddr> {
+        let path = path.as_ref();
+        if path.as_os_str().as_bytes().first() == Some(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path must not start with a null byte",
+            ));
+        }
+
+        let inner = SockAddr::unix(path)?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Creates an address in the Linux abstract namespace.
+    ///
+    /// The name is not null-terminated and may contain arbitrary bytes. Such
+    /// addresses have no presence in the file system, and go away once the
+    /// last socket bound to them is closed.
+    pub fn from_abstract_name<N: AsRef<[u8]>>(name: N) -> io::Result<UnixSocketAddr> {
+        // A leading null byte marks the path as abstract
+        let mut path = vec![0];
+        path.extend_from_slice(name.as_ref());
+
+        let inner = SockAddr::unix(OsStr::from_bytes(&path))?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Creates an unnamed address.
+    ///
+    /// Binding a socket to an unnamed address autobinds it: the kernel picks
+    /// a unique name in the Linux abstract namespace, which can be read back
+    /// with `local_addr`. Connecting from an autobound socket gives the peer
+    /// an address it can reply to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{UnixDatagram, UnixSocketAddr};
+    ///
+    /// tokio_uring::start(async {
+    ///     let socket = UnixDatagram::bind_addr(&UnixSocketAddr::unnamed()).unwrap();
+    ///     let addr = socket.local_addr().unwrap();
+    ///     assert!(addr.as_abstract_name().is_some());
+    /// });
+    /// ```
+    pub fn unnamed() -> UnixSocketAddr {
+        // Only the family, with no path
+        let (_, inner) = unsafe {
+            SockAddr::init(|storage, len| {
+                (*storage).ss_family = libc::AF_UNIX as libc::sa_family_t;
+                *len = std::mem::size_of::<libc::sa_family_t>() as libc::socklen_t;
+                Ok(())
+            })
+        }
+        .expect("initializing an unnamed address cannot fail");
+
+        UnixSocketAddr { inner }
+    }
+
+    /// Returns `true` if the address is unnamed.
+    pub fn is_unnamed(&self) -> bool {
+        self.sun_path().is_empty()
+    }
+
+    /// Returns the file system path of the address, if it has one.
+    pub fn as_pathname(&self) -> Option<&Path> {
+        match self.sun_path() {
+            [] | [0, ..] => None,
+            path => {
+                // Paths written by the kernel may include the null terminator
+                let path = match path.iter().position(|&b| b == 0) {
+                    Some(len) => &path[..len],
+                    None => path,
+                };
+                Some(Path::new(OsStr::from_bytes(path)))
+            }
+        }
+    }
+
+    /// Returns the name of the address in the Linux abstract namespace, if
+    /// it has one.
+    pub fn as_abstract_name(&self) -> Option<&[u8]> {
+        match self.sun_path() {
+            [0, name @ ..] => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The used part of `sun_path`, as given by the length of the address.
+    fn sun_path(&self) -> &[u8] {
+        // Safety: the address is always `AF_UNIX`, and its length never
+        // exceeds the size of `sockaddr_un`.
+        let storage: &libc::sockaddr_un = unsafe { &*self.inner.as_ptr().cast() };
+        let offset = std::mem::size_of::<libc::sa_family_t>();
+        let len = (self.inner.len() as usize)
+            .saturating_sub(offset)
+            .min(storage.sun_path.len());
+
+        unsafe { std::slice::from_raw_parts(storage.sun_path.as_ptr().cast(), len) }
+    }
+}
+
+impl fmt::Debug for UnixSocketAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.as_pathname() {
+            write!(f, "{:?} (pathname)", path)
+        } else if let Some(name) = self.as_abstract_name() {
+            write!(f, "\"{}\" (abstract)", name.escape_ascii())
+        } else {
+            f.write_str("(unnamed)")
+        }
+    }
+}
+
+impl PartialEq for UnixSocketAddr {
+    fn eq(&self, other: &UnixSocketAddr) -> bool {
+        self.as_pathname() == other.as_pathname()
+            && self.as_abstract_name() == other.as_abstract_name()
+    }
+}
+
+impl Eq for UnixSocketAddr {}
@@ -0,0 +1,246 @@
+use super::UnixSocketAddr;
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::Socket,
+    net::{RecvFlags, SendFlags},
+};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+    path::Path,
+};
+
+/// A Unix datagram socket.
+///
+/// Like UDP, Unix datagram sockets are connectionless: a `UnixDatagram` may
+/// exchange datagrams with many peers using [`send_to`] and [`recv_from`], or
+/// be [`connect`]ed to a single peer and use [`send`] and [`recv`]. Unlike UDP,
+/// datagrams are reliable and never reordered.
+///
+/// Besides file system paths, sockets may be bound to names in the Linux
+/// abstract namespace, see [`UnixSocketAddr::from_abstract_name`].
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::net::UnixDatagram;
+///
+/// fn main() -> std::io::Result<()> {
+///     let dir = tempfile::tempdir()?;
+///     let server_path = dir.path().join("server.sock");
+///     let client_path = dir.path().join("client.sock");
+///
+///     tokio_uring::start(async {
+///         let server = UnixDatagram::bind(&server_path)?;
+///         let client = UnixDatagram::bind(&client_path)?;
+///
+///         let (result, _) = client.send_to(b"hello".as_slice(), &server_path).await;
+///         result?;
+///
+///         let (result, buf) = server.recv_from(vec![0; 32]).await;
+///         let (n_bytes, addr) = result?;
+///
+///         assert_eq!(&buf[..n_bytes], b"hello");
+///         assert_eq!(addr.as_pathname(), Some(client_path.as_path()));
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`send_to`]: UnixDatagram::send_to
+/// [`recv_from`]: UnixDatagram::recv_from
+/// [`connect`]: UnixDatagram::connect
+/// [`send`]: UnixDatagram::send
+/// [`recv`]: UnixDatagram::recv
+pub struct UnixDatagram {
+    inner: Socket,
+}
+
+impl UnixDatagram {
+    /// Creates a new Unix datagram socket bound to the specified file path.
+    /// The file path cannot yet exist, and is not removed when the socket is
+    /// dropped.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixDatagram> {
+        let socket = Socket::bind_unix(path, libc::SOCK_DGRAM)?;
+        Ok(UnixDatagram { inner: socket })
+    }
+
+    /// Creates a new Unix datagram socket bound to the specified address,
+    /// which may be in the Linux abstract namespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{UnixDatagram, UnixSocketAddr};
+    ///
+    /// tokio_uring::start(async {
+    ///     let addr = UnixSocketAddr::from_abstract_name(b"tokio-uring-doc").unwrap();
+    ///     let server = UnixDatagram::bind_addr(&addr).unwrap();
+    ///
+    ///     let client = UnixDatagram::unbound().unwrap();
+    ///     let (res, _) = client.send_to_addr(b"ping".as_slice(), &addr).await;
+    ///     res.unwrap();
+    ///
+    ///     let (res, buf) = server.recv_from(vec![0; 4]).await;
+    ///     let (_, peer) = res.unwrap();
+    ///     assert_eq!(buf, b"ping");
+    ///     assert!(peer.is_unnamed());
+    /// });
+    /// ```
+    pub fn bind_addr(addr: &UnixSocketAddr) -> io::Result<UnixDatagram> {
+        let socket = Socket::bind_unix_sockaddr(addr.inner.clone(), libc::SOCK_DGRAM)?;
+        Ok(UnixDatagram { inner: socket })
+    }
+
+    /// Creates a new Unix datagram socket which is not bound to any address.
+    ///
+    /// Datagrams sent from an unbound socket have an unnamed origin, so the
+    /// peer cannot reply to them.
+    pub fn unbound() -> io::Result<UnixDatagram> {
+        let socket = Socket::new_unix(libc::SOCK_DGRAM)?;
+        Ok(UnixDatagram { inner: socket })
+    }
+
+    /// Creates an unnamed pair of connected Unix datagram sockets.
+    pub fn pair() -> io::Result<(UnixDatagram, UnixDatagram)> {
+        let (first, second) = Socket::pair_unix(libc::SOCK_DGRAM)?;
+        Ok((
+            UnixDatagram { inner: first },
+            UnixDatagram { inner: second },
+        ))
+    }
+
+    /// Returns the address the socket is bound to, such as the name picked by
+    /// the kernel when bound to [`UnixSocketAddr::unnamed`].
+    pub fn local_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.local_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Returns the address of the peer the socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.peer_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Connects the socket to the specified file path, allowing [`send`] and
+    /// [`recv`] to be used, and only receiving datagrams from that peer.
+    ///
+    /// [`send`]: UnixDatagram::send
+    /// [`recv`]: UnixDatagram::recv
+    pub async fn connect<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let addr = UnixSocketAddr::from_pathname(path)?;
+        self.connect_addr(&addr).await
+    }
+
+    /// Connects the socket to the specified address, which may be in the
+    /// Linux abstract namespace.
+    pub async fn connect_addr(&self, addr: &UnixSocketAddr) -> io::Result<()> {
+        self.inner.connect(addr.inner.clone()).await
+    }
+
+    /// Sends a datagram to the socket bound to the specified file path. On
+    /// success, returns the number of bytes written.
+    pub async fn send_to<T: IoBuf, P: AsRef<Path>>(
+        &self,
+        buf: T,
+        path: P,
+    ) -> crate::BufResult<usize, T> {
+        let addr = match UnixSocketAddr::from_pathname(path) {
+            Ok(addr) => addr,
+            Err(e) => return (Err(e), buf),
+        };
+        self.send_to_addr(buf, &addr).await
+    }
+
+    /// Sends a datagram to the specified address, which may be in the Linux
+    /// abstract namespace. On success, returns the number of bytes written.
+    pub async fn send_to_addr<T: IoBuf>(
+        &self,
+        buf: T,
+        addr: &UnixSocketAddr,
+    ) -> crate::BufResult<usize, T> {
+        self.inner
+            .send_to_sockaddr(buf, addr.inner.clone(), 0)
+            .await
+    }
+
+    /// Sends a datagram to the specified address, with the given flags. On
+    /// success, returns the number of bytes written.
+    pub async fn send_to_addr_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        addr: &UnixSocketAddr,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner
+            .send_to_sockaddr(buf, addr.inner.clone(), flags.bits())
+            .await
+    }
+
+    /// Receives a single datagram on the socket. On success, returns the
+    /// number of bytes read and the origin.
+    pub async fn recv_from<T: IoBufMut>(
+        &self,
+        buf: T,
+    ) -> crate::BufResult<(usize, UnixSocketAddr), T> {
+        self.recv_from_with_flags(buf, RecvFlags::empty()).await
+    }
+
+    /// Receives a single datagram on the socket, with the given flags. On
+    /// success, returns the number of bytes read and the origin.
+    pub async fn recv_from_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<(usize, UnixSocketAddr), T> {
+        let (res, buf) = self.inner.recv_from_sockaddr(buf, flags.bits()).await;
+        let res = res.map(|(n, inner)| (n, UnixSocketAddr { inner }));
+        (res, buf)
+    }
+
+    /// Sends a datagram to the peer the socket is connected to. On success,
+    /// returns the number of bytes written.
+    pub async fn send<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, 0).await
+    }
+
+    /// Sends a datagram to the peer the socket is connected to, with the
+    /// given flags. On success, returns the number of bytes written.
+    pub async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
+    }
+
+    /// Receives a single datagram on the socket. On success, returns the
+    /// number of bytes read.
+    ///
+    /// If the datagram is longer than the buffer, the excess is discarded.
+    pub async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, 0).await
+    }
+
+    /// Receives a single datagram on the socket, with the given flags. On
+    /// success, returns the number of bytes read.
+    ///
+    /// With [`RecvFlags::TRUNC`], the returned length is the length of the
+    /// datagram, which is greater than the buffer capacity if the datagram was
+    /// truncated.
+    pub async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
+    }
+}
+
+impl AsRawFd for UnixDatagram {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -1,6 +1,10 @@
-use super::UnixStream;
-use crate::driver::Socket;
-use std::{io, path::Path};
+use super::{UCred, UnixSocketAddr, UnixStream};
+use crate::{driver::Socket, net::listen_fds};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+    path::Path,
+};
 
 /// A Unix socket server, listening for connections.
 ///
@@ -46,6 +50,59 @@ impl UnixListener {
         Ok(UnixListener { inner: socket })
     }
 
+    /// Creates a new UnixListener bound to the specified address, which may
+    /// be in the Linux abstract namespace, or unnamed to let the kernel pick
+    /// an abstract name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio_uring::net::{UnixListener, UnixSocketAddr, UnixStream};
+    ///
+    /// tokio_uring::start(async {
+    ///     let listener = UnixListener::bind_addr(&UnixSocketAddr::unnamed()).unwrap();
+    ///     let addr = listener.local_addr().unwrap();
+    ///
+    ///     let (client, (_, peer)) = tokio::try_join!(
+    ///         UnixStream::connect_addr(&addr),
+    ///         listener.accept_with_addr(),
+    ///     )
+    ///     .unwrap();
+    ///
+    ///     assert_eq!(client.peer_addr().unwrap(), addr);
+    ///     assert!(peer.is_unnamed());
+    /// });
+    /// ```
+    pub fn bind_addr(addr: &UnixSocketAddr) -> io::Result<UnixListener> {
+        let socket = Socket::bind_unix_sockaddr(addr.inner.clone(), libc::SOCK_STREAM)?;
+        socket.listen(1024)?;
+        Ok(UnixListener { inner: socket })
+    }
+
+    /// Returns the address the socket is bound to, such as the name picked by
+    /// the kernel when bound to [`UnixSocketAddr::unnamed`].
+    pub fn local_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.local_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Adopts the listeners passed by the service manager for socket
+    /// activation, in the order they are configured in the `.socket` unit.
+    ///
+    /// Returns an empty list if the process was not socket activated. Returns
+    /// an error if any of the passed descriptors is not a listening Unix
+    /// stream socket, closing all of them. The `LISTEN_*` environment
+    /// variables are removed in either case, see [`sd_listen_fds`].
+    ///
+    /// [`sd_listen_fds`]: crate::net::sd_listen_fds
+    pub fn from_listen_fds() -> io::Result<Vec<UnixListener>> {
+        let sockets = listen_fds::listeners(&[socket2::Domain::UNIX])?;
+        Ok(sockets
+            .into_iter()
+            .map(|inner| UnixListener { inner })
+            .collect())
+    }
+
     /// Accepts a new incoming connection from this listener.
     ///
     /// This function will yield once a new Unix domain socket connection
@@ -58,4 +115,54 @@ impl UnixListener {
         let stream = UnixStream { inner: socket };
         Ok(stream)
     }
+
+    /// Accepts a new incoming connection from this listener, along with the
+    /// address of the peer.
+    ///
+    /// The address is unnamed unless the peer bound its socket before
+    /// connecting.
+    pub async fn accept_with_addr(&self) -> io::Result<(UnixStream, UnixSocketAddr)> {
+        let (socket, inner) = self.inner.accept_sockaddr().await?;
+        let stream = UnixStream { inner: socket };
+        Ok((stream, UnixSocketAddr { inner }))
+    }
+
+    /// Accepts a new incoming connection from this listener, along with the
+    /// credentials of the connecting process.
+    ///
+    /// The credentials are the ones of the peer when it connected, read with
+    /// `SO_PEERCRED` once the connection is accepted. They are suited to
+    /// authorize the requests of local clients.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tokio_uring::net::UnixListener;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let listener = UnixListener::bind("/run/my-daemon.sock")?;
+    ///
+    ///     tokio_uring::start(async move {
+    ///         loop {
+    ///             let (stream, cred) = listener.accept_with_cred().await?;
+    ///             if cred.uid() != 0 {
+    ///                 continue;
+    ///             }
+    ///
+    ///             stream.write(b"hello root".as_slice()).await.0?;
+    ///         }
+    ///     })
+    /// }
+    /// ```
+    pub async fn accept_with_cred(&self) -> io::Result<(UnixStream, UCred)> {
+        let stream = self.accept().await?;
+        let cred = stream.peer_cred()?;
+        Ok((stream, cred))
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
 }
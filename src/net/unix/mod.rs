@@ -1,5 +1,17 @@
+mod addr;
+pub use addr::UnixSocketAddr;
+
+mod datagram;
+pub use datagram::UnixDatagram;
+
 mod listener;
 pub use listener::UnixListener;
 
+mod seqpacket;
+pub use seqpacket::{UnixSeqpacket, UnixSeqpacketListener};
+
 mod stream;
 pub use stream::UnixStream;
+
+mod ucred;
+pub use ucred::UCred;
@@ -0,0 +1,201 @@
+use super::{UCred, UnixSocketAddr};
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::Socket,
+    net::{RecvFlags, SendFlags},
+};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+    path::Path,
+};
+
+/// A Unix sequenced-packet socket server, listening for connections.
+///
+/// Sequenced-packet sockets (`SOCK_SEQPACKET`) are connection oriented like
+/// streams, and preserve message boundaries like datagrams: each
+/// [`send`](UnixSeqpacket::send) is received by a single
+/// [`recv`](UnixSeqpacket::recv), in order.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::net::{UnixSeqpacket, UnixSeqpacketListener};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("server.sock");
+///
+/// tokio_uring::start(async {
+///     let listener = UnixSeqpacketListener::bind(&path).unwrap();
+///
+///     let (client, server) =
+///         tokio::try_join!(UnixSeqpacket::connect(&path), listener.accept()).unwrap();
+///
+///     client.send(b"hello".as_slice()).await.0.unwrap();
+///     client.send(b"world".as_slice()).await.0.unwrap();
+///
+///     // Each message is received on its own
+///     let (res, buf) = server.recv(vec![0; 32]).await;
+///     assert_eq!(&buf[..res.unwrap()], b"hello");
+///     let (res, buf) = server.recv(vec![0; 32]).await;
+///     assert_eq!(&buf[..res.unwrap()], b"world");
+/// });
+/// ```
+pub struct UnixSeqpacketListener {
+    inner: Socket,
+}
+
+/// A connected Unix sequenced-packet socket, preserving message boundaries.
+///
+/// A connection is established with [`connect`], by [`accepting`] it from a
+/// [`listener`], or created as one of a [`pair`].
+///
+/// [`connect`]: UnixSeqpacket::connect
+/// [`accepting`]: UnixSeqpacketListener::accept
+/// [`listener`]: UnixSeqpacketListener
+/// [`pair`]: UnixSeqpacket::pair
+pub struct UnixSeqpacket {
+    inner: Socket,
+}
+
+impl UnixSeqpacketListener {
+    /// Creates a new listener bound to the specified file path. The file path
+    /// cannot yet exist, and is not removed when the listener is dropped.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixSeqpacketListener> {
+        let addr = UnixSocketAddr::from_pathname(path)?;
+        UnixSeqpacketListener::bind_addr(&addr)
+    }
+
+    /// Creates a new listener bound to the specified address, which may be in
+    /// the Linux abstract namespace, or unnamed to let the kernel pick an
+    /// abstract name.
+    pub fn bind_addr(addr: &UnixSocketAddr) -> io::Result<UnixSeqpacketListener> {
+        let socket = Socket::bind_unix_sockaddr(addr.inner.clone(), libc::SOCK_SEQPACKET)?;
+        socket.listen(1024)?;
+        Ok(UnixSeqpacketListener { inner: socket })
+    }
+
+    /// Returns the address the socket is bound to, such as the name picked by
+    /// the kernel when bound to [`UnixSocketAddr::unnamed`].
+    pub fn local_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.local_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    pub async fn accept(&self) -> io::Result<UnixSeqpacket> {
+        let (socket, _) = self.inner.accept().await?;
+        Ok(UnixSeqpacket { inner: socket })
+    }
+
+    /// Accepts a new incoming connection from this listener, along with the
+    /// address of the peer.
+    pub async fn accept_with_addr(&self) -> io::Result<(UnixSeqpacket, UnixSocketAddr)> {
+        let (socket, inner) = self.inner.accept_sockaddr().await?;
+        Ok((UnixSeqpacket { inner: socket }, UnixSocketAddr { inner }))
+    }
+
+    /// Accepts a new incoming connection from this listener, along with the
+    /// credentials of the connecting process.
+    ///
+    /// See [`UnixListener::accept_with_cred`](crate::net::UnixListener::accept_with_cred).
+    pub async fn accept_with_cred(&self) -> io::Result<(UnixSeqpacket, UCred)> {
+        let socket = self.accept().await?;
+        let cred = socket.peer_cred()?;
+        Ok((socket, cred))
+    }
+}
+
+impl UnixSeqpacket {
+    /// Connects to the listener bound to the specified file path.
+    pub async fn connect<P: AsRef<Path>>(path: P) -> io::Result<UnixSeqpacket> {
+        let addr = UnixSocketAddr::from_pathname(path)?;
+        UnixSeqpacket::connect_addr(&addr).await
+    }
+
+    /// Connects to the listener bound to the specified address, which may be
+    /// in the Linux abstract namespace.
+    pub async fn connect_addr(addr: &UnixSocketAddr) -> io::Result<UnixSeqpacket> {
+        let socket = Socket::new_unix(libc::SOCK_SEQPACKET)?;
+        socket.connect(addr.inner.clone()).await?;
+        Ok(UnixSeqpacket { inner: socket })
+    }
+
+    /// Creates an unnamed pair of connected sequenced-packet sockets.
+    pub fn pair() -> io::Result<(UnixSeqpacket, UnixSeqpacket)> {
+        let (first, second) = Socket::pair_unix(libc::SOCK_SEQPACKET)?;
+        Ok((
+            UnixSeqpacket { inner: first },
+            UnixSeqpacket { inner: second },
+        ))
+    }
+
+    /// Returns the address the socket is bound to, such as the name picked by
+    /// the kernel when bound to [`UnixSocketAddr::unnamed`].
+    pub fn local_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.local_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Returns the address of the peer the socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.peer_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Returns the credentials of the process on the other end of the
+    /// connection, captured when the connection was established.
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        UCred::from_fd(self.inner.as_raw_fd())
+    }
+
+    /// Sends the buffer as a single message. On success, returns the number of
+    /// bytes written, which is the whole buffer.
+    pub async fn send<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, 0).await
+    }
+
+    /// Sends the buffer as a single message, with the given flags. On success,
+    /// returns the number of bytes written.
+    pub async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
+    }
+
+    /// Receives a single message. On success, returns the number of bytes
+    /// read, `0` once the peer closed the connection.
+    ///
+    /// If the message is longer than the buffer, the excess is discarded.
+    pub async fn recv<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, 0).await
+    }
+
+    /// Receives a single message, with the given flags. On success, returns
+    /// the number of bytes read.
+    ///
+    /// With [`RecvFlags::TRUNC`], the returned length is the length of the
+    /// message, which is greater than the buffer capacity if the message was
+    /// truncated.
+    pub async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
+    }
+}
+
+impl AsRawFd for UnixSeqpacketListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl AsRawFd for UnixSeqpacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
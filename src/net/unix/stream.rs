@@ -1,14 +1,21 @@
+use super::{UCred, UnixSocketAddr};
 use crate::{
     buf::{IoBuf, IoBufMut},
     driver::Socket,
+    net::{RecvFlags, SendFlags},
 };
 use socket2::SockAddr;
-use std::{io, path::Path};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+    path::Path,
+};
 
 /// A Unix stream between two local sockets on a Unix OS.
 ///
 /// A Unix stream can either be created by connecting to an endpoint, via the
-/// [`connect`] method, or by [`accepting`] a connection from a [`listener`].
+/// [`connect`] method, by [`accepting`] a connection from a [`listener`], or
+/// as one of a [`pair`].
 ///
 /// # Examples
 ///
@@ -33,8 +40,9 @@ use std::{io, path::Path};
 /// [`connect`]: UnixStream::connect
 /// [`accepting`]: crate::net::UnixListener::accept
 /// [`listener`]: crate::net::UnixListener
+/// [`pair`]: UnixStream::pair
 pub struct UnixStream {
-    pub(super) inner: Socket,
+    pub(crate) inner: Socket,
 }
 
 impl UnixStream {
@@ -48,15 +56,92 @@ impl UnixStream {
         Ok(unix_stream)
     }
 
+    /// Opens a Unix connection to the specified address, which may be in the
+    /// Linux abstract namespace.
+    pub async fn connect_addr(addr: &UnixSocketAddr) -> io::Result<UnixStream> {
+        let socket = Socket::new_unix(libc::SOCK_STREAM)?;
+        socket.connect(addr.inner.clone()).await?;
+        Ok(UnixStream { inner: socket })
+    }
+
+    /// Creates an unnamed pair of connected Unix streams, as for a child
+    /// process talking to its parent, or for tests exercising both ends of a
+    /// connection.
+    pub fn pair() -> io::Result<(UnixStream, UnixStream)> {
+        let (first, second) = Socket::pair_unix(libc::SOCK_STREAM)?;
+        Ok((UnixStream { inner: first }, UnixStream { inner: second }))
+    }
+
+    /// Returns the address the socket is bound to, such as the name picked by
+    /// the kernel when bound to [`UnixSocketAddr::unnamed`].
+    pub fn local_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.local_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Returns the address of the peer the socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<UnixSocketAddr> {
+        let inner = self.inner.peer_sockaddr()?;
+        Ok(UnixSocketAddr { inner })
+    }
+
+    /// Returns the credentials of the process on the other end of the stream,
+    /// captured when the connection was established.
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        UCred::from_fd(self.inner.as_raw_fd())
+    }
+
     /// Read some data from the stream into the buffer, returning the original buffer and
     /// quantity of data read.
     pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
         self.inner.read(buf).await
     }
 
+    /// Reads until the end of the stream, appending to `buf`, and returns
+    /// the buffer with the number of bytes read.
+    ///
+    /// The bytes are read into the spare capacity of `buf`, whose capacity
+    /// doubles each time it fills up. On error, `buf` holds the data read
+    /// until then.
+    pub async fn read_to_end(&self, buf: Vec<u8>) -> crate::BufResult<usize, Vec<u8>> {
+        crate::io::read_to_end(buf, move |buf| self.read(buf)).await
+    }
+
     /// Write some data to the stream from the buffer, returning the original buffer and
     /// quantity of data written.
     pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
         self.inner.write(buf).await
     }
+
+    /// Read some data from the stream into the buffer with the given flags,
+    /// returning the original buffer and quantity of data read.
+    ///
+    /// With [`RecvFlags::WAITALL`], the read only completes once the buffer
+    /// is full, unless the stream ends or an error occurs first.
+    pub async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
+    }
+
+    /// Write some data to the stream from the buffer with the given flags,
+    /// returning the original buffer and quantity of data written.
+    ///
+    /// Passing [`SendFlags::NOSIGNAL`] makes writing to a stream closed by the
+    /// peer fail with `EPIPE` instead of raising `SIGPIPE`.
+    pub async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
+    }
+}
+
+impl AsRawFd for UnixStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
 }
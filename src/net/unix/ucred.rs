@@ -0,0 +1,61 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+/// Credentials of the process on the other end of a Unix socket, as captured
+/// by the kernel when the connection was established.
+///
+/// See `SO_PEERCRED` in `unix(7)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UCred {
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+}
+
+impl UCred {
+    /// Returns the process ID of the peer, if known.
+    ///
+    /// The ID is not known if the peer process is in a PID namespace which is
+    /// not a descendant of the namespace of the caller.
+    pub fn pid(&self) -> Option<libc::pid_t> {
+        if self.pid == 0 {
+            None
+        } else {
+            Some(self.pid)
+        }
+    }
+
+    /// Returns the effective user ID of the peer.
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    /// Returns the effective group ID of the peer.
+    pub fn gid(&self) -> libc::gid_t {
+        self.gid
+    }
+
+    /// Reads the peer credentials of a connected Unix socket.
+    pub(crate) fn from_fd(fd: RawFd) -> io::Result<UCred> {
+        let mut cred = libc::ucred {
+            pid: 0,
+            uid: 0,
+            gid: 0,
+        };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+        syscall!(getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len
+        ))?;
+
+        Ok(UCred {
+            pid: cred.pid,
+            uid: cred.uid,
+            gid: cred.gid,
+        })
+    }
+}
@@ -0,0 +1,64 @@
+use socket2::SockAddr;
+use std::{fmt, io};
+
+/// The address of a vsock socket, made of a context identifier (CID) and a
+/// port.
+///
+/// The CID identifies the virtual machine, or the host, the socket belongs
+/// to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VsockAddr {
+    cid: u32,
+    port: u32,
+}
+
+impl VsockAddr {
+    /// Binds to any CID the local machine has.
+    pub const CID_ANY: u32 = libc::VMADDR_CID_ANY;
+
+    /// The hypervisor.
+    pub const CID_HYPERVISOR: u32 = libc::VMADDR_CID_HYPERVISOR;
+
+    /// The local machine, for communication between processes on the same
+    /// machine.
+    pub const CID_LOCAL: u32 = libc::VMADDR_CID_LOCAL;
+
+    /// The host, as seen from a guest.
+    pub const CID_HOST: u32 = libc::VMADDR_CID_HOST;
+
+    /// Binds to any free port.
+    pub const PORT_ANY: u32 = libc::VMADDR_PORT_ANY;
+
+    /// Creates an address from a CID and a port.
+    pub const fn new(cid: u32, port: u32) -> VsockAddr {
+        VsockAddr { cid, port }
+    }
+
+    /// Returns the context identifier.
+    pub const fn cid(&self) -> u32 {
+        self.cid
+    }
+
+    /// Returns the port.
+    pub const fn port(&self) -> u32 {
+        self.port
+    }
+
+    pub(crate) fn to_sockaddr(self) -> SockAddr {
+        // Never fails, see `SockAddr::vsock`
+        SockAddr::vsock(self.cid, self.port).unwrap()
+    }
+
+    pub(crate) fn from_sockaddr(addr: &SockAddr) -> io::Result<VsockAddr> {
+        let (cid, port) = addr
+            .vsock_address()
+            .ok_or_else(|| io::Error::other("Could not get vsock address"))?;
+        Ok(VsockAddr { cid, port })
+    }
+}
+
+impl fmt::Display for VsockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.cid, self.port)
+    }
+}
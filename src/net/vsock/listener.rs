@@ -0,0 +1,64 @@
+use super::{VsockAddr, VsockStream};
+use crate::driver::Socket;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// A vsock socket server, listening for connections.
+///
+/// Vsock sockets (`AF_VSOCK`) provide communication between virtual machines
+/// and their host. You can accept a new connection by using the
+/// [`accept`](`VsockListener::accept`) method.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::net::{VsockAddr, VsockListener};
+///
+/// fn main() -> std::io::Result<()> {
+///     let listener = VsockListener::bind(VsockAddr::new(VsockAddr::CID_ANY, 1234))?;
+///
+///     tokio_uring::start(async move {
+///         let (stream, addr) = listener.accept().await?;
+///         println!("connection from {}", addr);
+///
+///         let (result, _) = stream.write(b"hello".as_slice()).await;
+///         result?;
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+pub struct VsockListener {
+    inner: Socket,
+}
+
+impl VsockListener {
+    /// Creates a new VsockListener, which will be bound to the specified
+    /// address.
+    ///
+    /// The returned listener is ready for accepting connections.
+    pub fn bind(addr: VsockAddr) -> io::Result<VsockListener> {
+        let socket = Socket::bind_vsock(addr.to_sockaddr(), libc::SOCK_STREAM)?;
+        socket.listen(1024)?;
+        Ok(VsockListener { inner: socket })
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    ///
+    /// This function will yield once a new vsock connection is established.
+    /// When established, the corresponding [`VsockStream`] and the remote
+    /// peer's address will be returned.
+    ///
+    /// [`VsockStream`]: struct@crate::net::VsockStream
+    pub async fn accept(&self) -> io::Result<(VsockStream, VsockAddr)> {
+        let (socket, addr) = self.inner.accept_sockaddr().await?;
+        let stream = VsockStream { inner: socket };
+        Ok((stream, VsockAddr::from_sockaddr(&addr)?))
+    }
+}
+
+impl AsRawFd for VsockListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -0,0 +1,8 @@
+mod addr;
+pub use addr::VsockAddr;
+
+mod listener;
+pub use listener::VsockListener;
+
+mod stream;
+pub use stream::VsockStream;
@@ -0,0 +1,96 @@
+use super::VsockAddr;
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::Socket,
+    net::{RecvFlags, SendFlags},
+};
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// A vsock stream between a local and a remote socket.
+///
+/// A vsock stream can either be created by connecting to an endpoint, via the
+/// [`connect`] method, or by [`accepting`] a connection from a [`listener`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::net::{VsockAddr, VsockStream};
+///
+/// fn main() -> std::io::Result<()> {
+///     tokio_uring::start(async {
+///         // Connect to the host
+///         let stream = VsockStream::connect(VsockAddr::new(VsockAddr::CID_HOST, 1234)).await?;
+///
+///         // Write some data.
+///         let (result, _) = stream.write(b"hello world!".as_slice()).await;
+///         result.unwrap();
+///
+///         Ok(())
+///     })
+/// }
+/// ```
+///
+/// [`connect`]: VsockStream::connect
+/// [`accepting`]: crate::net::VsockListener::accept
+/// [`listener`]: crate::net::VsockListener
+pub struct VsockStream {
+    pub(crate) inner: Socket,
+}
+
+impl VsockStream {
+    /// Opens a vsock connection to the given address.
+    pub async fn connect(addr: VsockAddr) -> io::Result<VsockStream> {
+        let socket = Socket::new_vsock(libc::SOCK_STREAM)?;
+        socket.connect(addr.to_sockaddr()).await?;
+        Ok(VsockStream { inner: socket })
+    }
+
+    /// Read some data from the stream into the buffer, returning the original buffer and
+    /// quantity of data read.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.read(buf).await
+    }
+
+    /// Reads until the end of the stream, appending to `buf`, and returns
+    /// the buffer with the number of bytes read.
+    ///
+    /// The bytes are read into the spare capacity of `buf`, whose capacity
+    /// doubles each time it fills up. On error, `buf` holds the data read
+    /// until then.
+    pub async fn read_to_end(&self, buf: Vec<u8>) -> crate::BufResult<usize, Vec<u8>> {
+        crate::io::read_to_end(buf, move |buf| self.read(buf)).await
+    }
+
+    /// Write some data to the stream from the buffer, returning the original buffer and
+    /// quantity of data written.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.inner.write(buf).await
+    }
+
+    /// Read some data from the stream into the buffer with the given flags,
+    /// returning the original buffer and quantity of data read.
+    pub async fn recv_with_flags<T: IoBufMut>(
+        &self,
+        buf: T,
+        flags: RecvFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.recv(buf, flags.bits()).await
+    }
+
+    /// Write some data to the stream from the buffer with the given flags,
+    /// returning the original buffer and quantity of data written.
+    pub async fn send_with_flags<T: IoBuf>(
+        &self,
+        buf: T,
+        flags: SendFlags,
+    ) -> crate::BufResult<usize, T> {
+        self.inner.send(buf, flags.bits()).await
+    }
+}
+
+impl AsRawFd for VsockStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+
+macro_rules! op_kinds {
+    (
+        $(
+            $(#[$meta:meta])*
+            $kind:ident = $opcode:literal, $name:literal;
+        )*
+    ) => {
+        /// The kind of an `io_uring` operation, by opcode.
+        ///
+        /// The runtime records the kind of each operation it submits, and
+        /// reports it wherever it describes operations: in the latencies of
+        /// [`metrics`](crate::metrics), the slow operations reported by the
+        /// watchdog, see [`SlowOp::kind`](crate::SlowOp::kind), the
+        /// operations in flight of [`debug_dump`](crate::debug_dump), and the
+        /// messages of the checks of the operations made in debug builds. The
+        /// errors of the operations are the ones of the kernel, with their
+        /// error number, and are not wrapped.
+        ///
+        /// Opcodes newer than this type are reported as [`OpKind::Other`].
+        /// Its `Display` implementation prints the lowercase name of the
+        /// operation, e.g. `read`, or its opcode for [`OpKind::Other`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use tokio_uring::OpKind;
+        ///
+        /// let kind = OpKind::from(22);
+        /// assert_eq!(kind, OpKind::Read);
+        /// assert_eq!(kind.opcode(), 22);
+        /// assert_eq!(kind.to_string(), "read");
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum OpKind {
+            $(
+                $(#[$meta])*
+                $kind,
+            )*
+
+            /// An opcode without a variant yet.
+            Other(u8),
+        }
+
+        impl OpKind {
+            /// Returns the opcode of the operation, one of the `IORING_OP_*`
+            /// constants of `io_uring.h`.
+            pub const fn opcode(self) -> u8 {
+                match self {
+                    $(OpKind::$kind => $opcode,)*
+                    OpKind::Other(opcode) => opcode,
+                }
+            }
+
+            /// Returns the lowercase name of the operation, `"other"` for
+            /// [`OpKind::Other`].
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(OpKind::$kind => $name,)*
+                    OpKind::Other(_) => "other",
+                }
+            }
+        }
+
+        impl From<u8> for OpKind {
+            fn from(opcode: u8) -> OpKind {
+                match opcode {
+                    $($opcode => OpKind::$kind,)*
+                    opcode => OpKind::Other(opcode),
+                }
+            }
+        }
+    };
+}
+
+op_kinds! {
+    /// `IORING_OP_NOP`
+    Nop = 0, "nop";
+    /// `IORING_OP_READV`
+    Readv = 1, "readv";
+    /// `IORING_OP_WRITEV`
+    Writev = 2, "writev";
+    /// `IORING_OP_FSYNC`
+    Fsync = 3, "fsync";
+    /// `IORING_OP_READ_FIXED`
+    ReadFixed = 4, "read_fixed";
+    /// `IORING_OP_WRITE_FIXED`
+    WriteFixed = 5, "write_fixed";
+    /// `IORING_OP_POLL_ADD`
+    PollAdd = 6, "poll_add";
+    /// `IORING_OP_POLL_REMOVE`
+    PollRemove = 7, "poll_remove";
+    /// `IORING_OP_SYNC_FILE_RANGE`
+    SyncFileRange = 8, "sync_file_range";
+    /// `IORING_OP_SENDMSG`
+    SendMsg = 9, "sendmsg";
+    /// `IORING_OP_RECVMSG`
+    RecvMsg = 10, "recvmsg";
+    /// `IORING_OP_TIMEOUT`
+    Timeout = 11, "timeout";
+    /// `IORING_OP_TIMEOUT_REMOVE`
+    TimeoutRemove = 12, "timeout_remove";
+    /// `IORING_OP_ACCEPT`
+    Accept = 13, "accept";
+    /// `IORING_OP_ASYNC_CANCEL`
+    AsyncCancel = 14, "async_cancel";
+    /// `IORING_OP_LINK_TIMEOUT`
+    LinkTimeout = 15, "link_timeout";
+    /// `IORING_OP_CONNECT`
+    Connect = 16, "connect";
+    /// `IORING_OP_FALLOCATE`
+    Fallocate = 17, "fallocate";
+    /// `IORING_OP_OPENAT`
+    OpenAt = 18, "openat";
+    /// `IORING_OP_CLOSE`
+    Close = 19, "close";
+    /// `IORING_OP_FILES_UPDATE`
+    FilesUpdate = 20, "files_update";
+    /// `IORING_OP_STATX`
+    Statx = 21, "statx";
+    /// `IORING_OP_READ`
+    Read = 22, "read";
+    /// `IORING_OP_WRITE`
+    Write = 23, "write";
+    /// `IORING_OP_FADVISE`
+    Fadvise = 24, "fadvise";
+    /// `IORING_OP_MADVISE`
+    Madvise = 25, "madvise";
+    /// `IORING_OP_SEND`
+    Send = 26, "send";
+    /// `IORING_OP_RECV`
+    Recv = 27, "recv";
+    /// `IORING_OP_OPENAT2`
+    OpenAt2 = 28, "openat2";
+    /// `IORING_OP_EPOLL_CTL`
+    EpollCtl = 29, "epoll_ctl";
+    /// `IORING_OP_SPLICE`
+    Splice = 30, "splice";
+    /// `IORING_OP_PROVIDE_BUFFERS`
+    ProvideBuffers = 31, "provide_buffers";
+    /// `IORING_OP_REMOVE_BUFFERS`
+    RemoveBuffers = 32, "remove_buffers";
+    /// `IORING_OP_TEE`
+    Tee = 33, "tee";
+    /// `IORING_OP_SHUTDOWN`
+    Shutdown = 34, "shutdown";
+    /// `IORING_OP_RENAMEAT`
+    RenameAt = 35, "renameat";
+    /// `IORING_OP_UNLINKAT`
+    UnlinkAt = 36, "unlinkat";
+    /// `IORING_OP_MKDIRAT`
+    MkDirAt = 37, "mkdirat";
+    /// `IORING_OP_SYMLINKAT`
+    SymlinkAt = 38, "symlinkat";
+    /// `IORING_OP_LINKAT`
+    LinkAt = 39, "linkat";
+    /// `IORING_OP_MSG_RING`
+    MsgRing = 40, "msg_ring";
+    /// `IORING_OP_FSETXATTR`
+    FSetXattr = 41, "fsetxattr";
+    /// `IORING_OP_SETXATTR`
+    SetXattr = 42, "setxattr";
+    /// `IORING_OP_FGETXATTR`
+    FGetXattr = 43, "fgetxattr";
+    /// `IORING_OP_GETXATTR`
+    GetXattr = 44, "getxattr";
+    /// `IORING_OP_SOCKET`
+    Socket = 45, "socket";
+    /// `IORING_OP_URING_CMD`
+    UringCmd = 46, "uring_cmd";
+    /// `IORING_OP_SEND_ZC`
+    SendZc = 47, "send_zc";
+    /// `IORING_OP_SENDMSG_ZC`
+    SendMsgZc = 48, "sendmsg_zc";
+    /// `IORING_OP_READ_MULTISHOT`
+    ReadMultishot = 49, "read_multishot";
+    /// `IORING_OP_WAITID`
+    Waitid = 50, "waitid";
+    /// `IORING_OP_FUTEX_WAIT`
+    FutexWait = 51, "futex_wait";
+    /// `IORING_OP_FUTEX_WAKE`
+    FutexWake = 52, "futex_wake";
+    /// `IORING_OP_FUTEX_WAITV`
+    FutexWaitv = 53, "futex_waitv";
+    /// `IORING_OP_FIXED_FD_INSTALL`
+    FixedFdInstall = 54, "fixed_fd_install";
+    /// `IORING_OP_FTRUNCATE`
+    Ftruncate = 55, "ftruncate";
+    /// `IORING_OP_BIND`
+    Bind = 56, "bind";
+    /// `IORING_OP_LISTEN`
+    Listen = 57, "listen";
+}
+
+impl From<OpKind> for u8 {
+    fn from(kind: OpKind) -> u8 {
+        kind.opcode()
+    }
+}
+
+impl fmt::Display for OpKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpKind::Other(opcode) => write!(f, "opcode {}", opcode),
+            kind => f.write_str(kind.name()),
+        }
+    }
+}
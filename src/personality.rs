@@ -0,0 +1,104 @@
+//! Running operations with alternate credentials.
+//!
+//! A [`Personality`] captures the credentials of the thread of the runtime,
+//! its user and group ids and capabilities, with
+//! `IORING_REGISTER_PERSONALITY`. The operations submitted while polling a
+//! future wrapped with [`with_personality`] are then performed by the kernel
+//! with these credentials, whatever the credentials of the thread at the
+//! time.
+//!
+//! This lets a server capture its privileged credentials at startup, drop
+//! its privileges, and still open the few privileged files it needs later,
+//! such as TLS keys being rotated, without keeping the privileges for all
+//! its other operations. The personalities of a runtime are as sensitive as
+//! the credentials they capture: code able to call [`with_personality`] can
+//! use them.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_uring::fs::File;
+//! use tokio_uring::personality::{with_personality, Personality};
+//!
+//! tokio_uring::start(async {
+//!     // Captured while still running as root
+//!     let root = Personality::register()?;
+//!
+//!     // Drop privileges, e.g. with `setresuid(2)`
+//!
+//!     let key = with_personality(root.id(), File::open("/etc/ssl/private/server.key")).await?;
+//!     # let _ = key;
+//!     Ok::<(), std::io::Error>(())
+//! })
+//! .unwrap();
+//! ```
+
+use crate::driver::personality::{self, PersonalityRegistration};
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+
+/// Credentials registered with the ring of the runtime, unregistered when
+/// dropped.
+pub struct Personality {
+    registration: PersonalityRegistration,
+}
+
+impl Personality {
+    /// Registers the current credentials of the thread of the runtime.
+    ///
+    /// Requires Linux 5.6 or later. Fails if called outside of a
+    /// `tokio-uring` runtime.
+    pub fn register() -> io::Result<Personality> {
+        Ok(Personality {
+            registration: PersonalityRegistration::new()?,
+        })
+    }
+
+    /// Returns the identifier of the credentials, to pass to
+    /// [`with_personality`].
+    pub fn id(&self) -> u16 {
+        self.registration.id
+    }
+}
+
+impl fmt::Debug for Personality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Personality")
+            .field("id", &self.id())
+            .finish()
+    }
+}
+
+/// Runs `future`, performing the operations it submits with the credentials
+/// registered as `id`.
+///
+/// The credentials apply to the operations submitted while `future` is
+/// polled, including the operations of the futures it awaits, but not the
+/// ones of the tasks it spawns. Operations with an `id` which is not
+/// registered, for example because its [`Personality`] was dropped, fail
+/// with `EINVAL`.
+///
+/// # Panics
+///
+/// This function panics if polled outside of a `tokio-uring` runtime.
+pub async fn with_personality<F: Future>(id: u16, future: F) -> F::Output {
+    tokio::pin!(future);
+
+    crate::future::poll_fn(|cx| {
+        // Restored even if polling panics
+        struct Restore(Option<u16>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                personality::set(self.0);
+            }
+        }
+
+        let _restore = Restore(personality::set(Some(id)));
+
+        future.as_mut().poll(cx)
+    })
+    .await
+}
@@ -0,0 +1,223 @@
+//! Anonymous pipes.
+//!
+//! A pipe is a unidirectional channel: data written to the [`PipeWrite`] end
+//! is read from the [`PipeRead`] end. Pipes are typically used to communicate
+//! with child processes.
+//!
+//! # Examples
+//!
+//! ```
+//! fn main() -> std::io::Result<()> {
+//!     tokio_uring::start(async {
+//!         let (rx, tx) = tokio_uring::pipe::pipe()?;
+//!
+//!         let (res, _) = tx.write(b"hello".as_slice()).await;
+//!         res?;
+//!
+//!         let (res, buf) = rx.read(vec![0; 5]).await;
+//!         assert_eq!(&buf[..res?], b"hello");
+//!
+//!         Ok(())
+//!     })
+//! }
+//! ```
+
+use crate::buf::{IoBuf, IoBufMut};
+use crate::driver::{self, SharedFd};
+
+use std::fmt;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+/// Creates a new pipe, with both ends closed on exec.
+///
+/// See [`PipeOptions`] to configure the pipe.
+pub fn pipe() -> io::Result<(PipeRead, PipeWrite)> {
+    PipeOptions::new().pipe()
+}
+
+/// Options and flags which can be used to configure how a pipe is created.
+///
+/// # Examples
+///
+/// Creating a pipe whose ends are inherited by child processes:
+///
+/// ```no_run
+/// use tokio_uring::pipe::PipeOptions;
+///
+/// let (rx, tx) = PipeOptions::new().cloexec(false).pipe().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PipeOptions {
+    nonblocking: bool,
+    cloexec: bool,
+}
+
+impl PipeOptions {
+    /// Creates a blank new set of options ready for configuration.
+    ///
+    /// By default, the ends are in blocking mode, and are closed on exec.
+    pub fn new() -> PipeOptions {
+        PipeOptions {
+            nonblocking: false,
+            cloexec: true,
+        }
+    }
+
+    /// Sets the `O_NONBLOCK` flag on both ends.
+    ///
+    /// Reads and writes still wait for the pipe to become ready. The flag only
+    /// matters to other users of the ends, such as child processes.
+    pub fn nonblocking(&mut self, nonblocking: bool) -> &mut PipeOptions {
+        self.nonblocking = nonblocking;
+        self
+    }
+
+    /// Sets the `O_CLOEXEC` flag on both ends, closing them when executing a
+    /// new program.
+    pub fn cloexec(&mut self, cloexec: bool) -> &mut PipeOptions {
+        self.cloexec = cloexec;
+        self
+    }
+
+    /// Creates a pipe with the options specified by `self`, see `pipe2(2)`.
+    pub fn pipe(&self) -> io::Result<(PipeRead, PipeWrite)> {
+        let mut flags = 0;
+        if self.nonblocking {
+            flags |= libc::O_NONBLOCK;
+        }
+        if self.cloexec {
+            flags |= libc::O_CLOEXEC;
+        }
+
+        let mut fds = [-1; 2];
+        syscall!(pipe2(fds.as_mut_ptr(), flags))?;
+
+        let rx = PipeRead {
+            fd: SharedFd::new(fds[0]),
+        };
+        let tx = PipeWrite {
+            fd: SharedFd::new(fds[1]),
+        };
+        Ok((rx, tx))
+    }
+}
+
+impl Default for PipeOptions {
+    fn default() -> PipeOptions {
+        PipeOptions::new()
+    }
+}
+
+/// The reading end of a pipe.
+pub struct PipeRead {
+    /// Open file descriptor
+    pub(crate) fd: SharedFd,
+}
+
+impl PipeRead {
+    /// Read some data from the pipe into the buffer, returning the original
+    /// buffer and quantity of data read.
+    ///
+    /// Waits until some data is available. A read of 0 bytes means all the
+    /// writing ends were closed.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        driver::read_ready(&self.fd, buf).await
+    }
+
+    /// Reads until all the writing ends are closed, appending to `buf`, and
+    /// returns the buffer with the number of bytes read.
+    ///
+    /// The bytes are read into the spare capacity of `buf`, whose capacity
+    /// doubles each time it fills up. On error, `buf` holds the data read
+    /// until then.
+    pub async fn read_to_end(&self, buf: Vec<u8>) -> crate::BufResult<usize, Vec<u8>> {
+        crate::io::read_to_end(buf, move |buf| self.read(buf)).await
+    }
+
+    /// Closes the reading end.
+    ///
+    /// The method completes once the close operation has completed,
+    /// guaranteeing that resources associated with the pipe end have been
+    /// released.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await;
+        Ok(())
+    }
+}
+
+/// The writing end of a pipe.
+pub struct PipeWrite {
+    /// Open file descriptor
+    pub(crate) fd: SharedFd,
+}
+
+impl PipeWrite {
+    /// Write some data to the pipe from the buffer, returning the original
+    /// buffer and quantity of data written.
+    ///
+    /// Waits until the pipe has room for some data. Writing fails with
+    /// `EPIPE` once all the reading ends were closed.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        driver::write_ready(&self.fd, buf).await
+    }
+
+    /// Closes the writing end, signalling the end of the data to the reader
+    /// once all the writing ends are closed.
+    ///
+    /// The method completes once the close operation has completed,
+    /// guaranteeing that resources associated with the pipe end have been
+    /// released.
+    pub async fn close(self) -> io::Result<()> {
+        self.fd.close().await;
+        Ok(())
+    }
+}
+
+impl AsRawFd for PipeRead {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl FromRawFd for PipeRead {
+    /// Adopts the reading end of a pipe, such as the stdout of a child
+    /// process.
+    unsafe fn from_raw_fd(fd: RawFd) -> PipeRead {
+        PipeRead {
+            fd: SharedFd::new(fd),
+        }
+    }
+}
+
+impl fmt::Debug for PipeRead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeRead")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
+
+impl AsRawFd for PipeWrite {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl FromRawFd for PipeWrite {
+    /// Adopts the writing end of a pipe, such as the stdin of a child
+    /// process.
+    unsafe fn from_raw_fd(fd: RawFd) -> PipeWrite {
+        PipeWrite {
+            fd: SharedFd::new(fd),
+        }
+    }
+}
+
+impl fmt::Debug for PipeWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipeWrite")
+            .field("fd", &self.fd.raw_fd())
+            .finish()
+    }
+}
@@ -0,0 +1,233 @@
+//! Waiting for child processes.
+//!
+//! A [`Child`] wraps a child process spawned with [`std::process::Command`],
+//! and waits for it to exit through the ring, with `IORING_OP_WAITID`: no
+//! thread blocks in `waitpid(2)`, and no `SIGCHLD` handler is installed. On
+//! kernels older than 6.7, which do not support the operation, the runtime
+//! polls a pidfd of the process instead.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::process::{Command, Stdio};
+//!
+//! fn main() -> std::io::Result<()> {
+//!     tokio_uring::start(async {
+//!         let mut child = tokio_uring::process::spawn(
+//!             Command::new("ls").stdout(Stdio::piped()),
+//!         )?;
+//!
+//!         let stdout = child.take_stdout().unwrap();
+//!         let (res, buf) = stdout.read(vec![0; 4096]).await;
+//!         println!("{}", String::from_utf8_lossy(&buf[..res?]));
+//!
+//!         let status = child.wait().await?;
+//!         println!("ls exited with {}", status);
+//!         Ok(())
+//!     })
+//! }
+//! ```
+
+use crate::driver::{self, Op, SharedFd, Waitid};
+use crate::pipe::{PipeRead, PipeWrite};
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::os::unix::process::ExitStatusExt;
+use std::pin::Pin;
+use std::process::{self, Command, ExitStatus};
+use std::task::{Context, Poll};
+
+/// Spawns `command` as a child process.
+///
+/// The pipes of the standard streams configured with [`Stdio::piped`] are
+/// taken from the child with [`Child::take_stdin`], [`Child::take_stdout`]
+/// and [`Child::take_stderr`].
+///
+/// [`Stdio::piped`]: std::process::Stdio::piped
+pub fn spawn(command: &mut Command) -> io::Result<Child> {
+    command.spawn().map(Child::from_std)
+}
+
+/// A child process, waited for through the ring.
+///
+/// Unlike [`std::process::Child`], dropping a `Child` neither waits for the
+/// process nor kills it. A child process which is never waited for remains a
+/// zombie until the parent exits.
+pub struct Child {
+    child: process::Child,
+
+    /// Set once the process was reaped
+    status: Option<ExitStatus>,
+}
+
+/// Waits for a child process to exit, canceling the wait when dropped.
+struct Exit {
+    op: Op<Waitid>,
+    done: bool,
+}
+
+impl Child {
+    /// Wraps a child process spawned with [`Command::spawn`].
+    ///
+    /// The process must not have been waited for.
+    pub fn from_std(child: process::Child) -> Child {
+        Child {
+            child,
+            status: None,
+        }
+    }
+
+    /// Returns the process id of the child.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Takes the writing end of the standard input of the child, if it was
+    /// piped and not taken yet.
+    pub fn take_stdin(&mut self) -> Option<PipeWrite> {
+        let fd = self.child.stdin.take()?.into_raw_fd();
+        // Safety: the pipe is owned by the returned end
+        Some(unsafe { PipeWrite::from_raw_fd(fd) })
+    }
+
+    /// Takes the reading end of the standard output of the child, if it was
+    /// piped and not taken yet.
+    pub fn take_stdout(&mut self) -> Option<PipeRead> {
+        let fd = self.child.stdout.take()?.into_raw_fd();
+        // Safety: see `take_stdin`
+        Some(unsafe { PipeRead::from_raw_fd(fd) })
+    }
+
+    /// Takes the reading end of the standard error of the child, if it was
+    /// piped and not taken yet.
+    pub fn take_stderr(&mut self) -> Option<PipeRead> {
+        let fd = self.child.stderr.take()?.into_raw_fd();
+        // Safety: see `take_stdin`
+        Some(unsafe { PipeRead::from_raw_fd(fd) })
+    }
+
+    /// Sends `SIGKILL` to the child, unless it was already waited for.
+    pub fn kill(&mut self) -> io::Result<()> {
+        match self.status {
+            Some(_) => Ok(()),
+            None => self.child.kill(),
+        }
+    }
+
+    /// Waits for the child to exit, returning its exit status.
+    ///
+    /// The standard input of the child is closed first, if it was not taken,
+    /// so that a child reading it does not wait for more input forever.
+    ///
+    /// Dropping the future before the child exited cancels the wait, and the
+    /// child can be waited for again.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        drop(self.child.stdin.take());
+
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+
+            let pid = self.child.id() as libc::pid_t;
+
+            // The process is reaped by `try_wait`, so that dropping the future
+            // does not lose its exit status
+            match Op::waitid(pid, libc::WEXITED | libc::WNOWAIT) {
+                Ok(op) => match (Exit { op, done: false }).await {
+                    Ok(()) => {}
+                    // The operation is not supported
+                    Err(e) if e.raw_os_error() == Some(libc::EINVAL) => exit_pidfd(pid).await?,
+                    Err(e) => return Err(e),
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the exit status of the child if it exited, without waiting.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_some() {
+            return Ok(self.status);
+        }
+
+        // Safety: `siginfo_t` is plain data
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        syscall!(waitid(
+            libc::P_PID,
+            self.child.id(),
+            &mut info,
+            libc::WEXITED | libc::WNOHANG
+        ))?;
+
+        // Safety: filled in by `waitid` for children which exited
+        if unsafe { info.si_pid() } == 0 {
+            return Ok(None);
+        }
+
+        self.status = Some(exit_status(&info));
+        Ok(self.status)
+    }
+}
+
+/// Waits for the process `pid` to exit by polling a pidfd of it.
+async fn exit_pidfd(pid: libc::pid_t) -> io::Result<()> {
+    // Safety: the system call takes no pointer
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let fd = SharedFd::new(fd as _);
+    driver::ready(&fd, libc::POLLIN).await
+}
+
+/// Returns the exit status described by the `siginfo_t` of an exited child.
+fn exit_status(info: &libc::siginfo_t) -> ExitStatus {
+    // Safety: filled in by `waitid` for children which exited
+    let (code, status) = unsafe { (info.si_code, info.si_status()) };
+
+    // Encoded as `waitpid(2)` does
+    let raw = match code {
+        libc::CLD_EXITED => (status & 0xff) << 8,
+        libc::CLD_DUMPED => status | 0x80,
+        _ => status,
+    };
+    ExitStatus::from_raw(raw)
+}
+
+impl Future for Exit {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let completion = ready!(Pin::new(&mut self.op).poll(cx));
+        self.done = true;
+        Poll::Ready(completion.result.map(drop))
+    }
+}
+
+impl Drop for Exit {
+    fn drop(&mut self) {
+        if !self.done {
+            self.op.request_cancel();
+        }
+    }
+}
+
+impl From<process::Child> for Child {
+    fn from(child: process::Child) -> Child {
+        Child::from_std(child)
+    }
+}
+
+impl fmt::Debug for Child {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("id", &self.child.id())
+            .field("status", &self.status)
+            .finish()
+    }
+}
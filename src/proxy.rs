@@ -0,0 +1,276 @@
+//! Zero-copy proxying between streams.
+//!
+//! [`pipe_between`] forwards the data of two connected streams to each other,
+//! as a TCP proxy or a load balancer does once it picked the upstream server
+//! of a client. The data is spliced from one socket to the other through a
+//! pipe, without being copied to user space.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_uring::net::{TcpListener, TcpStream};
+//! use tokio_uring::proxy::pipe_between;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     tokio_uring::start(async {
+//!         let listener = TcpListener::bind("0.0.0.0:8080".parse().unwrap())?;
+//!
+//!         loop {
+//!             let (client, _) = listener.accept().await?;
+//!
+//!             tokio_uring::spawn(async move {
+//!                 let upstream = TcpStream::connect("10.0.0.1:80".parse().unwrap()).await?;
+//!                 let (sent, received) = pipe_between(&client, &upstream).await?;
+//!                 println!("sent {} bytes, received {} bytes", sent, received);
+//!                 Ok::<(), std::io::Error>(())
+//!             });
+//!         }
+//!     })
+//! }
+//! ```
+
+use crate::driver::{Op, PollAdd, SharedFd, Splice};
+use crate::future::poll_fn;
+use crate::net::{TcpStream, UnixStream, VsockStream};
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// `SPLICE_F_NONBLOCK`, from `linux/fcntl.h`
+const SPLICE_F_NONBLOCK: u32 = 2;
+
+/// A stream socket whose data can be spliced: a [`TcpStream`], a
+/// [`UnixStream`] or a [`VsockStream`].
+pub trait Stream: sealed::Sealed {}
+
+impl Stream for TcpStream {}
+impl Stream for UnixStream {}
+impl Stream for VsockStream {}
+
+// The socket of a stream, for the crate only
+#[allow(private_interfaces)]
+mod sealed {
+    use crate::driver::SharedFd;
+    use crate::net::{TcpStream, UnixStream, VsockStream};
+
+    pub trait Sealed {
+        fn shared_fd(&self) -> &SharedFd;
+    }
+
+    impl Sealed for TcpStream {
+        fn shared_fd(&self) -> &SharedFd {
+            self.inner.shared_fd()
+        }
+    }
+
+    impl Sealed for UnixStream {
+        fn shared_fd(&self) -> &SharedFd {
+            self.inner.shared_fd()
+        }
+    }
+
+    impl Sealed for VsockStream {
+        fn shared_fd(&self) -> &SharedFd {
+            self.inner.shared_fd()
+        }
+    }
+}
+
+/// Forwards the data read from `a` to `b`, and the data read from `b` to `a`,
+/// until both streams reached their end, returning the number of bytes
+/// forwarded from `a` to `b` and from `b` to `a`.
+///
+/// Each direction moves the data through a pipe of its own, with splice
+/// operations, waiting for the data read to be written before reading more:
+/// a peer which does not read its data slows the other one down, instead of
+/// having the proxy buffer it. Once a stream reached its end, the writing
+/// half of the other one is shut down, and the data keeps flowing the other
+/// way, as for HTTP clients half-closing their connection after sending their
+/// request.
+///
+/// # Errors
+///
+/// Fails as soon as either direction fails, such as when a peer resets its
+/// connection: the operations of the other direction are canceled, and the
+/// streams can be dropped right away. The streams should be dropped then, as
+/// the data in flight in the pipes is lost. The operations are canceled as
+/// well if the future is dropped, for example by a timeout.
+pub async fn pipe_between<A: Stream, B: Stream>(a: &A, b: &B) -> io::Result<(u64, u64)> {
+    let mut a_to_b = Direction::new(a.shared_fd(), b.shared_fd())?;
+    let mut b_to_a = Direction::new(b.shared_fd(), a.shared_fd())?;
+
+    let res = poll_fn(|cx| {
+        let a_to_b = a_to_b.poll(cx)?;
+        let b_to_a = b_to_a.poll(cx)?;
+
+        if a_to_b.is_ready() && b_to_a.is_ready() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+
+    // The direction which did not fail waits for the kernel to release its
+    // operation
+    a_to_b.stop().await;
+    b_to_a.stop().await;
+
+    res.map(|()| (a_to_b.forwarded, b_to_a.forwarded))
+}
+
+/// Forwards the data of `src` to `dst`.
+struct Direction<'a> {
+    src: &'a SharedFd,
+    dst: &'a SharedFd,
+
+    /// Pipe the data moves through
+    rx: SharedFd,
+    tx: SharedFd,
+    capacity: u32,
+
+    /// Bytes in the pipe, not written to `dst` yet
+    buffered: u32,
+
+    /// Whether the next splice goes ahead without polling its socket first
+    ready: bool,
+
+    state: State,
+
+    /// Bytes written to `dst`
+    forwarded: u64,
+}
+
+enum State {
+    /// No operation in flight
+    Idle,
+
+    /// Waiting for the socket of the next splice to be ready
+    Polling(Op<PollAdd>),
+
+    /// Moving data to or from the pipe
+    Splicing(Op<Splice>),
+
+    /// `src` reached its end, and `dst` was shut down
+    Done,
+}
+
+impl<'a> Direction<'a> {
+    fn new(src: &'a SharedFd, dst: &'a SharedFd) -> io::Result<Direction<'a>> {
+        let mut fds = [-1; 2];
+        syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+        let (rx, tx) = (SharedFd::new(fds[0]), SharedFd::new(fds[1]));
+        let capacity = syscall!(fcntl(tx.raw_fd(), libc::F_GETPIPE_SZ))?;
+
+        Ok(Direction {
+            src,
+            dst,
+            rx,
+            tx,
+            capacity: capacity as u32,
+            buffered: 0,
+            ready: false,
+            state: State::Idle,
+            forwarded: 0,
+        })
+    }
+
+    /// Forwards data until `src` reached its end.
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            match &mut self.state {
+                State::Idle => self.submit()?,
+                State::Polling(op) => {
+                    let completion = ready!(Pin::new(op).poll(cx));
+                    self.state = State::Idle;
+                    completion.result?;
+                    self.ready = true;
+                }
+                State::Splicing(op) => {
+                    let completion = ready!(Pin::new(op).poll(cx));
+                    self.state = State::Idle;
+
+                    let n = match completion.result {
+                        // The socket is not ready after all, poll it
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            self.ready = false;
+                            continue;
+                        }
+                        res => res?,
+                    };
+
+                    if self.buffered > 0 {
+                        if n == 0 {
+                            return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+                        }
+                        self.buffered -= n;
+                        self.forwarded += n as u64;
+                    } else if n > 0 {
+                        self.buffered = n;
+                    } else {
+                        // The end of `src`, forwarded as is
+                        match syscall!(shutdown(self.dst.raw_fd(), libc::SHUT_WR)) {
+                            Err(e) if e.raw_os_error() != Some(libc::ENOTCONN) => {
+                                return Poll::Ready(Err(e))
+                            }
+                            _ => self.state = State::Done,
+                        }
+                    }
+
+                    // Data read is written right away, as the socket usually
+                    // has room for it, while reads wait for data
+                    self.ready = self.buffered > 0;
+                }
+                State::Done => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    /// Submits the next operation: a splice if its socket is ready, or a poll
+    /// of the socket otherwise.
+    fn submit(&mut self) -> io::Result<()> {
+        self.state = match (self.buffered > 0, self.ready) {
+            (false, false) => State::Polling(Op::poll_add(self.src, libc::POLLIN)?),
+            (true, false) => State::Polling(Op::poll_add(self.dst, libc::POLLOUT)?),
+            (false, true) => State::Splicing(Op::splice(
+                self.src,
+                -1,
+                &self.tx,
+                -1,
+                self.capacity,
+                SPLICE_F_NONBLOCK,
+            )?),
+            (true, true) => State::Splicing(Op::splice(
+                &self.rx,
+                -1,
+                self.dst,
+                -1,
+                self.buffered,
+                SPLICE_F_NONBLOCK,
+            )?),
+        };
+
+        Ok(())
+    }
+
+    /// Cancels the operation in flight, waiting for its completion.
+    async fn stop(&mut self) {
+        match std::mem::replace(&mut self.state, State::Done) {
+            State::Polling(op) => drop(op.cancel_and_wait().await),
+            State::Splicing(op) => drop(op.cancel_and_wait().await),
+            State::Idle | State::Done => {}
+        }
+    }
+}
+
+impl Drop for Direction<'_> {
+    fn drop(&mut self) {
+        match &self.state {
+            State::Polling(op) => op.request_cancel(),
+            State::Splicing(op) => op.request_cancel(),
+            State::Idle | State::Done => {}
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! Recording and replaying of I/O, for hermetic tests.
+//!
+//! A [`Recorder`] wraps a stream or a file, forwarding its reads and writes
+//! while appending them, with their data and their results, to a
+//! [`Recording`]. Saved with [`Recording::to_bytes`], the recording is
+//! replayed by a [`Replay`], which serves the recorded reads and checks the
+//! writes against the recorded ones, without the kernel nor the peer: a
+//! test recorded once against a live service then runs in CI without it,
+//! and always sees the same data, split the same way. This module is only
+//! available with the `test-util` feature.
+//!
+//! # Examples
+//!
+//! Recording an exchange with a server, then replaying it:
+//!
+//! ```no_run
+//! use tokio_uring::net::TcpStream;
+//! use tokio_uring::record::{Recorder, Recording, Replay};
+//!
+//! tokio_uring::start(async {
+//!     let stream = TcpStream::connect("127.0.0.1:6379".parse().unwrap()).await.unwrap();
+//!     let stream = Recorder::new(stream);
+//!
+//!     let (res, _) = stream.write(b"PING\r\n".to_vec()).await;
+//!     res.unwrap();
+//!     let (res, _) = stream.read(vec![0; 64]).await;
+//!     res.unwrap();
+//!
+//!     let bytes = stream.recording().to_bytes();
+//!
+//!     // Later on, without the server
+//!     let replay = Replay::new(&Recording::from_bytes(&bytes).unwrap());
+//!     let (res, _) = replay.write(b"PING\r\n".to_vec()).await;
+//!     res.unwrap();
+//!     let (res, buf) = replay.read(vec![0; 64]).await;
+//!     assert_eq!(&buf[..res.unwrap()], b"+PONG\r\n");
+//!     assert!(replay.is_done());
+//! });
+//! ```
+
+use crate::buf::{IoBuf, IoBufMut};
+use crate::fs::File;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::{TryFrom, TryInto};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// A read or a write, with its data and its result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A read of a stream, or of a file at `offset`, with the data read or
+    /// the error number of its failure.
+    Read {
+        /// Position of the read in the file, or `None` for a stream.
+        offset: Option<u64>,
+        /// The data read, or the error number.
+        result: Result<Vec<u8>, i32>,
+    },
+
+    /// A write to a stream, or to a file at `offset`, with the number of
+    /// bytes written or the error number of its failure.
+    Write {
+        /// Position of the write in the file, or `None` for a stream.
+        offset: Option<u64>,
+        /// The data submitted, of which a prefix may have been written.
+        data: Vec<u8>,
+        /// The number of bytes written, or the error number.
+        result: Result<usize, i32>,
+    },
+}
+
+/// A sequence of [`Event`]s, appended to by [`Recorder`]s and served by
+/// [`Replay`]s.
+///
+/// Clones share the same events, so that a recording outlives the recorder
+/// it was taken from.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    events: Rc<RefCell<Vec<Event>>>,
+}
+
+/// Tags of the serialized events
+const READ: u8 = 0;
+const WRITE: u8 = 1;
+
+impl Recording {
+    /// Creates an empty recording.
+    pub fn new() -> Recording {
+        Recording::default()
+    }
+
+    /// Appends an event, as to script a peer by hand.
+    pub fn push(&self, event: Event) {
+        self.events.borrow_mut().push(event);
+    }
+
+    /// Returns a copy of the events recorded so far.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    /// Serializes the recording, to be stored along with the tests.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        for event in self.events.borrow().iter() {
+            let (tag, offset) = match event {
+                Event::Read { offset, .. } => (READ, offset),
+                Event::Write { offset, .. } => (WRITE, offset),
+            };
+            bytes.push(tag);
+            match offset {
+                Some(offset) => {
+                    bytes.push(1);
+                    bytes.extend_from_slice(&offset.to_le_bytes());
+                }
+                None => bytes.push(0),
+            }
+
+            match event {
+                Event::Read { result, .. } => match result {
+                    Ok(data) => {
+                        bytes.push(0);
+                        put_data(&mut bytes, data);
+                    }
+                    Err(errno) => put_errno(&mut bytes, *errno),
+                },
+                Event::Write { data, result, .. } => {
+                    put_data(&mut bytes, data);
+                    match result {
+                        Ok(n) => {
+                            bytes.push(0);
+                            bytes.extend_from_slice(&(*n as u64).to_le_bytes());
+                        }
+                        Err(errno) => put_errno(&mut bytes, *errno),
+                    }
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Deserializes a recording serialized by [`to_bytes`](Recording::to_bytes).
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`InvalidData`](io::ErrorKind::InvalidData) if `bytes` is
+    /// not a serialized recording.
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<Recording> {
+        let mut events = Vec::new();
+
+        while !bytes.is_empty() {
+            let tag = take_u8(&mut bytes)?;
+            let offset = match take_u8(&mut bytes)? {
+                0 => None,
+                _ => Some(take_u64(&mut bytes)?),
+            };
+
+            let event = match tag {
+                READ => {
+                    let result = match take_u8(&mut bytes)? {
+                        0 => Ok(take_data(&mut bytes)?),
+                        _ => Err(take_u32(&mut bytes)? as i32),
+                    };
+                    Event::Read { offset, result }
+                }
+                WRITE => {
+                    let data = take_data(&mut bytes)?;
+                    let result = match take_u8(&mut bytes)? {
+                        0 => Ok(take_u64(&mut bytes)? as usize),
+                        _ => Err(take_u32(&mut bytes)? as i32),
+                    };
+                    Event::Write {
+                        offset,
+                        data,
+                        result,
+                    }
+                }
+                _ => return Err(invalid_recording()),
+            };
+            events.push(event);
+        }
+
+        Ok(Recording {
+            events: Rc::new(RefCell::new(events)),
+        })
+    }
+}
+
+fn put_data(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(data);
+}
+
+fn put_errno(bytes: &mut Vec<u8>, errno: i32) {
+    bytes.push(1);
+    bytes.extend_from_slice(&(errno as u32).to_le_bytes());
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(invalid_recording());
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn take_u8(bytes: &mut &[u8]) -> io::Result<u8> {
+    Ok(take(bytes, 1)?[0])
+}
+
+fn take_u32(bytes: &mut &[u8]) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap()))
+}
+
+fn take_u64(bytes: &mut &[u8]) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(take(bytes, 8)?.try_into().unwrap()))
+}
+
+fn take_data(bytes: &mut &[u8]) -> io::Result<Vec<u8>> {
+    let len = take_u64(bytes)?;
+    let len = usize::try_from(len).map_err(|_| invalid_recording())?;
+    Ok(take(bytes, len)?.to_vec())
+}
+
+fn invalid_recording() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid recording")
+}
+
+/// Returns the error number of a failed operation.
+fn errno(e: &io::Error) -> i32 {
+    e.raw_os_error().unwrap_or(libc::EIO)
+}
+
+/// A stream or a file, whose reads and writes are appended to a
+/// [`Recording`].
+///
+/// Streams are read and written with `read` and `write`, files with
+/// `read_at` and `write_at`, as the wrapped types are.
+#[derive(Debug)]
+pub struct Recorder<S> {
+    inner: S,
+    recording: Recording,
+}
+
+impl<S> Recorder<S> {
+    /// Wraps `inner`, recording to a new recording.
+    pub fn new(inner: S) -> Recorder<S> {
+        Recorder::with_recording(inner, Recording::new())
+    }
+
+    /// Wraps `inner`, appending to `recording`, as to record several files
+    /// of a test into a single recording.
+    pub fn with_recording(inner: S, recording: Recording) -> Recorder<S> {
+        Recorder { inner, recording }
+    }
+
+    /// Returns the recording.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Returns the wrapped stream or file.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Stream> Recorder<S> {
+    /// Reads some data from the stream into the buffer, recording the data
+    /// read.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.inner.read(buf).await;
+        self.record_read(None, &res, &buf);
+        (res, buf)
+    }
+
+    /// Writes some data of the buffer to the stream, recording the data and
+    /// the number of bytes written.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        let data = crate::buf::deref(&buf).to_vec();
+        let (res, buf) = self.inner.write(buf).await;
+        self.record_write(None, data, &res);
+        (res, buf)
+    }
+}
+
+impl Recorder<File> {
+    /// Reads some data of the file at `pos` into the buffer, recording the
+    /// data read.
+    pub async fn read_at<T: IoBufMut>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        let (res, buf) = self.inner.read_at(buf, pos).await;
+        self.record_read(Some(pos), &res, &buf);
+        (res, buf)
+    }
+
+    /// Writes some data of the buffer to the file at `pos`, recording the
+    /// data and the number of bytes written.
+    pub async fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        let data = crate::buf::deref(&buf).to_vec();
+        let (res, buf) = self.inner.write_at(buf, pos).await;
+        self.record_write(Some(pos), data, &res);
+        (res, buf)
+    }
+}
+
+impl<S> Recorder<S> {
+    fn record_read<T: IoBuf>(&self, offset: Option<u64>, res: &io::Result<usize>, buf: &T) {
+        let result = match res {
+            Ok(n) => Ok(crate::buf::deref(buf)[..*n].to_vec()),
+            Err(e) => Err(errno(e)),
+        };
+        self.recording.push(Event::Read { offset, result });
+    }
+
+    fn record_write(&self, offset: Option<u64>, data: Vec<u8>, res: &io::Result<usize>) {
+        let result = match res {
+            Ok(n) => Ok(*n),
+            Err(e) => Err(errno(e)),
+        };
+        self.recording.push(Event::Write {
+            offset,
+            data,
+            result,
+        });
+    }
+}
+
+/// Serves the events of a [`Recording`], in order, in place of a stream or
+/// a file.
+///
+/// Reads complete right away with the recorded data, and writes with the
+/// recorded number of bytes written, or with the recorded errors.
+///
+/// # Panics
+///
+/// The methods panic when the code under test diverges from the recording:
+/// when the next event is not the operation called, at another offset, when
+/// the data written differs from the recorded data, when a buffer cannot
+/// hold the recorded data read, or when the recording ran out of events.
+#[derive(Debug)]
+pub struct Replay {
+    events: RefCell<VecDeque<Event>>,
+}
+
+impl Replay {
+    /// Replays the events of `recording`, as recorded so far.
+    pub fn new(recording: &Recording) -> Replay {
+        Replay {
+            events: RefCell::new(recording.events().into()),
+        }
+    }
+
+    /// Returns whether all the events were served, as tests check once done.
+    pub fn is_done(&self) -> bool {
+        self.events.borrow().is_empty()
+    }
+
+    /// Serves the next recorded read of a stream.
+    pub async fn read<T: IoBufMut>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.serve_read(None, buf)
+    }
+
+    /// Serves the next recorded write to a stream.
+    pub async fn write<T: IoBuf>(&self, buf: T) -> crate::BufResult<usize, T> {
+        self.serve_write(None, buf)
+    }
+
+    /// Serves the next recorded read of a file.
+    pub async fn read_at<T: IoBufMut>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        self.serve_read(Some(pos), buf)
+    }
+
+    /// Serves the next recorded write to a file.
+    pub async fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        self.serve_write(Some(pos), buf)
+    }
+
+    fn serve_read<T: IoBufMut>(&self, pos: Option<u64>, mut buf: T) -> crate::BufResult<usize, T> {
+        let data = match self.next() {
+            Event::Read { offset, result } if offset == pos => match result {
+                Ok(data) => data,
+                Err(errno) => return (Err(io::Error::from_raw_os_error(errno)), buf),
+            },
+            event => panic!("replay diverged: read at {:?}, recorded {:?}", pos, event),
+        };
+
+        assert!(
+            data.len() <= buf.bytes_total(),
+            "replay diverged: read of {} bytes, recorded {} bytes",
+            buf.bytes_total(),
+            data.len()
+        );
+
+        // Safety: the buffer holds `data.len()` bytes, checked above
+        unsafe {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), buf.stable_mut_ptr(), data.len());
+            buf.set_init(data.len());
+        }
+        (Ok(data.len()), buf)
+    }
+
+    fn serve_write<T: IoBuf>(&self, pos: Option<u64>, buf: T) -> crate::BufResult<usize, T> {
+        let written = crate::buf::deref(&buf);
+
+        let res = match self.next() {
+            Event::Write {
+                offset,
+                data,
+                result,
+            } if offset == pos && data == written => result,
+            event => panic!(
+                "replay diverged: write of {:?} at {:?}, recorded {:?}",
+                written, pos, event
+            ),
+        };
+
+        (res.map_err(io::Error::from_raw_os_error), buf)
+    }
+
+    fn next(&self) -> Event {
+        match self.events.borrow_mut().pop_front() {
+            Some(event) => event,
+            None => panic!("replay diverged: no more recorded events"),
+        }
+    }
+}
+
+/// A stream whose reads and writes can be recorded.
+pub trait Stream: sealed::Sealed {}
+
+impl<S: sealed::Sealed> Stream for S {}
+
+type BufFuture<'a, T> = Pin<Box<dyn Future<Output = crate::BufResult<usize, T>> + 'a>>;
+
+mod sealed {
+    use super::BufFuture;
+    use crate::buf::{IoBuf, IoBufMut};
+    use crate::io::DuplexStream;
+    use crate::net::{TcpStream, UnixStream, VsockStream};
+
+    pub trait Sealed {
+        fn read<T: IoBufMut>(&self, buf: T) -> BufFuture<'_, T>;
+        fn write<T: IoBuf>(&self, buf: T) -> BufFuture<'_, T>;
+    }
+
+    macro_rules! impl_stream {
+        ($($ty:ty),*) => {
+            $(
+                impl Sealed for $ty {
+                    fn read<T: IoBufMut>(&self, buf: T) -> BufFuture<'_, T> {
+                        Box::pin(<$ty>::read(self, buf))
+                    }
+
+                    fn write<T: IoBuf>(&self, buf: T) -> BufFuture<'_, T> {
+                        Box::pin(<$ty>::write(self, buf))
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_stream!(TcpStream, UnixStream, VsockStream, DuplexStream);
+}
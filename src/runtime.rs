@@ -1,19 +1,37 @@
+use crate::builder::{Builder, Placement};
 use crate::driver::Driver;
+use crate::Features;
 
 use std::future::Future;
 use std::io;
+use std::task::Poll;
+use std::time::{Duration, Instant};
 use tokio::io::unix::AsyncFd;
 use tokio::task::LocalSet;
 
 pub(crate) struct Runtime {
-    /// io-uring driver
-    driver: AsyncFd<Driver>,
-
     /// LocalSet for !Send tasks
+    ///
+    /// Dropped before the driver, so that operations owned by tasks that are
+    /// still alive are released and can be cancelled on shutdown.
     local: LocalSet,
 
+    /// io-uring driver
+    driver: AsyncFd<Driver>,
+
     /// Tokio runtime, always current-thread
     rt: tokio::runtime::Runtime,
+
+    /// How long to poll for completions after the last one before sleeping,
+    /// `None` to never poll
+    spin: Option<Duration>,
+
+    /// Completions to wait for once woken up, and for how long
+    min_complete: Option<(usize, Duration)>,
+
+    /// CPUs and memory policy of the thread, restored once the driver is
+    /// dropped
+    _placement: Placement,
 }
 
 /// Spawns a new asynchronous task, returning a [`JoinHandle`] for it.
@@ -33,23 +51,117 @@ pub(crate) struct Runtime {
 /// that processes each received connection.
 ///
 /// ```no_run
+/// tokio_uring::start(async {
+///     let handle = tokio_uring::spawn(async {
+///         println!("hello from a background task");
+///     });
+///
+///     // Let the task complete
+///     handle.await.unwrap();
+/// });
+/// ```
+pub fn spawn<T: std::future::Future + 'static>(task: T) -> tokio::task::JoinHandle<T::Output> {
+    tokio::task::spawn_local(task)
+}
+
+/// Shuts the current runtime down without waiting for the operations in
+/// flight, for processes about to exit.
+///
+/// When a runtime shuts down, it cancels the operations in flight, and waits
+/// for the kernel to complete them, as the kernel may still use their
+/// buffers. Operations which cannot be canceled, such as reads of a file on
+/// an unresponsive network file system, hold up the shutdown until they
+/// complete. Once `shutdown_now` was called, the operations are canceled
+/// right away, and the runtime leaks their buffers and file descriptors when
+/// shutting down instead of waiting: the runtime returns as soon as the
+/// future passed to [`start`](crate::start) does, and the process can exit
+/// without the kernel writing to freed memory.
+///
+/// The tasks keep running until the runtime shuts down, and the operations
+/// they submit meanwhile are leaked as well. The memory leaked is only
+/// reclaimed by exiting, so this is meant for the last runtime of a process.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tokio_uring::fs::File;
+///
 /// fn main() {
 ///     tokio_uring::start(async {
-///         let handle = tokio_uring::spawn(async {
-///             println!("hello from a background task");
-///         });
+///         let file = File::open("/mnt/nfs/data.bin").await.unwrap();
+///         let read = file.read_at(vec![0; 4096], 0);
 ///
-///         // Let the task complete
-///         handle.await.unwrap();
+///         if tokio::time::timeout(Duration::from_secs(10), read).await.is_err() {
+///             // Exit even if the server does not answer the read
+///             tokio_uring::shutdown_now();
+///         }
 ///     });
 /// }
 /// ```
-pub fn spawn<T: std::future::Future + 'static>(task: T) -> tokio::task::JoinHandle<T::Output> {
-    tokio::task::spawn_local(task)
+pub fn shutdown_now() {
+    assert!(
+        crate::driver::is_current(),
+        "`shutdown_now` must be called from a `tokio-uring` runtime"
+    );
+    crate::driver::shutdown_now();
+}
+
+/// Returns the `io_uring` features supported by the ring of the current
+/// runtime, see [`Features`].
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+pub fn features() -> Features {
+    assert!(
+        crate::driver::is_current(),
+        "`features` must be called from a `tokio-uring` runtime"
+    );
+    crate::driver::features()
+}
+
+/// Runs a blocking syscall on the Tokio blocking pool, for operations
+/// `io-uring` does not support.
+pub(crate) async fn spawn_blocking<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
 }
 
 impl Runtime {
-    pub(crate) fn new() -> io::Result<Runtime> {
+    pub(crate) fn new(builder: &Builder) -> io::Result<Runtime> {
+        // Blocking on the runtime would block the thread driving the tasks of
+        // the outer runtime, which may be the ones the future waits for.
+        if crate::driver::is_current() {
+            return Err(io::Error::other(
+                "cannot start a `tokio-uring` runtime from within a `tokio-uring` runtime; \
+                 spawn the future with `tokio_uring::spawn` instead, or start the runtime \
+                 on another thread",
+            ));
+        }
+
+        // Likewise for a Tokio runtime, whose blocking threads are within it
+        // too
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(io::Error::other(
+                "cannot start a `tokio-uring` runtime from within a Tokio runtime; \
+                 start it on a thread of its own instead, e.g. with `std::thread::spawn`",
+            ));
+        }
+
+        // Applied first, for the rings to be allocated on the NUMA node
+        let placement = Placement::apply(builder)?;
+
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()?;
@@ -58,10 +170,42 @@ impl Runtime {
 
         let driver = {
             let _guard = rt.enter();
-            AsyncFd::new(Driver::new()?)?
+            AsyncFd::new(Driver::from_uring(builder.build_uring()?))?
         };
 
-        Ok(Runtime { driver, local, rt })
+        driver.get_ref().reserve_ops(builder.preallocated_ops());
+
+        if let Some((ops, backpressure)) = builder.max_ops_config() {
+            driver.get_ref().set_max_ops(ops, backpressure);
+        }
+
+        if let Some((ops, backpressure)) = builder.max_ops_per_fd_config() {
+            driver.get_ref().set_max_ops_per_fd(ops, backpressure);
+        }
+
+        let (retries, backpressure) = builder.busy_retries_config();
+        driver.get_ref().set_busy_retries(retries, backpressure);
+
+        if builder.seccomp_compatible_config() {
+            driver.get_ref().disable_optional_ops();
+        }
+
+        if let Some(watchdog) = builder.build_watchdog() {
+            driver.get_ref().set_watchdog(watchdog);
+        }
+
+        if let Some(slots) = builder.auto_register_files_config() {
+            driver.get_ref().auto_register_files(slots)?;
+        }
+
+        Ok(Runtime {
+            driver,
+            local,
+            rt,
+            spin: builder.spin_duration(),
+            min_complete: builder.min_complete_config(),
+            _placement: placement,
+        })
     }
 
     pub(crate) fn block_on<F>(&mut self, future: F) -> F::Output
@@ -71,19 +215,82 @@ impl Runtime {
         self.driver.get_ref().with(|| {
             let drive = async {
                 loop {
-                    // Wait for read-readiness
-                    let mut guard = self.driver.readable().await.unwrap();
+                    // Let the other tasks run, then submit again the
+                    // operations the kernel was too busy for
+                    if self.driver.get_ref().submission_deferred() {
+                        tokio::task::yield_now().await;
+                        self.driver.get_ref().tick();
+                        continue;
+                    }
+
+                    if let Some(spin) = self.spin {
+                        let mut last = Instant::now();
+
+                        // Keep the task woken while polling, so that the
+                        // thread never sleeps
+                        crate::future::poll_fn(|cx| {
+                            if self.driver.get_ref().poll_completions() {
+                                last = Instant::now();
+                            }
+
+                            if last.elapsed() < spin {
+                                cx.waker().wake_by_ref();
+                                Poll::Pending
+                            } else {
+                                Poll::Ready(())
+                            }
+                        })
+                        .await;
+                    }
+
+                    // Wait for read-readiness, or for a submission to be
+                    // deferred
+                    let readable = self.driver.readable();
+                    tokio::pin!(readable);
+                    let guard = crate::future::poll_fn(|cx| {
+                        if let Poll::Ready(guard) = readable.as_mut().poll(cx) {
+                            return Poll::Ready(Some(guard));
+                        }
+                        self.driver.get_ref().poll_deferred(cx).map(|()| None)
+                    })
+                    .await;
+                    let mut guard = match guard {
+                        Some(guard) => guard.unwrap(),
+                        None => continue,
+                    };
+
+                    if let Some((count, max_wait)) = self.min_complete {
+                        self.driver
+                            .get_ref()
+                            .wait_for_completions(count, max_wait)
+                            .unwrap();
+                    }
+
                     self.driver.get_ref().tick();
                     guard.clear_ready();
                 }
             };
 
+            let watch = async {
+                let period = match self.driver.get_ref().watchdog_period() {
+                    Some(period) => period,
+                    None => return std::future::pending().await,
+                };
+
+                loop {
+                    crate::time::sleep(period).await;
+                    self.driver.get_ref().check_slow_ops();
+                }
+            };
+
             tokio::pin!(drive);
+            tokio::pin!(watch);
             tokio::pin!(future);
 
             self.rt
                 .block_on(self.local.run_until(crate::future::poll_fn(|cx| {
                     assert!(drive.as_mut().poll(cx).is_pending());
+                    assert!(watch.as_mut().poll(cx).is_pending());
                     future.as_mut().poll(cx)
                 })))
         })
@@ -0,0 +1,336 @@
+//! Synchronization on futex words, shared with other processes.
+//!
+//! A [`Futex`] is a 32-bit word which tasks wait on until woken, with the
+//! `IORING_OP_FUTEX_WAIT` and `IORING_OP_FUTEX_WAKE` operations: the waiting
+//! task parks on the ring, not on its thread, and the other tasks of the
+//! runtime keep running. Mapped from a file with [`Futex::map`], the word is
+//! shared with the other processes mapping the same file, whether they wait
+//! on it through `io_uring` or with the `futex` system call.
+//!
+//! [`Mutex`] and [`Notify`] are built on top of a futex word, and
+//! synchronize the tasks of different runtimes and processes.
+//!
+//! These operations are supported since Linux 6.7, older kernels fail them
+//! with `EINVAL`.
+//!
+//! # Examples
+//!
+//! Serializing the updates of a file by several processes:
+//!
+//! ```no_run
+//! use tokio_uring::fs::File;
+//! use tokio_uring::sync::futex::{Futex, Mutex};
+//!
+//! fn main() -> std::io::Result<()> {
+//!     tokio_uring::start(async {
+//!         // A file of one page, shared by the processes
+//!         let lock = std::fs::OpenOptions::new()
+//!             .read(true)
+//!             .write(true)
+//!             .create(true)
+//!             .open("/dev/shm/app.lock")?;
+//!         lock.set_len(4096)?;
+//!
+//!         let mutex = Mutex::from_futex(Futex::map(&lock, 0)?);
+//!
+//!         let _guard = mutex.lock().await?;
+//!         let file = File::open("state").await?;
+//!         // ...
+//!         file.close().await
+//!     })
+//! }
+//! ```
+
+use crate::driver::{Op, Word};
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::task::{Context, Poll};
+
+/// A 32-bit word tasks wait on until woken.
+///
+/// Cloning a futex returns a handle to the same word.
+#[derive(Clone)]
+pub struct Futex {
+    word: Rc<Word>,
+}
+
+/// Lock on a futex word, shared with other processes.
+///
+/// The lock protects no data of its own: the data shared with other processes
+/// lives in shared memory as well, next to the word.
+///
+/// The word is 0 while unlocked, 1 while locked, and 2 while locked with
+/// waiters, as other implementations of futex locks, which the lock is
+/// compatible with.
+pub struct Mutex {
+    futex: Futex,
+}
+
+/// Holds a [`Mutex`] locked, unlocking it when dropped.
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a> {
+    mutex: &'a Mutex,
+}
+
+/// Notifies tasks, of this runtime or of other processes, of an event.
+///
+/// The word counts the notifications: a task waits for it to change.
+pub struct Notify {
+    futex: Futex,
+}
+
+/// Waits on a futex word, canceling the wait when dropped.
+struct Wait<'a> {
+    futex: &'a Futex,
+    op: Op<crate::driver::Futex>,
+    done: bool,
+}
+
+impl Futex {
+    /// Creates a futex word holding `value`, private to the process.
+    pub fn new(value: u32) -> Futex {
+        Futex {
+            word: Rc::new(Word::Boxed(Box::new(AtomicU32::new(value)))),
+        }
+    }
+
+    /// Maps the futex word at `offset` in `file`, which is shared with the
+    /// other processes mapping it, such as a file of `/dev/shm`.
+    ///
+    /// Fails if `offset` is not a multiple of 4, or if the file does not
+    /// hold the 4 bytes at `offset`.
+    pub fn map(file: &impl AsRawFd, offset: u64) -> io::Result<Futex> {
+        Ok(Futex {
+            word: Rc::new(Word::map(file.as_raw_fd(), offset)?),
+        })
+    }
+
+    /// Returns the word.
+    pub fn value(&self) -> &AtomicU32 {
+        self.word.as_atomic()
+    }
+
+    /// Waits until woken, if the word holds `expected`.
+    ///
+    /// Completes right away if the word does not hold `expected`. The task
+    /// may be woken spuriously as well: the word is checked again once the
+    /// wait completes. Dropping the future cancels the wait, and wakes
+    /// another waiter, in case the wait consumed a wake-up meant for it.
+    pub async fn wait(&self, expected: u32) -> io::Result<()> {
+        let op = Op::futex_wait(&self.word, expected)?;
+
+        let res = Wait {
+            futex: self,
+            op,
+            done: false,
+        }
+        .await;
+
+        match res {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            res => res,
+        }
+    }
+
+    /// Wakes up to `count` tasks waiting on the word, returning the number
+    /// of tasks woken.
+    pub async fn wake(&self, count: u32) -> io::Result<usize> {
+        let op = Op::futex_wake(&self.word, count)?;
+        Ok(op.await.result? as usize)
+    }
+
+    /// Wakes up to `count` tasks waiting on the word, without waiting for
+    /// the wake to complete.
+    fn wake_in_background(&self, count: u32) {
+        // The operation completes in the background once dropped
+        if Op::futex_wake(&self.word, count).is_err() {
+            // No room in the ring, wake with the system call instead
+            let flags = match &*self.word {
+                Word::Boxed(_) => libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                Word::Mapped { .. } => libc::FUTEX_WAKE,
+            };
+
+            // Safety: the word lives as long as `self`
+            unsafe {
+                libc::syscall(
+                    libc::SYS_futex,
+                    self.value() as *const AtomicU32,
+                    flags,
+                    count,
+                )
+            };
+        }
+    }
+}
+
+impl Future for Wait<'_> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let completion = ready!(Pin::new(&mut self.op).poll(cx));
+        self.done = true;
+        Poll::Ready(completion.result.map(drop))
+    }
+}
+
+impl Drop for Wait<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.op.request_cancel();
+            self.futex.wake_in_background(1);
+        }
+    }
+}
+
+impl Mutex {
+    /// Creates an unlocked mutex, private to the process.
+    pub fn new() -> Mutex {
+        Mutex::from_futex(Futex::new(0))
+    }
+
+    /// Creates a mutex locked through `futex`, which holds 0 if unlocked.
+    pub fn from_futex(futex: Futex) -> Mutex {
+        Mutex { futex }
+    }
+
+    /// Locks the mutex, waiting until it is unlocked.
+    ///
+    /// Fails if waiting on the futex word fails. Dropping the future while
+    /// waiting gives up on the lock.
+    pub async fn lock(&self) -> io::Result<MutexGuard<'_>> {
+        let word = self.futex.value();
+
+        let mut state = match word.compare_exchange(0, 1, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => return Ok(MutexGuard { mutex: self }),
+            Err(state) => state,
+        };
+
+        // Flag the waiters, for the owner to wake one of them on unlock
+        if state != 2 {
+            state = word.swap(2, Ordering::Acquire);
+        }
+
+        while state != 0 {
+            self.futex.wait(2).await?;
+            state = word.swap(2, Ordering::Acquire);
+        }
+
+        Ok(MutexGuard { mutex: self })
+    }
+
+    /// Locks the mutex if it is unlocked.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_>> {
+        self.futex
+            .value()
+            .compare_exchange(0, 1, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| MutexGuard { mutex: self })
+    }
+
+    /// Returns the futex word of the mutex.
+    pub fn futex(&self) -> &Futex {
+        &self.futex
+    }
+}
+
+impl Default for Mutex {
+    fn default() -> Mutex {
+        Mutex::new()
+    }
+}
+
+impl Drop for MutexGuard<'_> {
+    fn drop(&mut self) {
+        let futex = &self.mutex.futex;
+
+        if futex.value().swap(0, Ordering::Release) == 2 {
+            futex.wake_in_background(1);
+        }
+    }
+}
+
+impl Notify {
+    /// Creates a notifier, private to the process.
+    pub fn new() -> Notify {
+        Notify::from_futex(Futex::new(0))
+    }
+
+    /// Creates a notifier counting its notifications in `futex`.
+    pub fn from_futex(futex: Futex) -> Notify {
+        Notify { futex }
+    }
+
+    /// Returns a future waiting for the next notification.
+    ///
+    /// The notifications are counted from the call, not from the first poll:
+    /// calling `notified` before checking whether the event already happened
+    /// does not miss a notification sent in between.
+    pub fn notified(&self) -> impl Future<Output = io::Result<()>> + '_ {
+        let count = self.futex.value().load(Ordering::Acquire);
+
+        async move {
+            while self.futex.value().load(Ordering::Acquire) == count {
+                self.futex.wait(count).await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Notifies one waiting task.
+    pub fn notify_one(&self) {
+        self.futex.value().fetch_add(1, Ordering::Release);
+        self.futex.wake_in_background(1);
+    }
+
+    /// Notifies all the waiting tasks.
+    pub fn notify_all(&self) {
+        self.futex.value().fetch_add(1, Ordering::Release);
+        self.futex.wake_in_background(i32::MAX as u32);
+    }
+
+    /// Returns the futex word of the notifier.
+    pub fn futex(&self) -> &Futex {
+        &self.futex
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Notify {
+        Notify::new()
+    }
+}
+
+impl fmt::Debug for Futex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Futex")
+            .field("value", self.value())
+            .field("shared", &matches!(*self.word, Word::Mapped { .. }))
+            .finish()
+    }
+}
+
+impl fmt::Debug for Mutex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mutex").field("futex", &self.futex).finish()
+    }
+}
+
+impl fmt::Debug for MutexGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MutexGuard").finish()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("futex", &self.futex)
+            .finish()
+    }
+}
@@ -0,0 +1,22 @@
+//! Synchronization primitives for tasks on a `tokio-uring` runtime.
+//!
+//! All the tasks of a `tokio-uring` runtime run on the same thread, so these
+//! primitives track their state with plain reference counts and `RefCell`s,
+//! without atomic operations. Their handles are `!Send`, and can only be used
+//! by the tasks of the runtime that created them.
+//!
+//! Threads outside of the runtime send messages with an
+//! [`mpsc::RemoteSender`], which wakes the runtime through the ring.
+//!
+//! The primitives of the [`futex`] module synchronize tasks of different
+//! runtimes and processes, through futex words.
+//!
+//! [`OpsPermit`] bounds the operations in flight by the capacity of the ring
+//! of the runtime.
+
+pub mod futex;
+pub mod mpsc;
+pub mod oneshot;
+
+mod semaphore;
+pub use semaphore::{AcquireError, OpsPermit, Semaphore, SemaphorePermit, TryAcquireError};
@@ -0,0 +1,429 @@
+//! A bounded multi-producer, single-consumer channel.
+//!
+//! The channel buffers up to a fixed number of messages. Once the buffer is
+//! full, [`Sender::send`] waits for the receiver to make room, which pushes
+//! back on fast producers.
+//!
+//! [`Sender`] and [`Receiver`] are `!Send`: they are meant for the tasks of
+//! one `tokio-uring` runtime, and share the channel without atomic
+//! operations. Other threads send messages with a [`RemoteSender`], created
+//! by [`Receiver::remote_sender`], which wakes the receiver through an
+//! `eventfd` polled by the ring.
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_uring::sync::mpsc;
+//!
+//! tokio_uring::start(async {
+//!     let (tx, mut rx) = mpsc::channel(16);
+//!
+//!     tokio_uring::spawn(async move {
+//!         for i in 0..10 {
+//!             tx.send(i).await.unwrap();
+//!         }
+//!     });
+//!
+//!     let mut sum = 0;
+//!     while let Some(i) = rx.recv().await {
+//!         sum += i;
+//!     }
+//!     assert_eq!(sum, 45);
+//! });
+//! ```
+
+mod remote;
+pub use remote::RemoteSender;
+
+use crate::driver::{Op, PollAdd, SharedFd};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// Creates a bounded channel buffering up to `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "mpsc channel capacity must be positive");
+
+    let chan = Rc::new(RefCell::new(Chan {
+        buffer: VecDeque::with_capacity(capacity),
+        capacity,
+        rx_waker: None,
+        tx_wakers: Vec::new(),
+        senders: 1,
+        rx_closed: false,
+        remote: None,
+    }));
+
+    let tx = Sender { chan: chan.clone() };
+    let rx = Receiver {
+        chan,
+        eventfd: None,
+        poll: None,
+    };
+    (tx, rx)
+}
+
+/// Sends messages to the [`Receiver`] of a channel, from the tasks of the
+/// runtime.
+///
+/// Senders are cloned to send from several tasks. The channel is closed for
+/// the receiver once all the senders are dropped.
+pub struct Sender<T> {
+    chan: Rc<RefCell<Chan<T>>>,
+}
+
+/// Receives the messages of a channel.
+pub struct Receiver<T> {
+    chan: Rc<RefCell<Chan<T>>>,
+
+    /// Duplicate of the `eventfd` of the remote senders, once created
+    eventfd: Option<SharedFd>,
+
+    /// Poll of the `eventfd`, kept in flight between calls to `recv`
+    poll: Option<Op<PollAdd>>,
+}
+
+/// Error returned by [`Sender::send`] once the receiver is closed, holding the
+/// message.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+/// Error returned by [`Sender::try_send`], holding the message.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// The buffer of the channel is full.
+    Full(T),
+
+    /// The receiver is closed.
+    Closed(T),
+}
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    /// No message is buffered, but senders remain.
+    Empty,
+
+    /// No message is buffered, and all the senders were dropped.
+    Disconnected,
+}
+
+struct Chan<T> {
+    buffer: VecDeque<T>,
+    capacity: usize,
+
+    /// Waker of the receiver, waiting for a message
+    rx_waker: Option<Waker>,
+
+    /// Wakers of the senders waiting for room in the buffer
+    tx_wakers: Vec<Waker>,
+
+    /// Number of live `Sender` handles
+    senders: usize,
+
+    /// Set once the receiver is closed or dropped
+    rx_closed: bool,
+
+    /// State shared with the remote senders, once created
+    remote: Option<Arc<remote::Remote<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends a message, waiting for room in the buffer if it is full.
+    ///
+    /// Returns the message in an error if the receiver is closed.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut value = Some(value);
+
+        crate::future::poll_fn(|cx| {
+            let mut chan = self.chan.borrow_mut();
+
+            if chan.rx_closed {
+                return Poll::Ready(Err(SendError(value.take().unwrap())));
+            }
+
+            if chan.buffer.len() < chan.capacity {
+                chan.push(value.take().unwrap());
+                return Poll::Ready(Ok(()));
+            }
+
+            if !chan.tx_wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                chan.tx_wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Sends a message if there is room in the buffer, without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut chan = self.chan.borrow_mut();
+
+        if chan.rx_closed {
+            Err(TrySendError::Closed(value))
+        } else if chan.buffer.len() < chan.capacity {
+            chan.push(value);
+            Ok(())
+        } else {
+            Err(TrySendError::Full(value))
+        }
+    }
+
+    /// Returns `true` if the receiver is closed.
+    pub fn is_closed(&self) -> bool {
+        self.chan.borrow().rx_closed
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.chan.borrow_mut().senders += 1;
+        Sender {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut chan = self.chan.borrow_mut();
+        chan.senders -= 1;
+
+        // The receiver sees the end of the messages
+        if chan.senders == 0 {
+            if let Some(waker) = chan.rx_waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chan = self.chan.borrow();
+        f.debug_struct("Sender")
+            .field("len", &chan.buffer.len())
+            .field("capacity", &chan.capacity)
+            .field("closed", &chan.rx_closed)
+            .finish()
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receives the next message, waiting for one if none is buffered.
+    ///
+    /// Returns `None` once the buffer is empty and all the senders, local and
+    /// remote, were dropped, or the receiver was closed.
+    pub async fn recv(&mut self) -> Option<T> {
+        crate::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receives the next message if one is buffered, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut chan = self.chan.borrow_mut();
+
+        match chan.pop() {
+            Some(value) => Ok(value),
+            None if chan.is_disconnected() => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Closes the channel: senders can no longer send, but the messages
+    /// already buffered can still be received.
+    pub fn close(&mut self) {
+        let mut chan = self.chan.borrow_mut();
+        chan.rx_closed = true;
+
+        for waker in chan.tx_wakers.drain(..) {
+            waker.wake();
+        }
+
+        if let Some(remote) = &chan.remote {
+            remote.close();
+        }
+    }
+
+    /// Creates a sender usable from any thread.
+    ///
+    /// The first call creates the `eventfd` the remote senders signal new
+    /// messages with. Remote senders have a buffer of their own, with the
+    /// same capacity as the channel.
+    pub fn remote_sender(&mut self) -> io::Result<RemoteSender<T>>
+    where
+        T: Send,
+    {
+        let mut chan = self.chan.borrow_mut();
+
+        if let Some(remote) = &chan.remote {
+            return Ok(RemoteSender::new(remote.clone()));
+        }
+
+        let remote = Arc::new(remote::Remote::new(chan.capacity, chan.rx_closed)?);
+        let fd = syscall!(fcntl(remote.eventfd(), libc::F_DUPFD_CLOEXEC, 0))?;
+        self.eventfd = Some(SharedFd::new(fd));
+        chan.remote = Some(remote.clone());
+
+        Ok(RemoteSender::new(remote))
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        loop {
+            {
+                let mut chan = self.chan.borrow_mut();
+
+                if let Some(value) = chan.pop() {
+                    return Poll::Ready(Some(value));
+                }
+
+                if chan.is_disconnected() {
+                    return Poll::Ready(None);
+                }
+
+                match &chan.rx_waker {
+                    Some(waker) if waker.will_wake(cx.waker()) => {}
+                    _ => chan.rx_waker = Some(cx.waker().clone()),
+                }
+            }
+
+            // Remote senders signal the `eventfd` when their buffer is no
+            // longer empty.
+            let eventfd = match &self.eventfd {
+                Some(eventfd) => eventfd,
+                None => return Poll::Pending,
+            };
+
+            let poll = match &mut self.poll {
+                Some(poll) => poll,
+                None => {
+                    let op = Op::poll_add(eventfd, libc::POLLIN)
+                        .expect("failed to submit the eventfd poll");
+                    self.poll.insert(op)
+                }
+            };
+
+            ready!(Pin::new(poll).poll(cx));
+            self.poll = None;
+
+            // Reset the `eventfd`, which is non-blocking
+            let mut count = 0u64;
+            unsafe {
+                libc::read(
+                    eventfd.raw_fd(),
+                    &mut count as *mut u64 as *mut libc::c_void,
+                    8,
+                );
+            }
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+
+        // Complete the poll of the `eventfd`, so that it does not linger
+        if self.poll.is_some() {
+            if let Some(remote) = &self.chan.borrow().remote {
+                remote.notify();
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chan = self.chan.borrow();
+        f.debug_struct("Receiver")
+            .field("len", &chan.buffer.len())
+            .field("capacity", &chan.capacity)
+            .field("closed", &chan.rx_closed)
+            .finish()
+    }
+}
+
+impl<T> Chan<T> {
+    /// Buffers a message, there must be room for it.
+    fn push(&mut self, value: T) {
+        self.buffer.push_back(value);
+
+        if let Some(waker) = self.rx_waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Takes the next message, local messages first.
+    fn pop(&mut self) -> Option<T> {
+        if let Some(value) = self.buffer.pop_front() {
+            // There is room for the waiting senders
+            for waker in self.tx_wakers.drain(..) {
+                waker.wake();
+            }
+            return Some(value);
+        }
+
+        self.remote.as_ref().and_then(|remote| remote.pop())
+    }
+
+    /// Returns `true` if no message can arrive anymore.
+    fn is_disconnected(&self) -> bool {
+        let remote_senders = self.remote.as_ref().is_some_and(|r| r.has_senders());
+        self.rx_closed || (self.senders == 0 && !remote_senders)
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl<T> std::error::Error for SendError<T> {}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => f.write_str("Full(..)"),
+            TrySendError::Closed(..) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => write!(f, "no available capacity"),
+            TrySendError::Closed(..) => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Disconnected => write!(f, "channel disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
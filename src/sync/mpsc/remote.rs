@@ -0,0 +1,250 @@
+use crate::future::ThreadWaker;
+use crate::sync::mpsc::{SendError, TrySendError};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// Sends messages to the [`Receiver`] of a channel from any thread.
+///
+/// Created by [`Receiver::remote_sender`]. Messages are buffered apart from
+/// the ones of local senders, and the receiving runtime is woken through an
+/// `eventfd` polled by its ring, when the buffer stops being empty.
+///
+/// [`send`](RemoteSender::send) can be awaited on any executor, such as a
+/// multi-threaded Tokio runtime, and [`blocking_send`] waits by parking the
+/// thread.
+///
+/// [`Receiver`]: super::Receiver
+/// [`Receiver::remote_sender`]: super::Receiver::remote_sender
+/// [`blocking_send`]: RemoteSender::blocking_send
+///
+/// # Examples
+///
+/// ```
+/// use tokio_uring::sync::mpsc;
+///
+/// tokio_uring::start(async {
+///     let (tx, mut rx) = mpsc::channel::<u32>(16);
+///     drop(tx);
+///
+///     let remote = rx.remote_sender().unwrap();
+///     std::thread::spawn(move || {
+///         remote.blocking_send(42).unwrap();
+///     });
+///
+///     assert_eq!(rx.recv().await, Some(42));
+///     assert_eq!(rx.recv().await, None);
+/// });
+/// ```
+pub struct RemoteSender<T> {
+    remote: Arc<Remote<T>>,
+}
+
+/// State shared by the receiver and the remote senders.
+pub(super) struct Remote<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+
+    /// Signalled when the buffer stops being empty, or the channel closes
+    eventfd: File,
+}
+
+struct State<T> {
+    buffer: VecDeque<T>,
+
+    /// Wakers of the senders waiting for room in the buffer
+    tx_wakers: Vec<Waker>,
+
+    /// Number of live `RemoteSender` handles
+    senders: usize,
+
+    /// Set once the receiver is closed or dropped
+    rx_closed: bool,
+}
+
+impl<T> RemoteSender<T> {
+    pub(super) fn new(remote: Arc<Remote<T>>) -> RemoteSender<T> {
+        remote.lock().senders += 1;
+        RemoteSender { remote }
+    }
+
+    /// Sends a message, waiting for room in the buffer if it is full.
+    ///
+    /// Returns the message in an error if the receiver is closed.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut value = Some(value);
+        crate::future::poll_fn(|cx| self.poll_send(cx, &mut value)).await
+    }
+
+    /// Sends a message, blocking the thread while the buffer is full.
+    ///
+    /// Returns the message in an error if the receiver is closed. This must
+    /// not be called from an asynchronous task.
+    pub fn blocking_send(&self, value: T) -> Result<(), SendError<T>> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut value = Some(value);
+
+        loop {
+            match self.poll_send(&mut cx, &mut value) {
+                Poll::Ready(res) => return res,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Sends a message if there is room in the buffer, without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let state = self.remote.lock();
+
+        if state.rx_closed {
+            Err(TrySendError::Closed(value))
+        } else if state.buffer.len() < self.remote.capacity {
+            self.remote.push(state, value);
+            Ok(())
+        } else {
+            Err(TrySendError::Full(value))
+        }
+    }
+
+    /// Returns `true` if the receiver is closed.
+    pub fn is_closed(&self) -> bool {
+        self.remote.lock().rx_closed
+    }
+
+    fn poll_send(
+        &self,
+        cx: &mut Context<'_>,
+        value: &mut Option<T>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        let mut state = self.remote.lock();
+
+        if state.rx_closed {
+            return Poll::Ready(Err(SendError(value.take().unwrap())));
+        }
+
+        if state.buffer.len() < self.remote.capacity {
+            self.remote.push(state, value.take().unwrap());
+            return Poll::Ready(Ok(()));
+        }
+
+        if !state.tx_wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.tx_wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Clone for RemoteSender<T> {
+    fn clone(&self) -> RemoteSender<T> {
+        RemoteSender::new(self.remote.clone())
+    }
+}
+
+impl<T> Drop for RemoteSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.remote.lock();
+        state.senders -= 1;
+
+        // The receiver sees the end of the messages
+        if state.senders == 0 {
+            drop(state);
+            self.remote.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for RemoteSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.remote.lock();
+        f.debug_struct("RemoteSender")
+            .field("len", &state.buffer.len())
+            .field("capacity", &self.remote.capacity)
+            .field("closed", &state.rx_closed)
+            .finish()
+    }
+}
+
+impl<T> Remote<T> {
+    pub(super) fn new(capacity: usize, rx_closed: bool) -> io::Result<Remote<T>> {
+        let fd = syscall!(eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK))?;
+
+        Ok(Remote {
+            state: Mutex::new(State {
+                buffer: VecDeque::with_capacity(capacity),
+                tx_wakers: Vec::new(),
+                senders: 0,
+                rx_closed,
+            }),
+            capacity,
+            eventfd: unsafe { File::from_raw_fd(fd) },
+        })
+    }
+
+    pub(super) fn eventfd(&self) -> RawFd {
+        self.eventfd.as_raw_fd()
+    }
+
+    /// Takes the next message, waking the senders waiting for room.
+    pub(super) fn pop(&self) -> Option<T> {
+        let mut state = self.lock();
+        let value = state.buffer.pop_front()?;
+        let wakers = std::mem::take(&mut state.tx_wakers);
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+        Some(value)
+    }
+
+    pub(super) fn has_senders(&self) -> bool {
+        self.lock().senders > 0
+    }
+
+    /// Closes the channel, failing the senders waiting for room.
+    pub(super) fn close(&self) {
+        let mut state = self.lock();
+        state.rx_closed = true;
+        let wakers = std::mem::take(&mut state.tx_wakers);
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Signals the receiver.
+    pub(super) fn notify(&self) {
+        let one = 1u64;
+
+        // The counter cannot overflow in practice, the receiver resets it
+        unsafe {
+            libc::write(self.eventfd(), &one as *const u64 as *const libc::c_void, 8);
+        }
+    }
+
+    /// Buffers a message, there must be room for it.
+    fn push(&self, mut state: MutexGuard<'_, State<T>>, value: T) {
+        let was_empty = state.buffer.is_empty();
+        state.buffer.push_back(value);
+        drop(state);
+
+        // A receiver waiting for messages saw an empty buffer, it is woken by
+        // the first message.
+        if was_empty {
+            self.notify();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // The state stays consistent if a holder of the lock panics
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,221 @@
+//! A channel sending a single message between tasks of the runtime.
+//!
+//! [`Sender`] and [`Receiver`] are `!Send`, and share the channel without
+//! atomic operations.
+//!
+//! # Examples
+//!
+//! ```
+//! use tokio_uring::sync::oneshot;
+//!
+//! tokio_uring::start(async {
+//!     let (tx, rx) = oneshot::channel();
+//!
+//!     tokio_uring::spawn(async move {
+//!         tx.send("done").unwrap();
+//!     });
+//!
+//!     assert_eq!(rx.await, Ok("done"));
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+/// Creates a channel sending a single message.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Rc::new(RefCell::new(Inner {
+        value: None,
+        rx_waker: None,
+        tx_waker: None,
+        tx_dropped: false,
+        rx_closed: false,
+    }));
+
+    let tx = Sender {
+        inner: inner.clone(),
+    };
+    let rx = Receiver { inner };
+    (tx, rx)
+}
+
+/// Sends the message of a channel.
+pub struct Sender<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+/// Receives the message of a channel, by awaiting it.
+///
+/// Completes with an error if the sender is dropped without sending.
+pub struct Receiver<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+/// Error returned by awaiting a [`Receiver`] whose sender was dropped without
+/// sending.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RecvError(());
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    /// The message was not sent yet.
+    Empty,
+
+    /// The sender was dropped without sending, or the message was already
+    /// received.
+    Closed,
+}
+
+struct Inner<T> {
+    value: Option<T>,
+
+    /// Waker of the receiver, waiting for the message
+    rx_waker: Option<Waker>,
+
+    /// Waker of the sender, waiting for the receiver to close
+    tx_waker: Option<Waker>,
+
+    /// Set once the sender sent the message or was dropped
+    tx_dropped: bool,
+
+    /// Set once the receiver is closed or dropped
+    rx_closed: bool,
+}
+
+impl<T> Sender<T> {
+    /// Sends the message.
+    ///
+    /// Returns the message in an error if the receiver is closed.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.rx_closed {
+            return Err(value);
+        }
+
+        inner.value = Some(value);
+        Ok(())
+        // Dropping the sender wakes the receiver
+    }
+
+    /// Returns `true` if the receiver is closed, and a message would not be
+    /// received.
+    pub fn is_closed(&self) -> bool {
+        self.inner.borrow().rx_closed
+    }
+
+    /// Waits for the receiver to be closed, for example to stop computing a
+    /// message nobody waits for anymore.
+    pub async fn closed(&mut self) {
+        crate::future::poll_fn(|cx| {
+            let mut inner = self.inner.borrow_mut();
+
+            if inner.rx_closed {
+                return Poll::Ready(());
+            }
+
+            inner.tx_waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.tx_dropped = true;
+
+        if let Some(waker) = inner.rx_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Receives the message if it was sent, without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut inner = self.inner.borrow_mut();
+
+        match inner.value.take() {
+            Some(value) => Ok(value),
+            None if inner.tx_dropped => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Closes the channel, so that the sender can no longer send.
+    ///
+    /// A message sent before closing can still be received.
+    pub fn close(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.rx_closed = true;
+
+        if let Some(waker) = inner.tx_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut inner = self.inner.borrow_mut();
+
+        if let Some(value) = inner.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+
+        if inner.tx_dropped {
+            return Poll::Ready(Err(RecvError(())));
+        }
+
+        inner.rx_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish()
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Closed => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
@@ -0,0 +1,390 @@
+use crate::driver::OpPermit;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// Bounds the number of tasks of the runtime running a section at once.
+///
+/// Permits are handed to the waiting tasks in the order they started waiting,
+/// so a task acquiring many permits is not starved by tasks acquiring fewer.
+///
+/// To bound the operations in flight by the capacity of the ring rather than
+/// by an arbitrary number, see [`OpsPermit`].
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use tokio_uring::sync::Semaphore;
+///
+/// tokio_uring::start(async {
+///     let semaphore = Rc::new(Semaphore::new(2));
+///
+///     let tasks: Vec<_> = (0..8)
+///         .map(|_| {
+///             let semaphore = semaphore.clone();
+///             tokio_uring::spawn(async move {
+///                 let _permit = semaphore.acquire().await.unwrap();
+///                 // At most two tasks run here at once
+///             })
+///         })
+///         .collect();
+///
+///     for task in tasks {
+///         task.await.unwrap();
+///     }
+/// });
+/// ```
+pub struct Semaphore {
+    state: RefCell<State>,
+}
+
+/// Permits acquired from a [`Semaphore`], released when dropped.
+#[must_use]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+/// Error returned by [`Semaphore::acquire`] when the semaphore is closed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AcquireError(());
+
+/// Error returned by [`Semaphore::try_acquire`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryAcquireError {
+    /// The semaphore is closed.
+    Closed,
+
+    /// Not enough permits are available, or other tasks are waiting for them.
+    NoPermits,
+}
+
+/// A permit bounding the operations in flight by the capacity of the ring.
+///
+/// A permit is acquired once fewer permits than entries of the completion
+/// queue are held, and fewer operations than entries of the completion queue
+/// are in flight, including the operations submitted without a permit. A task
+/// submitting one operation at a time while holding a permit keeps the ring
+/// from overflowing, whatever the number of tasks.
+///
+/// If the runtime bounds the operations in flight with
+/// [`Builder::max_ops`](crate::Builder::max_ops) to fewer than the entries of
+/// the completion queue, permits are bounded by that number instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_uring::fs::File;
+/// use tokio_uring::sync::OpsPermit;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     tokio_uring::start(async {
+///         let tasks: Vec<_> = (0..10_000)
+///             .map(|i| {
+///                 tokio_uring::spawn(async move {
+///                     let _permit = OpsPermit::acquire().await?;
+///                     let file = File::open(format!("data/{}", i)).await?;
+///                     let (res, _) = file.read_at(vec![0; 4096], 0).await;
+///                     res
+///                 })
+///             })
+///             .collect();
+///
+///         for task in tasks {
+///             task.await.unwrap()?;
+///         }
+///         Ok(())
+///     })
+/// }
+/// ```
+#[must_use]
+pub struct OpsPermit {
+    permit: OpPermit,
+}
+
+struct State {
+    permits: usize,
+    closed: bool,
+
+    /// Tasks waiting for permits, in order
+    waiters: VecDeque<Waiter>,
+
+    /// Identifies the next waiter
+    next_id: u64,
+}
+
+struct Waiter {
+    id: u64,
+    permits: usize,
+    waker: Waker,
+}
+
+/// Future acquiring permits, leaving the queue of waiters when dropped.
+struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+
+    /// Set while in the queue of waiters
+    id: Option<u64>,
+}
+
+impl Semaphore {
+    /// Creates a semaphore with `permits` available permits.
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            state: RefCell::new(State {
+                permits,
+                closed: false,
+                waiters: VecDeque::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    /// Returns the number of available permits.
+    pub fn available_permits(&self) -> usize {
+        self.state.borrow().permits
+    }
+
+    /// Adds `n` permits, waking the waiting tasks they satisfy.
+    pub fn add_permits(&self, n: usize) {
+        let mut state = self.state.borrow_mut();
+        state.permits += n;
+        state.wake_head();
+    }
+
+    /// Acquires a permit, waiting until one is available.
+    ///
+    /// Fails if the semaphore is closed.
+    pub async fn acquire(&self) -> Result<SemaphorePermit<'_>, AcquireError> {
+        self.acquire_many(1).await
+    }
+
+    /// Acquires `n` permits at once, waiting until they are available.
+    ///
+    /// Fails if the semaphore is closed.
+    pub async fn acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_>, AcquireError> {
+        Acquire {
+            semaphore: self,
+            permits: n,
+            id: None,
+        }
+        .await
+    }
+
+    /// Acquires a permit if one is available and no task waits for one.
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_many(1)
+    }
+
+    /// Acquires `n` permits if they are available and no task waits for
+    /// permits.
+    pub fn try_acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        let mut state = self.state.borrow_mut();
+
+        if state.closed {
+            Err(TryAcquireError::Closed)
+        } else if state.waiters.is_empty() && state.permits >= n {
+            state.permits -= n;
+            Ok(SemaphorePermit {
+                semaphore: self,
+                permits: n,
+            })
+        } else {
+            Err(TryAcquireError::NoPermits)
+        }
+    }
+
+    /// Closes the semaphore, failing the tasks waiting for permits and the
+    /// later acquisitions.
+    ///
+    /// The permits already acquired are unaffected.
+    pub fn close(&self) {
+        let mut state = self.state.borrow_mut();
+        state.closed = true;
+
+        for waiter in &state.waiters {
+            waiter.waker.wake_by_ref();
+        }
+    }
+
+    /// Returns `true` if the semaphore is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.borrow().closed
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("waiters", &state.waiters.len())
+            .field("closed", &state.closed)
+            .finish()
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Returns the number of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Drops the permits without releasing them to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+impl OpsPermit {
+    /// Acquires a permit, waiting until the operations in flight and the
+    /// permits held are below the capacity of the ring.
+    ///
+    /// Fails if called outside of a `tokio-uring` runtime.
+    pub async fn acquire() -> io::Result<OpsPermit> {
+        let permit = crate::future::poll_fn(OpPermit::poll_acquire).await?;
+        Ok(OpsPermit { permit })
+    }
+
+    /// Returns the number of entries of the completion queue, or the maximum
+    /// number of operations of the runtime if lower, bounding both the
+    /// permits held and the operations in flight.
+    pub fn limit(&self) -> usize {
+        self.permit.limit()
+    }
+}
+
+impl fmt::Debug for OpsPermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpsPermit")
+            .field("limit", &self.limit())
+            .finish()
+    }
+}
+
+impl State {
+    /// Wakes the first waiting task, if enough permits are available for it.
+    fn wake_head(&self) {
+        if let Some(waiter) = self.waiters.front() {
+            if waiter.permits <= self.permits || self.closed {
+                waiter.waker.wake_by_ref();
+            }
+        }
+    }
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Result<SemaphorePermit<'a>, AcquireError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        let mut state = semaphore.state.borrow_mut();
+
+        if state.closed {
+            if let Some(id) = self.id.take() {
+                state.waiters.retain(|waiter| waiter.id != id);
+            }
+            return Poll::Ready(Err(AcquireError(())));
+        }
+
+        let first = match (self.id, state.waiters.front()) {
+            (Some(id), Some(head)) => head.id == id,
+            (None, head) => head.is_none(),
+            (Some(_), None) => unreachable!("waiter left the queue"),
+        };
+
+        if first && state.permits >= self.permits {
+            state.permits -= self.permits;
+            if self.id.take().is_some() {
+                state.waiters.pop_front();
+            }
+
+            // The next task may be satisfied by the remaining permits
+            state.wake_head();
+
+            return Poll::Ready(Ok(SemaphorePermit {
+                semaphore,
+                permits: self.permits,
+            }));
+        }
+
+        match self.id {
+            Some(id) => {
+                let waiter = state.waiters.iter_mut().find(|w| w.id == id).unwrap();
+                if !waiter.waker.will_wake(cx.waker()) {
+                    waiter.waker = cx.waker().clone();
+                }
+            }
+            None => {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.waiters.push_back(Waiter {
+                    id,
+                    permits: self.permits,
+                    waker: cx.waker().clone(),
+                });
+                drop(state);
+                self.id = Some(id);
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            let mut state = self.semaphore.state.borrow_mut();
+            let was_first = state.waiters.front().map(|w| w.id) == Some(id);
+            state.waiters.retain(|waiter| waiter.id != id);
+
+            // The next task may be satisfied by the available permits
+            if was_first {
+                state.wake_head();
+            }
+        }
+    }
+}
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "semaphore closed")
+    }
+}
+
+impl std::error::Error for AcquireError {}
+
+impl fmt::Display for TryAcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryAcquireError::Closed => write!(f, "semaphore closed"),
+            TryAcquireError::NoPermits => write!(f, "no permits available"),
+        }
+    }
+}
+
+impl std::error::Error for TryAcquireError {}
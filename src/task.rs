@@ -0,0 +1,38 @@
+//! Asynchronous task utilities.
+
+use crate::driver::Op;
+
+/// Yields execution back to the `tokio-uring` runtime.
+///
+/// A task yielding with this function is not resumed until the driver has
+/// processed the completions that are currently available. This is done by
+/// submitting a no-op to the ring and waiting for its completion, which the
+/// kernel posts after any operation that completed before it. Compute heavy
+/// tasks can call `yield_now` periodically so that I/O performed by other
+/// tasks on the same thread makes progress.
+///
+/// When called outside of a `tokio-uring` runtime, this falls back to
+/// [`tokio::task::yield_now`].
+///
+/// # Examples
+///
+/// ```no_run
+/// tokio_uring::start(async {
+///     for i in 0..1_000_000u64 {
+///         // Do some work...
+///
+///         if i % 1_000 == 0 {
+///             tokio_uring::task::yield_now().await;
+///         }
+///     }
+/// });
+/// ```
+pub async fn yield_now() {
+    match Op::nop() {
+        Ok(op) => {
+            // A no-op cannot fail.
+            let _ = op.await;
+        }
+        Err(_) => tokio::task::yield_now().await,
+    }
+}
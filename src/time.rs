@@ -0,0 +1,372 @@
+//! Utilities for tracking time.
+//!
+//! Timers are `io_uring` timeout operations, completed by the kernel once they
+//! expire.
+//!
+//! With the `test-util` feature, time can be [paused](pause) and
+//! [advanced](advance) manually, letting tests exercise timeout logic quickly
+//! and deterministically.
+
+use crate::driver::{self, Op, Timeout};
+
+use futures_core::Stream;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Future returned by [`sleep`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep {
+    timer: Timer,
+}
+
+enum Timer {
+    /// Submitted, until it expires
+    Armed(Op<Timeout>),
+
+    /// Not submitted yet, for lack of room in the runtime. Submitted for the
+    /// rest of `duration` once there is room.
+    Waiting {
+        start: Instant,
+        duration: Duration,
+    },
+
+    Expired,
+}
+
+/// Stream of ticks returned by [`interval`].
+#[must_use = "streams do nothing unless polled"]
+pub struct Interval {
+    period: Duration,
+    state: Ticks,
+}
+
+enum Ticks {
+    /// A single multishot timer posting a completion every period
+    Multishot(Op<Timeout>),
+
+    /// A timer re-armed after each tick, where multishot timers are not
+    /// supported, or while time is paused
+    Rearm(Sleep),
+}
+
+/// Error returned by [`timeout`] when the deadline elapsed first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elapsed(());
+
+/// Waits until `duration` has elapsed.
+///
+/// Dropping the returned future before it completes cancels the timer.
+///
+/// If the runtime has no room for another operation, see
+/// [`Builder::max_ops`](crate::Builder::max_ops), the timer waits for room,
+/// whatever the backpressure, and is then submitted for the rest of
+/// `duration`.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// tokio_uring::start(async {
+///     tokio_uring::time::sleep(Duration::from_millis(100)).await;
+///     println!("100 ms have elapsed");
+/// });
+/// ```
+pub fn sleep(duration: Duration) -> Sleep {
+    let timer = match submitted(Op::timeout(duration)) {
+        Some(op) => Timer::Armed(op),
+        None => Timer::Waiting {
+            start: Instant::now(),
+            duration,
+        },
+    };
+    Sleep { timer }
+}
+
+/// Creates a stream ticking every `period`.
+///
+/// The first tick completes after one period, not immediately. A single
+/// multishot timeout drives all the ticks, so the interval does not drift,
+/// and the expirations missed while the task was busy are not skipped: each
+/// of them is returned by a later call to [`tick`](Interval::tick), right
+/// away. Kernels older than 6.4 do not support multishot timeouts, in which
+/// case, as well as while time is [paused](pause), or if the runtime has no
+/// room for the timer, see [`sleep`], the timer is re-armed after each tick
+/// instead, and late ticks delay the next ones.
+///
+/// Dropping the interval cancels the timer.
+///
+/// # Panics
+///
+/// This function panics if `period` is zero, or if called outside of a
+/// `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// tokio_uring::start(async {
+///     let mut interval = tokio_uring::time::interval(Duration::from_secs(1));
+///
+///     for _ in 0..5 {
+///         interval.tick().await;
+///         println!("one more second has elapsed");
+///     }
+/// });
+/// ```
+pub fn interval(period: Duration) -> Interval {
+    assert!(!period.is_zero(), "`period` must be non-zero");
+
+    #[cfg(feature = "test-util")]
+    if crate::driver::time::paused() {
+        return Interval {
+            period,
+            state: Ticks::Rearm(sleep(period)),
+        };
+    }
+
+    let state = match submitted(Op::interval(period)) {
+        Some(op) => Ticks::Multishot(op),
+        None => Ticks::Rearm(sleep(period)),
+    };
+    Interval { period, state }
+}
+
+/// Returns the timer submitted, or `None` if the runtime has no room for it
+/// yet, see `Builder::max_ops` and `Builder::busy_retries`.
+fn submitted(res: io::Result<Op<Timeout>>) -> Option<Op<Timeout>> {
+    match res {
+        Ok(op) => Some(op),
+        Err(e)
+            if e.kind() == io::ErrorKind::QuotaExceeded
+                || e.raw_os_error() == Some(libc::EBUSY) =>
+        {
+            None
+        }
+        Err(e) => panic!("failed to submit timeout: {}", e),
+    }
+}
+
+/// Requires `future` to complete before `duration` has elapsed.
+///
+/// The timer starts when `timeout` is called, not when the returned future is
+/// first polled. If the future completes first, its output is returned.
+/// Otherwise, the future is dropped and an [`Elapsed`] error is returned.
+///
+/// # Panics
+///
+/// This function panics if called outside of a `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tokio_uring::net::TcpListener;
+///
+/// tokio_uring::start(async {
+///     let listener = TcpListener::bind("127.0.0.1:8080".parse().unwrap()).unwrap();
+///
+///     match tokio_uring::time::timeout(Duration::from_secs(5), listener.accept()).await {
+///         Ok(res) => println!("accepted {:?}", res.unwrap().1),
+///         Err(_) => println!("no connection within 5 seconds"),
+///     }
+/// });
+/// ```
+pub fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> impl Future<Output = Result<F::Output, Elapsed>> {
+    let mut sleep = sleep(duration);
+
+    async move {
+        tokio::pin!(future);
+
+        crate::future::poll_fn(|cx| {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+
+            Pin::new(&mut sleep).poll(cx).map(|_| Err(Elapsed(())))
+        })
+        .await
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            match &mut self.timer {
+                Timer::Armed(op) => {
+                    // The timer completes with `ETIME` once it expires. Any
+                    // other outcome also ends the sleep, there is nothing to
+                    // retry.
+                    ready!(Pin::new(op).poll(cx));
+                    self.timer = Timer::Expired;
+                }
+                Timer::Waiting { start, duration } => {
+                    ready!(driver::poll_any_room(cx));
+
+                    let remaining = duration.saturating_sub(start.elapsed());
+                    if remaining.is_zero() {
+                        self.timer = Timer::Expired;
+                    } else if let Some(op) = submitted(Op::timeout(remaining)) {
+                        self.timer = Timer::Armed(op);
+                    }
+                }
+                Timer::Expired => return Poll::Ready(()),
+            }
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Timer::Armed(op) = &self.timer {
+            op.cancel();
+        }
+    }
+}
+
+impl Interval {
+    /// Waits for the next tick.
+    pub async fn tick(&mut self) {
+        crate::future::poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Polls for the next tick.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            match &mut self.state {
+                Ticks::Multishot(op) => match ready!(op.poll_next_shot(cx)) {
+                    // Expired, and more expirations follow
+                    Some((Err(e), _)) if e.raw_os_error() == Some(libc::ETIME) => {
+                        return Poll::Ready(())
+                    }
+                    // Multishot timers are not supported: the first tick is
+                    // driven by a single-shot timer submitted now
+                    Some((Err(e), _)) if e.raw_os_error() == Some(libc::EINVAL) => {
+                        self.state = Ticks::Rearm(sleep(self.period));
+                    }
+                    // Any other outcome ends the timer, which is re-armed
+                    _ => {
+                        self.state = match submitted(Op::interval(self.period)) {
+                            Some(op) => Ticks::Multishot(op),
+                            None => Ticks::Rearm(sleep(self.period)),
+                        };
+                    }
+                },
+                Ticks::Rearm(sleep) => {
+                    ready!(Pin::new(&mut *sleep).poll(cx));
+                    *sleep = crate::time::sleep(self.period);
+                    return Poll::Ready(());
+                }
+            }
+        }
+    }
+
+    /// Returns the period of the interval.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+}
+
+impl Drop for Interval {
+    fn drop(&mut self) {
+        if let Ticks::Multishot(op) = &self.state {
+            op.cancel();
+        }
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interval")
+            .field("period", &self.period)
+            .finish()
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "deadline has elapsed".fmt(f)
+    }
+}
+
+impl Error for Elapsed {}
+
+impl From<Elapsed> for io::Error {
+    fn from(_: Elapsed) -> io::Error {
+        io::ErrorKind::TimedOut.into()
+    }
+}
+
+/// Pauses time on the current runtime.
+///
+/// Timers created while time is paused are not submitted to the kernel. They
+/// only expire when time is moved forward with [`advance`], or immediately
+/// for a zero duration. Timers created before pausing are unaffected.
+///
+/// This function is only available with the `test-util` feature.
+///
+/// # Panics
+///
+/// This function panics if time is already paused, or if called outside of a
+/// `tokio-uring` runtime.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_uring::time;
+///
+/// tokio_uring::start(async {
+///     time::pause();
+///
+///     let sleep = tokio_uring::spawn(time::sleep(Duration::from_secs(3600)));
+///     time::advance(Duration::from_secs(3600)).await;
+///
+///     // Completes without waiting an hour
+///     sleep.await.unwrap();
+/// });
+/// ```
+#[cfg(feature = "test-util")]
+pub fn pause() {
+    crate::driver::time::pause();
+}
+
+/// Moves paused time forward by `duration`.
+///
+/// Timers expiring within `duration` complete, in deadline order, and the
+/// calling task yields so the tasks waiting on them can run.
+///
+/// This function is only available with the `test-util` feature.
+///
+/// # Panics
+///
+/// This function panics if time is not paused, or if called outside of a
+/// `tokio-uring` runtime.
+#[cfg(feature = "test-util")]
+pub async fn advance(duration: Duration) {
+    crate::driver::time::advance(duration);
+    crate::task::yield_now().await;
+}
@@ -0,0 +1,68 @@
+// The submissions are only validated in debug builds
+#![cfg(debug_assertions)]
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::FromRawFd;
+
+use tokio_uring::fs::{File, OpenOptions};
+use tokio_uring::pipe::PipeRead;
+
+#[test]
+fn read_without_capacity() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        // Valid, and reads nothing
+        let file = File::open(tempfile.path()).await.unwrap();
+        let (res, _) = file.read_at(Vec::new(), 0).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+#[should_panic(expected = "which is a pipe and cannot seek")]
+fn offset_on_pipe() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fifo");
+    let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+    tokio_uring::start(async {
+        // Opening for reading and writing does not wait for a writer
+        let fifo = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .await
+            .unwrap();
+        let _ = fifo.read_at(vec![0; 16], 4096).await;
+    });
+}
+
+#[test]
+#[should_panic(expected = "which is closed")]
+fn closed_fd() {
+    tokio_uring::start(async {
+        // Not an open fd
+        let closed = unsafe { PipeRead::from_raw_fd(libc::c_int::MAX - 1) };
+        let _ = closed.read(vec![0; 16]).await;
+    });
+}
+
+#[test]
+fn valid_submissions() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = File::create(tempfile.path()).await.unwrap();
+        let (res, _) = file.write_at(&b"hello"[..], 4096).await;
+        res.unwrap();
+
+        let (rx, tx) = tokio_uring::pipe::pipe().unwrap();
+        let (res, _) = tx.write(&b"hello"[..]).await;
+        res.unwrap();
+        let (res, buf) = rx.read(vec![0; 16]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
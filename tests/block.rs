@@ -0,0 +1,128 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::AsRawFd;
+
+use tokio_uring::buf::IoBuf;
+use tokio_uring::device::{Block, ZonedFile};
+use tokio_uring::fs::File;
+
+const LOOP_SET_FD: libc::c_ulong = 0x4c00;
+const LOOP_CLR_FD: libc::c_ulong = 0x4c01;
+const LOOP_CTL_GET_FREE: libc::c_ulong = 0x4c82;
+
+/// A loop device backed by a temporary file, detached when dropped.
+struct LoopDevice {
+    path: String,
+    device: fs::File,
+    backing: tempfile::NamedTempFile,
+}
+
+impl LoopDevice {
+    /// Attaches a file of `size` bytes, or returns `None` without the rights
+    /// to attach loop devices.
+    fn new(size: u64) -> Option<LoopDevice> {
+        let control = match fs::File::open("/dev/loop-control") {
+            Ok(control) => control,
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return None,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => panic!("{}", e),
+        };
+
+        let backing = tempfile::NamedTempFile::new().unwrap();
+        backing.as_file().set_len(size).unwrap();
+
+        let n = unsafe { libc::ioctl(control.as_raw_fd(), LOOP_CTL_GET_FREE as _) };
+        assert!(n >= 0, "{}", io::Error::last_os_error());
+
+        let path = format!("/dev/loop{}", n);
+        let device = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .ok()?;
+        let res = unsafe {
+            libc::ioctl(
+                device.as_raw_fd(),
+                LOOP_SET_FD as _,
+                backing.as_file().as_raw_fd(),
+            )
+        };
+        if res < 0 {
+            return None;
+        }
+
+        Some(LoopDevice {
+            path,
+            device,
+            backing,
+        })
+    }
+}
+
+impl Drop for LoopDevice {
+    fn drop(&mut self) {
+        unsafe { libc::ioctl(self.device.as_raw_fd(), LOOP_CLR_FD as _) };
+    }
+}
+
+#[test]
+fn regular_file_is_not_block() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+        let err = Block::from_file(file).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOTBLK));
+    });
+}
+
+#[test]
+fn loop_device() {
+    let dev = match LoopDevice::new(4 * 1024 * 1024) {
+        Some(dev) => dev,
+        None => return,
+    };
+
+    tokio_uring::start(async {
+        let block = Block::open(&dev.path).await.unwrap();
+
+        assert_eq!(block.size().unwrap(), 4 * 1024 * 1024);
+        let block_size = block.logical_block_size().unwrap();
+        assert!(block_size >= 512);
+        assert!(block.physical_block_size().unwrap() >= block_size);
+
+        assert_eq!(block.zone_size().unwrap(), None);
+        assert_eq!(block.zone_count().unwrap(), 0);
+
+        // An aligned buffer for `O_DIRECT`
+        let mut buf: Vec<u8> = Vec::with_capacity(8192);
+        let offset = buf.as_ptr().align_offset(4096);
+        buf.resize(offset + 4096, 7u8);
+        let (res, _) = block.write_at(buf.slice(offset..), 0).await;
+        assert_eq!(res.unwrap(), 4096);
+        block.sync_all().await.unwrap();
+
+        block.discard(0, 4096).await.unwrap();
+        block.close().await.unwrap();
+    });
+
+    // Discarding punched a hole in the backing file
+    let mut data = vec![0xff; 4096];
+    dev.backing.as_file().read_exact_at(&mut data, 0).unwrap();
+    assert!(data.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn loop_device_is_not_zoned() {
+    let dev = match LoopDevice::new(1024 * 1024) {
+        Some(dev) => dev,
+        None => return,
+    };
+
+    tokio_uring::start(async {
+        let block = Block::open(&dev.path).await.unwrap();
+        let err = ZonedFile::from_block(block).await.unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOTTY));
+    });
+}
@@ -1,4 +1,10 @@
-use tokio_uring::buf::{IoBuf, IoBufMut};
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::io::Write;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use tokio_uring::buf::{AllocBuf, Allocator, FixedBufRegistry, Global, IoBuf, IoBufMut};
 
 #[test]
 fn test_vec() {
@@ -53,6 +59,24 @@ fn test_slice() {
     assert_eq!(v.bytes_total(), 5);
 }
 
+#[test]
+fn test_slice_spare_capacity() {
+    let mut v = Vec::with_capacity(16);
+    v.extend_from_slice(b"hello");
+
+    let mut slice = v.slice(5..);
+    assert_eq!(slice.bytes_init(), 0);
+    assert_eq!(slice.bytes_total(), 11);
+    assert!(slice.is_empty());
+
+    unsafe {
+        std::ptr::copy(b" world".as_ptr(), slice.stable_mut_ptr(), 6);
+        slice.set_init(6);
+    }
+    assert_eq!(&slice[..], b" world");
+    assert_eq!(slice.into_inner(), b"hello world");
+}
+
 const DATA: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789!?";
 
 macro_rules! test_slice {
@@ -117,3 +141,79 @@ test_slice! {
     vec => Vec::from(DATA);
     slice => DATA;
 }
+
+/// Allocates from the global allocator, counting the live allocations.
+#[derive(Default)]
+struct Counting {
+    live: Cell<usize>,
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        self.live.set(self.live.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_alloc_buf() {
+    let alloc = Rc::new(Counting::default());
+
+    let mut buf = AllocBuf::with_capacity_in(16, alloc.clone());
+    assert_eq!(alloc.live.get(), 1);
+    assert_eq!(buf.bytes_init(), 0);
+    assert_eq!(buf.bytes_total(), 16);
+    assert_eq!(buf.stable_ptr(), buf.as_ptr());
+
+    unsafe {
+        buf.stable_mut_ptr().copy_from(b"hello".as_ptr(), 5);
+        buf.set_init(5);
+    }
+    assert_eq!(&buf[..], b"hello");
+    buf.clear();
+    assert!(buf.is_empty());
+
+    drop(buf);
+    assert_eq!(alloc.live.get(), 0);
+
+    // Nothing is allocated for an empty buffer
+    let buf = AllocBuf::with_capacity_in(0, alloc.clone());
+    assert_eq!(alloc.live.get(), 0);
+    drop(buf);
+
+    let registry = FixedBufRegistry::aligned_in(2, 4096, 4096, alloc.clone());
+    assert_eq!(alloc.live.get(), 2);
+    assert!(registry.check_out(1).unwrap().iter().all(|&b| b == 0));
+    drop(registry);
+    assert_eq!(alloc.live.get(), 0);
+}
+
+#[test]
+fn test_read_chunks_in() {
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    tempfile.write_all(&data).unwrap();
+
+    tokio_uring::start(async {
+        let alloc = Rc::new(Counting::default());
+
+        let file = tokio_uring::fs::File::open(tempfile.path()).await.unwrap();
+        let mut chunks = file.read_chunks_in(4096, alloc.clone()).await.unwrap();
+
+        let mut contents = Vec::new();
+        while let Some(chunk) = chunks.next().await.unwrap() {
+            contents.extend_from_slice(&chunk);
+            chunks.recycle(chunk);
+        }
+        assert!(contents == data);
+        assert!(alloc.live.get() > 0);
+
+        drop(chunks);
+        assert_eq!(alloc.live.get(), 0);
+    });
+}
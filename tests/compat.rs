@@ -0,0 +1,123 @@
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio_uring::compat::Compat;
+use tokio_uring::net::{TcpListener, TcpStream};
+
+async fn connected_pair() -> (TcpStream, TcpStream) {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let listener = TcpListener::bind(addr).unwrap();
+    let (tx, rx) = tokio::join!(TcpStream::connect(addr), listener.accept());
+    (tx.unwrap(), rx.unwrap().0)
+}
+
+#[test]
+fn tokio_io_traits() {
+    tokio_uring::start(async {
+        let (client, server) = connected_pair().await;
+        let mut client = Compat::new(client);
+        let server = Compat::new(server);
+
+        // Larger than the buffers of the adapter
+        let data: Vec<u8> = (0..300_000).map(|i| (i % 251) as u8).collect();
+        let expected = data.clone();
+
+        let echo = tokio_uring::spawn(async move {
+            let (mut rx, mut tx) = tokio::io::split(server);
+            let copied = tokio::io::copy(&mut rx, &mut tx).await.unwrap();
+            tx.shutdown().await.unwrap();
+            copied
+        });
+
+        let writer = async {
+            client.write_all(&data).await.unwrap();
+            client.flush().await.unwrap();
+        };
+        writer.await;
+        let (rx, mut tx) = tokio::io::split(client);
+        tx.shutdown().await.unwrap();
+
+        let mut received = Vec::new();
+        let mut rx = rx;
+        rx.read_to_end(&mut received).await.unwrap();
+
+        assert_eq!(received, expected);
+        assert_eq!(echo.await.unwrap(), expected.len() as u64);
+    });
+}
+
+#[test]
+fn buffered_lines() {
+    tokio_uring::start(async {
+        let (client, server) = connected_pair().await;
+        let mut client = Compat::new(client);
+
+        client.write_all(b"hello\nworld\n").await.unwrap();
+        client.shutdown().await.unwrap();
+
+        let mut lines = BufReader::new(Compat::new(server)).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "hello");
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "world");
+        assert!(lines.next_line().await.unwrap().is_none());
+    });
+}
+
+#[test]
+fn unix_stream() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("socket");
+        let listener = tokio_uring::net::UnixListener::bind(&path).unwrap();
+
+        let (client, server) = tokio::join!(
+            tokio_uring::net::UnixStream::connect(&path),
+            listener.accept()
+        );
+        let mut client = Compat::new(client.unwrap());
+        let mut server = Compat::new(server.unwrap());
+
+        client.write_all(b"ping").await.unwrap();
+        client.flush().await.unwrap();
+
+        let mut buf = [0; 4];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+    });
+}
+
+#[cfg(feature = "hyper")]
+#[test]
+fn hyper_http2() {
+    use http_body_util::{BodyExt, Empty, Full};
+    use hyper::body::{Bytes, Incoming};
+    use hyper::{Request, Response};
+    use std::convert::Infallible;
+    use tokio_uring::compat::LocalExecutor;
+
+    async fn hello(req: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+        let body = format!("hello {}", req.uri().path());
+        Ok(Response::new(Full::new(Bytes::from(body))))
+    }
+
+    tokio_uring::start(async {
+        let (client, server) = connected_pair().await;
+
+        let conn = hyper::server::conn::http2::Builder::new(LocalExecutor)
+            .serve_connection(Compat::new(server), hyper::service::service_fn(hello));
+        tokio_uring::spawn(conn);
+
+        let (mut sender, conn) =
+            hyper::client::conn::http2::handshake(LocalExecutor, Compat::new(client))
+                .await
+                .unwrap();
+        tokio_uring::spawn(conn);
+
+        for path in ["/one", "/two"] {
+            let req = Request::get(path).body(Empty::<Bytes>::new()).unwrap();
+            let res = sender.send_request(req).await.unwrap();
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body, format!("hello {}", path));
+        }
+    });
+}
@@ -0,0 +1,99 @@
+use std::{
+    ffi::CStr,
+    fs::{File, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
+    os::unix::io::{AsRawFd, FromRawFd},
+};
+
+use tokio_uring::io::{Console, Input};
+
+/// Opens a pseudoterminal, returning the master and the slave.
+fn openpty() -> (File, File) {
+    let (master, name) = unsafe {
+        let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC);
+        assert!(fd >= 0);
+        assert_eq!(libc::grantpt(fd), 0);
+        assert_eq!(libc::unlockpt(fd), 0);
+
+        let mut name = [0; 64];
+        assert_eq!(libc::ptsname_r(fd, name.as_mut_ptr(), name.len()), 0);
+        let name = CStr::from_ptr(name.as_ptr()).to_str().unwrap().to_string();
+
+        (File::from_raw_fd(fd), name)
+    };
+
+    let slave = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY | libc::O_CLOEXEC)
+        .open(name)
+        .unwrap();
+
+    (master, slave)
+}
+
+fn lflag(file: &File) -> libc::tcflag_t {
+    let mut termios = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::tcgetattr(file.as_raw_fd(), &mut termios) },
+        0
+    );
+    termios.c_lflag
+}
+
+#[test]
+fn reads_keys_in_raw_mode() {
+    let (mut master, slave) = openpty();
+    let observer = slave.try_clone().unwrap();
+    assert_ne!(lflag(&observer) & libc::ICANON, 0);
+
+    // The slave hangs up once the master is closed
+    let _master = tokio_uring::start(async {
+        let mut console = Console::from_std(slave).unwrap();
+        console.enable_raw_mode().unwrap();
+        assert_eq!(lflag(&observer) & libc::ICANON, 0);
+
+        master
+            .write_all("a\u{e9}\x1b[A\x1b[3~\x03\r\x1b".as_bytes())
+            .unwrap();
+
+        let mut inputs = Vec::new();
+        for _ in 0..7 {
+            inputs.push(console.read_input().await.unwrap().unwrap());
+        }
+        assert_eq!(
+            inputs,
+            [
+                Input::Char('a'),
+                Input::Char('\u{e9}'),
+                Input::Up,
+                Input::Delete,
+                Input::Ctrl('c'),
+                Input::Enter,
+                Input::Esc,
+            ]
+        );
+
+        // Keys pressed later are waited for
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            master.write_all(b"\x1b[6~").unwrap();
+            master
+        });
+        assert_eq!(console.read_input().await.unwrap(), Some(Input::PageDown));
+        let master = writer.join().unwrap();
+
+        console.write_all(b"done\r\n").await.unwrap();
+        master
+    });
+
+    // The mode is restored on drop
+    assert_ne!(lflag(&observer) & libc::ICANON, 0);
+}
+
+#[test]
+fn from_std_requires_a_terminal() {
+    let file = tempfile::tempfile().unwrap();
+    assert!(Console::from_std(file).is_err());
+}
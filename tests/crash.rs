@@ -0,0 +1,87 @@
+use std::os::unix::fs::FileExt;
+
+use tokio_uring::crash::{LogEntry, LoggedFile, WriteLog};
+use tokio_uring::fs::File;
+
+#[test]
+fn logs_writes_and_syncs() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    let log = tokio_uring::start(async {
+        let file = LoggedFile::new(File::create(tempfile.path()).await.unwrap());
+
+        let (res, _) = file.write_at(b"hello".to_vec(), 0).await;
+        assert_eq!(res.unwrap(), 5);
+        file.sync_data().await.unwrap();
+        let (res, _) = file.write_at(b" world".to_vec(), 5).await;
+        assert_eq!(res.unwrap(), 6);
+        file.sync_all().await.unwrap();
+
+        file.log().clone()
+    });
+
+    assert_eq!(
+        log.entries(),
+        vec![
+            LogEntry::Write {
+                offset: 0,
+                data: b"hello".to_vec()
+            },
+            LogEntry::Sync,
+            LogEntry::Write {
+                offset: 5,
+                data: b" world".to_vec()
+            },
+            LogEntry::Sync,
+        ]
+    );
+    assert_eq!(log.sync_points(), vec![2, 4]);
+}
+
+#[test]
+fn replay_prefixes() {
+    let log = WriteLog::new();
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = LoggedFile::with_log(File::create(tempfile.path()).await.unwrap(), log.clone());
+
+        for (i, record) in [b"aaaa", b"bbbb", b"cccc"].iter().enumerate() {
+            let (res, _) = file.write_at(record.to_vec(), i as u64 * 4).await;
+            res.unwrap();
+        }
+        // Overwrites the first record
+        let (res, _) = file.write_at(b"AA".to_vec(), 1).await;
+        res.unwrap();
+    });
+
+    let expected: [&[u8]; 5] = [b"", b"aaaa", b"aaaabbbb", b"aaaabbbbcccc", b"aAAabbbbcccc"];
+    for (prefix, expected) in expected.iter().enumerate() {
+        let image = tempfile::tempfile().unwrap();
+        log.replay(prefix, &image).unwrap();
+
+        let mut contents = vec![0; image.metadata().unwrap().len() as usize];
+        image.read_exact_at(&mut contents, 0).unwrap();
+        assert_eq!(&contents[..], *expected);
+    }
+
+    // The last prefix matches the file
+    assert_eq!(
+        std::fs::read(tempfile.path()).unwrap(),
+        expected[4].to_vec()
+    );
+}
+
+#[test]
+fn failed_writes_are_not_logged() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        // Opened read only
+        let file = LoggedFile::new(File::open(tempfile.path()).await.unwrap());
+
+        let (res, _) = file.write_at(b"lost".to_vec(), 0).await;
+        assert!(res.is_err());
+        assert!(file.log().is_empty());
+    });
+}
@@ -0,0 +1,47 @@
+use tokio_uring::buf::FixedBufRegistry;
+use tokio_uring::diagnostics::{self, Outcome};
+
+#[test]
+fn self_test_passes() {
+    tokio_uring::start(async {
+        let report = diagnostics::self_test().await;
+        assert!(report.passed(), "{}", report);
+
+        assert!(!report.kernel_release().is_empty());
+        assert!(report
+            .supported_opcodes()
+            .contains(&io_uring::opcode::Nop::CODE));
+
+        let names: Vec<_> = report.checks().iter().map(|check| check.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "probe",
+                "nop",
+                "timeout",
+                "file",
+                "tcp",
+                "registered buffers"
+            ]
+        );
+        assert!(report
+            .checks()
+            .iter()
+            .all(|check| *check.outcome() == Outcome::Passed));
+    });
+}
+
+#[test]
+fn self_test_skips_registered_buffers() {
+    tokio_uring::start(async {
+        let registry = FixedBufRegistry::new(vec![vec![0; 64]]);
+        registry.register().unwrap();
+
+        let report = diagnostics::self_test().await;
+        assert!(report.passed(), "{}", report);
+
+        let check = report.checks().last().unwrap();
+        assert_eq!(check.name(), "registered buffers");
+        assert!(matches!(check.outcome(), Outcome::Skipped(_)));
+    });
+}
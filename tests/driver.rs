@@ -90,6 +90,66 @@ fn too_many_submissions() {
     });
 }
 
+#[test]
+fn panic_while_submitting() {
+    /// Panics when the runtime builds the submission entry of a write
+    struct PanickingBuf;
+
+    unsafe impl IoBuf for PanickingBuf {
+        fn stable_ptr(&self) -> *const u8 {
+            panic!("stable_ptr");
+        }
+
+        fn bytes_init(&self) -> usize {
+            0
+        }
+
+        fn bytes_total(&self) -> usize {
+            0
+        }
+    }
+
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = std::rc::Rc::new(File::create(tempfile.path()).await.unwrap());
+
+        let writer = file.clone();
+        let res = tokio_uring::spawn(async move {
+            let _ = writer.write_at(PanickingBuf, 0).await;
+        })
+        .await;
+        assert!(res.unwrap_err().is_panic());
+
+        // The driver keeps going
+        let (res, _) = file.write_at(b"hello".to_vec(), 0).await;
+        assert_eq!(res.unwrap(), 5);
+    });
+}
+
+#[test]
+fn panic_with_operations_in_flight() {
+    let res = std::panic::catch_unwind(|| {
+        tokio_uring::start(async {
+            let (a, _b) = tokio_uring::net::UnixStream::pair().unwrap();
+
+            // Never completes, the runtime cancels it on shutdown
+            tokio_uring::spawn(async move {
+                let _ = a.read(vec![0; 16]).await;
+            });
+            tokio::task::yield_now().await;
+
+            panic!("boom");
+        })
+    });
+    assert!(res.is_err());
+
+    // The process is still alive, and can start a new runtime
+    tokio_uring::start(async {
+        tokio_uring::task::yield_now().await;
+    });
+}
+
 fn tempfile() -> NamedTempFile {
     NamedTempFile::new().unwrap()
 }
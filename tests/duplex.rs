@@ -0,0 +1,60 @@
+use tokio_uring::io::duplex;
+
+#[test]
+fn write_waits_for_room() {
+    tokio_uring::start(async {
+        let (client, server) = duplex(4);
+
+        let writer = tokio_uring::spawn(async move {
+            let mut buf = b"hello world".to_vec();
+            while !buf.is_empty() {
+                let (res, b) = client.write(buf).await;
+                buf = b;
+                let n = res.unwrap();
+                assert!(n <= 4);
+                buf.drain(..n);
+            }
+        });
+
+        let mut received = Vec::new();
+        while received.len() < 11 {
+            let (res, buf) = server.read(vec![0; 3]).await;
+            received.extend_from_slice(&buf[..res.unwrap()]);
+        }
+        assert_eq!(received, b"hello world");
+
+        writer.await.unwrap();
+
+        // The client was dropped
+        let (res, _) = server.read(vec![0; 3]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn read_waits_for_data() {
+    tokio_uring::start(async {
+        let (client, server) = duplex(16);
+
+        let reader = tokio_uring::spawn(async move {
+            let (res, buf) = server.read(Vec::with_capacity(16)).await;
+            assert_eq!(&buf[..res.unwrap()], b"ping");
+        });
+        tokio::task::yield_now().await;
+
+        let (res, _) = client.write(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap(), 4);
+        reader.await.unwrap();
+    });
+}
+
+#[test]
+fn write_to_dropped_peer() {
+    tokio_uring::start(async {
+        let (client, server) = duplex(16);
+        drop(server);
+
+        let (res, _) = client.write(b"ping".as_slice()).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe);
+    });
+}
@@ -0,0 +1,299 @@
+use std::future::Future;
+use std::io::Write;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use tempfile::NamedTempFile;
+
+use tokio_uring::fault::{self, Fault, Target};
+use tokio_uring::fs::{Advice, File, OpenOptions};
+use tokio_uring::Backpressure;
+
+const HELLO: &[u8] = b"hello world...";
+
+fn tempfile() -> NamedTempFile {
+    let mut tempfile = NamedTempFile::new().unwrap();
+    tempfile.write_all(HELLO).unwrap();
+    tempfile
+}
+
+#[test]
+fn error_once() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        fault::inject(Fault::error(Target::Read, libc::EINTR));
+
+        let (res, buf) = file.read_at(vec![0; 32], 0).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EINTR));
+
+        let (res, buf) = file.read_at(buf, 0).await;
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+    });
+}
+
+#[test]
+fn error_skip_and_times() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        fault::inject(Fault::error(Target::Read, libc::EAGAIN).skip(1).times(2));
+
+        let mut results = vec![];
+        let mut buf = vec![0; 32];
+
+        for _ in 0..4 {
+            let (res, b) = file.read_at(buf, 0).await;
+            results.push(res.map_err(|e| e.raw_os_error().unwrap()));
+            buf = b;
+        }
+
+        let n = HELLO.len();
+        assert_eq!(
+            results,
+            [Ok(n), Err(libc::EAGAIN), Err(libc::EAGAIN), Ok(n)]
+        );
+    });
+}
+
+#[test]
+fn error_does_not_match_other_targets() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        fault::inject(Fault::error(Target::Write, libc::ECANCELED).always());
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let (res, _) = file.read_at(vec![0; 32], 0).await;
+        assert_eq!(res.unwrap(), HELLO.len());
+
+        let (res, _) = file.write_at(HELLO, 0).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+
+        fault::clear();
+
+        // Opened read-only
+        let (res, _) = file.write_at(HELLO, 0).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBADF));
+    });
+}
+
+#[test]
+fn short_read() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        fault::inject(Fault::short(Target::Read, 5));
+
+        let (res, buf) = file.read_at(Vec::with_capacity(32), 0).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(&buf[..], &HELLO[..5]);
+    });
+}
+
+#[test]
+fn delay() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        fault::inject(Fault::delay(Target::Read, Duration::from_millis(50)));
+
+        let start = Instant::now();
+        let (res, _) = file.read_at(vec![0; 32], 0).await;
+        assert_eq!(res.unwrap(), HELLO.len());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    });
+}
+
+#[test]
+fn busy_submits_are_deferred() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        // More than the default retries
+        fault::busy_submits(9);
+
+        let (res, buf) = file.read_at(vec![0; 32], 0).await;
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+
+        #[cfg(feature = "metrics")]
+        assert_eq!(tokio_uring::metrics::busy_deferrals(), 1);
+    });
+}
+
+#[test]
+fn busy_submits_retried() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        fault::busy_submits(8);
+
+        let (res, buf) = file.read_at(vec![0; 32], 0).await;
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+
+        #[cfg(feature = "metrics")]
+        assert_eq!(tokio_uring::metrics::busy_deferrals(), 0);
+    });
+}
+
+#[test]
+fn busy_backpressure_fail() {
+    let tempfile = tempfile();
+
+    tokio_uring::builder()
+        .busy_retries(0, Backpressure::Fail)
+        .start(async {
+            let file = File::open(tempfile.path()).await.unwrap();
+
+            fault::busy_submits(1);
+
+            // Submitted, and deferred
+            let mut first = Box::pin(file.read_at(vec![0; 32], 0));
+            std::future::poll_fn(|cx| {
+                assert!(first.as_mut().poll(cx).is_pending());
+                Poll::Ready(())
+            })
+            .await;
+
+            // Fails while the kernel is busy
+            let (res, _) = file.read_at(vec![0; 32], 0).await;
+            assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBUSY));
+
+            let (res, buf) = first.await;
+            assert_eq!(&buf[..res.unwrap()], HELLO);
+
+            // The deferred submission went through
+            let (res, buf) = file.read_at(buf, 0).await;
+            assert_eq!(&buf[..res.unwrap()], HELLO);
+        })
+        .unwrap();
+}
+
+#[test]
+fn delay_without_room_fails() {
+    let tempfile = tempfile();
+
+    tokio_uring::builder()
+        .busy_retries(0, Backpressure::Fail)
+        .start(async {
+            let file = File::open(tempfile.path()).await.unwrap();
+
+            fault::busy_submits(usize::MAX);
+
+            // Fill the submission queue, but for one entry
+            let mut advises = Vec::new();
+            for _ in 0..255 {
+                advises.push(Box::pin(file.advise(0, 0, Advice::Normal)));
+            }
+            std::future::poll_fn(|cx| {
+                for advise in &mut advises {
+                    assert!(advise.as_mut().poll(cx).is_pending());
+                }
+                Poll::Ready(())
+            })
+            .await;
+
+            // The delay and the operation do not fit
+            fault::inject(Fault::delay(Target::Any, Duration::from_millis(10)));
+            let res = file.advise(0, 0, Advice::Normal).await;
+            assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBUSY));
+
+            fault::clear();
+            for advise in advises {
+                advise.await.unwrap();
+            }
+        })
+        .unwrap();
+}
+
+#[test]
+fn failed_submit_fails_its_operations() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        fault::fail_submit(libc::EOWNERDEAD);
+
+        let (res, _) = file.write_at(&b"failed"[..], 0).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EOWNERDEAD));
+
+        // The runtime is still usable, and the failed write never happened
+        let (res, buf) = file.read_at(vec![0; 32], 0).await;
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+    });
+}
+
+#[test]
+fn failed_submit_transient() {
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        // The read stays queued, and is submitted by the next operation
+        fault::fail_submit(libc::EAGAIN);
+        let read = tokio_uring::spawn(async move {
+            let (res, buf) = file.read_at(vec![0; 32], 0).await;
+            assert_eq!(&buf[..res.unwrap()], HELLO);
+        });
+
+        tokio_uring::task::yield_now().await;
+        read.await.unwrap();
+    });
+}
+
+#[test]
+fn busy_submits_keep_registered_files() {
+    let mut other = NamedTempFile::new().unwrap();
+    other.write_all(b"other").unwrap();
+    let tempfile = tempfile();
+
+    tokio_uring::builder()
+        .auto_register_files(1)
+        .busy_retries(0, Backpressure::Fail)
+        .start(async {
+            let a = File::open(tempfile.path()).await.unwrap();
+            let b = File::open(other.path()).await.unwrap();
+
+            fault::busy_submits(usize::MAX);
+
+            // Both batches stay queued: the second read of `a` uses the only
+            // slot, which the second read of `b` must not take over
+            let mut first = Box::pin(a.read_many_at(vec![(vec![0; 32], 0), (vec![0; 32], 0)]));
+            let mut second = Box::pin(b.read_many_at(vec![(vec![0; 32], 0), (vec![0; 32], 0)]));
+            std::future::poll_fn(|cx| {
+                assert!(first.as_mut().poll(cx).is_pending());
+                assert!(second.as_mut().poll(cx).is_pending());
+                Poll::Ready(())
+            })
+            .await;
+
+            fault::clear();
+
+            for (res, buf) in first.await.unwrap() {
+                assert_eq!(&buf[..res.unwrap()], HELLO);
+            }
+            for (res, buf) in second.await.unwrap() {
+                assert_eq!(&buf[..res.unwrap()], b"other");
+            }
+        })
+        .unwrap();
+}
@@ -0,0 +1,73 @@
+use std::net::SocketAddr;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::process::Command;
+
+use tokio_uring::fd;
+use tokio_uring::fs::File;
+use tokio_uring::net::{TcpListener, TcpStream, UdpSocket, UnixSeqpacket};
+
+/// Returns `true` if a child process inherits `fd`.
+fn inherited(fd: RawFd) -> bool {
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("test -e /proc/self/fd/{}", fd))
+        .status()
+        .unwrap()
+        .success()
+}
+
+#[test]
+fn resources_are_cloexec() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+        let (rx, tx) = tokio_uring::pipe::pipe().unwrap();
+        let udp = UdpSocket::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let (unix, _peer) = UnixSeqpacket::pair().unwrap();
+
+        let addr: SocketAddr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let listener = TcpListener::bind(addr).unwrap();
+        let (stream, accepted) = tokio::join!(TcpStream::connect(addr), listener.accept());
+        let (stream, (accepted, _)) = (stream.unwrap(), accepted.unwrap());
+
+        let fds = [
+            file.as_raw_fd(),
+            rx.as_raw_fd(),
+            tx.as_raw_fd(),
+            udp.as_raw_fd(),
+            unix.as_raw_fd(),
+            listener.as_raw_fd(),
+            stream.as_raw_fd(),
+            accepted.as_raw_fd(),
+        ];
+
+        let inheritable = fd::inheritable_fds().unwrap();
+        for &fd in &fds {
+            assert!(!inheritable.contains(&fd), "fd {} is inheritable", fd);
+            assert!(!inherited(fd));
+        }
+        assert!(fd::is_cloexec(&listener).unwrap());
+    });
+}
+
+#[test]
+fn opt_out_of_cloexec() {
+    tokio_uring::start(async {
+        let (rx, _tx) = tokio_uring::pipe::pipe().unwrap();
+
+        fd::set_cloexec(&rx, false).unwrap();
+        assert!(!fd::is_cloexec(&rx).unwrap());
+        assert!(fd::inheritable_fds().unwrap().contains(&rx.as_raw_fd()));
+        assert!(inherited(rx.as_raw_fd()));
+
+        fd::set_cloexec(&rx, true).unwrap();
+        assert!(fd::is_cloexec(&rx).unwrap());
+        assert!(!inherited(rx.as_raw_fd()));
+    });
+}
@@ -0,0 +1,162 @@
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+use tokio_uring::buf::{self, FixedBufRegistry, IoBuf, MemlockError, SizeClassPool};
+use tokio_uring::fs::File;
+
+#[test]
+fn read_and_write_fixed() {
+    let mut tempfile = NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+
+    tokio_uring::start(async {
+        let registry = FixedBufRegistry::new(vec![Vec::with_capacity(64), b"HELLO".to_vec()]);
+        registry.register().unwrap();
+        assert!(registry.is_registered());
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let buf = registry.check_out(0).unwrap();
+        assert!(registry.check_out(0).is_none());
+
+        let (res, buf) = file.read_fixed_at(buf, 0).await;
+        assert_eq!(res.unwrap(), 11);
+        assert_eq!(&buf[..], b"hello world");
+        drop(buf);
+
+        // The initialized length is kept by the registry
+        let buf = registry.check_out(0).unwrap();
+        assert_eq!(&buf[..], b"hello world");
+
+        let file = File::create(tempfile.path()).await.unwrap();
+        let (res, _) = file.write_fixed_at(registry.check_out(1).unwrap(), 0).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"HELLO");
+
+        // Buffers are in use
+        assert_eq!(
+            registry.unregister().unwrap_err().raw_os_error(),
+            Some(libc::EBUSY)
+        );
+        drop(buf);
+        registry.unregister().unwrap();
+    });
+}
+
+#[test]
+fn registered_memory_accounting() {
+    tokio_uring::start(async {
+        assert_eq!(buf::registered_memory(), 0);
+
+        let registry = FixedBufRegistry::new((0..4).map(|_| vec![0; 10_000]));
+        let locked = registry.locked_memory();
+        assert!(locked >= 40_000);
+
+        registry.register().unwrap();
+        assert_eq!(buf::registered_memory(), locked);
+
+        // A single set of buffers is registered at once
+        let other = FixedBufRegistry::new(vec![vec![0; 16]]);
+        let err = other.register().unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+
+        drop(registry);
+        assert_eq!(buf::registered_memory(), 0);
+        other.register().unwrap();
+    });
+}
+
+#[test]
+fn register_outside_runtime() {
+    let registry = FixedBufRegistry::new(vec![vec![0; 16]]);
+    assert!(registry.register().is_err());
+    assert_eq!(buf::registered_memory(), 0);
+}
+
+#[test]
+fn memlock_limit_exceeded() {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    assert_eq!(
+        unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) },
+        0
+    );
+    if limit.rlim_cur == libc::RLIM_INFINITY || limit.rlim_cur > 64 << 20 {
+        return;
+    }
+
+    tokio_uring::start(async {
+        let size = limit.rlim_cur as usize + 4096;
+        let registry = FixedBufRegistry::new(vec![vec![0; size]]);
+
+        match registry.register() {
+            // Exempt from the limit with `CAP_IPC_LOCK`
+            Ok(()) => {}
+            Err(e) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::OutOfMemory);
+                let memlock = e.get_ref().unwrap().downcast_ref::<MemlockError>().unwrap();
+                assert_eq!(memlock.limit(), limit.rlim_cur);
+                assert_eq!(memlock.required(), registry.locked_memory() as u64);
+            }
+        }
+    });
+}
+
+#[test]
+fn aligned_buffers() {
+    let registry = FixedBufRegistry::aligned(3, 8192, 4096);
+    assert_eq!(registry.len(), 3);
+
+    for i in 0..3 {
+        let buf = registry.check_out(i).unwrap();
+        assert_eq!(buf.as_ptr() as usize % 4096, 0);
+        assert_eq!(buf.len(), 8192);
+        assert!(buf.iter().all(|&b| b == 0));
+    }
+}
+
+#[test]
+fn size_class_pool() {
+    let mut tempfile = NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+
+    tokio_uring::start(async {
+        let pool = SizeClassPool::new(&[(4096, 1), (512, 2)]);
+        assert_eq!(pool.sizes().collect::<Vec<_>>(), [512, 4096]);
+        pool.register().unwrap();
+
+        // The smallest buffer fitting, then the larger ones
+        let a = pool.get(100).unwrap();
+        assert_eq!(a.capacity(), 512);
+        let b = pool.get(100).unwrap();
+        let c = pool.get(100).unwrap();
+        assert_eq!(c.capacity(), 4096);
+        assert!(pool.get(1).is_none());
+        assert!(pool.get(8192).is_none());
+        drop((b, c));
+
+        // Plain reads and writes of buffers of the pool, whole or sliced
+        let file = File::open(tempfile.path()).await.unwrap();
+        let (res, a) = file.read_at(a, 6).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(&a[..], b"world");
+
+        let out = File::create(tempfile.path()).await.unwrap();
+        let mut buf = pool.get(16).unwrap();
+        assert!(buf.is_empty());
+        buf.extend_from_slice(b"pooled");
+        let (res, _) = out.write_at(buf, 0).await;
+        assert_eq!(res.unwrap(), 6);
+        let (res, a) = out.write_at(a.slice(..5), 6).await;
+        assert_eq!(res.unwrap(), 5);
+        out.sync_all().await.unwrap();
+
+        // Handed out buffers keep the pool registered
+        assert!(pool.unregister().is_err());
+        drop(a);
+        pool.unregister().unwrap();
+    });
+
+    assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"pooledworld");
+}
@@ -0,0 +1,77 @@
+use tokio_uring::fs::File;
+
+/// Forks, runs `child` in the child process, and returns its exit code.
+fn fork(child: impl FnOnce() -> i32) -> i32 {
+    match unsafe { libc::fork() } {
+        -1 => panic!("fork failed: {}", std::io::Error::last_os_error()),
+        0 => {
+            let code = child();
+            unsafe { libc::_exit(code) }
+        }
+        pid => {
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+            assert!(libc::WIFEXITED(status));
+            libc::WEXITSTATUS(status)
+        }
+    }
+}
+
+#[test]
+fn inherited_runtime_fails_clearly() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        // The child is still within the runtime, polling its futures would
+        // need the runtime to make progress
+        let code = fork(|| {
+            let err = match poll_once(File::open(tempfile.path())) {
+                Ok(_) => return 1,
+                Err(e) => e,
+            };
+            if err.to_string().contains("inherited across fork()") {
+                0
+            } else {
+                2
+            }
+        });
+        assert_eq!(code, 0);
+
+        // The parent keeps using its runtime
+        let (res, _) = file.read_at(vec![0; 16], 0).await;
+        res.unwrap();
+    });
+}
+
+#[test]
+fn child_starts_a_new_runtime() {
+    let tempfile = tempfile::NamedTempFile::new().unwrap();
+
+    // A runtime which exited before forking
+    tokio_uring::start(async {
+        File::open(tempfile.path()).await.unwrap();
+    });
+
+    let code = fork(|| {
+        tokio_uring::start(async {
+            let file = File::create(tempfile.path()).await.unwrap();
+            let (res, _) = file.write_at(&b"child"[..], 0).await;
+            res.map(|_| 0).unwrap_or(1)
+        })
+    });
+    assert_eq!(code, 0);
+    assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"child");
+}
+
+/// Polls a future which must complete without waiting.
+fn poll_once<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut cx = Context::from_waker(Waker::noop());
+    match Box::pin(future).as_mut().poll(&mut cx) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future waits"),
+    }
+}
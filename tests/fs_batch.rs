@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use tempfile::NamedTempFile;
+use tokio_uring::fs::{File, OpenOptions, SyncPolicy, WriteBatcher};
+
+async fn open(tempfile: &NamedTempFile) -> File {
+    OpenOptions::new()
+        .write(true)
+        .open(tempfile.path())
+        .await
+        .unwrap()
+}
+
+fn contents(tempfile: &NamedTempFile) -> Vec<u8> {
+    std::fs::read(tempfile.path()).unwrap()
+}
+
+#[test]
+fn writes_once_threshold_reached() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let mut batcher = WriteBatcher::new(open(&tempfile).await);
+        batcher.threshold(100);
+
+        for _ in 0..9 {
+            batcher.write(&[b'a'; 10]).await.unwrap();
+        }
+        assert_eq!(batcher.buffered(), 90);
+        assert!(contents(&tempfile).is_empty());
+
+        batcher.write(&[b'b'; 10]).await.unwrap();
+        assert_eq!(batcher.buffered(), 0);
+        assert_eq!(contents(&tempfile).len(), 100);
+
+        batcher.write(b"tail").await.unwrap();
+        batcher.flush().await.unwrap();
+        assert_eq!(&contents(&tempfile)[90..], b"bbbbbbbbbbtail");
+
+        batcher.close().await.unwrap();
+    });
+}
+
+#[test]
+fn flushes_after_linger() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let mut batcher = WriteBatcher::new(open(&tempfile).await);
+        batcher
+            .linger(Duration::from_millis(20))
+            .sync_policy(SyncPolicy::Data);
+
+        batcher.write(b"hello ").await.unwrap();
+        batcher.write(b"world").await.unwrap();
+        assert!(contents(&tempfile).is_empty());
+
+        tokio_uring::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(batcher.buffered(), 0);
+        assert_eq!(contents(&tempfile), b"hello world");
+
+        // A new linger starts with the next write
+        batcher.write(b"!").await.unwrap();
+        tokio_uring::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(contents(&tempfile), b"hello world!");
+    });
+}
+
+#[test]
+fn close_flushes() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let mut batcher = WriteBatcher::new(open(&tempfile).await);
+        batcher.linger(Duration::from_secs(60));
+
+        batcher.write(b"record\n").await.unwrap();
+        batcher.close().await.unwrap();
+        assert_eq!(contents(&tempfile), b"record\n");
+    });
+}
+
+#[test]
+fn drop_writes_buffered_data() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let batcher = WriteBatcher::new(open(&tempfile).await);
+        batcher.write(b"last words").await.unwrap();
+        drop(batcher);
+    });
+
+    assert_eq!(contents(&tempfile), b"last words");
+}
+
+#[test]
+fn failed_write_keeps_data_buffered() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        // Opened read only, the writes fail
+        let file = File::open(tempfile.path()).await.unwrap();
+        let batcher = WriteBatcher::new(file);
+
+        batcher.write(b"kept").await.unwrap();
+        assert!(batcher.flush().await.is_err());
+        assert_eq!(batcher.buffered(), 4);
+    });
+}
@@ -0,0 +1,92 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+use tempfile::NamedTempFile;
+use tokio_uring::fs::{OpenOptions, SyncCoalescer};
+
+#[test]
+fn concurrent_syncs_share_fsyncs() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = OpenOptions::new()
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+        let file = Rc::new(file);
+        let syncs = SyncCoalescer::new(&file);
+
+        let tasks: Vec<_> = (0..50u64)
+            .map(|i| {
+                let file = file.clone();
+                let syncs = syncs.clone();
+                tokio_uring::spawn(async move {
+                    let (res, _) = file.write_at(vec![b'x'; 10], i * 10).await;
+                    res.unwrap();
+                    syncs.sync().await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let stats = syncs.stats();
+        assert_eq!(stats.requests, 50);
+        assert!(stats.syncs >= 1 && stats.syncs < 50, "{:?}", stats);
+        assert!(stats.max_batch > 1);
+    });
+}
+
+#[test]
+fn window_gathers_requests() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = OpenOptions::new()
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+        let mut syncs = SyncCoalescer::new(&file);
+        syncs.window(Duration::from_millis(50)).data_only(true);
+
+        let first = tokio_uring::spawn({
+            let syncs = syncs.clone();
+            async move { syncs.sync().await }
+        });
+        tokio_uring::time::sleep(Duration::from_millis(10)).await;
+
+        // Joins the fsync waiting for its window
+        syncs.sync().await.unwrap();
+        first.await.unwrap().unwrap();
+
+        let stats = syncs.stats();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.syncs, 1);
+        assert_eq!(stats.max_batch, 2);
+    });
+}
+
+#[test]
+fn sync_after_fsync_started_waits_for_next() {
+    let tempfile = NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let file = OpenOptions::new()
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+        let syncs = SyncCoalescer::new(&file);
+
+        syncs.sync().await.unwrap();
+        syncs.sync().await.unwrap();
+        assert_eq!(syncs.stats().syncs, 2);
+
+        drop(syncs);
+        file.close().await.unwrap();
+    });
+}
@@ -1,11 +1,14 @@
 use std::{
+    fs::Permissions,
     io::prelude::*,
+    os::unix::fs::{MetadataExt, PermissionsExt},
     os::unix::io::{AsRawFd, FromRawFd, RawFd},
+    time::{Duration, UNIX_EPOCH},
 };
 
 use tempfile::NamedTempFile;
 
-use tokio_uring::fs::File;
+use tokio_uring::fs::{self, Advice, File, OpenOptions};
 
 #[path = "../src/future.rs"]
 #[allow(warnings)]
@@ -62,6 +65,106 @@ fn cancel_read() {
     });
 }
 
+#[test]
+fn cancel_read_in_flight() {
+    tokio_uring::start(async {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) }, 0);
+        let _rx = unsafe { std::fs::File::from_raw_fd(fds[0]) };
+        let tx = unsafe { std::fs::File::from_raw_fd(fds[1]) };
+        let rx = File::open(format!("/proc/self/fd/{}", fds[0]))
+            .await
+            .unwrap();
+
+        // Nothing is written to the pipe, the read stays in flight
+        let mut read = rx.read_at(vec![0; 64], 0);
+        tokio::select! {
+            _ = &mut read => panic!("read completed"),
+            _ = tokio_uring::time::sleep(Duration::from_millis(10)) => {}
+        }
+
+        let (res, buf) = read.cancel().await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+        assert_eq!(buf.capacity(), 64);
+        drop(tx);
+    });
+}
+
+#[test]
+fn cancel_read_completed() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let mut read = file.read_at(Vec::with_capacity(1024), 0);
+        poll_once(&mut read).await;
+        tokio_uring::time::sleep(Duration::from_millis(10)).await;
+
+        // The read completed before the cancellation, its result is kept
+        let (res, buf) = read.cancel().await;
+        assert_eq!(res.unwrap(), HELLO.len());
+        assert_eq!(buf, HELLO);
+
+        // A read never polled is never submitted
+        let (res, buf) = file.read_at(Vec::with_capacity(1024), 0).cancel().await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+        assert!(buf.is_empty());
+    });
+}
+
+#[test]
+fn dropped_read_recycles_buffer() {
+    use std::{cell::RefCell, rc::Rc};
+
+    tokio_uring::start(async {
+        let recycled = Rc::new(RefCell::new(vec![]));
+        let pool = recycled.clone();
+        tokio_uring::buf::set_orphan_recycler(move |buf: Vec<u8>| pool.borrow_mut().push(buf));
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) }, 0);
+        let _rx = unsafe { std::fs::File::from_raw_fd(fds[0]) };
+        let mut tx = unsafe { std::fs::File::from_raw_fd(fds[1]) };
+        let rx = File::open(format!("/proc/self/fd/{}", fds[0]))
+            .await
+            .unwrap();
+
+        // Drop the read while in flight, then complete it
+        poll_once(rx.read_at(vec![0; 64], 0)).await;
+        tx.write_all(HELLO).unwrap();
+
+        while recycled.borrow().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(recycled.borrow()[0].capacity(), 64);
+
+        // Without a recycler, the buffer is dropped
+        tokio_uring::buf::clear_orphan_recycler::<Vec<u8>>();
+        poll_once(rx.read_at(vec![0; 64], 0)).await;
+        tx.write_all(HELLO).unwrap();
+        tokio_uring::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(recycled.borrow().len(), 1);
+    });
+}
+
+#[test]
+fn read_at_outlives_file() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let read = file.read_at(Vec::with_capacity(1024), 0);
+
+        // The future keeps the file descriptor open
+        drop(file);
+        let (res, buf) = read.await;
+        assert_eq!(res.unwrap(), HELLO.len());
+        assert_eq!(buf, HELLO);
+    });
+}
+
 #[test]
 fn explicit_close() {
     let mut tempfile = tempfile();
@@ -81,7 +184,7 @@ fn explicit_close() {
 fn drop_open() {
     tokio_uring::start(async {
         let tempfile = tempfile();
-        let _ = File::create(tempfile.path());
+        drop(File::create(tempfile.path()));
 
         // Do something else
         let file = File::create(tempfile.path()).await.unwrap();
@@ -120,6 +223,108 @@ fn sync_doesnt_kill_anything() {
     });
 }
 
+#[test]
+fn mmap_write_through() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(&[0; 8192]).unwrap();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        // Unaligned start
+        let mut region = unsafe { file.mmap(4100..4110).unwrap() };
+        assert_eq!(region.len(), 10);
+        region.madvise(Advice::Sequential).unwrap();
+        region.copy_from_slice(b"0123456789");
+        region.msync_async().await.unwrap();
+        drop(region);
+
+        let (res, buf) = file.read_at(vec![0; 10], 4100).await;
+        res.unwrap();
+        assert_eq!(buf, b"0123456789");
+
+        // Writes to the file are visible in the region
+        file.write_at(b"abc".as_slice(), 0).await.0.unwrap();
+        let region = unsafe { file.mmap(0..3).unwrap() };
+        assert_eq!(&region[..], b"abc");
+    });
+}
+
+#[test]
+fn mmap_invalid_range() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        let err = unsafe { file.mmap(10..10) }.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        // Read-only file
+        let err = unsafe { file.mmap(0..10) }.unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EACCES));
+    });
+}
+
+#[test]
+fn set_times() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let atime = UNIX_EPOCH + Duration::new(1_000_000_000, 123_456_789);
+        let mtime = UNIX_EPOCH + Duration::from_secs(2_000_000_000);
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        file.set_times(Some(atime), Some(mtime)).await.unwrap();
+
+        let metadata = std::fs::metadata(tempfile.path()).unwrap();
+        assert_eq!(metadata.accessed().unwrap(), atime);
+        assert_eq!(metadata.modified().unwrap(), mtime);
+
+        // `None` leaves the time unchanged
+        file.set_times(None, Some(atime)).await.unwrap();
+
+        let metadata = std::fs::metadata(tempfile.path()).unwrap();
+        assert_eq!(metadata.accessed().unwrap(), atime);
+        assert_eq!(metadata.modified().unwrap(), atime);
+    });
+}
+
+#[test]
+fn set_permissions() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+
+        fs::set_permissions(tempfile.path(), Permissions::from_mode(0o600))
+            .await
+            .unwrap();
+
+        let mode = std::fs::metadata(tempfile.path()).unwrap().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    });
+}
+
+#[test]
+fn chown_unchanged() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let before = std::fs::metadata(tempfile.path()).unwrap();
+
+        // Changing to the current owner needs no privileges
+        fs::chown(tempfile.path(), Some(before.uid()), None)
+            .await
+            .unwrap();
+        fs::chown(tempfile.path(), None, None).await.unwrap();
+
+        let after = std::fs::metadata(tempfile.path()).unwrap();
+        assert_eq!(after.uid(), before.uid());
+        assert_eq!(after.gid(), before.gid());
+    });
+}
+
 fn tempfile() -> NamedTempFile {
     NamedTempFile::new().unwrap()
 }
@@ -142,7 +347,8 @@ async fn poll_once(future: impl std::future::Future) {
 fn assert_invalid_fd(fd: RawFd) {
     use std::fs::File;
 
-    let mut f = unsafe { File::from_raw_fd(fd) };
+    // The fd is already closed, so the `File` must not close it again on drop.
+    let mut f = std::mem::ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
     let mut buf = vec![];
 
     match f.read_to_end(&mut buf) {
@@ -150,3 +356,570 @@ fn assert_invalid_fd(fd: RawFd) {
         res => panic!("{:?}", res),
     }
 }
+
+#[test]
+fn read_write_at_current_position() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        let (res, _) = file.write(&b"hello "[..]).await;
+        assert_eq!(res.unwrap(), 6);
+        let (res, _) = file.write(&b"world"[..]).await;
+        assert_eq!(res.unwrap(), 5);
+        assert_eq!(file.seek(std::io::SeekFrom::Current(0)).unwrap(), 11);
+
+        // Positional I/O leaves the position unchanged
+        let (res, _) = file.write_at(&b"H"[..], 0).await;
+        res.unwrap();
+        assert_eq!(file.seek(std::io::SeekFrom::Current(0)).unwrap(), 11);
+
+        assert_eq!(file.seek(std::io::SeekFrom::Start(0)).unwrap(), 0);
+        let (res, buf) = file.read(vec![0; 6]).await;
+        assert_eq!(&buf[..res.unwrap()], b"Hello ");
+        let (res, buf) = file.read(vec![0; 16]).await;
+        assert_eq!(&buf[..res.unwrap()], b"world");
+        let (res, _) = file.read(vec![0; 16]).await;
+        assert_eq!(res.unwrap(), 0);
+
+        assert_eq!(file.seek(std::io::SeekFrom::End(-5)).unwrap(), 6);
+        let (res, buf) = file.read(vec![0; 5]).await;
+        assert_eq!(&buf[..res.unwrap()], b"world");
+
+        assert!(file.seek(std::io::SeekFrom::Current(-100)).is_err());
+    });
+}
+
+#[test]
+fn read_to_end_from_current_position() {
+    let mut tempfile = tempfile();
+    let data: Vec<u8> = (0..1_000_000u32).map(|i| (i % 251) as u8).collect();
+    tempfile.write_all(&data).unwrap();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+        file.seek(std::io::SeekFrom::Start(1000)).unwrap();
+
+        let (res, buf) = file.read_to_end(b"prefix".to_vec()).await;
+        assert_eq!(res.unwrap(), data.len() - 1000);
+        assert_eq!(&buf[..6], b"prefix");
+        assert!(buf[6..] == data[1000..]);
+
+        // The position is at the end of the file
+        assert_eq!(
+            file.seek(std::io::SeekFrom::Current(0)).unwrap(),
+            data.len() as u64
+        );
+        let (res, buf) = file.read_to_end(Vec::new()).await;
+        assert_eq!(res.unwrap(), 0);
+        assert!(buf.is_empty());
+    });
+}
+
+#[test]
+fn read_full_sized_from_file() {
+    let mut tempfile = tempfile();
+    let data: Vec<u8> = (0..1_000_000u32).map(|i| (i % 251) as u8).collect();
+    tempfile.write_all(&data).unwrap();
+
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        let contents = file.read_full().await.unwrap();
+        assert!(contents == data);
+
+        let rest = file.read_at_auto(999_000).await.unwrap();
+        assert_eq!(rest.capacity(), 1000);
+        assert!(rest == data[999_000..]);
+
+        assert!(file.read_at_auto(2_000_000).await.unwrap().is_empty());
+
+        // The position is left unchanged
+        assert_eq!(file.seek(std::io::SeekFrom::Current(0)).unwrap(), 0);
+    });
+}
+
+#[test]
+fn read_at_auto_fails_on_pipes() {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fifo");
+    let cpath = CString::new(path.as_os_str().as_bytes()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(cpath.as_ptr(), 0o600) }, 0);
+
+    tokio_uring::start(async {
+        // Opening for reading and writing does not wait for a writer
+        let fifo = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .await
+            .unwrap();
+
+        for pos in [0, 4096] {
+            let err = fifo.read_at_auto(pos).await.unwrap_err();
+            assert_eq!(err.raw_os_error(), Some(libc::ESPIPE));
+        }
+    });
+}
+
+#[test]
+fn cursor_reads_and_writes_in_sequence() {
+    use std::io::SeekFrom;
+    use tokio_uring::fs::FileCursor;
+
+    let tempfile = tempfile();
+
+    tokio_uring::start(async {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+        let mut cursor = FileCursor::new(file);
+
+        cursor.write_all(&b"hello "[..]).await.0.unwrap();
+        cursor.write_all(&b"world"[..]).await.0.unwrap();
+        assert_eq!(cursor.position(), 11);
+
+        assert_eq!(cursor.seek(SeekFrom::End(-5)).await.unwrap(), 6);
+        let (res, buf) = cursor.read(vec![0; 16]).await;
+        assert_eq!(&buf[..res.unwrap()], b"world");
+        assert_eq!(cursor.position(), 11);
+
+        // At the end of the file
+        let (res, _) = cursor.read(vec![0; 16]).await;
+        assert_eq!(res.unwrap(), 0);
+
+        assert_eq!(cursor.seek(SeekFrom::Current(-11)).await.unwrap(), 0);
+        let (res, buf) = cursor.read(vec![0; 5]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+
+        let err = cursor.seek(SeekFrom::Current(-6)).await.unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+        assert_eq!(cursor.position(), 5);
+
+        // The position of the file itself is left unchanged
+        let file = cursor.into_file();
+        assert_eq!(file.seek(SeekFrom::Current(0)).unwrap(), 0);
+    });
+}
+
+#[test]
+fn read_to_end_of_pipe() {
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+
+    tokio_uring::start(async {
+        // Reopened, as a file is not created from a file descriptor
+        let file = File::open(format!("/proc/self/fd/{}", fds[0]))
+            .await
+            .unwrap();
+        unsafe { libc::close(fds[0]) };
+
+        let writer = std::thread::spawn(move || {
+            let mut tx = unsafe { std::fs::File::from_raw_fd(fds[1]) };
+            for _ in 0..100 {
+                tx.write_all(&[7; 1000]).unwrap();
+            }
+        });
+
+        let (res, buf) = file.read_to_end(Vec::new()).await;
+        assert_eq!(res.unwrap(), 100_000);
+        assert!(buf.iter().all(|&b| b == 7));
+        writer.join().unwrap();
+    });
+}
+
+#[test]
+fn concurrent_appends_do_not_clobber() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+
+        let log = std::rc::Rc::new(fs::AppendFile::open(&path).await.unwrap());
+        let other = std::rc::Rc::new(fs::AppendFile::open(&path).await.unwrap());
+
+        let tasks: Vec<_> = (0..64)
+            .map(|i| {
+                let file = if i % 2 == 0 { &log } else { &other }.clone();
+                tokio_uring::spawn(async move {
+                    let record = format!("record {:02}\n", i).into_bytes();
+                    let (res, _) = file.append(record).await;
+                    assert_eq!(res.unwrap(), 10);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let log = std::rc::Rc::try_unwrap(log).unwrap();
+        let other = std::rc::Rc::try_unwrap(other).unwrap();
+
+        // Positional writes append as well
+        let (res, _) = log.write_at(&b"positional\n"[..], 0).await;
+        res.unwrap();
+
+        log.close().await.unwrap();
+        other.close().await.unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let mut records: Vec<&str> = contents.lines().collect();
+        assert_eq!(records.pop(), Some("positional"));
+        records.sort_unstable();
+        let expected: Vec<String> = (0..64).map(|i| format!("record {:02}", i)).collect();
+        assert_eq!(records, expected);
+    });
+}
+
+#[test]
+fn try_clone_closes_independently() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        let clone = file.try_clone().unwrap();
+        assert_ne!(clone.as_raw_fd(), file.as_raw_fd());
+        assert!(tokio_uring::fd::is_cloexec(&clone).unwrap());
+
+        let writer = tokio_uring::spawn(async move {
+            let (res, _) = clone.write(HELLO).await;
+            res.unwrap();
+            clone.close().await.unwrap();
+        });
+        writer.await.unwrap();
+
+        // The position is shared
+        assert_eq!(
+            file.seek(std::io::SeekFrom::Current(0)).unwrap(),
+            HELLO.len() as u64
+        );
+        read_hello(&file).await;
+        file.close().await.unwrap();
+    });
+}
+
+#[test]
+fn write_with_flags() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        let (res, _) = file
+            .write_at_with_flags(&b"hello"[..], 0, fs::RwFlags::DSYNC)
+            .await;
+        assert_eq!(res.unwrap(), 5);
+
+        // The offset is ignored when appending
+        let (res, _) = file
+            .write_at_with_flags(&b" world"[..], 0, fs::RwFlags::APPEND | fs::RwFlags::DSYNC)
+            .await;
+        assert_eq!(res.unwrap(), 6);
+
+        let (res, buf) = file
+            .read_at_with_flags(vec![0; 32], 0, fs::RwFlags::empty())
+            .await;
+        assert_eq!(&buf[..res.unwrap()], b"hello world");
+
+        assert!((fs::RwFlags::APPEND | fs::RwFlags::DSYNC).contains(fs::RwFlags::DSYNC));
+        assert!(!fs::RwFlags::APPEND.contains(fs::RwFlags::DSYNC));
+    });
+}
+
+#[test]
+fn read_many_at_returns_in_order() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        let records: Vec<u8> = (0..64u8).flat_map(|i| [i; 16]).collect();
+        tempfile.write_all(&records).unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let reads = [42u64, 3, 17, 63, 64]
+            .iter()
+            .map(|i| (vec![0; 16], i * 16))
+            .collect();
+
+        let results = file.read_many_at(reads).await.unwrap();
+        let records: Vec<_> = results
+            .into_iter()
+            .map(|(res, buf)| buf[..res.unwrap()].to_vec())
+            .collect();
+
+        // The last read is past the end of the file
+        assert_eq!(
+            records,
+            [
+                vec![42; 16],
+                vec![3; 16],
+                vec![17; 16],
+                vec![63; 16],
+                vec![]
+            ]
+        );
+    });
+}
+
+#[test]
+fn read_first_at_cancels_losers() {
+    use futures_core::Stream;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        // A read from a FIFO nobody writes to never completes
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fifo");
+        let cpath = CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(cpath.as_ptr(), 0o600) }, 0);
+        let fifo = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .await
+            .unwrap();
+
+        let reads = vec![(&fifo, vec![0; 32], 0), (&file, vec![0; 32], 0)];
+        let (winner, (res, buf), mut losers) = fs::read_first_at(reads).await.unwrap();
+        assert_eq!(winner, 1);
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+        assert_eq!(losers.len(), 1);
+
+        // The loser gives its buffer back once canceled
+        let (index, (res, buf)) =
+            future::poll_fn(|cx| std::pin::Pin::new(&mut losers).poll_next(cx))
+                .await
+                .unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+        assert_eq!(buf.capacity(), 32);
+        assert!(losers.is_empty());
+
+        assert!(fs::read_first_at::<Vec<u8>>(vec![]).await.is_err());
+    });
+}
+
+#[test]
+fn read_hedged_issues_second_read_after_delay() {
+    use std::cell::Cell;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        tempfile.write_all(HELLO).unwrap();
+        let file = File::open(tempfile.path()).await.unwrap();
+
+        // A fast read is not hedged
+        let calls = Cell::new(0);
+        let factory = |len| {
+            calls.set(calls.get() + 1);
+            vec![0; len]
+        };
+        let (res, buf) = file
+            .read_hedged(factory, 0, 32, Duration::from_secs(10))
+            .await;
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+        assert_eq!(calls.get(), 1);
+
+        // A read from a FIFO waits for a writer
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fifo");
+        let cpath = CString::new(path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(cpath.as_ptr(), 0o600) }, 0);
+        let fifo = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .await
+            .unwrap();
+
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .write_all(HELLO)
+                .unwrap();
+        });
+
+        calls.set(0);
+        let factory = |len| {
+            calls.set(calls.get() + 1);
+            vec![0; len]
+        };
+        let (res, buf) = fifo
+            .read_hedged(factory, 0, 32, Duration::from_millis(10))
+            .await;
+        assert_eq!(&buf[..res.unwrap()], HELLO);
+        assert_eq!(calls.get(), 2);
+
+        writer.join().unwrap();
+    });
+}
+
+#[test]
+fn read_extents_in_order() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        let data: Vec<u8> = (0..=255u8).collect();
+        tempfile.write_all(&data).unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let extents = file
+            .read_extents(&[(200, 10), (0, 4), (250, 16), (1000, 8)])
+            .await
+            .unwrap();
+
+        assert_eq!(extents.len(), 4);
+        assert_eq!(extents.get(0).unwrap(), &data[200..210]);
+        assert_eq!(extents.get(1).unwrap(), &data[0..4]);
+        // Short read at the end of the file
+        assert_eq!(extents.get(2).unwrap(), &data[250..]);
+        assert_eq!(extents.get(3).unwrap(), b"");
+
+        // The extents follow each other in a single buffer
+        assert_eq!(extents.into_inner().len(), 38);
+
+        // A failed read is reported for its extent
+        let write_only = OpenOptions::new()
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+        let extents = write_only.read_extents(&[(0, 4)]).await.unwrap();
+        assert_eq!(
+            extents.get(0).unwrap_err().raw_os_error(),
+            Some(libc::EBADF)
+        );
+    });
+}
+
+#[test]
+fn seek_data_and_hole_of_sparse_file() {
+    tokio_uring::start(async {
+        let tempfile = tempfile();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tempfile.path())
+            .await
+            .unwrap();
+
+        // Data far after a hole, so that it is not in the same block
+        let offset = 16 * 1024 * 1024;
+        let (res, _) = file.write_at(b"sparse".to_vec(), offset).await;
+        res.unwrap();
+        let len = offset + 6;
+
+        let data = file.seek_data(0).unwrap().unwrap();
+        assert!(data <= offset);
+        assert!(file.seek_hole(data).unwrap() >= len);
+        assert_eq!(file.seek_data(len).unwrap(), None);
+
+        match file.extent_map() {
+            Ok(extents) => {
+                let last = extents.last().unwrap();
+                assert!(last.logical() + last.len() >= len);
+            }
+            // Not supported by tmpfs
+            Err(e) => assert_eq!(e.raw_os_error(), Some(libc::EOPNOTSUPP)),
+        }
+
+        let copy = NamedTempFile::new().unwrap();
+        let dst = OpenOptions::new()
+            .write(true)
+            .open(copy.path())
+            .await
+            .unwrap();
+        let copied = fs::copy_sparse(&file, &dst).await.unwrap();
+        assert!(copied >= 6 && copied <= len);
+
+        let content = std::fs::read(copy.path()).unwrap();
+        assert_eq!(content.len() as u64, len);
+        assert_eq!(&content[offset as usize..], b"sparse");
+        assert!(content[..offset as usize].iter().all(|&b| b == 0));
+    });
+}
+
+/// Returns the number of pages of the file cached.
+fn cached_pages(file: &std::fs::File, len: usize) -> usize {
+    unsafe {
+        let addr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        );
+        assert_ne!(addr, libc::MAP_FAILED);
+
+        let mut pages = vec![0u8; len.div_ceil(4096)];
+        assert_eq!(libc::mincore(addr, len, pages.as_mut_ptr()), 0);
+        libc::munmap(addr, len);
+
+        pages.iter().filter(|&&page| page & 1 != 0).count()
+    }
+}
+
+#[test]
+fn read_chunks_drop_behind() {
+    tokio_uring::start(async {
+        let mut tempfile = tempfile();
+        let data: Vec<u8> = (0..1024 * 1024 + 10u32).map(|i| (i % 251) as u8).collect();
+        tempfile.write_all(&data).unwrap();
+        // Dirty pages are not dropped
+        tempfile.as_file().sync_all().unwrap();
+
+        let file = File::open(tempfile.path()).await.unwrap();
+        let mut chunks = file.read_chunks(64 * 1024).await.unwrap();
+        chunks.drop_behind(true);
+
+        let mut contents = Vec::new();
+        while let Some(chunk) = chunks.next().await.unwrap() {
+            contents.extend_from_slice(&chunk);
+            chunks.recycle(chunk);
+        }
+        assert!(contents == data);
+        // The advice completes in the background
+        let mut cached = cached_pages(tempfile.as_file(), data.len());
+        for _ in 0..100 {
+            if cached == 0 {
+                break;
+            }
+            tokio_uring::time::sleep(Duration::from_millis(10)).await;
+            cached = cached_pages(tempfile.as_file(), data.len());
+        }
+        assert_eq!(cached, 0);
+
+        // Read back into the cache, and dropped again
+        let (res, _) = file.read_at(Vec::with_capacity(data.len()), 0).await;
+        res.unwrap();
+        assert!(cached_pages(tempfile.as_file(), data.len()) > 0);
+        file.advise(0, 0, Advice::DontNeed).await.unwrap();
+        assert_eq!(cached_pages(tempfile.as_file(), data.len()), 0);
+    });
+}
@@ -0,0 +1,62 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io::prelude::*;
+
+use tempfile::NamedTempFile;
+
+use tokio_uring::fs::{self, Crc32, HashAlgorithm};
+
+#[test]
+fn crc32_known_value() {
+    tokio_uring::start(async {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        tempfile.write_all(b"123456789").unwrap();
+
+        let crc = fs::hash_file(tempfile.path(), Crc32::new()).await.unwrap();
+        assert_eq!(crc, 0xcbf4_3926);
+    });
+}
+
+#[test]
+fn crc32_empty() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+
+        let crc = fs::hash_file(tempfile.path(), Crc32::new()).await.unwrap();
+        assert_eq!(crc, 0);
+    });
+}
+
+#[test]
+fn hasher_over_many_chunks() {
+    tokio_uring::start(async {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        let data: Vec<u8> = (0..2_000_001u32).map(|i| (i % 253) as u8).collect();
+        tempfile.write_all(&data).unwrap();
+
+        // Chunking does not change the hash
+        let mut expected = Crc32::new();
+        expected.update(&data);
+
+        let crc = fs::hash_file(tempfile.path(), Crc32::new()).await.unwrap();
+        assert_eq!(crc, expected.finalize());
+
+        let mut expected = DefaultHasher::new();
+        expected.write(&data);
+
+        let hash = fs::hash_file(tempfile.path(), DefaultHasher::new())
+            .await
+            .unwrap();
+        assert_eq!(hash, expected.finish());
+    });
+}
+
+#[test]
+fn missing_file() {
+    tokio_uring::start(async {
+        let err = fs::hash_file("/does/not/exist", Crc32::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use tempfile::NamedTempFile;
+
+use tokio_uring::fs::{File, OpenOptions};
+
+async fn open(tempfile: &NamedTempFile) -> File {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(tempfile.path())
+        .await
+        .unwrap()
+}
+
+#[test]
+fn exclusive_waits_for_release() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+        let a = open(&tempfile).await;
+        let b = open(&tempfile).await;
+
+        let lock = a.lock_range(0, 100, true).await.unwrap();
+
+        let waiter = tokio_uring::spawn(async move {
+            let lock = b.lock_range(50, 100, true).await.unwrap();
+            drop(lock);
+            b.close().await.unwrap();
+        });
+
+        // The ranges overlap, the second lock waits
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        lock.unlock().unwrap();
+        waiter.await.unwrap();
+    });
+}
+
+#[test]
+fn shared_and_disjoint_locks() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+        let a = open(&tempfile).await;
+        let b = open(&tempfile).await;
+
+        // Shared locks do not conflict
+        let _a_shared = a.lock_range(0, 10, false).await.unwrap();
+        let _b_shared = b.lock_range(0, 10, false).await.unwrap();
+
+        // Neither do exclusive locks on disjoint ranges
+        let _a_exclusive = a.lock_range(10, 10, true).await.unwrap();
+        let _b_exclusive = b.lock_range(20, 0, true).await.unwrap();
+    });
+}
+
+#[test]
+fn dropped_wait_releases_lock() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+        let a = open(&tempfile).await;
+        let b = open(&tempfile).await;
+
+        let lock = a.lock_range(0, 0, true).await.unwrap();
+
+        // Give up waiting for the lock
+        let wait = b.lock_range(0, 0, true);
+        let res = tokio::time::timeout(Duration::from_millis(50), wait).await;
+        assert!(res.is_err());
+
+        drop(lock);
+
+        // Once acquired in the background, the abandoned lock is released
+        let lock = tokio::time::timeout(Duration::from_secs(5), a.lock_range(0, 0, true))
+            .await
+            .unwrap()
+            .unwrap();
+        drop(lock);
+    });
+}
+
+#[test]
+fn lock_outlives_file() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+        let a = open(&tempfile).await;
+        let b = open(&tempfile).await;
+
+        let lock = a.lock_range(0, 10, true).await.unwrap();
+        a.close().await.unwrap();
+
+        // Still held by the guard
+        let wait = b.lock_range(0, 10, true);
+        let res = tokio::time::timeout(Duration::from_millis(50), wait).await;
+        assert!(res.is_err());
+
+        drop(lock);
+        let _lock = b.lock_range(0, 10, true).await.unwrap();
+    });
+}
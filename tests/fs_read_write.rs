@@ -0,0 +1,161 @@
+use std::io::prelude::*;
+
+use tempfile::NamedTempFile;
+
+use tokio_uring::fs;
+
+#[test]
+fn read_empty() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+
+        let contents = fs::read(tempfile.path()).await.unwrap();
+        assert!(contents.is_empty());
+    });
+}
+
+#[test]
+fn read_large() {
+    tokio_uring::start(async {
+        let mut tempfile = NamedTempFile::new().unwrap();
+
+        // Spans several chunks, and ends in the middle of one
+        let data: Vec<u8> = (0..1_000_003u32).map(|i| (i % 251) as u8).collect();
+        tempfile.write_all(&data).unwrap();
+
+        let contents = fs::read(tempfile.path()).await.unwrap();
+        assert_eq!(contents.len(), data.len());
+        assert!(contents == data);
+    });
+}
+
+#[test]
+fn read_to_string() {
+    tokio_uring::start(async {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        tempfile.write_all("hello wörld".as_bytes()).unwrap();
+
+        let contents = fs::read_to_string(tempfile.path()).await.unwrap();
+        assert_eq!(contents, "hello wörld");
+    });
+}
+
+#[test]
+fn read_to_string_invalid_utf8() {
+    tokio_uring::start(async {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        tempfile.write_all(&[0xff, 0xfe]).unwrap();
+
+        let err = fs::read_to_string(tempfile.path()).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    });
+}
+
+#[test]
+fn read_missing() {
+    tokio_uring::start(async {
+        let err = fs::read("/does/not/exist").await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn write_then_read() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        let data = vec![7; 300_000];
+        let (res, buf) = fs::write(&path, data).await;
+        res.unwrap();
+        assert_eq!(buf.len(), 300_000);
+
+        assert_eq!(std::fs::read(&path).unwrap(), buf);
+    });
+}
+
+#[test]
+fn write_truncates() {
+    tokio_uring::start(async {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        tempfile.write_all(b"a longer previous content").unwrap();
+
+        let (res, _) = fs::write(tempfile.path(), "short").await;
+        res.unwrap();
+
+        assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"short");
+    });
+}
+
+#[test]
+fn write_atomic_replaces() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state");
+        std::fs::write(&path, b"old contents, longer").unwrap();
+
+        let (res, _) = fs::write_atomic(&path, "new").await;
+        res.unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+
+        // No temporary file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    });
+}
+
+#[test]
+fn write_atomic_creates() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state");
+
+        let (res, buf) = fs::write_atomic(&path, vec![1; 200_000]).await;
+        res.unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), buf);
+    });
+}
+
+#[test]
+fn write_atomic_missing_dir() {
+    tokio_uring::start(async {
+        let (res, _) = fs::write_atomic("/does/not/exist/state", "new").await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn copy_direct() {
+    tokio_uring::start(async {
+        let mut src = NamedTempFile::new().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dst = dir.path().join("copy");
+
+        // Several rounds of the pipeline, ending in the middle of a block
+        let data: Vec<u8> = (0..5 * 4096 + 100u32).map(|i| (i % 251) as u8).collect();
+        src.write_all(&data).unwrap();
+
+        let copied = match fs::copy_direct(src.path(), &dst, 4096, 2).await {
+            Ok(copied) => copied,
+            // No `O_DIRECT` on this file system
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => return,
+            Err(e) => panic!("{}", e),
+        };
+        assert_eq!(copied, data.len() as u64);
+        assert!(std::fs::read(&dst).unwrap() == data);
+
+        // An empty file, over the previous copy
+        let empty = NamedTempFile::new().unwrap();
+        assert_eq!(
+            fs::copy_direct(empty.path(), &dst, 4096, 2).await.unwrap(),
+            0
+        );
+        assert!(std::fs::read(&dst).unwrap().is_empty());
+
+        let err = fs::copy_direct(src.path(), &dst, 1000, 2)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
@@ -0,0 +1,105 @@
+use std::io;
+
+use tokio_uring::fs::{self, NamedTempFile};
+
+#[test]
+fn tempfile_in_link() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+
+        let file = match fs::tempfile_in(dir.path()).await {
+            // Not all filesystems support `O_TMPFILE`
+            Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => return,
+            res => res.unwrap(),
+        };
+
+        let (res, _) = file.write_at(&b"hello world"[..], 0).await;
+        res.unwrap();
+
+        // The file is anonymous
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let path = dir.path().join("linked");
+        file.link(&path).await.unwrap();
+        file.close().await.unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello world");
+    });
+}
+
+#[test]
+fn named_deleted_on_drop() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+
+        let tmp = NamedTempFile::new_in(dir.path()).await.unwrap();
+        let path = tmp.path().to_owned();
+        assert!(path.starts_with(dir.path()));
+        assert!(path.exists());
+
+        drop(tmp);
+
+        // The file is deleted in the background
+        for _ in 0..100 {
+            if !path.exists() {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+        panic!("file not deleted");
+    });
+}
+
+#[test]
+fn named_close() {
+    tokio_uring::start(async {
+        let tmp = NamedTempFile::new().await.unwrap();
+        let path = tmp.path().to_owned();
+
+        let (res, _) = tmp.write_at(&b"hello"[..], 0).await;
+        res.unwrap();
+
+        tmp.close().await.unwrap();
+        assert!(!path.exists());
+    });
+}
+
+#[test]
+fn named_persist() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        std::fs::write(&target, b"old").unwrap();
+
+        let tmp = NamedTempFile::new_in(dir.path()).await.unwrap();
+        let (res, _) = tmp.write_at(&b"new"[..], 0).await;
+        res.unwrap();
+
+        let file = tmp.persist(&target).await.unwrap();
+        file.close().await.unwrap();
+
+        assert_eq!(std::fs::read(&target).unwrap(), b"new");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    });
+}
+
+#[test]
+fn named_keep() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+
+        let tmp = NamedTempFile::new_in(dir.path()).await.unwrap();
+        let (file, path) = tmp.keep();
+        file.close().await.unwrap();
+
+        assert!(path.exists());
+    });
+}
+
+#[test]
+fn named_missing_dir() {
+    tokio_uring::start(async {
+        let err = NamedTempFile::new_in("/does/not/exist").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    });
+}
@@ -0,0 +1,156 @@
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use tokio_uring::sync::futex::{Futex, Mutex, Notify};
+
+#[test]
+fn wait_returns_if_value_differs() {
+    tokio_uring::start(async {
+        let futex = Futex::new(1);
+        futex.wait(0).await.unwrap();
+    });
+}
+
+#[test]
+fn wake_waiting_task() {
+    tokio_uring::start(async {
+        let futex = Futex::new(0);
+
+        let waiter = {
+            let futex = futex.clone();
+            tokio_uring::spawn(async move { futex.wait(0).await })
+        };
+
+        // Until the waiter is parked on the ring, there is no one to wake
+        let mut woken = 0;
+        while woken == 0 {
+            tokio_uring::task::yield_now().await;
+            woken = futex.wake(1).await.unwrap();
+        }
+        assert_eq!(woken, 1);
+
+        waiter.await.unwrap().unwrap();
+    });
+}
+
+#[test]
+fn dropped_wait_is_canceled() {
+    tokio_uring::start(async {
+        let futex = Futex::new(0);
+
+        let res = tokio::time::timeout(Duration::from_millis(20), futex.wait(0)).await;
+        assert!(res.is_err());
+
+        // The wait left the word
+        tokio_uring::task::yield_now().await;
+        assert_eq!(futex.wake(1).await.unwrap(), 0);
+    });
+}
+
+#[test]
+fn mutex_serializes_tasks() {
+    tokio_uring::start(async {
+        let mutex = Rc::new(Mutex::new());
+        let inside = Rc::new(std::cell::Cell::new(false));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let mutex = mutex.clone();
+                let inside = inside.clone();
+                tokio_uring::spawn(async move {
+                    for _ in 0..10 {
+                        let _guard = mutex.lock().await.unwrap();
+                        assert!(!inside.replace(true));
+                        tokio::time::sleep(Duration::from_micros(100)).await;
+                        inside.set(false);
+                    }
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert!(mutex.try_lock().is_some());
+        assert_eq!(mutex.futex().value().load(Ordering::SeqCst), 0);
+    });
+}
+
+#[test]
+fn mutex_shared_between_runtimes() {
+    let file = tempfile::tempfile().unwrap();
+    file.set_len(8).unwrap();
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let file = file.try_clone().unwrap();
+            thread::spawn(move || {
+                tokio_uring::start(async {
+                    let mutex = Mutex::from_futex(Futex::map(&file, 0).unwrap());
+                    let counter = Futex::map(&file, 4).unwrap();
+
+                    for _ in 0..100 {
+                        let _guard = mutex.lock().await.unwrap();
+                        // Not atomic, relying on the lock
+                        let value = counter.value().load(Ordering::Relaxed);
+                        tokio_uring::task::yield_now().await;
+                        counter.value().store(value + 1, Ordering::Relaxed);
+                    }
+                })
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let counter = Futex::map(&file, 4).unwrap();
+    assert_eq!(counter.value().load(Ordering::SeqCst), 400);
+}
+
+#[test]
+fn notify_between_runtimes() {
+    let file = tempfile::tempfile().unwrap();
+    file.set_len(4).unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let waiter = {
+        let file = file.try_clone().unwrap();
+        thread::spawn(move || {
+            tokio_uring::start(async {
+                let notify = Notify::from_futex(Futex::map(&file, 0).unwrap());
+                let notified = notify.notified();
+                tx.send(()).unwrap();
+                notified.await.unwrap();
+            })
+        })
+    };
+
+    tokio_uring::start(async {
+        let notify = Notify::from_futex(Futex::map(&file, 0).unwrap());
+
+        // Whether the waiter is parked yet or not, the notification is seen
+        rx.recv().unwrap();
+        thread::sleep(Duration::from_millis(10));
+        notify.notify_all();
+    });
+
+    waiter.join().unwrap();
+}
+
+#[test]
+fn map_checks_offset() {
+    let file = tempfile::tempfile().unwrap();
+    file.set_len(4).unwrap();
+
+    let err = Futex::map(&file, 2).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = Futex::map(&file, 4).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,53 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr};
+
+use tokio_uring::net::{EchoReply, IcmpSocket};
+
+/// Ping sockets are only allowed to the groups of `net.ipv4.ping_group_range`.
+fn bind(addr: IpAddr) -> Option<IcmpSocket> {
+    match IcmpSocket::bind(addr) {
+        Ok(socket) => Some(socket),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => None,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn ping_localhost() {
+    tokio_uring::start(async {
+        let socket = match bind(IpAddr::V4(Ipv4Addr::UNSPECIFIED)) {
+            Some(socket) => socket,
+            None => return,
+        };
+        let identifier = socket.identifier().unwrap();
+        assert_ne!(identifier, 0);
+
+        for sequence in 1..4 {
+            let (res, _) = socket
+                .send_echo(IpAddr::V4(Ipv4Addr::LOCALHOST), sequence, b"ping".to_vec())
+                .await;
+            res.unwrap();
+
+            let (res, buf) = socket.recv_echo(vec![0; 64]).await;
+            let reply = res.unwrap();
+            assert_eq!(reply.source(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+            assert_eq!(reply.identifier(), identifier);
+            assert_eq!(reply.sequence(), sequence);
+            assert_eq!(reply.payload_len(), 4);
+            assert_eq!(&buf[EchoReply::HEADER_LEN..][..4], b"ping");
+        }
+    });
+}
+
+#[test]
+fn recv_echo_buffer_too_small() {
+    tokio_uring::start(async {
+        let socket = match bind(IpAddr::V4(Ipv4Addr::UNSPECIFIED)) {
+            Some(socket) => socket,
+            None => return,
+        };
+
+        let (res, _) = socket.recv_echo(vec![0; 4]).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EMSGSIZE));
+    });
+}
@@ -0,0 +1,78 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use tokio_uring::fs::File;
+use tokio_uring::io::{copy, duplex};
+use tokio_uring::net::UnixStream;
+use tokio_uring::pipe::pipe;
+
+fn data() -> Vec<u8> {
+    (0..300_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn copy_file_through_pipe() {
+    let data = data();
+    let mut src = tempfile::NamedTempFile::new().unwrap();
+    src.write_all(&data).unwrap();
+    let mut dst = tempfile::NamedTempFile::new().unwrap();
+
+    tokio_uring::start(async {
+        let (rx, tx) = pipe().unwrap();
+
+        let file = File::open(src.path()).await.unwrap();
+        let writer = tokio_uring::spawn(async move {
+            let n = copy(&file, &tx).await.unwrap();
+            tx.close().await.unwrap();
+            n
+        });
+
+        let out = File::create(dst.path()).await.unwrap();
+        assert_eq!(copy(&rx, &out).await.unwrap(), data.len() as u64);
+        assert_eq!(writer.await.unwrap(), data.len() as u64);
+
+        // The position of the file advanced past the data copied
+        assert_eq!(out.seek(SeekFrom::Current(0)).unwrap(), data.len() as u64);
+    });
+
+    let mut copied = Vec::new();
+    dst.as_file_mut().seek(SeekFrom::Start(0)).unwrap();
+    dst.as_file_mut().read_to_end(&mut copied).unwrap();
+    assert!(copied == data);
+}
+
+#[test]
+fn copy_socket_to_memory() {
+    let data = data();
+
+    tokio_uring::start(async {
+        let (a, b) = UnixStream::pair().unwrap();
+        let (client, server) = duplex(4096);
+
+        let mut sent = data.clone();
+        let sender = tokio_uring::spawn(async move {
+            while !sent.is_empty() {
+                let (res, buf) = a.write(sent).await;
+                sent = buf;
+                sent.drain(..res.unwrap());
+            }
+            // Dropping the socket ends the data
+        });
+
+        let reader = tokio_uring::spawn(async move {
+            let mut received = Vec::new();
+            loop {
+                let (res, buf) = server.read(vec![0; 8192]).await;
+                match res.unwrap() {
+                    0 => return received,
+                    n => received.extend_from_slice(&buf[..n]),
+                }
+            }
+        });
+
+        assert_eq!(copy(&b, &client).await.unwrap(), data.len() as u64);
+        sender.await.unwrap();
+        drop(client);
+
+        assert!(reader.await.unwrap() == data);
+    });
+}
@@ -0,0 +1,136 @@
+use std::{
+    env,
+    os::unix::{io::AsRawFd, process::CommandExt},
+    process::Command,
+};
+
+use tokio_uring::net::{sd_listen_fds, TcpListener, TcpStream, UnixListener};
+
+/// Set in the child process, which runs the test body with the socket passed
+/// as the first activation descriptor.
+const CHILD_VAR: &str = "TOKIO_URING_LISTEN_FDS_CHILD";
+
+/// Runs the test `name` in a child process, with `fd` inherited as descriptor
+/// 3 and `LISTEN_FDS=1`, as systemd does.
+fn run_child(name: &str, fd: &impl AsRawFd, envs: &[(&str, String)]) {
+    let fd = fd.as_raw_fd();
+    let mut cmd = Command::new(env::current_exe().unwrap());
+    cmd.args(["--exact", name, "--test-threads=1", "--nocapture"])
+        .env(CHILD_VAR, "1")
+        .env("LISTEN_FDS", "1")
+        .envs(envs.iter().map(|(k, v)| (k, v)));
+
+    // Safety: `dup2` and `fcntl` are async-signal-safe. `dup2` clears
+    // close-on-exec on the new descriptor, unless it is already 3.
+    unsafe {
+        cmd.pre_exec(move || {
+            let res = if fd == 3 {
+                libc::fcntl(fd, libc::F_SETFD, 0)
+            } else {
+                libc::dup2(fd, 3)
+            };
+            if res == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    let status = cmd.status().unwrap();
+    assert!(status.success(), "child test {} failed", name);
+}
+
+/// The child is socket activated for itself, which is only known once it
+/// runs.
+fn set_listen_pid() {
+    env::set_var("LISTEN_PID", std::process::id().to_string());
+}
+
+#[test]
+fn adopt_tcp_listener() {
+    if env::var_os(CHILD_VAR).is_none() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        run_child("adopt_tcp_listener", &listener, &[("TEST_ADDR", addr)]);
+        return;
+    }
+
+    set_listen_pid();
+    let addr = env::var("TEST_ADDR").unwrap().parse().unwrap();
+
+    let mut listeners = TcpListener::from_listen_fds().unwrap();
+    assert_eq!(listeners.len(), 1);
+    assert!(env::var_os("LISTEN_PID").is_none());
+    assert!(env::var_os("LISTEN_FDS").is_none());
+
+    // The descriptors were taken
+    assert!(sd_listen_fds(true).unwrap().is_empty());
+
+    let listener = listeners.pop().unwrap();
+    tokio_uring::start(async move {
+        let (tx, rx) = tokio::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+        let (rx, _) = rx;
+
+        tx.write(b"test".as_slice()).await.0.unwrap();
+        let (res, buf) = rx.read(vec![0; 4]).await;
+        res.unwrap();
+        assert_eq!(buf, b"test");
+    });
+}
+
+#[test]
+fn adopt_unix_listener() {
+    if env::var_os(CHILD_VAR).is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("activated.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let path = path.to_str().unwrap().to_string();
+        run_child("adopt_unix_listener", &listener, &[("TEST_PATH", path)]);
+        return;
+    }
+
+    set_listen_pid();
+    let path = env::var("TEST_PATH").unwrap();
+
+    let mut listeners = UnixListener::from_listen_fds().unwrap();
+    assert_eq!(listeners.len(), 1);
+
+    let listener = listeners.pop().unwrap();
+    tokio_uring::start(async move {
+        let tx = tokio_uring::net::UnixStream::connect(&path).await.unwrap();
+        let rx = listener.accept().await.unwrap();
+
+        tx.write(b"test".as_slice()).await.0.unwrap();
+        let (res, buf) = rx.read(vec![0; 4]).await;
+        res.unwrap();
+        assert_eq!(buf, b"test");
+    });
+}
+
+#[test]
+fn reject_mismatched_socket() {
+    if env::var_os(CHILD_VAR).is_none() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        run_child("reject_mismatched_socket", &socket, &[]);
+        return;
+    }
+
+    set_listen_pid();
+    let err = TcpListener::from_listen_fds().err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // The descriptor was closed
+    assert_eq!(unsafe { libc::fcntl(3, libc::F_GETFD) }, -1);
+}
+
+#[test]
+fn ignore_other_pid() {
+    if env::var_os(CHILD_VAR).is_none() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        run_child("ignore_other_pid", &listener, &[("LISTEN_PID", "1".into())]);
+        return;
+    }
+
+    assert!(TcpListener::from_listen_fds().unwrap().is_empty());
+    assert!(env::var_os("LISTEN_PID").is_none());
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+/// `IORING_OP_NOP`
+const IORING_OP_NOP: u8 = 0;
+/// `IORING_OP_TIMEOUT`
+const IORING_OP_TIMEOUT: u8 = 11;
+
+#[test]
+fn records_latencies_by_opcode() {
+    tokio_uring::start(async {
+        tokio_uring::metrics::reset_op_latencies();
+
+        for _ in 0..10 {
+            tokio_uring::task::yield_now().await;
+        }
+        tokio_uring::time::sleep(Duration::from_millis(20)).await;
+
+        let nops = tokio_uring::metrics::op_latency(IORING_OP_NOP).unwrap();
+        assert_eq!(nops.count(), 10);
+        assert!(nops.min() <= nops.mean() && nops.mean() <= nops.max());
+        assert_eq!(nops.buckets().map(|(_, n)| n).sum::<u64>(), 10);
+
+        // The kernel completes the timeout once it expired
+        let timeouts = tokio_uring::metrics::op_latency(IORING_OP_TIMEOUT).unwrap();
+        assert_eq!(timeouts.count(), 1);
+        assert!(timeouts.min() >= Duration::from_millis(20));
+        assert_eq!(timeouts.percentile(0.99), timeouts.max());
+
+        let opcodes: Vec<u8> = tokio_uring::metrics::op_latencies()
+            .into_iter()
+            .map(|(kind, _)| kind.opcode())
+            .collect();
+        assert_eq!(opcodes, [IORING_OP_NOP, IORING_OP_TIMEOUT]);
+    });
+}
+
+#[test]
+fn reset_clears_latencies() {
+    tokio_uring::start(async {
+        tokio_uring::task::yield_now().await;
+        assert!(tokio_uring::metrics::op_latency(IORING_OP_NOP).is_some());
+
+        tokio_uring::metrics::reset_op_latencies();
+        assert!(tokio_uring::metrics::op_latencies().is_empty());
+    });
+}
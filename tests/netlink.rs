@@ -0,0 +1,119 @@
+use tokio_uring::net::{NetlinkMessages, NetlinkSocket};
+
+fn message(message_type: u16, flags: u16, sequence: u32, payload: &[u8]) -> Vec<u8> {
+    let mut msg = Vec::new();
+    msg.extend_from_slice(&(16 + payload.len() as u32).to_ne_bytes());
+    msg.extend_from_slice(&message_type.to_ne_bytes());
+    msg.extend_from_slice(&flags.to_ne_bytes());
+    msg.extend_from_slice(&sequence.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes());
+    msg.extend_from_slice(payload);
+    msg
+}
+
+#[test]
+fn framing_skips_padding() {
+    let mut buf = message(1, 2, 3, b"abcde");
+    buf.extend_from_slice(&[0; 3]);
+    buf.extend_from_slice(&message(4, 5, 6, b"xy"));
+
+    let msgs: Vec<_> = NetlinkMessages::new(&buf)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(msgs.len(), 2);
+    assert_eq!(msgs[0].message_type(), 1);
+    assert_eq!(msgs[0].flags(), 2);
+    assert_eq!(msgs[0].sequence(), 3);
+    assert_eq!(msgs[0].payload(), b"abcde");
+    assert_eq!(msgs[1].message_type(), 4);
+    assert_eq!(msgs[1].payload(), b"xy");
+}
+
+#[test]
+fn framing_rejects_bad_length() {
+    let mut buf = message(1, 0, 0, b"abcd");
+    buf.truncate(18);
+
+    let mut msgs = NetlinkMessages::new(&buf);
+    assert!(msgs.next().unwrap().is_err());
+    assert!(msgs.next().is_none());
+}
+
+#[test]
+fn error_message() {
+    let buf = message(
+        libc::NLMSG_ERROR as u16,
+        0,
+        0,
+        &(-libc::EPERM).to_ne_bytes(),
+    );
+    let msg = NetlinkMessages::new(&buf).next().unwrap().unwrap();
+    assert_eq!(msg.error().unwrap().raw_os_error(), Some(libc::EPERM));
+
+    let buf = message(libc::NLMSG_ERROR as u16, 0, 0, &0i32.to_ne_bytes());
+    let msg = NetlinkMessages::new(&buf).next().unwrap().unwrap();
+    assert!(msg.error().is_none());
+}
+
+#[test]
+fn dump_links() {
+    tokio_uring::start(async {
+        let socket = NetlinkSocket::new(libc::NETLINK_ROUTE).unwrap();
+        socket.bind(0, 0).unwrap();
+
+        let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+        let request = message(
+            libc::RTM_GETLINK,
+            flags,
+            42,
+            &[libc::AF_UNSPEC as u8, 0, 0, 0],
+        );
+        let (res, _) = socket.send(request).await;
+        res.unwrap();
+
+        let mut links = 0;
+        let mut buf = vec![0; 32 * 1024];
+        'dump: loop {
+            let (res, b) = socket.recv_from(buf).await;
+            let (n, pid) = res.unwrap();
+            assert_eq!(pid, 0);
+
+            for msg in NetlinkMessages::new(&b[..n]) {
+                let msg = msg.unwrap();
+                assert_eq!(msg.sequence(), 42);
+                assert!(msg.error().is_none());
+
+                match msg.message_type() {
+                    libc::RTM_NEWLINK => links += 1,
+                    t if t == libc::NLMSG_DONE as u16 => break 'dump,
+                    t => panic!("unexpected message type {}", t),
+                }
+            }
+
+            buf = b;
+        }
+
+        // At least the loopback interface
+        assert!(links >= 1);
+    });
+}
+
+#[test]
+fn truncated_datagram() {
+    tokio_uring::start(async {
+        let socket = NetlinkSocket::new(libc::NETLINK_ROUTE).unwrap();
+
+        let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+        let request = message(
+            libc::RTM_GETLINK,
+            flags,
+            1,
+            &[libc::AF_UNSPEC as u8, 0, 0, 0],
+        );
+        let (res, _) = socket.send(request).await;
+        res.unwrap();
+
+        let (res, _) = socket.recv(vec![0; 8]).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    });
+}
@@ -0,0 +1,141 @@
+// The metrics record the operations in flight in tables of their own
+#![cfg(not(any(feature = "metrics", feature = "bench-internals")))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::future::Future;
+use std::io::Write;
+use std::pin::Pin;
+use std::task::Poll;
+
+use tempfile::NamedTempFile;
+use tokio_uring::buf::FixedBufRegistry;
+use tokio_uring::fs::File;
+
+/// Counts the allocations of the current thread, while enabled.
+struct Counting;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = COUNTING.try_with(|counting| {
+            if counting.get() {
+                ALLOCATIONS.with(|n| n.set(n.get() + 1));
+            }
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Runs `f`, returning its output and the number of allocations it made.
+async fn allocations<F: Future>(f: F) -> (F::Output, usize) {
+    ALLOCATIONS.with(|n| n.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    let output = f.await;
+    COUNTING.with(|counting| counting.set(false));
+    (output, ALLOCATIONS.with(|n| n.get()))
+}
+
+fn tempfile() -> NamedTempFile {
+    let mut tempfile = NamedTempFile::new().unwrap();
+    tempfile.write_all(&[1; 4096]).unwrap();
+    tempfile
+}
+
+#[test]
+fn steady_state_reads_and_writes() {
+    let tempfile = tempfile();
+
+    tokio_uring::builder()
+        .preallocate_ops(16)
+        .start(async {
+            let registry = FixedBufRegistry::new((0..2).map(|_| vec![0; 4096]));
+            registry.register().unwrap();
+            let file = tokio_uring::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(tempfile.path())
+                .await
+                .unwrap();
+
+            let fixed = registry.check_out(0).unwrap();
+            let buf = Vec::with_capacity(4096);
+
+            let (_, allocated) = allocations(async {
+                let mut fixed = fixed;
+                let mut buf = buf;
+
+                for _ in 0..100 {
+                    let (res, b) = file.read_fixed_at(fixed, 0).await;
+                    assert_eq!(res.unwrap(), 4096);
+                    let (res, b) = file.write_fixed_at(b, 0).await;
+                    assert_eq!(res.unwrap(), 4096);
+                    fixed = b;
+
+                    buf.clear();
+                    let (read, written) =
+                        tokio::join!(file.read_at(buf, 0), file.write_at(&b"steady"[..], 4096));
+                    assert_eq!(read.0.unwrap(), 4096);
+                    assert_eq!(written.0.unwrap(), 6);
+                    buf = read.1;
+
+                    file.sync_data().await.unwrap();
+                }
+            })
+            .await;
+
+            assert_eq!(allocated, 0);
+        })
+        .unwrap();
+}
+
+#[test]
+fn preallocated_ops_in_flight() {
+    let tempfile = tempfile();
+
+    for (preallocated, expect_allocations) in [(0, true), (256, false)] {
+        tokio_uring::builder()
+            .preallocate_ops(preallocated)
+            .start(async {
+                let file = File::open(tempfile.path()).await.unwrap();
+
+                // More than the operations the runtime has room for by default
+                let mut reads: Vec<_> = (0..200)
+                    .map(|_| file.read_at(Vec::with_capacity(16), 0))
+                    .collect();
+                let mut done = vec![false; reads.len()];
+
+                let (_, allocated) = allocations(std::future::poll_fn(|cx| {
+                    for (read, done) in reads.iter_mut().zip(&mut done) {
+                        if !*done {
+                            if let Poll::Ready((res, _)) = Pin::new(read).poll(cx) {
+                                assert_eq!(res.unwrap(), 16);
+                                *done = true;
+                            }
+                        }
+                    }
+
+                    if done.iter().all(|&done| done) {
+                        Poll::Ready(())
+                    } else {
+                        Poll::Pending
+                    }
+                }))
+                .await;
+
+                assert_eq!(allocated > 0, expect_allocations);
+            })
+            .unwrap();
+    }
+}
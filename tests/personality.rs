@@ -0,0 +1,67 @@
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+
+use tokio_uring::fs::File;
+use tokio_uring::personality::{with_personality, Personality};
+
+/// Sets the filesystem user id of the current thread, which clears the
+/// filesystem capabilities when leaving root.
+fn set_fsuid(uid: libc::uid_t) {
+    unsafe { libc::setfsuid(uid) };
+}
+
+#[test]
+fn open_with_personality() {
+    if unsafe { libc::geteuid() } != 0 {
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("secret");
+    std::fs::write(&path, b"key").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    // On its own thread, for the credentials not to leak to other tests
+    std::thread::spawn(move || {
+        tokio_uring::start(async {
+            let root = Personality::register().unwrap();
+
+            set_fsuid(65534);
+
+            let err = File::open(&path).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+            let file = with_personality(root.id(), File::open(&path))
+                .await
+                .unwrap();
+            let (res, buf) = file.read_at(vec![0; 3], 0).await;
+            assert_eq!(&buf[..res.unwrap()], b"key");
+            file.close().await.unwrap();
+
+            // Only the operations of the wrapped future use the personality
+            let err = File::open(&path).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+            set_fsuid(0);
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn unregistered_personality_fails() {
+    tokio_uring::start(async {
+        let id = Personality::register().unwrap().id();
+
+        let err = with_personality(id, File::open("/dev/null"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    });
+}
+
+#[test]
+fn register_outside_runtime_fails() {
+    assert!(Personality::register().is_err());
+}
@@ -0,0 +1,133 @@
+use std::{
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd},
+    process::{Command, Stdio},
+};
+
+use tokio_uring::pipe::{pipe, PipeOptions, PipeRead};
+
+fn fd_flags(fd: &impl AsRawFd) -> (libc::c_int, libc::c_int) {
+    unsafe {
+        (
+            libc::fcntl(fd.as_raw_fd(), libc::F_GETFL),
+            libc::fcntl(fd.as_raw_fd(), libc::F_GETFD),
+        )
+    }
+}
+
+#[test]
+fn read_write() {
+    tokio_uring::start(async {
+        let (rx, tx) = pipe().unwrap();
+
+        let (res, _) = tx.write(b"hello".as_slice()).await;
+        assert_eq!(res.unwrap(), 5);
+
+        let (res, buf) = rx.read(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
+
+#[test]
+fn read_waits_for_writer() {
+    tokio_uring::start(async {
+        let (rx, tx) = pipe().unwrap();
+
+        let reader = tokio_uring::spawn(async move {
+            let (res, buf) = rx.read(vec![0; 32]).await;
+            buf[..res.unwrap()].to_vec()
+        });
+
+        tokio_uring::task::yield_now().await;
+        tx.write(b"later".as_slice()).await.0.unwrap();
+
+        assert_eq!(reader.await.unwrap(), b"later");
+    });
+}
+
+#[test]
+fn eof_after_close() {
+    tokio_uring::start(async {
+        let (rx, tx) = pipe().unwrap();
+        tx.close().await.unwrap();
+
+        let (res, _) = rx.read(vec![0; 32]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn read_to_end_grows_buffer() {
+    tokio_uring::start(async {
+        let (rx, tx) = pipe().unwrap();
+
+        let writer = tokio_uring::spawn(async move {
+            for i in 0..64u8 {
+                tx.write(vec![i; 1024]).await.0.unwrap();
+            }
+        });
+
+        let (res, buf) = rx.read_to_end(Vec::with_capacity(10)).await;
+        assert_eq!(res.unwrap(), 64 * 1024);
+        assert!(buf.capacity() >= 64 * 1024);
+        for (i, chunk) in buf.chunks(1024).enumerate() {
+            assert!(chunk.iter().all(|&b| b == i as u8));
+        }
+        writer.await.unwrap();
+    });
+}
+
+#[test]
+fn nonblocking_waits() {
+    tokio_uring::start(async {
+        let (rx, tx) = PipeOptions::new().nonblocking(true).pipe().unwrap();
+        assert_ne!(fd_flags(&rx).0 & libc::O_NONBLOCK, 0);
+
+        let reader = tokio_uring::spawn(async move {
+            let (res, buf) = rx.read(vec![0; 32]).await;
+            buf[..res.unwrap()].to_vec()
+        });
+
+        tokio_uring::task::yield_now().await;
+        tx.write(b"ready".as_slice()).await.0.unwrap();
+
+        assert_eq!(reader.await.unwrap(), b"ready");
+    });
+}
+
+#[test]
+fn options_flags() {
+    tokio_uring::start(async {
+        let (rx, tx) = pipe().unwrap();
+        assert_eq!(fd_flags(&rx).0 & libc::O_NONBLOCK, 0);
+        assert_ne!(fd_flags(&tx).1 & libc::FD_CLOEXEC, 0);
+
+        let (rx, _tx) = PipeOptions::new().cloexec(false).pipe().unwrap();
+        assert_eq!(fd_flags(&rx).1 & libc::FD_CLOEXEC, 0);
+    });
+}
+
+#[test]
+fn child_stdout() {
+    let mut child = Command::new("echo")
+        .arg("from child")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+
+    tokio_uring::start(async {
+        let rx = unsafe { PipeRead::from_raw_fd(stdout.into_raw_fd()) };
+
+        let mut out = Vec::new();
+        loop {
+            let (res, buf) = rx.read(vec![0; 64]).await;
+            match res.unwrap() {
+                0 => break,
+                n => out.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(out, b"from child\n");
+    });
+
+    assert!(child.wait().unwrap().success());
+}
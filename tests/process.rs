@@ -0,0 +1,67 @@
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use tokio_uring::process;
+
+#[test]
+fn wait_exit_code() {
+    tokio_uring::start(async {
+        let mut child = process::spawn(Command::new("sh").args(["-c", "exit 3"])).unwrap();
+        let status = child.wait().await.unwrap();
+        assert_eq!(status.code(), Some(3));
+
+        // The status is kept once reaped
+        assert_eq!(child.wait().await.unwrap(), status);
+        assert_eq!(child.try_wait().unwrap(), Some(status));
+    });
+}
+
+#[test]
+fn wait_killed() {
+    tokio_uring::start(async {
+        let mut child = process::spawn(Command::new("sleep").arg("60")).unwrap();
+        assert_eq!(child.try_wait().unwrap(), None);
+
+        child.kill().unwrap();
+        let status = child.wait().await.unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+    });
+}
+
+#[test]
+fn dropped_wait_keeps_status() {
+    tokio_uring::start(async {
+        let mut child = process::spawn(Command::new("sleep").arg("0.1")).unwrap();
+
+        let res = tokio::time::timeout(Duration::from_millis(10), child.wait()).await;
+        assert!(res.is_err());
+
+        assert!(child.wait().await.unwrap().success());
+    });
+}
+
+#[test]
+fn piped_stdio() {
+    tokio_uring::start(async {
+        let mut child = process::spawn(
+            Command::new("cat")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped()),
+        )
+        .unwrap();
+
+        let stdin = child.take_stdin().unwrap();
+        let stdout = child.take_stdout().unwrap();
+        assert!(child.take_stderr().is_none());
+
+        let (res, _) = stdin.write(b"hello".as_slice()).await;
+        res.unwrap();
+        drop(stdin);
+
+        let (res, buf) = stdout.read(vec![0; 16]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+
+        assert!(child.wait().await.unwrap().success());
+    });
+}
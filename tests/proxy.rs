@@ -0,0 +1,230 @@
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener as StdListener, TcpStream as StdStream};
+use std::thread;
+use std::time::Duration;
+
+use tokio_uring::net::{connect_via_proxy, Proxy, TcpStream};
+use tokio_uring::proxy::pipe_between;
+
+/// Connects the proxy to a client and to an upstream server, returning the
+/// client, the server and both ends of the proxy.
+async fn connections() -> (StdStream, StdStream, TcpStream, TcpStream) {
+    let (client, downstream) = connection().await;
+    let (server, upstream) = connection().await;
+    (client, server, downstream, upstream)
+}
+
+async fn connection() -> (StdStream, TcpStream) {
+    let listener = StdListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap())
+        .await
+        .unwrap();
+    let (peer, _) = listener.accept().unwrap();
+    (peer, stream)
+}
+
+#[test]
+fn forwards_both_ways_with_half_close() {
+    tokio_uring::start(async {
+        let (mut client, mut server, downstream, upstream) = connections().await;
+
+        let request = vec![1; 1024 * 1024];
+        let peers = thread::spawn(move || {
+            let client = thread::spawn(move || {
+                client.write_all(&request).unwrap();
+                client.shutdown(Shutdown::Write).unwrap();
+
+                let mut response = Vec::new();
+                client.read_to_end(&mut response).unwrap();
+                response
+            });
+
+            // The request ends with the half-close of the client
+            let mut request = Vec::new();
+            server.read_to_end(&mut request).unwrap();
+            assert_eq!(request.len(), 1024 * 1024);
+
+            server.write_all(b"response").unwrap();
+            drop(server);
+
+            client.join().unwrap()
+        });
+
+        let (sent, received) = pipe_between(&downstream, &upstream).await.unwrap();
+        assert_eq!(sent, 1024 * 1024);
+        assert_eq!(received, 8);
+
+        let response = tokio::task::spawn_blocking(move || peers.join().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response, b"response");
+    });
+}
+
+#[test]
+fn dropping_cancels_operations() {
+    tokio_uring::start(async {
+        let (mut client, _server, downstream, upstream) = connections().await;
+
+        // Idle connections, the proxy waits for data
+        let res = tokio::time::timeout(
+            Duration::from_millis(50),
+            pipe_between(&downstream, &upstream),
+        )
+        .await;
+        assert!(res.is_err());
+
+        // The connection is closed once dropped, which in-flight operations
+        // would prevent
+        drop(downstream);
+        tokio_uring::task::yield_now().await;
+
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(client.read(&mut buf).unwrap(), 0);
+    });
+}
+
+/// Runs `serve` on the connection of a proxy server, on a thread of its own.
+fn proxy_server<F>(serve: F) -> (std::net::SocketAddr, thread::JoinHandle<()>)
+where
+    F: FnOnce(StdStream) + Send + 'static,
+{
+    let listener = StdListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || serve(listener.accept().unwrap().0));
+    (addr, server)
+}
+
+fn read_bytes(stream: &mut StdStream, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf).unwrap();
+    buf
+}
+
+fn read_request(stream: &mut StdStream) -> String {
+    let mut request = Vec::new();
+    while !request.ends_with(b"\r\n\r\n") {
+        request.extend(read_bytes(stream, 1));
+    }
+    String::from_utf8(request).unwrap()
+}
+
+#[test]
+fn socks5_with_credentials() {
+    let (addr, server) = proxy_server(|mut stream| {
+        assert_eq!(read_bytes(&mut stream, 4), [5, 2, 0, 2]);
+        stream.write_all(&[5, 2]).unwrap();
+
+        assert_eq!(read_bytes(&mut stream, 13), b"\x01\x04user\x06secret");
+        stream.write_all(&[1, 0]).unwrap();
+
+        // The host name is resolved by the proxy
+        assert_eq!(read_bytes(&mut stream, 5), [5, 1, 0, 3, 11]);
+        assert_eq!(read_bytes(&mut stream, 13), b"example.com\x01\xbb");
+        stream
+            .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0x1f, 0x90])
+            .unwrap();
+
+        stream.write_all(b"hello").unwrap();
+        assert_eq!(read_bytes(&mut stream, 4), b"ping");
+    });
+
+    tokio_uring::start(async {
+        let mut proxy = Proxy::socks5(addr);
+        proxy.credentials("user", "secret");
+        let stream = connect_via_proxy(&proxy, "example.com:443").await.unwrap();
+
+        let (res, buf) = stream.read(vec![0; 5]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+        let (res, _) = stream.write(b"ping".to_vec()).await;
+        res.unwrap();
+    });
+
+    server.join().unwrap();
+}
+
+#[test]
+fn socks5_connection_refused() {
+    let (addr, server) = proxy_server(|mut stream| {
+        assert_eq!(read_bytes(&mut stream, 3), [5, 1, 0]);
+        stream.write_all(&[5, 0]).unwrap();
+
+        assert_eq!(
+            read_bytes(&mut stream, 10),
+            [5, 1, 0, 1, 192, 0, 2, 1, 0, 80]
+        );
+        stream.write_all(&[5, 5, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+    });
+
+    tokio_uring::start(async {
+        let proxy = Proxy::socks5(addr);
+        let err = connect_via_proxy(&proxy, "192.0.2.1:80")
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::ECONNREFUSED));
+    });
+
+    server.join().unwrap();
+}
+
+#[test]
+fn http_connect_keeps_tunneled_data() {
+    let (addr, server) = proxy_server(|mut stream| {
+        let request = read_request(&mut stream);
+        assert!(request.starts_with("CONNECT [2001:db8::1]:25 HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nProxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"));
+
+        // The greeting of the target arrives with the response
+        stream
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n220 ready\r\n")
+            .unwrap();
+    });
+
+    tokio_uring::start(async {
+        let mut proxy = Proxy::http(addr);
+        proxy.credentials("user", "secret");
+        let stream = connect_via_proxy(&proxy, "[2001:db8::1]:25").await.unwrap();
+
+        let (res, buf) = stream.read(vec![0; 64]).await;
+        assert_eq!(&buf[..res.unwrap()], b"220 ready\r\n");
+    });
+
+    server.join().unwrap();
+}
+
+#[test]
+fn http_proxy_authentication_required() {
+    let (addr, server) = proxy_server(|mut stream| {
+        read_request(&mut stream);
+        stream
+            .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+    });
+
+    tokio_uring::start(async {
+        let proxy = Proxy::http(addr);
+        let err = connect_via_proxy(&proxy, "example.com:80")
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    });
+
+    server.join().unwrap();
+}
+
+#[test]
+fn invalid_target() {
+    tokio_uring::start(async {
+        let proxy = Proxy::socks5("127.0.0.1:1080".parse().unwrap());
+        let err = connect_via_proxy(&proxy, "example.com")
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    });
+}
@@ -0,0 +1,123 @@
+use std::io::Write;
+
+use tokio_uring::fs::File;
+use tokio_uring::io::duplex;
+use tokio_uring::record::{Event, Recorder, Recording, Replay};
+
+/// Client of a line-based echo protocol, run against a live peer and
+/// against replays.
+async fn ping<R, W, RF, WF>(read: R, write: W) -> Vec<u8>
+where
+    R: Fn(Vec<u8>) -> RF,
+    W: Fn(Vec<u8>) -> WF,
+    RF: std::future::Future<Output = tokio_uring::BufResult<usize, Vec<u8>>>,
+    WF: std::future::Future<Output = tokio_uring::BufResult<usize, Vec<u8>>>,
+{
+    let (res, _) = write(b"PING\n".to_vec()).await;
+    assert_eq!(res.unwrap(), 5);
+
+    let mut response = Vec::new();
+    while !response.ends_with(b"\n") {
+        let (res, buf) = read(vec![0; 3]).await;
+        let n = res.unwrap();
+        assert!(n > 0);
+        response.extend_from_slice(&buf[..n]);
+    }
+    response
+}
+
+#[test]
+fn record_and_replay_stream() {
+    let bytes = tokio_uring::start(async {
+        let (client, server) = duplex(64);
+        let client = Recorder::new(client);
+
+        let server = tokio_uring::spawn(async move {
+            let (res, _) = server.read(vec![0; 5]).await;
+            assert_eq!(res.unwrap(), 5);
+            let (res, _) = server.write(b"PONG\n".to_vec()).await;
+            res.unwrap();
+        });
+
+        let response = ping(|buf| client.read(buf), |buf| client.write(buf)).await;
+        assert_eq!(response, b"PONG\n");
+        server.await.unwrap();
+
+        // The response was read in two parts
+        let events = client.recording().events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            Event::Write {
+                offset: None,
+                data: b"PING\n".to_vec(),
+                result: Ok(5)
+            }
+        );
+        client.recording().to_bytes()
+    });
+
+    tokio_uring::start(async {
+        let recording = Recording::from_bytes(&bytes).unwrap();
+        let replay = Replay::new(&recording);
+
+        let response = ping(|buf| replay.read(buf), |buf| replay.write(buf)).await;
+        assert_eq!(response, b"PONG\n");
+        assert!(replay.is_done());
+    });
+}
+
+#[test]
+fn record_and_replay_file() {
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+
+    let recording = Recording::new();
+    tokio_uring::start(async {
+        let file = File::open(tempfile.path()).await.unwrap();
+        let file = Recorder::with_recording(file, recording.clone());
+
+        let (res, buf) = file.read_at(vec![0; 5], 6).await;
+        assert_eq!(&buf[..res.unwrap()], b"world");
+
+        // Read-only
+        let (res, _) = file.write_at(b"hi".to_vec(), 0).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBADF));
+    });
+
+    tokio_uring::start(async {
+        let replay = Replay::new(&recording);
+
+        let (res, buf) = replay.read_at(vec![0; 5], 6).await;
+        assert_eq!(&buf[..res.unwrap()], b"world");
+        let (res, _) = replay.write_at(b"hi".to_vec(), 0).await;
+        assert_eq!(res.unwrap_err().raw_os_error(), Some(libc::EBADF));
+        assert!(replay.is_done());
+    });
+
+    let bytes = recording.to_bytes();
+    let decoded = Recording::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.events(), recording.events());
+}
+
+#[test]
+#[should_panic(expected = "replay diverged")]
+fn replay_diverged() {
+    let recording = Recording::new();
+    recording.push(Event::Write {
+        offset: None,
+        data: b"PING\n".to_vec(),
+        result: Ok(5),
+    });
+
+    tokio_uring::start(async {
+        let replay = Replay::new(&recording);
+        let _ = replay.write(b"QUIT\n".to_vec()).await;
+    });
+}
+
+#[test]
+fn invalid_recording() {
+    let err = Recording::from_bytes(&[1, 0, 5]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -1,4 +1,6 @@
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
+use tokio_uring::{Backpressure, Features, WaitStrategy};
 
 #[test]
 fn use_tokio_types_from_runtime() {
@@ -34,3 +36,761 @@ fn spawn_a_task() {
         assert_eq!(2, *cell.borrow());
     });
 }
+
+#[test]
+fn yield_now_lets_io_progress() {
+    use std::cell::Cell;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello").unwrap();
+
+    tokio_uring::start(async {
+        let done = Rc::new(Cell::new(false));
+        let d = done.clone();
+        let path = tempfile.path().to_owned();
+
+        tokio_uring::spawn(async move {
+            let file = tokio_uring::fs::File::open(path).await.unwrap();
+            let (res, buf) = file.read_at(vec![0; 16], 0).await;
+            assert_eq!(&buf[..res.unwrap()], b"hello");
+            d.set(true);
+        });
+
+        // Spin without any other await point
+        let mut yields = 0;
+        while !done.get() {
+            tokio_uring::task::yield_now().await;
+            yields += 1;
+            assert!(yields < 10_000);
+        }
+    });
+}
+
+#[test]
+fn shutdown_cancels_pending_ops() {
+    tokio_uring::start(async {
+        let listener = tokio_uring::net::TcpListener::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+
+        // Never completes on its own
+        tokio_uring::spawn(async move {
+            let _ = listener.accept().await;
+        });
+
+        tokio_uring::task::yield_now().await;
+    });
+}
+
+fn thread_cpus() -> Vec<usize> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    assert_eq!(unsafe { libc::sched_getaffinity(0, size, &mut set) }, 0);
+    (0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect()
+}
+
+#[test]
+fn builder_pins_thread() {
+    std::thread::spawn(|| {
+        let before = thread_cpus();
+        let cpu = *before.last().unwrap();
+
+        let inside = tokio_uring::builder()
+            .thread_cpus([cpu])
+            .start(async { thread_cpus() })
+            .unwrap();
+        assert_eq!(inside, [cpu]);
+
+        // Restored once the runtime exits
+        assert_eq!(thread_cpus(), before);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn builder_rejects_offline_cpus() {
+    std::thread::spawn(|| {
+        let before = thread_cpus();
+
+        let err = tokio_uring::builder()
+            .thread_cpus([libc::CPU_SETSIZE as usize - 1])
+            .start(async {})
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+        assert_eq!(thread_cpus(), before);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn builder_numa_node() {
+    let res = tokio_uring::builder().numa_node(0).start(async {
+        let (rx, tx) = tokio_uring::pipe::pipe().unwrap();
+        tx.write(b"hello".as_slice()).await.0.unwrap();
+        let (res, buf) = rx.read(vec![0; 5]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+
+    match res {
+        Ok(()) => {}
+        // Kernel built without NUMA support
+        Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => {}
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn builder_sqpoll() {
+    let res = tokio_uring::builder()
+        .sqpoll(Duration::from_millis(10))
+        .start(async {
+            let (rx, tx) = tokio_uring::pipe::pipe().unwrap();
+            for _ in 0..300 {
+                tx.write(b"hello".as_slice()).await.0.unwrap();
+                let (res, buf) = rx.read(vec![0; 5]).await;
+                assert_eq!(&buf[..res.unwrap()], b"hello");
+            }
+        });
+
+    match res {
+        Ok(()) => {}
+        // SQPOLL requires privileges on older kernels
+        Err(e) if e.raw_os_error() == Some(libc::EPERM) => {}
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn builder_sqpoll_cpu_requires_sqpoll() {
+    let err = tokio_uring::builder()
+        .sqpoll_cpu(0)
+        .start(async {})
+        .unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+}
+
+/// Runs a runtime on its own thread until `stop` is sent, returning a handle
+/// to it.
+fn runtime_thread() -> (
+    tokio_uring::Handle,
+    std::sync::mpsc::Sender<()>,
+    std::thread::JoinHandle<()>,
+) {
+    let (handle_tx, handle_rx) = std::sync::mpsc::channel();
+    let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+
+    let thread = std::thread::spawn(move || {
+        tokio_uring::start(async move {
+            handle_tx.send(tokio_uring::Handle::current()).unwrap();
+
+            // Wait for the stop signal off the runtime thread
+            tokio::task::spawn_blocking(move || stop_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        })
+    });
+
+    (handle_rx.recv().unwrap(), stop_tx, thread)
+}
+
+#[test]
+fn handle_spawns_from_other_threads() {
+    let (handle, stop, runtime) = runtime_thread();
+    let runtime_id = runtime.thread().id();
+
+    let workers: Vec<_> = (0..4)
+        .map(|i| {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                let join = handle.spawn(async move { (i, std::thread::current().id()) });
+                join.blocking_join().unwrap()
+            })
+        })
+        .collect();
+
+    for (i, worker) in workers.into_iter().enumerate() {
+        let (output, thread) = worker.join().unwrap();
+        assert_eq!(output, i);
+        assert_eq!(thread, runtime_id);
+    }
+
+    stop.send(()).unwrap();
+    runtime.join().unwrap();
+}
+
+#[test]
+fn handle_spawn_pinned_uses_uring_resources() {
+    let (handle, stop, runtime) = runtime_thread();
+
+    let join = handle.spawn_pinned(|| async {
+        let (rx, tx) = tokio_uring::pipe::pipe().unwrap();
+        let (res, _) = tx.write(&b"hello"[..]).await;
+        res.unwrap();
+
+        let (res, buf) = rx.read(vec![0; 16]).await;
+        buf[..res.unwrap()].to_vec()
+    });
+    assert_eq!(join.blocking_join().unwrap(), b"hello");
+
+    stop.send(()).unwrap();
+    runtime.join().unwrap();
+}
+
+#[test]
+fn handle_join_handle_awaited_on_runtime() {
+    tokio_uring::start(async {
+        let handle = tokio_uring::Handle::current();
+        let output = handle.spawn(async { 42 }).await.unwrap();
+        assert_eq!(output, 42);
+    });
+}
+
+#[test]
+fn handle_reports_panics() {
+    let (handle, stop, runtime) = runtime_thread();
+
+    let err = handle
+        .spawn(async { panic!("task failed") })
+        .blocking_join()
+        .unwrap_err();
+    assert!(err.is_panic());
+
+    stop.send(()).unwrap();
+    runtime.join().unwrap();
+}
+
+#[test]
+fn handle_cancels_after_shutdown() {
+    let (handle, stop, runtime) = runtime_thread();
+
+    stop.send(()).unwrap();
+    runtime.join().unwrap();
+
+    let err = handle.spawn(async {}).blocking_join().unwrap_err();
+    assert!(err.is_cancelled());
+}
+
+#[test]
+#[should_panic]
+fn handle_current_outside_runtime() {
+    tokio_uring::Handle::current();
+}
+
+#[test]
+fn nested_start_fails() {
+    tokio_uring::start(async {
+        let err = tokio_uring::builder().start(async {}).unwrap_err();
+        assert!(err.to_string().contains("within a `tokio-uring` runtime"));
+
+        // The outer runtime keeps working
+        tokio_uring::task::yield_now().await;
+    });
+}
+
+#[test]
+fn start_within_tokio_fails() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let err = rt
+        .block_on(async { tokio_uring::builder().start(async {}) })
+        .unwrap_err();
+    assert!(err.to_string().contains("within a Tokio runtime"));
+
+    // The blocking threads are within the runtime too
+    let err = rt
+        .block_on(async {
+            tokio::task::spawn_blocking(|| tokio_uring::builder().start(async {})).await
+        })
+        .unwrap()
+        .unwrap_err();
+    assert!(err.to_string().contains("within a Tokio runtime"));
+
+    // A thread of its own is not
+    let output = rt
+        .block_on(async {
+            std::thread::spawn(|| tokio_uring::builder().start(async { 1 }))
+                .join()
+                .unwrap()
+        })
+        .unwrap();
+    assert_eq!(output, 1);
+}
+
+#[test]
+#[should_panic(expected = "within a `tokio-uring` runtime")]
+fn nested_start_panics() {
+    tokio_uring::start(async {
+        tokio_uring::start(async {});
+    });
+}
+
+fn thread_cpu_time() -> Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    assert_eq!(
+        unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) },
+        0
+    );
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+/// Runs I/O on a runtime with the given wait strategy, and returns the CPU
+/// time the thread used while sleeping for 100ms.
+fn idle_cpu_time(strategy: WaitStrategy) -> Duration {
+    tokio_uring::builder()
+        .wait_strategy(strategy)
+        .start(async {
+            let (rx, tx) = tokio_uring::pipe::pipe().unwrap();
+            for _ in 0..100 {
+                let (res, _) = tx.write(&b"ping"[..]).await;
+                res.unwrap();
+                let (res, buf) = rx.read(vec![0; 4]).await;
+                assert_eq!(&buf[..res.unwrap()], b"ping");
+            }
+
+            let start = thread_cpu_time();
+            tokio_uring::time::sleep(Duration::from_millis(100)).await;
+            thread_cpu_time() - start
+        })
+        .unwrap()
+}
+
+#[test]
+fn wait_strategy_block_sleeps() {
+    assert!(idle_cpu_time(WaitStrategy::Block) < Duration::from_millis(20));
+}
+
+#[test]
+fn wait_strategy_busy_poll_spins() {
+    assert!(idle_cpu_time(WaitStrategy::BusyPoll) > Duration::from_millis(50));
+}
+
+#[test]
+fn wait_strategy_hybrid_sleeps_once_idle() {
+    let cpu = idle_cpu_time(WaitStrategy::Hybrid(Duration::from_millis(5)));
+    assert!(cpu < Duration::from_millis(50));
+}
+
+#[test]
+fn min_complete_batches_completions() {
+    tokio_uring::builder()
+        .min_complete(2, Duration::from_secs(5))
+        .start(async {
+            let start = std::time::Instant::now();
+
+            let first = async {
+                tokio_uring::time::sleep(Duration::from_millis(10)).await;
+                start.elapsed()
+            };
+            let second = async {
+                tokio_uring::time::sleep(Duration::from_millis(100)).await;
+                start.elapsed()
+            };
+            let (first, second) = tokio::join!(first, second);
+
+            // The first timer is processed along with the second one
+            assert!(first >= Duration::from_millis(100));
+            assert!(second < Duration::from_secs(5));
+        })
+        .unwrap();
+}
+
+#[test]
+fn min_complete_bounds_the_wait() {
+    tokio_uring::builder()
+        .min_complete(2, Duration::from_millis(100))
+        .start(async {
+            let start = std::time::Instant::now();
+            tokio_uring::time::sleep(Duration::from_millis(10)).await;
+
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(100));
+            assert!(elapsed < Duration::from_secs(5));
+        })
+        .unwrap();
+}
+
+#[test]
+fn builder_napi_busy_poll() {
+    let res = tokio_uring::builder()
+        .napi_busy_poll(Duration::from_micros(50), true)
+        .start(async {
+            let addr = std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let listener = tokio_uring::net::TcpListener::bind(addr).unwrap();
+
+            let (client, server) = tokio::join!(
+                tokio_uring::net::TcpStream::connect(addr),
+                listener.accept()
+            );
+            let (client, (server, _)) = (client.unwrap(), server.unwrap());
+
+            let (res, _) = client.write(&b"ping"[..]).await;
+            res.unwrap();
+            let (res, buf) = server.read(vec![0; 4]).await;
+            assert_eq!(&buf[..res.unwrap()], b"ping");
+        });
+
+    match res {
+        Ok(()) => {}
+        // Kernels older than 6.9
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {}
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn seccomp_compatible_reads_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    let data: Vec<u8> = (0..300_000).map(|i| i as u8).collect();
+    std::fs::write(&path, &data).unwrap();
+
+    let contents = tokio_uring::builder()
+        .seccomp_compatible(true)
+        .start(tokio_uring::fs::read(&path))
+        .unwrap()
+        .unwrap();
+    assert_eq!(contents, data);
+}
+
+#[test]
+fn features_of_ring() {
+    tokio_uring::start(async {
+        let features = tokio_uring::features();
+        // Kernels supporting the runtime report these
+        assert!(features.contains(Features::NODROP | Features::SUBMIT_STABLE));
+        assert!(features.contains(Features::STATX));
+        assert!(format!("{:?}", features).contains("NODROP"));
+    });
+
+    let features = tokio_uring::builder()
+        .seccomp_compatible(true)
+        .start(async { tokio_uring::features() })
+        .unwrap();
+    assert!(features.contains(Features::NODROP));
+    assert!(!features.contains(Features::STATX));
+}
+
+#[test]
+fn builder_syscalls() {
+    let mut builder = tokio_uring::builder();
+    let syscalls = builder.syscalls();
+    assert!(syscalls.contains(&"io_uring_setup"));
+    assert!(syscalls.contains(&"io_uring_enter"));
+    assert!(!syscalls.contains(&"io_uring_register"));
+    assert!(!syscalls.contains(&"openat2"));
+    assert!(!syscalls.contains(&"statx"));
+
+    builder
+        .seccomp_compatible(true)
+        .thread_cpus([0])
+        .numa_node(0)
+        .napi_busy_poll(Duration::from_micros(50), false);
+    let syscalls = builder.syscalls();
+    for syscall in [
+        "fstat",
+        "sched_setaffinity",
+        "set_mempolicy",
+        "io_uring_register",
+    ] {
+        assert!(syscalls.contains(&syscall), "missing {}", syscall);
+    }
+    assert!(syscalls.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn restrict_opcodes() {
+    // `IORING_OP_OPENAT`, `IORING_OP_READ`
+    const OPENAT: u8 = 18;
+    const READ: u8 = 22;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data");
+    std::fs::write(&path, b"hello").unwrap();
+
+    tokio_uring::builder()
+        .restrict(&[OPENAT, READ])
+        .start(async {
+            let file = tokio_uring::fs::File::open(&path).await.unwrap();
+            let (res, buf) = file.read_at(vec![0; 5], 0).await;
+            assert_eq!(&buf[..res.unwrap()], b"hello");
+
+            // `IORING_OP_WRITE` is not allowed
+            let (res, _) = file.write_at(&b"world"[..], 0).await;
+            assert_eq!(
+                res.unwrap_err().kind(),
+                std::io::ErrorKind::PermissionDenied
+            );
+
+            // Closing is always allowed
+            file.close().await.unwrap();
+        })
+        .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+}
+
+/// Writes to a pipe without an operation, which may not have room.
+fn write_pipe(tx: &tokio_uring::pipe::PipeWrite, data: &[u8]) {
+    use std::os::unix::io::AsRawFd;
+
+    let n = unsafe { libc::write(tx.as_raw_fd(), data.as_ptr().cast(), data.len()) };
+    assert_eq!(n, data.len() as isize);
+}
+
+#[test]
+fn max_ops_fail() {
+    use tokio_uring::pipe::pipe;
+
+    tokio_uring::builder()
+        .max_ops(2, Backpressure::Fail)
+        .start(async {
+            let tempfile = tempfile::NamedTempFile::new().unwrap();
+            let file = tokio_uring::fs::File::open(tempfile.path()).await.unwrap();
+            let (rx1, tx1) = pipe().unwrap();
+            let (rx2, _tx2) = pipe().unwrap();
+
+            // Both stay in flight until written to
+            let read = tokio_uring::spawn(async move { rx1.read(vec![0; 8]).await.0 });
+            tokio_uring::spawn(async move {
+                let _ = rx2.read(vec![0; 8]).await;
+            });
+            // Without an operation of its own, which would take room
+            tokio::task::yield_now().await;
+
+            let (res, buf) = file.read_at(vec![0; 8], 0).await;
+            assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::QuotaExceeded);
+            assert_eq!(buf.capacity(), 8);
+
+            let err = file.sync_all().await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::QuotaExceeded);
+
+            // Room is made once the read completed
+            write_pipe(&tx1, b"hello");
+            assert_eq!(read.await.unwrap().unwrap(), 5);
+
+            let (res, _) = file.read_at(vec![0; 8], 0).await;
+            assert_eq!(res.unwrap(), 0);
+        })
+        .unwrap();
+}
+
+#[test]
+fn max_ops_wait() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use tokio_uring::pipe::pipe;
+
+    tokio_uring::builder()
+        .max_ops(1, Backpressure::Wait)
+        .start(async {
+            let (rx1, tx1) = pipe().unwrap();
+            let (rx2, tx2) = pipe().unwrap();
+
+            let first = tokio_uring::spawn(async move { rx1.read(vec![0; 8]).await.0 });
+            tokio::task::yield_now().await;
+
+            // Waits for the first read to complete before being submitted
+            let submitted = Rc::new(Cell::new(false));
+            let second = tokio_uring::spawn({
+                let submitted = submitted.clone();
+                async move {
+                    let res = rx2.read(vec![0; 8]).await.0;
+                    submitted.set(true);
+                    res
+                }
+            });
+
+            write_pipe(&tx2, b"second");
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert!(!submitted.get());
+
+            write_pipe(&tx1, b"first");
+            assert_eq!(first.await.unwrap().unwrap(), 5);
+            assert_eq!(second.await.unwrap().unwrap(), 6);
+        })
+        .unwrap();
+}
+
+#[test]
+fn max_ops_per_fd() {
+    use std::rc::Rc;
+    use tokio_uring::pipe::pipe;
+
+    tokio_uring::builder()
+        .max_ops_per_fd(1, Backpressure::Fail)
+        .start(async {
+            let (rx1, tx1) = pipe().unwrap();
+            let (rx2, tx2) = pipe().unwrap();
+            let rx1 = Rc::new(rx1);
+
+            let read = tokio_uring::spawn({
+                let rx1 = rx1.clone();
+                async move { rx1.read(vec![0; 8]).await.0 }
+            });
+            tokio::task::yield_now().await;
+
+            let (res, buf) = rx1.read(vec![0; 8]).await;
+            assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::QuotaExceeded);
+            assert_eq!(buf.capacity(), 8);
+
+            // Other file descriptors are not affected
+            write_pipe(&tx2, b"other");
+            let (res, _) = rx2.read(vec![0; 8]).await;
+            assert_eq!(res.unwrap(), 5);
+
+            write_pipe(&tx1, b"hello");
+            assert_eq!(read.await.unwrap().unwrap(), 5);
+
+            write_pipe(&tx1, b"again");
+            let (res, _) = rx1.read(vec![0; 8]).await;
+            assert_eq!(res.unwrap(), 5);
+        })
+        .unwrap();
+}
+
+#[test]
+fn max_ops_per_fd_wait() {
+    use std::rc::Rc;
+    use tokio_uring::pipe::pipe;
+
+    tokio_uring::builder()
+        .max_ops_per_fd(1, Backpressure::Wait)
+        .start(async {
+            let (rx, tx) = pipe().unwrap();
+            let rx = Rc::new(rx);
+
+            let reads: Vec<_> = (0..3)
+                .map(|_| {
+                    let rx = rx.clone();
+                    tokio_uring::spawn(async move { rx.read(vec![0; 8]).await.0 })
+                })
+                .collect();
+
+            // Each read waits for the previous one to complete
+            for read in reads {
+                tokio::task::yield_now().await;
+                write_pipe(&tx, b"data");
+                assert_eq!(read.await.unwrap().unwrap(), 4);
+            }
+        })
+        .unwrap();
+}
+
+#[test]
+fn debug_dump() {
+    use tokio_uring::net::UnixStream;
+
+    tokio_uring::start(async {
+        let (a, b) = UnixStream::pair().unwrap();
+        let read = tokio_uring::spawn(async move {
+            let (res, _) = a.read(vec![0; 16]).await;
+            res.unwrap()
+        });
+        tokio::task::yield_now().await;
+
+        let dump = tokio_uring::debug_dump();
+        assert_eq!(dump.in_flight(), [(tokio_uring::OpKind::Read, 1)]);
+        assert_eq!(dump.unsubmitted(), 0);
+        assert_eq!(dump.waiting_for_room(), 0);
+
+        // The kernel consumed the submission
+        let (head, tail) = dump.sq_head_tail().unwrap();
+        assert_eq!(head, tail);
+        assert!(dump.to_string().contains("in flight: 1, read: 1"));
+
+        let (res, _) = b.write(b"ping".as_slice()).await;
+        res.unwrap();
+        assert_eq!(read.await.unwrap(), 4);
+
+        let dump = tokio_uring::debug_dump();
+        assert!(dump.in_flight().is_empty());
+    });
+}
+
+#[test]
+fn shutdown_now() {
+    use tokio_uring::net::UnixStream;
+
+    tokio_uring::start(async {
+        let (a, _b) = UnixStream::pair().unwrap();
+        tokio_uring::spawn(async move {
+            let _ = a.read(vec![0; 16]).await;
+        });
+        tokio::task::yield_now().await;
+
+        tokio_uring::shutdown_now();
+        assert_eq!(tokio_uring::debug_dump().unsubmitted(), 0);
+    });
+
+    // The next runtime starts afresh
+    tokio_uring::start(async {
+        let (a, b) = UnixStream::pair().unwrap();
+        let (res, _) = a.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, _) = b.read(vec![0; 4]).await;
+        assert_eq!(res.unwrap(), 4);
+        assert!(tokio_uring::debug_dump().in_flight().is_empty());
+    });
+}
+
+#[test]
+fn auto_register_files() {
+    use std::io::Write;
+    use tokio_uring::fs::File;
+    use tokio_uring::net::UnixStream;
+
+    let mut a = tempfile::NamedTempFile::new().unwrap();
+    a.write_all(b"aaaa").unwrap();
+    let mut b = tempfile::NamedTempFile::new().unwrap();
+    b.write_all(b"bbbb").unwrap();
+
+    tokio_uring::builder()
+        .auto_register_files(1)
+        .start(async {
+            let file = File::open(a.path()).await.unwrap();
+            for _ in 0..3 {
+                let (res, buf) = file.read_at(vec![0; 4], 0).await;
+                assert_eq!(&buf[..res.unwrap()], b"aaaa");
+            }
+            file.close().await.unwrap();
+
+            // The next file takes the number of the registered one
+            let file = File::open(b.path()).await.unwrap();
+            let (res, buf) = file.read_at(vec![0; 4], 0).await;
+            assert_eq!(&buf[..res.unwrap()], b"bbbb");
+
+            // Registered sockets are closed once dropped
+            let (x, y) = UnixStream::pair().unwrap();
+            for _ in 0..3 {
+                let (res, _) = x.write(b"ping".as_slice()).await;
+                res.unwrap();
+            }
+            drop(x);
+
+            let mut received = 0;
+            loop {
+                let (res, _) = y.read(vec![0; 16]).await;
+                match res.unwrap() {
+                    0 => break,
+                    n => received += n,
+                }
+            }
+            assert_eq!(received, 12);
+        })
+        .unwrap();
+}
@@ -0,0 +1,101 @@
+use std::{
+    ffi::CStr,
+    fs::File,
+    io::{Read, Write},
+    os::unix::io::FromRawFd,
+    time::Duration,
+};
+
+use tokio_uring::device::Serial;
+
+/// Opens a pseudoterminal, returning the master and the path of the slave.
+fn openpty() -> (File, String) {
+    unsafe {
+        let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY | libc::O_CLOEXEC);
+        assert!(fd >= 0);
+        assert_eq!(libc::grantpt(fd), 0);
+        assert_eq!(libc::unlockpt(fd), 0);
+
+        let mut name = [0; 64];
+        assert_eq!(libc::ptsname_r(fd, name.as_mut_ptr(), name.len()), 0);
+        let name = CStr::from_ptr(name.as_ptr()).to_str().unwrap().to_string();
+
+        (File::from_raw_fd(fd), name)
+    }
+}
+
+#[test]
+fn read_waits_for_data() {
+    let (mut master, path) = openpty();
+    let serial = Serial::open(&path).unwrap();
+    serial.set_raw().unwrap();
+
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        master.write_all(b"hello").unwrap();
+        master
+    });
+
+    tokio_uring::start(async {
+        let (res, buf) = serial.read(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+
+    writer.join().unwrap();
+}
+
+#[test]
+fn write_to_master() {
+    let (mut master, path) = openpty();
+    let serial = Serial::open(&path).unwrap();
+    serial.set_raw().unwrap();
+
+    tokio_uring::start(async {
+        let (res, _) = serial.write(b"world".as_slice()).await;
+        assert_eq!(res.unwrap(), 5);
+    });
+
+    let mut buf = [0; 5];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"world");
+}
+
+#[test]
+fn from_std_master() {
+    let (master, path) = openpty();
+    let slave = Serial::open(&path).unwrap();
+    slave.set_raw().unwrap();
+    let master = Serial::from_std(master).unwrap();
+
+    tokio_uring::start(async {
+        let (res, _) = slave.write(b"ping".as_slice()).await;
+        res.unwrap();
+
+        let (res, buf) = master.read(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+
+        master.close().await.unwrap();
+    });
+}
+
+#[test]
+fn baud_rate() {
+    let (_master, path) = openpty();
+    let serial = Serial::open(&path).unwrap();
+
+    serial.set_baud_rate(115_200).unwrap();
+    assert_eq!(serial.baud_rate().unwrap(), 115_200);
+
+    let err = serial.set_baud_rate(12345).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn termios_on_regular_file() {
+    let file = tempfile::tempfile().unwrap();
+    let serial = Serial::from_std(file).unwrap();
+    assert_eq!(
+        serial.termios().unwrap_err().raw_os_error(),
+        Some(libc::ENOTTY)
+    );
+}
@@ -0,0 +1,95 @@
+use std::{io, net::SocketAddr};
+
+use tokio_uring::net::{RecvFlags, SendFlags, SockAddr, Socket};
+
+#[test]
+fn send_msg_joins_buffers() {
+    tokio_uring::start(async {
+        let dir = tempfile::tempdir().unwrap();
+        let addr = SockAddr::unix(dir.path().join("raw.sock")).unwrap();
+
+        let rx = Socket::new(libc::AF_UNIX, libc::SOCK_DGRAM, 0).unwrap();
+        rx.bind(&addr).unwrap();
+        let tx = Socket::new(libc::AF_UNIX, libc::SOCK_DGRAM, 0).unwrap();
+
+        let bufs = vec![b"hello ".to_vec(), b"world".to_vec()];
+        let (res, bufs) = tx.send_msg(bufs, Some(&addr), SendFlags::empty()).await;
+        assert_eq!(res.unwrap(), 11);
+        assert_eq!(bufs.len(), 2);
+
+        let (res, buf) = rx.recv(vec![0; 32], RecvFlags::empty()).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello world");
+    });
+}
+
+#[test]
+fn send_msg_connected() {
+    tokio_uring::start(async {
+        let rx = Socket::new(libc::AF_INET, libc::SOCK_DGRAM, 0).unwrap();
+        rx.bind(&SockAddr::from(
+            "127.0.0.1:0".parse::<SocketAddr>().unwrap(),
+        ))
+        .unwrap();
+        let rx_addr = local_addr(&rx);
+
+        let tx = Socket::new(libc::AF_INET, libc::SOCK_DGRAM, 0).unwrap();
+        tx.connect(&rx_addr).await.unwrap();
+
+        let bufs = vec![b"ab".as_slice(), b"cd".as_slice()];
+        let (res, _) = tx.send_msg(bufs, None, SendFlags::empty()).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let (res, buf) = rx.recv_from(vec![0; 32], RecvFlags::empty()).await;
+        let (n, from) = res.unwrap();
+        assert_eq!(&buf[..n], b"abcd");
+        assert_eq!(from.as_socket(), local_addr(&tx).as_socket());
+    });
+}
+
+#[test]
+fn raw_icmp_echo() {
+    let socket = match Socket::new(libc::AF_INET, libc::SOCK_RAW, libc::IPPROTO_ICMP) {
+        Ok(socket) => socket,
+        // Requires CAP_NET_RAW
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(e) => panic!("{}", e),
+    };
+
+    tokio_uring::start(async {
+        let addr = SockAddr::from("127.0.0.1:0".parse::<SocketAddr>().unwrap());
+
+        // type, code, checksum, identifier, sequence number
+        let request = vec![8, 0, 0xf7, 0xfe, 0, 1, 0, 0];
+        let (res, _) = socket.send_to(request, &addr, SendFlags::empty()).await;
+        assert_eq!(res.unwrap(), 8);
+
+        // The request is also delivered to raw sockets, skip it
+        loop {
+            let (res, buf) = socket.recv(vec![0; 1500], RecvFlags::empty()).await;
+            let n = res.unwrap();
+            let header_len = ((buf[0] & 0x0f) * 4) as usize;
+            let icmp = &buf[header_len..n];
+
+            if icmp[0] == 0 {
+                // Echo reply with our identifier
+                assert_eq!(&icmp[4..6], &[0, 1]);
+                break;
+            }
+        }
+    });
+}
+
+fn local_addr(socket: &Socket) -> SockAddr {
+    use std::os::unix::io::AsRawFd;
+
+    unsafe {
+        SockAddr::init(|storage, len| {
+            if libc::getsockname(socket.as_raw_fd(), storage.cast(), len) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        })
+    }
+    .unwrap()
+    .1
+}
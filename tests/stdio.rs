@@ -0,0 +1,94 @@
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use tokio_uring::io::{stdin, stdout};
+
+/// Redirects `target` to `fd` until dropped.
+struct Redirect {
+    target: RawFd,
+    saved: RawFd,
+}
+
+impl Redirect {
+    fn new(fd: RawFd, target: RawFd) -> Redirect {
+        let saved = unsafe { libc::dup(target) };
+        assert!(saved >= 0);
+        assert!(unsafe { libc::dup2(fd, target) } >= 0);
+        Redirect { target, saved }
+    }
+}
+
+impl Drop for Redirect {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dup2(self.saved, self.target);
+            libc::close(self.saved);
+        }
+    }
+}
+
+fn pipe() -> (std::fs::File, std::fs::File) {
+    let mut fds = [-1; 2];
+    assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) }, 0);
+    unsafe {
+        (
+            std::fs::File::from_raw_fd(fds[0]),
+            std::fs::File::from_raw_fd(fds[1]),
+        )
+    }
+}
+
+// A single test, as it redirects the standard streams of the process
+#[test]
+fn stdin_lines_and_stdout_line_buffering() {
+    use std::io::{Read, Write};
+
+    tokio_uring::start(async {
+        let (rx, mut tx) = pipe();
+        let redirect = Redirect::new(rx.as_raw_fd(), libc::STDIN_FILENO);
+        let mut input = stdin().unwrap();
+        drop(redirect);
+
+        tx.write_all(b"hello\nworld\npartial").unwrap();
+        drop(tx);
+
+        let mut line = String::new();
+        assert_eq!(input.read_line(&mut line).await.unwrap(), 6);
+        assert_eq!(line, "hello\n");
+
+        // Reads return the buffered data first
+        let (res, buf) = input.read(vec![0; 3]).await;
+        assert_eq!(&buf[..res.unwrap()], b"wor");
+
+        line.clear();
+        input.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "ld\n");
+
+        line.clear();
+        assert_eq!(input.read_line(&mut line).await.unwrap(), 7);
+        assert_eq!(line, "partial");
+        assert_eq!(input.read_line(&mut line).await.unwrap(), 0);
+
+        let (mut rx, tx) = pipe();
+        let redirect = Redirect::new(tx.as_raw_fd(), libc::STDOUT_FILENO);
+        let mut output = stdout().unwrap();
+        drop(redirect);
+        drop(tx);
+
+        // Nothing is written until the line is complete
+        output.write_all(b"hello ").await.unwrap();
+        let flags = unsafe { libc::fcntl(rx.as_raw_fd(), libc::F_GETFL) };
+        unsafe { libc::fcntl(rx.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) };
+        let mut buf = [0; 32];
+        assert!(rx.read(&mut buf).is_err());
+
+        output.write_all(b"world\n").await.unwrap();
+        let n = rx.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello world\n");
+
+        // The rest is written on drop
+        output.write_all(b"bye").await.unwrap();
+        drop(output);
+        let n = rx.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"bye");
+    });
+}
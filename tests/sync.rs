@@ -0,0 +1,310 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::thread;
+
+use tokio_uring::sync::{mpsc, oneshot, OpsPermit, Semaphore, TryAcquireError};
+
+#[test]
+fn mpsc_send_recv() {
+    tokio_uring::start(async {
+        let (tx, mut rx) = mpsc::channel(4);
+
+        for i in 0..4 {
+            tx.try_send(i).unwrap();
+        }
+        assert!(matches!(tx.try_send(4), Err(mpsc::TrySendError::Full(4))));
+
+        for i in 0..4 {
+            assert_eq!(rx.recv().await, Some(i));
+        }
+        assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Empty));
+
+        drop(tx);
+        assert_eq!(rx.recv().await, None);
+        assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    });
+}
+
+#[test]
+fn mpsc_backpressure() {
+    tokio_uring::start(async {
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let senders: Vec<_> = (0..3)
+            .map(|task| {
+                let tx = tx.clone();
+                tokio_uring::spawn(async move {
+                    for i in 0..100 {
+                        tx.send(task * 100 + i).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = vec![];
+        while let Some(i) = rx.recv().await {
+            received.push(i);
+        }
+
+        for sender in senders {
+            sender.await.unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!(received, (0..300).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn mpsc_close() {
+    tokio_uring::start(async {
+        let (tx, mut rx) = mpsc::channel(2);
+        tx.send(1).await.unwrap();
+
+        rx.close();
+        assert!(tx.is_closed());
+        assert_eq!(tx.send(2).await, Err(mpsc::SendError(2)));
+
+        // Buffered messages are still received
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, None);
+    });
+}
+
+#[test]
+fn mpsc_waiting_sender_fails_on_drop() {
+    tokio_uring::start(async {
+        let (tx, rx) = mpsc::channel(1);
+        tx.send(1).await.unwrap();
+
+        let sender = tokio_uring::spawn(async move { tx.send(2).await });
+        tokio::task::yield_now().await;
+
+        drop(rx);
+        assert_eq!(sender.await.unwrap(), Err(mpsc::SendError(2)));
+    });
+}
+
+#[test]
+fn mpsc_remote_blocking_send() {
+    tokio_uring::start(async {
+        let (tx, mut rx) = mpsc::channel(2);
+        drop(tx);
+
+        let remote = rx.remote_sender().unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|t| {
+                let remote = remote.clone();
+                thread::spawn(move || {
+                    for i in 0..250 {
+                        remote.blocking_send(t * 250 + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(remote);
+
+        let mut received = vec![];
+        while let Some(i) = rx.recv().await {
+            received.push(i);
+        }
+
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!(received, (0..1000).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn mpsc_remote_from_other_runtime() {
+    tokio_uring::start(async {
+        let (tx, mut rx) = mpsc::channel(8);
+        let remote = rx.remote_sender().unwrap();
+
+        let other = thread::spawn(move || {
+            tokio_uring::start(async move {
+                for i in 0..100 {
+                    remote.send(i).await.unwrap();
+                }
+            })
+        });
+
+        // Local and remote senders mix
+        tx.send(1000).await.unwrap();
+        drop(tx);
+
+        let mut received = vec![];
+        while let Some(i) = rx.recv().await {
+            received.push(i);
+        }
+        other.join().unwrap();
+
+        assert_eq!(received.len(), 101);
+        assert_eq!(received[0], 1000);
+        assert_eq!(&received[1..], (0..100).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn mpsc_remote_closed() {
+    tokio_uring::start(async {
+        let (_tx, mut rx) = mpsc::channel(1);
+        let remote = rx.remote_sender().unwrap();
+
+        remote.try_send(1).unwrap();
+        assert!(matches!(
+            remote.try_send(2),
+            Err(mpsc::TrySendError::Full(2))
+        ));
+
+        drop(rx);
+        assert!(remote.is_closed());
+        assert_eq!(remote.blocking_send(3), Err(mpsc::SendError(3)));
+    });
+}
+
+#[test]
+fn oneshot_send_recv() {
+    tokio_uring::start(async {
+        let (tx, rx) = oneshot::channel();
+
+        tokio_uring::spawn(async move {
+            tx.send(42).unwrap();
+        });
+
+        assert_eq!(rx.await, Ok(42));
+    });
+}
+
+#[test]
+fn oneshot_sender_dropped() {
+    tokio_uring::start(async {
+        let (tx, mut rx) = oneshot::channel::<u32>();
+        assert_eq!(rx.try_recv(), Err(oneshot::TryRecvError::Empty));
+
+        drop(tx);
+        assert_eq!(rx.try_recv(), Err(oneshot::TryRecvError::Closed));
+        assert!(rx.await.is_err());
+    });
+}
+
+#[test]
+fn oneshot_receiver_closed() {
+    tokio_uring::start(async {
+        let (mut tx, rx) = oneshot::channel();
+
+        let waiter = tokio_uring::spawn(async move {
+            tx.closed().await;
+            tx.send(1)
+        });
+        tokio::task::yield_now().await;
+
+        drop(rx);
+        assert_eq!(waiter.await.unwrap(), Err(1));
+    });
+}
+
+#[test]
+fn semaphore_bounds_tasks() {
+    tokio_uring::start(async {
+        let semaphore = Rc::new(Semaphore::new(2));
+        let running = Rc::new(Cell::new(0));
+        let max_running = Rc::new(Cell::new(0));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let semaphore = semaphore.clone();
+                let running = running.clone();
+                let max_running = max_running.clone();
+                tokio_uring::spawn(async move {
+                    let _permit = semaphore.acquire().await.unwrap();
+                    running.set(running.get() + 1);
+                    max_running.set(max_running.get().max(running.get()));
+                    tokio::task::yield_now().await;
+                    running.set(running.get() - 1);
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(max_running.get(), 2);
+        assert_eq!(semaphore.available_permits(), 2);
+    });
+}
+
+#[test]
+fn semaphore_fifo() {
+    tokio_uring::start(async {
+        let semaphore = Rc::new(Semaphore::new(1));
+        let permit = semaphore.try_acquire().unwrap();
+
+        // A task waiting for many permits is not overtaken by later tasks
+        let many = {
+            let semaphore = semaphore.clone();
+            tokio_uring::spawn(async move {
+                let permit = semaphore.acquire_many(3).await.unwrap();
+                permit.num_permits()
+            })
+        };
+        tokio::task::yield_now().await;
+
+        semaphore.add_permits(1);
+        assert_eq!(
+            semaphore.try_acquire().err(),
+            Some(TryAcquireError::NoPermits)
+        );
+
+        semaphore.add_permits(1);
+        drop(permit);
+        assert_eq!(many.await.unwrap(), 3);
+        assert_eq!(semaphore.available_permits(), 3);
+
+        semaphore.try_acquire_many(3).unwrap().forget();
+        assert_eq!(semaphore.available_permits(), 0);
+    });
+}
+
+#[test]
+fn semaphore_close() {
+    tokio_uring::start(async {
+        let semaphore = Rc::new(Semaphore::new(0));
+
+        let waiter = {
+            let semaphore = semaphore.clone();
+            tokio_uring::spawn(async move { semaphore.acquire().await.is_err() })
+        };
+        tokio::task::yield_now().await;
+
+        semaphore.close();
+        assert!(waiter.await.unwrap());
+        assert_eq!(semaphore.try_acquire().err(), Some(TryAcquireError::Closed));
+    });
+}
+
+#[test]
+fn ops_permit_bounded_by_ring() {
+    tokio_uring::start(async {
+        let first = OpsPermit::acquire().await.unwrap();
+        let limit = first.limit();
+
+        let mut permits = vec![first];
+        for _ in 1..limit {
+            permits.push(OpsPermit::acquire().await.unwrap());
+        }
+
+        let waiter = tokio_uring::spawn(async { OpsPermit::acquire().await.map(drop) });
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(!waiter.is_finished());
+
+        permits.pop();
+        waiter.await.unwrap().unwrap();
+    });
+}
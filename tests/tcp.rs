@@ -0,0 +1,465 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio_uring::net::{KeepaliveConfig, RecvFlags, SendFlags, TcpListener, TcpOptions, TcpStream};
+
+/// Finds a local port that is currently unused.
+fn free_addr() -> SocketAddr {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    std::thread::sleep(Duration::from_millis(1));
+    addr
+}
+
+async fn connected_pair() -> (TcpStream, TcpStream) {
+    let addr = free_addr();
+    let listener = TcpListener::bind(addr).unwrap();
+    let (tx, rx) = tokio::join!(TcpStream::connect(addr), listener.accept());
+    (tx.unwrap(), rx.unwrap().0)
+}
+
+#[test]
+fn recv_waitall() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let reader = tokio_uring::spawn(async move {
+            rx.recv_with_flags(Vec::with_capacity(10), RecvFlags::WAITALL)
+                .await
+        });
+
+        for chunk in [b"hello".as_slice(), b"world".as_slice()] {
+            let (res, _) = tx.send_with_flags(chunk, SendFlags::empty()).await;
+            res.unwrap();
+            tokio_uring::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let (res, buf) = reader.await.unwrap();
+        assert_eq!(res.unwrap(), 10);
+        assert_eq!(buf, b"helloworld");
+    });
+}
+
+#[test]
+fn read_to_end() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        tokio_uring::spawn(async move {
+            let (res, _) = tx.write(vec![1; 10_000]).await;
+            assert_eq!(res.unwrap(), 10_000);
+        });
+
+        let (res, buf) = rx.read_to_end(Vec::new()).await;
+        assert_eq!(res.unwrap(), 10_000);
+        assert_eq!(buf, vec![1; 10_000]);
+    });
+}
+
+#[test]
+fn tcp_info_tracks_transfer() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let (res, _) = tx.write(vec![7; 1000]).await;
+        assert_eq!(res.unwrap(), 1000);
+        let (res, _) = rx.read(vec![0; 1000]).await;
+        assert!(res.unwrap() > 0);
+
+        let info = tx.tcp_info().unwrap();
+        assert!(info.cwnd() > 0);
+        assert!(info.snd_mss() > 0);
+        assert_eq!(info.total_retransmits(), 0);
+
+        let info = rx.tcp_info().unwrap();
+        assert!(info.bytes_received() > 0);
+    });
+}
+
+#[test]
+fn keepalive_and_user_timeout() {
+    tokio_uring::start(async {
+        let (tx, _rx) = connected_pair().await;
+        assert_eq!(tx.keepalive().unwrap(), None);
+        assert_eq!(tx.user_timeout().unwrap(), None);
+
+        let config = KeepaliveConfig::new()
+            .with_idle(Duration::from_secs(30))
+            .with_interval(Duration::from_secs(5))
+            .with_count(4);
+        tx.set_keepalive(config).unwrap();
+        assert_eq!(tx.keepalive().unwrap(), Some(config));
+
+        tx.disable_keepalive().unwrap();
+        assert_eq!(tx.keepalive().unwrap(), None);
+
+        tx.set_user_timeout(Duration::from_secs(20)).unwrap();
+        assert_eq!(tx.user_timeout().unwrap(), Some(Duration::from_secs(20)));
+        tx.set_user_timeout(Duration::ZERO).unwrap();
+        assert_eq!(tx.user_timeout().unwrap(), None);
+    });
+}
+
+#[test]
+fn connect_with_data() {
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let listener = TcpListener::bind(addr).unwrap();
+        listener.set_fastopen(16).unwrap();
+
+        // Whether or not a cookie is granted, the data reaches the listener
+        for _ in 0..2 {
+            let connect = TcpStream::connect_with_data(addr, b"hello".as_slice());
+            let ((res, _), accepted) = tokio::join!(connect, listener.accept());
+            let (tx, n) = res.unwrap();
+            assert_eq!(n, 5);
+
+            let (rx, _) = accepted.unwrap();
+            let (res, buf) = rx.read(vec![0; 5]).await;
+            assert_eq!(&buf[..res.unwrap()], b"hello");
+            drop(tx);
+        }
+    });
+}
+
+#[test]
+fn mptcp_connection() {
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let mut options = TcpOptions::new();
+        options.mptcp(true);
+
+        let listener = options.bind(addr).unwrap();
+        let mptcp = listener.is_mptcp().unwrap();
+
+        let (tx, accepted) = tokio::join!(options.connect(addr), listener.accept());
+        let (tx, (rx, _)) = (tx.unwrap(), accepted.unwrap());
+
+        // Without MPTCP support in the kernel, all sockets fall back to TCP
+        assert_eq!(tx.is_mptcp().unwrap(), mptcp);
+        assert_eq!(rx.is_mptcp().unwrap(), mptcp);
+
+        let (res, _) = tx.write(b"hello".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = rx.read(vec![0; 5]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
+
+#[test]
+fn plain_tcp_by_default() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+        assert!(!tx.is_mptcp().unwrap());
+        assert!(!rx.is_mptcp().unwrap());
+    });
+}
+
+#[test]
+fn reuseport_cpu_steering() {
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let listeners = [
+            TcpListener::bind(addr).unwrap(),
+            TcpListener::bind(addr).unwrap(),
+        ];
+
+        // Loopback connections are handled by the CPU of the connecting task
+        let cpu = unsafe { libc::sched_getcpu() } as usize;
+        let expected = &listeners[cpu % 2];
+        expected.set_reuseport_cpu_steering(2).unwrap();
+
+        for _ in 0..8 {
+            let accept = tokio_uring::time::timeout(Duration::from_secs(1), expected.accept());
+            let (tx, rx) = tokio::join!(TcpStream::connect(addr), accept);
+            let (rx, _) = rx
+                .expect("connection steered to the wrong listener")
+                .unwrap();
+            assert_eq!(rx.incoming_cpu().unwrap() % 2, cpu % 2);
+            drop(tx.unwrap());
+        }
+    });
+}
+
+#[test]
+fn reuseport_cpu_steering_empty_group() {
+    tokio_uring::start(async {
+        let listener = TcpListener::bind(free_addr()).unwrap();
+        let err = listener.set_reuseport_cpu_steering(0).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+        listener.set_incoming_cpu(0).unwrap();
+        assert_eq!(listener.incoming_cpu().unwrap(), 0);
+    });
+}
+
+#[test]
+fn recv_dontwait() {
+    tokio_uring::start(async {
+        let (_tx, rx) = connected_pair().await;
+
+        let (res, _) = rx.recv_with_flags(vec![0; 10], RecvFlags::DONTWAIT).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+    });
+}
+
+#[test]
+fn send_nosignal() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+        drop(rx);
+
+        // The first sends may succeed before the reset reaches the socket.
+        loop {
+            let (res, _) = tx.send_with_flags(vec![0; 1024], SendFlags::NOSIGNAL).await;
+
+            if let Err(e) = res {
+                assert!(matches!(
+                    e.raw_os_error(),
+                    Some(libc::EPIPE | libc::ECONNRESET)
+                ));
+                break;
+            }
+
+            tokio_uring::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+}
+
+#[test]
+fn incoming_yields_connections() {
+    use futures_core::Stream;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let listener = TcpListener::bind(addr).unwrap();
+        let mut incoming = listener.incoming();
+
+        for _ in 0..3 {
+            let next = poll_fn(|cx| Pin::new(&mut incoming).poll_next(cx));
+            let (tx, rx) = tokio::join!(TcpStream::connect(addr), next);
+            tx.unwrap();
+            rx.unwrap().unwrap();
+        }
+    });
+}
+
+#[test]
+fn serve_limits_concurrency() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let listener = TcpListener::bind(addr).unwrap();
+
+        let active = Rc::new(Cell::new(0));
+        let max_active = Rc::new(Cell::new(0));
+        let served = Rc::new(Cell::new(0));
+
+        let server = {
+            let (active, max_active, served) = (active.clone(), max_active.clone(), served.clone());
+
+            tokio_uring::spawn(async move {
+                listener
+                    .serve(2, move |stream, _| {
+                        let (active, max_active, served) =
+                            (active.clone(), max_active.clone(), served.clone());
+
+                        async move {
+                            active.set(active.get() + 1);
+                            max_active.set(max_active.get().max(active.get()));
+
+                            // Hold the connection until the client closes it
+                            let (res, _) = stream.read(vec![0; 8]).await;
+                            res.unwrap();
+
+                            active.set(active.get() - 1);
+                            served.set(served.get() + 1);
+                        }
+                    })
+                    .await
+            })
+        };
+
+        let mut clients = vec![];
+        for _ in 0..5 {
+            clients.push(TcpStream::connect(addr).await.unwrap());
+        }
+
+        tokio_uring::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(active.get(), 2);
+
+        // Closing the clients frees slots for the pending connections
+        drop(clients);
+
+        while served.get() < 5 {
+            tokio_uring::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(max_active.get(), 2);
+        server.abort();
+    });
+}
+
+/// Serves `range` of a file of 3 MiB, and returns the bytes received.
+async fn serve_range(range: std::ops::Range<u64>) -> (Vec<u8>, Vec<u8>) {
+    let data: Vec<u8> = (0..3 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+    std::fs::write(&path, &data).unwrap();
+    let file = tokio_uring::fs::File::open(&path).await.unwrap();
+
+    let (tx, rx) = connected_pair().await;
+    let reader = tokio_uring::spawn(async move {
+        let mut received = Vec::new();
+        loop {
+            let (res, buf) = rx.read(vec![0; 64 * 1024]).await;
+            let n = res.unwrap();
+            if n == 0 {
+                return received;
+            }
+            received.extend_from_slice(&buf[..n]);
+        }
+    });
+
+    let sent = tokio_uring::net::serve_file_range(&tx, &file, range.clone())
+        .await
+        .unwrap();
+    drop(tx);
+    let received = reader.await.unwrap();
+    assert_eq!(sent, received.len() as u64);
+
+    let end = std::cmp::min(range.end as usize, data.len());
+    let expected = data[range.start as usize..end].to_vec();
+    (received, expected)
+}
+
+#[test]
+fn serve_file_range() {
+    tokio_uring::start(async {
+        let (received, expected) = serve_range(1000..2_500_000).await;
+        assert_eq!(received, expected);
+
+        // Past the end of the file
+        let (received, expected) = serve_range(3 * 1024 * 1024 - 10..u64::MAX / 2).await;
+        assert_eq!(received.len(), 10);
+        assert_eq!(received, expected);
+
+        let (received, _) = serve_range(5..5).await;
+        assert!(received.is_empty());
+    });
+}
+
+#[test]
+fn serve_file_range_without_splice() {
+    tokio_uring::builder()
+        .seccomp_compatible(true)
+        .start(async {
+            let (received, expected) = serve_range(1..1_000_000).await;
+            assert_eq!(received, expected);
+        })
+        .unwrap();
+}
+
+#[test]
+fn serve_with_shutdown_drains_connections() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let listener = TcpListener::bind(addr).unwrap();
+        let (stop, stopped) = tokio_uring::sync::oneshot::channel::<()>();
+        let handled = Rc::new(Cell::new(0));
+
+        let server = {
+            let handled = handled.clone();
+            tokio_uring::spawn(async move {
+                listener
+                    .serve_with_shutdown(
+                        10,
+                        move |stream, _| {
+                            let handled = handled.clone();
+                            async move {
+                                // Echo one message, then complete
+                                let (res, buf) = stream.read(vec![0; 16]).await;
+                                let n = res.unwrap();
+                                let (res, _) = stream.write(buf[..n].to_vec()).await;
+                                res.unwrap();
+                                handled.set(handled.get() + 1);
+                            }
+                        },
+                        async move {
+                            let _ = stopped.await;
+                        },
+                    )
+                    .await
+            })
+        };
+
+        let first = TcpStream::connect(addr).await.unwrap();
+        let second = TcpStream::connect(addr).await.unwrap();
+
+        // Let the server accept both connections
+        tokio_uring::time::sleep(Duration::from_millis(50)).await;
+        stop.send(()).unwrap();
+        tokio_uring::time::sleep(Duration::from_millis(50)).await;
+
+        // Draining: the handlers wait for their message
+        assert!(!server.is_finished());
+        assert_eq!(handled.get(), 0);
+
+        for stream in [first, second] {
+            let (res, _) = stream.write(&b"ping"[..]).await;
+            res.unwrap();
+            let (res, buf) = stream.read(vec![0; 16]).await;
+            assert_eq!(&buf[..res.unwrap()], b"ping");
+        }
+
+        server.await.unwrap().unwrap();
+        assert_eq!(handled.get(), 2);
+    });
+}
+
+#[cfg(feature = "tower")]
+#[test]
+fn serve_service_calls_service() {
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let listener = TcpListener::bind(addr).unwrap();
+        let (stop, stopped) = tokio_uring::sync::oneshot::channel::<()>();
+
+        // Greets each client with its position
+        let greeted = std::rc::Rc::new(std::cell::Cell::new(0));
+        let service = tower::service_fn(move |stream: TcpStream| {
+            let greeted = greeted.clone();
+            async move {
+                greeted.set(greeted.get() + 1);
+                let (res, _) = stream
+                    .write(format!("hello {}", greeted.get()).into_bytes())
+                    .await;
+                res.map(drop)
+            }
+        });
+
+        let server = tokio_uring::spawn(async move {
+            listener
+                .serve_service(10, service, async move {
+                    let _ = stopped.await;
+                })
+                .await
+        });
+
+        for expected in ["hello 1", "hello 2"] {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let (res, buf) = stream.read(vec![0; 16]).await;
+            assert_eq!(&buf[..res.unwrap()], expected.as_bytes());
+        }
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    });
+}
@@ -0,0 +1,149 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use tokio_uring::time;
+
+#[test]
+fn sleep() {
+    tokio_uring::start(async {
+        let start = Instant::now();
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    });
+}
+
+#[test]
+fn timeout_completes() {
+    tokio_uring::start(async {
+        let res = time::timeout(Duration::from_secs(5), async { 1 }).await;
+        assert_eq!(res, Ok(1));
+    });
+}
+
+#[test]
+fn timeout_elapses() {
+    tokio_uring::start(async {
+        let start = Instant::now();
+        let res = time::timeout(Duration::from_millis(50), std::future::pending::<()>()).await;
+        assert!(res.is_err());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    });
+}
+
+#[test]
+fn drop_sleep_cancels_timer() {
+    let start = Instant::now();
+
+    tokio_uring::start(async {
+        drop(time::sleep(Duration::from_secs(60)));
+    });
+
+    // The runtime does not wait for the timer on shutdown
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn interval_ticks_every_period() {
+    tokio_uring::start(async {
+        let start = Instant::now();
+        let mut interval = time::interval(Duration::from_millis(20));
+        assert_eq!(interval.period(), Duration::from_millis(20));
+
+        for i in 1..=3 {
+            interval.tick().await;
+            assert!(start.elapsed() >= Duration::from_millis(20 * i));
+        }
+    });
+}
+
+#[test]
+fn interval_delivers_missed_ticks() {
+    tokio_uring::start(async {
+        let mut interval = time::interval(Duration::from_millis(10));
+
+        // Busy while the timer expires several times
+        std::thread::sleep(Duration::from_millis(55));
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            interval.tick().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(10));
+    });
+}
+
+#[test]
+fn drop_interval_cancels_timer() {
+    let start = Instant::now();
+
+    tokio_uring::start(async {
+        let mut interval = time::interval(Duration::from_millis(10));
+        interval.tick().await;
+        drop(interval);
+
+        drop(time::interval(Duration::from_secs(3600)));
+    });
+
+    // The runtime does not wait for the timers on shutdown
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn pending_timers_dropped_at_shutdown() {
+    thread_local! {
+        static PANICKED: Cell<bool> = const { Cell::new(false) };
+    }
+
+    // Tokio catches the panics of the tasks dropped with the runtime
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        PANICKED.with(|panicked| panicked.set(true));
+        hook(info);
+    }));
+
+    let start = Instant::now();
+
+    tokio_uring::start(async {
+        tokio_uring::spawn(time::sleep(Duration::from_secs(3600)));
+        tokio_uring::spawn(async {
+            let mut interval = time::interval(Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+            }
+        });
+        tokio_uring::spawn(time::timeout(
+            Duration::from_secs(3600),
+            std::future::pending::<()>(),
+        ));
+
+        // Let the tasks start their timers
+        tokio_uring::task::yield_now().await;
+    });
+
+    // The tasks are dropped with the runtime, canceling their timers
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(!PANICKED.with(Cell::get));
+}
+
+#[test]
+fn timers_wait_for_room() {
+    tokio_uring::builder()
+        .max_ops(1, tokio_uring::Backpressure::Fail)
+        .start(async {
+            let start = Instant::now();
+            let first = tokio_uring::spawn(time::sleep(Duration::from_millis(100)));
+
+            // Submitted once the first timer expired, and already late
+            time::sleep(Duration::from_millis(10)).await;
+            assert!(start.elapsed() >= Duration::from_millis(100));
+            first.await.unwrap();
+
+            // Re-armed after each tick instead
+            let sleep = time::sleep(Duration::from_millis(50));
+            let mut interval = time::interval(Duration::from_millis(10));
+            sleep.await;
+            interval.tick().await;
+            interval.tick().await;
+        })
+        .unwrap();
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use tokio_uring::time;
+
+#[test]
+fn advance_expires_timers_in_order() {
+    tokio_uring::start(async {
+        time::pause();
+
+        let order = Rc::new(RefCell::new(vec![]));
+
+        for secs in [30, 10, 20] {
+            let order = order.clone();
+            let sleep = time::sleep(Duration::from_secs(secs));
+
+            tokio_uring::spawn(async move {
+                sleep.await;
+                order.borrow_mut().push(secs);
+            });
+        }
+
+        time::advance(Duration::from_secs(15)).await;
+        assert_eq!(*order.borrow(), [10]);
+
+        time::advance(Duration::from_secs(15)).await;
+        assert_eq!(*order.borrow(), [10, 20, 30]);
+    });
+}
+
+#[test]
+fn timeout_elapses_without_waiting() {
+    let start = Instant::now();
+
+    tokio_uring::start(async {
+        time::pause();
+
+        let task = tokio_uring::spawn(time::timeout(
+            Duration::from_secs(3600),
+            std::future::pending::<()>(),
+        ));
+
+        time::advance(Duration::from_secs(3599)).await;
+        assert!(!task.is_finished());
+
+        time::advance(Duration::from_secs(1)).await;
+        assert!(task.await.unwrap().is_err());
+    });
+
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn zero_sleep_completes_immediately() {
+    tokio_uring::start(async {
+        time::pause();
+        time::sleep(Duration::ZERO).await;
+    });
+}
+
+#[test]
+fn pending_timers_on_shutdown() {
+    let start = Instant::now();
+
+    tokio_uring::start(async {
+        time::pause();
+
+        // Cancelled
+        drop(time::sleep(Duration::from_secs(3600)));
+
+        // Still pending when the runtime shuts down
+        tokio_uring::spawn(time::sleep(Duration::from_secs(3600)));
+        tokio_uring::task::yield_now().await;
+    });
+
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[test]
+fn interval_ticks_on_advance() {
+    tokio_uring::start(async {
+        time::pause();
+
+        let mut interval = time::interval(Duration::from_secs(60));
+        let tick = tokio_uring::spawn(async move {
+            interval.tick().await;
+            interval
+        });
+
+        time::advance(Duration::from_secs(59)).await;
+        assert!(!tick.is_finished());
+
+        time::advance(Duration::from_secs(1)).await;
+        let mut interval = tick.await.unwrap();
+
+        // The timer was re-armed after the tick
+        let tick = tokio_uring::spawn(async move { interval.tick().await });
+        time::advance(Duration::from_secs(60)).await;
+        tick.await.unwrap();
+    });
+}
@@ -0,0 +1,115 @@
+use std::{
+    io,
+    net::{Ipv4Addr, UdpSocket},
+};
+
+use tokio_uring::device::{Tun, TunOptions};
+
+/// Creates a tun device on 10.213.<subnet>.1/24, or `None` without
+/// `CAP_NET_ADMIN`.
+fn open(subnet: u8) -> Option<Tun> {
+    let res = TunOptions::new()
+        .address(Ipv4Addr::new(10, 213, subnet, 1))
+        .netmask(Ipv4Addr::new(255, 255, 255, 0))
+        .mtu(1400)
+        .up(true)
+        .open("uring%d");
+
+    match res {
+        Ok(tun) => Some(tun),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => None,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+fn checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[test]
+fn invalid_name() {
+    let err = TunOptions::new()
+        .open("a-very-long-interface-name")
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn read_routed_packet() {
+    let tun = match open(1) {
+        Some(tun) => tun,
+        None => return,
+    };
+    assert!(tun.name().starts_with("uring"));
+
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("10.213.1.1:0").unwrap();
+        socket.send_to(b"hello", "10.213.1.2:9999").unwrap();
+
+        // Skip any packets sent by the kernel when the interface came up
+        loop {
+            let (res, buf) = tun.read(vec![0; 1500]).await;
+            let n = res.unwrap();
+            let packet = &buf[..n];
+
+            // IPv4 UDP to 10.213.1.2:9999
+            if packet[0] >> 4 == 4 && packet[9] == 17 && packet[16..20] == [10, 213, 1, 2] {
+                assert_eq!(&packet[22..24], &9999u16.to_be_bytes());
+                assert_eq!(&packet[28..], b"hello");
+                break;
+            }
+        }
+
+        tun.close().await.unwrap();
+    });
+}
+
+#[test]
+fn write_delivers_packet() {
+    let tun = match open(2) {
+        Some(tun) => tun,
+        None => return,
+    };
+
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind("10.213.2.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+        socket
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+
+        // UDP from 10.213.2.2:4000, without a checksum
+        let payload = b"world";
+        let mut udp = Vec::new();
+        udp.extend_from_slice(&4000u16.to_be_bytes());
+        udp.extend_from_slice(&port.to_be_bytes());
+        udp.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+        udp.extend_from_slice(&[0, 0]);
+        udp.extend_from_slice(payload);
+
+        let mut packet = vec![0x45, 0];
+        packet.extend_from_slice(&(20 + udp.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0x40, 0, 64, 17, 0, 0]);
+        packet.extend_from_slice(&[10, 213, 2, 2, 10, 213, 2, 1]);
+        let sum = checksum(&packet);
+        packet[10..12].copy_from_slice(&sum.to_be_bytes());
+        packet.extend_from_slice(&udp);
+
+        let len = packet.len();
+        let (res, _) = tun.write(packet).await;
+        assert_eq!(res.unwrap(), len);
+
+        let mut buf = [0; 32];
+        let (n, from) = socket.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], payload);
+        assert_eq!(from, "10.213.2.2:4000".parse().unwrap());
+    });
+}
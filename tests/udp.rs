@@ -0,0 +1,214 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio_uring::net::{RecvFlags, SendFlags, UdpSocket};
+
+/// Finds a local port that is currently unused.
+fn free_addr() -> SocketAddr {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    drop(socket);
+    std::thread::sleep(Duration::from_millis(1));
+    addr
+}
+
+async fn connected_pair() -> (UdpSocket, UdpSocket) {
+    let (first_addr, second_addr) = (free_addr(), free_addr());
+
+    let first = UdpSocket::bind(first_addr).await.unwrap();
+    let second = UdpSocket::bind(second_addr).await.unwrap();
+    first.connect(second_addr).await.unwrap();
+    second.connect(first_addr).await.unwrap();
+
+    (first, second)
+}
+
+#[test]
+fn recv_connected() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let (res, _) = tx.write(b"hello".as_slice()).await;
+        res.unwrap();
+
+        let (res, buf) = rx.recv(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
+
+#[test]
+fn peek_leaves_datagram_queued() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let (res, _) = tx.write(b"hello".as_slice()).await;
+        res.unwrap();
+
+        let (res, buf) = rx.peek(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+
+        let (res, buf) = rx.recv(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
+
+#[test]
+fn trunc_reports_datagram_len() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let (res, _) = tx.write(vec![7; 100]).await;
+        res.unwrap();
+
+        let flags = RecvFlags::PEEK | RecvFlags::TRUNC;
+        let (res, buf) = rx.recv_with_flags(Vec::with_capacity(10), flags).await;
+        assert_eq!(res.unwrap(), 100);
+        // Only the part fitting in the buffer is initialized
+        assert_eq!(buf, [7; 10]);
+
+        let (res, buf) = rx
+            .recv_with_flags(Vec::with_capacity(10), RecvFlags::TRUNC)
+            .await;
+        assert_eq!(res.unwrap(), 100);
+        assert_eq!(buf.len(), 10);
+    });
+}
+
+#[test]
+fn send_more_coalesces() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let (res, _) = tx
+            .send_with_flags(b"hello ".as_slice(), SendFlags::MORE)
+            .await;
+        res.unwrap();
+        let (res, _) = tx
+            .send_with_flags(b"world".as_slice(), SendFlags::empty())
+            .await;
+        res.unwrap();
+
+        let (res, buf) = rx.recv(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello world");
+    });
+}
+
+#[test]
+fn recv_from_with_flags() {
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        let (res, _) = tx.write(vec![1; 100]).await;
+        res.unwrap();
+
+        let (res, buf) = rx
+            .recv_from_with_flags(Vec::with_capacity(10), RecvFlags::TRUNC)
+            .await;
+        assert_eq!(res.unwrap().0, 100);
+        assert_eq!(buf.len(), 10);
+    });
+}
+
+#[test]
+fn datagram_stream_and_sink() {
+    use futures_core::Stream;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    tokio_uring::start(async {
+        let (first_addr, second_addr) = (free_addr(), free_addr());
+        let first = UdpSocket::bind(first_addr).await.unwrap();
+        let second = UdpSocket::bind(second_addr).await.unwrap();
+
+        let mut sink = first.sink();
+        for msg in [&b"one"[..], b"two", b"three"] {
+            poll_fn(|cx| sink.poll_ready(cx)).await.unwrap();
+            sink.start_send((msg.to_vec(), second_addr)).unwrap();
+        }
+        poll_fn(|cx| sink.poll_close(cx)).await.unwrap();
+
+        let mut datagrams = second.datagrams(4);
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            let next = poll_fn(|cx| Pin::new(&mut datagrams).poll_next(cx)).await;
+            let (buf, addr) = next.unwrap().unwrap();
+            assert_eq!(addr, first_addr);
+            received.push(buf);
+        }
+
+        // The excess of a datagram longer than the buffers is discarded
+        assert_eq!(received, [&b"one"[..], b"two", b"thre"]);
+    });
+}
+
+#[test]
+fn datagram_sink_not_ready() {
+    tokio_uring::start(async {
+        let socket = UdpSocket::bind(free_addr()).await.unwrap();
+        let addr = free_addr();
+
+        let mut sink = socket.sink();
+        sink.start_send((vec![1], addr)).unwrap();
+        assert!(sink.start_send((vec![2], addr)).is_err());
+    });
+}
+
+#[test]
+fn dropped_datagram_stream_leaves_datagrams() {
+    use futures_core::Stream;
+    use std::future::poll_fn;
+    use std::pin::Pin;
+    use std::task::Poll;
+
+    tokio_uring::start(async {
+        let (tx, rx) = connected_pair().await;
+
+        // Arm a receive, and drop the stream
+        let mut datagrams = rx.datagrams(16);
+        poll_fn(|cx| {
+            assert!(Pin::new(&mut datagrams).poll_next(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        drop(datagrams);
+        tokio_uring::time::sleep(Duration::from_millis(10)).await;
+
+        let (res, _) = tx.write(b"hello".to_vec()).await;
+        res.unwrap();
+
+        let (res, buf) = rx.read(vec![0; 16]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
+
+#[cfg(feature = "futures-sink")]
+#[test]
+fn datagram_stream_forwarded_to_sink() {
+    use futures_util::StreamExt;
+
+    tokio_uring::start(async {
+        let (first_addr, second_addr) = (free_addr(), free_addr());
+        let first = UdpSocket::bind(first_addr).await.unwrap();
+        let second = UdpSocket::bind(second_addr).await.unwrap();
+
+        for msg in [&b"one"[..], b"two"] {
+            let (res, _) = first.send_to(msg.to_vec(), second_addr).await;
+            res.unwrap();
+        }
+
+        // Echo the datagrams back to their sender
+        second
+            .datagrams(16)
+            .take(2)
+            .forward(second.sink())
+            .await
+            .unwrap();
+
+        for msg in [&b"one"[..], b"two"] {
+            let (res, buf) = first.recv_from(vec![0; 16]).await;
+            let (n, addr) = res.unwrap();
+            assert_eq!(addr, second_addr);
+            assert_eq!(&buf[..n], msg);
+        }
+    });
+}
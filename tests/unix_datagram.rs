@@ -0,0 +1,110 @@
+use tokio_uring::net::{RecvFlags, UnixDatagram, UnixSocketAddr};
+
+#[test]
+fn send_to_recv_from_pathname() {
+    let dir = tempfile::tempdir().unwrap();
+    let server_path = dir.path().join("server.sock");
+    let client_path = dir.path().join("client.sock");
+
+    tokio_uring::start(async {
+        let server = UnixDatagram::bind(&server_path).unwrap();
+        let client = UnixDatagram::bind(&client_path).unwrap();
+
+        let (res, _) = client.send_to(b"hello".as_slice(), &server_path).await;
+        assert_eq!(res.unwrap(), 5);
+
+        let (res, buf) = server.recv_from(vec![0; 32]).await;
+        let (n, addr) = res.unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(addr.as_pathname(), Some(client_path.as_path()));
+        assert_eq!(addr, UnixSocketAddr::from_pathname(&client_path).unwrap());
+
+        // Reply to the origin
+        let (res, _) = server.send_to_addr(b"world".as_slice(), &addr).await;
+        res.unwrap();
+
+        let (res, buf) = client.recv(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"world");
+    });
+}
+
+#[test]
+fn abstract_namespace() {
+    tokio_uring::start(async {
+        let name = format!("tokio-uring-test-{}", std::process::id());
+        let addr = UnixSocketAddr::from_abstract_name(&name).unwrap();
+        assert_eq!(addr.as_abstract_name(), Some(name.as_bytes()));
+        assert_eq!(addr.as_pathname(), None);
+
+        let server = UnixDatagram::bind_addr(&addr).unwrap();
+        let client_addr = UnixSocketAddr::from_abstract_name(format!("{}-client", name)).unwrap();
+        let client = UnixDatagram::bind_addr(&client_addr).unwrap();
+
+        let (res, _) = client.send_to_addr(b"ping".as_slice(), &addr).await;
+        res.unwrap();
+
+        let (res, buf) = server.recv_from(vec![0; 32]).await;
+        let (n, peer) = res.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(peer, client_addr);
+    });
+}
+
+#[test]
+fn unbound_origin_is_unnamed() {
+    let dir = tempfile::tempdir().unwrap();
+    let server_path = dir.path().join("server.sock");
+
+    tokio_uring::start(async {
+        let server = UnixDatagram::bind(&server_path).unwrap();
+        let client = UnixDatagram::unbound().unwrap();
+
+        let (res, _) = client.send_to(b"hello".as_slice(), &server_path).await;
+        res.unwrap();
+
+        let (res, _) = server.recv_from(vec![0; 32]).await;
+        let (_, addr) = res.unwrap();
+        assert!(addr.is_unnamed());
+        assert_eq!(addr.as_pathname(), None);
+        assert_eq!(addr.as_abstract_name(), None);
+    });
+}
+
+#[test]
+fn connected_send_recv() {
+    let dir = tempfile::tempdir().unwrap();
+    let server_path = dir.path().join("server.sock");
+
+    tokio_uring::start(async {
+        let server = UnixDatagram::bind(&server_path).unwrap();
+        let client = UnixDatagram::unbound().unwrap();
+        client.connect(&server_path).await.unwrap();
+
+        let (res, _) = client.send(b"hello".as_slice()).await;
+        res.unwrap();
+
+        let (res, buf) = server.recv(vec![0; 32]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hello");
+    });
+}
+
+#[test]
+fn pair_trunc_reports_datagram_len() {
+    tokio_uring::start(async {
+        let (first, second) = UnixDatagram::pair().unwrap();
+
+        let (res, _) = first.send(vec![7; 100]).await;
+        res.unwrap();
+
+        let (res, buf) = second.recv_with_flags(vec![0; 10], RecvFlags::TRUNC).await;
+        assert_eq!(res.unwrap(), 100);
+        assert_eq!(buf, vec![7; 10]);
+    });
+}
+
+#[test]
+fn pathname_too_long() {
+    let path = "a".repeat(200);
+    assert!(UnixSocketAddr::from_pathname(&path).is_err());
+    assert!(UnixDatagram::bind(&path).is_err());
+}
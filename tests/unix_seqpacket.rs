@@ -0,0 +1,64 @@
+use tokio_uring::net::{RecvFlags, UnixSeqpacket, UnixSeqpacketListener, UnixSocketAddr};
+
+#[test]
+fn message_boundaries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("server.sock");
+
+    tokio_uring::start(async {
+        let listener = UnixSeqpacketListener::bind(&path).unwrap();
+
+        let (client, (server, cred)) =
+            tokio::try_join!(UnixSeqpacket::connect(&path), listener.accept_with_cred()).unwrap();
+        assert_eq!(cred.uid(), unsafe { libc::geteuid() });
+
+        for msg in [&b"one"[..], b"two", b"three"] {
+            let (res, _) = client.send(msg).await;
+            assert_eq!(res.unwrap(), msg.len());
+        }
+
+        for msg in [&b"one"[..], b"two", b"three"] {
+            let (res, buf) = server.recv(vec![0; 64]).await;
+            assert_eq!(&buf[..res.unwrap()], msg);
+        }
+
+        // The peer closing is seen as an empty message
+        drop(client);
+        let (res, _) = server.recv(vec![0; 64]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn truncated_message() {
+    tokio_uring::start(async {
+        let (a, b) = UnixSeqpacket::pair().unwrap();
+
+        a.send(b"hello world".as_slice()).await.0.unwrap();
+        a.send(b"next".as_slice()).await.0.unwrap();
+
+        // The excess of the message is discarded, the next message is intact
+        let (res, buf) = b.recv_with_flags(vec![0; 5], RecvFlags::TRUNC).await;
+        assert_eq!(res.unwrap(), 11);
+        assert_eq!(buf, b"hello");
+
+        let (res, buf) = b.recv(vec![0; 64]).await;
+        assert_eq!(&buf[..res.unwrap()], b"next");
+    });
+}
+
+#[test]
+fn abstract_address() {
+    tokio_uring::start(async {
+        let name = format!("tokio-uring-seqpacket-{}", std::process::id());
+        let addr = UnixSocketAddr::from_abstract_name(name).unwrap();
+        let listener = UnixSeqpacketListener::bind_addr(&addr).unwrap();
+
+        let (client, server) =
+            tokio::try_join!(UnixSeqpacket::connect_addr(&addr), listener.accept()).unwrap();
+
+        server.send(b"hi".as_slice()).await.0.unwrap();
+        let (res, buf) = client.recv(vec![0; 8]).await;
+        assert_eq!(&buf[..res.unwrap()], b"hi");
+    });
+}
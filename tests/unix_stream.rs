@@ -0,0 +1,66 @@
+use tokio_uring::net::{UnixListener, UnixSocketAddr, UnixStream};
+
+#[test]
+fn accept_with_cred() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("server.sock");
+
+    tokio_uring::start(async {
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let (client, (server, cred)) =
+            tokio::try_join!(UnixStream::connect(&path), listener.accept_with_cred()).unwrap();
+
+        // Both ends belong to this process
+        assert_eq!(cred.pid(), Some(std::process::id() as libc::pid_t));
+        assert_eq!(cred.uid(), unsafe { libc::geteuid() });
+        assert_eq!(cred.gid(), unsafe { libc::getegid() });
+        assert_eq!(client.peer_cred().unwrap(), cred);
+
+        let (res, _) = client.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = server.read(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+    });
+}
+
+#[test]
+fn abstract_and_autobind() {
+    tokio_uring::start(async {
+        let name = format!("tokio-uring-stream-{}", std::process::id());
+        let addr = UnixSocketAddr::from_abstract_name(&name).unwrap();
+        let listener = UnixListener::bind_addr(&addr).unwrap();
+        assert_eq!(listener.local_addr().unwrap(), addr);
+
+        let (client, (server, peer)) =
+            tokio::try_join!(UnixStream::connect_addr(&addr), listener.accept_with_addr()).unwrap();
+
+        // The client was not bound
+        assert!(peer.is_unnamed());
+        assert!(client.local_addr().unwrap().is_unnamed());
+        assert_eq!(client.peer_addr().unwrap(), addr);
+        assert_eq!(server.local_addr().unwrap(), addr);
+
+        // Binding an unnamed address picks an abstract name
+        let autobound = UnixListener::bind_addr(&UnixSocketAddr::unnamed()).unwrap();
+        let local = autobound.local_addr().unwrap();
+        assert!(!local.as_abstract_name().unwrap().is_empty());
+    });
+}
+
+#[test]
+fn pair() {
+    tokio_uring::start(async {
+        let (a, b) = UnixStream::pair().unwrap();
+        assert_eq!(a.peer_cred().unwrap(), b.peer_cred().unwrap());
+
+        let (res, _) = a.write(b"ping".as_slice()).await;
+        res.unwrap();
+        let (res, buf) = b.read(vec![0; 4]).await;
+        assert_eq!(&buf[..res.unwrap()], b"ping");
+
+        drop(a);
+        let (res, _) = b.read(vec![0; 4]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
@@ -0,0 +1,40 @@
+use std::io;
+
+use tokio_uring::net::{VsockAddr, VsockListener, VsockStream};
+
+#[test]
+fn addr_display() {
+    let addr = VsockAddr::new(VsockAddr::CID_HOST, 1234);
+    assert_eq!(addr.cid(), 2);
+    assert_eq!(addr.port(), 1234);
+    assert_eq!(addr.to_string(), "2:1234");
+}
+
+#[test]
+fn local_connect_accept() {
+    // Requires the `vsock_loopback` transport
+    let listener = match VsockListener::bind(VsockAddr::new(VsockAddr::CID_LOCAL, 41234)) {
+        Ok(listener) => listener,
+        Err(e) if is_unsupported(&e) => return,
+        Err(e) => panic!("{}", e),
+    };
+
+    tokio_uring::start(async move {
+        let addr = VsockAddr::new(VsockAddr::CID_LOCAL, 41234);
+        let (tx, (rx, peer)) =
+            tokio::try_join!(VsockStream::connect(addr), listener.accept()).unwrap();
+        assert_eq!(peer.cid(), VsockAddr::CID_LOCAL);
+
+        tx.write(b"test".as_slice()).await.0.unwrap();
+        let (res, buf) = rx.read(vec![0; 4]).await;
+        res.unwrap();
+        assert_eq!(buf, b"test");
+    });
+}
+
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EAFNOSUPPORT) | Some(libc::EADDRNOTAVAIL) | Some(libc::ENODEV)
+    )
+}
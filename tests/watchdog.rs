@@ -0,0 +1,105 @@
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tempfile::NamedTempFile;
+
+use tokio_uring::fault::{self, Fault, Target};
+use tokio_uring::fs::File;
+use tokio_uring::{OpKind, SlowOp};
+
+/// `IORING_OP_READ`
+const IORING_OP_READ: u8 = 22;
+
+fn tempfile() -> NamedTempFile {
+    let mut tempfile = NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+    tempfile
+}
+
+fn recording_builder(cancel: bool) -> (tokio_uring::Builder, Arc<Mutex<Vec<SlowOp>>>) {
+    let reported = Arc::new(Mutex::new(Vec::new()));
+
+    let mut builder = tokio_uring::builder();
+    builder
+        .slow_op_threshold(Duration::from_millis(50))
+        .cancel_slow_ops(cancel)
+        .on_slow_op({
+            let reported = reported.clone();
+            move |op| reported.lock().unwrap().push(op.clone())
+        });
+
+    (builder, reported)
+}
+
+#[test]
+fn reports_slow_file_ops() {
+    let tempfile = tempfile();
+    let (builder, reported) = recording_builder(false);
+
+    builder
+        .start(async {
+            let file = File::open(tempfile.path()).await.unwrap();
+
+            // Stands in for a hung device
+            fault::inject(Fault::delay(Target::Read, Duration::from_millis(300)));
+
+            let (res, buf) = file.read_at(vec![0; 32], 0).await;
+            assert_eq!(&buf[..res.unwrap()], b"hello world");
+
+            let reported = reported.lock().unwrap();
+            assert_eq!(reported.len(), 1);
+            assert_eq!(reported[0].opcode(), IORING_OP_READ);
+            assert_eq!(reported[0].kind(), OpKind::Read);
+            assert!(reported[0].to_string().starts_with("read operation on fd"));
+            assert_eq!(reported[0].fd(), file.as_raw_fd());
+            assert!(reported[0].age() >= Duration::from_millis(50));
+            assert!(!reported[0].cancelled());
+        })
+        .unwrap();
+}
+
+#[test]
+fn cancels_slow_file_ops() {
+    let tempfile = tempfile();
+    let (builder, reported) = recording_builder(true);
+
+    builder
+        .start(async {
+            let file = File::open(tempfile.path()).await.unwrap();
+
+            fault::inject(Fault::delay(Target::Read, Duration::from_millis(300)));
+
+            // The delayed read is linked behind a timeout, which the kernel
+            // cannot cancel it from: it completes once the delay elapsed.
+            let (res, _) = file.read_at(vec![0; 32], 0).await;
+            match res {
+                Ok(_) => {}
+                Err(e) => assert_eq!(e.raw_os_error(), Some(libc::ECANCELED)),
+            }
+
+            let reported = reported.lock().unwrap();
+            assert_eq!(reported.len(), 1);
+            assert!(reported[0].cancelled());
+        })
+        .unwrap();
+}
+
+#[test]
+fn ignores_ops_waiting_for_events() {
+    let (builder, reported) = recording_builder(true);
+
+    builder
+        .start(async {
+            let (rx, _tx) = tokio_uring::pipe::pipe().unwrap();
+
+            // An empty pipe, and a timer, are not stuck
+            let read = rx.read(vec![0; 32]);
+            let res = tokio_uring::time::timeout(Duration::from_millis(200), read).await;
+            assert!(res.is_err());
+
+            assert!(reported.lock().unwrap().is_empty());
+        })
+        .unwrap();
+}
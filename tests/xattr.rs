@@ -0,0 +1,66 @@
+use std::ffi::OsString;
+use std::io;
+
+use tempfile::NamedTempFile;
+
+use tokio_uring::fs::{get_xattr, list_xattr, set_xattr};
+
+/// Filesystems such as tmpfs may not support user attributes.
+fn unsupported(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(libc::EOPNOTSUPP)
+}
+
+#[test]
+fn set_get_list() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+
+        match set_xattr(tempfile.path(), "user.greeting", "hello world").await {
+            Err(e) if unsupported(&e) => return,
+            res => res.unwrap(),
+        }
+
+        let value = get_xattr(tempfile.path(), "user.greeting").await.unwrap();
+        assert_eq!(value.as_deref(), Some(&b"hello world"[..]));
+
+        let names = list_xattr(tempfile.path()).await.unwrap();
+        assert!(names.contains(&OsString::from("user.greeting")));
+    });
+}
+
+#[test]
+fn get_missing() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+
+        match get_xattr(tempfile.path(), "user.missing").await {
+            Err(e) if unsupported(&e) => {}
+            res => assert_eq!(res.unwrap(), None),
+        }
+    });
+}
+
+#[test]
+fn set_empty_value() {
+    tokio_uring::start(async {
+        let tempfile = NamedTempFile::new().unwrap();
+
+        match set_xattr(tempfile.path(), "user.empty", b"").await {
+            Err(e) if unsupported(&e) => return,
+            res => res.unwrap(),
+        }
+
+        let value = get_xattr(tempfile.path(), "user.empty").await.unwrap();
+        assert_eq!(value, Some(vec![]));
+    });
+}
+
+#[test]
+fn missing_file() {
+    tokio_uring::start(async {
+        let err = get_xattr("/does/not/exist", "user.greeting")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    });
+}